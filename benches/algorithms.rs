@@ -0,0 +1,142 @@
+#![allow(missing_docs, clippy::pedantic)]
+
+//! Compare the fuzzy algorithms on generated corpora shaped like real inputs: file paths, log
+//! lines and non-ASCII text.
+//!
+//! Every algorithm runs through `skim::engine::rank_lines`, i.e. the engine that `--algo` picks,
+//! with the match positions and the sort. `sk --bench-match FILE PATTERN` does the same on any
+//! file.
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use rand::rngs::StdRng;
+use rand::{RngExt as _, SeedableRng as _};
+
+use skim::engine::rank_lines;
+use skim::prelude::*;
+
+const LINES: usize = 50_000;
+
+const WORDS: &[&str] = &[
+    "src", "lib", "bin", "test", "config", "util", "core", "main", "model", "view", "server", "client", "api",
+    "handler", "engine", "parser", "render", "cache", "index", "build",
+];
+const EXTENSIONS: &[&str] = &["rs", "toml", "md", "json", "ts", "py", "c", "h"];
+const LEVELS: &[&str] = &["TRACE", "DEBUG", "INFO", "WARN", "ERROR"];
+const MESSAGES: &[&str] = &[
+    "connection refused",
+    "request completed",
+    "retrying after timeout",
+    "cache miss",
+    "user logged in",
+    "failed to parse header",
+    "worker started",
+    "queue is full",
+];
+const UNICODE_WORDS: &[&str] = &[
+    "café",
+    "naïve",
+    "résumé",
+    "straße",
+    "Ελληνικά",
+    "русский",
+    "日本語",
+    "中文",
+    "한국어",
+    "العربية",
+    "हिन्दी",
+    "emoji🚀",
+    "Ærø",
+    "İstanbul",
+    "Ňoño",
+    "ﬁle",
+];
+
+fn rng() -> StdRng {
+    StdRng::seed_from_u64(42)
+}
+
+fn pick<'a>(rng: &mut StdRng, words: &[&'a str]) -> &'a str {
+    words[rng.random_range(0..words.len())]
+}
+
+/// `src/engine/parser_12.rs`-like paths, 2 to 8 components deep
+fn paths() -> Vec<String> {
+    let mut rng = rng();
+    (0..LINES)
+        .map(|i| {
+            let depth = rng.random_range(2..=8usize);
+            let dirs: Vec<&str> = (1..depth).map(|_| pick(&mut rng, WORDS)).collect();
+            let ext = pick(&mut rng, EXTENSIONS);
+            format!("{}/{}_{i}.{ext}", dirs.join("/"), pick(&mut rng, WORDS))
+        })
+        .collect()
+}
+
+/// Timestamped log lines with a level, a module path and a message
+fn logs() -> Vec<String> {
+    let mut rng = rng();
+    (0..LINES)
+        .map(|i| {
+            format!(
+                "2024-05-{:02}T{:02}:{:02}:{:02}.{:03}Z {:<5} {}::{}: {} id={i}",
+                rng.random_range(1..=28u32),
+                rng.random_range(0..24u32),
+                rng.random_range(0..60u32),
+                rng.random_range(0..60u32),
+                rng.random_range(0..1000u32),
+                pick(&mut rng, LEVELS),
+                pick(&mut rng, WORDS),
+                pick(&mut rng, WORDS),
+                pick(&mut rng, MESSAGES),
+            )
+        })
+        .collect()
+}
+
+/// Sentences mixing scripts, accents and wide characters
+fn unicode() -> Vec<String> {
+    let mut rng = rng();
+    (0..LINES)
+        .map(|_| {
+            let len = rng.random_range(3..=10usize);
+            let words: Vec<&str> = (0..len).map(|_| pick(&mut rng, UNICODE_WORDS)).collect();
+            words.join(" ")
+        })
+        .collect()
+}
+
+fn algorithms() -> Vec<FuzzyAlgorithm> {
+    vec![
+        FuzzyAlgorithm::Arinae,
+        FuzzyAlgorithm::SkimV2,
+        FuzzyAlgorithm::Clangd,
+        FuzzyAlgorithm::Fzy,
+        #[cfg(feature = "frizbee")]
+        FuzzyAlgorithm::Frizbee,
+    ]
+}
+
+fn bench_corpus(c: &mut Criterion, name: &str, lines: &[String], patterns: &[&str]) {
+    let mut group = c.benchmark_group(format!("algorithms_{name}"));
+    group.sample_size(10);
+    for algorithm in algorithms() {
+        let opts = SkimOptionsBuilder::default().algorithm(algorithm).build().unwrap();
+        for pattern in patterns {
+            group.bench_with_input(
+                BenchmarkId::new(format!("{algorithm:?}"), pattern),
+                pattern,
+                |b, pattern| b.iter(|| rank_lines(lines, pattern, &opts).len()),
+            );
+        }
+    }
+    group.finish();
+}
+
+fn bench_algorithms(c: &mut Criterion) {
+    bench_corpus(c, "paths", &paths(), &["main", "srcengrs", "test_1.rs"]);
+    bench_corpus(c, "logs", &logs(), &["error", "warnconnref", "05-12"]);
+    bench_corpus(c, "unicode", &unicode(), &["cafe", "日本", "strasse"]);
+}
+
+criterion_group!(benches, bench_algorithms);
+criterion_main!(benches);
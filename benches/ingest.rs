@@ -0,0 +1,53 @@
+#![allow(missing_docs, clippy::pedantic)]
+
+//! Ingest throughput of the reader pipeline on 10M lines: plain lines share the buffer of their
+//! chunk, while a field option makes every line allocate its own text and ranges.
+
+use std::fs;
+use std::io::Cursor;
+
+use criterion::{BatchSize, Criterion, Throughput, criterion_group, criterion_main};
+
+use skim::prelude::*;
+
+fn load(file: &str) -> Vec<u8> {
+    fs::read(format!("benches/fixtures/{file}")).expect("{file} missing")
+}
+
+fn ingest(reader: &SkimItemReader, data: Vec<u8>) -> usize {
+    let rx = reader.of_bufread(Cursor::new(data));
+    let mut count = 0;
+    while let Ok(batch) = rx.recv() {
+        count += batch.len();
+    }
+    count
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let data = load("10M.txt");
+    let mut group = c.benchmark_group("ingest_10M");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    let options = [
+        ("plain", SkimOptionsBuilder::default().build()),
+        ("ansi", SkimOptionsBuilder::default().ansi(true).build()),
+        (
+            "nth",
+            SkimOptionsBuilder::default().nth(vec!["1..".to_string()]).build(),
+        ),
+    ];
+    for (name, opts) in options {
+        let opts = opts.unwrap();
+        let reader = SkimItemReader::new(SkimItemReaderOption::from_options(&opts));
+        group.bench_function(name, |b| {
+            b.iter_batched(|| data.clone(), |data| ingest(&reader, data), BatchSize::LargeInput)
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = criterion_benchmark
+);
+criterion_main!(benches);
@@ -18,15 +18,15 @@ async fn main() -> Result<()> {
 
     skim.enter().await?;
 
-    let output = skim
-        .run_until(async move {
-            for i in 1..=10 {
-                let _ = event_tx.try_send(Event::ClearItems);
-                let _ = tx_item.send(vec![Arc::new(format!("item {i}")) as Arc<dyn SkimItem>]);
-                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-            }
-        })
-        .await?;
+    // skim is moved into the future, box it rather than keeping it on the stack
+    let output = Box::pin(skim.run_until(async move {
+        for i in 1..=10 {
+            let _ = event_tx.try_send(Event::ClearItems);
+            let _ = tx_item.send(vec![Arc::new(format!("item {i}")) as Arc<dyn SkimItem>]);
+            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        }
+    }))
+    .await?;
 
     for item in &output.selected_items {
         println!("{}", item.output());
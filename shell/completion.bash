@@ -23,16 +23,28 @@ _sk() {
 
     case "${cmd}" in
         sk)
-            opts="-t -n -d -e -b -m -c -i -I -p -q -1 -0 -f -x -h -V --tac --min-query-length --no-sort --tiebreak --nth --with-nth --hide-nth --delimiter --exact --regex --algo --case --typos --no-typos --normalize --split-match --last-match --scheme --bind --multi --no-multi --no-mouse --cmd --interactive --color --highlight-line --no-hscroll --keep-right --skip-to-pattern --no-clear-if-empty --no-clear-start --no-clear --show-cmd-error --cycle --disabled --disable-pattern --layout --reverse --height --no-height --min-height --margin --prompt --cmd-prompt --selector --multi-selector --ansi --tabstop --ellipsis --info --no-info --inline-info --header --header-lines --border --border-no-collapse --no-border --wrap --multiline --scrollbar --no-scrollbar --history --history-size --cmd-history --cmd-history-size --preview --preview-window --image --query --cmd-query --read0 --print0 --print-query --print-cmd --print-score --print-header --print-current --output-format --no-strip-ansi --select-1 --exit-0 --sync --pre-select-n --pre-select-pat --pre-select-items --pre-select-file --filter --shell --shell-bindings --man --listen --remote --popup --log-level --log-file --flags --extended --literal --hscroll-off --filepath-word --jump-labels --no-bold --phony --tail --style --no-color --padding --border-label --border-label-pos --wrap-sign --no-multi-line --raw --track --gap --gap-line --freeze-left --freeze-right --scroll-off --gutter --gutter-raw --marker-multi-line --list-border --list-label --list-label-pos --no-input --info-command --separator --no-separator --ghost --input-border --input-label --input-label-pos --preview-label --preview-label-pos --header-first --header-border --header-lines-border --footer --footer-border --footer-label --footer-label-pos --with-shell --expect --help --version"
+            opts="-t -n -d -e -b -m -c -i -I -p -q -1 -0 -f -x -h -V --tac --unique --unique-by --min-query-length --suggest-on-empty --no-sort --reverse-sort --tie-order --tiebreak --nth --nth-cycle --with-nth --hide-nth --match-ranges-field --max-item-length --skip-long-items --annotation-sep --delimiter --grep-mode --exact --exact-boundary --regex --no-extended --field-names --algo --case --typos --no-typos --match-window --normalize --no-normalize --split-match --last-match --scheme --bind --multi --no-multi --no-mouse --word-separators --cmd --interactive --no-input --source --input-file --color --highlight-line --selection-count --max-display --no-hscroll --hscroll-off --scroll-off --max-fps --keep-right --skip-to-pattern --no-clear-if-empty --reload-delay --defer-ingest --reload-interval --no-clear-start --no-clear --show-cmd-error --cmd-timeout --cmd-retry --cycle --jump-labels --disabled --disable-pattern --layout --reverse --height --no-height --min-height --anchor --margin --prompt --cmd-prompt --prompt2 --selector --multi-selector --ansi --tabstop --ellipsis --truncate-side --group-by-field --tree --info --no-info --inline-info --header --header-lines --border --border-no-collapse --no-border --border-label --border-label-pos --list-label --list-label-pos --input-label --input-label-pos --header-label --header-label-pos --wrap --multiline --scrollbar --no-scrollbar --history --history-size --cmd-history --cmd-history-size --preview --preview-window --wrap-sign --preview-prefetch --preview-label --preview-label-pos --preview-pane --image --query --cmd-query --query2 --read0 --encoding --delta --print0 --print-query --print-cmd --print-score --print-positions --print-header --print-annotation --print-current --print-selection-order --print-all-keys --exit-code --output-format --grep-output --print-jump --no-strip-ansi --select-1 --exit-0 --select-1-on-reload --exit-0-on-reload --on-start --on-accept --on-abort --sync --pre-select-n --pre-select-pat --pre-select-items --pre-select-file --pre-select-cmd --filter --filter-sort --filter-or-ui --with-shell --dry-run --shell --shell-bindings --shell-init --history-widget --completion-context --man --bench-match --profile --listen --remote --popup --tmux-pane --log-level --log-file --debug-overlay --flags --extended --literal --filepath-word --no-bold --phony --tail --style --no-color --padding --no-multi-line --raw --track --gap --gap-line --freeze-left --freeze-right --gutter --gutter-raw --marker-multi-line --list-border --info-command --separator --no-separator --ghost --input-border --header-first --header-border --header-lines-border --footer --footer-border --footer-label --footer-label-pos --expect --help --version"
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 1 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
             fi
             case "${prev}" in
+                --unique-by)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
                 --min-query-length)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
+                --suggest-on-empty)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --tie-order)
+                    COMPREPLY=($(compgen -W "input reverse" -- "${cur}"))
+                    return 0
+                    ;;
                 --tiebreak)
                     COMPREPLY=($(compgen -W "score -score begin -begin end -end length -length index -index pathname -pathname" -- "${cur}"))
                     return 0
@@ -49,6 +61,10 @@ _sk() {
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
+                --nth-cycle)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
                 --with-nth)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
@@ -57,6 +73,18 @@ _sk() {
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
+                --match-ranges-field)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --max-item-length)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --annotation-sep)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
                 --delimiter)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
@@ -65,6 +93,10 @@ _sk() {
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
+                --field-names)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
                 --algo)
                     COMPREPLY=($(compgen -W "arinae clangd fzy frizbee skim_v2" -- "${cur}"))
                     return 0
@@ -77,6 +109,10 @@ _sk() {
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
+                --match-window)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
                 --split-match)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
@@ -93,6 +129,18 @@ _sk() {
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
+                --multi)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                -m)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --word-separators)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
                 --cmd)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
@@ -101,6 +149,14 @@ _sk() {
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
+                --source)
+                    COMPREPLY=($(compgen -W "ps env git-files git-branches" -- "${cur}"))
+                    return 0
+                    ;;
+                --input-file)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
                 -I)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
@@ -109,10 +165,50 @@ _sk() {
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
+                --max-display)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --hscroll-off)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --scroll-off)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --max-fps)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
                 --skip-to-pattern)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
+                --reload-delay)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --defer-ingest)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --reload-interval)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --cmd-timeout)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --cmd-retry)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --jump-labels)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
                 --disable-pattern)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
@@ -129,6 +225,10 @@ _sk() {
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
+                --anchor)
+                    COMPREPLY=($(compgen -W "cursor top bottom" -- "${cur}"))
+                    return 0
+                    ;;
                 --margin)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
@@ -145,6 +245,10 @@ _sk() {
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
+                --prompt2)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
                 --selector)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
@@ -161,6 +265,14 @@ _sk() {
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
+                --truncate-side)
+                    COMPREPLY=($(compgen -W "start middle end" -- "${cur}"))
+                    return 0
+                    ;;
+                --group-by-field)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
                 --info)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
@@ -177,6 +289,38 @@ _sk() {
                     COMPREPLY=($(compgen -W "force-off none plain rounded double thick light-double-dashed heavy-double-dashed light-triple-dashed heavy-triple-dashed light-quadruple-dashed heavy-quadruple-dashed quadrant-inside quadrant-outside" -- "${cur}"))
                     return 0
                     ;;
+                --border-label)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --border-label-pos)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --list-label)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --list-label-pos)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --input-label)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --input-label-pos)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --header-label)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --header-label-pos)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
                 --multiline)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
@@ -209,6 +353,26 @@ _sk() {
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
+                --wrap-sign)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --preview-prefetch)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --preview-label)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --preview-label-pos)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --preview-pane)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
                 --image)
                     COMPREPLY=($(compgen -W "detect halfblocks" -- "${cur}"))
                     return 0
@@ -225,167 +389,191 @@ _sk() {
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --output-format)
+                --query2)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --pre-select-n)
-                    COMPREPLY=($(compgen -f "${cur}"))
+                --encoding)
+                    COMPREPLY=($(compgen -W "strict lossy latin1" -- "${cur}"))
                     return 0
                     ;;
-                --pre-select-pat)
-                    COMPREPLY=($(compgen -f "${cur}"))
+                --print-selection-order)
+                    COMPREPLY=($(compgen -W "selection display input" -- "${cur}"))
                     return 0
                     ;;
-                --pre-select-items)
+                --exit-code)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --pre-select-file)
+                --output-format)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --filter)
+                --grep-output)
+                    COMPREPLY=($(compgen -W "vim vscode" -- "${cur}"))
+                    return 0
+                    ;;
+                --print-jump)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                -f)
+                --on-start)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --shell)
-                    COMPREPLY=($(compgen -W "bash elvish fish nushell power-shell zsh" -- "${cur}"))
+                --on-accept)
+                    COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --listen)
+                --on-abort)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --remote)
+                --pre-select-n)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --popup)
+                --pre-select-pat)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --log-level)
+                --pre-select-items)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --log-file)
+                --pre-select-file)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --flags)
-                    COMPREPLY=($(compgen -W "no-preview-pty show-score show-index single-reader single-matcher" -- "${cur}"))
+                --pre-select-cmd)
+                    COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --hscroll-off)
+                --filter)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --jump-labels)
+                -f)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --tail)
+                --filter-or-ui)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --style)
+                --with-shell)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --padding)
+                --shell)
+                    COMPREPLY=($(compgen -W "bash elvish fish nushell power-shell zsh" -- "${cur}"))
+                    return 0
+                    ;;
+                --shell-init)
+                    COMPREPLY=($(compgen -W "bash elvish fish nushell power-shell zsh" -- "${cur}"))
+                    return 0
+                    ;;
+                --history-widget)
+                    COMPREPLY=($(compgen -W "bash elvish fish nushell power-shell zsh" -- "${cur}"))
+                    return 0
+                    ;;
+                --completion-context)
+                    COMPREPLY=($(compgen -W "path dir host proc" -- "${cur}"))
+                    return 0
+                    ;;
+                --bench-match)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --border-label)
+                --profile)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --border-label-pos)
+                --listen)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --wrap-sign)
+                --remote)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --gap)
+                --popup)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --gap-line)
+                --tmux-pane)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --freeze-left)
+                --log-level)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --freeze-right)
+                --log-file)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --scroll-off)
+                --flags)
+                    COMPREPLY=($(compgen -W "no-preview-pty show-score show-index single-reader single-matcher" -- "${cur}"))
+                    return 0
+                    ;;
+                --tail)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --gutter)
+                --style)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --gutter-raw)
+                --padding)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --marker-multi-line)
+                --gap)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --list-border)
+                --gap-line)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --list-label)
+                --freeze-left)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --list-label-pos)
+                --freeze-right)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --info-command)
+                --gutter)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --separator)
+                --gutter-raw)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --ghost)
+                --marker-multi-line)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --input-border)
+                --list-border)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --input-label)
+                --info-command)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --input-label-pos)
+                --separator)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --preview-label)
+                --ghost)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --preview-label-pos)
+                --input-border)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
@@ -413,10 +601,6 @@ _sk() {
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
-                --with-shell)
-                    COMPREPLY=($(compgen -f "${cur}"))
-                    return 0
-                    ;;
                 --expect)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
@@ -1,4 +1,8 @@
+complete -c sk -l unique-by -d 'Drop the items whose FIELD was read before, implies --unique' -r
 complete -c sk -l min-query-length -d 'Minimum query length to start showing results' -r
+complete -c sk -l suggest-on-empty -d 'Show the K closest items when nothing matches' -r
+complete -c sk -l tie-order -d 'Order of the results whose ranks are equal' -r -f -a "input\t'In the order the items were read, reversed by --tac'
+reverse\t'In the opposite order'"
 complete -c sk -s t -l tiebreak -d 'Comma-separated list of sort criteria to apply when the scores are tied.' -r -f -a "score\t''
 -score\t''
 begin\t''
@@ -12,9 +16,14 @@ index\t''
 pathname\t''
 -pathname\t''"
 complete -c sk -s n -l nth -d 'Fields to be matched' -r
+complete -c sk -l nth-cycle -d 'Field sets the toggle-nth action cycles through after nth' -r
 complete -c sk -l with-nth -d 'Fields to be transformed' -r
 complete -c sk -l hide-nth -d 'Fields to hide from display while keeping them searchable' -r
+complete -c sk -l match-ranges-field -d 'Read precomputed match ranges from this field' -r
+complete -c sk -l max-item-length -d 'Truncate input lines longer than N bytes' -r
+complete -c sk -l annotation-sep -d 'Split each input line at the first SEP into the item and an annotation' -r
 complete -c sk -s d -l delimiter -d 'Delimiter between fields' -r
+complete -c sk -l field-names -d 'Names of the fields, for NAME:term scopes in the query' -r
 complete -c sk -l algo -d 'Fuzzy matching algorithm' -r -f -a "arinae\t'Arinae: typo-resistant & natural algorithm, default'
 clangd\t'Clangd fuzzy matching algorithm'
 fzy\t'Fzy matching algorithm (https://github.com/jhawthorn/fzy)'
@@ -24,28 +33,54 @@ complete -c sk -l case -d 'Case sensitivity' -r -f -a "respect\t'Case-sensitive
 ignore\t'Case-insensitive matching'
 smart\t'Smart case: case-insensitive unless query contains uppercase'"
 complete -c sk -l typos -d 'Enable typo-tolerant matching' -r
+complete -c sk -l match-window -d 'Only fuzzy-match a window of BYTES bytes of longer items' -r
 complete -c sk -l split-match -d 'Enable split matching and set delimiter' -r
 complete -c sk -l scheme -r -f -a "default\t'Default scheme, no modifications to the options'
 path\t'Path scheme: will find the furthest match in the item and set pathname as the main tiebreak'
 history\t'History scheme: will force index as the first tiebreak'"
 complete -c sk -s b -l bind -d 'Comma-separated key, event, and action bindings' -r
+complete -c sk -s m -l multi -d 'Enable multiple selection, of at most N items if given' -r
+complete -c sk -l word-separators -d 'Characters ending a word in the query, besides whitespace' -r
 complete -c sk -s c -l cmd -d 'Command to invoke dynamically in interactive mode' -r
+complete -c sk -l source -d 'Read the items from a source built into skim instead of stdin or the default command' -r -f -a "ps\t'The running processes: pid, parent pid and command line'
+env\t'The environment variables: name and value'
+git-files\t'The files tracked by git'
+git-branches\t'The local and remote git branches: name, date of the last commit and its subject'"
+complete -c sk -l input-file -d 'Read the items from the file at PATH instead of stdin or the default command' -r
 complete -c sk -s I -d 'Replace replstr with the selected item in commands' -r
 complete -c sk -l color -d 'Set color theme' -r
+complete -c sk -l max-display -d 'List at most N of the matched items' -r
+complete -c sk -l hscroll-off -d 'Number of columns to keep visible after the match when scrolling long items' -r
+complete -c sk -l scroll-off -d 'Number of items to keep visible above and below the cursor when scrolling the list' -r
+complete -c sk -l max-fps -d 'Most frames drawn per second for the updates of the reader, the matcher and the preview' -r
 complete -c sk -l skip-to-pattern -d 'Show the matched pattern at the line start' -r
+complete -c sk -l reload-delay -d 'Delay in milliseconds before re-running the command on reload' -r
+complete -c sk -l defer-ingest -d 'Do not parse the input read while typing, until the query stays unchanged for MS milliseconds' -r
+complete -c sk -l reload-interval -d 'Reload every SECONDS' -r
+complete -c sk -l cmd-timeout -d 'Kill the command after this many seconds' -r
+complete -c sk -l cmd-retry -d 'Run the command again, up to N times, when it fails or times out without any output' -r
+complete -c sk -l jump-labels -d 'Label characters of the jump and jump-accept actions' -r
 complete -c sk -l disable-pattern -d 'Disable items based on this regex pattern' -r
 complete -c sk -l layout -d 'Set layout' -r -f -a "default\t'Display from the bottom of the screen'
 reverse\t'Display from the top of the screen'
 reverse-list\t'Display from the top of the screen, prompt at the bottom'"
 complete -c sk -l height -d 'Height of skim\'s window' -r
 complete -c sk -l min-height -d 'Minimum height of skim\'s window' -r
+complete -c sk -l anchor -d 'Where to place skim\'s window when --height is set' -r -f -a "cursor\t'Start on the cursor line, scrolling the terminal up if there is not enough room below it'
+top\t'Stick to the top of the screen, drawing over the existing content'
+bottom\t'Stick to the bottom of the screen, scrolling the terminal up to keep the existing content'"
 complete -c sk -l margin -d 'Screen margin' -r
 complete -c sk -s p -l prompt -d 'Set prompt' -r
 complete -c sk -l cmd-prompt -d 'Set prompt in command mode' -r
+complete -c sk -l prompt2 -d 'Set the prompt of the secondary query, see --query2' -r
 complete -c sk -l selector -d 'Set selected item icon' -r
 complete -c sk -l multi-selector -d 'Set multi-selected item icon' -r
 complete -c sk -l tabstop -d 'Number of spaces that make up a tab' -r
 complete -c sk -l ellipsis -d 'The characters used to display truncated lines' -r
+complete -c sk -l truncate-side -d 'Which side of the items wider than the list to cut' -r -f -a "start\t'Cut the start, keeping the end of the items visible'
+middle\t'Keep both ends of the items, cutting their middle'
+end\t'Cut the end'"
+complete -c sk -l group-by-field -d 'List the items under the group read from this field' -r
 complete -c sk -l info -d 'Set matching result count display position' -r
 complete -c sk -l header -d 'Set header, displayed next to the info' -r
 complete -c sk -l header-lines -d 'Number of lines of the input treated as header' -r
@@ -63,6 +98,14 @@ light-quadruple-dashed\t''
 heavy-quadruple-dashed\t''
 quadrant-inside\t''
 quadrant-outside\t''"
+complete -c sk -l border-label -d 'Label shown on the outer edge of skim\'s borders, with `--border`' -r
+complete -c sk -l border-label-pos -d 'Position of the border label' -r
+complete -c sk -l list-label -d 'Label shown on the border of the item list, with --border' -r
+complete -c sk -l list-label-pos -d 'Position of the list label, see --border-label-pos' -r
+complete -c sk -l input-label -d 'Label shown on the border of the input, with --border' -r
+complete -c sk -l input-label-pos -d 'Position of the input label, see --border-label-pos' -r
+complete -c sk -l header-label -d 'Label shown on the border of the header, with --border' -r
+complete -c sk -l header-label-pos -d 'Position of the header label, see --border-label-pos' -r
 complete -c sk -l multiline -d 'Split item text into multiple display lines at the given separator character defaults to \\n if read0 is set, and \\\\n if not (matching literal \\n in text)' -r
 complete -c sk -l scrollbar -d 'Set scrollbar style for the item list' -r
 complete -c sk -l history -d 'History file' -r
@@ -71,25 +114,66 @@ complete -c sk -l cmd-history -d 'Command history file' -r
 complete -c sk -l cmd-history-size -d 'Maximum number of query history entries to keep' -r
 complete -c sk -l preview -d 'Preview command' -r
 complete -c sk -l preview-window -d 'Preview window layout' -r
+complete -c sk -l wrap-sign -d 'Continuation marker for wrapped preview lines' -r
+complete -c sk -l preview-prefetch -d 'Prefetch the previews of N items around the cursor' -r
+complete -c sk -l preview-label -d 'Label shown on the top border of the preview window' -r
+complete -c sk -l preview-label-pos -d 'Position of the preview label, see --border-label-pos' -r
+complete -c sk -l preview-pane -d 'Show the preview in a tmux pane instead of the preview window' -r
 complete -c sk -l image -d 'Enable image preview' -r -f -a "detect\t'Default: automatically detect the available backend at startup'
 halfblocks\t'Force halfblocks if you want blurry previews but a faster startup or if the detection fails'"
 complete -c sk -s q -l query -d 'Initial query' -r
 complete -c sk -l cmd-query -d 'Initial query in interactive mode' -r
+complete -c sk -l query2 -d 'Initial secondary query' -r
+complete -c sk -l encoding -d 'How to read the lines that are not valid UTF-8' -r -f -a "strict\t'Skip the lines that are not valid UTF-8'
+lossy\t'Replace the invalid bytes with �'
+latin1\t'Read the lines that are not valid UTF-8 as Latin-1'"
+complete -c sk -l print-selection-order -d 'Order of the selected items in the output' -r -f -a "selection\t'In the order the items were selected'
+display\t'In the order of the list, the items no longer shown last'
+input\t'In the order of their index, the order they were read in'"
+complete -c sk -l exit-code -d 'Exit with a custom code when skim is ended by a key, as KEY:CODE (e.g. ctrl-d:3)' -r
 complete -c sk -l output-format -d 'Set the output format If set, overrides all print_ options Will be expanded the same way as preview or commands' -r
+complete -c sk -l grep-output -d 'Print the selected file:line[:col]:text records as the arguments of an editor' -r -f -a "vim\t'+LINE FILE'
+vscode\t'FILE:LINE:COL'"
+complete -c sk -l print-jump -d 'Print each selected item as a jump target of an editor, expanding TEMPLATE with its fields' -r
+complete -c sk -l on-start -d 'Command to run when skim starts' -r
+complete -c sk -l on-accept -d 'Command to run when an item is accepted' -r
+complete -c sk -l on-abort -d 'Command to run when skim is aborted' -r
 complete -c sk -l pre-select-n -d 'Pre-select the first n items in multi-selection mode' -r
 complete -c sk -l pre-select-pat -d 'Pre-select the matched items in multi-selection mode' -r
 complete -c sk -l pre-select-items -d 'Pre-select the items separated by newline character' -r
 complete -c sk -l pre-select-file -d 'Pre-select the items read from this file' -r
+complete -c sk -l pre-select-cmd -d 'Pre-select the items printed by this command' -r
 complete -c sk -s f -l filter -d 'Query for filter mode' -r
+complete -c sk -l filter-or-ui -d 'Filter with the query, falling back to the TUI unless there is exactly one match' -r
+complete -c sk -l with-shell -d 'Shell command and flags used to run the preview, execute and reload commands' -r
 complete -c sk -l shell -d 'Generate shell completion script' -r -f -a "bash\t'Bourne Again SHell'
 elvish\t'Elvish shell'
 fish\t'Friendly Interactive SHell'
 nushell\t'Nushell (nu)'
 power-shell\t'PowerShell'
 zsh\t'Zsh'"
+complete -c sk -l shell-init -d 'Generate the shell integration for SHELL: completions and key bindings' -r -f -a "bash\t'Bourne Again SHell'
+elvish\t'Elvish shell'
+fish\t'Friendly Interactive SHell'
+nushell\t'Nushell (nu)'
+power-shell\t'PowerShell'
+zsh\t'Zsh'"
+complete -c sk -l history-widget -d 'Search the history of SHELL and print the chosen command' -r -f -a "bash\t'Bourne Again SHell'
+elvish\t'Elvish shell'
+fish\t'Friendly Interactive SHell'
+nushell\t'Nushell (nu)'
+power-shell\t'PowerShell'
+zsh\t'Zsh'"
+complete -c sk -l completion-context -d 'Search the candidates of a shell completion instead of the input' -r -f -a "path\t'Files and directories under the directory of the word being completed'
+dir\t'Directories under the directory of the word being completed'
+host\t'Hosts from the ssh config, known_hosts and /etc/hosts'
+proc\t'Running processes, as listed by ps -ef'"
+complete -c sk -l bench-match -d 'Time every fuzzy algorithm on the lines of FILE and print the results' -r
+complete -c sk -l profile -d 'Apply the options of a profile defined in SKIM_OPTIONS_FILE' -r
 complete -c sk -l listen -d 'Run an IPC socket with optional name (defaults to sk)' -r
 complete -c sk -l remote -d 'Send commands to an IPC socket with optional name (defaults to sk)' -r
 complete -c sk -l popup -d 'Run in a tmux or zellij popup' -r
+complete -c sk -l tmux-pane -d 'Run in a tmux pane split off the current one, instead of a popup' -r
 complete -c sk -l log-level -d 'Set the log level' -r
 complete -c sk -l log-file -d 'Pipe log output to a file' -r
 complete -c sk -l flags -d 'Feature flags' -r -f -a "no-preview-pty\t'Disable preview PTY on Linux'
@@ -97,53 +181,48 @@ show-score\t'Display the item\'s match score before its value in the item list (
 show-index\t'Display the item\'s index before its value in the item list'
 single-reader\t'Limit the reader thread pool to a single thread'
 single-matcher\t'Limit the matcher thread pool to a single thread'"
-complete -c sk -l hscroll-off -r
-complete -c sk -l jump-labels -r
 complete -c sk -l tail -r
 complete -c sk -l style -r
 complete -c sk -l padding -r
-complete -c sk -l border-label -r
-complete -c sk -l border-label-pos -r
-complete -c sk -l wrap-sign -r
 complete -c sk -l gap -r
 complete -c sk -l gap-line -r
 complete -c sk -l freeze-left -r
 complete -c sk -l freeze-right -r
-complete -c sk -l scroll-off -r
 complete -c sk -l gutter -r
 complete -c sk -l gutter-raw -r
 complete -c sk -l marker-multi-line -r
 complete -c sk -l list-border -r
-complete -c sk -l list-label -r
-complete -c sk -l list-label-pos -r
 complete -c sk -l info-command -r
 complete -c sk -l separator -r
 complete -c sk -l ghost -r
 complete -c sk -l input-border -r
-complete -c sk -l input-label -r
-complete -c sk -l input-label-pos -r
-complete -c sk -l preview-label -r
-complete -c sk -l preview-label-pos -r
 complete -c sk -l header-border -r
 complete -c sk -l header-lines-border -r
 complete -c sk -l footer -r
 complete -c sk -l footer-border -r
 complete -c sk -l footer-label -r
 complete -c sk -l footer-label-pos -r
-complete -c sk -l with-shell -r
 complete -c sk -l expect -d 'Deprecated, kept for compatibility purposes. See accept() bind instead' -r
 complete -c sk -l tac -d 'Show results in reverse order'
+complete -c sk -l unique -d 'Drop the items read again'
 complete -c sk -l no-sort -d 'Do not sort the results'
+complete -c sk -l reverse-sort -d 'Sort the results worst match first'
+complete -c sk -l skip-long-items -d 'Skip the lines longer than max-item-length instead of truncating them'
+complete -c sk -l grep-mode -d 'Read file:line:col:text records, as printed by rg --column --line-number --no-heading'
 complete -c sk -s e -l exact -d 'Run in exact mode'
+complete -c sk -l exact-boundary -d 'Make exact terms match whole words only'
 complete -c sk -l regex -d 'Start in regex mode instead of fuzzy-match'
+complete -c sk -l no-extended -d 'Match the query as a single term'
 complete -c sk -l no-typos -d 'Disable typo-tolerant matching'
-complete -c sk -l normalize -d 'Normalize unicode characters'
+complete -c sk -l normalize -d 'Ignore the accents of Latin letters when matching (default)'
+complete -c sk -l no-normalize -d 'Match accented letters literally'
 complete -c sk -l last-match -d 'Highlight the last match found, not the first one This makes tiebreak more pertinent on path items where we want to prioritize a match on the last parts'
-complete -c sk -s m -l multi -d 'Enable multiple selection'
 complete -c sk -l no-multi -d 'Disable multiple selection'
 complete -c sk -l no-mouse -d 'Disable mouse'
 complete -c sk -s i -l interactive -d 'Start skim in interactive mode'
+complete -c sk -l no-input -d 'Start without reading any items'
 complete -c sk -l highlight-line -d 'Highlight the entire current line, not just the text'
+complete -c sk -l selection-count -d 'Show the selection count (e.g. 3/10 selected) at the right of the current line'
 complete -c sk -l no-hscroll -d 'Disable horizontal scroll'
 complete -c sk -l keep-right -d 'Keep the right end of the line visible on overflow'
 complete -c sk -l no-clear-if-empty -d 'Do not clear previous line if the command returns an empty result'
@@ -155,6 +234,7 @@ complete -c sk -l disabled -d 'Disable matching entirely'
 complete -c sk -l reverse -d 'Shorthand for reverse layout'
 complete -c sk -l no-height -d 'Disable height (force full screen)'
 complete -c sk -l ansi -d 'Parse ANSI color codes in input strings'
+complete -c sk -l tree -d 'Lay the items out as a tree of their `/`-separated paths'
 complete -c sk -l no-info -d 'Alias for --info=hidden'
 complete -c sk -l inline-info -d 'Alias for --info=inline'
 complete -c sk -l border-no-collapse -d 'Do not collapse adjacent borders into a shared row or column'
@@ -162,18 +242,27 @@ complete -c sk -l no-border -d 'Disables all borders, including in tmux/zellij p
 complete -c sk -l wrap -d 'Wrap items in the item list'
 complete -c sk -l no-scrollbar -d 'Disable the scrollbar in the item list'
 complete -c sk -l read0 -d 'Read input delimited by ASCII NUL(\\0) characters'
+complete -c sk -l delta -d 'Read the input as changes to the items'
 complete -c sk -l print0 -d 'Print output delimited by ASCII NUL(\\0) characters'
 complete -c sk -l print-query -d 'Print the query as the first line'
 complete -c sk -l print-cmd -d 'Print the command as the first line (after print-query)'
 complete -c sk -l print-score -d 'Print the score after each item'
+complete -c sk -l print-positions -d 'Print the byte offsets of the matched characters after each item, comma-separated'
 complete -c sk -l print-header -d 'Print the header as the first line (after print-score)'
+complete -c sk -l print-annotation -d 'Print the annotation of each item after it, separated by annotation-sep'
 complete -c sk -l print-current -d 'Print the current (highlighted) item as the first line (after print-header)'
+complete -c sk -l print-all-keys -d 'Print the key that ended skim as the first line (after print-current)'
 complete -c sk -l no-strip-ansi -d 'Print the ANSI codes, making the output exactly match the input even when --ansi is on'
 complete -c sk -s 1 -l select-1 -d 'Do not enter the TUI if the query passed in -q matches only one item and return it'
 complete -c sk -s 0 -l exit-0 -d 'Do not enter the TUI if the query passed in -q does not match any item'
+complete -c sk -l select-1-on-reload -d 'Accept right away when a read of the command leaves only one item matching'
+complete -c sk -l exit-0-on-reload -d 'Exit right away when a read of the command leaves no item matching'
 complete -c sk -l sync -d 'Synchronous search for multi-staged filtering'
+complete -c sk -l filter-sort -d 'Sort the matches of filter by score'
+complete -c sk -l dry-run -d 'Show the execute and execute-silent commands in the status line instead of running them'
 complete -c sk -l shell-bindings -d 'Generate shell key bindings - only for bash, zsh and fish'
 complete -c sk -l man -d 'Generate man page and output it to stdout'
+complete -c sk -l debug-overlay -d 'Show the durations of the last renders, matcher run and read over the interface'
 complete -c sk -s x -l extended
 complete -c sk -l literal
 complete -c sk -l filepath-word
@@ -183,7 +272,6 @@ complete -c sk -l no-color
 complete -c sk -l no-multi-line
 complete -c sk -l raw
 complete -c sk -l track
-complete -c sk -l no-input
 complete -c sk -l no-separator
 complete -c sk -l header-first
 complete -c sk -s h -l help -d 'Print help (see more with \'--help\')'
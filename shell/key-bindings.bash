@@ -292,9 +292,7 @@ if [[ $- =~ i ]]; then
 	}
 
 	_skim_proc_completion() {
-		_skim_complete -m --preview 'echo {}' --preview-window down:3:wrap --min-height 15 -- "$@" < <(
-			command ps -ef | sed 1d
-		)
+		_skim_complete -m --preview 'echo {}' --preview-window down:3:wrap --min-height 15 --completion-context proc -- "$@" </dev/null
 	}
 
 	_skim_proc_completion_post() {
@@ -302,12 +300,7 @@ if [[ $- =~ i ]]; then
 	}
 
 	_skim_host_completion() {
-		_skim_complete --no-multi -- "$@" < <(
-			command cat <(command tail -n +1 ~/.ssh/config ~/.ssh/config.d/* /etc/ssh/ssh_config 2>/dev/null | command grep -i '^\s*host\(name\)\? ' | awk '{for (i = 2; i <= NF; i++) print $1 " " $i}' | command grep -v '[*?]') \
-				<(command grep -soE '^[[a-z0-9.,:-]+' ~/.ssh/known_hosts | tr ',' '\n' | tr -d '[' | awk '{ print $1 " " $1 }') \
-				<(command grep -sv '^\s*\(#\|$\)' /etc/hosts | command grep -Fv '0.0.0.0') |
-				awk '{if (length($2) > 0) {print $2}}' | sort -u
-		)
+		_skim_complete --no-multi --completion-context host -- "$@" </dev/null
 	}
 
 	_skim_var_completion() {
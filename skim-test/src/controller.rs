@@ -1,38 +1,8 @@
-//! Zellij-backed end-to-end test harness.
-//!
-//! This is a drop-in replacement for the old tmux-based harness. It drives a
-//! real `sk` process running inside a [Zellij](https://zellij.dev) pane and
-//! observes it exactly like a user would: by sending keystrokes and reading the
-//! rendered screen back.
-//!
-//! Unlike tmux, Zellij has no detached-server model, so the harness spawns a
-//! Zellij client attached to an in-process pseudo-terminal (via `portable-pty`,
-//! which uses `openpty` on Unix and ConPTY on Windows). The harness is
-//! cross-platform (Linux, macOS and Windows). A few details are load-bearing for
-//! the non-Linux runners:
-//! - the pane's shell is resolved to an absolute `bash` path (the Zellij
-//!   server's own environment may not have `bash` on `PATH`);
-//! - `ZELLIJ_SOCKET_DIR` is forced to a short path so the session's unix socket
-//!   path stays under the OS cap (macOS's default `$TMPDIR` is too long — see
-//!   [`zellij_socket_dir`]);
-//! - the drain thread answers the client's cursor-position report (`ESC[6n`),
-//!   which the Windows ConPTY client blocks on to learn the terminal size;
-//! - [`ZellijController::wait_ready`] nudges the client's terminal size until the
-//!   server hands the pane a non-zero geometry to render into.
-//!
-//! The pure-harness e2e tests (`interactive.rs`) therefore run on all three
-//! platforms; the tests that additionally install POSIX mock binaries
-//! (`execute.rs`, `popup.rs`, `listen.rs`) stay `#![cfg(unix)]` for that reason,
-//! not the multiplexer's.
-//!
-//! The public surface (`ZellijController`, `Keys`, `wait`, `sk`, the `sk_test!`
-//! DSL and the `line!`/`keys!`/`out!` helpers) mirrors the previous tmux
-//! harness so existing tests port over unchanged.
+//! The Zellij session running the program under test
 
-use std::fmt::{Display, Formatter};
 use std::fs::File;
 use std::io::{BufReader, ErrorKind, Read, Result, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
@@ -42,10 +12,11 @@ use std::time::{Duration, Instant};
 use portable_pty::{CommandBuilder, MasterPty, PtySize, native_pty_system};
 use rand::RngExt as _;
 use rand::distr::Alphanumeric;
+use regex::Regex;
 use tempfile::{NamedTempFile, TempDir, tempdir};
 use which::which;
 
-use crate::common::{SK, SKIM_ENV_REMOVES, SKIM_SHELL_ENV_CLEAR};
+use crate::{Keys, SKIM_ENV_REMOVES, sk, wait};
 
 /// Build the minimal Zellij config used for every test session.
 ///
@@ -107,42 +78,14 @@ fn zellij_socket_dir() -> Result<std::path::PathBuf> {
 /// shell prompt.
 const PROMPT: &str = "skim$ ";
 
-/// Build the shell command that runs `sk` with `opts`, clears the `SKIM_*`
-/// environment inline, and atomically writes its selection to `outfile` (via a
-/// `.part` rename) so readers never observe a half-written result file.
-///
-/// The pane shell is `bash`, which treats `\` as an escape, so any native
-/// Windows path embedded here (the `sk` binary, the outfile) must use forward
-/// slashes — bash on Windows accepts `./target/release/sk.exe` and `C:/Users/…`.
-/// On Unix these paths have no backslashes, so the conversion is a no-op. The
-/// stored `outfile` the caller reads back is left untouched (native separators).
-pub fn sk(outfile: &str, opts: &[&str]) -> String {
-    let sk_bin = SK.replace('\\', "/");
-    let out = outfile.replace('\\', "/");
-    format!(
-        "{}{} {} > {}.part; mv {}.part {}",
-        SKIM_SHELL_ENV_CLEAR,
-        sk_bin,
-        opts.join(" "),
-        out,
-        out,
-        out
-    )
-}
-
 /// Longest a single `zellij` CLI invocation may take before it is killed. Well
 /// above a healthy call (tens of ms) but bounded, so a wedged Zellij server
 /// (e.g. after a session was force-killed) surfaces as a retryable error
 /// instead of blocking the test forever.
 const ZELLIJ_CMD_TIMEOUT: Duration = Duration::from_secs(8);
 
-/// Overall wall-clock budget for a [`wait`] loop. Generous enough for a slow
-/// machine under load, bounded so a genuinely failing assertion (or a dead
-/// session) fails in seconds rather than minutes.
-const WAIT_BUDGET: Duration = Duration::from_secs(20);
-
 /// Budget for the pane's *first* render (see [`ZellijController::wait_ready`]).
-/// Larger than [`WAIT_BUDGET`]: on a cold macOS/Windows runner the Zellij server
+/// Larger than the [`wait`](crate::wait) budget: on a cold macOS/Windows runner the Zellij server
 /// extracts its bundled plugin assets and starts a shell on first use, which can
 /// take considerably longer than a warm session's steady-state calls. Bounded so
 /// a genuinely dead session still fails within a minute rather than hanging.
@@ -171,27 +114,6 @@ const CPR_REPLY: &[u8] = b"\x1b[24;80R";
 /// session is spawned instead of failing the test outright.
 const SESSION_SPAWN_ATTEMPTS: usize = 4;
 
-/// Poll `pred` until it succeeds or [`WAIT_BUDGET`] elapses. On timeout the most
-/// recent error returned by `pred` is surfaced, so a persistent failure keeps its
-/// diagnostic cause.
-pub fn wait<F, T>(pred: F) -> Result<T>
-where
-    F: Fn() -> Result<T>,
-{
-    let deadline = Instant::now() + WAIT_BUDGET;
-    loop {
-        match pred() {
-            Ok(t) => return Ok(t),
-            Err(e) => {
-                if Instant::now() >= deadline {
-                    return Err(e);
-                }
-            }
-        }
-        sleep(Duration::from_millis(10));
-    }
-}
-
 /// Run a `Command`, returning its captured stdout/stderr. The child is killed and
 /// a `TimedOut` error returned if it does not finish within `timeout`. Output
 /// pipes are drained on threads so a chatty process cannot deadlock on a full
@@ -247,90 +169,20 @@ fn output_with_timeout(mut cmd: Command, timeout: Duration) -> Result<(Vec<u8>,
     }
 }
 
-/// A keystroke (or run of characters) to inject into the pane. Each variant
-/// encodes to the raw terminal bytes a real terminal would send (see
-/// [`Keys::encode`]): literal text, a single char, a modified key
-/// (`Ctrl`/`Alt`), or a named special key.
-pub enum Keys<'a> {
-    Str(&'a str),
-    Key(char),
-    Ctrl(&'a Keys<'a>),
-    Alt(&'a Keys<'a>),
-    Enter,
-    Tab,
-    BTab,
-    Left,
-    Right,
-    BSpace,
-    Up,
-    Down,
-    Escape,
-}
-
-impl Display for Keys<'_> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
-        use Keys::*;
-        match self {
-            Str(s) => write!(f, "{}", s),
-            Key(c) => write!(f, "{}", c),
-            Ctrl(k) => write!(f, "C-{}", k),
-            Alt(k) => write!(f, "M-{}", k),
-            Enter => write!(f, "Enter"),
-            Tab => write!(f, "Tab"),
-            BTab => write!(f, "BTab"),
-            Left => write!(f, "Left"),
-            Right => write!(f, "Right"),
-            BSpace => write!(f, "BSpace"),
-            Up => write!(f, "Up"),
-            Down => write!(f, "Down"),
-            Escape => write!(f, "Escape"),
-        }
-    }
-}
-
-impl Keys<'_> {
-    /// The raw bytes a terminal would deliver for this key, appended to `out`.
-    fn encode(&self, out: &mut Vec<u8>) {
-        use Keys::*;
-        match self {
-            Str(s) => out.extend_from_slice(s.as_bytes()),
-            Key(c) => {
-                let mut buf = [0u8; 4];
-                out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
-            }
-            Ctrl(inner) => {
-                let mut inner_bytes = Vec::new();
-                inner.encode(&mut inner_bytes);
-                // Ctrl masks the low 5 bits of the (upper-cased) ASCII byte.
-                if let Some(&b) = inner_bytes.first() {
-                    out.push(b.to_ascii_uppercase() & 0x1f);
-                }
-            }
-            Alt(inner) => {
-                out.push(0x1b);
-                inner.encode(out);
-            }
-            Enter => out.push(b'\r'),
-            Tab => out.push(b'\t'),
-            BTab => out.extend_from_slice(&[0x1b, b'[', b'Z']),
-            Left => out.extend_from_slice(&[0x1b, b'[', b'D']),
-            Right => out.extend_from_slice(&[0x1b, b'[', b'C']),
-            BSpace => out.push(0x7f),
-            Up => out.extend_from_slice(&[0x1b, b'[', b'A']),
-            Down => out.extend_from_slice(&[0x1b, b'[', b'B']),
-            Escape => out.push(0x1b),
-        }
-    }
-}
-
 /// Drives a single `sk` process inside its own Zellij session.
 ///
 /// The `window` field is retained (holding the Zellij session name) so the
-/// public shape matches the previous tmux controller.
+/// public shape matches the previous tmux controller. The session is torn down
+/// on drop, after dumping its artifacts if the test is panicking.
 pub struct ZellijController {
+    /// Name of the Zellij session
     pub window: String,
+    /// Temporary directory for the output files, removed on drop
     pub tempdir: TempDir,
+    /// Output file of the last [`start_sk`](Self::start_sk)
     pub outfile: Option<String>,
+    /// Program run by [`start_sk`](Self::start_sk)
+    pub binary: String,
     // The client process and its master PTY must stay alive for the session's
     // lifetime: dropping the master hangs up the pane and tears the session
     // down early.
@@ -398,7 +250,7 @@ impl ZellijController {
     /// clean, deterministic prompt. The session is torn down on drop.
     ///
     /// Zellij's startup handshake is occasionally racy (see
-    /// [`SESSION_SPAWN_ATTEMPTS`]); a session that dies before it renders is torn
+    /// `SESSION_SPAWN_ATTEMPTS`); a session that dies before it renders is torn
     /// down and a fresh one is spawned, up to that many attempts.
     pub fn new_named(name: &str) -> Result<Self> {
         // Keep session names short: Zellij caps them (~36 chars) and they also
@@ -416,7 +268,7 @@ impl ZellijController {
             // The failed attempt's controller has been dropped here, tearing the
             // dead session down before the next attempt spawns a fresh one.
         }
-        Err(last_err.unwrap_or_else(|| std::io::Error::new(ErrorKind::Other, "failed to start zellij session")))
+        Err(last_err.unwrap_or_else(|| std::io::Error::other("failed to start zellij session")))
     }
 
     /// Spawn a single Zellij session + client and bring it to a ready prompt.
@@ -521,6 +373,7 @@ impl ZellijController {
             window: session,
             tempdir,
             outfile: None,
+            binary: std::env::var("SKIM_TEST_BIN").unwrap_or_else(|_| "sk".to_string()),
             master: pair.master,
             child: Some(child),
             client_output,
@@ -530,6 +383,13 @@ impl ZellijController {
         Ok(controller)
     }
 
+    /// Run `binary` instead of `$SKIM_TEST_BIN` (or `sk`) in [`start_sk`](Self::start_sk)
+    #[must_use]
+    pub fn with_binary(mut self, binary: impl Into<String>) -> Self {
+        self.binary = binary.into();
+        self
+    }
+
     /// Like [`new_named`](Self::new_named) but with a random session name.
     pub fn new() -> Result<Self> {
         let name: String = rand::rng()
@@ -581,10 +441,10 @@ impl ZellijController {
         let deadline = Instant::now() + FIRST_RENDER_BUDGET;
         let mut wide = false;
         loop {
-            if let Ok(dump) = self.action(&["dump-screen"]) {
-                if !dump.trim().is_empty() {
-                    break;
-                }
+            if let Ok(dump) = self.action(&["dump-screen"])
+                && !dump.trim().is_empty()
+            {
+                break;
             }
             // A dead client will never render; fail fast so `new_named` respawns.
             if self.client_exited.load(Ordering::SeqCst) {
@@ -682,7 +542,7 @@ impl ZellijController {
         Ok(Self::to_lines(&dump))
     }
 
-    /// Like [`capture`], but preserves ANSI styling (colors / attributes).
+    /// Like [`capture`](Self::capture), but preserves ANSI styling (colors / attributes).
     pub fn capture_colored(&self) -> Result<Vec<String>> {
         let dump = wait(|| self.action(&["dump-screen", "--ansi"]))?;
         Ok(Self::to_lines(&dump))
@@ -718,6 +578,98 @@ impl ZellijController {
         }
     }
 
+    /// Like [`until`](Self::until), on the screen with its ANSI styling (see
+    /// [`capture_colored`](Self::capture_colored)).
+    pub fn until_colored<F>(&self, pred: F) -> std::io::Result<()>
+    where
+        F: Fn(&[String]) -> bool,
+    {
+        wait(|| {
+            let lines = self.capture_colored()?;
+            if pred(&lines) {
+                return Ok(());
+            }
+            Err(std::io::Error::other("pred not matched"))
+        })
+        .map_err(|_| {
+            std::io::Error::new(
+                ErrorKind::TimedOut,
+                self.capture_colored().unwrap_or_default().join("\n"),
+            )
+        })
+    }
+
+    /// Wait until a line of the screen matches `pattern`, returning the screen.
+    ///
+    /// On timeout, the error holds the last screen.
+    pub fn wait_for_regex(&self, pattern: &str) -> Result<Vec<String>> {
+        let re = Regex::new(pattern).map_err(|e| std::io::Error::new(ErrorKind::InvalidInput, e))?;
+        wait(|| {
+            let lines = self.capture()?;
+            if lines.iter().any(|line| re.is_match(line)) {
+                return Ok(lines);
+            }
+            Err(std::io::Error::other("pattern not matched"))
+        })
+        .map_err(|_| {
+            std::io::Error::new(
+                ErrorKind::TimedOut,
+                format!(
+                    "Timed out waiting for /{pattern}/, got:\n{}",
+                    self.capture().unwrap_or_default().join("\n")
+                ),
+            )
+        })
+    }
+
+    /// Wait until `text` is shown with the SGR attributes `sgr` (e.g. `"1;31"` for bold red),
+    /// as set by the last escape sequence before it on its line.
+    ///
+    /// On timeout, the error holds the last colored screen.
+    pub fn wait_for_style(&self, text: &str, sgr: &str) -> Result<()> {
+        let escaped = regex::escape(sgr);
+        let re = Regex::new(&format!(r"\x1b\[{escaped}m[^\x1b]*{}", regex::escape(text)))
+            .map_err(|e| std::io::Error::new(ErrorKind::InvalidInput, e))?;
+        self.until_colored(|lines| lines.iter().any(|line| re.is_match(line)))
+            .map_err(|e| {
+                std::io::Error::new(
+                    ErrorKind::TimedOut,
+                    format!("Timed out waiting for {text:?} styled with {sgr:?}, got:\n{e}"),
+                )
+            })
+    }
+
+    /// Write the screen (plain and colored), the output and the Zellij client log to
+    /// `<$SKIM_TEST_ARTIFACTS>/<test>`, returning that directory.
+    ///
+    /// `$SKIM_TEST_ARTIFACTS` defaults to `target/skim-test-artifacts`. [`sk_test!`](crate::sk_test)
+    /// calls this when a test fails, and so does the drop of a controller while panicking.
+    pub fn dump_artifacts(&self, test: &str) -> Result<PathBuf> {
+        let dir = std::env::var_os("SKIM_TEST_ARTIFACTS")
+            .map_or_else(|| PathBuf::from("target").join("skim-test-artifacts"), PathBuf::from)
+            .join(test);
+        std::fs::create_dir_all(&dir)?;
+        // the captures are bottom line first, the files read top to bottom
+        let screen = |lines: Vec<String>| lines.into_iter().rev().collect::<Vec<_>>().join("\n");
+        if let Ok(lines) = self.action(&["dump-screen"]).map(|dump| Self::to_lines(&dump)) {
+            std::fs::write(dir.join("screen.txt"), screen(lines))?;
+        }
+        if let Ok(lines) = self
+            .action(&["dump-screen", "--ansi"])
+            .map(|dump| Self::to_lines(&dump))
+        {
+            std::fs::write(dir.join("screen.ansi"), screen(lines))?;
+        }
+        if let Some(outfile) = &self.outfile
+            && Path::new(outfile).exists()
+        {
+            std::fs::copy(outfile, dir.join("output.txt"))?;
+        }
+        std::fs::write(dir.join("client.log"), self.client_output_tail())?;
+        eprintln!("skim-test: artifacts written to {}", dir.display());
+        Ok(dir)
+    }
+
     /// Capture skim output without ANSI sequences
     pub fn output(&self) -> Result<Vec<String>> {
         if let Some(ref outfile) = self.outfile {
@@ -756,7 +708,7 @@ impl ZellijController {
     /// Returns the path of that output file.
     pub fn start_sk(&mut self, stdin_cmd: Option<&str>, opts: &[&str]) -> Result<String> {
         let outfile = self.tempfile()?;
-        let sk_cmd = sk(&outfile, opts);
+        let sk_cmd = sk(&self.binary, &outfile, opts);
         let cmd = match stdin_cmd {
             Some(s) => format!("{} | {}", s, sk_cmd),
             None => sk_cmd,
@@ -771,6 +723,10 @@ impl ZellijController {
 
 impl Drop for ZellijController {
     fn drop(&mut self) {
+        if std::thread::panicking() {
+            let test = std::thread::current().name().unwrap_or(&self.window).replace("::", "-");
+            let _ = self.dump_artifacts(&test);
+        }
         // Kill the client first, then reap it so it doesn't linger as a zombie
         // (many controllers are created per test binary), then remove the session.
         if let Some(mut child) = self.child.take() {
@@ -780,422 +736,3 @@ impl Drop for ZellijController {
         let _ = Self::run(&["delete-session", &self.window, "--force"]);
     }
 }
-// ============================================================================
-// sk_test! - Macro for writing compact tmux-based integration tests
-// ============================================================================
-//
-// USAGE GUIDE
-// -----------
-//
-// 1. INPUT SYNTAX:
-//    - Echo string:  "a\\nb\\nc"      -> Runs: echo -n -e 'a\nb\nc'
-//    - Command:      @cmd "seq 1 100" -> Runs: seq 1 100 (pipe to sk)
-//
-// 2. DSL SYNTAX (Only syntax supported):
-//
-// sk_test!(test_name, "input", &["--opts"], {
-//   @capture[0] eq(">");                     // Wait until capture[0] == ">"
-//   @capture[1] trim().starts_with("3/3");   // Wait until capture[1].trim().starts_with("3/3")
-//   @capture[-1] eq("foo");                  // Wait until last line == "foo"
-//   @capture[*] contains("bar");             // Wait until any line contains "bar"
-//   @output[0] eq("result");                 // Wait until output[0] == "result"
-//   @output[-1] eq("last");                  // Wait until output[-1] (last line) == "last"
-//   @output[*] starts_with("prefix");        // Wait until any output line starts with "prefix"
-//   @capture_colored[0] contains("\x1b");    // Wait until colored capture contains ANSI
-//   @lines |l| (l.len() > 5);                // Complex assertion with closure
-//   @keys Enter, Tab;                        // Send multiple keys
-//   @dbg;                                    // Debug print current capture
-// });
-//
-// NOTE: All methods use wait() for consistent retry behavior. Any ZellijController
-//       method that takes no args and returns Result<Vec<String>> can be used:
-//       capture, output, capture_colored, etc.
-//
-// EXAMPLES
-// --------
-//
-// Example 1: Simple test with echo input (all methods wait/retry)
-//   sk_test!(simple, "a\\nb\\nc", &[], {
-//     @capture[0] eq(">");        // Waits until condition is met
-//     @keys Enter;
-//     @output[0] eq("a");          // Waits until output is available
-//   });
-//
-// Example 2: Using command input with @cmd
-//   sk_test!(with_seq, @cmd "seq 1 10", &["--bind", "'ctrl-t:toggle-all'"], {
-//     @capture[0] eq(">");
-//     @keys Ctrl(&Key('t'));
-//     @capture[2] eq(">>1");
-//   });
-//
-// Example 3: Complex closures with @lines
-//   sk_test!(complex, "apple\\nbanana", &[], {
-//     @lines |l| (l.len() > 4);
-//     @keys Str("ana");
-//     @lines |l| (l.iter().any(|x| x.contains("banana")));
-//   });
-//
-// Example 4: Method chaining
-//   sk_test!(chaining, "  foo  \\n  bar  ", &[], {
-//     @capture[2] trim().eq("foo");
-//     @keys Enter;
-//     @output[0] trim().eq("foo");
-//   });
-//
-// Example 5: Using wildcards and negative indices
-//   sk_test!(wildcards, "apple\\nbanana\\ncherry", &[], {
-//     @capture[*] contains("3/3");           // Any line contains "3/3"
-//     @capture[-1] starts_with(">");         // Last line starts with ">"
-//     @keys Str("ana");
-//     @capture[*] contains("banana");        // Any line contains "banana"
-//     @keys Enter;
-//     @output[0] eq("banana");               // First output line
-//     @output[-1] eq("banana");              // Last output line
-//     @output[*] starts_with("b");           // Any output line starts with "b"
-//   });
-//
-// Example 6: New array syntax test
-//   sk_test!(new_syntax_test, "foo\\nbar\\nbaz", &[], {
-//     @capture[0] starts_with(">");
-//     @capture[1] contains("3/3");
-//     @keys Enter;
-//     @output[0] eq("foo");
-//     @output[- 1] eq("foo");
-//   });
-//
-// Example 7: Wildcard syntax test
-//   sk_test!(wildcard_syntax_test, "apple\\nbanana\\ncherry", &[], {
-//     @capture[*] contains("3/3");
-//     @keys Str("ana");
-//     @capture[*] contains("banana");
-//     @keys Enter;
-//     @output[*] eq("banana");
-//   });
-//
-// Example 8: Comprehensive example showing all features
-//   sk_test!(comprehensive_example, "foo\\nbar\\nbaz\\nqux", &[], {
-//     // Positive index with simple method
-//     @capture[0] starts_with(">");
-//
-//     // Positive index with method chain
-//     @capture[1] trim().contains("4/4");
-//
-//     // Wildcard - check if any line matches
-//     @capture[*] contains("foo");
-//
-//     // Send keys
-//     @keys Str("ba");
-//
-//     // Negative index - last line
-//     @capture[- 1] contains("bar");
-//
-//     // Select first match
-//     @keys Enter;
-//
-//     // Output assertions
-//     @output[0] eq("bar");           // First output line
-//     @output[- 1] eq("bar");         // Last output line
-//     @output[*] starts_with("b");    // Any output line starts with "b"
-//   });
-//
-// Example 9: Using capture_colored for ANSI escape sequences
-//   sk_test!(ansi_test, @cmd "echo -e '\\x1b[31mred\\x1b[0m'", &["--ansi"], {
-//     @capture[*] contains("red");
-//     @capture_colored[*] contains("\x1b[31m");  // Check for ANSI codes
-//     @keys Enter;
-//   });
-//
-// DSL COMMAND REFERENCE
-// ---------------------
-// @METHOD[N] method_chain     Wait until METHOD[N].method_chain is true (N = line number)
-// @METHOD[-N] method_chain    Wait until METHOD[-N].method_chain is true (negative index)
-// @METHOD[*] method_chain     Wait until any line matches (uses .iter().any())
-//   where METHOD is any ZellijController method returning Result<Vec<String>>:
-//     - capture: Wait until condition is true
-//     - output: Wait until condition is true
-//     - capture_colored: Wait until condition is true on colored capture
-//   All methods use wait() for consistent retry behavior
-// @lines |l| (expr)           Call tmux.until(|l| expr)? with closure
-// @keys key1, key2            Send keys (automatically adds ?)
-// @dbg                        Debug print current capture
-//
-// NOTES
-// -----
-// - The `tmux` variable is implicitly available in DSL blocks
-// - All variants automatically handle Result propagation and Ok(()) return
-// - DSL closures must be wrapped in parentheses: |l| (expr)
-// - Method chains support any String/&str method: eq(), starts_with(), contains(), trim(), etc.
-// - You can chain methods: trim().starts_with("foo")
-// - Negative indices work like Python: -1 is last element, -2 is second-to-last, etc.
-// - ALL methods use wait() with retry logic - no immediate assertions
-// - wait() retries every 10ms for up to 10 seconds before timing out
-//
-#[allow(unused_macros)]
-macro_rules! sk_test {
-    // Standard variant with echo input: explicit variable name with block
-    ($name:tt, $input:expr, $options:expr, $tmux:ident => $content:block) => {
-      #[test]
-      #[allow(unused_variables)]
-      fn $name() -> std::io::Result<()> {
-        let mut $tmux = crate::common::zellij::ZellijController::new()?;
-        $tmux.start_sk(Some(&format!("echo -n -e '{}'", $input)), $options)?;
-
-        $content
-
-        Ok(())
-      }
-    };
-
-    // Standard variant with arbitrary command: use @cmd marker
-    ($name:tt, @cmd $cmd:expr, $options:expr, $tmux:ident => $content:block) => {
-      #[test]
-      #[allow(unused_variables)]
-      fn $name() -> std::io::Result<()> {
-        let mut $tmux = crate::common::zellij::ZellijController::new()?;
-        $tmux.start_sk(Some($cmd), $options)?;
-
-        $content
-
-        Ok(())
-      }
-    };
-
-    // DSL variant with echo input
-    ($name:tt, $input:expr, $options:expr, { $($content:tt)* }) => {
-      #[test]
-      #[allow(unused_variables)]
-      fn $name() -> std::io::Result<()> {
-        let mut tmux = crate::common::zellij::ZellijController::new_named(stringify!($name))?;
-        tmux.start_sk(Some(&format!("echo -n -e '{}'", $input)), $options)?;
-
-        sk_test!(@expand tmux; $($content)*);
-
-        Ok(())
-      }
-    };
-
-    // DSL variant with arbitrary command: use @cmd marker
-    ($name:tt, @cmd $cmd:expr, $options:expr, { $($content:tt)* }) => {
-      #[test]
-      #[allow(unused_variables)]
-      fn $name() -> std::io::Result<()> {
-        let mut tmux = crate::common::zellij::ZellijController::new_named(stringify!($name))?;
-        tmux.start_sk(Some($cmd), $options)?;
-
-        sk_test!(@expand tmux; $($content)*);
-
-        Ok(())
-      }
-    };
-
-    // Token processing rules
-    (@expand $tmux:ident; ) => {};
-
-    // Generic method patterns - works with any ZellijController method
-    // @method[*] - check if any line matches (uses .iter().any())
-    (@expand $tmux:ident; @ $method:ident [ * ] $($rest:tt)*) => {
-        sk_test!(@method_any_collect $tmux, $method, [] ; $($rest)*);
-    };
-
-    // @method[-idx] for negative index - supports arbitrary method chains (must come before positive)
-    (@expand $tmux:ident; @ $method:ident [ - $idx:literal ] $($rest:tt)*) => {
-        sk_test!(@method_neg_collect $tmux, $method, $idx, [] ; $($rest)*);
-    };
-
-    // @method[idx] for positive index - supports arbitrary method chains
-    (@expand $tmux:ident; @ $method:ident [ $idx:literal ] $($rest:tt)*) => {
-        sk_test!(@method_pos_collect $tmux, $method, $idx, [] ; $($rest)*);
-    };
-
-    // Collect tokens until semicolon for positive index - dispatches to wait or assert
-    (@method_pos_collect $tmux:ident, $method:ident, $idx:expr, [$($methods:tt)*] ; ; $($rest:tt)*) => {
-        sk_test!(@method_pos_dispatch $tmux, $method, $idx, [$($methods)*]);
-        sk_test!(@expand $tmux; $($rest)*);
-    };
-    (@method_pos_collect $tmux:ident, $method:ident, $idx:expr, [$($methods:tt)*] ; $next:tt $($rest:tt)*) => {
-        sk_test!(@method_pos_collect $tmux, $method, $idx, [$($methods)* $next] ; $($rest)*);
-    };
-
-    // Dispatch for positive index - all methods use wait()
-    (@method_pos_dispatch $tmux:ident, $method:ident, $idx:expr, [$($methods:tt)*]) => {
-        {
-            if crate::common::zellij::wait(|| {
-                let lines = $tmux.$method()?;
-                if lines.len() > $idx && lines[$idx].$($methods)* {
-                    Ok(true)
-                } else {
-                    Err(std::io::Error::new(std::io::ErrorKind::Other, "condition not met"))
-                }
-            }).is_err() {
-                let lines = $tmux.$method().unwrap_or_default();
-                let actual = if lines.len() > $idx { &lines[$idx] } else { "<no line>" };
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::TimedOut,
-                    format!("Timed out waiting for {}[{}].{}, got: {}", stringify!($method), $idx, stringify!($($methods)*), actual)
-                ));
-            }
-        }
-    };
-
-    // Collect tokens until semicolon for negative index - dispatches to wait or assert
-    (@method_neg_collect $tmux:ident, $method:ident, $idx:expr, [$($methods:tt)*] ; ; $($rest:tt)*) => {
-        sk_test!(@method_neg_dispatch $tmux, $method, $idx, [$($methods)*]);
-        sk_test!(@expand $tmux; $($rest)*);
-    };
-    (@method_neg_collect $tmux:ident, $method:ident, $idx:expr, [$($methods:tt)*] ; $next:tt $($rest:tt)*) => {
-        sk_test!(@method_neg_collect $tmux, $method, $idx, [$($methods)* $next] ; $($rest)*);
-    };
-
-    // Dispatch for negative index - all methods use wait()
-    (@method_neg_dispatch $tmux:ident, $method:ident, $idx:expr, [$($methods:tt)*]) => {
-        {
-            if crate::common::zellij::wait(|| {
-                let lines = $tmux.$method()?;
-                if $idx > 0 && lines.len() >= $idx {
-                    let actual_idx = lines.len() - $idx;
-                    if lines[actual_idx].$($methods)* {
-                        Ok(true)
-                    } else {
-                        Err(std::io::Error::new(std::io::ErrorKind::Other, "condition not met"))
-                    }
-                } else {
-                    Err(std::io::Error::new(std::io::ErrorKind::Other, "not enough lines"))
-                }
-            }).is_err() {
-                let lines = $tmux.$method().unwrap_or_default();
-                let actual_idx = lines.len().saturating_sub($idx);
-                let actual = if $idx > 0 && lines.len() >= $idx { &lines[actual_idx] } else { "<no line>" };
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::TimedOut,
-                    format!("Timed out waiting for {}[-{}].{}, got: {}", stringify!($method), $idx, stringify!($($methods)*), actual)
-                ));
-            }
-        }
-    };
-
-    // Collect tokens until semicolon for wildcard [*] - dispatches to wait or assert
-    (@method_any_collect $tmux:ident, $method:ident, [$($methods:tt)*] ; ; $($rest:tt)*) => {
-        sk_test!(@method_any_dispatch $tmux, $method, [$($methods)*]);
-        sk_test!(@expand $tmux; $($rest)*);
-    };
-    (@method_any_collect $tmux:ident, $method:ident, [$($methods:tt)*] ; $next:tt $($rest:tt)*) => {
-        sk_test!(@method_any_collect $tmux, $method, [$($methods)* $next] ; $($rest)*);
-    };
-
-    // Dispatch for wildcard - all methods use wait()
-    (@method_any_dispatch $tmux:ident, $method:ident, [$($methods:tt)*]) => {
-        {
-            if crate::common::zellij::wait(|| {
-                let lines = $tmux.$method()?;
-                if lines.iter().any(|line| line.$($methods)*) {
-                    Ok(true)
-                } else {
-                    Err(std::io::Error::new(std::io::ErrorKind::Other, "condition not met"))
-                }
-            }).is_err() {
-                let lines = $tmux.$method().unwrap_or_default();
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::TimedOut,
-                    format!("Timed out waiting for {}[*] any line matching .{}, got: {:?}", stringify!($method), stringify!($($methods)*), lines)
-                ));
-            }
-        }
-    };
-
-    // @lines command for tmux.until with closure
-    (@expand $tmux:ident; @ lines | $param:ident | ( $($body:tt)* ) ; $($rest:tt)*) => {
-        $tmux.until(|$param| $($body)*)?;
-        sk_test!(@expand $tmux; $($rest)*);
-    };
-
-    // @keys command for send_keys - supports any number of keys
-    (@expand $tmux:ident; @ keys $($key:expr),+ ; $($rest:tt)*) => {
-        send_keys!($tmux, $($key),+)?;
-        sk_test!(@expand $tmux; $($rest)*);
-    };
-
-    // @dbg command for debug printing
-    (@expand $tmux:ident; @ dbg ; $($rest:tt)*) => {
-        match $tmux.capture() {
-            Ok(lines) => println!("DBG: capture: {:?}", lines),
-            Err(e) => println!("DBG: capture failed: {}", e),
-        }
-        match $tmux.output() {
-            Ok(lines) => println!("DBG: output: {:?}", lines),
-            Err(e) => println!("DBG: output failed: {}", e),
-        }
-        sk_test!(@expand $tmux; $($rest)*);
-    };
-
-    // Pass through regular Rust statements that access tmux (catch-all, must be last)
-    (@expand $tmux:ident; $stmt:stmt ; $($rest:tt)*) => {
-        #[allow(redundant_semicolons)]
-        {
-            $stmt;
-            sk_test!(@expand $tmux; $($rest)*);
-        }
-    };
-
-}
-
-#[allow(unused_macros)]
-macro_rules! assert_line {
-    ($tmux:ident, $line_nr:literal $($expression:tt)+) => {
-      {
-      if $tmux.until(|l| l.len() > $line_nr && l[$line_nr] $($expression)+).is_err() {
-          let lines = $tmux.capture().unwrap_or_default();
-          let actual = if lines.len() > $line_nr { &lines[$line_nr] } else { "<no line>" };
-          Err(std::io::Error::new(
-              std::io::ErrorKind::TimedOut,
-              format!(
-                  "Timed out waiting for condition on line {}, got {} but expected it to {}",
-                  $line_nr,
-                  actual,
-                  stringify!($($expression)+)
-              ),
-          ))
-        } else {
-          Ok(())
-        }
-      }?
-    };
-}
-
-#[allow(unused_macros)]
-macro_rules! send_keys {
-    ($tmux:ident, $($key:expr),+) => {
-      $tmux.send_keys(&[$($key),+])
-    };
-}
-
-#[allow(unused_macros)]
-macro_rules! assert_output_line {
-    ($tmux:ident, $line_nr:literal $($expression:tt)+) => {
-        let output = $tmux.output()?;
-        println!("Output: {output:?}");
-        assert!(output[$line_nr] $($expression)+, "Timed out waiting for condition on output line {}, expected it to {}", $line_nr, stringify!($($expression)+));
-    };
-}
-
-// Ultra-short aliases for compact test writing
-// Usage: line!(t, 0 == ">") instead of assert_line!(t, 0 == ">")
-#[allow(unused_macros)]
-macro_rules! line {
-    ($tmux:ident, $line_nr:literal $($expression:tt)+) => {
-        assert_line!($tmux, $line_nr $($expression)+)
-    };
-}
-
-#[allow(unused_macros)]
-macro_rules! keys {
-    ($tmux:ident, $($key:expr),+) => {
-        send_keys!($tmux, $($key),+)
-    };
-}
-
-#[allow(unused_macros)]
-macro_rules! out {
-    ($tmux:ident, $line_nr:literal $($expression:tt)+) => {
-        assert_output_line!($tmux, $line_nr $($expression)+)
-    };
-}
@@ -0,0 +1,111 @@
+//! Keystrokes sent to the pane
+
+use std::fmt::{Display, Formatter};
+
+/// A keystroke (or run of characters) to inject into the pane. Each variant
+/// encodes to the raw terminal bytes a real terminal would send (see
+/// `Keys::encode`): literal text, a single char, a modified key
+/// (`Ctrl`/`Alt`), or a named special key.
+pub enum Keys<'a> {
+    /// Literal text
+    Str(&'a str),
+    /// A single character
+    Key(char),
+    /// The key with Ctrl held
+    Ctrl(&'a Keys<'a>),
+    /// The key with Alt held
+    Alt(&'a Keys<'a>),
+    /// Enter
+    Enter,
+    /// Tab
+    Tab,
+    /// Shift-Tab
+    BTab,
+    /// Left arrow
+    Left,
+    /// Right arrow
+    Right,
+    /// Backspace
+    BSpace,
+    /// Up arrow
+    Up,
+    /// Down arrow
+    Down,
+    /// Escape
+    Escape,
+}
+
+impl Display for Keys<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        use Keys::*;
+        match self {
+            Str(s) => write!(f, "{}", s),
+            Key(c) => write!(f, "{}", c),
+            Ctrl(k) => write!(f, "C-{}", k),
+            Alt(k) => write!(f, "M-{}", k),
+            Enter => write!(f, "Enter"),
+            Tab => write!(f, "Tab"),
+            BTab => write!(f, "BTab"),
+            Left => write!(f, "Left"),
+            Right => write!(f, "Right"),
+            BSpace => write!(f, "BSpace"),
+            Up => write!(f, "Up"),
+            Down => write!(f, "Down"),
+            Escape => write!(f, "Escape"),
+        }
+    }
+}
+
+impl Keys<'_> {
+    /// The raw bytes a terminal would deliver for this key, appended to `out`.
+    pub(crate) fn encode(&self, out: &mut Vec<u8>) {
+        use Keys::*;
+        match self {
+            Str(s) => out.extend_from_slice(s.as_bytes()),
+            Key(c) => {
+                let mut buf = [0u8; 4];
+                out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            }
+            Ctrl(inner) => {
+                let mut inner_bytes = Vec::new();
+                inner.encode(&mut inner_bytes);
+                // Ctrl masks the low 5 bits of the (upper-cased) ASCII byte.
+                if let Some(&b) = inner_bytes.first() {
+                    out.push(b.to_ascii_uppercase() & 0x1f);
+                }
+            }
+            Alt(inner) => {
+                out.push(0x1b);
+                inner.encode(out);
+            }
+            Enter => out.push(b'\r'),
+            Tab => out.push(b'\t'),
+            BTab => out.extend_from_slice(&[0x1b, b'[', b'Z']),
+            Left => out.extend_from_slice(&[0x1b, b'[', b'D']),
+            Right => out.extend_from_slice(&[0x1b, b'[', b'C']),
+            BSpace => out.push(0x7f),
+            Up => out.extend_from_slice(&[0x1b, b'[', b'A']),
+            Down => out.extend_from_slice(&[0x1b, b'[', b'B']),
+            Escape => out.push(0x1b),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Keys::*;
+
+    fn bytes(key: &super::Keys) -> Vec<u8> {
+        let mut out = Vec::new();
+        key.encode(&mut out);
+        out
+    }
+
+    #[test]
+    fn encodes_modified_keys() {
+        assert_eq!(bytes(&Ctrl(&Key('t'))), vec![0x14]);
+        assert_eq!(bytes(&Alt(&Key('b'))), vec![0x1b, b'b']);
+        assert_eq!(bytes(&Up), vec![0x1b, b'[', b'A']);
+        assert_eq!(format!("{}", Ctrl(&Key('t'))), "C-t");
+    }
+}
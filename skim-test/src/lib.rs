@@ -0,0 +1,136 @@
+//! End-to-end test harness for [skim](https://github.com/skim-rs/skim).
+//!
+//! It drives a real `sk` process running inside a [Zellij](https://zellij.dev) pane (0.44 or
+//! later on `PATH`) and observes it exactly like a user would: by sending keystrokes and reading
+//! the rendered screen back. skim's own e2e tests use it, and so can the programs that wrap `sk`
+//! or build on the `skim` library, by pointing it at their binary.
+//!
+//! ```no_run
+//! use skim_test::Keys::*;
+//! use skim_test::ZellijController;
+//!
+//! # fn main() -> std::io::Result<()> {
+//! let mut tmux = ZellijController::new()?.with_binary("./target/debug/my-picker");
+//! tmux.start_sk(Some("printf 'foo\\nbar\\n'"), &["--height", "10"])?;
+//! tmux.wait_for_regex(r"2/2")?;
+//! tmux.send_keys(&[Str("ba"), Enter])?;
+//! assert_eq!(tmux.output()?, vec!["bar"]);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The [`sk_test!`] macro wraps this in a compact DSL, see its documentation.
+//!
+//! The binary defaults to `$SKIM_TEST_BIN`, then to `sk` on `PATH`; `sk_test!` uses the `sk`
+//! binary of the package under test when it has one. When a test fails, the last screen, the
+//! output and the Zellij client log are written under `$SKIM_TEST_ARTIFACTS` (default:
+//! `target/skim-test-artifacts`), see [`ZellijController::dump_artifacts`].
+//!
+//! The harness is cross-platform (Linux, macOS and Windows), the pane shell being `bash`.
+
+mod controller;
+mod keys;
+mod macros;
+
+use std::io::Result;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+pub use controller::ZellijController;
+pub use keys::Keys;
+
+/// Environment variables that must be cleared so `SKIM_DEFAULT_OPTIONS` and friends don't leak
+/// in from the outer test environment
+pub const SKIM_ENV_REMOVES: &[&str] = &["SKIM_DEFAULT_OPTIONS", "SKIM_DEFAULT_COMMAND", "SKIM_OPTIONS_FILE"];
+
+/// Shell-level env-clearing prefix for embedding sk in a shell command string (e.g. commands
+/// typed into the pane)
+#[cfg(unix)]
+pub const SKIM_SHELL_ENV_CLEAR: &str = "SKIM_DEFAULT_OPTIONS= SKIM_DEFAULT_COMMAND= SKIM_OPTIONS_FILE= ";
+/// Shell-level env-clearing prefix for embedding sk in a shell command string (e.g. commands
+/// typed into the pane)
+#[cfg(windows)]
+pub const SKIM_SHELL_ENV_CLEAR: &str = "";
+
+/// Overall wall-clock budget for a [`wait`] loop. Generous enough for a slow
+/// machine under load, bounded so a genuinely failing assertion (or a dead
+/// session) fails in seconds rather than minutes.
+const WAIT_BUDGET: Duration = Duration::from_secs(20);
+
+/// Build the shell command that runs `binary` with `opts`, clears the `SKIM_*`
+/// environment inline, and atomically writes its selection to `outfile` (via a
+/// `.part` rename) so readers never observe a half-written result file.
+///
+/// The pane shell is `bash`, which treats `\` as an escape, so any native
+/// Windows path embedded here (the binary, the outfile) must use forward
+/// slashes — bash on Windows accepts `./target/release/sk.exe` and `C:/Users/…`.
+/// On Unix these paths have no backslashes, so the conversion is a no-op. The
+/// stored `outfile` the caller reads back is left untouched (native separators).
+#[must_use]
+pub fn sk(binary: &str, outfile: &str, opts: &[&str]) -> String {
+    let sk_bin = binary.replace('\\', "/");
+    let out = outfile.replace('\\', "/");
+    format!(
+        "{}{} {} > {}.part; mv {}.part {}",
+        SKIM_SHELL_ENV_CLEAR,
+        sk_bin,
+        opts.join(" "),
+        out,
+        out,
+        out
+    )
+}
+
+/// Poll `pred` until it succeeds or the wait budget (20s) elapses. On timeout the most
+/// recent error returned by `pred` is surfaced, so a persistent failure keeps its
+/// diagnostic cause.
+pub fn wait<F, T>(pred: F) -> Result<T>
+where
+    F: Fn() -> Result<T>,
+{
+    let deadline = Instant::now() + WAIT_BUDGET;
+    loop {
+        match pred() {
+            Ok(t) => return Ok(t),
+            Err(e) => {
+                if Instant::now() >= deadline {
+                    return Err(e);
+                }
+            }
+        }
+        sleep(Duration::from_millis(10));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sk_command_writes_the_output_atomically() {
+        let cmd = sk("./target/debug/sk", "/tmp/out", &["--multi", "-q foo"]);
+        assert!(cmd.ends_with("./target/debug/sk --multi -q foo > /tmp/out.part; mv /tmp/out.part /tmp/out"));
+        assert!(cmd.starts_with(SKIM_SHELL_ENV_CLEAR));
+    }
+
+    #[test]
+    fn sk_command_uses_forward_slashes() {
+        let cmd = sk(r".\target\debug\sk.exe", r"C:\tmp\out", &[]);
+        assert!(cmd.contains("./target/debug/sk.exe"));
+        assert!(cmd.contains("C:/tmp/out.part"));
+    }
+
+    #[test]
+    fn wait_returns_the_first_success() {
+        let calls = std::cell::Cell::new(0);
+        let result = wait(|| {
+            calls.set(calls.get() + 1);
+            if calls.get() < 3 {
+                Err(std::io::Error::other("not yet"))
+            } else {
+                Ok(calls.get())
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+    }
+}
@@ -0,0 +1,300 @@
+//! The `sk_test!` DSL and its assertion helpers
+
+/// Write a compact end-to-end test
+///
+/// The test starts a [`ZellijController`](crate::ZellijController) named after it, runs the
+/// program under test with the given options and input, then runs the DSL statements, each
+/// waiting (see [`wait`](crate::wait)) until its condition holds. When one times out, the test
+/// fails with the last value seen and the session artifacts are dumped (see
+/// [`ZellijController::dump_artifacts`](crate::ZellijController::dump_artifacts)).
+///
+/// The program is the `sk` binary of the package under test when there is one
+/// (`CARGO_BIN_EXE_sk`), else `$SKIM_TEST_BIN`, else `sk`.
+///
+/// # Input
+///
+/// - `"a\\nb\\nc"` runs `echo -n -e 'a\nb\nc' | sk ...`
+/// - `@cmd "seq 1 100"` runs `seq 1 100 | sk ...`
+///
+/// # Statements
+///
+/// ```text
+/// @capture[0] eq(">");                   wait until the bottom line of the screen == ">"
+/// @capture[1] trim().starts_with("3/3"); method chains work on the line
+/// @capture[-1] eq("foo");                negative indices count from the end (top line)
+/// @capture[*] contains("bar");           wait until any line contains "bar"
+/// @output[0] eq("result");               same on the lines printed by sk once it exits
+/// @capture_colored[*] contains("\x1b");  same on the screen with its ANSI styling
+/// @lines |l| (l.len() > 5);              wait until the closure accepts the screen
+/// @keys Enter, Tab;                      send keys (see `Keys`)
+/// @dbg;                                  print the screen and the output
+/// ```
+///
+/// Any [`ZellijController`](crate::ZellijController) method taking no argument and returning
+/// `Result<Vec<String>>` can be used in place of `capture`. Other Rust statements are run as
+/// is, with the controller available as `tmux`. Closures must be wrapped in parentheses.
+///
+/// # Examples
+///
+/// ```ignore
+/// use skim_test::Keys::*;
+/// use skim_test::sk_test;
+///
+/// sk_test!(select_banana, "apple\\nbanana\\ncherry", &[], {
+///     @capture[*] contains("3/3");
+///     @keys Str("ana");
+///     @capture[*] contains("banana");
+///     @keys Enter;
+///     @output[0] eq("banana");
+/// });
+///
+/// sk_test!(toggle_all, @cmd "seq 1 10", &["--multi", "--bind", "'ctrl-t:toggle-all'"], {
+///     @capture[0] eq(">");
+///     @keys Ctrl(&Key('t'));
+///     @capture[2] eq(">>1");
+///     let screen = tmux.wait_for_regex(r"10/10 \[10\]")?;
+/// });
+/// ```
+///
+/// The `sk_test!(name, input, options, tmux => { ... })` form runs a plain Rust block instead
+/// of the DSL.
+#[macro_export]
+macro_rules! sk_test {
+    // Standard variant with echo input: explicit variable name with block
+    ($name:tt, $input:expr, $options:expr, $tmux:ident => $content:block) => {
+        $crate::sk_test!(@test $name, $tmux, &format!("echo -n -e '{}'", $input), $options, $content);
+    };
+
+    // Standard variant with arbitrary command: use @cmd marker
+    ($name:tt, @cmd $cmd:expr, $options:expr, $tmux:ident => $content:block) => {
+        $crate::sk_test!(@test $name, $tmux, $cmd, $options, $content);
+    };
+
+    // DSL variant with echo input
+    ($name:tt, $input:expr, $options:expr, { $($content:tt)* }) => {
+        $crate::sk_test!(@test $name, tmux, &format!("echo -n -e '{}'", $input), $options, {
+            $crate::sk_test!(@expand tmux; $($content)*);
+        });
+    };
+
+    // DSL variant with arbitrary command: use @cmd marker
+    ($name:tt, @cmd $cmd:expr, $options:expr, { $($content:tt)* }) => {
+        $crate::sk_test!(@test $name, tmux, $cmd, $options, {
+            $crate::sk_test!(@expand tmux; $($content)*);
+        });
+    };
+
+    // The test function, dumping the artifacts when the body fails
+    (@test $name:tt, $tmux:ident, $stdin:expr, $options:expr, $content:block) => {
+        #[test]
+        #[allow(unused_variables, unused_mut)]
+        fn $name() -> std::io::Result<()> {
+            let mut $tmux = $crate::ZellijController::new_named(stringify!($name))?;
+            if let Some(binary) = option_env!("CARGO_BIN_EXE_sk") {
+                $tmux.binary = binary.to_string();
+            }
+            $tmux.start_sk(Some($stdin), $options)?;
+
+            let result = (|| -> std::io::Result<()> {
+                $content
+                Ok(())
+            })();
+            if result.is_err() {
+                let _ = $tmux.dump_artifacts(stringify!($name));
+            }
+            result
+        }
+    };
+
+    // Token processing rules
+    (@expand $tmux:ident; ) => {};
+
+    // Generic method patterns - works with any ZellijController method
+    // @method[*] - check if any line matches (uses .iter().any())
+    (@expand $tmux:ident; @ $method:ident [ * ] $($rest:tt)*) => {
+        $crate::sk_test!(@method_any_collect $tmux, $method, [] ; $($rest)*);
+    };
+
+    // @method[-idx] for negative index - supports arbitrary method chains (must come before positive)
+    (@expand $tmux:ident; @ $method:ident [ - $idx:literal ] $($rest:tt)*) => {
+        $crate::sk_test!(@method_neg_collect $tmux, $method, $idx, [] ; $($rest)*);
+    };
+
+    // @method[idx] for positive index - supports arbitrary method chains
+    (@expand $tmux:ident; @ $method:ident [ $idx:literal ] $($rest:tt)*) => {
+        $crate::sk_test!(@method_pos_collect $tmux, $method, $idx, [] ; $($rest)*);
+    };
+
+    // Collect tokens until semicolon for positive index
+    (@method_pos_collect $tmux:ident, $method:ident, $idx:expr, [$($methods:tt)*] ; ; $($rest:tt)*) => {
+        $crate::sk_test!(@method_pos_dispatch $tmux, $method, $idx, [$($methods)*]);
+        $crate::sk_test!(@expand $tmux; $($rest)*);
+    };
+    (@method_pos_collect $tmux:ident, $method:ident, $idx:expr, [$($methods:tt)*] ; $next:tt $($rest:tt)*) => {
+        $crate::sk_test!(@method_pos_collect $tmux, $method, $idx, [$($methods)* $next] ; $($rest)*);
+    };
+
+    // Dispatch for positive index - all methods use wait()
+    (@method_pos_dispatch $tmux:ident, $method:ident, $idx:expr, [$($methods:tt)*]) => {
+        {
+            if $crate::wait(|| {
+                let lines = $tmux.$method()?;
+                if lines.len() > $idx && lines[$idx].$($methods)* {
+                    Ok(true)
+                } else {
+                    Err(std::io::Error::other("condition not met"))
+                }
+            }).is_err() {
+                let lines = $tmux.$method().unwrap_or_default();
+                let actual = if lines.len() > $idx { &lines[$idx] } else { "<no line>" };
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!("Timed out waiting for {}[{}].{}, got: {}", stringify!($method), $idx, stringify!($($methods)*), actual)
+                ));
+            }
+        }
+    };
+
+    // Collect tokens until semicolon for negative index
+    (@method_neg_collect $tmux:ident, $method:ident, $idx:expr, [$($methods:tt)*] ; ; $($rest:tt)*) => {
+        $crate::sk_test!(@method_neg_dispatch $tmux, $method, $idx, [$($methods)*]);
+        $crate::sk_test!(@expand $tmux; $($rest)*);
+    };
+    (@method_neg_collect $tmux:ident, $method:ident, $idx:expr, [$($methods:tt)*] ; $next:tt $($rest:tt)*) => {
+        $crate::sk_test!(@method_neg_collect $tmux, $method, $idx, [$($methods)* $next] ; $($rest)*);
+    };
+
+    // Dispatch for negative index - all methods use wait()
+    (@method_neg_dispatch $tmux:ident, $method:ident, $idx:expr, [$($methods:tt)*]) => {
+        {
+            if $crate::wait(|| {
+                let lines = $tmux.$method()?;
+                if $idx > 0 && lines.len() >= $idx {
+                    let actual_idx = lines.len() - $idx;
+                    if lines[actual_idx].$($methods)* {
+                        Ok(true)
+                    } else {
+                        Err(std::io::Error::other("condition not met"))
+                    }
+                } else {
+                    Err(std::io::Error::other("not enough lines"))
+                }
+            }).is_err() {
+                let lines = $tmux.$method().unwrap_or_default();
+                let actual_idx = lines.len().saturating_sub($idx);
+                let actual = if $idx > 0 && lines.len() >= $idx { &lines[actual_idx] } else { "<no line>" };
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!("Timed out waiting for {}[-{}].{}, got: {}", stringify!($method), $idx, stringify!($($methods)*), actual)
+                ));
+            }
+        }
+    };
+
+    // Collect tokens until semicolon for wildcard [*]
+    (@method_any_collect $tmux:ident, $method:ident, [$($methods:tt)*] ; ; $($rest:tt)*) => {
+        $crate::sk_test!(@method_any_dispatch $tmux, $method, [$($methods)*]);
+        $crate::sk_test!(@expand $tmux; $($rest)*);
+    };
+    (@method_any_collect $tmux:ident, $method:ident, [$($methods:tt)*] ; $next:tt $($rest:tt)*) => {
+        $crate::sk_test!(@method_any_collect $tmux, $method, [$($methods)* $next] ; $($rest)*);
+    };
+
+    // Dispatch for wildcard - all methods use wait()
+    (@method_any_dispatch $tmux:ident, $method:ident, [$($methods:tt)*]) => {
+        {
+            if $crate::wait(|| {
+                let lines = $tmux.$method()?;
+                if lines.iter().any(|line| line.$($methods)*) {
+                    Ok(true)
+                } else {
+                    Err(std::io::Error::other("condition not met"))
+                }
+            }).is_err() {
+                let lines = $tmux.$method().unwrap_or_default();
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!("Timed out waiting for {}[*] any line matching .{}, got: {:?}", stringify!($method), stringify!($($methods)*), lines)
+                ));
+            }
+        }
+    };
+
+    // @lines command for tmux.until with closure
+    (@expand $tmux:ident; @ lines | $param:ident | ( $($body:tt)* ) ; $($rest:tt)*) => {
+        $tmux.until(|$param| $($body)*)?;
+        $crate::sk_test!(@expand $tmux; $($rest)*);
+    };
+
+    // @keys command for send_keys - supports any number of keys
+    (@expand $tmux:ident; @ keys $($key:expr),+ ; $($rest:tt)*) => {
+        $crate::send_keys!($tmux, $($key),+)?;
+        $crate::sk_test!(@expand $tmux; $($rest)*);
+    };
+
+    // @dbg command for debug printing
+    (@expand $tmux:ident; @ dbg ; $($rest:tt)*) => {
+        match $tmux.capture() {
+            Ok(lines) => println!("DBG: capture: {:?}", lines),
+            Err(e) => println!("DBG: capture failed: {}", e),
+        }
+        match $tmux.output() {
+            Ok(lines) => println!("DBG: output: {:?}", lines),
+            Err(e) => println!("DBG: output failed: {}", e),
+        }
+        $crate::sk_test!(@expand $tmux; $($rest)*);
+    };
+
+    // Pass through regular Rust statements that access tmux (catch-all, must be last)
+    (@expand $tmux:ident; $stmt:stmt ; $($rest:tt)*) => {
+        #[allow(redundant_semicolons)]
+        {
+            $stmt;
+            $crate::sk_test!(@expand $tmux; $($rest)*);
+        }
+    };
+}
+
+/// Wait until a line of the screen (bottom line first) satisfies an expression, e.g.
+/// `assert_line!(tmux, 0 == ">")`, failing with the line seen last
+#[macro_export]
+macro_rules! assert_line {
+    ($tmux:ident, $line_nr:literal $($expression:tt)+) => {
+      {
+      if $tmux.until(|l| l.len() > $line_nr && l[$line_nr] $($expression)+).is_err() {
+          let lines = $tmux.capture().unwrap_or_default();
+          let actual = if lines.len() > $line_nr { &lines[$line_nr] } else { "<no line>" };
+          Err(std::io::Error::new(
+              std::io::ErrorKind::TimedOut,
+              format!(
+                  "Timed out waiting for condition on line {}, got {} but expected it to {}",
+                  $line_nr,
+                  actual,
+                  stringify!($($expression)+)
+              ),
+          ))
+        } else {
+          Ok(())
+        }
+      }?
+    };
+}
+
+/// Send keys to the pane, e.g. `send_keys!(tmux, Str("foo"), Enter)`
+#[macro_export]
+macro_rules! send_keys {
+    ($tmux:ident, $($key:expr),+) => {
+      $tmux.send_keys(&[$($key),+])
+    };
+}
+
+/// Assert that a line of the output satisfies an expression, e.g.
+/// `assert_output_line!(tmux, 0 == "foo")`
+#[macro_export]
+macro_rules! assert_output_line {
+    ($tmux:ident, $line_nr:literal $($expression:tt)+) => {
+        let output = $tmux.output()?;
+        println!("Output: {output:?}");
+        assert!(output[$line_nr] $($expression)+, "Timed out waiting for condition on output line {}, expected it to {}", $line_nr, stringify!($($expression)+));
+    };
+}
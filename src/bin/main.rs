@@ -4,11 +4,11 @@
 #![cfg_attr(coverage, allow(unused_features), feature(coverage_attribute))]
 
 extern crate clap;
-extern crate env_logger;
-extern crate log;
 extern crate shlex;
 extern crate skim;
 
+use clap::ValueEnum as _;
+use crossterm::event::KeyEvent;
 use eyre::{Result, eyre};
 #[cfg(feature = "listen")]
 use interprocess::bound_util::RefWrite;
@@ -16,54 +16,52 @@ use interprocess::bound_util::RefWrite;
 use interprocess::local_socket::ToNsName as _;
 #[cfg(feature = "listen")]
 use interprocess::local_socket::traits::Stream as _;
-use log::trace;
 #[cfg(feature = "listen")]
 use skim::binds::parse_action_chain;
 use skim::reader::CommandCollector;
 use std::fs::File;
 use std::io;
-use std::io::{BufReader, BufWriter, IsTerminal, Write};
+use std::io::{BufReader, BufWriter, IsTerminal, Read as _, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
 
 use skim::prelude::*;
+use tracing::{debug, trace};
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 
 fn init_logger(opts: &SkimOptions) {
-    let target = if let Some(ref log_file) = opts.log_file.as_ref().or(std::env::var("SKIM_LOG_FILE").ok().as_ref()) {
-        env_logger::Target::Pipe(Box::new(File::create(log_file).expect("Failed to create log file")))
+    let writer = if let Some(ref log_file) = opts.log_file.as_ref().or(std::env::var("SKIM_LOG_FILE").ok().as_ref()) {
+        BoxMakeWriter::new(std::sync::Mutex::new(
+            File::create(log_file).expect("Failed to create log file"),
+        ))
     } else {
-        env_logger::Target::Stdout
+        BoxMakeWriter::new(io::stdout)
     };
 
-    let env_var = "SKIM_LOG";
-
-    let format = |buf: &mut env_logger::fmt::Formatter, record: &log::Record<'_>| {
-        writeln!(
-            buf,
-            "[{} {} {} ({}:{})] [{}/{:?}] {}",
-            buf.timestamp_nanos(),
-            record.level().as_str(),
-            record.module_path().unwrap_or("sk"),
-            record.file().unwrap_or_default(),
-            record.line().unwrap_or_default(),
-            std::thread::current().name().unwrap_or("?"),
-            std::thread::current().id(),
-            record.args()
-        )
-    };
-
-    if let Some(level) = opts.log_level {
-        env_logger::builder()
-            .filter_level(level)
-            .parse_env(env_var)
-            .target(target)
-            .format(format)
-            .init();
-    } else {
-        env_logger::builder()
-            .parse_env(env_var)
-            .target(target)
-            .format(format)
-            .init();
-    }
+    // `--log-level` is the default, refined per target by `SKIM_LOG`
+    let level = opts
+        .log_level
+        .and_then(|level| level.as_str().parse().ok())
+        .unwrap_or(LevelFilter::ERROR);
+    let filter = EnvFilter::builder()
+        .with_default_directive(level.into())
+        .with_env_var("SKIM_LOG")
+        .from_env_lossy();
+
+    // The spans of the reader, the matcher and the renders are logged with their duration when closed
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(writer)
+        .with_ansi(false)
+        .with_file(true)
+        .with_line_number(true)
+        .with_thread_names(true)
+        .with_thread_ids(true)
+        .with_span_events(FmtSpan::CLOSE)
+        .init();
 }
 
 //------------------------------------------------------------------------------
@@ -86,11 +84,18 @@ fn main() -> Result<()> {
         }
         return Ok(());
     }
+    if let Some(shell) = opts.shell_init {
+        skim::shell::generate_init(&shell, &mut std::io::stdout())?;
+        return Ok(());
+    }
     // Man page
     if opts.man {
         crate::manpage::generate(&mut std::io::stdout())?;
         return Ok(());
     }
+    if let Some([file, pattern]) = opts.bench_match.take().as_deref() {
+        return bench_match(&mut opts, file, pattern);
+    }
 
     #[cfg(feature = "listen")]
     if let Some(remote) = opts.remote {
@@ -102,7 +107,7 @@ fn main() -> Result<()> {
         loop {
             action_chain.clear();
             let len = std::io::stdin().read_line(&mut action_chain)?;
-            log::debug!("Got line {} from stdin", action_chain.trim());
+            debug!("Got line {} from stdin", action_chain.trim());
             if len == 0 {
                 break;
             }
@@ -111,7 +116,7 @@ fn main() -> Result<()> {
                 stream
                     .as_write()
                     .write_all(format!("{}\n", ron::ser::to_string(&act)?).as_bytes())?;
-                log::debug!("Sent action {act:?} to listener");
+                debug!("Sent action {act:?} to listener");
             }
         }
         return Ok(());
@@ -126,11 +131,42 @@ fn main() -> Result<()> {
     }
 }
 
+/// Time every fuzzy algorithm on the lines of `file` and print a table, for `--bench-match`
+fn bench_match(opts: &mut SkimOptions, file: &str, pattern: &str) -> Result<()> {
+    const RUNS: usize = 5;
+    let data = std::fs::read(file)?;
+    let data = String::from_utf8_lossy(&data);
+    let lines: Vec<&str> = if opts.read0 {
+        data.split_terminator('\0').collect()
+    } else {
+        data.lines().collect()
+    };
+
+    let mut out = io::stdout().lock();
+    writeln!(out, "{} lines, pattern {pattern:?}, best of {RUNS} runs", lines.len())?;
+    for algorithm in FuzzyAlgorithm::value_variants() {
+        opts.algorithm = *algorithm;
+        let mut matches = 0;
+        let mut best = Duration::MAX;
+        for _ in 0..RUNS {
+            let start = Instant::now();
+            matches = skim::engine::rank_lines(&lines, pattern, opts).len();
+            best = best.min(start.elapsed());
+        }
+        let name = algorithm
+            .to_possible_value()
+            .map(|v| v.get_name().to_string())
+            .unwrap_or_default();
+        writeln!(out, "{name:<10} {matches:>10} matches {best:>12.2?}")?;
+    }
+    Ok(())
+}
+
 /// Returns `None` if the popup should not open, otherwise run the popup and return the result
 #[cfg(unix)]
 #[allow(clippy::option_option)]
 fn check_and_run_popup(opts: &SkimOptions) -> Option<Option<SkimOutput>> {
-    if opts.popup.is_some() && popup::check_env() {
+    if (opts.popup.is_some() || opts.tmux_pane.is_some()) && popup::check_env() {
         Some(crate::popup::run_with(opts))
     } else {
         None
@@ -156,31 +192,68 @@ fn sk_main(mut opts: SkimOptions) -> Result<i32> {
     let history_file = opts.history_file.clone();
     //------------------------------------------------------------------------------
     let bin_options = BinOptions::from_opts(&opts);
+    let exit_codes = opts.exit_codes.clone();
+    // the number of matches filter mode already printed, as it found them
+    let mut streamed = None;
 
     //------------------------------------------------------------------------------
     // output
 
+    let history = match &opts.history_widget {
+        Some(shell) => Some(read_shell_history(shell)?),
+        None => None,
+    };
+
+    let candidates = match &opts.completion_context {
+        Some(context) => {
+            // the query starts with the directory to list
+            let (root, query) = skim::shell::completion_base(opts.query.as_deref().unwrap_or_default());
+            opts.query = Some(query);
+            Some(skim::shell::completion_candidates(context, &root)?)
+        }
+        None => None,
+    };
+
     let Some(result) = check_and_run_popup(&opts).unwrap_or_else(|| {
         // read from pipe or command
-        let rx_item = if io::stdin().is_terminal() || (opts.interactive && opts.cmd.is_some()) {
+        let rx_item = if let Some(history) = history {
+            Some(cmd_collector.borrow().of_bufread(io::Cursor::new(history)))
+        } else if let Some(candidates) = candidates {
+            Some(cmd_collector.borrow().of_bufread(BufReader::new(candidates)))
+        } else if opts.no_input
+            || opts.source.is_some()
+            || opts.input_file.is_some()
+            || io::stdin().is_terminal()
+            || (opts.interactive && opts.cmd.is_some())
+        {
             None
         } else {
-            let rx_item = cmd_collector.borrow().of_bufread(BufReader::new(std::io::stdin()));
+            let rx_item = cmd_collector.borrow().of_bufread(stdin_source(&opts));
             Some(rx_item)
         };
+        if opts.filter.is_some() && bin_options.streams_items() {
+            return run_filter_streamed(opts, rx_item, &bin_options)
+                .map(|(result, num_matched)| {
+                    streamed = Some(num_matched);
+                    result
+                })
+                .ok();
+        }
         Skim::run_with(opts, rx_item).ok()
     }) else {
         return Ok(135);
     };
-    log::debug!("result: {result:?}");
+    debug!("result: {result:?}");
 
+    let final_key = KeyEvent::new(result.final_key.code, result.final_key.modifiers);
+    let custom_exit_code = exit_codes.get(&final_key).copied();
     if result.is_abort {
-        return Ok(130);
+        return Ok(custom_exit_code.unwrap_or(130));
     }
 
     // Output — use a large BufWriter to batch all writes into a few syscalls
     // instead of one syscall per item (Rust's default LineWriter flushes on \n).
-    {
+    if streamed.is_none() {
         let stdout = io::stdout();
         let mut out = BufWriter::with_capacity(1 << 20, stdout.lock());
         result.write_output(&mut out, &bin_options)?;
@@ -199,35 +272,136 @@ fn sk_main(mut opts: SkimOptions) -> Result<i32> {
         write_history_to_file(&cmd_history, &result.cmd, limit, &file)?;
     }
 
-    Ok(i32::from(result.selected_items.is_empty()))
+    if let Some(code) = custom_exit_code {
+        return Ok(code);
+    }
+    if result.suggested {
+        return Ok(3);
+    }
+    Ok(i32::from(streamed.unwrap_or(result.selected_items.len()) == 0))
+}
+
+/// Runs filter mode, printing the matches as they are found; returns the output and their number
+fn run_filter_streamed(
+    opts: SkimOptions,
+    source: Option<SkimItemReceiver>,
+    bin_options: &BinOptions,
+) -> Result<(SkimOutput, usize)> {
+    let query = opts.query.clone().unwrap_or_default();
+    let cmd = opts.cmd_query.clone().or_else(|| opts.cmd.clone()).unwrap_or_default();
+    let mut out = BufWriter::with_capacity(1 << 20, io::stdout().lock());
+    let mut num_matched = 0;
+    let mut written: io::Result<()> = Ok(());
+    let result = Skim::run_filter(opts, source, |items| {
+        num_matched += items.len();
+        if written.is_ok() {
+            written = items
+                .iter()
+                .try_for_each(|item| bin_options.write_item(&mut out, item, &query, &cmd))
+                .and_then(|()| out.flush());
+        }
+    })?;
+    written?;
+    Ok((result, num_matched))
+}
+
+/// Stdin, decompressed with `--decompress`
+#[cfg_attr(not(feature = "decompress"), allow(unused_variables))]
+fn stdin_source(opts: &SkimOptions) -> Box<dyn io::BufRead + Send> {
+    let stdin = BufReader::new(io::stdin());
+    #[cfg(feature = "decompress")]
+    if opts.decompress {
+        return Box::new(skim::helper::decompress::Decompressed::new(stdin));
+    }
+    Box::new(stdin)
+}
+
+/// Commands of the history of `shell`, NUL-separated for `--history-widget`
+fn read_shell_history(shell: &skim::shell::Shell) -> Result<String> {
+    let path = skim::shell::history_file(shell).ok_or_else(|| eyre!("no history file for {shell:?}"))?;
+    let data = std::fs::read(&path).map_err(|e| eyre!("failed to read {}: {e}", path.display()))?;
+    Ok(skim::shell::parse_history(shell, &data).join("\0"))
 }
 
+/// Add `latest` at the end of the history file
+///
+/// Other skim instances may have written the file since `orig_history` was read from it, so the
+/// entries now on disk are kept, after those of `orig_history` that they lack. The whole
+/// read-merge-write runs under an advisory lock on the history itself (created empty if missing).
+/// The file is then replaced by a rename, so an instance that was waiting for the lock checks that
+/// it still holds the current history, and locks the new one otherwise.
 fn write_history_to_file(
     orig_history: &[String],
     latest: &str,
     limit: usize,
     filename: &str,
 ) -> Result<(), std::io::Error> {
-    if orig_history.last().map(String::as_str) == Some(latest) {
+    let (mut locked, path) = loop {
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(filename)?;
+        file.lock()?;
+        // A symlinked history is replaced at its target
+        let path = std::fs::canonicalize(filename)?;
+        if is_same_file(&file, &path)? {
+            break (file, path);
+        }
+    };
+
+    let mut on_disk = Vec::new();
+    locked.read_to_end(&mut on_disk)?;
+    let on_disk = String::from_utf8_lossy(&on_disk);
+    let on_disk: Vec<&str> = on_disk.lines().collect();
+    let known: std::collections::HashSet<&str> = on_disk.iter().copied().collect();
+    let mut history: Vec<&str> = orig_history
+        .iter()
+        .map(String::as_str)
+        .filter(|h| !known.contains(h))
+        .collect();
+    history.extend(on_disk);
+
+    if history.last() == Some(&latest) {
         // no point of having at the end of the history 5x the same command...
         return Ok(());
     }
     let additional_lines = usize::from(!latest.trim().is_empty());
-    let start_index = if orig_history.len() + additional_lines > limit {
-        orig_history.len() + additional_lines - limit
-    } else {
-        0
-    };
-
-    let mut history = orig_history[start_index..].to_vec();
-    history.push(latest.to_string());
-
-    let file = File::create(filename)?;
-    let mut file = BufWriter::new(file);
+    // Older copies of the latest entry are dropped so that it only appears once, at the end
+    history.retain(|h| additional_lines == 0 || *h != latest);
+    let start_index = (history.len() + additional_lines).saturating_sub(limit);
+    history.drain(..start_index);
+    history.push(latest);
+
+    // Write to a temporary file next to the history and rename it over, with the permissions it
+    // had, so that concurrent readers never see a partially written file
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    file.as_file().set_permissions(locked.metadata()?.permissions())?;
     file.write_all(history.join("\n").as_bytes())?;
+    file.persist(&path).map_err(|e| e.error)?;
     Ok(())
 }
 
+/// Whether `file` is still the one at `path`, i.e. was not replaced since it was opened
+#[cfg(unix)]
+fn is_same_file(file: &File, path: &Path) -> Result<bool, std::io::Error> {
+    use std::os::unix::fs::MetadataExt as _;
+    let opened = file.metadata()?;
+    match std::fs::metadata(path) {
+        Ok(current) => Ok(opened.dev() == current.dev() && opened.ino() == current.ino()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Always true: std exposes no stable file identity outside of Unix
+#[cfg(not(unix))]
+fn is_same_file(_file: &File, _path: &Path) -> Result<bool, std::io::Error> {
+    Ok(true)
+}
+
 #[cfg(test)]
 #[cfg_attr(coverage, coverage(off))]
 mod tests {
@@ -251,9 +425,9 @@ mod tests {
         let dir = tempfile::tempdir().unwrap();
         let file = dir.path().join("hist");
         let file_str = file.to_str().unwrap();
-        // The latest equals the last entry → nothing is written, no file created.
+        // The latest equals the last entry → nothing is written, the locked file stays empty.
         write_history_to_file(&["a".to_string(), "b".to_string()], "b", 10, file_str).unwrap();
-        assert!(!file.exists());
+        assert_eq!(read(&file), "");
     }
 
     #[test]
@@ -266,6 +440,77 @@ mod tests {
         assert_eq!(read(&file), "c\nd");
     }
 
+    #[test]
+    fn write_history_moves_duplicate_to_the_end() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("hist");
+        let file_str = file.to_str().unwrap();
+        let history = ["a".to_string(), "b".to_string(), "c".to_string()];
+        write_history_to_file(&history, "a", 3, file_str).unwrap();
+        assert_eq!(read(&file), "b\nc\na");
+    }
+
+    #[test]
+    fn write_history_replaces_file_without_leftovers() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("hist");
+        let file_str = file.to_str().unwrap();
+        std::fs::write(&file, "a").unwrap();
+        write_history_to_file(&["a".to_string()], "b", 10, file_str).unwrap();
+        assert_eq!(read(&file), "a\nb");
+        // Neither a temporary nor a lock file is left behind
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_history_keeps_symlinks_and_permissions() {
+        use std::os::unix::fs::PermissionsExt as _;
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("hist");
+        let link = dir.path().join("link");
+        std::fs::write(&target, "a").unwrap();
+        std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o640)).unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+        write_history_to_file(&["a".to_string()], "b", 10, link.to_str().unwrap()).unwrap();
+        assert!(std::fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+        assert_eq!(read(&target), "a\nb");
+        let mode = std::fs::metadata(&target).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+    }
+
+    #[test]
+    fn write_history_keeps_entries_written_by_other_instances() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("hist");
+        let file_str = file.to_str().unwrap();
+        // Both instances read "a\nb", the other one wrote "c" first and dropped "a" for its limit
+        std::fs::write(&file, "b\nc").unwrap();
+        write_history_to_file(&["a".to_string(), "b".to_string()], "d", 10, file_str).unwrap();
+        assert_eq!(read(&file), "a\nb\nc\nd");
+        // Already last, nothing to write
+        write_history_to_file(&["a".to_string(), "b".to_string()], "d", 10, file_str).unwrap();
+        assert_eq!(read(&file), "a\nb\nc\nd");
+        write_history_to_file(&["a".to_string(), "b".to_string()], "c", 3, file_str).unwrap();
+        assert_eq!(read(&file), "b\nd\nc");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_history_keeps_concurrent_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("hist");
+        let file_str = file.to_str().unwrap();
+        std::thread::scope(|s| {
+            for i in 0..8 {
+                s.spawn(move || write_history_to_file(&[], &format!("t{i}"), 100, file_str).unwrap());
+            }
+        });
+        let mut lines: Vec<String> = read(&file).lines().map(String::from).collect();
+        lines.sort();
+        assert_eq!(lines, (0..8).map(|i| format!("t{i}")).collect::<Vec<_>>());
+    }
+
     #[test]
     fn write_history_empty_latest_does_not_count_towards_limit() {
         let dir = tempfile::tempdir().unwrap();
@@ -82,6 +82,38 @@ impl SkimEvent {
             _ => None,
         }
     }
+
+    /// The name of the event, as read by [`SkimEvent::from_name`]
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            SkimEvent::Start => "start",
+            SkimEvent::Load => "load",
+            SkimEvent::Change => "change",
+            SkimEvent::Result => "result",
+            SkimEvent::Focus => "focus",
+            SkimEvent::Zero => "zero",
+            SkimEvent::One => "one",
+            SkimEvent::DoubleClick => "double-click",
+        }
+    }
+
+    /// The event routed through the reserved `code`, if it is one
+    #[must_use]
+    pub fn from_key_code(code: KeyCode) -> Option<Self> {
+        [
+            SkimEvent::Start,
+            SkimEvent::Load,
+            SkimEvent::Change,
+            SkimEvent::Result,
+            SkimEvent::Focus,
+            SkimEvent::Zero,
+            SkimEvent::One,
+            SkimEvent::DoubleClick,
+        ]
+        .into_iter()
+        .find(|event| event.key_code() == code)
+    }
 }
 
 impl From<SkimEvent> for KeyEvent {
@@ -199,9 +231,15 @@ pub fn get_default_key_map() -> KeyMap {
     ret.insert(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL), vec![Action::Up(1)]);
     ret.insert(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL), vec![Action::ToggleInteractive]);
     ret.insert(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL), vec![Action::RotateMode]);
+    ret.insert(KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL), vec![Action::TransposeChars]);
     ret.insert(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL), vec![Action::UnixLineDiscard]);
     ret.insert(KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL), vec![Action::UnixWordRubout]);
     ret.insert(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::CONTROL), vec![Action::Yank]);
+    #[cfg(unix)]
+    ret.insert(KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL), vec![Action::Suspend]);
+    ret.insert(KeyEvent::new(KeyCode::Char('_'), KeyModifiers::CONTROL), vec![Action::Undo]);
+    // terminals send ctrl-_ as 0x1f, which crossterm reads as ctrl-7
+    ret.insert(KeyEvent::new(KeyCode::Char('7'), KeyModifiers::CONTROL), vec![Action::Undo]);
 
 
     ret.insert(KeyEvent::new(KeyCode::Backspace, KeyModifiers::ALT), vec![Action::BackwardKillWord]);
@@ -211,6 +249,8 @@ pub fn get_default_key_map() -> KeyMap {
     ret.insert(KeyEvent::new(KeyCode::Char('f'), KeyModifiers::ALT), vec![Action::ForwardWord]);
     ret.insert(KeyEvent::new(KeyCode::Char('h'), KeyModifiers::ALT), vec![Action::ScrollLeft(1)]);
     ret.insert(KeyEvent::new(KeyCode::Char('l'), KeyModifiers::ALT), vec![Action::ScrollRight(1)]);
+    ret.insert(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::ALT), vec![Action::ToggleQuery2]);
+    ret.insert(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::ALT), vec![Action::YankPop]);
 
     KeyMap(ret)
 }
@@ -277,6 +317,8 @@ pub fn parse_key(key: &str) -> Result<KeyEvent> {
             "end" => KeyCode::End,
             "pgup" => KeyCode::PageUp,
             "pgdown" => KeyCode::PageDown,
+            "del" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
             s => match SkimEvent::from_name(s) {
                 Some(event) => event.key_code(),
                 None => return Err(eyre!("Unknown key {}", s)),
@@ -289,6 +331,55 @@ pub fn parse_key(key: &str) -> Result<KeyEvent> {
     Ok(KeyEvent::new(keycode, mods))
 }
 
+/// Formats a crossterm `KeyEvent` as a key name, the way [`parse_key`] reads it back.
+///
+/// Modifiers come first, in the `ctrl-alt-shift-` order, and shifted characters are written
+/// in uppercase instead (`ctrl-A`). Reserved event codes are named after their [`SkimEvent`],
+/// and keys [`parse_key`] cannot name fall back to their lowercased crossterm name.
+#[must_use]
+pub fn key_name(key: &KeyEvent) -> String {
+    let mut mods = key.modifiers;
+    let name = match key.code {
+        KeyCode::Char(' ') => String::from("space"),
+        KeyCode::Char(c) if mods.contains(KeyModifiers::SHIFT) && c.to_uppercase().ne(c.to_lowercase()) => {
+            mods.remove(KeyModifiers::SHIFT);
+            c.to_uppercase().collect()
+        }
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => match SkimEvent::from_key_code(key.code) {
+            Some(event) => return event.name().to_string(),
+            None => format!("f{n}"),
+        },
+        KeyCode::Enter => String::from("enter"),
+        KeyCode::Backspace => String::from("bspace"),
+        KeyCode::Up => String::from("up"),
+        KeyCode::Down => String::from("down"),
+        KeyCode::Left => String::from("left"),
+        KeyCode::Right => String::from("right"),
+        KeyCode::Tab => String::from("tab"),
+        KeyCode::BackTab => String::from("btab"),
+        KeyCode::Esc => String::from("esc"),
+        KeyCode::Home => String::from("home"),
+        KeyCode::End => String::from("end"),
+        KeyCode::PageUp => String::from("pgup"),
+        KeyCode::PageDown => String::from("pgdown"),
+        KeyCode::Delete => String::from("del"),
+        KeyCode::Insert => String::from("insert"),
+        code => format!("{code:?}").to_lowercase(),
+    };
+    let mut prefix = String::new();
+    for (modifier, label) in [
+        (KeyModifiers::CONTROL, "ctrl-"),
+        (KeyModifiers::ALT, "alt-"),
+        (KeyModifiers::SHIFT, "shift-"),
+    ] {
+        if mods.contains(modifier) {
+            prefix.push_str(label);
+        }
+    }
+    prefix + name.as_str()
+}
+
 /// Parse an iterator of keymaps into a `KeyMap`
 pub fn parse_keymaps<'a, T>(maps: T) -> KeyMap
 where
@@ -167,6 +167,41 @@ fn double_click_accepts_by_default() {
     );
 }
 
+#[test]
+fn alt_q_moves_to_the_secondary_query_by_default() {
+    assert_eq!(
+        get_default_key_map().get(&KeyEvent::new(KeyCode::Char('q'), KeyModifiers::ALT)),
+        Some(&vec![ToggleQuery2])
+    );
+}
+
+#[test]
+fn key_name_is_read_back_by_parse_key() {
+    for name in [
+        "a",
+        "A",
+        "ctrl-alt-f5",
+        "alt-A",
+        "ctrl-space",
+        "shift-up",
+        "alt-bspace",
+        "del",
+        "insert",
+        "btab",
+        "change",
+        "double-click",
+    ] {
+        assert_eq!(key_name(&parse_key(name).unwrap()), name);
+    }
+    // modifiers are written in the ctrl-alt-shift order
+    assert_eq!(
+        key_name(&parse_key("shift-alt-ctrl-left").unwrap()),
+        "ctrl-alt-shift-left"
+    );
+    // terminals report shifted characters in uppercase
+    assert_eq!(key_name(&KeyEvent::new(KeyCode::Char('A'), KeyModifiers::SHIFT)), "A");
+}
+
 #[test]
 fn parse_key_error_cases() {
     // Empty input.
@@ -1,6 +1,9 @@
 use std::fmt::{Display, Error, Formatter};
 
+use std::sync::Arc;
+
 use crate::fuzzy_matcher::MatchIndices;
+use crate::item::RankBuilder;
 use crate::{MatchEngine, MatchRange, MatchResult, SkimItem};
 
 //------------------------------------------------------------------------------
@@ -139,6 +142,49 @@ impl Display for AndEngine {
     }
 }
 
+//------------------------------------------------------------------------------
+// NotEngine, a combinator
+pub struct NotEngine {
+    engine: Box<dyn MatchEngine>,
+    rank_builder: Arc<RankBuilder>,
+}
+
+impl NotEngine {
+    pub fn builder(engine: Box<dyn MatchEngine>) -> Self {
+        Self {
+            engine,
+            rank_builder: Default::default(),
+        }
+    }
+
+    pub fn rank_builder(mut self, rank_builder: Arc<RankBuilder>) -> Self {
+        self.rank_builder = rank_builder;
+        self
+    }
+
+    pub fn build(self) -> Self {
+        self
+    }
+}
+
+impl MatchEngine for NotEngine {
+    fn match_item(&self, item: &dyn SkimItem) -> Option<MatchResult> {
+        if self.engine.match_item(item).is_some() {
+            return None;
+        }
+        Some(MatchResult {
+            rank: self.rank_builder.build_rank(0, 0, 0, &item.text()),
+            matched_range: MatchRange::ByteRange(0, 0),
+        })
+    }
+}
+
+impl Display for NotEngine {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "(Not: {})", self.engine)
+    }
+}
+
 #[cfg(test)]
 #[path = "andor_tests.rs"]
 mod tests;
@@ -99,3 +99,12 @@ fn merge_dedups_and_sorts_overlapping_ranges() {
     // Sorted and de-duplicated union of {3,1} and {1,2}.
     assert_eq!(merged.matched_range, MatchRange::Chars(vec![1, 2, 3]));
 }
+
+#[test]
+fn not_engine_inverts_the_subengine() {
+    let engine = NotEngine::builder(exact("foo")).build();
+    assert!(engine.match_item(&"a foo bar".to_string()).is_none());
+    let result = engine.match_item(&"a bar".to_string()).unwrap();
+    assert_eq!(result.matched_range, MatchRange::ByteRange(0, 0));
+    assert_eq!(format!("{engine}"), "(Not: (Exact|(?i)foo))");
+}
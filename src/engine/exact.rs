@@ -1,4 +1,5 @@
 use crate::engine::util::{contains_upper, regex_match};
+use crate::field::is_word_bounded;
 use crate::item::RankBuilder;
 use crate::{CaseMatching, MatchEngine, MatchRange, MatchResult, SkimItem};
 use regex::{Regex, escape};
@@ -14,6 +15,8 @@ pub struct ExactMatchingParam {
     pub prefix: bool,
     pub postfix: bool,
     pub inverse: bool,
+    /// Only match where the query is delimited by word boundaries
+    pub boundary: bool,
     pub case: CaseMatching,
     __non_exhaustive: bool,
 }
@@ -25,6 +28,7 @@ pub struct ExactEngine {
     query_regex: Option<Regex>,
     rank_builder: Arc<RankBuilder>,
     inverse: bool,
+    boundary: bool,
 }
 
 impl ExactEngine {
@@ -61,6 +65,7 @@ impl ExactEngine {
             query_regex,
             rank_builder: Default::default(),
             inverse: param.inverse,
+            boundary: param.boundary,
         }
     }
 
@@ -72,6 +77,16 @@ impl ExactEngine {
     pub fn build(self) -> Self {
         self
     }
+
+    /// The first match in `text[start..end]` that is delimited by word boundaries, the characters
+    /// around the range counting
+    fn bounded_match(&self, text: &str, start: usize, end: usize) -> Option<(usize, usize)> {
+        self.query_regex
+            .as_ref()?
+            .find_iter(&text[start..end])
+            .map(|m| (m.start() + start, m.end() + start))
+            .find(|&(s, e)| is_word_bounded(text, s, e))
+    }
 }
 
 impl MatchEngine for ExactEngine {
@@ -87,8 +102,11 @@ impl MatchEngine for ExactEngine {
                 break;
             }
 
-            matched_result =
-                regex_match(&item_text[start..end], self.query_regex.as_ref()).map(|(s, e)| (s + start, e + start));
+            matched_result = if self.boundary {
+                self.bounded_match(&item_text, start, end)
+            } else {
+                regex_match(&item_text[start..end], self.query_regex.as_ref()).map(|(s, e)| (s + start, e + start))
+            };
 
             if self.inverse {
                 matched_result = matched_result.xor(Some((0, 0)));
@@ -112,7 +130,8 @@ impl Display for ExactEngine {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
         write!(
             f,
-            "(Exact|{}{})",
+            "({}|{}{})",
+            if self.boundary { "Boundary" } else { "Exact" },
             if self.inverse { "!" } else { "" },
             self.query_regex.as_ref().map_or("", regex::Regex::as_str)
         )
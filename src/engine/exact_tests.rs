@@ -111,3 +111,29 @@ fn display_shows_query_and_inverse_marker() {
     );
     assert!(format!("{inverse}").starts_with("(Exact|!"));
 }
+
+#[test]
+fn boundary_skips_matches_inside_words() {
+    let e = engine(
+        "foo",
+        ExactMatchingParam {
+            boundary: true,
+            ..Default::default()
+        },
+    );
+    let result = e.match_item(&"foobar foo.rs".to_string()).unwrap();
+    assert_eq!(result.matched_range, MatchRange::ByteRange(7, 10));
+    assert!(e.match_item(&"foobar xfoo".to_string()).is_none());
+    assert_eq!(format!("{e}"), "(Boundary|(?i)foo)");
+
+    let inverse = engine(
+        "foo",
+        ExactMatchingParam {
+            boundary: true,
+            inverse: true,
+            ..Default::default()
+        },
+    );
+    assert!(inverse.match_item(&"foobar".to_string()).is_some());
+    assert!(inverse.match_item(&"a foo".to_string()).is_none());
+}
@@ -1,19 +1,21 @@
 use regex::Regex;
 
 use crate::engine::all::MatchAllEngine;
-use crate::engine::andor::{AndEngine, OrEngine};
+use crate::engine::andor::{AndEngine, NotEngine, OrEngine};
 use crate::engine::exact::{ExactEngine, ExactMatchingParam};
 use crate::engine::fuzzy::{FuzzyAlgorithm, FuzzyEngine};
+use crate::engine::query::{self, Term, TermKind};
 use crate::engine::regexp::RegexEngine;
+use crate::engine::scoped::{FieldScopedEngine, SourceScopedEngine};
+use crate::fuzzy_matcher::segment::Segmenter;
 use crate::item::RankBuilder;
 use crate::{CaseMatching, MatchEngine, MatchEngineFactory, Typos};
-use std::sync::{Arc, LazyLock};
-
-static RE_OR_WITH_SPACES: LazyLock<Regex> = LazyLock::new(|| Regex::new(r" *\|+ *").unwrap());
+use std::sync::Arc;
 
 //------------------------------------------------------------------------------
 // Exact engine factory
 /// Factory for creating exact or fuzzy match engines based on configuration
+#[allow(clippy::struct_excessive_bools)]
 pub struct ExactOrFuzzyEngineFactory {
     exact_mode: bool,
     fuzzy_algorithm: FuzzyAlgorithm,
@@ -21,6 +23,10 @@ pub struct ExactOrFuzzyEngineFactory {
     typos: Typos,
     filter_mode: bool,
     last_match: bool,
+    segmenter: Option<Arc<dyn Segmenter>>,
+    match_window: Option<usize>,
+    extended: bool,
+    exact_boundary: bool,
 }
 
 impl ExactOrFuzzyEngineFactory {
@@ -34,6 +40,10 @@ impl ExactOrFuzzyEngineFactory {
             typos: Typos::Disabled,
             filter_mode: false,
             last_match: false,
+            segmenter: None,
+            match_window: None,
+            extended: true,
+            exact_boundary: false,
         }
     }
 
@@ -83,6 +93,41 @@ impl ExactOrFuzzyEngineFactory {
         self
     }
 
+    /// Sets the word segmenter used to score word starts in non-ASCII text
+    ///
+    /// Only the Arinae algorithm makes use of it.
+    #[must_use]
+    pub fn segmenter(mut self, segmenter: Option<Arc<dyn Segmenter>>) -> Self {
+        self.segmenter = segmenter;
+        self
+    }
+
+    /// Sets the number of bytes of longer items that fuzzy matching considers
+    #[must_use]
+    pub fn match_window(mut self, match_window: Option<usize>) -> Self {
+        self.match_window = match_window;
+        self
+    }
+
+    /// Sets whether the `'`, `!`, `^` and `$` operators are parsed
+    ///
+    /// When false, the whole query is matched as is, fuzzily or exactly depending on
+    /// `exact_mode`.
+    #[must_use]
+    pub fn extended(mut self, extended: bool) -> Self {
+        self.extended = extended;
+        self
+    }
+
+    /// Sets whether exact terms only match between word boundaries, as `'abc'` terms do
+    ///
+    /// Prefix, suffix and whole-item terms are not affected.
+    #[must_use]
+    pub fn exact_boundary(mut self, exact_boundary: bool) -> Self {
+        self.exact_boundary = exact_boundary;
+        self
+    }
+
     /// Builds the factory (currently a no-op, returns self)
     #[must_use]
     pub fn build(self) -> Self {
@@ -90,71 +135,75 @@ impl ExactOrFuzzyEngineFactory {
     }
 }
 
+impl ExactOrFuzzyEngineFactory {
+    fn fuzzy_engine(&self, query: &str, case: CaseMatching, typos: Typos) -> Box<dyn MatchEngine> {
+        Box::new(
+            FuzzyEngine::builder()
+                .query(query)
+                .algorithm(self.fuzzy_algorithm)
+                .case(case)
+                .typos(typos)
+                .filter_mode(self.filter_mode)
+                .last_match(self.last_match)
+                .segmenter(self.segmenter.clone())
+                .match_window(self.match_window)
+                .rank_builder(self.rank_builder.clone())
+                .build(),
+        )
+    }
+
+    fn exact_engine(&self, query: &str, param: ExactMatchingParam) -> Box<dyn MatchEngine> {
+        Box::new(
+            ExactEngine::builder(query, param)
+                .rank_builder(self.rank_builder.clone())
+                .build(),
+        )
+    }
+}
+
 impl MatchEngineFactory for ExactOrFuzzyEngineFactory {
     fn create_engine_with_case(&self, query: &str, case: CaseMatching) -> Box<dyn MatchEngine> {
-        // 'abc => match exact "abc"
-        // ^abc => starts with "abc"
-        // abc$ => ends with "abc"
-        // ^abc$ => match exact "abc"
-        // !^abc => items not starting with "abc"
-        // !abc$ => items not ending with "abc"
-        // !^abc$ => not "abc"
-
-        let mut query = query;
-        let mut exact = self.exact_mode;
         let mut param = ExactMatchingParam::default();
         param.case = case;
 
-        if query.starts_with('\'') {
-            exact = !exact;
-            query = &query[1..];
+        if !self.extended {
+            return if self.exact_mode {
+                param.boundary = self.exact_boundary;
+                self.exact_engine(query, param)
+            } else {
+                self.fuzzy_engine(query, case, self.typos)
+            };
         }
 
-        if query.starts_with('!') {
-            query = &query[1..];
-            exact = true;
-            param.inverse = true;
-        }
-
-        if query.is_empty() {
-            // if only "!" was provided, will still show all items
-            return Box::new(
-                MatchAllEngine::builder()
-                    .rank_builder(self.rank_builder.clone())
-                    .build(),
-            );
-        }
-
-        if query.starts_with('^') {
-            query = &query[1..];
-            exact = true;
-            param.prefix = true;
-        }
-
-        if query.ends_with('$') {
-            query = &query[..(query.len() - 1)];
-            exact = true;
-            param.postfix = true;
-        }
-
-        if exact {
-            Box::new(
-                ExactEngine::builder(query, param)
-                    .rank_builder(self.rank_builder.clone())
-                    .build(),
-            )
-        } else {
-            Box::new(
-                FuzzyEngine::builder()
-                    .query(query)
-                    .algorithm(self.fuzzy_algorithm)
-                    .case(case)
-                    .typos(self.typos)
-                    .filter_mode(self.filter_mode)
-                    .last_match(self.last_match)
+        // See `engine::query` for the operators
+        let term = Term::parse(query, self.exact_mode);
+        // `abc~` tolerates typos even when `--typos` is disabled
+        let typos = match self.typos {
+            Typos::Disabled if term.typos => Typos::Smart,
+            typos => typos,
+        };
+        match term.kind {
+            TermKind::Fuzzy | TermKind::Exact if term.text.is_empty() => {
+                // if only "!" was provided, will still show all items
+                Box::new(
+                    MatchAllEngine::builder()
+                        .rank_builder(self.rank_builder.clone())
+                        .build(),
+                )
+            }
+            TermKind::Fuzzy if term.inverse => Box::new(
+                NotEngine::builder(self.fuzzy_engine(&term.text, case, typos))
                     .rank_builder(self.rank_builder.clone())
                     .build(),
-            )
+            ),
+            TermKind::Fuzzy => self.fuzzy_engine(&term.text, case, typos),
+            kind => {
+                param.prefix = matches!(kind, TermKind::Prefix | TermKind::Equal);
+                param.postfix = matches!(kind, TermKind::Suffix | TermKind::Equal);
+                param.boundary = kind == TermKind::Boundary || (kind == TermKind::Exact && self.exact_boundary);
+                param.inverse = term.inverse;
+                self.exact_engine(&term.text, param)
+            }
         }
     }
 }
@@ -163,6 +212,7 @@ impl MatchEngineFactory for ExactOrFuzzyEngineFactory {
 /// Factory for creating AND/OR composite match engines
 pub struct AndOrEngineFactory {
     inner: Box<dyn MatchEngineFactory>,
+    fields: Option<(Regex, Vec<String>)>,
 }
 
 impl AndOrEngineFactory {
@@ -170,51 +220,56 @@ impl AndOrEngineFactory {
     pub fn new(factory: impl MatchEngineFactory + 'static) -> Self {
         Self {
             inner: Box::new(factory),
+            fields: None,
         }
     }
 
+    /// Enables the `FIELD:term` scopes
+    ///
+    /// Items are split into fields at `delimiter`, and `names` name the fields in order.
+    #[must_use]
+    pub fn fields(mut self, delimiter: Regex, names: Vec<String>) -> Self {
+        self.fields = Some((delimiter, names));
+        self
+    }
+
     fn parse_andor(&self, query: &str, case: CaseMatching) -> Box<dyn MatchEngine> {
         if query.trim().is_empty() {
             return self.inner.create_engine_with_case(query, case);
         }
-        let and_engines = RE_OR_WITH_SPACES
-            .replace_all(&Self::mask_escape_space(query), "|")
-            .split(' ')
-            .filter_map(|and_term| {
-                if and_term.is_empty() {
-                    return None;
-                }
-                let or_engines = and_term
-                    .split('|')
-                    .filter_map(|term| {
-                        if term.is_empty() {
-                            return None;
-                        }
+        let field_names = self.fields.as_ref().map(|(_, names)| names.as_slice());
+        let and_engines = query::split(query, field_names)
+            .into_iter()
+            .map(|group| {
+                let mut or_engines = group
+                    .into_iter()
+                    .map(|(field, term)| {
                         debug!("Creating Or engine for {term}");
-                        Some(
-                            self.inner
-                                .create_engine_with_case(&Self::unmask_escape_space(term), case),
-                        )
+                        let engine = self.inner.create_engine_with_case(&term, case);
+                        if field.is_none()
+                            && let Some(source) = query::source_term(&term)
+                        {
+                            let source = self.inner.create_engine_with_case(&source, case);
+                            return Box::new(SourceScopedEngine::new(source, engine)) as Box<dyn MatchEngine>;
+                        }
+                        match (field, &self.fields) {
+                            (Some(field), Some((delimiter, _))) => {
+                                Box::new(FieldScopedEngine::new(engine, field, delimiter.clone()))
+                            }
+                            _ => engine,
+                        }
                     })
                     .collect::<Vec<_>>();
                 debug!("Building or matcher engine from Ors");
                 if or_engines.len() == 1 {
-                    return Some(or_engines.into_iter().next().unwrap());
+                    return or_engines.pop().unwrap();
                 }
-                Some(Box::new(OrEngine::builder().engines(or_engines).build()) as Box<dyn MatchEngine>)
+                Box::new(OrEngine::builder().engines(or_engines).build()) as Box<dyn MatchEngine>
             })
             .collect();
         debug!("Creating and matcher engine from Ors");
         Box::new(AndEngine::builder().engines(and_engines).build())
     }
-
-    fn mask_escape_space(string: &str) -> String {
-        string.replace("\\ ", "\0")
-    }
-
-    fn unmask_escape_space(string: &str) -> String {
-        string.replace('\0', " ")
-    }
 }
 
 impl MatchEngineFactory for AndOrEngineFactory {
@@ -345,4 +400,85 @@ mod test {
         let engine = factory.create_engine("ab.");
         assert_eq!(format!("{engine}"), "(Regex: ab.)");
     }
+
+    #[test]
+    fn inverse_fuzzy_term() {
+        use super::*;
+        let factory = ExactOrFuzzyEngineFactory::builder().build();
+        let engine = factory.create_engine("!'abc");
+        assert_eq!(format!("{engine}"), "(Not: (Fuzzy: abc))");
+        assert!(engine.match_item(&"xyz".to_string()).is_some());
+        assert!(engine.match_item(&"a_b_c".to_string()).is_none());
+    }
+
+    #[test]
+    fn not_extended_matches_the_query_as_is() {
+        use super::*;
+        let factory = ExactOrFuzzyEngineFactory::builder().extended(false).build();
+        assert_eq!(format!("{}", factory.create_engine("!^abc")), "(Fuzzy: !^abc)");
+        let factory = ExactOrFuzzyEngineFactory::builder()
+            .exact_mode(true)
+            .extended(false)
+            .build();
+        assert_eq!(format!("{}", factory.create_engine("'a b")), "(Exact|(?i)'a b)");
+    }
+
+    #[test]
+    fn exact_boundary_terms() {
+        use super::*;
+        let factory = ExactOrFuzzyEngineFactory::builder().build();
+        assert_eq!(format!("{}", factory.create_engine("'foo'")), "(Boundary|(?i)foo)");
+        assert_eq!(format!("{}", factory.create_engine("'foo")), "(Exact|(?i)foo)");
+
+        let factory = ExactOrFuzzyEngineFactory::builder().exact_boundary(true).build();
+        assert_eq!(format!("{}", factory.create_engine("'foo")), "(Boundary|(?i)foo)");
+        assert_eq!(format!("{}", factory.create_engine("^foo")), "(Exact|(?i)^foo)");
+        assert_eq!(format!("{}", factory.create_engine("foo")), "(Fuzzy: foo)");
+    }
+
+    #[test]
+    fn tilde_enables_typos_for_the_term() {
+        use super::*;
+        for algorithm in [FuzzyAlgorithm::Arinae, FuzzyAlgorithm::Fzy] {
+            let factory = ExactOrFuzzyEngineFactory::builder().fuzzy_algorithm(algorithm).build();
+            let item = "hello world".to_string();
+            assert!(
+                factory.create_engine("helxlo").match_item(&item).is_none(),
+                "{algorithm:?}"
+            );
+            assert!(
+                factory.create_engine("helxlo~").match_item(&item).is_some(),
+                "{algorithm:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn andor_field_scopes() {
+        use super::*;
+        let factory = AndOrEngineFactory::new(ExactOrFuzzyEngineFactory::builder().build()).fields(
+            Regex::new("[/.]").unwrap(),
+            vec!["dir".into(), "name".into(), "ext".into()],
+        );
+        let engine = factory.create_engine("name:foo !ext:tmp");
+        assert!(engine.match_item(&"src/foo.rs".to_string()).is_some());
+        assert!(engine.match_item(&"src/foo.tmp".to_string()).is_none());
+        assert!(engine.match_item(&"foo/bar.rs".to_string()).is_none());
+
+        // without `fields`, scopes are part of the terms
+        let factory = AndOrEngineFactory::new(ExactOrFuzzyEngineFactory::builder().build());
+        assert_eq!(format!("{}", factory.create_engine("2:ab")), "(And: (Fuzzy: 2:ab))");
+    }
+
+    #[test]
+    fn andor_source_scopes() {
+        use super::*;
+        let factory = AndOrEngineFactory::new(ExactOrFuzzyEngineFactory::builder().build());
+        let engine = factory.create_engine("source:git rs");
+        assert_eq!(format!("{engine}"), "(And: (Source: (Fuzzy: git)), (Fuzzy: rs))");
+        let item = crate::reader::SourcedItem::new("git", Arc::new("src/lib.rs".to_string()));
+        assert!(engine.match_item(&item).is_some());
+        let item = crate::reader::SourcedItem::new("files", Arc::new("src/lib.rs".to_string()));
+        assert!(engine.match_item(&item).is_none());
+    }
 }
@@ -8,6 +8,7 @@ use crate::fuzzy_matcher::clangd::ClangdMatcher;
 #[cfg(feature = "frizbee")]
 use crate::fuzzy_matcher::frizbee::FrizbeeMatcher;
 use crate::fuzzy_matcher::fzy::FzyMatcher;
+use crate::fuzzy_matcher::segment::Segmenter;
 use crate::fuzzy_matcher::skim::SkimMatcherV2;
 
 use crate::item::RankBuilder;
@@ -51,6 +52,10 @@ pub struct FuzzyEngineBuilder {
     typos: Typos,
     /// When true, prefer the last (rightmost) occurrence on tied scores.
     last_match: bool,
+    /// Word segmenter used for boundary bonuses on non-ASCII text (Arinae only).
+    segmenter: Option<Arc<dyn Segmenter>>,
+    /// Bytes of longer ranges handed to the matcher, see [`FuzzyEngine::window`].
+    match_window: Option<usize>,
 }
 
 impl FuzzyEngineBuilder {
@@ -90,6 +95,24 @@ impl FuzzyEngineBuilder {
         self
     }
 
+    /// Word segmenter giving the word starts of non-ASCII text a boundary bonus, e.g. for CJK
+    ///
+    /// Only the Arinae algorithm uses it.
+    #[must_use]
+    pub fn segmenter(mut self, segmenter: Option<Arc<dyn Segmenter>>) -> Self {
+        self.segmenter = segmenter;
+        self
+    }
+
+    /// Only match a window of `match_window` bytes of the longer matching ranges (`--match-window`)
+    ///
+    /// See [`exceeds_window`] for the items it cuts.
+    #[must_use]
+    pub fn match_window(mut self, match_window: Option<usize>) -> Self {
+        self.match_window = match_window;
+        self
+    }
+
     /// Compute the effective `max_typos` for the given query.
     ///
     /// - `Typos::Disabled` → `None` (no typo tolerance)
@@ -142,7 +165,8 @@ impl FuzzyEngineBuilder {
                 Box::new(matcher)
             }
             FuzzyAlgorithm::Arinae => {
-                let matcher = ArinaeMatcher::new(self.case, !matches!(self.typos, Typos::Disabled), self.last_match);
+                let matcher = ArinaeMatcher::new(self.case, !matches!(self.typos, Typos::Disabled), self.last_match)
+                    .segmenter(self.segmenter);
                 debug!("Initialized Arinae algorithm");
                 Box::new(matcher)
             }
@@ -152,6 +176,7 @@ impl FuzzyEngineBuilder {
             matcher,
             query: self.query,
             rank_builder: self.rank_builder,
+            match_window: self.match_window,
         }
     }
 }
@@ -161,6 +186,7 @@ pub struct FuzzyEngine {
     query: String,
     matcher: Box<dyn FuzzyMatcher>,
     rank_builder: Arc<RankBuilder>,
+    match_window: Option<usize>,
 }
 
 impl FuzzyEngine {
@@ -171,6 +197,42 @@ impl FuzzyEngine {
     }
 }
 
+/// Whether a matching range of `item` is longer than `window`, so that `--match-window` cuts it
+pub(crate) fn exceeds_window(item: &dyn SkimItem, window: usize) -> bool {
+    let text = item.text();
+    let default_range = [(0, text.len())];
+    item.get_matching_ranges()
+        .unwrap_or(&default_range)
+        .iter()
+        .any(|&(start, end)| min(end, text.len()).saturating_sub(start) > window)
+}
+
+impl FuzzyEngine {
+    /// Part of `text[start..end]` handed to the matcher when `--match-window` caps it
+    ///
+    /// The window starts at the first occurrence of the query's first char, where any match
+    /// starts at the earliest, so only the part of the match past the window is lost.
+    fn window(&self, text: &str, start: usize, end: usize) -> (usize, usize) {
+        let Some(window) = self
+            .match_window
+            .filter(|&window| window > 0 && end.saturating_sub(start) > window)
+        else {
+            return (start, end);
+        };
+        let first = self.query.chars().next().map(|c| c.to_lowercase().collect::<String>());
+        let window_start = first
+            .and_then(|first| {
+                text[start..end]
+                    .char_indices()
+                    .find(|(_, c)| c.to_lowercase().eq(first.chars()))
+                    .map(|(i, _)| start + i)
+            })
+            .unwrap_or(start);
+        let window_end = text.floor_char_boundary((window_start + window).min(end));
+        (window_start, window_end)
+    }
+}
+
 impl MatchEngine for FuzzyEngine {
     fn match_item(&self, item: &dyn SkimItem) -> Option<MatchResult> {
         let item_text = item.text();
@@ -180,6 +242,7 @@ impl MatchEngine for FuzzyEngine {
         for &(start, end) in item.get_matching_ranges().unwrap_or(&default_range) {
             let start = min(start, item_text.len());
             let end = min(end, item_text.len());
+            let (start, end) = self.window(&item_text, start, end);
 
             let result = if self.query.is_empty() {
                 Some((0i64, vec![]))
@@ -381,4 +444,38 @@ mod tests {
         let engine = FuzzyEngine::builder().query("foo").build();
         assert_eq!(format!("{engine}"), "(Fuzzy: foo)");
     }
+
+    #[test]
+    fn match_window_starts_at_the_first_query_char() {
+        let engine = |window| {
+            FuzzyEngine::builder()
+                .query("abc")
+                .algorithm(FuzzyAlgorithm::SkimV2)
+                .match_window(window)
+                .build()
+        };
+        let item = format!("{}abc", "z".repeat(50));
+        let result = engine(Some(8)).match_item(&item).unwrap();
+        assert_eq!(result.range_char_indices(&item), vec![50, 51, 52]);
+
+        // `c` is past the window starting at `a`
+        let item = format!("a{}bc", "x".repeat(20));
+        assert!(engine(Some(8)).match_item(&item).is_none());
+        assert!(engine(None).match_item(&item).is_some());
+    }
+
+    #[test]
+    fn exceeds_window_counts_the_matching_ranges() {
+        let item = RangedItem {
+            text: format!("{} short", "z".repeat(50)),
+            ranges: vec![(51, 56)],
+        };
+        assert!(!exceeds_window(&item, 8));
+        assert!(exceeds_window(&item.text, 8));
+        let item = RangedItem {
+            ranges: vec![(0, 5), (0, 9)],
+            ..item
+        };
+        assert!(exceeds_window(&item, 8));
+    }
 }
@@ -1,9 +1,86 @@
-pub mod all;
-pub mod andor;
-pub mod exact;
-pub mod factory;
-pub mod fuzzy;
-pub mod normalized;
-pub mod regexp;
-pub mod split;
+//! Match engines, and [`rank_lines`] to run them without the TUI
+//!
+//! [`rank_lines`] is what filter mode does, packaged for library users such as editor
+//! plugins: no reader, matcher thread, `App` or terminal is involved.
+
+pub(crate) mod all;
+pub(crate) mod andor;
+pub(crate) mod exact;
+pub(crate) mod factory;
+pub(crate) mod fuzzy;
+pub(crate) mod normalized;
+pub mod query;
+pub(crate) mod regexp;
+pub(crate) mod scoped;
+#[cfg(feature = "script")]
+pub(crate) mod scripted;
+pub(crate) mod split;
 mod util;
+
+use crate::field::FieldRange;
+use crate::helper::item::DefaultSkimItem;
+use crate::matcher::Matcher;
+use crate::{Rank, SkimItem as _, SkimOptions};
+
+/// A line matched by [`rank_lines`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RankedLine {
+    /// Position of the line in the input
+    pub index: usize,
+    /// Raw match measurements, `rank.score` being the match score
+    pub rank: Rank,
+    /// Char indices of the matched characters, sorted
+    pub positions: Vec<usize>,
+}
+
+/// Match `lines` against `query` and return the matches, best first
+///
+/// The engine is built from `options` as in the finder: `algorithm`, `case`, `exact`, `regex`,
+/// `typos`, `normalize`, `split_match` and the extended query syntax apply, the matches are
+/// sorted by `tiebreak` unless `no_sort` is set, and `nth` restricts the matched fields. With
+/// `ansi`, escape codes are stripped and the positions refer to the stripped line.
+///
+/// Everything runs on the calling thread. Match positions are always computed, even if
+/// `options.filter` is set.
+///
+/// ```
+/// use skim::engine::rank_lines;
+/// use skim::SkimOptions;
+///
+/// let ranked = rank_lines(["src/main.rs", "README.md", "src/lib.rs"], "srs", &SkimOptions::default());
+/// let indices: Vec<_> = ranked.iter().map(|r| r.index).collect();
+/// assert_eq!(indices.len(), 2);
+/// assert!(indices.contains(&0) && indices.contains(&2));
+/// ```
+pub fn rank_lines<I>(lines: I, query: &str, options: &SkimOptions) -> Vec<RankedLine>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let (factory, rank_builder) = Matcher::engine_factory_for(options, false);
+    let engine = factory.create_engine_with_case(query, options.case);
+    let matching_fields: Vec<FieldRange> = options.nth.iter().filter_map(|f| FieldRange::from_str(f)).collect();
+
+    let mut matched = Vec::new();
+    for (index, line) in lines.into_iter().enumerate() {
+        let item = DefaultSkimItem::new(line.as_ref(), options.ansi, &[], &matching_fields, &options.delimiter);
+        let Some(mut result) = engine.match_item(&item) else {
+            continue;
+        };
+        result.apply_external_ranges(&item);
+        let mut positions = result.range_char_indices(&item.text());
+        positions.sort_unstable();
+        let mut rank = result.rank;
+        rank.index = i32::try_from(index).unwrap_or(i32::MAX);
+        matched.push((rank_builder.sort_key(&rank), RankedLine { index, rank, positions }));
+    }
+    if !options.no_sort {
+        // Stable: ties keep the input order, as `MatchedItem`'s ordering does
+        matched.sort_by_key(|(key, _)| *key);
+    }
+    matched.into_iter().map(|(_, line)| line).collect()
+}
+
+#[cfg(test)]
+#[path = "mod_tests.rs"]
+mod tests;
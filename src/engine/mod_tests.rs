@@ -0,0 +1,77 @@
+use super::*;
+use crate::prelude::SkimOptionsBuilder;
+
+#[test]
+fn rank_lines_sorts_the_matches_and_reports_positions() {
+    let lines = ["axbxc", "abc", "nothing", "zabc"];
+    let ranked = rank_lines(lines, "abc", &SkimOptions::default());
+    let indices: Vec<_> = ranked.iter().map(|r| r.index).collect();
+    assert_eq!(indices, vec![1, 3, 0]);
+    assert_eq!(ranked[0].positions, vec![0, 1, 2]);
+    assert_eq!(ranked[1].positions, vec![1, 2, 3]);
+    assert_eq!(ranked[2].positions, vec![0, 2, 4]);
+    assert!(ranked[0].rank.score >= ranked[2].rank.score);
+}
+
+#[test]
+fn rank_lines_follows_the_options() {
+    let lines = ["b foo", "a foo", "a bar"];
+    let options = SkimOptionsBuilder::default()
+        .no_sort(true)
+        .nth(vec!["1".to_string()])
+        .build()
+        .unwrap();
+    let ranked = rank_lines(lines, "a", &options);
+    assert_eq!(ranked.iter().map(|r| r.index).collect::<Vec<_>>(), vec![1, 2]);
+
+    let options = SkimOptionsBuilder::default()
+        .exact(true)
+        .filter(String::new())
+        .build()
+        .unwrap();
+    let ranked = rank_lines(["f-o-o", "xfoo"], "foo", &options);
+    assert_eq!(ranked.len(), 1);
+    assert_eq!(ranked[0].positions, vec![1, 2, 3]);
+}
+
+/// Matches the items ending with the query
+struct SuffixEngine(String);
+
+impl std::fmt::Display for SuffixEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "(Suffix: {})", self.0)
+    }
+}
+
+impl crate::MatchEngine for SuffixEngine {
+    fn match_item(&self, item: &dyn crate::SkimItem) -> Option<crate::MatchResult> {
+        let text = item.text();
+        let begin = text.strip_suffix(self.0.as_str())?.len();
+        Some(crate::MatchResult {
+            rank: Rank {
+                score: i32::try_from(begin).unwrap_or(i32::MAX),
+                ..Rank::default()
+            },
+            matched_range: crate::MatchRange::ByteRange(begin, text.len()),
+        })
+    }
+}
+
+struct SuffixFactory;
+
+impl crate::MatchEngineFactory for SuffixFactory {
+    fn create_engine_with_case(&self, query: &str, _case: crate::CaseMatching) -> Box<dyn crate::MatchEngine> {
+        Box::new(SuffixEngine(query.to_string()))
+    }
+}
+
+#[test]
+fn rank_lines_uses_the_custom_engine_factory() {
+    let options = SkimOptionsBuilder::default()
+        .engine_factory(std::rc::Rc::new(SuffixFactory))
+        .build()
+        .unwrap();
+    let ranked = rank_lines(["a.rs", "rs.md", "main.rs"], ".rs", &options);
+    assert_eq!(ranked.iter().map(|r| r.index).collect::<Vec<_>>(), vec![2, 0]);
+    assert_eq!(ranked[0].positions, vec![4, 5, 6]);
+}
@@ -1,13 +1,16 @@
 //! Normalized match engine for matching with Unicode normalization (removing diacritics).
 //!
 //! This engine wraps another engine and normalizes both the query and item text before matching,
-//! then maps the results back to the original text.
+//! then maps the results back to the original text. Normalization folds the accented Latin
+//! letters to their base letter (`é` to `e`, `ø` to `o`) with the table of
+//! [`fold_char`](crate::engine::util::fold_char), so every matching algorithm and the exact
+//! engine see the folded text while the displayed text is untouched.
 
 use std::borrow::Cow;
 use std::fmt::{Display, Error, Formatter};
 
 use crate::engine::util::{
-    map_byte_range_to_original, map_char_indices_to_original, normalize_with_byte_mapping, normalize_with_char_mapping,
+    map_byte_range_to_original, map_byte_to_normalized, needs_folding, normalize, normalize_with_byte_mapping,
 };
 use crate::{CaseMatching, MatchEngine, MatchEngineFactory, MatchRange, MatchResult, SkimItem};
 
@@ -27,34 +30,36 @@ impl NormalizedEngine {
 impl MatchEngine for NormalizedEngine {
     fn match_item(&self, item: &dyn SkimItem) -> Option<MatchResult> {
         let item_text = item.text();
+        if !needs_folding(&item_text) {
+            return self.inner.match_item(item);
+        }
 
-        // Normalize the item text
-        let (normalized_text, char_mapping) = normalize_with_char_mapping(&item_text);
-        let (_, byte_mapping) = normalize_with_byte_mapping(&item_text);
-
-        // Create a wrapper item with normalized text
-        let normalized_item: &dyn SkimItem = &NormalizedItem(normalized_text);
+        // Normalize the item text, keeping its matching ranges (`--nth`) on the same chars
+        let (normalized_text, byte_mapping) = normalize_with_byte_mapping(&item_text);
+        let ranges = item.get_matching_ranges().map(|ranges| {
+            ranges
+                .iter()
+                .map(|&(start, end)| {
+                    (
+                        map_byte_to_normalized(start, &byte_mapping),
+                        map_byte_to_normalized(end, &byte_mapping),
+                    )
+                })
+                .collect()
+        });
+        let normalized_item = NormalizedItem {
+            text: normalized_text,
+            ranges,
+        };
 
         // Match using the inner engine
-        let mut result = self.inner.match_item(normalized_item)?;
-
-        // Map the matched range back to the original text
-        result.matched_range = match result.matched_range {
-            MatchRange::Chars(indices) => MatchRange::Chars(map_char_indices_to_original(&indices, &char_mapping)),
-            MatchRange::CharRange(start, end) => {
-                let orig_start = char_mapping.get(start).copied().unwrap_or(start);
-                let orig_end = if end > 0 {
-                    char_mapping.get(end - 1).copied().map_or(end, |e| e + 1)
-                } else {
-                    0
-                };
-                MatchRange::CharRange(orig_start, orig_end)
-            }
-            MatchRange::ByteRange(start, end) => {
-                let (orig_start, orig_end) = map_byte_range_to_original(start, end, &byte_mapping, &item_text);
-                MatchRange::ByteRange(orig_start, orig_end)
-            }
-        };
+        let mut result = self.inner.match_item(&normalized_item)?;
+
+        // Chars fold one to one, only the byte ranges need mapping back to the original text
+        if let MatchRange::ByteRange(start, end) = result.matched_range {
+            let (orig_start, orig_end) = map_byte_range_to_original(start, end, &byte_mapping, &item_text);
+            result.matched_range = MatchRange::ByteRange(orig_start, orig_end);
+        }
 
         Some(result)
     }
@@ -66,12 +71,19 @@ impl Display for NormalizedEngine {
     }
 }
 
-/// Simple string wrapper implementing `SkimItem` for normalized matching
-struct NormalizedItem(String);
+/// The normalized text of an item, with its matching ranges mapped to it
+struct NormalizedItem {
+    text: String,
+    ranges: Option<Vec<(usize, usize)>>,
+}
 
 impl SkimItem for NormalizedItem {
     fn text(&self) -> Cow<'_, str> {
-        Cow::Borrowed(&self.0)
+        Cow::Borrowed(&self.text)
+    }
+
+    fn get_matching_ranges(&self) -> Option<&[(usize, usize)]> {
+        self.ranges.as_deref()
     }
 }
 
@@ -93,7 +105,7 @@ impl NormalizedEngineFactory {
 impl MatchEngineFactory for NormalizedEngineFactory {
     fn create_engine_with_case(&self, query: &str, case: CaseMatching) -> Box<dyn MatchEngine> {
         // Normalize the query
-        let (normalized_query, _) = normalize_with_char_mapping(query);
+        let normalized_query = normalize(query);
 
         // Create the inner engine with the normalized query
         let inner_engine = self.inner.create_engine_with_case(&normalized_query, case);
@@ -203,4 +215,39 @@ mod tests {
         let result = engine.match_item(&"café".to_string()).unwrap();
         assert_eq!(result.matched_range, MatchRange::Chars(vec![0, 2]));
     }
+
+    /// An item restricted to its second comma-separated field, as with `--nth 2`
+    struct NthItem {
+        text: &'static str,
+        ranges: [(usize, usize); 1],
+    }
+
+    impl NthItem {
+        fn new(text: &'static str) -> Self {
+            let start = text.find(',').unwrap() + 1;
+            Self {
+                text,
+                ranges: [(start, text.len())],
+            }
+        }
+    }
+
+    impl SkimItem for NthItem {
+        fn text(&self) -> Cow<'_, str> {
+            Cow::Borrowed(self.text)
+        }
+
+        fn get_matching_ranges(&self) -> Option<&[(usize, usize)]> {
+            Some(&self.ranges)
+        }
+    }
+
+    #[test]
+    fn matching_ranges_follow_the_normalized_text() {
+        let inner = Box::new(ExactEngine::builder("cafe", ExactMatchingParam::default()).build());
+        let engine = NormalizedEngine::new(inner);
+        let result = engine.match_item(&NthItem::new("thé,café")).unwrap();
+        assert_eq!(result.matched_range, MatchRange::ByteRange(5, 10));
+        assert!(engine.match_item(&NthItem::new("café,thé")).is_none());
+    }
 }
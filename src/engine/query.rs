@@ -0,0 +1,263 @@
+//! Parser for the extended query syntax
+//!
+//! A query is a list of space-separated groups that must all match. The terms of a group are
+//! separated by `|`, any of them matching being enough. `\ ` is a literal space.
+//!
+//! | Term       | Matches                                  |
+//! | ---------- | ---------------------------------------- |
+//! | `abc`      | fuzzy `abc` (exact with `--exact`)       |
+//! | `abc~`     | fuzzy `abc`, tolerating typos            |
+//! | `'abc`     | exact `abc` (fuzzy with `--exact`)       |
+//! | `'abc'`    | exact `abc` between word boundaries      |
+//! | `^abc`     | items starting with `abc`                |
+//! | `abc$`     | items ending with `abc`                  |
+//! | `^abc$`    | items equal to `abc`                     |
+//! | `!abc`     | items not containing `abc`               |
+//! | `!'abc`    | items not fuzzy-matching `abc`           |
+//! | `!^abc`    | items not starting with `abc`            |
+//! | `!'abc'`   | items without the word `abc`             |
+//! | `FIELD:abc`| `abc` within a field                     |
+//! | `source:abc`| items read from a source labeled `abc`  |
+//!
+//! `FIELD` is a field expression as in `--nth` (`2`, `-1`, `3..`) or one of the names given with
+//! `--field-names`. It comes after the `!` and `'` operators and before `^`: `!ext:tmp`,
+//! `name:^src`. A `FIELD:` that is not a field expression nor a known name is part of the term.
+//! `source:` matches the label of the items read through `Reader::add_source`, and is part of the
+//! term for the other items.
+//!
+//! [`Query::parse`] returns the whole tree; the engine factories use [`split`] and
+//! [`Term::parse`], since the terms go through the inner factories as strings.
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+use crate::field::FieldRange;
+
+static RE_OR_WITH_SPACES: LazyLock<Regex> = LazyLock::new(|| Regex::new(r" *\|+ *").unwrap());
+
+/// How a term matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TermKind {
+    /// Fuzzy match
+    Fuzzy,
+    /// Exact substring
+    Exact,
+    /// Exact, at the start of the item
+    Prefix,
+    /// Exact, at the end of the item
+    Suffix,
+    /// Exact, the whole item
+    Equal,
+    /// Exact, delimited by word boundaries
+    Boundary,
+}
+
+/// A single term of a query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Term {
+    /// Text to match, without the operators
+    pub text: String,
+    /// How `text` matches
+    pub kind: TermKind,
+    /// Whether the term selects the items that do not match
+    pub inverse: bool,
+    /// Field the term is restricted to
+    pub field: Option<FieldRange>,
+    /// Whether the term tolerates typos even without `--typos`, only for fuzzy terms
+    pub typos: bool,
+}
+
+impl Term {
+    /// Parse the `'`, `!`, `^` and `$` operators of a single term
+    ///
+    /// `exact_mode` is the `--exact` flag, which `'` toggles. A term quoted on both sides,
+    /// `'abc'`, is an exact-boundary term. A trailing `~` on a fuzzy term enables typos for
+    /// it. Field scopes are not handled here, see [`Query::parse`].
+    #[must_use]
+    pub fn parse(term: &str, exact_mode: bool) -> Self {
+        let mut text = term;
+        let mut exact = exact_mode;
+        let mut inverse = false;
+
+        if let Some(rest) = strip_quotes(text) {
+            return Self::boundary(rest, false);
+        }
+        if let Some(rest) = text.strip_prefix('\'') {
+            exact = !exact;
+            text = rest;
+        }
+
+        if let Some(rest) = text.strip_prefix('!') {
+            inverse = true;
+            exact = true;
+            text = rest;
+            if let Some(rest) = strip_quotes(text) {
+                return Self::boundary(rest, true);
+            }
+            // as in fzf, `!'abc` excludes the fuzzy matches of `abc`
+            if let Some(rest) = text.strip_prefix('\'') {
+                exact = false;
+                text = rest;
+            }
+        }
+
+        // a lone `!` or `'` matches everything, the anchors are not looked for
+        let (prefix, suffix) = if text.is_empty() {
+            (false, false)
+        } else {
+            let prefix = text.strip_prefix('^').inspect(|rest| text = rest).is_some();
+            let suffix = text.strip_suffix('$').inspect(|rest| text = rest).is_some();
+            (prefix, suffix)
+        };
+
+        let mut typos = false;
+        let kind = match (prefix, suffix) {
+            (true, true) => TermKind::Equal,
+            (true, false) => TermKind::Prefix,
+            (false, true) => TermKind::Suffix,
+            (false, false) if exact => TermKind::Exact,
+            (false, false) => {
+                if let Some(rest) = text.strip_suffix('~')
+                    && !rest.is_empty()
+                {
+                    typos = true;
+                    text = rest;
+                }
+                TermKind::Fuzzy
+            }
+        };
+
+        Self {
+            text: text.to_string(),
+            kind,
+            inverse,
+            field: None,
+            typos,
+        }
+    }
+
+    fn boundary(text: &str, inverse: bool) -> Self {
+        Self {
+            text: text.to_string(),
+            kind: TermKind::Boundary,
+            inverse,
+            field: None,
+            typos: false,
+        }
+    }
+}
+
+/// `abc` for `'abc'`, the quotes enclosing at least one character
+fn strip_quotes(term: &str) -> Option<&str> {
+    term.strip_prefix('\'')?
+        .strip_suffix('\'')
+        .filter(|text| !text.is_empty())
+}
+
+/// A parsed query: every group must match, and within a group, any term
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    /// The space-separated groups, each holding its `|`-separated terms
+    pub groups: Vec<Vec<Term>>,
+}
+
+impl Query {
+    /// Parse `query`, resolving the `FIELD:` scopes against `field_names`
+    ///
+    /// `exact_mode` is the `--exact` flag.
+    ///
+    /// ```
+    /// use skim::engine::query::{Query, TermKind};
+    ///
+    /// let names = ["name".to_string(), "ext".to_string()];
+    /// let query = Query::parse("name:foo !ext:tmp ^src/ | ^lib/", false, &names);
+    /// assert_eq!(query.groups.len(), 3);
+    /// assert_eq!(query.groups[1][0].text, "tmp");
+    /// assert!(query.groups[1][0].inverse);
+    /// assert_eq!(query.groups[2][1].kind, TermKind::Prefix);
+    /// ```
+    #[must_use]
+    pub fn parse(query: &str, exact_mode: bool, field_names: &[String]) -> Self {
+        let groups = split(query, Some(field_names))
+            .into_iter()
+            .map(|group| {
+                group
+                    .into_iter()
+                    .map(|(field, term)| Term {
+                        field,
+                        ..Term::parse(&term, exact_mode)
+                    })
+                    .collect()
+            })
+            .collect();
+        Self { groups }
+    }
+}
+
+/// Split `query` into groups of terms, each term keeping its operators
+///
+/// With `field_names`, the `FIELD:` scopes are taken off the terms and returned alongside them;
+/// without, they are left in the terms.
+pub(crate) fn split(query: &str, field_names: Option<&[String]>) -> Vec<Vec<(Option<FieldRange>, String)>> {
+    RE_OR_WITH_SPACES
+        .replace_all(&mask_escape_space(query), "|")
+        .split(' ')
+        .filter_map(|group| {
+            let terms: Vec<_> = group
+                .split('|')
+                .filter(|term| !term.is_empty())
+                .map(|term| {
+                    let term = unmask_escape_space(term);
+                    match field_names {
+                        Some(names) => take_scope(&term, names),
+                        None => (None, term),
+                    }
+                })
+                .collect();
+            (!terms.is_empty()).then_some(terms)
+        })
+        .collect()
+}
+
+/// Take the `FIELD:` scope off `term`, keeping the `!` and `'` operators in front of it
+fn take_scope(term: &str, field_names: &[String]) -> (Option<FieldRange>, String) {
+    let rest = term.trim_start_matches(['!', '\'']);
+    let operators = &term[..term.len() - rest.len()];
+    if let Some((scope, text)) = rest.split_once(':')
+        && !scope.is_empty()
+        && !text.is_empty()
+        && let Some(field) = resolve_field(scope, field_names)
+    {
+        return (Some(field), format!("{operators}{text}"));
+    }
+    (None, term.to_string())
+}
+
+/// The term matched against the source of the items for a `source:` term, keeping its operators
+pub(crate) fn source_term(term: &str) -> Option<String> {
+    let rest = term.trim_start_matches(['!', '\'']);
+    let operators = &term[..term.len() - rest.len()];
+    let text = rest.strip_prefix("source:")?;
+    (!text.is_empty()).then(|| format!("{operators}{text}"))
+}
+
+/// A field name, or else a field expression
+fn resolve_field(scope: &str, field_names: &[String]) -> Option<FieldRange> {
+    field_names
+        .iter()
+        .position(|name| name == scope)
+        .map(|index| FieldRange::Single(i32::try_from(index + 1).unwrap_or(i32::MAX)))
+        .or_else(|| FieldRange::from_str(scope))
+}
+
+fn mask_escape_space(string: &str) -> String {
+    string.replace("\\ ", "\0")
+}
+
+fn unmask_escape_space(string: &str) -> String {
+    string.replace('\0', " ")
+}
+
+#[cfg(test)]
+#[path = "query_tests.rs"]
+mod tests;
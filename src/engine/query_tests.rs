@@ -0,0 +1,168 @@
+use super::*;
+
+fn term(text: &str, kind: TermKind, inverse: bool) -> Term {
+    Term {
+        text: text.to_string(),
+        kind,
+        inverse,
+        field: None,
+        typos: false,
+    }
+}
+
+fn names(names: &[&str]) -> Vec<String> {
+    names.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn term_operators() {
+    assert_eq!(Term::parse("abc", false), term("abc", TermKind::Fuzzy, false));
+    assert_eq!(Term::parse("'abc", false), term("abc", TermKind::Exact, false));
+    assert_eq!(Term::parse("^abc", false), term("abc", TermKind::Prefix, false));
+    assert_eq!(Term::parse("abc$", false), term("abc", TermKind::Suffix, false));
+    assert_eq!(Term::parse("^abc$", false), term("abc", TermKind::Equal, false));
+    assert_eq!(Term::parse("!abc", false), term("abc", TermKind::Exact, true));
+    assert_eq!(Term::parse("!^abc", false), term("abc", TermKind::Prefix, true));
+    assert_eq!(Term::parse("!abc$", false), term("abc", TermKind::Suffix, true));
+    assert_eq!(Term::parse("!^abc$", false), term("abc", TermKind::Equal, true));
+}
+
+#[test]
+fn quote_toggles_exact_mode() {
+    assert_eq!(Term::parse("abc", true), term("abc", TermKind::Exact, false));
+    assert_eq!(Term::parse("'abc", true), term("abc", TermKind::Fuzzy, false));
+}
+
+#[test]
+fn inverse_fuzzy_term() {
+    assert_eq!(Term::parse("!'abc", false), term("abc", TermKind::Fuzzy, true));
+    assert_eq!(Term::parse("!'abc", true), term("abc", TermKind::Fuzzy, true));
+    // the quote before the `!` keeps the historical inverse exact match
+    assert_eq!(Term::parse("'!abc", false), term("abc", TermKind::Exact, true));
+}
+
+#[test]
+fn lone_operators_are_empty_terms() {
+    assert_eq!(Term::parse("!", false), term("", TermKind::Exact, true));
+    assert_eq!(Term::parse("'", false), term("", TermKind::Exact, false));
+    assert_eq!(Term::parse("^", false), term("", TermKind::Prefix, false));
+    assert_eq!(Term::parse("$", false), term("", TermKind::Suffix, false));
+}
+
+#[test]
+fn groups_and_alternatives() {
+    let query = Query::parse("'abc | def ^gh ij|kl  mn", false, &[]);
+    assert_eq!(
+        query.groups,
+        vec![
+            vec![term("abc", TermKind::Exact, false), term("def", TermKind::Fuzzy, false)],
+            vec![term("gh", TermKind::Prefix, false)],
+            vec![term("ij", TermKind::Fuzzy, false), term("kl", TermKind::Fuzzy, false)],
+            vec![term("mn", TermKind::Fuzzy, false)],
+        ]
+    );
+}
+
+#[test]
+fn escaped_spaces_stay_in_the_term() {
+    let query = Query::parse(r"foo\ bar baz", false, &[]);
+    assert_eq!(query.groups.len(), 2);
+    assert_eq!(query.groups[0][0].text, "foo bar");
+}
+
+#[test]
+fn empty_terms_and_groups_are_dropped() {
+    assert_eq!(Query::parse("", false, &[]), Query::default());
+    assert_eq!(Query::parse("  | ", false, &[]), Query::default());
+    assert_eq!(
+        Query::parse("|abc", false, &[]).groups,
+        vec![vec![term("abc", TermKind::Fuzzy, false)]]
+    );
+}
+
+#[test]
+fn field_scopes() {
+    let query = Query::parse("name:foo !ext:tmp ^src/ | ^lib/", false, &names(&["name", "ext"]));
+    assert_eq!(
+        query.groups,
+        vec![
+            vec![Term {
+                field: Some(FieldRange::Single(1)),
+                ..term("foo", TermKind::Fuzzy, false)
+            }],
+            vec![Term {
+                field: Some(FieldRange::Single(2)),
+                ..term("tmp", TermKind::Exact, true)
+            }],
+            vec![
+                term("src/", TermKind::Prefix, false),
+                term("lib/", TermKind::Prefix, false)
+            ],
+        ]
+    );
+}
+
+#[test]
+fn field_expression_scopes() {
+    let query = Query::parse("2:foo -1:^bar '2..:baz", false, &[]);
+    let fields: Vec<_> = query.groups.iter().map(|group| group[0].field.clone()).collect();
+    assert_eq!(
+        fields,
+        vec![
+            Some(FieldRange::Single(2)),
+            Some(FieldRange::Single(-1)),
+            Some(FieldRange::RightInf(2))
+        ]
+    );
+    assert_eq!(query.groups[1][0].kind, TermKind::Prefix);
+    assert_eq!(query.groups[2][0].kind, TermKind::Exact);
+}
+
+#[test]
+fn unknown_scopes_are_part_of_the_term() {
+    let query = Query::parse("foo:bar :baz 2: http://x", false, &names(&["name"]));
+    let texts: Vec<_> = query.groups.iter().map(|group| group[0].text.as_str()).collect();
+    assert_eq!(texts, vec!["foo:bar", ":baz", "2:", "http://x"]);
+    assert!(query.groups.iter().all(|group| group[0].field.is_none()));
+}
+
+#[test]
+fn split_without_field_names_keeps_the_scopes() {
+    assert_eq!(split("!2:foo", None), vec![vec![(None, "!2:foo".to_string())]]);
+    assert_eq!(
+        split("!2:foo", Some(&[])),
+        vec![vec![(Some(FieldRange::Single(2)), "!foo".to_string())]]
+    );
+}
+
+#[test]
+fn quoted_terms_match_at_word_boundaries() {
+    assert_eq!(Term::parse("'abc'", false), term("abc", TermKind::Boundary, false));
+    assert_eq!(Term::parse("'abc'", true), term("abc", TermKind::Boundary, false));
+    assert_eq!(Term::parse("!'abc'", false), term("abc", TermKind::Boundary, true));
+    // a lone pair of quotes is an exact `'`
+    assert_eq!(Term::parse("''", false), term("'", TermKind::Exact, false));
+}
+
+#[test]
+fn tilde_enables_typos_on_fuzzy_terms() {
+    let typos = |text, inverse| Term {
+        typos: true,
+        ..term(text, TermKind::Fuzzy, inverse)
+    };
+    assert_eq!(Term::parse("abc~", false), typos("abc", false));
+    assert_eq!(Term::parse("!'abc~", false), typos("abc", true));
+    // exact and anchored terms keep the `~`
+    assert_eq!(Term::parse("'abc~", false), term("abc~", TermKind::Exact, false));
+    assert_eq!(Term::parse("^abc~", false), term("abc~", TermKind::Prefix, false));
+    assert_eq!(Term::parse("abc~", true), term("abc~", TermKind::Exact, false));
+    assert_eq!(Term::parse("~", false), term("~", TermKind::Fuzzy, false));
+}
+
+#[test]
+fn source_terms_keep_their_operators() {
+    assert_eq!(source_term("source:git").as_deref(), Some("git"));
+    assert_eq!(source_term("!'source:^git").as_deref(), Some("!'^git"));
+    assert_eq!(source_term("source:"), None);
+    assert_eq!(source_term("git"), None);
+}
@@ -0,0 +1,182 @@
+//! Scoped engines, matching another engine against a single field of the items or their source
+//!
+//! Built by `AndOrEngineFactory` for the `FIELD:term` and `source:term` terms of the query.
+
+use regex::Regex;
+use std::borrow::Cow;
+use std::fmt::{Display, Error, Formatter};
+
+use crate::field::{FieldRange, parse_matching_fields};
+use crate::{MatchEngine, MatchRange, MatchResult, SkimItem};
+
+/// Engine restricting another engine to some fields of the items
+pub struct FieldScopedEngine {
+    engine: Box<dyn MatchEngine>,
+    fields: Vec<FieldRange>,
+    delimiter: Regex,
+}
+
+impl FieldScopedEngine {
+    /// Creates an engine matching `engine` against `field`, fields being split at `delimiter`
+    pub fn new(engine: Box<dyn MatchEngine>, field: FieldRange, delimiter: Regex) -> Self {
+        Self::with_fields(engine, vec![field], delimiter)
+    }
+
+    /// Creates an engine matching `engine` against `fields`, or the whole text without any
+    ///
+    /// Used by `toggle-nth`, the scope replacing the one the items were read with.
+    pub fn with_fields(engine: Box<dyn MatchEngine>, fields: Vec<FieldRange>, delimiter: Regex) -> Self {
+        Self {
+            engine,
+            fields,
+            delimiter,
+        }
+    }
+}
+
+impl MatchEngine for FieldScopedEngine {
+    fn match_item(&self, item: &dyn SkimItem) -> Option<MatchResult> {
+        let text = item.text();
+        let ranges = if self.fields.is_empty() {
+            vec![(0, text.len())]
+        } else {
+            let ranges = parse_matching_fields(&self.delimiter, &text, &self.fields);
+            if ranges.is_empty() {
+                // a missing field is empty: nothing matches in it, and inverse terms match
+                vec![(text.len(), text.len())]
+            } else {
+                ranges
+            }
+        };
+        let scoped = ScopedItem {
+            text: text.into_owned(),
+            ranges,
+        };
+        self.engine.match_item(&scoped)
+    }
+}
+
+impl Display for FieldScopedEngine {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "(Field {:?}: {})", self.fields, self.engine)
+    }
+}
+
+/// Engine matching another engine against the source label of the items
+///
+/// Items without a source, i.e. not read through `Reader::add_source`, are matched against the
+/// whole term instead, `source:` included.
+pub struct SourceScopedEngine {
+    engine: Box<dyn MatchEngine>,
+    unscoped: Box<dyn MatchEngine>,
+}
+
+impl SourceScopedEngine {
+    /// Creates an engine matching `engine` against the source of the items, and `unscoped`
+    /// against the items without one
+    pub fn new(engine: Box<dyn MatchEngine>, unscoped: Box<dyn MatchEngine>) -> Self {
+        Self { engine, unscoped }
+    }
+}
+
+impl MatchEngine for SourceScopedEngine {
+    fn match_item(&self, item: &dyn SkimItem) -> Option<MatchResult> {
+        let Some(source) = item.source() else {
+            return self.unscoped.match_item(item);
+        };
+        let result = self.engine.match_item(&source.into_owned())?;
+        // the match is in the label, which is not part of the text
+        Some(MatchResult {
+            rank: result.rank,
+            matched_range: MatchRange::Chars(Vec::new()),
+        })
+    }
+}
+
+impl Display for SourceScopedEngine {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "(Source: {})", self.engine)
+    }
+}
+
+/// The item text with the scoped fields as its only matching ranges, so that the match positions
+/// still refer to the whole text
+struct ScopedItem {
+    text: String,
+    ranges: Vec<(usize, usize)>,
+}
+
+impl SkimItem for ScopedItem {
+    fn text(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.text)
+    }
+
+    fn get_matching_ranges(&self) -> Option<&[(usize, usize)]> {
+        Some(&self.ranges)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use super::*;
+    use crate::engine::exact::{ExactEngine, ExactMatchingParam};
+    use std::sync::Arc;
+
+    fn scoped(query: &str, inverse: bool, field: FieldRange) -> FieldScopedEngine {
+        let mut param = ExactMatchingParam::default();
+        param.inverse = inverse;
+        let engine = Box::new(ExactEngine::builder(query, param).build());
+        FieldScopedEngine::new(engine, field, Regex::new(",").unwrap())
+    }
+
+    #[test]
+    fn matches_within_the_field_only() {
+        let engine = scoped("foo", false, FieldRange::Single(2));
+        let result = engine.match_item(&"bar,foo,baz".to_string()).unwrap();
+        assert_eq!(result.matched_range, MatchRange::ByteRange(4, 7));
+        assert!(engine.match_item(&"foo,bar,baz".to_string()).is_none());
+    }
+
+    #[test]
+    fn source_scope_matches_the_label_of_sourced_items() {
+        let exact = |query: &str, inverse: bool| {
+            let mut param = ExactMatchingParam::default();
+            param.inverse = inverse;
+            Box::new(ExactEngine::builder(query, param).build()) as Box<dyn MatchEngine>
+        };
+        let engine = SourceScopedEngine::new(exact("git", false), exact("source:git", false));
+        let item = |label: &str| crate::reader::SourcedItem::new(label, Arc::new("src/lib.rs".to_string()));
+        let result = engine.match_item(&item("git")).unwrap();
+        assert_eq!(result.matched_range, MatchRange::Chars(Vec::new()));
+        assert!(engine.match_item(&item("files")).is_none());
+        // items without a source match the term as it is
+        assert!(engine.match_item(&"source:git".to_string()).is_some());
+        assert!(engine.match_item(&"git".to_string()).is_none());
+
+        let engine = SourceScopedEngine::new(exact("git", true), exact("!source:git", true));
+        assert!(engine.match_item(&item("files")).is_some());
+        assert!(engine.match_item(&item("git")).is_none());
+    }
+
+    #[test]
+    fn matches_within_any_of_the_fields_or_the_whole_text() {
+        let exact = |query: &str| Box::new(ExactEngine::builder(query, ExactMatchingParam::default()).build());
+        let comma = || Regex::new(",").unwrap();
+        let fields = vec![FieldRange::Single(1), FieldRange::Single(3)];
+        let engine = FieldScopedEngine::with_fields(exact("baz"), fields.clone(), comma());
+        assert!(engine.match_item(&"bar,foo,baz".to_string()).is_some());
+        let engine = FieldScopedEngine::with_fields(exact("foo"), fields, comma());
+        assert!(engine.match_item(&"bar,foo,baz".to_string()).is_none());
+
+        let engine = FieldScopedEngine::with_fields(exact("o,b"), Vec::new(), comma());
+        assert!(engine.match_item(&"bar,foo,baz".to_string()).is_some());
+    }
+
+    #[test]
+    fn missing_field_only_matches_inverse_terms() {
+        let item = "foo".to_string();
+        assert!(scoped("foo", false, FieldRange::Single(3)).match_item(&item).is_none());
+        assert!(scoped("foo", true, FieldRange::Single(3)).match_item(&item).is_some());
+    }
+}
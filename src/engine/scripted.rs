@@ -0,0 +1,74 @@
+//! Match engine adjusting the scores of another engine with a Rhai script (`--rank-hook`)
+
+use std::fmt::{Display, Error, Formatter};
+use std::rc::Rc;
+
+use crate::script::Script;
+use crate::{CaseMatching, MatchEngine, MatchEngineFactory, MatchResult, SkimItem};
+
+/// Engine running the rank hook on the matches of its inner engine
+pub struct ScriptedEngine {
+    inner: Box<dyn MatchEngine>,
+    query: String,
+    script: Script,
+}
+
+impl MatchEngine for ScriptedEngine {
+    fn match_item(&self, item: &dyn SkimItem) -> Option<MatchResult> {
+        let mut result = self.inner.match_item(item)?;
+        if let Some(score) = self.script.rank(&item.text(), &self.query, result.rank.score) {
+            result.rank.score = score;
+        }
+        Some(result)
+    }
+}
+
+impl Display for ScriptedEngine {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "(Scripted: {})", self.inner)
+    }
+}
+
+/// Factory wrapping the engines of another factory in a [`ScriptedEngine`]
+pub struct ScriptedEngineFactory {
+    inner: Rc<dyn MatchEngineFactory>,
+    script: Script,
+}
+
+impl ScriptedEngineFactory {
+    /// Creates a factory adjusting the scores of `inner` with `script`
+    pub fn new(inner: Rc<dyn MatchEngineFactory>, script: Script) -> Self {
+        Self { inner, script }
+    }
+}
+
+impl MatchEngineFactory for ScriptedEngineFactory {
+    fn create_engine_with_case(&self, query: &str, case: CaseMatching) -> Box<dyn MatchEngine> {
+        Box::new(ScriptedEngine {
+            inner: self.inner.create_engine_with_case(query, case),
+            query: query.to_string(),
+            script: self.script.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use super::*;
+    use crate::prelude::ExactOrFuzzyEngineFactory;
+
+    #[test]
+    fn the_script_adjusts_the_score_of_the_matches() {
+        let script = Script::new(r#"if text.ends_with(".rs") { score + 1000 } else { score }"#).unwrap();
+        let inner = Rc::new(ExactOrFuzzyEngineFactory::builder().build());
+        let factory = ScriptedEngineFactory::new(inner.clone(), script);
+        let engine = factory.create_engine_with_case("ab", CaseMatching::Smart);
+        let plain = inner.create_engine_with_case("ab", CaseMatching::Smart);
+
+        let score = |engine: &dyn MatchEngine, text: &str| engine.match_item(&text.to_string()).unwrap().rank.score;
+        assert_eq!(score(&*engine, "ab.rs"), score(&*plain, "ab.rs") + 1000);
+        assert_eq!(score(&*engine, "ab.md"), score(&*plain, "ab.md"));
+        assert!(engine.match_item(&"cd.rs".to_string()).is_none());
+    }
+}
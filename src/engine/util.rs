@@ -1,37 +1,70 @@
-use crate::fuzzy_matcher::MatchIndices;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
 use regex::Regex;
 use unicode_normalization::UnicodeNormalization;
 
-/// Normalize a string and return a mapping from normalized char indices to original char indices.
+/// Latin letters whose accent is not a combining mark, so that NFD leaves them as they are
+const STROKED_LETTERS: &[(char, char)] = &[
+    ('Ø', 'O'),
+    ('ø', 'o'),
+    ('Đ', 'D'),
+    ('đ', 'd'),
+    ('Ħ', 'H'),
+    ('ħ', 'h'),
+    ('ı', 'i'),
+    ('Ł', 'L'),
+    ('ł', 'l'),
+    ('Ŧ', 'T'),
+    ('ŧ', 't'),
+    ('ƀ', 'b'),
+    ('Ɨ', 'I'),
+    ('ɨ', 'i'),
+    ('Ƶ', 'Z'),
+    ('ƶ', 'z'),
+];
+
+/// The folded form of the accented letters of the Latin-1 Supplement, Latin Extended-A and -B
+/// and Latin Extended Additional blocks
 ///
-/// Returns (`normalized_string`, mapping) where mapping[i] gives the original char index
-/// for the i-th character in the normalized string.
-pub fn normalize_with_char_mapping(s: &str) -> (String, Vec<usize>) {
-    let mut normalized = String::new();
-    let mut mapping = Vec::new();
-
-    for (orig_char_idx, orig_char) in s.chars().enumerate() {
-        // Decompose this character into NFD form
-        for decomposed_char in orig_char.nfd() {
-            if !unicode_normalization::char::is_combining_mark(decomposed_char) {
-                normalized.push(decomposed_char);
-                mapping.push(orig_char_idx);
+/// Built once from the NFD decompositions: a letter folds to its base when it decomposes to a
+/// single letter and combining marks. Other scripts are left alone, as in fzf.
+static FOLDS: LazyLock<HashMap<char, char>> = LazyLock::new(|| {
+    let mut folds: HashMap<char, char> = (0xC0..=0x24F)
+        .chain(0x1E00..=0x1EFF)
+        .filter_map(char::from_u32)
+        .filter_map(|c| {
+            let mut bases = c.nfd().filter(|&d| !unicode_normalization::char::is_combining_mark(d));
+            match (bases.next(), bases.next()) {
+                (Some(base), None) if base != c => Some((c, base)),
+                _ => None,
             }
-        }
+        })
+        .collect();
+    folds.extend(STROKED_LETTERS.iter().copied());
+    folds
+});
+
+/// The letter `c` without its accent, `é` giving `e`
+///
+/// Every char folds to a single char, so the char indices of a folded string are those of the
+/// original.
+pub fn fold_char(c: char) -> char {
+    if c.is_ascii() {
+        c
+    } else {
+        FOLDS.get(&c).copied().unwrap_or(c)
     }
+}
 
-    (normalized, mapping)
+/// Whether folding changes `s`
+pub fn needs_folding(s: &str) -> bool {
+    !s.is_ascii() && s.chars().any(|c| fold_char(c) != c)
 }
 
-/// Map character indices from normalized string back to original string.
-///
-/// Given indices into a normalized string and the char mapping from `normalize_with_char_mapping`,
-/// returns the corresponding indices in the original string.
-pub fn map_char_indices_to_original(normalized_indices: &[usize], char_mapping: &[usize]) -> MatchIndices {
-    normalized_indices
-        .iter()
-        .filter_map(|&idx| char_mapping.get(idx).copied())
-        .collect()
+/// Fold the accents of `s`, see [`fold_char`]
+pub fn normalize(s: &str) -> String {
+    s.chars().map(fold_char).collect()
 }
 
 /// Normalize a string and return a mapping from normalized byte positions to original byte positions.
@@ -39,26 +72,27 @@ pub fn map_char_indices_to_original(normalized_indices: &[usize], char_mapping:
 /// Returns (`normalized_string`, `byte_mapping`) where `byte_mapping`[i] gives the original byte position
 /// for the i-th byte in the normalized string.
 pub fn normalize_with_byte_mapping(s: &str) -> (String, Vec<usize>) {
-    let mut normalized = String::new();
-    let mut byte_mapping = Vec::new();
+    let mut normalized = String::with_capacity(s.len());
+    let mut byte_mapping = Vec::with_capacity(s.len());
 
     for (orig_byte_pos, orig_char) in s.char_indices() {
-        // Decompose this character into NFD form
-        for decomposed_char in orig_char.nfd() {
-            if !unicode_normalization::char::is_combining_mark(decomposed_char) {
-                let char_start = normalized.len();
-                normalized.push(decomposed_char);
-                // Map each byte of the decomposed char to the original byte position
-                for _ in char_start..normalized.len() {
-                    byte_mapping.push(orig_byte_pos);
-                }
-            }
+        let char_start = normalized.len();
+        normalized.push(fold_char(orig_char));
+        // Map each byte of the folded char to the original byte position
+        for _ in char_start..normalized.len() {
+            byte_mapping.push(orig_byte_pos);
         }
     }
 
     (normalized, byte_mapping)
 }
 
+/// Map a byte position of the original string to the normalized one, given the byte mapping
+/// of [`normalize_with_byte_mapping`]
+pub fn map_byte_to_normalized(orig: usize, byte_mapping: &[usize]) -> usize {
+    byte_mapping.partition_point(|&pos| pos < orig)
+}
+
 /// Map a byte range from normalized string back to original string.
 ///
 /// Given a (start, end) byte range in a normalized string and the byte mapping,
@@ -148,6 +182,25 @@ mod tests {
         assert_eq!(map_byte_range_to_original(1, 0, &[0, 1, 2], "abc"), (1, 1));
     }
 
+    #[test]
+    fn folds_accented_latin_letters_only() {
+        assert_eq!(normalize("café naïve Ørsted łódź"), "cafe naive Orsted lodz");
+        assert_eq!(normalize("Việt"), "Viet");
+        // other scripts, and letters that are not a single base letter, are kept
+        assert_eq!(normalize("слово й ß æ 한"), "слово й ß æ 한");
+        assert!(!needs_folding("cafe слово"));
+        assert!(needs_folding("café"));
+    }
+
+    #[test]
+    fn byte_positions_map_both_ways() {
+        let (normalized, mapping) = normalize_with_byte_mapping("aéb");
+        assert_eq!(normalized, "aeb");
+        assert_eq!(mapping, vec![0, 1, 3]);
+        assert_eq!(map_byte_to_normalized(3, &mapping), 2);
+        assert_eq!(map_byte_to_normalized(4, &mapping), 3);
+    }
+
     #[test]
     fn byte_range_maps_normal_range() {
         // A normal in-bounds range maps to the byte span of the original char.
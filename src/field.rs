@@ -110,6 +110,26 @@ impl FieldRange {
     }
 }
 
+/// Whether `ch` is part of a word
+///
+/// Words are runs of alphanumeric characters, everything else separates them. This is what the
+/// exact-boundary matches (`'abc'`) and the word motions of the query line agree on.
+#[must_use]
+pub fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric()
+}
+
+/// Whether the byte range `start..end` of `text` is delimited by word boundaries
+///
+/// The characters right before and right after the range, if any, must not be word characters.
+/// For example, `foo` is bounded in "a foo.rs" but not in "foobar".
+#[must_use]
+pub fn is_word_bounded(text: &str, start: usize, end: usize) -> bool {
+    let before = text[..start].chars().next_back();
+    let after = text[end..].chars().next();
+    !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+}
+
 // ("|", "a|b||c") -> [(0, 2), (2, 4), (4, 5), (5, 6)]
 // explain: split to ["a|", "b|", "|", "c"]
 fn get_ranges_by_delimiter(delimiter: &Regex, text: &str) -> Vec<(usize, usize)> {
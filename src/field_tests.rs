@@ -253,3 +253,14 @@ fn test_get_string_by_range() {
     // An unparsable range yields None.
     assert_eq!(get_string_by_range(&re, text, "not-a-range"), None);
 }
+
+#[test]
+fn test_is_word_bounded() {
+    let text = "a foo.rs foobar é";
+    assert!(is_word_bounded(text, 2, 5));
+    assert!(!is_word_bounded(text, 9, 12));
+    // the ends of the text are boundaries
+    assert!(is_word_bounded("foo", 0, 3));
+    // non-ASCII letters are word characters
+    assert!(!is_word_bounded("éfoo", 2, 5));
+}
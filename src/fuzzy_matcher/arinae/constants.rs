@@ -12,6 +12,11 @@ pub(super) const START_OF_STRING_BONUS: Score = 16;
 /// Extra bonus for a camelCase transition.
 pub(super) const CAMEL_CASE_BONUS: Score = 6;
 
+/// Extra bonus at a word start reported by a [`Segmenter`](crate::fuzzy_matcher::segment::Segmenter).
+/// Matches the bonus given after `_` or `.`: a segmented word start is a
+/// sub-word boundary, not a hard separator.
+pub(super) const SEGMENT_BONUS: Score = MED_BONUS;
+
 /// Bonus for each additional consecutive matched character.
 pub(super) const CONSECUTIVE_BONUS: Score = 11;
 
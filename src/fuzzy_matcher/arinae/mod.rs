@@ -34,17 +34,20 @@ mod prefilter;
 mod tests;
 
 use std::cell::RefCell;
+use std::sync::Arc;
 
+use derive_more::Debug;
 use thread_local::ThreadLocal;
 
 use self::algo::{full_dp, range_dp};
 use self::atom::Atom;
 use self::banding::{BandingInfo, compute_banding};
-use self::constants::{CAMEL_CASE_BONUS, START_OF_STRING_BONUS};
+use self::constants::{CAMEL_CASE_BONUS, SEGMENT_BONUS, START_OF_STRING_BONUS};
 use self::prefilter::cheap_typo_prefilter;
 
 use self::matrix::{CELL_ZERO, Cell, Dir, SWMatrix};
 use crate::CaseMatching;
+use crate::fuzzy_matcher::segment::Segmenter;
 use crate::fuzzy_matcher::{FuzzyMatcher, MatchIndices, ScoreType};
 
 type Score = i16;
@@ -66,6 +69,16 @@ fn precompute_bonuses<C: Atom>(cho: &[C], buf: &mut Vec<Score>) {
     buf.extend(bonus_iter);
 }
 
+/// Raise the bonus of every segmenter-reported word start to at least
+/// `SEGMENT_BONUS`. Real separators keep their (usually higher) bonus.
+fn apply_segment_bonuses(starts: &[usize], buf: &mut [Score]) {
+    for &i in starts {
+        if let Some(b) = buf.get_mut(i) {
+            *b = (*b).max(SEGMENT_BONUS);
+        }
+    }
+}
+
 /// Arinae fuzzy matcher: Smith-Waterman local alignment with affine gap
 /// penalties and context-sensitive bonuses.
 #[derive(Debug, Default)]
@@ -79,6 +92,9 @@ pub struct ArinaeMatcher {
     #[allow(clippy::type_complexity)]
     char_buf: ThreadLocal<RefCell<(Vec<char>, Vec<char>)>>,
     bonus_buf: ThreadLocal<RefCell<Vec<Score>>>,
+    #[debug(skip)]
+    segmenter: Option<Arc<dyn Segmenter>>,
+    segment_buf: ThreadLocal<RefCell<Vec<usize>>>,
 }
 
 impl ArinaeMatcher {
@@ -93,6 +109,26 @@ impl ArinaeMatcher {
         }
     }
 
+    /// Use `segmenter` to find word starts in non-ASCII choices, which then
+    /// get a boundary bonus just like characters following a separator.
+    #[must_use]
+    pub fn segmenter(mut self, segmenter: Option<Arc<dyn Segmenter>>) -> Self {
+        self.segmenter = segmenter;
+        self
+    }
+
+    /// Compute the bonuses for a non-ASCII choice, including word starts
+    /// reported by the segmenter if one is set.
+    fn precompute_char_bonuses(&self, choice: &str, cho: &[char], buf: &mut Vec<Score>) {
+        precompute_bonuses(cho, buf);
+        if let Some(segmenter) = &self.segmenter {
+            let mut starts = self.segment_buf.get_or(|| RefCell::new(Vec::new())).borrow_mut();
+            starts.clear();
+            segmenter.word_starts(choice, &mut starts);
+            apply_segment_bonuses(&starts, buf);
+        }
+    }
+
     #[inline(always)]
     fn respect_case<C: Atom>(&self, pattern: &[C]) -> bool {
         self.case == CaseMatching::Respect
@@ -194,7 +230,7 @@ impl ArinaeMatcher {
         };
 
         let mut bonus_buf = self.bonus_buf.get_or(|| RefCell::new(Vec::new())).borrow_mut();
-        precompute_bonuses(cho_buf, &mut bonus_buf);
+        self.precompute_char_bonuses(choice, cho_buf, &mut bonus_buf);
 
         // Call dispatch_dp directly to avoid double-borrowing bonus_buf.
         self.dispatch_dp(cho_buf, pat_buf, &bonus_buf, respect_case, compute_indices, &banding)
@@ -270,7 +306,7 @@ impl ArinaeMatcher {
                 compute_banding::<false, char>(pat_buf, cho_buf, respect_case)?
             };
             let mut bonus_buf = self.bonus_buf.get_or(|| RefCell::new(Vec::new())).borrow_mut();
-            precompute_bonuses(cho_buf, &mut bonus_buf);
+            self.precompute_char_bonuses(choice, cho_buf, &mut bonus_buf);
             if self.allow_typos {
                 range_dp::<true, _>(
                     cho_buf,
@@ -607,6 +607,72 @@ fn full_match_traceback_consumes_pattern() {
     assert_eq!(idx, vec![0, 1, 2]);
 }
 
+// ----- Word segmentation -----
+
+/// Segmenter splitting on a fixed set of words, for deterministic tests.
+struct FixedSegmenter(&'static [&'static str]);
+
+impl Segmenter for FixedSegmenter {
+    fn word_starts(&self, text: &str, starts: &mut Vec<usize>) {
+        let mut idx = 0;
+        let mut rest = text;
+        while !rest.is_empty() {
+            let word = self
+                .0
+                .iter()
+                .find(|w| rest.starts_with(**w))
+                .copied()
+                .unwrap_or_else(|| {
+                    let c = rest.chars().next().unwrap();
+                    &rest[..c.len_utf8()]
+                });
+            starts.push(idx);
+            idx += word.chars().count();
+            rest = &rest[word.len()..];
+        }
+    }
+}
+
+fn matcher_segmented() -> ArinaeMatcher {
+    ArinaeMatcher::default().segmenter(Some(Arc::new(FixedSegmenter(&["北京", "大学生", "活动"]))))
+}
+
+#[test]
+fn segmenter_boosts_word_start_matches() {
+    let plain = matcher().fuzzy_match("北京大学生活动", "大学").unwrap();
+    let segmented = matcher_segmented().fuzzy_match("北京大学生活动", "大学").unwrap();
+    assert!(segmented > plain, "segmented {segmented} <= plain {plain}");
+
+    // The range path applies the same bonuses.
+    let (range_score, begin, end) = matcher_segmented().fuzzy_match_range("北京大学生活动", "大学").unwrap();
+    assert_eq!(range_score, segmented);
+    assert_eq!((begin, end), (2, 3));
+}
+
+#[test]
+fn segmenter_prefers_whole_word_over_mid_word() {
+    // "学生" is a word start in the second choice only.
+    let m = ArinaeMatcher::default().segmenter(Some(Arc::new(FixedSegmenter(&["大学生", "学生", "会"]))));
+    let mid = m.fuzzy_match("大学生", "学生").unwrap();
+    let start = m.fuzzy_match("会学生", "学生").unwrap();
+    assert!(start > mid, "start {start} <= mid {mid}");
+}
+
+#[test]
+fn segmenter_ignored_for_ascii() {
+    assert_eq!(
+        matcher_segmented().fuzzy_match("hello world", "wor"),
+        matcher().fuzzy_match("hello world", "wor")
+    );
+}
+
+#[test]
+fn segment_bonus_keeps_higher_separator_bonus() {
+    let mut buf = vec![0, SEGMENT_BONUS + 4, 0];
+    apply_segment_bonuses(&[1, 2, 10], &mut buf);
+    assert_eq!(buf, vec![0, SEGMENT_BONUS + 4, SEGMENT_BONUS]);
+}
+
 // ---------------------------------------------------------------------------
 // Direct unit tests for the DP kernels and banding helpers.
 //
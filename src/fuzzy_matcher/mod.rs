@@ -11,6 +11,8 @@ pub mod clangd;
 pub mod frizbee;
 /// Fzy fuzzy matching algorithm
 pub mod fzy;
+/// Word segmentation hooks for boundary bonuses on non-ASCII text
+pub mod segment;
 /// Skim fuzzy matching algorithm
 pub mod skim;
 mod util;
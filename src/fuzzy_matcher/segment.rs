@@ -0,0 +1,69 @@
+//! Word segmentation hooks for scoring non-ASCII text.
+//!
+//! Scripts such as Chinese or Japanese do not separate words with spaces, so
+//! the separator/camelCase heuristics used by the matchers never fire on them
+//! and every character looks like it sits mid-word. A [`Segmenter`] fills that
+//! gap: it reports where words start, and matchers that support it (currently
+//! [`ArinaeMatcher`](super::arinae::ArinaeMatcher)) award a word-boundary bonus
+//! at those positions.
+
+/// Splits text into words for the purpose of boundary bonuses.
+///
+/// Implementations only need to be correct on non-ASCII input: matchers skip
+/// segmentation entirely when both the choice and the pattern are ASCII.
+pub trait Segmenter: Send + Sync {
+    /// Push the char index of every word start in `text` into `starts`.
+    ///
+    /// `starts` is cleared by the caller. Indices must be in increasing order;
+    /// index `0` may be omitted since the start of the string already gets a
+    /// bonus.
+    fn word_starts(&self, text: &str, starts: &mut Vec<usize>);
+}
+
+/// Chinese word segmentation backed by [jieba-rs](https://docs.rs/jieba-rs).
+#[cfg(feature = "jieba")]
+pub struct JiebaSegmenter {
+    jieba: jieba_rs::Jieba,
+}
+
+#[cfg(feature = "jieba")]
+impl JiebaSegmenter {
+    /// Create a segmenter using jieba's bundled dictionary.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            jieba: jieba_rs::Jieba::new(),
+        }
+    }
+}
+
+#[cfg(feature = "jieba")]
+impl Default for JiebaSegmenter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "jieba")]
+impl Segmenter for JiebaSegmenter {
+    fn word_starts(&self, text: &str, starts: &mut Vec<usize>) {
+        let mut idx = 0;
+        for word in self.jieba.cut(text, false) {
+            starts.push(idx);
+            idx += word.chars().count();
+        }
+    }
+}
+
+#[cfg(all(test, feature = "jieba"))]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jieba_reports_char_indices_of_word_starts() {
+        let mut starts = Vec::new();
+        JiebaSegmenter::new().word_starts("我们中出了一个叛徒", &mut starts);
+        assert_eq!(starts, vec![0, 2, 3, 4, 5, 7]);
+    }
+}
@@ -0,0 +1,100 @@
+//! Transparent decompression of the gzip and zstd inputs (`--decompress`, `--input-file`)
+
+use std::io::{self, BufRead, BufReader, Cursor, Read};
+
+use flate2::bufread::MultiGzDecoder;
+
+/// A compression format recognized from the first bytes of the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Compression {
+    /// `gzip`, possibly several members in a row
+    Gzip,
+    /// `zstd`, possibly several frames in a row
+    Zstd,
+}
+
+impl Compression {
+    /// The length of the longest magic number
+    const MAGIC_LEN: usize = 4;
+
+    /// The compression of the input starting with `head`, if any
+    #[must_use]
+    pub(crate) fn detect(head: &[u8]) -> Option<Self> {
+        if head.starts_with(&[0x1f, 0x8b]) {
+            Some(Self::Gzip)
+        } else if head.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Self::Zstd)
+        } else {
+            None
+        }
+    }
+
+    /// Decompresses `reader`, starting with the magic number
+    ///
+    /// # Errors
+    ///
+    /// Fails when the decoder cannot be created.
+    pub(crate) fn decoder(self, reader: impl BufRead + Send + 'static) -> io::Result<Box<dyn BufRead + Send>> {
+        Ok(match self {
+            Self::Gzip => Box::new(BufReader::new(MultiGzDecoder::new(reader))),
+            Self::Zstd => Box::new(BufReader::new(zstd::Decoder::with_buffer(reader)?)),
+        })
+    }
+}
+
+/// A reader decompressing its source when it is a gzip or zstd stream (`--decompress`)
+///
+/// The format is recognized from the magic number at the start of the source, which goes through
+/// unchanged when it is not compressed, as with `zcat -f`. The source is only looked at by the
+/// first read, so that a slow pipe does not hold up the start of skim.
+pub struct Decompressed<R> {
+    source: Option<R>,
+    reader: Option<Box<dyn BufRead + Send>>,
+}
+
+impl<R: BufRead + Send + 'static> Decompressed<R> {
+    /// Decompresses `source` when needed
+    #[must_use]
+    pub fn new(source: R) -> Self {
+        Self {
+            source: Some(source),
+            reader: None,
+        }
+    }
+
+    fn reader(&mut self) -> io::Result<&mut Box<dyn BufRead + Send>> {
+        if let Some(mut source) = self.source.take() {
+            // a pipe may give the magic number in several reads
+            let mut head = Vec::with_capacity(Compression::MAGIC_LEN);
+            (&mut source)
+                .take(Compression::MAGIC_LEN as u64)
+                .read_to_end(&mut head)?;
+            let compression = Compression::detect(&head);
+            let source = Cursor::new(head).chain(source);
+            self.reader = Some(match compression {
+                Some(compression) => compression.decoder(source)?,
+                None => Box::new(source),
+            });
+        }
+        // a failed detection has nothing more to read
+        Ok(self.reader.get_or_insert_with(|| Box::new(io::empty())))
+    }
+}
+
+impl<R: BufRead + Send + 'static> Read for Decompressed<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader()?.read(buf)
+    }
+}
+
+impl<R: BufRead + Send + 'static> BufRead for Decompressed<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.reader()?.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        if let Some(reader) = &mut self.reader {
+            reader.consume(amount);
+        }
+    }
+}
@@ -1,12 +1,15 @@
 //! Skim item helpers
 //! Including the `DefaultSkimItem`
-use crate::field::{FieldRange, parse_matching_fields, parse_transform_fields};
+use crate::field::{FieldRange, get_string_by_field, parse_matching_fields, parse_transform_fields};
+use crate::helper::mapped_file::MappedFile;
 use crate::tui::util::merge_styles;
-use crate::{DisplayContext, Matches, SkimItem};
+use crate::{DisplayContext, Matches, SkimItem, SourcePosition};
 use ansi_to_tui::IntoText;
 use ratatui::text::{Line, Span};
 use regex::Regex;
 use std::borrow::Cow;
+use std::ops::Deref;
+use std::sync::Arc;
 
 //------------------------------------------------------------------------------
 /// An item will store everything that one line input will need to be operated and displayed.
@@ -22,12 +25,92 @@ use std::borrow::Cow;
 #[derive(Debug)]
 pub struct DefaultSkimItem {
     /// The text that will be shown on screen.
-    text: Box<str>,
+    text: ItemText,
 
     /// Metadata containing miscellaneous fields when special options are used
     metadata: Option<Box<DefaultSkimItemMetadata>>,
 }
 
+/// The text of a [`DefaultSkimItem`]
+#[derive(Debug)]
+enum ItemText {
+    /// Owned by the item
+    Owned(Box<str>),
+    /// A line of some input shared by all the items read from it
+    ///
+    /// `start..end` was a valid UTF-8 range of the input when the item was created, see
+    /// [`DefaultSkimItem::from_shared`] and [`SharedInput::text`].
+    Shared {
+        input: Arc<SharedInput>,
+        start: usize,
+        end: usize,
+    },
+}
+
+impl Deref for ItemText {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        match self {
+            Self::Owned(text) => text,
+            Self::Shared { input, start, end } => input.text(*start, *end),
+        }
+    }
+}
+
+/// Input whose lines are the text of the items read from it, freed with the last of them
+#[derive(Debug)]
+pub(crate) enum SharedInput {
+    /// A chunk read from a command or stdin
+    Chunk(Box<[u8]>),
+    /// A file read with `--input-file`, its lines ending with `line_ending`
+    Mapped { file: MappedFile, line_ending: u8 },
+}
+
+impl SharedInput {
+    /// The content of the input
+    #[inline]
+    #[must_use]
+    pub(crate) fn bytes(&self) -> &[u8] {
+        match self {
+            Self::Chunk(bytes) => bytes,
+            Self::Mapped { file, .. } => file.bytes(),
+        }
+    }
+
+    /// The text of the line at `start..end`, a `&str` when its item was created
+    ///
+    /// A mapped file can be rewritten by another process while skim runs, so its bytes are
+    /// checked again and only their valid UTF-8 start is kept.
+    #[inline]
+    #[must_use]
+    pub(crate) fn text(&self, start: usize, end: usize) -> &str {
+        match self {
+            // SAFETY: the range was a `&str` when the item was created, and a chunk never changes
+            Self::Chunk(bytes) => unsafe { std::str::from_utf8_unchecked(&bytes[start..end]) },
+            Self::Mapped { file, .. } => {
+                let line = &file.bytes()[start..end];
+                std::str::from_utf8(line)
+                    .or_else(|err| std::str::from_utf8(&line[..err.valid_up_to()]))
+                    .unwrap_or_default()
+            }
+        }
+    }
+
+    /// Where the line starting at `offset` is in the file, `None` for a chunk
+    #[must_use]
+    pub(crate) fn position(&self, offset: usize) -> Option<SourcePosition> {
+        match self {
+            Self::Chunk(_) => None,
+            Self::Mapped { file, line_ending } => Some(SourcePosition {
+                line: file.line_number(offset, *line_ending),
+                offset,
+            }),
+        }
+    }
+}
+
 /// Additional metadata for a `SkimItem`
 #[derive(Debug, Default)]
 pub struct DefaultSkimItemMetadata {
@@ -54,8 +137,28 @@ pub struct DefaultSkimItemMetadata {
     /// used for matching so they stay searchable.
     hidden_ranges: Option<Vec<(usize, usize)>>,
 
+    /// Byte ranges (in the display/matching text) read from `--match-ranges-field`,
+    /// highlighted in place of the query's matches.
+    external_ranges: Option<Vec<(usize, usize)>>,
+
     /// Whether the item should be disabled or not
     disabled: bool,
+
+    /// Whether the line was cut by `--max-item-length`
+    truncated: bool,
+
+    /// Text read after `--annotation-sep`, shown next to the item but never matched
+    annotation: Option<Box<str>>,
+
+    /// Field read by `--group-by-field`, the group the item is listed under
+    group: Option<Box<str>>,
+
+    /// The line as read when it is not valid UTF-8 (`--encoding lossy|latin1`), output instead
+    /// of its decoded text
+    orig_bytes: Option<Box<[u8]>>,
+
+    /// The file the line was read from and its offset, for the items not pointing into it
+    source: Option<(Arc<SharedInput>, usize)>,
 }
 
 impl DefaultSkimItem {
@@ -169,18 +272,52 @@ impl DefaultSkimItem {
                     ansi_info,
                     matching_ranges,
                     hidden_ranges: None,
+                    external_ranges: None,
                     disabled: false,
+                    truncated: false,
+                    annotation: None,
+                    group: None,
+                    orig_bytes: None,
+                    source: None,
                 }))
             } else {
                 None
             };
 
         DefaultSkimItem {
-            text: temp_text,
+            text: ItemText::Owned(temp_text),
             metadata,
         }
     }
 
+    /// Creates the item of `line`, a line of `input`, without copying it
+    ///
+    /// This is the item [`new`](Self::new) creates from a line without ANSI codes or null bytes,
+    /// without any field option, but sharing the buffer of the input instead of allocating the
+    /// text of each line.
+    ///
+    /// # Panics
+    ///
+    /// If `line` is not part of `input`.
+    #[must_use]
+    pub(crate) fn from_shared(input: &Arc<SharedInput>, line: &str) -> Self {
+        debug_assert!(!Self::contains_ansi_escape(line) && !line.contains('\0'));
+        let bytes = input.bytes();
+        let start = (line.as_ptr() as usize).wrapping_sub(bytes.as_ptr() as usize);
+        assert!(
+            start <= bytes.len() && line.len() <= bytes.len() - start,
+            "the line is not part of the input"
+        );
+        DefaultSkimItem {
+            text: ItemText::Shared {
+                input: Arc::clone(input),
+                start,
+                end: start + line.len(),
+            },
+            metadata: None,
+        }
+    }
+
     /// Builder-style setter for the fields hidden from display (via `--hide-nth`).
     ///
     /// The fields are resolved against the item's display/matching text — which is
@@ -207,6 +344,87 @@ impl DefaultSkimItem {
         self
     }
 
+    /// Builder-style setter for the match ranges read from a field of `line` (via
+    /// `--match-ranges-field`).
+    ///
+    /// `line` is the raw input line, before `--with-nth` is applied. The field holds
+    /// comma-separated `START-END` byte ranges relative to [`text()`](Self::text).
+    /// Malformed ranges are skipped; a no-op when none can be parsed.
+    #[must_use]
+    pub fn match_ranges_field(mut self, line: &str, field: &FieldRange, delimiter: &Regex) -> Self {
+        let Some(spec) = get_string_by_field(delimiter, line, field) else {
+            return self;
+        };
+        let ranges = parse_match_ranges(spec);
+        if !ranges.is_empty() {
+            self.metadata.get_or_insert_default().external_ranges = Some(ranges);
+        }
+        self
+    }
+
+    /// Builder-style setter for the group read from a field of `line` (via `--group-by-field`)
+    ///
+    /// `line` is the raw input line, before `--with-nth` is applied, so the field can be
+    /// removed from the display. Its trailing delimiter is dropped; a no-op when the field is
+    /// missing or empty.
+    #[must_use]
+    pub fn group_field(mut self, line: &str, field: &FieldRange, delimiter: &Regex) -> Self {
+        let Some(group) = get_string_by_field(delimiter, line, field) else {
+            return self;
+        };
+        let group = delimiter
+            .find_iter(group)
+            .last()
+            .filter(|m| m.end() == group.len())
+            .map_or(group, |m| &group[..m.start()]);
+        if !group.is_empty() {
+            self.metadata.get_or_insert_default().group = Some(Box::from(group));
+        }
+        self
+    }
+
+    /// Builder-style setter for the annotation read after `--annotation-sep`
+    ///
+    /// The annotation is displayed but never matched. With `output_sep`, it is also
+    /// appended to [`output()`](SkimItem::output), after that separator.
+    #[must_use]
+    pub fn with_annotation(mut self, annotation: &str, output_sep: Option<&str>) -> Self {
+        if let Some(sep) = output_sep {
+            let output = format!("{}{sep}{annotation}", self.output());
+            self.metadata.get_or_insert_default().orig_text = Some(output.into_boxed_str());
+        }
+        self.metadata.get_or_insert_default().annotation = Some(Box::from(annotation));
+        self
+    }
+
+    /// Builder-style setter for the output of an item whose text was mapped from it (`--map-item`)
+    #[must_use]
+    pub fn with_output(mut self, output: &str) -> Self {
+        self.metadata.get_or_insert_default().orig_text = Some(Box::from(output));
+        self
+    }
+
+    /// Builder-style setter for the line the item was decoded from, output as it is
+    #[must_use]
+    pub fn with_orig_bytes(mut self, bytes: &[u8]) -> Self {
+        self.metadata.get_or_insert_default().orig_bytes = Some(Box::from(bytes));
+        self
+    }
+
+    /// Builder-style setter for the file holding `line`, the line the item was read from, see
+    /// [`source_position`](SkimItem::source_position)
+    ///
+    /// A no-op for a chunk, whose offsets are not those of a file.
+    #[must_use]
+    pub(crate) fn with_source(mut self, input: &Arc<SharedInput>, line: &[u8]) -> Self {
+        if matches!(**input, SharedInput::Mapped { .. }) {
+            let offset = (line.as_ptr() as usize).wrapping_sub(input.bytes().as_ptr() as usize);
+            debug_assert!(offset <= input.bytes().len(), "the line is not part of the input");
+            self.metadata.get_or_insert_default().source = Some((Arc::clone(input), offset));
+        }
+        self
+    }
+
     fn contains_ansi_escape(s: &str) -> bool {
         memchr::memchr(b'\x1b', s.as_bytes()).is_some()
     }
@@ -216,6 +434,17 @@ impl DefaultSkimItem {
         self.metadata.get_or_insert_default().disabled = true;
     }
 
+    /// Mark the item as cut from a longer line (`--max-item-length`)
+    pub fn mark_truncated(&mut self) {
+        self.metadata.get_or_insert_default().truncated = true;
+    }
+
+    /// Whether the item was cut from a longer line (`--max-item-length`)
+    #[must_use]
+    pub fn is_truncated(&self) -> bool {
+        self.metadata.as_ref().is_some_and(|x| x.truncated)
+    }
+
     /// Getter for `stripped_text` stored in the metadata
     #[must_use]
     pub fn stripped_text(&self) -> Option<&str> {
@@ -262,6 +491,28 @@ impl DefaultSkimItem {
         }
     }
 
+    /// Getter for `external_ranges` stored in metadata
+    #[must_use]
+    pub fn external_ranges(&self) -> Option<&[(usize, usize)]> {
+        if let Some(meta) = &self.metadata {
+            meta.external_ranges.as_ref().map(|v| v.as_ref() as &[(usize, usize)])
+        } else {
+            None
+        }
+    }
+
+    /// Getter for `annotation` stored in metadata
+    #[must_use]
+    pub fn annotation(&self) -> Option<&str> {
+        self.metadata.as_ref().and_then(|meta| meta.annotation.as_deref())
+    }
+
+    /// Getter for `group` stored in metadata
+    #[must_use]
+    pub fn group(&self) -> Option<&str> {
+        self.metadata.as_ref().and_then(|meta| meta.group.as_deref())
+    }
+
     /// Getter for `hidden_ranges` stored in metadata
     #[must_use]
     pub fn hidden_ranges(&self) -> Option<&[(usize, usize)]> {
@@ -286,7 +537,7 @@ impl DefaultSkimItem {
 impl From<String> for DefaultSkimItem {
     fn from(value: String) -> Self {
         Self {
-            text: Box::from(value),
+            text: ItemText::Owned(Box::from(value)),
             metadata: None,
         }
     }
@@ -299,7 +550,7 @@ impl SkimItem for DefaultSkimItem {
         if let Some(stripped) = self.stripped_text() {
             Cow::Borrowed(stripped)
         } else {
-            Cow::Borrowed(&self.text)
+            Cow::Borrowed(&*self.text)
         }
     }
 
@@ -307,7 +558,29 @@ impl SkimItem for DefaultSkimItem {
         if let Some(orig) = self.orig_text() {
             Cow::Borrowed(orig)
         } else {
-            Cow::Borrowed(&self.text)
+            Cow::Borrowed(&*self.text)
+        }
+    }
+
+    fn output_bytes(&self) -> Cow<'_, [u8]> {
+        if let Some(meta) = &self.metadata
+            && let Some(bytes) = &meta.orig_bytes
+        {
+            Cow::Borrowed(bytes)
+        } else {
+            Cow::Borrowed(self.orig_text().unwrap_or(&self.text).as_bytes())
+        }
+    }
+
+    fn source_position(&self) -> Option<SourcePosition> {
+        if let ItemText::Shared { input, start, .. } = &self.text {
+            input.position(*start)
+        } else if let Some(meta) = &self.metadata
+            && let Some((input, offset)) = &meta.source
+        {
+            input.position(*offset)
+        } else {
+            None
         }
     }
 
@@ -320,6 +593,18 @@ impl SkimItem for DefaultSkimItem {
         self.hidden_ranges()
     }
 
+    fn external_ranges(&self) -> Option<&[(usize, usize)]> {
+        self.external_ranges()
+    }
+
+    fn annotation(&self) -> Option<&str> {
+        self.annotation()
+    }
+
+    fn group(&self) -> Option<Cow<'_, str>> {
+        self.group().map(Cow::Borrowed)
+    }
+
     // The display function handles ANSI stripping, field highlighting, and match
     // rendering in a single pass; splitting it would require duplicating context handling.
     #[allow(clippy::too_many_lines)]
@@ -330,7 +615,7 @@ impl SkimItem for DefaultSkimItem {
             let text_bytes = self.text.as_bytes().to_vec();
             let Ok(parsed_text) = text_bytes.into_text() else {
                 // Fallback to plain text if parsing fails
-                return context.to_line(Cow::Borrowed(&self.text));
+                return context.to_line(Cow::Borrowed(&*self.text));
             };
 
             // Extract all spans from the parsed text (should be a single line)
@@ -522,7 +807,7 @@ impl SkimItem for DefaultSkimItem {
             .to_line(Cow::Owned(visible))
         } else {
             // No ANSI mapping needed, use text as-is
-            context.to_line(Cow::Borrowed(&self.text))
+            context.to_line(Cow::Borrowed(&*self.text))
         }
     }
 
@@ -625,6 +910,21 @@ fn escape_ansi(raw: &str) -> String {
     unsafe { String::from_utf8_unchecked(raw.bytes().map(|b| if b == 27 { b'?' } else { b }).collect()) }
 }
 
+/// Parse comma-separated `START-END` byte ranges, as read from `--match-ranges-field`.
+///
+/// Malformed entries are skipped and the result is normalized (see [`normalize_ranges`]).
+#[must_use]
+pub(crate) fn parse_match_ranges(spec: &str) -> Vec<(usize, usize)> {
+    let ranges: Vec<(usize, usize)> = spec
+        .split(',')
+        .filter_map(|range| {
+            let (start, end) = range.trim().split_once('-')?;
+            Some((start.parse().ok()?, end.parse().ok()?))
+        })
+        .collect();
+    normalize_ranges(&ranges)
+}
+
 /// Sort and merge a list of byte ranges into a canonical, non-overlapping form.
 ///
 /// Empty ranges are dropped. Overlapping or touching ranges are merged so callers
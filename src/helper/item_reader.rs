@@ -1,40 +1,71 @@
 //! Helper utilities for converting input sources into skim item streams.
 
+use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::error::Error;
 use std::io::{BufRead, BufReader};
+use std::ops::Range;
+use std::path::Path;
 use std::process::{Child, Stdio};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::thread;
+use std::time::{Duration, Instant};
 
 use crate::thread_pool::ThreadPool;
 
 /// Size of the read buffer used by the parallel I/O reader thread.
 const PARALLEL_READ_BUF_SIZE: usize = 256 * 1024;
+/// How often the dispatcher checks whether the query is still being typed.
+const TYPING_POLL_INTERVAL: Duration = Duration::from_millis(5);
 
 use regex::Regex;
 
 use crate::field::FieldRange;
-use crate::helper::item::DefaultSkimItem;
-use crate::reader::CommandCollector;
+#[cfg(feature = "decompress")]
+use crate::helper::decompress::Compression;
+use crate::helper::item::{DefaultSkimItem, SharedInput};
+use crate::helper::mapped_file::MappedFile;
+use crate::interpreter::Interpreter;
+use crate::item::RemovedItem;
+use crate::options::Encoding;
+use crate::process_group::{self, ProcessGroup};
+use crate::reader::{CommandCollector, StderrLog, TypingSignal};
 use crate::{SkimItem, SkimItemReceiver, SkimItemSender, SkimOptions};
 
 const DELIMITER_STR: &str = r"[\t\n ]+";
 const READ_BUFFER_SIZE: usize = 1024;
+/// Appended to the lines cut by `max_item_length`
+const ELLIPSIS: &str = "…";
 
 /// Options for configuring how items are read and parsed
 #[derive(Debug)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct SkimItemReaderOption {
     buf_size: usize,
     use_ansi_color: bool,
     transform_fields: Vec<FieldRange>,
     matching_fields: Vec<FieldRange>,
     hidden_fields: Vec<FieldRange>,
+    match_ranges_field: Option<FieldRange>,
+    group_field: Option<FieldRange>,
     delimiter: Regex,
     line_ending: u8,
+    encoding: Encoding,
     show_error: bool,
+    cmd_timeout: Option<Duration>,
+    cmd_retry: usize,
     disable_pattern: Option<Regex>,
+    shell: Interpreter,
+    max_item_length: Option<usize>,
+    skip_long_items: bool,
+    annotation_sep: Option<String>,
+    print_annotation: bool,
+    delta: bool,
+    #[cfg(feature = "script")]
+    map_item: Option<crate::script::Script>,
+    /// Lines truncated or skipped because of `max_item_length`
+    num_long_items: AtomicUsize,
 }
 
 impl Default for SkimItemReaderOption {
@@ -42,13 +73,27 @@ impl Default for SkimItemReaderOption {
         Self {
             buf_size: READ_BUFFER_SIZE,
             line_ending: b'\n',
+            encoding: Encoding::default(),
             use_ansi_color: false,
             transform_fields: Vec::new(),
             matching_fields: Vec::new(),
             hidden_fields: Vec::new(),
+            match_ranges_field: None,
+            group_field: None,
             delimiter: Regex::new(DELIMITER_STR).unwrap(),
             show_error: false,
+            cmd_timeout: None,
+            cmd_retry: 0,
             disable_pattern: None,
+            shell: Interpreter::from_env(),
+            max_item_length: None,
+            skip_long_items: false,
+            annotation_sep: None,
+            print_annotation: false,
+            delta: false,
+            #[cfg(feature = "script")]
+            map_item: None,
+            num_long_items: AtomicUsize::new(0),
         }
     }
 }
@@ -60,6 +105,7 @@ impl SkimItemReaderOption {
         Self {
             buf_size: READ_BUFFER_SIZE,
             line_ending: if options.read0 { b'\0' } else { b'\n' },
+            encoding: options.encoding,
             use_ansi_color: options.ansi,
             transform_fields: options
                 .with_nth
@@ -76,9 +122,22 @@ impl SkimItemReaderOption {
                 .iter()
                 .filter_map(|f| if f.is_empty() { None } else { FieldRange::from_str(f) })
                 .collect(),
+            match_ranges_field: options.match_ranges_field.map(field_index),
+            group_field: options.group_by_field.map(field_index),
             delimiter: options.delimiter.clone(),
             show_error: options.show_cmd_error,
+            cmd_timeout: options.cmd_timeout.map(Duration::from_secs),
+            cmd_retry: options.cmd_retry,
             disable_pattern: options.disable_pattern.clone(),
+            shell: Interpreter::from_options(options),
+            max_item_length: options.max_item_length,
+            skip_long_items: options.skip_long_items,
+            annotation_sep: options.annotation_sep.clone().filter(|sep| !sep.is_empty()),
+            print_annotation: options.print_annotation,
+            delta: options.delta,
+            #[cfg(feature = "script")]
+            map_item: options.map_item.clone(),
+            num_long_items: AtomicUsize::new(0),
         }
     }
 
@@ -96,6 +155,13 @@ impl SkimItemReaderOption {
         self
     }
 
+    /// Sets how the lines that are not valid UTF-8 are read, see `--encoding`
+    #[must_use]
+    pub fn encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
     /// Enables or disables ANSI color code parsing
     #[must_use]
     pub fn ansi(mut self, enable: bool) -> Self {
@@ -161,6 +227,20 @@ impl SkimItemReaderOption {
         self
     }
 
+    /// Sets the field holding precomputed match ranges (1-based)
+    #[must_use]
+    pub fn match_ranges_field(mut self, field: Option<usize>) -> Self {
+        self.match_ranges_field = field.map(field_index);
+        self
+    }
+
+    /// Sets the field holding the group of the items (1-based)
+    #[must_use]
+    pub fn group_field(mut self, field: Option<usize>) -> Self {
+        self.group_field = field.map(field_index);
+        self
+    }
+
     /// Enables reading null-terminated lines instead of newline-terminated
     #[must_use]
     pub fn read0(mut self, enable: bool) -> Self {
@@ -179,11 +259,104 @@ impl SkimItemReaderOption {
         self
     }
 
+    /// Sets how long the command may run before it is killed
+    #[must_use]
+    pub fn cmd_timeout(mut self, cmd_timeout: Option<Duration>) -> Self {
+        self.cmd_timeout = cmd_timeout;
+        self
+    }
+
+    /// Sets how many times a command that failed without output is run again
+    #[must_use]
+    pub fn cmd_retry(mut self, cmd_retry: usize) -> Self {
+        self.cmd_retry = cmd_retry;
+        self
+    }
+
+    /// Sets the maximum length of a line in bytes, longer lines are truncated with an ellipsis
+    #[must_use]
+    pub fn max_item_length(mut self, max_item_length: Option<usize>) -> Self {
+        self.max_item_length = max_item_length;
+        self
+    }
+
+    /// Sets whether lines longer than [`max_item_length`](Self::max_item_length) are skipped
+    /// instead of truncated
+    #[must_use]
+    pub fn skip_long_items(mut self, skip: bool) -> Self {
+        self.skip_long_items = skip;
+        self
+    }
+
+    /// Sets the separator after which the rest of a line is an annotation, displayed but never matched
+    #[must_use]
+    pub fn annotation_sep(mut self, sep: Option<&str>) -> Self {
+        self.annotation_sep = sep.filter(|sep| !sep.is_empty()).map(ToString::to_string);
+        self
+    }
+
+    /// Sets whether annotations are kept in the output, after the separator
+    #[must_use]
+    pub fn print_annotation(mut self, print: bool) -> Self {
+        self.print_annotation = print;
+        self
+    }
+
+    /// Reads the lines as changes to the items, see `--delta`
+    ///
+    /// A line starting with `-` removes the items output as the rest of the line, one starting with
+    /// `+` adds the rest of the line, the others are added as they are.
+    #[must_use]
+    pub fn delta(mut self, enable: bool) -> Self {
+        self.delta = enable;
+        self
+    }
+
+    /// Maps each line to the text displayed and matched with a Rhai script, see `--map-item`
+    ///
+    /// The line stays the output of its item.
+    #[cfg(feature = "script")]
+    #[must_use]
+    pub fn map_item(mut self, script: Option<crate::script::Script>) -> Self {
+        self.map_item = script;
+        self
+    }
+
+    /// Sets the shell running the commands, see [`SkimOptions::with_shell`]
+    ///
+    /// `None` uses `$SKIM_SHELL` or the platform default, an empty string runs the commands without a shell.
+    #[must_use]
+    pub fn shell(mut self, shell: Option<&str>) -> Self {
+        self.shell = shell.map_or_else(Interpreter::from_env, Interpreter::parse);
+        self
+    }
+
     /// Builds the options (currently a no-op, returns self)
     #[must_use]
     pub fn build(self) -> Self {
         self
     }
+
+    /// Whether the lines are the items as they are, but for their ANSI codes
+    fn is_plain(&self) -> bool {
+        #[cfg(feature = "script")]
+        if self.map_item.is_some() {
+            return false;
+        }
+        self.transform_fields.is_empty()
+            && self.matching_fields.is_empty()
+            && self.hidden_fields.is_empty()
+            && self.match_ranges_field.is_none()
+            && self.group_field.is_none()
+            && self.disable_pattern.is_none()
+            && self.max_item_length.is_none()
+            && self.annotation_sep.is_none()
+            && !self.delta
+    }
+}
+
+fn field_index(field: usize) -> FieldRange {
+    FieldRange::Single(i32::try_from(field).unwrap_or(i32::MAX))
 }
 
 /// Reader for converting various input sources into streams of skim items
@@ -196,6 +369,12 @@ pub struct SkimItemReader {
     /// [`with_thread_pool`](Self::with_thread_pool) or
     /// [`set_thread_pool`](Self::set_thread_pool).
     thread_pool: Arc<ThreadPool>,
+    /// Log the stderr of the commands is written to, unless `show_error` sends it to the items
+    stderr_log: Option<StderrLog>,
+    /// Variables exported to the commands, see [`CommandCollector::set_env`]
+    env: Vec<(String, String)>,
+    /// The chunks read are not parsed while it says the query is being typed
+    typing: TypingSignal,
 }
 
 fn default_thread_pool() -> Arc<ThreadPool> {
@@ -209,6 +388,9 @@ impl Default for SkimItemReader {
         Self {
             option: Arc::new(Default::default()),
             thread_pool: default_thread_pool(),
+            stderr_log: None,
+            env: Vec::new(),
+            typing: TypingSignal::default(),
         }
     }
 }
@@ -220,6 +402,9 @@ impl SkimItemReader {
         Self {
             option: Arc::new(option),
             thread_pool: default_thread_pool(),
+            stderr_log: None,
+            env: Vec::new(),
+            typing: TypingSignal::default(),
         }
     }
 
@@ -246,6 +431,13 @@ impl SkimItemReader {
 }
 
 impl SkimItemReader {
+    /// Number of lines truncated or skipped so far because of
+    /// [`SkimItemReaderOption::max_item_length`]
+    #[must_use]
+    pub fn num_long_items(&self) -> usize {
+        self.option.num_long_items.load(Ordering::Relaxed)
+    }
+
     /// Converts a `BufRead` source into a stream of skim items using the
     /// parallel pipeline.
     pub fn of_bufread(&self, source: impl BufRead + Send + 'static) -> SkimItemReceiver {
@@ -268,6 +460,8 @@ impl SkimItemReader {
     /// 2. **Dispatcher thread** (dedicated, lightweight) — drains that channel
     ///    and submits one pool job per chunk.  The bounded channel provides
     ///    natural back-pressure on the I/O thread when the pool is busy.
+    ///    While the [`TypingSignal`] says the query is being typed, the chunks
+    ///    are kept instead, and submitted in order once the typing stops.
     /// 3. **Pool jobs** — parse lines, validate UTF-8, apply ANSI stripping and
     ///    field transforms, and create `DefaultSkimItem` + `Arc` per line.
     ///    Because these jobs share the same pool as the matcher, reader and
@@ -278,18 +472,21 @@ impl SkimItemReader {
     ///    and `--tac` behaviour are correct.
     ///
     /// When `child` is `Some`, a **killer thread** is also spawned.  It waits
-    /// on `rx_interrupt` and kills the child process on request (or when the
-    /// reader is dropped).  This thread participates in `components_to_stop`
-    /// accounting so that [`ReaderControl::kill`] waits for it to finish.
+    /// on `rx_interrupt` and kills the child's whole process group on request
+    /// (or when the reader is dropped, or after `cmd_timeout`), so grandchildren
+    /// started by the shell do not outlive it.  This thread participates in
+    /// `components_to_stop` accounting so that [`ReaderControl::kill`] waits for
+    /// it to finish, and records in the returned [`CommandStatus`] how the child
+    /// ended.
     ///
-    /// Returns `(rx_item, tx_interrupt)`.  The caller must send on `tx_interrupt`
+    /// Returns `(rx_item, tx_interrupt, status)`.  The caller must send on `tx_interrupt`
     /// to signal shutdown; the killer thread (if any) will then kill the child.
     fn parallel_bufread(
         &self,
         source: impl BufRead + Send + 'static,
-        child: Option<Child>,
+        child: Option<(Child, ProcessGroup)>,
         components_to_stop: &Arc<AtomicUsize>,
-    ) -> (SkimItemReceiver, crate::prelude::Sender<i32>) {
+    ) -> (SkimItemReceiver, crate::prelude::Sender<i32>, Arc<CommandStatus>) {
         let (tx_item, rx_item): (SkimItemSender, SkimItemReceiver) = kanal::bounded(1024 * 1024);
         let option = self.option.clone();
         let pool = Arc::clone(&self.thread_pool);
@@ -304,15 +501,46 @@ impl SkimItemReader {
         Self::spawn_io_reader(source, tx_chunks, line_ending);
 
         // Stage 2: dispatcher thread — bridges the bounded channel to the pool.
+        let typing = self.typing.clone();
         thread::spawn(move || {
-            while let Ok((seq, chunk)) = rx_chunks.recv() {
+            let dispatch = |(seq, chunk): (usize, Vec<u8>)| {
                 let tx = tx_results.clone();
                 let opt = option.clone();
                 pool.spawn(move || {
-                    let result = Self::process_chunk(seq, &chunk, &opt);
+                    let result = Self::process_chunk(seq, &chunk, None, &opt);
                     let _ = tx.send(result);
                 });
+            };
+            // Chunks read while the query is being typed, parsed once it stops
+            let mut deferred = Vec::new();
+            loop {
+                if !deferred.is_empty() && !typing.is_typing() {
+                    deferred.drain(..).for_each(&dispatch);
+                }
+                let chunk = if deferred.is_empty() {
+                    match rx_chunks.recv() {
+                        Ok(chunk) => chunk,
+                        Err(_) => break,
+                    }
+                } else {
+                    // wake up to parse the deferred chunks when the typing stops
+                    match rx_chunks.recv_timeout(TYPING_POLL_INTERVAL) {
+                        Ok(chunk) => chunk,
+                        Err(kanal::ReceiveErrorTimeout::Timeout) => continue,
+                        Err(_) => break,
+                    }
+                };
+                if deferred.is_empty() && !typing.is_typing() {
+                    dispatch(chunk);
+                } else {
+                    deferred.push(chunk);
+                }
+            }
+            // The input ended while typing
+            while !deferred.is_empty() && typing.is_typing() {
+                thread::sleep(TYPING_POLL_INTERVAL);
             }
+            deferred.into_iter().for_each(dispatch);
             // rx_chunks closed → all chunks dispatched; tx_results dropped here
             // so the reorder thread exits once the last pool job finishes.
         });
@@ -334,6 +562,13 @@ impl SkimItemReader {
         let (tx_interrupt, rx_interrupt) = crate::prelude::bounded::<i32>(8);
         let components_to_stop_killer = components_to_stop.clone();
         components_to_stop.fetch_add(1, Ordering::SeqCst);
+        let option = Arc::clone(&self.option);
+        let status = Arc::new(CommandStatus::default());
+        let status_killer = Arc::clone(&status);
+        let deadline = option
+            .cmd_timeout
+            .filter(|_| child.is_some())
+            .map(|timeout| Instant::now() + timeout);
         thread::spawn(move || {
             debug!("parallel reader: killer thread start");
 
@@ -342,9 +577,15 @@ impl SkimItemReader {
             // timeout.  Both channels are bounded so this never busy-spins in
             // practice: the kill path is rare, and the done path fires quickly.
             loop {
-                if rx_interrupt.try_recv().is_ok_and(|v| v.is_some()) {
-                    // Explicit kill: terminate the child immediately.
-                    if let Some(mut c) = child {
+                let timed_out = deadline.is_some_and(|deadline| Instant::now() >= deadline);
+                if timed_out || rx_interrupt.try_recv().is_ok_and(|v| v.is_some()) {
+                    // Explicit kill or timeout: terminate the child and its descendants immediately.
+                    if timed_out {
+                        debug!("parallel reader: command timed out");
+                        status_killer.timed_out.store(true, Ordering::SeqCst);
+                    }
+                    if let Some((mut c, group)) = child {
+                        group.kill();
                         let _ = c.kill();
                         let _ = c.wait();
                     }
@@ -355,8 +596,10 @@ impl SkimItemReader {
                     Ok(()) => break,
                     Err(kanal::ReceiveErrorTimeout::Closed | kanal::ReceiveErrorTimeout::SendClosed) => {
                         // Natural EOF: child already exited; just reap if present.
-                        if let Some(mut c) = child {
-                            let _ = c.wait();
+                        if let Some((mut c, _)) = child
+                            && !c.wait().is_ok_and(|exit| exit.success())
+                        {
+                            status_killer.failed.store(true, Ordering::SeqCst);
                         }
                         break;
                     }
@@ -366,11 +609,17 @@ impl SkimItemReader {
                 }
             }
 
+            if option.max_item_length.is_some() {
+                debug!(
+                    "parallel reader: {} over-long lines truncated or skipped",
+                    option.num_long_items.load(Ordering::Relaxed)
+                );
+            }
             components_to_stop_killer.fetch_sub(1, Ordering::SeqCst);
             debug!("parallel reader: killer thread stop");
         });
 
-        (rx_item, tx_interrupt)
+        (rx_item, tx_interrupt, status)
     }
 
     /// Stage 1 of the parallel reader: reads large byte chunks from `source`,
@@ -399,7 +648,9 @@ impl SkimItemReader {
                     }
                     Ok(n) => n,
                     Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
-                    Err(_) => {
+                    Err(e) => {
+                        // e.g. a corrupt compressed input
+                        warn!("parallel reader: failed to read the input: {e}");
                         // Flush any accumulated data before exiting on error.
                         if !leftover.is_empty() {
                             let _ = tx_chunks.send((seq, std::mem::take(&mut leftover)));
@@ -438,7 +689,14 @@ impl SkimItemReader {
     }
 
     /// Parses a raw byte chunk into a tagged batch of items.
-    fn process_chunk(seq: usize, chunk: &[u8], opt: &SkimItemReaderOption) -> (usize, Vec<Arc<dyn SkimItem>>) {
+    ///
+    /// With `source`, the input file holding `chunk`, the items keep their position in it.
+    fn process_chunk(
+        seq: usize,
+        chunk: &[u8],
+        source: Option<&Arc<SharedInput>>,
+        opt: &SkimItemReaderOption,
+    ) -> (usize, Vec<Arc<dyn SkimItem>>) {
         let mut items = Vec::new();
         let line_ending = opt.line_ending;
 
@@ -453,29 +711,170 @@ impl SkimItemReader {
             chunk
         };
 
+        if opt.is_plain() {
+            let input = Arc::new(SharedInput::Chunk(Box::from(chunk_trimmed)));
+            return (seq, Self::process_shared(&input, 0..chunk_trimmed.len(), opt));
+        }
+
         for line_bytes in chunk_trimmed.split(|&b: &u8| b == line_ending) {
             // Strip optional \r for \r\n endings.
             let line_bytes: &[u8] = line_bytes.strip_suffix(b"\r").unwrap_or(line_bytes);
-            let Ok(line) = std::str::from_utf8(line_bytes) else {
+            let Some(decoded) = opt.encoding.decode(line_bytes) else {
                 continue;
             };
+            let mut line: &str = &decoded;
+            if opt.delta {
+                if let Some(output) = line.strip_prefix('-') {
+                    items.push(Arc::new(RemovedItem::new(output)) as Arc<dyn SkimItem>);
+                    continue;
+                }
+                line = line.strip_prefix('+').unwrap_or(line);
+            }
+            let mut annotation = None;
+            if let Some((text, rest)) = opt.annotation_sep.as_deref().and_then(|sep| line.split_once(sep)) {
+                line = text;
+                annotation = Some(rest);
+            }
+            let truncated;
+            let mut is_truncated = false;
+            if let Some(max) = opt.max_item_length.filter(|&max| line.len() > max) {
+                opt.num_long_items.fetch_add(1, Ordering::Relaxed);
+                if opt.skip_long_items {
+                    continue;
+                }
+                truncated = format!("{}{ELLIPSIS}", &line[..line.floor_char_boundary(max)]);
+                line = &truncated;
+                is_truncated = true;
+            }
+            #[cfg(feature = "script")]
+            let mapped = opt.map_item.as_ref().and_then(|script| script.map_item(line));
+            #[cfg(not(feature = "script"))]
+            let mapped: Option<String> = None;
             let mut item = DefaultSkimItem::new(
-                line,
+                mapped.as_deref().unwrap_or(line),
                 opt.use_ansi_color,
                 &opt.transform_fields,
                 &opt.matching_fields,
                 &opt.delimiter,
             )
             .hidden_fields(&opt.hidden_fields, &opt.delimiter);
+            if mapped.is_some() {
+                item = item.with_output(line);
+            }
+            if let Some(field) = &opt.match_ranges_field {
+                item = item.match_ranges_field(line, field, &opt.delimiter);
+            }
+            if let Some(field) = &opt.group_field {
+                item = item.group_field(line, field, &opt.delimiter);
+            }
             if opt.disable_pattern.as_ref().is_some_and(|re| re.is_match(line)) {
                 item.disable();
             }
+            if is_truncated {
+                item.mark_truncated();
+            }
+            if let Some(annotation) = annotation {
+                let output_sep = opt.annotation_sep.as_deref().filter(|_| opt.print_annotation);
+                item = item.with_annotation(annotation, output_sep);
+            }
+            // a decoded line is output as read, unless the output was changed from it
+            if matches!(decoded, Cow::Owned(_)) && item.output() == decoded {
+                item = item.with_orig_bytes(line_bytes);
+            }
+            if let Some(input) = source {
+                item = item.with_source(input, line_bytes);
+            }
             items.push(Arc::new(item) as Arc<dyn SkimItem>);
         }
 
         (seq, items)
     }
 
+    /// Parses the lines of `input` in `range` without field options, the items sharing its buffer
+    ///
+    /// Only the lines holding ANSI codes or null bytes, which are not displayed as they are, get
+    /// their own text, as do the lines that are not valid UTF-8, decoded as by `process_chunk`.
+    fn process_shared(
+        input: &Arc<SharedInput>,
+        range: Range<usize>,
+        opt: &SkimItemReaderOption,
+    ) -> Vec<Arc<dyn SkimItem>> {
+        let bytes = &input.bytes()[range];
+        let mut items = Vec::with_capacity(memchr::memchr_iter(opt.line_ending, bytes).count() + 1);
+        for line in bytes.split(|&b| b == opt.line_ending) {
+            // Strip optional \r for \r\n endings.
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            let Some(decoded) = opt.encoding.decode(line) else {
+                continue;
+            };
+            let item = match decoded {
+                Cow::Owned(text) => {
+                    let item = DefaultSkimItem::new(&text, opt.use_ansi_color, &[], &[], &opt.delimiter);
+                    let item = if item.output() == text {
+                        item.with_orig_bytes(line)
+                    } else {
+                        item
+                    };
+                    item.with_source(input, line)
+                }
+                Cow::Borrowed(text) if memchr::memchr2(b'\x1b', b'\0', line).is_some() => {
+                    DefaultSkimItem::new(text, opt.use_ansi_color, &[], &[], &opt.delimiter).with_source(input, line)
+                }
+                Cow::Borrowed(text) => DefaultSkimItem::from_shared(input, text),
+            };
+            items.push(Arc::new(item) as Arc<dyn SkimItem>);
+        }
+        items
+    }
+
+    /// Lists the lines of the regular file at `path`, mapped in memory (`--input-file`)
+    ///
+    /// The file is cut into chunks of lines parsed by the pool, as by [`parallel_bufread`],
+    /// without being read first: the items of plain lines point into the map. A gzip or zstd file
+    /// is decompressed through [`of_bufread`](Self::of_bufread) instead.
+    fn of_mapped_file(&self, path: &Path) -> std::io::Result<SkimItemReceiver> {
+        let file = MappedFile::open(path)?;
+        #[cfg(feature = "decompress")]
+        if let Some(compression) = Compression::detect(file.bytes()) {
+            return Ok(self.of_bufread(compression.decoder(std::io::Cursor::new(file))?));
+        }
+        let input = Arc::new(SharedInput::Mapped {
+            file,
+            line_ending: self.option.line_ending,
+        });
+        let (tx_item, rx_item): (SkimItemSender, SkimItemReceiver) = kanal::bounded(1024 * 1024);
+        let option = self.option.clone();
+        let pool = Arc::clone(&self.thread_pool);
+        let (tx_results, rx_results) = kanal::bounded::<(usize, Vec<Arc<dyn SkimItem>>)>(pool.num_threads() * 4);
+        let (tx_pipeline_done, _) = kanal::bounded::<()>(0);
+        Self::spawn_reorder_thread(rx_results, tx_item, tx_pipeline_done);
+
+        thread::spawn(move || {
+            let len = input.bytes().len();
+            let line_ending = option.line_ending;
+            let (mut start, mut seq) = (0, 0);
+            while start < len {
+                // up to the end of the line at the chunk size
+                let end = (start + PARALLEL_READ_BUF_SIZE).min(len);
+                let end = memchr::memchr(line_ending, &input.bytes()[end..]).map_or(len, |i| end + i + 1);
+                let (tx, input, opt) = (tx_results.clone(), Arc::clone(&input), option.clone());
+                pool.spawn(move || {
+                    let chunk = &input.bytes()[start..end];
+                    let items = if opt.is_plain() {
+                        let trimmed = end - usize::from(chunk.last() == Some(&opt.line_ending));
+                        Self::process_shared(&input, start..trimmed, &opt)
+                    } else {
+                        Self::process_chunk(seq, chunk, Some(&input), &opt).1
+                    };
+                    let _ = tx.send((seq, items));
+                });
+                start = end;
+                seq += 1;
+            }
+        });
+        Ok(rx_item)
+    }
+
     /// Stage 4: receives item batches from workers and emits them through the
     /// downstream channel in the original sequence order.  Drops
     /// `tx_pipeline_done` on exit to signal the killer thread that the
@@ -520,30 +919,182 @@ impl CommandCollector for SkimItemReader {
         cmd: &str,
         components_to_stop: Arc<AtomicUsize>,
     ) -> (SkimItemReceiver, crate::prelude::Sender<i32>) {
+        if self.option.cmd_timeout.is_some() || self.option.cmd_retry > 0 {
+            return self.supervise(cmd, &components_to_stop);
+        }
         let send_error = self.option.show_error;
-        let (child, source) = get_command_output(cmd, send_error).expect("command not found");
-        self.parallel_bufread(source, child, &components_to_stop)
+        let (child, source) =
+            get_command_output(cmd, &self.option.shell, send_error, self.stderr_log.as_ref(), &self.env)
+                .expect("command not found");
+        let (rx_item, tx_interrupt, _) = self.parallel_bufread(source, child, &components_to_stop);
+        (rx_item, tx_interrupt)
     }
 
     fn set_thread_pool(&mut self, pool: Arc<ThreadPool>) {
         self.thread_pool = pool;
     }
+
+    fn set_stderr_log(&mut self, log: StderrLog) {
+        self.stderr_log = Some(log);
+    }
+
+    fn set_env(&mut self, env: &[(&str, String)]) {
+        self.env = env
+            .iter()
+            .map(|(key, value)| ((*key).to_owned(), value.clone()))
+            .collect();
+    }
+
+    fn set_typing_signal(&mut self, typing: TypingSignal) {
+        self.typing = typing;
+    }
+
+    fn num_long_items(&self) -> usize {
+        Self::num_long_items(self)
+    }
+
+    fn of_reader(&self, source: Box<dyn std::io::Read + Send>) -> SkimItemReceiver {
+        self.of_bufread(BufReader::new(source))
+    }
+
+    fn of_file(&self, path: &Path) -> std::io::Result<SkimItemReceiver> {
+        // a pipe (`<(cmd)`, `/dev/stdin`...) has no size to map, it is read as it comes
+        if std::fs::metadata(path)?.is_file() {
+            self.of_mapped_file(path)
+        } else {
+            Ok(self.of_reader(Box::new(std::fs::File::open(path)?)))
+        }
+    }
 }
 
-type CommandOutput = (Option<Child>, Box<dyn BufRead + Send>);
+impl SkimItemReader {
+    /// Runs `cmd` until it succeeds or outputs something, at most `cmd_retry` more times,
+    /// forwarding the items of each run
+    ///
+    /// A timed out run adds a line saying so to the items, with `show_error`.
+    fn supervise(
+        &self,
+        cmd: &str,
+        components_to_stop: &Arc<AtomicUsize>,
+    ) -> (SkimItemReceiver, crate::prelude::Sender<i32>) {
+        let (tx_item, rx_item): (SkimItemSender, SkimItemReceiver) = kanal::bounded(1024 * 1024);
+        let (tx_interrupt, rx_interrupt) = crate::prelude::bounded::<i32>(8);
+        let reader = Self {
+            option: Arc::clone(&self.option),
+            thread_pool: Arc::clone(&self.thread_pool),
+            stderr_log: self.stderr_log.clone(),
+            env: self.env.clone(),
+            typing: self.typing.clone(),
+        };
+        let cmd = cmd.to_owned();
+        let components_to_stop = Arc::clone(components_to_stop);
+        components_to_stop.fetch_add(1, Ordering::SeqCst);
+        thread::spawn(move || {
+            let option = &reader.option;
+            for attempt in 0..=option.cmd_retry {
+                let output = get_command_output(
+                    &cmd,
+                    &option.shell,
+                    option.show_error,
+                    reader.stderr_log.as_ref(),
+                    &reader.env,
+                );
+                let Ok((child, source)) = output else {
+                    break;
+                };
+                let run_components = Arc::new(AtomicUsize::new(0));
+                let (rx_run, tx_interrupt_run, status) = reader.parallel_bufread(source, child, &run_components);
+                let mut sent = 0;
+                let interrupted = loop {
+                    if rx_interrupt.try_recv().is_ok_and(|v| v.is_some()) {
+                        let _ = tx_interrupt_run.send(1);
+                        break true;
+                    }
+                    match rx_run.recv_timeout(Duration::from_millis(1)) {
+                        Ok(items) => {
+                            sent += items.len();
+                            if tx_item.send(items).is_err() {
+                                let _ = tx_interrupt_run.send(1);
+                                break true;
+                            }
+                        }
+                        Err(kanal::ReceiveErrorTimeout::Timeout) => {}
+                        Err(_) => break false,
+                    }
+                };
+                // the killer thread of the run records how the command ended
+                while run_components.load(Ordering::SeqCst) != 0 {
+                    thread::sleep(Duration::from_millis(1));
+                }
+                if interrupted {
+                    break;
+                }
+                let timed_out = status.timed_out.load(Ordering::SeqCst);
+                if timed_out && option.show_error {
+                    let timeout = option.cmd_timeout.unwrap_or_default();
+                    let message = format!("[command timed out after {timeout:?}]");
+                    let _ = tx_item.send(vec![Arc::new(message) as Arc<dyn SkimItem>]);
+                }
+                let failed = timed_out || status.failed.load(Ordering::SeqCst);
+                if !failed || sent > 0 || attempt == option.cmd_retry {
+                    break;
+                }
+                debug!(
+                    "command failed without output, retrying ({} of {})",
+                    attempt + 1,
+                    option.cmd_retry
+                );
+            }
+            components_to_stop.fetch_sub(1, Ordering::SeqCst);
+        });
+        (rx_item, tx_interrupt)
+    }
+}
+
+/// How the command read by [`SkimItemReader::parallel_bufread`] ended
+#[derive(Default)]
+struct CommandStatus {
+    /// Killed after `cmd_timeout`
+    timed_out: AtomicBool,
+    /// Exited with a failure status
+    failed: AtomicBool,
+}
 
-fn get_command_output(cmd: &str, send_error: bool) -> Result<CommandOutput, Box<dyn Error>> {
+type CommandOutput = (Option<(Child, ProcessGroup)>, Box<dyn BufRead + Send>);
+
+fn get_command_output(
+    cmd: &str,
+    shell: &Interpreter,
+    send_error: bool,
+    stderr_log: Option<&StderrLog>,
+    env: &[(String, String)],
+) -> Result<CommandOutput, Box<dyn Error>> {
     let (reader, writer) = std::io::pipe()?;
-    let mut command = crate::shell_cmd(cmd);
+    let mut command = shell.command(cmd);
+    command.envs(env.iter().map(|(key, value)| (key, value)));
     command.stdout(writer.try_clone()?);
     if send_error {
         trace!("redirecting stderr to the output");
         command.stderr(writer);
+    } else if stderr_log.is_some() {
+        command.stderr(Stdio::piped());
     } else {
         command.stderr(Stdio::null());
     }
 
-    Ok((command.spawn().ok(), Box::new(BufReader::new(reader))))
+    let mut child = process_group::spawn(&mut command).ok();
+    if let Some(log) = stderr_log
+        && let Some(stderr) = child.as_mut().and_then(|(child, _)| child.stderr.take())
+    {
+        let log = log.clone();
+        thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                log.push("source", &line);
+            }
+        });
+    }
+
+    Ok((child, Box::new(BufReader::new(reader))))
 }
 
 #[cfg(test)]
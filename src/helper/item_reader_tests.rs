@@ -42,6 +42,83 @@ fn of_bufread_reads_newline_separated_items() {
     assert_eq!(drain(rx), vec!["a", "b", "c"]);
 }
 
+#[test]
+fn of_bufread_truncates_long_lines() {
+    // The cut falls on a char boundary: `é` is two bytes and does not fit in 4.
+    let opt = SkimItemReaderOption::default().max_item_length(Some(4)).build();
+    let reader = SkimItemReader::new(opt);
+    let rx = reader.of_bufread(Cursor::new("short\nabcé\nok\n"));
+    let items: Vec<_> = std::iter::from_fn(|| rx.recv().ok()).flatten().collect();
+    let texts: Vec<_> = items.iter().map(|i| i.text().into_owned()).collect();
+    assert_eq!(texts, vec!["shor…", "abc…", "ok"]);
+    let truncated = |i: usize| {
+        items[i]
+            .as_any()
+            .downcast_ref::<DefaultSkimItem>()
+            .unwrap()
+            .is_truncated()
+    };
+    assert!(truncated(0) && truncated(1) && !truncated(2));
+    assert_eq!(reader.num_long_items(), 2);
+}
+
+#[test]
+fn of_bufread_skips_long_lines() {
+    let opt = SkimItemReaderOption::default()
+        .max_item_length(Some(4))
+        .skip_long_items(true)
+        .build();
+    let reader = SkimItemReader::new(opt);
+    let rx = reader.of_bufread(Cursor::new("short\nok\n"));
+    assert_eq!(drain(rx), vec!["ok"]);
+    assert_eq!(reader.num_long_items(), 1);
+}
+
+#[test]
+fn of_bufread_splits_annotations() {
+    let opt = SkimItemReaderOption::default().annotation_sep(Some("\x1f")).build();
+    let reader = SkimItemReader::new(opt);
+    let rx = reader.of_bufread(Cursor::new("main.rs\x1f2 KiB\nREADME\n"));
+    let items: Vec<_> = std::iter::from_fn(|| rx.recv().ok()).flatten().collect();
+    let texts: Vec<_> = items.iter().map(|i| i.text().into_owned()).collect();
+    assert_eq!(texts, vec!["main.rs", "README"]);
+    assert_eq!(items[0].annotation(), Some("2 KiB"));
+    assert_eq!(items[0].output(), "main.rs");
+    assert_eq!(items[1].annotation(), None);
+}
+
+#[test]
+fn of_bufread_prints_annotations_when_asked() {
+    let opt = SkimItemReaderOption::default()
+        .annotation_sep(Some("::"))
+        .print_annotation(true)
+        .build();
+    let reader = SkimItemReader::new(opt);
+    let rx = reader.of_bufread(Cursor::new("a::b::c\n"));
+    let items: Vec<_> = std::iter::from_fn(|| rx.recv().ok()).flatten().collect();
+    assert_eq!(items[0].text(), "a");
+    assert_eq!(items[0].annotation(), Some("b::c"));
+    assert_eq!(items[0].output(), "a::b::c");
+}
+
+#[cfg(feature = "script")]
+#[test]
+fn of_bufread_maps_the_text_of_the_items() {
+    let script = crate::script::Script::new(r#"if text.contains("/") { text.split("/")[-1] }"#).unwrap();
+    let opt = SkimItemReaderOption::default()
+        .map_item(Some(script))
+        .nth(["1"].into_iter())
+        .build();
+    let reader = SkimItemReader::new(opt);
+    let rx = reader.of_bufread(Cursor::new("src/main rs\nREADME\n"));
+    let items: Vec<_> = std::iter::from_fn(|| rx.recv().ok()).flatten().collect();
+    let texts: Vec<_> = items.iter().map(|i| i.text().into_owned()).collect();
+    assert_eq!(texts, vec!["main rs", "README"]);
+    assert_eq!(items[0].output(), "src/main rs");
+    assert_eq!(items[0].get_matching_ranges(), Some(&[(0, 5)][..]));
+    assert_eq!(items[1].output(), "README");
+}
+
 #[test]
 fn of_bufread_read0_splits_on_nul() {
     let opt = SkimItemReaderOption::default().read0(true).build();
@@ -95,7 +172,7 @@ fn from_options_maps_read0_and_ansi() {
 
 #[test]
 fn invoke_runs_a_command() {
-    // The command runs through `shell_cmd` (`sh -c` / `cmd /c`); pick syntax that
+    // The command runs through the default shell (`sh -c` / `cmd /C`); pick syntax that
     // emits two newline-separated lines on each platform. The reader strips a
     // trailing `\r`, so cmd.exe's CRLF output still yields bare "x"/"y".
     let cmd = if cfg!(windows) {
@@ -108,6 +185,16 @@ fn invoke_runs_a_command() {
     assert_eq!(drain(rx), vec!["x", "y"]);
 }
 
+#[cfg(unix)]
+#[test]
+fn invoke_without_shell_runs_the_words_verbatim() {
+    // With an empty shell, quotes still group words but nothing is expanded or piped.
+    let opt = SkimItemReaderOption::default().shell(Some("")).build();
+    let mut reader = SkimItemReader::new(opt);
+    let (rx, _tx) = reader.invoke("echo '$HOME' a|b", Arc::new(AtomicUsize::new(0)));
+    assert_eq!(drain(rx), vec!["$HOME a|b"]);
+}
+
 #[test]
 fn invoke_with_show_error_redirects_stderr() {
     // show_error routes the child's stderr into the item stream. On cmd.exe the
@@ -123,6 +210,60 @@ fn invoke_with_show_error_redirects_stderr() {
     assert_eq!(drain(rx), vec!["oops"]);
 }
 
+#[cfg(unix)]
+#[test]
+fn invoke_kills_the_command_after_cmd_timeout() {
+    let opt = SkimItemReaderOption::default()
+        .show_error(true)
+        .cmd_timeout(Some(Duration::from_millis(200)))
+        .build();
+    let mut reader = SkimItemReader::new(opt);
+    let start = Instant::now();
+    let (rx, _tx) = reader.invoke("echo early; sleep 10; echo late", Arc::new(AtomicUsize::new(0)));
+    assert_eq!(drain(rx), vec!["early", "[command timed out after 200ms]"]);
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[cfg(unix)]
+#[test]
+fn invoke_retries_a_command_failing_without_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let marker = dir.path().join("ran");
+    // fails the first time, succeeds the next ones
+    let cmd = format!(
+        "test -e '{0}' && echo ok || {{ touch '{0}'; exit 1; }}",
+        marker.display()
+    );
+
+    let mut reader = SkimItemReader::new(SkimItemReaderOption::default().cmd_retry(1).build());
+    let (rx, _tx) = reader.invoke(&cmd, Arc::new(AtomicUsize::new(0)));
+    assert_eq!(drain(rx), vec!["ok"]);
+
+    std::fs::remove_file(&marker).unwrap();
+    let mut reader = SkimItemReader::new(
+        SkimItemReaderOption::default()
+            .cmd_timeout(Some(Duration::from_secs(5)))
+            .build(),
+    );
+    let (rx, _tx) = reader.invoke(&cmd, Arc::new(AtomicUsize::new(0)));
+    assert_eq!(drain(rx), Vec::<String>::new());
+}
+
+#[cfg(unix)]
+#[test]
+fn invoke_writes_stderr_to_the_log() {
+    let log = StderrLog::default();
+    let mut reader = SkimItemReader::default();
+    reader.set_stderr_log(log.clone());
+    let (rx, _tx) = reader.invoke("echo out; echo oops 1>&2", Arc::new(AtomicUsize::new(0)));
+    assert_eq!(drain(rx), vec!["out"]);
+    let start = Instant::now();
+    while log.is_empty() && start.elapsed() < Duration::from_secs(5) {
+        thread::sleep(Duration::from_millis(2));
+    }
+    assert_eq!(log.last(10), vec!["[source] oops"]);
+}
+
 #[test]
 fn read0_false_restores_newline_ending() {
     let opt = SkimItemReaderOption::default().read0(true).read0(false).build();
@@ -164,3 +305,259 @@ fn of_bufread_applies_with_nth_transform() {
     // The selected field retains its trailing delimiter.
     assert_eq!(drain(rx), vec!["beta "]);
 }
+
+#[test]
+fn of_bufread_reads_delta_lines() {
+    let reader = SkimItemReader::new(SkimItemReaderOption::default().delta(true));
+    let rx = reader.of_bufread(Cursor::new("+a\nb\n-a\n++c\n"));
+    let items: Vec<_> = std::iter::from_fn(|| rx.recv().ok()).flatten().collect();
+    let texts: Vec<_> = items.iter().map(|i| i.text().into_owned()).collect();
+    assert_eq!(texts, vec!["a", "b", "a", "+c"]);
+    let removals: Vec<_> = items.iter().map(|i| RemovedItem::is(i.as_ref())).collect();
+    assert_eq!(removals, vec![false, false, true, false]);
+
+    // Without --delta the lines are kept as they are.
+    let rx = SkimItemReader::default().of_bufread(Cursor::new("+a\n-a\n"));
+    assert_eq!(drain(rx), vec!["+a", "-a"]);
+}
+
+#[test]
+fn of_bufread_defers_the_input_read_while_typing() {
+    let typing = TypingSignal::new(Duration::from_millis(500));
+    let mut reader = SkimItemReader::default();
+    reader.set_typing_signal(typing.clone());
+    typing.touch();
+    let rx = reader.of_bufread(Cursor::new("a\nb\n"));
+    // nothing is parsed until the query stays unchanged for the delay
+    assert!(rx.recv_timeout(Duration::from_millis(100)).is_err());
+    assert!(typing.is_typing());
+    assert_eq!(drain(rx), vec!["a", "b"]);
+    assert!(!typing.is_typing());
+}
+
+#[test]
+fn of_bufread_is_not_deferred_without_a_delay() {
+    let typing = TypingSignal::default();
+    let mut reader = SkimItemReader::default();
+    reader.set_typing_signal(typing.clone());
+    typing.touch();
+    assert!(!typing.is_typing());
+    assert_eq!(drain(reader.of_bufread(Cursor::new("a\n"))), vec!["a"]);
+}
+
+#[test]
+fn process_chunk_shares_the_buffer_of_plain_lines() {
+    let opt = SkimItemReaderOption::default().ansi(true).build();
+    let (_, items) = SkimItemReader::process_chunk(0, b"a\r\n\x1b[31mred\x1b[0m\n\nb\0c\nlast", None, &opt);
+    let texts: Vec<_> = items.iter().map(|item| item.text().into_owned()).collect();
+    assert_eq!(texts, vec!["a", "red", "", "bc", "last"]);
+    assert_eq!(items[3].output(), "b\0c");
+    // the items of the other lines are the same as with a field option
+    let nth = SkimItemReaderOption::default()
+        .ansi(true)
+        .nth(["1.."].into_iter())
+        .build();
+    let (_, slow) = SkimItemReader::process_chunk(0, b"a\r\n\x1b[31mred\x1b[0m\n\nb\0c\nlast", None, &nth);
+    let outputs = |items: &[Arc<dyn SkimItem>]| items.iter().map(|item| item.output().into_owned()).collect::<Vec<_>>();
+    assert_eq!(outputs(&items), outputs(&slow));
+}
+
+#[test]
+fn process_chunk_falls_back_to_each_line_on_invalid_utf8() {
+    let opt = SkimItemReaderOption::default().build();
+    let (_, items) = SkimItemReader::process_chunk(0, b"a\n\xff\nb\n", None, &opt);
+    let texts: Vec<_> = items.iter().map(|item| item.text().into_owned()).collect();
+    assert_eq!(texts, vec!["a", "b"]);
+}
+
+fn mapped(content: &[u8], opt: SkimItemReaderOption) -> Vec<String> {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    std::io::Write::write_all(&mut file, content).unwrap();
+    let reader = SkimItemReader::new(opt);
+    drain(reader.of_file(file.path()).unwrap())
+}
+
+#[test]
+fn of_file_lists_the_lines_of_the_mapped_file() {
+    let content = b"a\r\n\x1b[31mred\x1b[0m\n\xff\n\nb\0c\nlast";
+    assert_eq!(
+        mapped(content, SkimItemReaderOption::default().ansi(true)),
+        vec!["a", "red", "", "bc", "last"]
+    );
+    // the items changed by an option are parsed from the map as from a pipe
+    let nth = || SkimItemReaderOption::default().nth(["2"].into_iter());
+    let rx = SkimItemReader::new(nth()).of_bufread(Cursor::new(content.to_vec()));
+    assert_eq!(mapped(content, nth()), drain(rx));
+    assert_eq!(mapped(b"", SkimItemReaderOption::default()), Vec::<String>::new());
+}
+
+#[test]
+fn of_file_keeps_the_order_across_chunks() {
+    let lines: Vec<String> = (0..200_000).map(|i| format!("line {i}")).collect();
+    let content = lines.join("\n") + "\n";
+    assert!(content.len() > 4 * PARALLEL_READ_BUF_SIZE);
+    assert_eq!(mapped(content.as_bytes(), SkimItemReaderOption::default()), lines);
+}
+
+#[test]
+#[cfg(unix)]
+fn of_file_reads_a_fifo_as_it_comes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fifo");
+    nix::unistd::mkfifo(&path, nix::sys::stat::Mode::S_IRWXU).unwrap();
+    let writer = {
+        let path = path.clone();
+        std::thread::spawn(move || std::fs::write(path, "a\nb\n").unwrap())
+    };
+    let rx = SkimItemReader::default().of_file(&path).unwrap();
+    assert_eq!(drain(rx), vec!["a", "b"]);
+    writer.join().unwrap();
+}
+
+#[test]
+#[cfg(unix)]
+fn of_file_checks_the_lines_of_a_file_rewritten_after_it_is_mapped() {
+    use std::io::{Seek, SeekFrom, Write};
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all("h\u{e9}llo\nworld\n".as_bytes()).unwrap();
+    let rx = SkimItemReader::default().of_file(file.path()).unwrap();
+    let mut items = Vec::new();
+    while let Ok(batch) = rx.recv() {
+        items.extend(batch);
+    }
+    assert_eq!(items[0].text(), "h\u{e9}llo");
+    // overwritten in place, the map sees the new bytes: `é` becomes invalid UTF-8
+    file.seek(SeekFrom::Start(1)).unwrap();
+    file.write_all(b"\xff\xff").unwrap();
+    file.flush().unwrap();
+    assert_eq!(items[0].text(), "h");
+    assert_eq!(items[1].text(), "world");
+}
+
+#[test]
+fn of_file_fails_on_a_missing_file() {
+    let reader = SkimItemReader::default();
+    assert!(reader.of_file(Path::new("/nonexistent/input")).is_err());
+}
+
+#[cfg(feature = "decompress")]
+fn gzip(content: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    std::io::Write::write_all(&mut encoder, content).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[test]
+#[cfg(feature = "decompress")]
+fn of_file_decompresses_gzip_and_zstd_files() {
+    // two gzip members in a row, as with `gzip -c a b > ab.gz`
+    let mut content = gzip(b"apple\nbanana\n");
+    content.extend(gzip(b"cherry\n"));
+    assert_eq!(
+        mapped(&content, SkimItemReaderOption::default()),
+        vec!["apple", "banana", "cherry"]
+    );
+    let content = zstd::encode_all(&b"a\tred\nb\tblue"[..], 0).unwrap();
+    let nth = SkimItemReaderOption::default().nth(["2"].into_iter());
+    assert_eq!(mapped(&content, nth), vec!["a\tred", "b\tblue"]);
+}
+
+#[test]
+#[cfg(feature = "decompress")]
+fn decompressed_stdin_recognizes_the_format_of_a_trickling_pipe() {
+    use crate::helper::decompress::Decompressed;
+
+    /// Gives one byte per read, as a slow pipe may
+    struct Trickle(Cursor<Vec<u8>>);
+    impl std::io::Read for Trickle {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = buf.len().min(1);
+            self.0.read(&mut buf[..len])
+        }
+    }
+
+    let read = |content: Vec<u8>| {
+        let source = BufReader::with_capacity(1, Trickle(Cursor::new(content)));
+        drain(SkimItemReader::default().of_bufread(Decompressed::new(source)))
+    };
+    assert_eq!(read(gzip(b"x\ny\n")), vec!["x", "y"]);
+    // plain input goes through, even shorter than a magic number
+    assert_eq!(read(b"plain\ntext".to_vec()), vec!["plain", "text"]);
+    assert_eq!(read(b"\x1f".to_vec()), vec!["\x1f"]);
+    assert_eq!(read(Vec::new()), Vec::<String>::new());
+    // a corrupt stream ends the input
+    assert_eq!(read(b"\x1f\x8bcorrupt".to_vec()), Vec::<String>::new());
+}
+
+#[test]
+fn encoding_decides_how_invalid_utf8_lines_are_read() {
+    let content = b"caf\xe9\nplain\n\xff\x1b[1mx\n";
+    let read = |opt: SkimItemReaderOption| {
+        let rx = SkimItemReader::new(opt).of_bufread(Cursor::new(content.to_vec()));
+        let mut items = Vec::new();
+        while let Ok(batch) = rx.recv() {
+            items.extend(batch);
+        }
+        items
+    };
+    let texts = |items: &[Arc<dyn SkimItem>]| items.iter().map(|item| item.text().into_owned()).collect::<Vec<_>>();
+    let outputs = |items: &[Arc<dyn SkimItem>]| {
+        items
+            .iter()
+            .map(|item| item.output_bytes().into_owned())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(texts(&read(SkimItemReaderOption::default())), vec!["plain"]);
+    let opt = || SkimItemReaderOption::default().encoding(Encoding::Lossy);
+    assert_eq!(texts(&read(opt())), vec!["caf\u{fffd}", "plain", "\u{fffd}?[1mx"]);
+    let latin1 = read(SkimItemReaderOption::default().encoding(Encoding::Latin1).ansi(true));
+    assert_eq!(texts(&latin1), vec!["caf\u{e9}", "plain", "\u{ff}x"]);
+    // the lines are output as read, with or without field options
+    assert_eq!(outputs(&latin1), vec![&b"caf\xe9"[..], b"plain", b"\xff\x1b[1mx"]);
+    // unless the output is not the line, here with its escape code replaced or truncated
+    let expected: Vec<&[u8]> = vec![b"caf\xe9", b"plain", "\u{fffd}?[1mx".as_bytes()];
+    assert_eq!(outputs(&read(opt())), expected);
+    assert_eq!(outputs(&read(opt().nth(["1"].into_iter()))), expected);
+    let items = read(opt().max_item_length(Some(3)));
+    assert_eq!(outputs(&items)[0], "caf\u{2026}".as_bytes());
+}
+
+#[test]
+fn of_file_records_the_position_of_the_lines() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    std::io::Write::write_all(&mut file, b"first\r\n\x1b[1mbold\n\xff\nlast").unwrap();
+    let positions = |opt: SkimItemReaderOption| {
+        let rx = SkimItemReader::new(opt).of_file(file.path()).unwrap();
+        let mut positions = Vec::new();
+        while let Ok(batch) = rx.recv() {
+            positions.extend(batch.iter().map(|item| {
+                let position = item.source_position().unwrap();
+                (position.line, position.offset)
+            }));
+        }
+        positions
+    };
+    let expected = vec![(1, 0), (2, 7), (3, 16), (4, 18)];
+    let opt = || SkimItemReaderOption::default().encoding(Encoding::Lossy);
+    assert_eq!(positions(opt()), expected);
+    assert_eq!(positions(opt().nth(["1"].into_iter())), expected);
+    // the items of a pipe have none
+    let rx = SkimItemReader::default().of_bufread(Cursor::new(b"a\nb".to_vec()));
+    while let Ok(batch) = rx.recv() {
+        assert!(batch.iter().all(|item| item.source_position().is_none()));
+    }
+}
+
+#[test]
+fn mapped_file_counts_the_lines_across_checkpoints() {
+    let line = "x".repeat(999) + "\n";
+    let content = line.repeat(3000);
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    std::io::Write::write_all(&mut file, content.as_bytes()).unwrap();
+    let mapped = MappedFile::open(file.path()).unwrap();
+    for n in [0, 1, 1048, 1049, 2097, 2999] {
+        assert_eq!(mapped.line_number(n * 1000, b'\n'), n + 1);
+    }
+    assert_eq!(mapped.line_number(content.len(), b'\n'), 3001);
+}
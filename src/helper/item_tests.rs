@@ -171,7 +171,7 @@ fn test_ansi_matching_and_display() {
     // The line should have the original ANSI codes intact
     // We can't easily verify the exact ANSI codes in the output, but we can check
     // that it's not empty and has multiple spans (original text + highlighted match)
-    assert!(!line.spans.is_empty());
+    assert_ne!(line.spans, []);
 }
 
 #[test]
@@ -205,7 +205,7 @@ fn test_ansi_char_indices_mapping() {
 
     // display() should map these to positions 6,7 in original text
     let line = item.display(context);
-    assert!(!line.spans.is_empty());
+    assert_ne!(line.spans, []);
 }
 
 #[test]
@@ -495,7 +495,7 @@ fn test_normalize_ranges_sorts_and_merges() {
     assert_eq!(normalize_ranges(&[(0, 4), (2, 6)]), vec![(0, 6)]);
     assert_eq!(normalize_ranges(&[(0, 3), (3, 6)]), vec![(0, 6)]);
     assert_eq!(normalize_ranges(&[(2, 2), (0, 1)]), vec![(0, 1)]);
-    assert!(normalize_ranges(&[]).is_empty());
+    assert_eq!(normalize_ranges(&[]), []);
 }
 
 #[test]
@@ -526,7 +526,7 @@ fn test_project_match_indices_drops_hidden_and_remaps() {
     assert_eq!(indices, vec![6, 7, 8]);
     // A match entirely inside the hidden field yields nothing.
     let indices = project_match_indices("apple RED 001", &Matches::CharRange(6, 9), &map);
-    assert!(indices.is_empty());
+    assert_eq!(indices, Vec::<usize>::new());
 }
 
 #[test]
@@ -655,3 +655,67 @@ fn test_hidden_field_ansi_highlight_remapped() {
     assert!(line.spans.iter().any(|span| span.style.bg == Some(Color::Yellow)));
     assert!(line.spans.iter().any(|span| span.style.fg == Some(Color::Red)));
 }
+
+#[test]
+fn test_group_field() {
+    use crate::field::FieldRange;
+    use regex::Regex;
+    let delimiter = Regex::new(":").unwrap();
+    let line = "src:main.rs";
+    let item = DefaultSkimItem::new(line, false, &[FieldRange::RightInf(2)], &[], &delimiter).group_field(
+        line,
+        &FieldRange::Single(1),
+        &delimiter,
+    );
+    assert_eq!(item.text(), "main.rs");
+    // the trailing delimiter is not part of the group
+    assert_eq!(SkimItem::group(&item).as_deref(), Some("src"));
+
+    let ungrouped =
+        DefaultSkimItem::new("foo", false, &[], &[], &delimiter).group_field("foo", &FieldRange::Single(2), &delimiter);
+    assert!(SkimItem::group(&ungrouped).is_none());
+}
+
+#[test]
+fn test_match_ranges_field() {
+    use crate::field::FieldRange;
+    use regex::Regex;
+    let delimiter = Regex::new(r"\t").unwrap();
+    let line = "8-11, 0-3,bogus\tfoo bar baz";
+    let item = DefaultSkimItem::new(line, false, &[FieldRange::RightInf(2)], &[], &delimiter).match_ranges_field(
+        line,
+        &FieldRange::Single(1),
+        &delimiter,
+    );
+    assert_eq!(item.text(), "foo bar baz");
+    // Malformed entries are skipped and the ranges are sorted.
+    assert_eq!(item.external_ranges(), Some(&[(0, 3), (8, 11)][..]));
+
+    let plain = DefaultSkimItem::new("foo", false, &[], &[], &delimiter).match_ranges_field(
+        "foo",
+        &FieldRange::Single(2),
+        &delimiter,
+    );
+    assert!(plain.external_ranges().is_none());
+}
+
+#[test]
+fn from_shared_is_the_item_of_the_line() {
+    let chunk = Arc::new(SharedInput::Chunk(Box::from(b"first\nsecond".as_slice())));
+    let line = std::str::from_utf8(&chunk.bytes()[6..]).unwrap();
+    let item = DefaultSkimItem::from_shared(&chunk, line);
+    let plain = DefaultSkimItem::new("second", false, &[], &[], &Regex::new(" ").unwrap());
+    assert_eq!(item.text(), plain.text());
+    assert_eq!(item.output(), "second");
+    assert_eq!(item.get_display_text(), "second");
+    assert!(item.matching_ranges().is_none() && item.orig_text().is_none());
+    drop(chunk);
+    assert_eq!(item.text(), "second");
+}
+
+#[test]
+#[should_panic(expected = "not part of the input")]
+fn from_shared_rejects_a_line_of_another_input() {
+    let chunk = Arc::new(SharedInput::Chunk(Box::from(b"first".as_slice())));
+    let _ = DefaultSkimItem::from_shared(&chunk, "first");
+}
@@ -0,0 +1,125 @@
+//! Read-only memory maps of the files read with `--input-file`
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Bytes between two checkpoints of the line index, see [`MappedFile::line_number`]
+const LINE_INDEX_STEP: usize = 1 << 20;
+
+/// The content of a file, mapped in memory where supported and read otherwise
+///
+/// The pages are only read from the disk when the lines are, so that huge files are listed
+/// without being copied first. Only regular files are mapped, pipes and devices having no size.
+/// Changing the file while it is mapped changes the items read from it, down to their valid UTF-8
+/// start (see [`SharedInput::text`](crate::helper::item::SharedInput::text)), and truncating it
+/// kills skim with `SIGBUS` once the pages past the new end are read, as with any memory map.
+#[derive(Debug)]
+pub(crate) struct MappedFile {
+    #[cfg(unix)]
+    map: Option<(std::ptr::NonNull<std::ffi::c_void>, std::num::NonZeroUsize)>,
+    #[cfg(not(unix))]
+    content: Box<[u8]>,
+    /// The number of lines before each [`LINE_INDEX_STEP`] bytes
+    line_index: OnceLock<Vec<usize>>,
+}
+
+// SAFETY: the map is private, read-only and never handed out mutably
+#[cfg(unix)]
+unsafe impl Send for MappedFile {}
+#[cfg(unix)]
+unsafe impl Sync for MappedFile {}
+
+impl MappedFile {
+    /// Maps the regular file at `path`
+    #[cfg(unix)]
+    pub(crate) fn open(path: &Path) -> io::Result<Self> {
+        use nix::sys::mman::{MapFlags, ProtFlags, mmap};
+
+        let file = File::open(path)?;
+        let len = usize::try_from(file.metadata()?.len()).map_err(io::Error::other)?;
+        // an empty file cannot be mapped
+        let Some(len) = std::num::NonZeroUsize::new(len) else {
+            return Ok(Self {
+                map: None,
+                line_index: OnceLock::new(),
+            });
+        };
+        // SAFETY: a new private read-only map, see the doc comment about changes to the file
+        let ptr = unsafe { mmap(None, len, ProtFlags::PROT_READ, MapFlags::MAP_PRIVATE, &file, 0) }?;
+        Ok(Self {
+            map: Some((ptr, len)),
+            line_index: OnceLock::new(),
+        })
+    }
+
+    /// Reads the file at `path`
+    #[cfg(not(unix))]
+    pub(crate) fn open(path: &Path) -> io::Result<Self> {
+        use std::io::Read as _;
+
+        let mut content = Vec::new();
+        File::open(path)?.read_to_end(&mut content)?;
+        Ok(Self {
+            content: content.into_boxed_slice(),
+            line_index: OnceLock::new(),
+        })
+    }
+
+    /// The content of the file
+    #[must_use]
+    pub(crate) fn bytes(&self) -> &[u8] {
+        #[cfg(unix)]
+        {
+            match self.map {
+                // SAFETY: the map is `len` bytes long and lives as long as `self`
+                Some((ptr, len)) => unsafe { std::slice::from_raw_parts(ptr.as_ptr().cast::<u8>(), len.get()) },
+                None => &[],
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            &self.content
+        }
+    }
+
+    /// The 1-based number of the line holding `offset`, the lines ending with `line_ending`
+    ///
+    /// The first call counts the lines of the whole file once, keeping their number at every
+    /// [`LINE_INDEX_STEP`] bytes, so that the next ones only count from the closest checkpoint.
+    /// All the calls must use the same `line_ending`.
+    #[must_use]
+    pub(crate) fn line_number(&self, offset: usize, line_ending: u8) -> usize {
+        let bytes = self.bytes();
+        let index = self.line_index.get_or_init(|| {
+            let mut lines = 0;
+            let mut index = Vec::with_capacity(bytes.len() / LINE_INDEX_STEP + 1);
+            for step in bytes.chunks(LINE_INDEX_STEP) {
+                index.push(lines);
+                lines += memchr::memchr_iter(line_ending, step).count();
+            }
+            index
+        });
+        // the end of a file of a whole number of steps has no checkpoint of its own
+        let checkpoint = (offset / LINE_INDEX_STEP).min(index.len().saturating_sub(1));
+        let before = index.get(checkpoint).copied().unwrap_or_default();
+        before + memchr::memchr_iter(line_ending, &bytes[checkpoint * LINE_INDEX_STEP..offset]).count() + 1
+    }
+}
+
+impl AsRef<[u8]> for MappedFile {
+    fn as_ref(&self) -> &[u8] {
+        self.bytes()
+    }
+}
+
+#[cfg(unix)]
+impl Drop for MappedFile {
+    fn drop(&mut self) {
+        if let Some((ptr, len)) = self.map {
+            // SAFETY: the map is not used anymore
+            let _ = unsafe { nix::sys::mman::munmap(ptr, len.get()) };
+        }
+    }
+}
@@ -1,6 +1,9 @@
 //! Skim helpers
+#[cfg(feature = "decompress")]
+pub mod decompress;
 pub mod item;
 pub(crate) mod item_reader;
 #[macro_use]
 pub(crate) mod macros;
+pub(crate) mod mapped_file;
 pub(crate) mod selector;
@@ -0,0 +1,248 @@
+//! Shell used to run source, preview and execute commands
+//!
+//! Commands go through `sh -c` on Unix and `cmd /C` on Windows, unless
+//! `--with-shell` or `$SKIM_SHELL` names another interpreter. The flags passed
+//! before the command and the quoting of `{}` placeholders depend on which
+//! family that interpreter belongs to. An empty `--with-shell` runs commands
+//! directly, without any shell.
+
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+use crate::SkimOptions;
+
+/// Family of a command interpreter, deciding how commands are passed and quoted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ShellKind {
+    /// `sh`, `bash`, `zsh` and friends: `-c`, single-quoted arguments
+    Posix,
+    /// `cmd.exe`: `/C`, double-quoted arguments with `^` escapes
+    Cmd,
+    /// Windows `PowerShell` or `pwsh`: `-Command`, single-quoted arguments
+    PowerShell,
+    /// No shell: the command is split into words and run as is
+    Direct,
+}
+
+/// Command interpreter, its flags and its family
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Interpreter {
+    program: String,
+    args: Vec<String>,
+    kind: ShellKind,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new(if cfg!(windows) { "cmd" } else { "sh" })
+    }
+}
+
+impl Interpreter {
+    /// Interpreter set by `--with-shell`, falling back to [`Interpreter::from_env`]
+    pub(crate) fn from_options(options: &SkimOptions) -> Self {
+        options.with_shell.as_deref().map_or_else(Self::from_env, Self::parse)
+    }
+
+    /// Interpreter set by `$SKIM_SHELL`, or the platform default
+    pub(crate) fn from_env() -> Self {
+        match env::var("SKIM_SHELL") {
+            Ok(spec) if !spec.trim().is_empty() => Self::parse(&spec),
+            _ => Self::default(),
+        }
+    }
+
+    /// Parse a program optionally followed by its flags, e.g. `bash -c`
+    ///
+    /// An empty spec means no shell at all.
+    pub(crate) fn parse(spec: &str) -> Self {
+        let words = shlex::split(spec).unwrap_or_default();
+        match words.split_first() {
+            None => Self {
+                program: String::new(),
+                args: Vec::new(),
+                kind: ShellKind::Direct,
+            },
+            Some((program, [])) => Self::new(program),
+            Some((program, args)) => Self {
+                args: args.to_vec(),
+                ..Self::new(program)
+            },
+        }
+    }
+
+    /// Interpreter running `program`, its family and flags guessed from the file name
+    pub(crate) fn new(program: &str) -> Self {
+        let stem = Path::new(program)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let (kind, args): (_, &[&str]) = match stem.as_str() {
+            "cmd" => (ShellKind::Cmd, &["/C"]),
+            "powershell" | "pwsh" => (ShellKind::PowerShell, &["-NoProfile", "-Command"]),
+            _ => (ShellKind::Posix, &["-c"]),
+        };
+        Self {
+            program: program.to_string(),
+            args: args.iter().map(ToString::to_string).collect(),
+            kind,
+        }
+    }
+
+    /// Program and arguments running `cmd`, for [`ShellKind::Direct`]
+    fn split(cmd: &str) -> (String, Vec<String>) {
+        let mut words = shlex::split(cmd).unwrap_or_default().into_iter();
+        (words.next().unwrap_or_default(), words.collect())
+    }
+
+    /// Build the process running `cmd` through the interpreter
+    pub(crate) fn command(&self, cmd: &str) -> Command {
+        if self.kind == ShellKind::Direct {
+            let (program, args) = Self::split(cmd);
+            let mut c = Command::new(program);
+            c.args(args);
+            return c;
+        }
+        let mut c = Command::new(&self.program);
+        c.args(&self.args);
+        #[cfg(windows)]
+        if self.kind == ShellKind::Cmd {
+            use std::os::windows::process::CommandExt as _;
+            // `cmd.exe` does not parse its command line using MSVC rules, so the default
+            // `Command::arg` escaping (quoting/backslash-escaping) corrupts shell
+            // metacharacters like `|`, `&`, `>` and embedded quotes. Pass the command
+            // string verbatim via `raw_arg` so cmd.exe sees exactly what the user wrote.
+            c.raw_arg(cmd);
+            return c;
+        }
+        c.arg(cmd);
+        c
+    }
+
+    /// Build the same process for `portable_pty`
+    pub(crate) fn pty_command(&self, cmd: &str) -> portable_pty::CommandBuilder {
+        if self.kind == ShellKind::Direct {
+            let (program, args) = Self::split(cmd);
+            let mut c = portable_pty::CommandBuilder::new(program);
+            c.args(args);
+            return c;
+        }
+        let mut c = portable_pty::CommandBuilder::new(&self.program);
+        c.args(&self.args);
+        c.arg(cmd);
+        c
+    }
+
+    /// Quote `arg` so that the interpreter passes it through as a single word
+    pub(crate) fn quote(&self, arg: &str) -> String {
+        match self.kind {
+            ShellKind::Posix | ShellKind::Direct => format!("'{}'", arg.replace('\'', "'\\''")),
+            ShellKind::PowerShell => format!("'{}'", arg.replace('\'', "''")),
+            ShellKind::Cmd => Self::quote_cmd(arg),
+        }
+    }
+
+    /// Quote `arg` for `cmd.exe` and the program it runs
+    ///
+    /// The argument is first quoted for the C runtime splitting the command line of the program,
+    /// then each char special to `cmd.exe` is escaped with `^`, the quotes included so that it
+    /// never sees a quoted part, where it would keep the carets.
+    fn quote_cmd(arg: &str) -> String {
+        let mut quoted = String::from('"');
+        let mut backslashes = 0;
+        for c in arg.chars() {
+            if c == '\\' {
+                backslashes += 1;
+                continue;
+            }
+            let escapes = if c == '"' { 2 * backslashes + 1 } else { backslashes };
+            quoted.extend(std::iter::repeat_n('\\', escapes));
+            quoted.push(c);
+            backslashes = 0;
+        }
+        quoted.extend(std::iter::repeat_n('\\', 2 * backslashes));
+        quoted.push('"');
+
+        let mut escaped = String::with_capacity(quoted.len() * 2);
+        for c in quoted.chars() {
+            if matches!(c, '^' | '&' | '|' | '<' | '>' | '(' | ')' | '%' | '!' | '"') {
+                escaped.push('^');
+            }
+            escaped.push(c);
+        }
+        escaped
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use super::*;
+
+    fn argv(interpreter: &Interpreter, cmd: &str) -> Vec<String> {
+        let cmd = interpreter.command(cmd);
+        std::iter::once(cmd.get_program())
+            .chain(cmd.get_args())
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn kind_from_program_name() {
+        assert_eq!(Interpreter::new("sh").kind, ShellKind::Posix);
+        assert_eq!(Interpreter::new("/usr/bin/bash").kind, ShellKind::Posix);
+        assert_eq!(Interpreter::new("cmd").kind, ShellKind::Cmd);
+        assert_eq!(Interpreter::new("C:/Windows/System32/CMD.EXE").kind, ShellKind::Cmd);
+        assert_eq!(Interpreter::new("powershell.exe").kind, ShellKind::PowerShell);
+        assert_eq!(Interpreter::new("pwsh").kind, ShellKind::PowerShell);
+    }
+
+    #[test]
+    fn command_args_per_kind() {
+        assert_eq!(argv(&Interpreter::new("bash"), "echo hi"), ["bash", "-c", "echo hi"]);
+        assert_eq!(
+            argv(&Interpreter::new("pwsh"), "echo hi"),
+            ["pwsh", "-NoProfile", "-Command", "echo hi"]
+        );
+        #[cfg(not(windows))]
+        assert_eq!(argv(&Interpreter::new("cmd"), "echo hi"), ["cmd", "/C", "echo hi"]);
+    }
+
+    #[test]
+    fn parse_explicit_flags() {
+        assert_eq!(
+            argv(&Interpreter::parse("bash --norc -c"), "echo hi"),
+            ["bash", "--norc", "-c", "echo hi"]
+        );
+        assert_eq!(argv(&Interpreter::parse("zsh"), "echo hi"), ["zsh", "-c", "echo hi"]);
+    }
+
+    #[test]
+    fn direct_splits_the_command() {
+        let direct = Interpreter::parse("");
+        assert_eq!(direct.kind, ShellKind::Direct);
+        let cmd = format!("echo {} $HOME", direct.quote("it's | a"));
+        assert_eq!(argv(&direct, &cmd), ["echo", "it's | a", "$HOME"]);
+    }
+
+    #[test]
+    fn quote_per_kind() {
+        assert_eq!(Interpreter::new("sh").quote("it's"), r"'it'\''s'");
+        assert_eq!(Interpreter::new("pwsh").quote("it's"), "'it''s'");
+        assert_eq!(Interpreter::new("cmd").quote("it's"), r#"^"it's^""#);
+    }
+
+    #[test]
+    fn cmd_quoting_escapes_the_metacharacters() {
+        let cmd = Interpreter::new("cmd");
+        assert_eq!(cmd.quote("a & del /q *"), r#"^"a ^& del /q *^""#);
+        assert_eq!(
+            cmd.quote("%PATH% | (x) <y> !z!"),
+            r#"^"^%PATH^% ^| ^(x^) ^<y^> ^!z^!^""#
+        );
+        // backslashes are only doubled before a quote
+        assert_eq!(cmd.quote(r#"C:\dir\ say "hi"\"#), r#"^"C:\dir\ say \^"hi\^"\\^""#);
+        assert_eq!(cmd.quote(""), r#"^"^""#);
+    }
+}
@@ -2,7 +2,9 @@
 //!
 //! This module provides the core item types used by skim, including ranked items,
 //! item pools for efficient storage, and ranking criteria for sorting matches.
+use std::borrow::Cow;
 use std::cmp::min;
+use std::collections::{HashMap, HashSet};
 use std::default::Default;
 use std::hash::Hash;
 use std::ops::Deref;
@@ -14,6 +16,7 @@ use clap::ValueEnum;
 #[cfg(feature = "cli")]
 use clap::builder::PossibleValue;
 
+use crate::field::{FieldRange, get_string_by_field};
 use crate::spinlock::{SpinLock, SpinLockGuard};
 use crate::{MatchRange, Rank, SkimItem};
 use tokio::sync::Notify;
@@ -24,12 +27,15 @@ use tokio::sync::Notify;
 #[derive(Debug)]
 pub struct RankBuilder {
     criterion: Vec<RankCriteria>,
+    /// Whether equal ranks list the items with the highest index first
+    last_first: bool,
 }
 
 impl Default for RankBuilder {
     fn default() -> Self {
         Self {
             criterion: vec![RankCriteria::Score, RankCriteria::Begin, RankCriteria::End],
+            last_first: false,
         }
     }
 }
@@ -43,7 +49,42 @@ impl RankBuilder {
         }
 
         criterion.dedup();
-        Self { criterion }
+        Self {
+            criterion,
+            last_first: false,
+        }
+    }
+
+    /// Creates the rank builder of `--tiebreak`, ordered by [`ordered_by`](Self::ordered_by)
+    #[must_use]
+    pub fn from_options(options: &crate::SkimOptions) -> Self {
+        Self::new(options.tiebreak.clone()).ordered_by(options)
+    }
+
+    /// Applies `--reverse-sort`, `--tie-order` and `--tac` to the criteria
+    ///
+    /// The index of an item is its position in the input, whatever `--tac` says: `--tac` reverses
+    /// the `index` criteria and the order of equal ranks instead, so that the index of an item
+    /// does not depend on the batch it was read in.
+    #[must_use]
+    pub fn ordered_by(mut self, options: &crate::SkimOptions) -> Self {
+        self.criterion = self
+            .criterion
+            .iter()
+            .map(|&criterion| {
+                let criterion = if options.reverse_sort {
+                    criterion.reversed()
+                } else {
+                    criterion
+                };
+                match criterion {
+                    RankCriteria::Index | RankCriteria::NegIndex if options.tac => criterion.reversed(),
+                    _ => criterion,
+                }
+            })
+            .collect();
+        self.last_first = options.tac != (options.tie_order == crate::options::TieOrder::Reverse);
+        self
     }
 
     /// Returns the tiebreak criteria slice.
@@ -52,6 +93,19 @@ impl RankBuilder {
         &self.criterion
     }
 
+    /// The sort key of `rank`: [`Rank::sort_key`] followed by the index, in the order of the
+    /// equal ranks
+    #[must_use]
+    pub fn sort_key(&self, rank: &Rank) -> [i32; 6] {
+        let key = rank.sort_key(&self.criterion);
+        let tie = if self.last_first {
+            rank.index.saturating_neg()
+        } else {
+            rank.index
+        };
+        [key[0], key[1], key[2], key[3], key[4], tie]
+    }
+
     /// Computes the byte offset of the first character after the last path separator
     /// (`/` or `\`) in `text`.  Returns `0` when no separator is present.
     fn path_name_offset(text: &str) -> i32 {
@@ -122,7 +176,7 @@ pub struct MatchedItem {
     pub matched_range: Option<MatchRange>,
     /// Sort key precomputed at construction time from `rank` and the tiebreak
     /// criteria.  Caching avoids recomputing it on every comparison during sort.
-    sort_key: [i32; 5],
+    sort_key: [i32; 6],
 }
 
 impl std::fmt::Debug for MatchedItem {
@@ -162,7 +216,7 @@ impl MatchedItem {
             item,
             rank,
             matched_range,
-            sort_key: rank.sort_key(rank_builder.criteria()),
+            sort_key: rank_builder.sort_key(&rank),
         }
     }
     /// Merge two sorted `Vec<MatchedItem>` lists into one, preserving sort order by rank.
@@ -226,20 +280,20 @@ impl MatchedItem {
     /// Merge `incoming` into an already-sorted `existing` vector in-place.
     ///
     /// This function chooses between two strategies:
-    /// - If `incoming` is small (≤ 256 items), insert them one-by-one using
-    ///   binary search to find the insertion point.  Each insert is O(n) due
-    ///   to element shifting, giving O(m·n) overall, but the constant factor
-    ///   is small for tiny m and avoids any extra allocation.
+    /// - If `incoming` holds a single item, insert it using binary search to
+    ///   find the insertion point.
     /// - Otherwise, perform a backwards in-place merge that writes the result
     ///   directly into `existing`'s buffer (after a single `reserve`).  This
     ///   is O(n+m) time with **zero additional heap allocation** beyond the
-    ///   amortised `Vec::reserve`.
+    ///   amortised `Vec::reserve`.  Only the items of `existing` ranking
+    ///   below the best incoming item are moved, each one once: inserting
+    ///   the items one-by-one instead would shift that tail once per item,
+    ///   which is what stalls the UI when small batches stream into
+    ///   millions of results.
     ///
     /// `existing` must be sorted according to the same ordering used by
     /// `MatchedItem::cmp`.
     pub fn merge_into_sorted(existing: &mut Vec<MatchedItem>, incoming: Vec<MatchedItem>) {
-        const SMALL_INSERT_THRESHOLD: usize = 256;
-
         if incoming.is_empty() {
             return;
         }
@@ -258,13 +312,12 @@ impl MatchedItem {
             return;
         }
 
-        if incoming.len() <= SMALL_INSERT_THRESHOLD {
-            for item in incoming {
-                let pos = existing.binary_search_by(|e| e.cmp(&item)).unwrap_or_else(|p| p);
+        match <[MatchedItem; 1]>::try_from(incoming) {
+            Ok([item]) => {
+                let pos = existing.partition_point(|e| e <= &item);
                 existing.insert(pos, item);
             }
-        } else {
-            Self::merge_backwards(existing, incoming);
+            Err(incoming) => Self::merge_backwards(existing, incoming),
         }
     }
 
@@ -339,6 +392,36 @@ impl MatchedItem {
     pub fn downcast_item<T: SkimItem>(&self) -> Option<&T> {
         (*self.item).as_any().downcast_ref::<T>()
     }
+
+    /// Byte offsets of the matched characters in the item's `text()`, sorted
+    ///
+    /// Empty if the item was not matched against a query.
+    #[must_use]
+    pub fn matched_byte_offsets(&self) -> Vec<usize> {
+        let text = self.item.text();
+        match &self.matched_range {
+            None => Vec::new(),
+            &Some(MatchRange::ByteRange(start, end)) => text
+                .get(start..end)
+                .map(|s| s.char_indices().map(|(i, _)| start + i).collect())
+                .unwrap_or_default(),
+            &Some(MatchRange::CharRange(start, end)) => text
+                .char_indices()
+                .skip(start)
+                .take(end.saturating_sub(start))
+                .map(|(i, _)| i)
+                .collect(),
+            Some(MatchRange::Chars(indices)) => {
+                let mut indices = indices.clone();
+                indices.sort_unstable();
+                text.char_indices()
+                    .enumerate()
+                    .filter(|(c, _)| indices.binary_search(c).is_ok())
+                    .map(|(_, (i, _))| i)
+                    .collect()
+            }
+        }
+    }
 }
 
 use std::cmp::Ordering as CmpOrd;
@@ -359,14 +442,72 @@ impl PartialOrd for MatchedItem {
 
 impl Ord for MatchedItem {
     fn cmp(&self, other: &Self) -> CmpOrd {
-        self.sort_key
-            .cmp(&other.sort_key)
-            .then_with(|| self.rank.index.cmp(&other.rank.index))
+        // the key ends with the index
+        self.sort_key.cmp(&other.sort_key)
     }
 }
 
 //------------------------------------------------------------------------------
+/// An item taken out of the pool, see `--delta`
+///
+/// The reader sends one holding the output of the items to remove. The pool puts an empty one in
+/// place of each removed item, so that the others keep their position, which is their index, and
+/// the matcher skips it.
+#[derive(Debug, Default)]
+pub struct RemovedItem {
+    output: String,
+}
+
+impl RemovedItem {
+    /// Removes the items whose [`output`](SkimItem::output) is `output`
+    #[must_use]
+    pub fn new(output: impl Into<String>) -> Self {
+        Self { output: output.into() }
+    }
+
+    /// Whether `item` is a removal or a removed item
+    #[must_use]
+    pub fn is(item: &dyn SkimItem) -> bool {
+        item.as_any().is::<Self>()
+    }
+}
+
+impl SkimItem for RemovedItem {
+    fn text(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.output)
+    }
+}
+
+//------------------------------------------------------------------------------
+/// The keys of the items read with `--unique`
+struct UniqueKeys {
+    /// The field of `--unique-by`, the whole text otherwise
+    field: Option<FieldRange>,
+    delimiter: regex::Regex,
+    seen: SpinLock<HashSet<String>>,
+}
+
+impl UniqueKeys {
+    fn key(&self, item: &dyn SkimItem) -> String {
+        let Some(field) = &self.field else {
+            return item.text().into_owned();
+        };
+        let line = item.output();
+        let key = get_string_by_field(&self.delimiter, &line, field).unwrap_or_default();
+        // the field ends with its delimiter, but for the last one
+        let key = self
+            .delimiter
+            .find_iter(key)
+            .last()
+            .filter(|m| m.end() == key.len())
+            .map_or(key, |m| &key[..m.start()]);
+        key.to_owned()
+    }
+}
+
 const ITEM_POOL_CAPACITY: usize = 16384;
+/// The removed items are dropped from the pool once there are this many, and they are most of it
+const COMPACTION_THRESHOLD: usize = 1024;
 
 /// Thread-safe pool for storing and managing items efficiently
 pub struct ItemPool {
@@ -376,6 +517,19 @@ pub struct ItemPool {
     pool: SpinLock<Vec<Arc<dyn SkimItem>>>,
     /// Number of items that were taken
     taken: AtomicUsize,
+    /// Number of items replaced by a [`RemovedItem`] and still in the pool
+    num_removed: AtomicUsize,
+    /// The removed items the item list still has to drop, see [`take_removed`](Self::take_removed)
+    removed: SpinLock<Vec<Arc<dyn SkimItem>>>,
+    /// Positions of the items by output, built by the first removal
+    positions: SpinLock<Option<HashMap<String, Vec<usize>>>>,
+    /// Positions dropped from the pool, see [`take_compacted`](Self::take_compacted)
+    compacted: SpinLock<Vec<usize>>,
+
+    /// Keys of the items read, when dropping the items read again (`--unique`)
+    unique: Option<UniqueKeys>,
+    /// Number of items dropped by `--unique`
+    num_duplicates: AtomicUsize,
 
     /// Reserved first N lines as header
     reserved_items: SpinLock<Vec<Arc<dyn SkimItem>>>,
@@ -398,6 +552,12 @@ impl Default for ItemPool {
             length: AtomicUsize::new(0),
             pool: SpinLock::new(Vec::with_capacity(ITEM_POOL_CAPACITY)),
             taken: AtomicUsize::new(0),
+            num_removed: AtomicUsize::new(0),
+            removed: SpinLock::new(Vec::new()),
+            positions: SpinLock::new(None),
+            compacted: SpinLock::new(Vec::new()),
+            unique: None,
+            num_duplicates: AtomicUsize::new(0),
             reserved_items: SpinLock::new(Vec::new()),
             lines_to_reserve: 0,
             tac: false,
@@ -420,6 +580,23 @@ impl ItemPool {
             length: AtomicUsize::new(0),
             pool: SpinLock::new(Vec::with_capacity(ITEM_POOL_CAPACITY)),
             taken: AtomicUsize::new(0),
+            num_removed: AtomicUsize::new(0),
+            removed: SpinLock::new(Vec::new()),
+            positions: SpinLock::new(None),
+            compacted: SpinLock::new(Vec::new()),
+            unique: (options.unique || options.unique_by.is_some()).then(|| UniqueKeys {
+                // the CLI rejects an invalid field, an embedder gets the whole items compared
+                field: options.unique_by.as_deref().and_then(|field| {
+                    let range = FieldRange::from_str(field);
+                    if range.is_none() {
+                        warn!("invalid unique_by field {field:?}, comparing the whole items");
+                    }
+                    range
+                }),
+                delimiter: options.delimiter.clone(),
+                seen: SpinLock::new(HashSet::new()),
+            }),
+            num_duplicates: AtomicUsize::new(0),
             reserved_items: SpinLock::new(Vec::new()),
             lines_to_reserve: options.header_lines,
             tac: options.tac,
@@ -437,6 +614,11 @@ impl ItemPool {
         self.len() == 0
     }
 
+    /// Returns true if [`take`](Self::take) returns the items last first (`--tac`)
+    pub(crate) fn tac(&self) -> bool {
+        self.tac
+    }
+
     /// Returns the number of items that have not been taken yet
     pub fn num_not_taken(&self) -> usize {
         self.length.load(Ordering::SeqCst) - self.taken.load(Ordering::SeqCst)
@@ -447,6 +629,29 @@ impl ItemPool {
         self.taken.load(Ordering::SeqCst)
     }
 
+    /// Returns the number of items removed by a [`RemovedItem`] and still counted by [`len`](Self::len)
+    pub fn num_removed(&self) -> usize {
+        self.num_removed.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of items dropped because they were read before, see `--unique`
+    pub fn num_duplicates(&self) -> usize {
+        self.num_duplicates.load(Ordering::SeqCst)
+    }
+
+    /// Returns the items removed since the last call, to drop from the matched items
+    pub fn take_removed(&self) -> Vec<Arc<dyn SkimItem>> {
+        std::mem::take(&mut *self.removed.lock())
+    }
+
+    /// Returns the sorted positions dropped from the pool since the last call
+    ///
+    /// The removed items are dropped once they are most of the pool: the items after them move
+    /// back, and their position, which is their index, changes.
+    pub fn take_compacted(&self) -> Vec<usize> {
+        std::mem::take(&mut *self.compacted.lock())
+    }
+
     /// Clears all items from the pool and resets counters
     pub fn clear(&self) {
         let mut items = self.pool.lock();
@@ -455,6 +660,14 @@ impl ItemPool {
         header_items.clear();
         self.taken.store(0, Ordering::SeqCst);
         self.length.store(0, Ordering::SeqCst);
+        self.num_removed.store(0, Ordering::SeqCst);
+        self.removed.lock().clear();
+        *self.positions.lock() = None;
+        self.compacted.lock().clear();
+        if let Some(unique) = &self.unique {
+            unique.seen.lock().clear();
+        }
+        self.num_duplicates.store(0, Ordering::SeqCst);
     }
 
     /// Resets the taken counter without clearing items
@@ -466,12 +679,55 @@ impl ItemPool {
     }
 
     /// append the items and return the `new_size` of the pool
-    pub fn append(&self, mut items: Vec<Arc<dyn SkimItem>>) -> usize {
+    ///
+    /// A [`RemovedItem`] among them removes the items appended before it with its output.
+    pub fn append(&self, items: Vec<Arc<dyn SkimItem>>) -> usize {
         let len = items.len();
         trace!("item pool, append {len} items");
         let mut pool = self.pool.lock();
         let mut header_items = self.reserved_items.lock();
+        let mut positions = self.positions.lock();
+
+        let mut added = Vec::with_capacity(len);
+        for item in items {
+            if RemovedItem::is(item.as_ref()) {
+                self.extend(&mut pool, &mut header_items, &mut positions, std::mem::take(&mut added));
+                let positions = positions.get_or_insert_with(|| {
+                    let mut positions = HashMap::new();
+                    Self::index(&mut positions, &pool, 0);
+                    positions
+                });
+                self.remove(&mut pool, positions, &item.text());
+            } else {
+                added.push(item);
+            }
+        }
+        self.extend(&mut pool, &mut header_items, &mut positions, added);
+        if let Some(positions) = positions.as_mut() {
+            self.compact(&mut pool, positions);
+        }
+        drop(positions);
+        self.length.store(pool.len(), Ordering::SeqCst);
+        trace!("item pool, done append {len} items, total: {}", pool.len());
+        let new_len = pool.len();
+        drop(pool);
+        drop(header_items);
+        // Wake any listener that is waiting for new items (e.g. the event loop
+        // or the filter-mode loop) so it can restart the matcher immediately
+        // instead of waiting for the next periodic tick.
+        self.items_available.notify_one();
+
+        new_len
+    }
 
+    fn extend(
+        &self,
+        pool: &mut Vec<Arc<dyn SkimItem>>,
+        header_items: &mut Vec<Arc<dyn SkimItem>>,
+        positions: &mut Option<HashMap<String, Vec<usize>>>,
+        mut items: Vec<Arc<dyn SkimItem>>,
+    ) {
+        let start = pool.len();
         let to_reserve = self.lines_to_reserve - header_items.len();
         if to_reserve > 0 {
             let to_reserve = min(to_reserve, items.len());
@@ -481,21 +737,80 @@ impl ItemPool {
             // Header items are always in input order, regardless of tac
             header_items.extend(items);
 
-            pool.extend(remaining);
+            self.extend_unique(pool, remaining);
         } else {
+            self.extend_unique(pool, items);
+        }
+        if let Some(positions) = positions {
+            Self::index(positions, pool, start);
+        }
+    }
+
+    /// Adds the positions of the items of `pool` from `start` on
+    fn index(positions: &mut HashMap<String, Vec<usize>>, pool: &[Arc<dyn SkimItem>], start: usize) {
+        for (position, item) in pool.iter().enumerate().skip(start) {
+            if !RemovedItem::is(item.as_ref()) {
+                positions.entry(item.output().into_owned()).or_default().push(position);
+            }
+        }
+    }
+
+    /// Appends the items, but for the ones read before with `--unique`
+    fn extend_unique(&self, pool: &mut Vec<Arc<dyn SkimItem>>, items: Vec<Arc<dyn SkimItem>>) {
+        let Some(unique) = &self.unique else {
             pool.extend(items);
+            return;
+        };
+        // The keys may split the items with a regex: compute them before taking the lock
+        let keys: Vec<String> = items.iter().map(|item| unique.key(item.as_ref())).collect();
+        let mut seen = unique.seen.lock();
+        let (len, before) = (items.len(), pool.len());
+        pool.extend(
+            items
+                .into_iter()
+                .zip(keys)
+                .filter_map(|(item, key)| seen.insert(key).then_some(item)),
+        );
+        self.num_duplicates
+            .fetch_add(len - (pool.len() - before), Ordering::SeqCst);
+    }
+
+    /// Replaces the items with `output` by a [`RemovedItem`], keeping the positions of the others
+    fn remove(&self, pool: &mut [Arc<dyn SkimItem>], positions: &mut HashMap<String, Vec<usize>>, output: &str) {
+        let Some(found) = positions.remove(output) else {
+            return;
+        };
+        let mut removed = self.removed.lock();
+        let tombstone: Arc<dyn SkimItem> = Arc::new(RemovedItem::default());
+        for &position in &found {
+            let item = std::mem::replace(&mut pool[position], tombstone.clone());
+            if let Some(unique) = &self.unique {
+                // it can be added again
+                let key = unique.key(item.as_ref());
+                unique.seen.lock().remove(&key);
+            }
+            removed.push(item);
         }
-        self.length.store(pool.len(), Ordering::SeqCst);
-        trace!("item pool, done append {len} items, total: {}", pool.len());
-        let new_len = pool.len();
-        drop(pool);
-        drop(header_items);
-        // Wake any listener that is waiting for new items (e.g. the event loop
-        // or the filter-mode loop) so it can restart the matcher immediately
-        // instead of waiting for the next periodic tick.
-        self.items_available.notify_one();
+        self.num_removed.fetch_add(found.len(), Ordering::SeqCst);
+    }
 
-        new_len
+    /// Drops the removed items from the pool once they are most of it, see [`take_compacted`](Self::take_compacted)
+    fn compact(&self, pool: &mut Vec<Arc<dyn SkimItem>>, positions: &mut HashMap<String, Vec<usize>>) {
+        let num_removed = self.num_removed.load(Ordering::SeqCst);
+        if num_removed < COMPACTION_THRESHOLD || num_removed * 2 < pool.len() {
+            return;
+        }
+        let dropped: Vec<usize> = (0..pool.len()).filter(|&p| RemovedItem::is(pool[p].as_ref())).collect();
+        debug!("item pool, dropping {} removed items", dropped.len());
+        pool.retain(|item| !RemovedItem::is(item.as_ref()));
+        let taken = self.taken.load(Ordering::SeqCst);
+        self.taken
+            .store(taken - dropped.partition_point(|&p| p < taken), Ordering::SeqCst);
+        self.num_removed.store(0, Ordering::SeqCst);
+        positions.clear();
+        Self::index(positions, pool, 0);
+        let mut compacted = self.compacted.lock();
+        *compacted = merge_compacted(&compacted, &dropped);
     }
 
     /// Takes items from the pool, copying new items since last take and releasing lock immediately
@@ -511,6 +826,11 @@ impl ItemPool {
         items
     }
 
+    /// Returns a copy of all the items, taken or not, in input order
+    pub fn items(&self) -> Vec<Arc<dyn SkimItem>> {
+        self.pool.lock().clone()
+    }
+
     /// Returns a copy of the reserved header items
     pub fn reserved(&self) -> Vec<Arc<dyn SkimItem>> {
         let guard = self.reserved_items.lock();
@@ -518,6 +838,22 @@ impl ItemPool {
     }
 }
 
+/// Merges the positions `later` dropped, counted after the ones `earlier` dropped, into the latter
+fn merge_compacted(earlier: &[usize], later: &[usize]) -> Vec<usize> {
+    let mut merged = Vec::with_capacity(earlier.len() + later.len());
+    let mut before = 0;
+    for &position in later {
+        // the position before the earlier compaction: skip the positions it dropped
+        while before < earlier.len() && earlier[before] <= position + before {
+            merged.push(earlier[before]);
+            before += 1;
+        }
+        merged.push(position + before);
+    }
+    merged.extend_from_slice(&earlier[before..]);
+    merged
+}
+
 /// Guard for accessing a slice of items from the pool
 pub struct ItemPoolGuard<'a, T: Sized + 'a> {
     guard: SpinLockGuard<'a, Vec<T>>,
@@ -562,6 +898,30 @@ pub enum RankCriteria {
     NegPathName,
 }
 
+impl RankCriteria {
+    /// The criterion sorting in the opposite order, e.g. `-score` for `score`
+    #[must_use]
+    pub fn reversed(self) -> Self {
+        use RankCriteria::{
+            Begin, End, Index, Length, NegBegin, NegEnd, NegIndex, NegLength, NegPathName, NegScore, PathName, Score,
+        };
+        match self {
+            Score => NegScore,
+            NegScore => Score,
+            Begin => NegBegin,
+            NegBegin => Begin,
+            End => NegEnd,
+            NegEnd => End,
+            Length => NegLength,
+            NegLength => Length,
+            Index => NegIndex,
+            NegIndex => Index,
+            PathName => NegPathName,
+            NegPathName => PathName,
+        }
+    }
+}
+
 #[cfg(feature = "cli")]
 impl ValueEnum for RankCriteria {
     fn value_variants<'a>() -> &'a [Self] {
@@ -59,6 +59,71 @@ fn matched_item_ordering_prefers_higher_score() {
     assert!(high < low);
 }
 
+fn ranked(index: i32, score: i32, options: &crate::SkimOptions) -> MatchedItem {
+    let rank = Rank {
+        score,
+        index,
+        ..Default::default()
+    };
+    MatchedItem::new(item("a"), rank, None, &RankBuilder::from_options(options))
+}
+
+#[test]
+fn rank_criteria_reversed_flips_the_direction() {
+    assert_eq!(RankCriteria::Score.reversed(), RankCriteria::NegScore);
+    assert_eq!(RankCriteria::NegIndex.reversed(), RankCriteria::Index);
+    assert_eq!(RankCriteria::Begin.reversed().reversed(), RankCriteria::Begin);
+}
+
+#[test]
+fn reverse_sort_puts_the_worst_matches_first() {
+    let mut options = crate::SkimOptions::default();
+    options.reverse_sort = true;
+    assert!(ranked(0, 10, &options) < ranked(1, 100, &options));
+    // equal ranks stay in input order
+    assert!(ranked(0, 10, &options) < ranked(1, 10, &options));
+}
+
+#[test]
+fn equal_ranks_follow_the_tie_order_and_tac() {
+    let first_wins = |options: &crate::SkimOptions| ranked(0, 10, options) < ranked(1, 10, options);
+    let mut options = crate::SkimOptions::default();
+    assert!(first_wins(&options));
+    options.tie_order = crate::options::TieOrder::Reverse;
+    assert!(!first_wins(&options));
+    options.tac = true;
+    assert!(first_wins(&options));
+    options.tie_order = crate::options::TieOrder::Input;
+    assert!(!first_wins(&options));
+    // the score still wins over the index
+    assert!(ranked(0, 100, &options) < ranked(1, 10, &options));
+}
+
+#[test]
+fn matched_byte_offsets_per_range_kind() {
+    let with_range = |range| {
+        MatchedItem::new(
+            item("héllo wörld"),
+            Rank::default(),
+            Some(range),
+            &RankBuilder::default(),
+        )
+    };
+    assert_eq!(
+        with_range(MatchRange::ByteRange(1, 4)).matched_byte_offsets(),
+        vec![1, 3]
+    );
+    assert_eq!(
+        with_range(MatchRange::CharRange(1, 3)).matched_byte_offsets(),
+        vec![1, 3]
+    );
+    assert_eq!(
+        with_range(MatchRange::Chars(vec![8, 0, 1])).matched_byte_offsets(),
+        vec![0, 1, 10]
+    );
+    assert_eq!(matched("a", 0, 0).matched_byte_offsets(), Vec::<usize>::new());
+}
+
 #[test]
 fn sorted_merge_handles_empty_inputs() {
     let a = vec![matched("a", 0, 10)];
@@ -123,7 +188,7 @@ fn merge_into_sorted_small_insert() {
 
 #[test]
 fn merge_into_sorted_large_uses_backwards_merge() {
-    // Force the > SMALL_INSERT_THRESHOLD branch with interleaving order.
+    // Interleaving order, so that every item of both sides moves.
     let mut existing: Vec<MatchedItem> = (0i32..300).map(|i| matched("x", i, 1000 - i * 2)).collect();
     let incoming: Vec<MatchedItem> = (0i32..300).map(|i| matched("y", 1000 + i, 1000 - i * 2 - 1)).collect();
     let total = existing.len() + incoming.len();
@@ -133,10 +198,34 @@ fn merge_into_sorted_large_uses_backwards_merge() {
     assert!(existing.windows(2).all(|w| w[0] <= w[1]));
 }
 
+#[test]
+fn merge_into_sorted_small_batch_keeps_the_better_items_in_place() {
+    let mut existing: Vec<MatchedItem> = (0i32..1000).map(|i| matched("x", i, 2000 - i)).collect();
+    let incoming = vec![
+        matched("y", 1000, 1500),
+        matched("y", 1001, 1500),
+        matched("y", 1002, 1100),
+    ];
+    MatchedItem::merge_into_sorted(&mut existing, incoming);
+    assert_eq!(existing.len(), 1003);
+    assert!(existing.windows(2).all(|w| w[0] <= w[1]));
+    // equal ranks keep the input order, the existing item first
+    let at = |i: usize| (existing[i].rank.score, existing[i].rank.index);
+    assert_eq!([at(500), at(501), at(502)], [(1500, 500), (1500, 1000), (1500, 1001)]);
+    assert_eq!(at(0), (2000, 0));
+}
+
+#[test]
+fn merge_into_sorted_single_item_orders_equal_ranks_by_index() {
+    let mut existing = vec![matched("a", 0, 50), matched("b", 2, 50)];
+    MatchedItem::merge_into_sorted(&mut existing, vec![matched("c", 1, 50)]);
+    let texts: Vec<_> = existing.iter().map(|m| m.item.text().into_owned()).collect();
+    assert_eq!(texts, vec!["a", "c", "b"]);
+}
+
 #[test]
 fn merge_into_sorted_large_with_incoming_holding_best() {
-    // >256 incoming forces the backwards in-place merge. Give incoming the
-    // highest scores so `existing` exhausts first, leaving an incoming run
+    // Give incoming the highest scores so `existing` exhausts first, leaving an incoming run
     // that is block-copied to the front (the `bi > 0` branch).
     let mut existing: Vec<MatchedItem> = (0i32..300).map(|i| matched("x", i, 500 - i * 2)).collect();
     let incoming: Vec<MatchedItem> = (0i32..300).map(|i| matched("y", 1000 + i, 2000 - i * 2)).collect();
@@ -216,3 +305,97 @@ fn item_pool_tac_reverses_take_order() {
     let taken: Vec<String> = pool.take().iter().map(|i| i.text().into_owned()).collect();
     assert_eq!(taken, vec!["c", "b", "a"]);
 }
+
+#[test]
+fn item_pool_removal_keeps_the_positions_of_the_others() {
+    let pool = ItemPool::new();
+    pool.append(vec![item("a"), item("b"), item("a"), item("c")]);
+    // The removal only applies to the items appended before it.
+    pool.append(vec![item("d"), Arc::new(RemovedItem::new("a")), item("a")]);
+    let texts: Vec<String> = pool.items().iter().map(|i| i.text().into_owned()).collect();
+    assert_eq!(texts, vec!["", "b", "", "c", "d", "a"]);
+    assert_eq!(pool.len(), 6);
+    assert_eq!(pool.num_removed(), 2);
+
+    let removed = pool.take_removed();
+    assert_eq!(removed.len(), 2);
+    assert!(removed.iter().all(|i| i.text() == "a"));
+    assert!(pool.take_removed().is_empty());
+
+    pool.clear();
+    assert_eq!(pool.num_removed(), 0);
+}
+
+#[test]
+fn item_pool_drops_the_removed_items_past_a_threshold() {
+    let pool = ItemPool::new();
+    let len = COMPACTION_THRESHOLD * 2;
+    pool.append((0..len).map(|i| item(&i.to_string())).collect());
+    assert_eq!(pool.take().len(), len);
+    // Not most of the pool yet
+    pool.append(vec![Arc::new(RemovedItem::new("0"))]);
+    assert_eq!((pool.len(), pool.num_removed()), (len, 1));
+    assert_eq!(pool.take_compacted(), Vec::<usize>::new());
+
+    pool.append(
+        (2..len)
+            .step_by(2)
+            .map(|i| Arc::new(RemovedItem::new(i.to_string())) as Arc<dyn SkimItem>)
+            .collect(),
+    );
+    assert_eq!((pool.len(), pool.num_removed()), (len / 2, 0));
+    assert_eq!(pool.take_compacted(), (0..len).step_by(2).collect::<Vec<_>>());
+    assert_eq!(pool.take_removed().len(), len / 2);
+    // The taken items moved back with the others
+    pool.append(vec![item("new")]);
+    assert_eq!(pool.num_not_taken(), 1);
+    let texts: Vec<String> = pool.items().iter().map(|i| i.text().into_owned()).collect();
+    assert_eq!(texts[..2], ["1", "3"]);
+    assert_eq!(texts.last().map(String::as_str), Some("new"));
+
+    // The positions were indexed again
+    pool.append(vec![Arc::new(RemovedItem::new("3"))]);
+    assert_eq!(pool.items()[1].text(), "");
+}
+
+#[test]
+fn merge_compacted_counts_later_positions_before_the_earlier_compaction() {
+    assert_eq!(merge_compacted(&[], &[1, 3]), vec![1, 3]);
+    assert_eq!(merge_compacted(&[5], &[0, 7]), vec![0, 5, 8]);
+    assert_eq!(merge_compacted(&[0, 1], &[0]), vec![0, 1, 2]);
+    assert_eq!(merge_compacted(&[2, 4], &[]), vec![2, 4]);
+}
+
+#[test]
+fn item_pool_unique_drops_the_items_read_again() {
+    let mut options = crate::SkimOptions::default();
+    options.unique = true;
+    options.header_lines = 1;
+    let pool = ItemPool::from_options(&options);
+    pool.append(vec![item("a"), item("a"), item("b"), item("a")]);
+    pool.append(vec![item("b"), item("c")]);
+    let texts: Vec<String> = pool.items().iter().map(|i| i.text().into_owned()).collect();
+    // the header line is not counted
+    assert_eq!(texts, vec!["a", "b", "c"]);
+    assert_eq!(pool.num_duplicates(), 2);
+
+    // a removed item can be read again
+    pool.append(vec![Arc::new(RemovedItem::new("b")), item("b")]);
+    assert_eq!(pool.num_duplicates(), 2);
+
+    pool.clear();
+    assert_eq!(pool.num_duplicates(), 0);
+    pool.append(vec![item("h"), item("a")]);
+    assert_eq!(pool.len(), 1);
+}
+
+#[test]
+fn item_pool_unique_by_compares_a_field() {
+    let mut options = crate::SkimOptions::default();
+    options.unique_by = Some("2".to_string());
+    let pool = ItemPool::from_options(&options);
+    pool.append(vec![item("1 ls"), item("2 cd /"), item("3 ls -l"), item("4 pwd")]);
+    let texts: Vec<String> = pool.items().iter().map(|i| i.text().into_owned()).collect();
+    assert_eq!(texts, vec!["1 ls", "2 cd /", "4 pwd"]);
+    assert_eq!(pool.num_duplicates(), 1);
+}
@@ -22,7 +22,7 @@
 #![cfg_attr(coverage, feature(coverage_attribute))]
 
 #[macro_use]
-extern crate log;
+extern crate tracing;
 
 #[global_allocator]
 static GLOBAL_ALLOCATOR: mimalloc::MiMalloc = mimalloc::MiMalloc;
@@ -30,35 +30,40 @@ static GLOBAL_ALLOCATOR: mimalloc::MiMalloc = mimalloc::MiMalloc;
 use std::any::Any;
 use std::borrow::Cow;
 use std::fmt::Display;
-use std::process::Command;
 use std::sync::Arc;
 
 use crate::fuzzy_matcher::MatchIndices;
 use ratatui::style::Style;
 use ratatui::text::{Line, Span};
+use unicode_segmentation::UnicodeSegmentation;
 
 pub use crate::engine::fuzzy::FuzzyAlgorithm;
 pub use crate::item::RankCriteria;
 pub use crate::options::SkimOptions;
 pub use crate::output::{BinOptions, SkimOutput};
 pub use crate::skim::*;
-pub use crate::skim_item::SkimItem;
+pub use crate::skim_item::{SkimItem, SourcePosition};
 use crate::tui::Size;
 pub use util::printf;
 
 pub mod binds;
-mod engine;
+pub mod engine;
 pub mod field;
 pub mod fuzzy_matcher;
 pub mod helper;
+mod interpreter;
 pub mod item;
 pub mod matcher;
 pub mod options;
 mod output;
+pub mod placeholder;
 #[cfg(unix)]
 pub mod popup;
 pub mod prelude;
+mod process_group;
 pub mod reader;
+#[cfg(feature = "script")]
+pub mod script;
 mod skim;
 mod skim_item;
 pub mod spinlock;
@@ -79,24 +84,6 @@ pub const SKIM_DEFAULT_COMMAND: &str = "find .";
 #[cfg(windows)]
 pub const SKIM_DEFAULT_COMMAND: &str = "dir /s /b /A:-D";
 
-#[cfg(unix)]
-fn shell_cmd(cmd: &str) -> Command {
-    let mut c = Command::new("sh");
-    c.arg("-c").arg(cmd);
-    c
-}
-#[cfg(windows)]
-fn shell_cmd(cmd: &str) -> Command {
-    use std::os::windows::process::CommandExt as _;
-    // `cmd.exe` does not parse its command line using MSVC rules, so the default
-    // `Command::arg` escaping (quoting/backslash-escaping) corrupts shell
-    // metacharacters like `|`, `&`, `>` and embedded quotes. Pass the command
-    // string verbatim via `raw_arg` so cmd.exe sees exactly what the user wrote.
-    let mut c = Command::new("cmd");
-    c.arg("/c").raw_arg(cmd);
-    c
-}
-
 //------------------------------------------------------------------------------
 /// Trait for downcasting to concrete types from trait objects
 pub trait AsAny {
@@ -150,74 +137,51 @@ pub struct DisplayContext {
 impl DisplayContext {
     /// Converts the context and text into a styled `Line` with highlighted matches
     ///
-    /// # Panics
-    ///
-    /// Panics if the byte ranges in `Matches::ByteRange` do not align with valid UTF-8 boundaries.
+    /// Highlights cover whole grapheme clusters: a cluster is highlighted when any of its chars
+    /// or bytes is matched, so that combining marks, emoji sequences and the like are never
+    /// split across spans.
     #[must_use]
     pub fn to_line(self, cow: Cow<str>) -> Line {
         let text: String = cow.into_owned();
+        if matches!(self.matches, Matches::None) {
+            return Line::from(vec![Span::styled(text, self.base_style)]);
+        }
 
         // Combine base_style with match style for highlighted text
         // Match style takes precedence for fg, but inherits bg from base if not set
-        match &self.matches {
-            Matches::CharIndices(indices) => {
-                let mut res = Line::default();
-                let mut chars = text.chars();
-                let mut prev_index = 0;
-                for &index in indices {
-                    let span_content = chars.by_ref().take(index - prev_index);
-                    res.push_span(Span::styled(span_content.collect::<String>(), self.base_style));
-                    let highlighted_char = chars.next().unwrap_or_default().to_string();
-
-                    res.push_span(Span::styled(
-                        highlighted_char,
-                        self.base_style.patch(self.matched_style),
-                    ));
-                    prev_index = index + 1;
+        let matched_style = self.base_style.patch(self.matched_style);
+        let mut indices: &[usize] = match &self.matches {
+            Matches::CharIndices(indices) => indices,
+            _ => &[],
+        };
+        let mut res = Line::default();
+        let mut span = String::new();
+        let mut span_matched = false;
+        let mut char_index = 0;
+        for (byte_index, grapheme) in text.grapheme_indices(true) {
+            let char_end = char_index + grapheme.chars().count();
+            let matched = match &self.matches {
+                Matches::CharIndices(_) => {
+                    let covered = indices.partition_point(|&index| index < char_end);
+                    let matched = indices[..covered].iter().any(|&index| index >= char_index);
+                    indices = &indices[covered..];
+                    matched
                 }
-                res.push_span(Span::styled(chars.collect::<String>(), self.base_style));
-                res
-            }
-            // AnsiString::from((context.text, indices, context.highlight_attr)),
-            #[allow(clippy::cast_possible_truncation)]
-            Matches::CharRange(start, end) => {
-                let mut chars = text.chars();
-                let mut res = Line::default();
-                res.push_span(Span::styled(
-                    chars.by_ref().take(*start).collect::<String>(),
-                    self.base_style,
-                ));
-                let highlighted_text = chars.by_ref().take(*end - *start).collect::<String>();
-
-                res.push_span(Span::styled(
-                    highlighted_text,
-                    self.base_style.patch(self.matched_style),
-                ));
-                res.push_span(Span::styled(chars.collect::<String>(), self.base_style));
-                res
-            }
-            Matches::ByteRange(start, end) => {
-                let mut bytes = text.bytes();
-                let mut res = Line::default();
-                res.push_span(Span::styled(
-                    String::from_utf8(bytes.by_ref().take(*start).collect()).unwrap(),
-                    self.base_style,
-                ));
-                let highlighted_bytes = bytes.by_ref().take(*end - *start).collect();
-                let highlighted_text = String::from_utf8(highlighted_bytes).unwrap();
-
-                res.push_span(Span::styled(
-                    highlighted_text,
-                    self.base_style.patch(self.matched_style),
-                ));
-                res.push_span(Span::styled(
-                    String::from_utf8(bytes.collect()).unwrap(),
-                    self.base_style,
-                ));
-                res
+                Matches::CharRange(start, end) => char_index < *end && char_end > *start,
+                Matches::ByteRange(start, end) => byte_index < *end && byte_index + grapheme.len() > *start,
+                Matches::None => false,
+            };
+            if matched != span_matched && !span.is_empty() {
+                let style = if span_matched { matched_style } else { self.base_style };
+                res.push_span(Span::styled(std::mem::take(&mut span), style));
             }
-            Matches::None => Line::from(vec![Span::styled(text, self.base_style)]),
+            span_matched = matched;
+            span.push_str(grapheme);
+            char_index = char_end;
         }
+        let style = if span_matched { matched_style } else { self.base_style };
+        res.push_span(Span::styled(span, style));
+        res
     }
 }
 
@@ -375,6 +339,35 @@ impl MatchResult {
             MatchRange::Chars(vec) => vec.clone(),
         }
     }
+
+    /// Replaces the matched range with the item's [`SkimItem::external_ranges`], if any
+    ///
+    /// The rank's `begin` and `end` are updated to span the external ranges, so
+    /// that the tiebreaks follow what is highlighted.
+    pub fn apply_external_ranges(&mut self, item: &dyn SkimItem) {
+        let Some(ranges) = item.external_ranges() else {
+            return;
+        };
+        let text = item.text();
+        let mut indices = MatchIndices::new();
+        let mut span: Option<(usize, usize)> = None;
+        for &(start, end) in ranges {
+            if start >= end || end > text.len() || !text.is_char_boundary(start) || !text.is_char_boundary(end) {
+                continue;
+            }
+            let first = text[..start].chars().count();
+            indices.extend(first..first + text[start..end].chars().count());
+            span = Some(span.map_or((start, end), |(b, e)| (b.min(start), e.max(end))));
+        }
+        let Some((begin, end)) = span else {
+            return;
+        };
+        indices.sort_unstable();
+        indices.dedup();
+        self.rank.begin = i32::try_from(begin).unwrap_or(i32::MAX);
+        self.rank.end = i32::try_from(end).unwrap_or(i32::MAX);
+        self.matched_range = MatchRange::Chars(indices);
+    }
 }
 
 /// A matching engine that can match queries against items
@@ -406,6 +399,15 @@ impl MatchEngineFactory for Box<dyn MatchEngineFactory> {
 pub trait Selector {
     /// Returns true if the item at the given index should be pre-selected
     fn should_select(&self, index: usize, item: &dyn SkimItem) -> bool;
+
+    /// Returns true if `item` can join the `selected` items
+    ///
+    /// Checked whenever an item is about to be selected, by the user or by pre-selection, to
+    /// constrain the selection, e.g. to the items of one directory. Refused selections are reported
+    /// in the status line. Everything can be selected by default.
+    fn can_select(&self, _item: &dyn SkimItem, _selected: &[&dyn SkimItem]) -> bool {
+        true
+    }
 }
 
 //------------------------------------------------------------------------------
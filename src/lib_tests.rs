@@ -52,6 +52,33 @@ fn to_line_byte_range_highlights_span() {
     assert_eq!(highlighted_text(&line, matched), "bc");
 }
 
+#[test]
+fn to_line_highlights_whole_grapheme_clusters() {
+    // `e` + U+0301 COMBINING ACUTE ACCENT, then a family emoji joined by ZWJs
+    let text = "cafe\u{301} \u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}!";
+    for matches in [
+        Matches::CharIndices(vec![3, 7]),
+        Matches::CharRange(4, 7),
+        Matches::ByteRange(5, 8),
+    ] {
+        let context = ctx(matches);
+        let matched = context.base_style.patch(context.matched_style);
+        let line = context.clone().to_line(Cow::Borrowed(text));
+        assert_eq!(line_text(&line), text);
+        assert!(line.spans.iter().all(|span| !span.content.starts_with('\u{301}')));
+        assert!(line.spans.iter().all(|span| !span.content.starts_with('\u{200D}')));
+        assert!(highlighted_text(&line, matched).starts_with("e\u{301}"));
+    }
+}
+
+#[test]
+fn to_line_wide_chars_keep_their_highlight() {
+    let context = ctx(Matches::CharIndices(vec![1, 2]));
+    let matched = context.base_style.patch(context.matched_style);
+    let line = context.clone().to_line(Cow::Borrowed("日本語"));
+    assert_eq!(highlighted_text(&line, matched), "本語");
+}
+
 #[test]
 fn to_line_none_has_no_highlight() {
     let context = ctx(Matches::None);
@@ -87,6 +114,39 @@ fn match_result_range_char_indices_variants() {
     assert_eq!(chars.range_char_indices("abcd"), vec![0, 3]);
 }
 
+struct Grepped(&'static str, Vec<(usize, usize)>);
+
+impl SkimItem for Grepped {
+    fn text(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self.0)
+    }
+
+    fn external_ranges(&self) -> Option<&[(usize, usize)]> {
+        Some(&self.1)
+    }
+}
+
+#[test]
+fn match_result_applies_external_ranges() {
+    let mut result = MatchResult {
+        rank: Rank::default(),
+        matched_range: MatchRange::ByteRange(0, 1),
+    };
+    // `é` is two bytes: (1, 3) covers it, (2, 3) splits it and is ignored.
+    result.apply_external_ranges(&Grepped("héllo world", vec![(7, 12), (1, 3), (2, 3)]));
+    assert_eq!(result.matched_range, MatchRange::Chars(vec![1, 6, 7, 8, 9, 10]));
+    assert_eq!((result.rank.begin, result.rank.end), (1, 12));
+
+    let mut untouched = MatchResult {
+        rank: Rank::default(),
+        matched_range: MatchRange::ByteRange(0, 1),
+    };
+    untouched.apply_external_ranges(&Grepped("abc", vec![(2, 9)]));
+    assert_eq!(untouched.matched_range, MatchRange::ByteRange(0, 1));
+    untouched.apply_external_ranges(&"abc");
+    assert_eq!(untouched.matched_range, MatchRange::ByteRange(0, 1));
+}
+
 #[test]
 fn as_any_downcasts_mutably() {
     let mut value: String = "hello".to_string();
@@ -33,10 +33,12 @@ Available color names:
     * info: info text (match count)
     * prompt: prompt text
     * cursor (or pointer): cursor/pointer
-    * selected (or marker): selected item marker
+    * selected (or marker): selected item marker and selection count
+    * gutter: selector and marker columns, e.g. `gutter-bg:236`
     * header: header text
     * border: border lines
     * scrollbar: item list scrollbar thumb
+    * label: border labels, see --border-label
 
 Adding `-fg`, `_fg`, `-bg`, `_bg`, `-underline`, `_underline` sets the corresponding part of
 the color. For instance, `normal-fg` (or simply `fg`) will set the foreground normal color.
@@ -146,7 +148,7 @@ const ACTION_BINDINGS_SS: &str = concat!(
 
 const ACTIONS_SS: &str = concat!(
     "\n* abort: ctrl-c  ctrl-q  esc
-* accept(...): enter double-click *the argument will be printed when the binding is triggered*
+* accept(...): enter double-click *the argument (e.g. `accept(edit)`) is printed on its own line before the selection, for scripts to dispatch on*
 * append-and-select
 * backward-char: ctrl-b  left
 * backward-delete-char: ctrl-h  bspace
@@ -155,20 +157,33 @@ const ACTIONS_SS: &str = concat!(
 * backward-word: alt-b   shift-left
 * beginning-of-line: ctrl-a  home
 * bind(...): *arg is a comma-separated list of `trigger:action[+action]` bindings to add (same syntax as --bind, including action triggers such as `act-up:last`)
+* bottom
+* change-border-label(...): *arg will be a expanded expression, see COMMAND EXPANSION for details; an empty arg removes the label*
+* change-header-label(...): *same as change-border-label, for --header-label*
+* change-input-label(...): *same as change-border-label, for --input-label*
+* change-list-label(...): *same as change-border-label, for --list-label*
+* change-preview-label(...): *same as change-border-label, for --preview-label*
 * clear-screen: ctrl-l
 * delete-char: del
 * delete-char/eof: ctrl-d
 * deselect-all
+* disable-search: *stops matching, as --disabled; the query is still typed and expanded as {q}
 * down: ctrl-j  ctrl-n  down
+* enable-search: *matches the query again after disable-search or --disabled
 * end-of-line: ctrl-e  end
 * execute(...): *arg will be a command, see COMMAND EXPANSION for details
 * execute-silent(...): *arg will be a command, see COMMAND EXPANSION for details
+* execute-echo(...): *arg will be a command, shown in the status line instead of being run
+* execute-tmux(...): *runs `tmux` with arg as arguments, expanded as for execute-silent (e.g. `execute-tmux(send-keys -t :2 {+} Enter)`); only inside tmux
 * forward-char: ctrl-f  right
 * forward-word: alt-f   shift-right
+* history-search
 * if-non-matched
 * if-query-empty
 * if-query-not-empty
 * ignore
+* jump: *label the visible items with `--jump-labels`, then move to the item whose label is typed; any other key cancels
+* jump-accept: *as `jump`, accepting the labeled item
 * kill-line
 * kill-word: alt-d
 * next-history: ctrl-n with `--history` or `--cmd-history`
@@ -176,40 +191,58 @@ const ACTIONS_SS: &str = concat!(
 * page-up: pgup
 * half-page-down
 * half-page-up
+* pick(...): *arg* is a command, see COMMAND EXPANSION. Its output is searched in a nested `sk` and the choice is inserted in the query, where the rest of the chain sees it as `{q}`
+* plugin(...): run the custom action registered under *arg* by a library embedder
 * preview-up: shift-up
 * preview-down: shift-down
 * preview-left
 * preview-right
 * preview-page-down
 * preview-page-up
+* preview(...): *arg is a command previewing the current item in place of the preview command, until the cursor moves or `refresh-preview`, see COMMAND EXPANSION for details
 * previous-history: ctrl-p with `--history` or `--cmd-history`
+* put(...): *inserts arg at the cursor, as is
 * redraw
 * refresh-cmd
 * refresh-preview
 * reload(...)
+* replace-query: *sets the query to the text of the current item
 * select-all
 * select-row
 * set-preview-cmd(...): *arg will be a expanded expression, see COMMAND EXPANSION for details
+* send-to-tmux(...): *types the selected items (or the current one), shell-quoted and without Enter, in the tmux pane or window arg (e.g. `%3` or `:2`); only inside tmux
 * set-query(...): *arg will be a expanded expression, see COMMAND EXPANSION for details
 ",
+    "* suspend: ctrl-z *stops skim as a shell job and gives the terminal back until it is continued, e.g. with `fg`; ",
+    "not available on Windows\n",
     "* suppress: *if bound to an action (e.g. `act-up:suppress`), suppresses that action's default behavior ",
     "so the rest of the non-recursive chain runs once in its place; if bound to a key, equivalent to `ignore`\n",
+    "* tab-complete: *completes the query token before the cursor to the longest common prefix of the words of the ",
+    "matched items starting with it (case-sensitive), or lists the candidates in the status line\n",
     "* toggle
 * toggle-all
+* toggle-group: *collapse the group of the current item to its best match, or expand it back, see --group-by-field; with --tree, collapse or expand the current directory
 * toggle+down: ctrl-i  tab
 * toggle-in: (--layout=reverse ? toggle+up:  toggle+down)
 * toggle-interactive
+* toggle-nth: *matches the next field set of --nth-cycle, or switches between --nth and the whole line without it
 * toggle-out: (--layout=reverse ? toggle+down:  toggle+up)
 * toggle-preview
 * toggle-preview-wrap
+* toggle-query2: alt-q *moves the cursor to the secondary query of --query2, or back to the query; the editing actions apply to the line with the cursor
 * toggle-sort
+* toggle-search: *disable-search or enable-search
+* toggle-stderr: *show or hide a pane with the stderr of the source and preview commands
 * toggle+up: btab    shift-tab
 * top
+* transpose-chars: ctrl-t
 * unbind(...): *arg is a comma-separated list of keys or action triggers (e.g. `act-up`) to unbind
+* undo: ctrl-_ *restores the query from before the last edit, typed characters are undone together
 * unix-line-discard: ctrl-u
 * unix-word-rubout: ctrl-w
 * up: ctrl-k  ctrl-p  up
-* yank: ctrl-y
+* yank: ctrl-y *inserts the last text removed by the kill and rubout actions
+* yank-pop: alt-y *right after `yank`, replaces the yanked text with the previous one of the last 16 removed
 ",
 );
 
@@ -330,15 +363,24 @@ Exact search can be enabled by default by the `--exact` command-line flag. In ex
         "
 In the `preview` flag, `execute`, `reload`, `set-query`... binds, sk will expand placeholders:
 * {} (or --replstr if used) will be expanded to the current item.
+* {raw} will be expanded to the current item, without the shell quoting applied to the other placeholders.
 * {q} (or {cq} for legacy reasons) will be expanded to the current query input.
 * {+} will be expanded to either the currently selected items in multi-select mode, or the current
  item in single-select.
 * {n} will be expanded to the index of the current item.
 * {+n} will be expanded to the index(es) of the corresponding {+} item(s).
+* {ln} and {off} will be expanded to the line number and the byte offset of the current item in the file read
+ with --input-file, and to nothing for other items, e.g. `sk --input-file app.log --preview 'bat --color=always
+ --highlight-line {ln} app.log' --preview-window +{ln}-/2`.
 * {FIELD_INDEX_EXPRESSION} will be expanded to the field index expression run against the current
  item.
 * {+FIELD_INDEX_EXPRESSION} will be expanded to the field index expression run against the {+}
  item(s).
+* {+:SEP}, {+n:SEP} and {+FIELD_INDEX_EXPRESSION:SEP} join the item(s) with SEP instead of a space.
+
+Other text between braces is left as is, so that shell and `awk` code keeps working. A `{` that is never closed,
+or a {+...} that is not a valid field index expression, is an error: the command is not run and the error is shown
+in the status line until the next key press.
 ",
     );
     subsection(
@@ -360,6 +402,14 @@ It will expand to the corresponding fields, separated by the `--delimiter|-d` op
         "
 If set, skim will collect items with this command if no input is piped in.
 If not set, defaults to `find .` on unix-like systems and `dir /s /b /A:-D` on Windows.",
+    );
+    subsection(
+        &mut custom,
+        "SKIM_SHELL",
+        "
+Interpreter running the source, preview and execute commands when `--with-shell` is not set, optionally followed by its flags (e.g. `bash --norc -c`).
+Defaults to `sh` on unix-like systems and `cmd` on Windows.
+Without flags, `cmd` is passed `/C`, `powershell` and `pwsh` are passed `-NoProfile -Command`, any other shell is passed `-c`.",
     );
     subsection(
         &mut custom,
@@ -380,6 +430,16 @@ Example:
 --reverse
 --prompt '## '
 ```
+
+Lines after a `[profile.NAME]` header only apply with `--profile NAME`, after `SKIM_DEFAULT_OPTIONS` and before the command line.
+Example:
+```
+--reverse
+[profile.grep]
+--ansi --delimiter : --preview 'builtin:file {1}' --preview-window +{2}-/2
+[profile.files]
+--multi --preview 'ls -l {}'
+```
 ",
     );
 
@@ -389,6 +449,20 @@ Example:
         "If set to a non-empty value, will disable coloring",
     );
 
+    section(&mut custom, "EXPORTED VARIABLES", "");
+    subsection(
+        &mut custom,
+        "SKIM_QUERY, SKIM_CMD_QUERY",
+        "
+Set by skim for the commands it runs (source, reload, preview, execute and hooks) to the query and the command query.",
+    );
+    subsection(
+        &mut custom,
+        "SKIM_POS, SKIM_SELECT_COUNT, SKIM_MATCH_COUNT, SKIM_TOTAL_COUNT",
+        "
+Position of the cursor in the list, starting from 1 (0 when nothing matches), number of selected items, of matched items and of items read.",
+    );
+
     section(&mut custom, "THEME", THEME_SECTION);
 
     #[cfg(feature = "listen")]
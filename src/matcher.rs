@@ -1,16 +1,22 @@
 //! This module contains the matching coordinator
 use crate::thread_pool::{self, ThreadPool};
 use crate::tui::item_list::{MergeStrategy, ProcessedItems};
+use regex::Regex;
 use std::rc::Rc;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
+use crate::engine::andor::AndEngine;
+use crate::engine::fuzzy::exceeds_window;
 use crate::engine::normalized::NormalizedEngineFactory;
+use crate::engine::scoped::FieldScopedEngine;
 use crate::engine::split::SplitMatchEngineFactory;
-use crate::item::{ItemPool, MatchedItem, RankBuilder};
+use crate::field::FieldRange;
+use crate::item::{ItemPool, MatchedItem, RankBuilder, RemovedItem};
 use crate::prelude::{AndOrEngineFactory, ExactOrFuzzyEngineFactory, RegexEngineFactory};
 use crate::spinlock::SpinLock;
-use crate::{CaseMatching, MatchEngineFactory, SkimItem, SkimOptions};
+use crate::{CaseMatching, MatchEngineFactory, SkimItem, SkimOptions, Typos};
 
 /// Merges per-worker match results and writes them into `processed_items`.
 ///
@@ -87,6 +93,9 @@ pub struct MatcherControl {
     interrupt: Arc<AtomicBool>,
     processed: Arc<AtomicUsize>,
     matched: Arc<AtomicUsize>,
+    windowed: Arc<AtomicUsize>,
+    /// Duration of the run in nanoseconds once it completed, 0 before or once taken
+    took: Arc<AtomicU64>,
 }
 
 impl Default for MatcherControl {
@@ -96,6 +105,8 @@ impl Default for MatcherControl {
             interrupt: Arc::new(AtomicBool::new(false)),
             processed: Default::default(),
             matched: Default::default(),
+            windowed: Default::default(),
+            took: Default::default(),
         }
     }
 }
@@ -113,6 +124,13 @@ impl MatcherControl {
         self.matched.load(Ordering::Relaxed)
     }
 
+    /// Returns the number of items processed so far whose matched text was longer than the match
+    /// window, see [`Matcher::match_window`].
+    #[must_use]
+    pub fn get_num_windowed(&self) -> usize {
+        self.windowed.load(Ordering::Relaxed)
+    }
+
     /// Signals the matcher to stop processing.
     pub fn kill(&mut self) {
         self.interrupt.store(true, Ordering::Relaxed);
@@ -123,6 +141,15 @@ impl MatcherControl {
     pub fn stopped(&self) -> bool {
         self.stopped.load(Ordering::Relaxed)
     }
+
+    /// Returns how long the run took, once, after it completed without being killed.
+    #[must_use]
+    pub fn take_duration(&self) -> Option<Duration> {
+        match self.took.swap(0, Ordering::Relaxed) {
+            0 => None,
+            nanos => Some(Duration::from_nanos(nanos)),
+        }
+    }
 }
 
 impl Drop for MatcherControl {
@@ -138,6 +165,8 @@ pub struct Matcher {
     case_matching: CaseMatching,
     /// The rank builder shared with all engines; used to attach criteria to `MatchedItem`s.
     pub rank_builder: Arc<RankBuilder>,
+    match_window: Option<usize>,
+    scope: Option<(Vec<FieldRange>, Regex)>,
 }
 
 impl Matcher {
@@ -147,6 +176,8 @@ impl Matcher {
             engine_factory,
             case_matching: CaseMatching::default(),
             rank_builder: Arc::new(RankBuilder::default()),
+            match_window: None,
+            scope: None,
         }
     }
 
@@ -164,6 +195,22 @@ impl Matcher {
         self
     }
 
+    /// Sets the match window, only used to count the items whose matched text is longer than it:
+    /// the engines built by the factory do the actual capping.
+    #[must_use]
+    pub fn match_window(mut self, match_window: Option<usize>) -> Self {
+        self.match_window = match_window;
+        self
+    }
+
+    /// Sets the fields to match, split at `delimiter`, in place of the ones the items were read
+    /// with (`--nth`); no fields match the whole text. Used by `toggle-nth`.
+    #[must_use]
+    pub fn scope(mut self, fields: Vec<FieldRange>, delimiter: Regex) -> Self {
+        self.scope = Some((fields, delimiter));
+        self
+    }
+
     /// Finalizes the builder and returns the configured Matcher.
     #[must_use]
     pub fn build(self) -> Self {
@@ -185,23 +232,52 @@ impl Matcher {
     /// computation.
     #[must_use]
     pub fn create_engine_factory_with_builder(options: &SkimOptions) -> (Rc<dyn MatchEngineFactory>, Arc<RankBuilder>) {
-        if options.regex {
+        Self::engine_factory_for(options, options.filter.is_some())
+    }
+
+    /// Same as [`Matcher::create_engine_factory_with_builder`], `filter_mode` dropping the match
+    /// positions of fuzzy matches
+    pub(crate) fn engine_factory_for(
+        options: &SkimOptions,
+        filter_mode: bool,
+    ) -> (Rc<dyn MatchEngineFactory>, Arc<RankBuilder>) {
+        let (factory, rank_builder) = Self::matching_engine_factory(options, filter_mode);
+        #[cfg(feature = "script")]
+        if let Some(script) = &options.rank_hook {
+            let factory = crate::engine::scripted::ScriptedEngineFactory::new(factory, script.clone());
+            return (Rc::new(factory), rank_builder);
+        }
+        (factory, rank_builder)
+    }
+
+    /// The engine factory of [`Matcher::engine_factory_for`], before the `--rank-hook`
+    fn matching_engine_factory(
+        options: &SkimOptions,
+        filter_mode: bool,
+    ) -> (Rc<dyn MatchEngineFactory>, Arc<RankBuilder>) {
+        if let Some(factory) = &options.engine_factory {
+            (factory.clone(), Arc::new(RankBuilder::from_options(options)))
+        } else if options.regex {
             let regex_factory = RegexEngineFactory::builder();
             let factory: Rc<dyn MatchEngineFactory> = if options.normalize {
                 Rc::new(NormalizedEngineFactory::new(regex_factory))
             } else {
                 Rc::new(regex_factory)
             };
-            (factory, Arc::new(RankBuilder::default()))
+            (factory, Arc::new(RankBuilder::default().ordered_by(options)))
         } else {
-            let rank_builder = Arc::new(RankBuilder::new(options.tiebreak.clone()));
-            log::debug!("Creating matcher for algo {:?}", options.algorithm);
+            let rank_builder = Arc::new(RankBuilder::from_options(options));
+            debug!("Creating matcher for algo {:?}", options.algorithm);
             let fuzzy_engine_factory = ExactOrFuzzyEngineFactory::builder()
                 .fuzzy_algorithm(options.algorithm)
                 .exact_mode(options.exact)
                 .typos(options.typos)
-                .filter_mode(options.filter.is_some())
+                .filter_mode(filter_mode)
                 .last_match(options.last_match)
+                .segmenter(options.segmenter.clone())
+                .match_window(options.match_window)
+                .extended(!options.no_extended)
+                .exact_boundary(options.exact_boundary)
                 .rank_builder(rank_builder.clone())
                 .build();
 
@@ -212,8 +288,15 @@ impl Matcher {
                 factory = Box::new(SplitMatchEngineFactory::new(factory, delimiter));
             }
 
-            // Wrap with AndOrEngineFactory so that queries like "foo:bar baz:qux" work
-            factory = Box::new(AndOrEngineFactory::new(factory));
+            // Wrap with AndOrEngineFactory so that queries like "foo:bar baz:qux" work, field
+            // scopes being left to the split matching when it is enabled
+            if !options.no_extended {
+                let and_or = AndOrEngineFactory::new(factory);
+                factory = match options.split_match {
+                    Some(_) => Box::new(and_or),
+                    None => Box::new(and_or.fields(options.delimiter.clone(), options.field_names.clone())),
+                };
+            }
 
             // Wrap with NormalizedEngineFactory if normalization is requested
             if options.normalize {
@@ -225,6 +308,50 @@ impl Matcher {
         }
     }
 
+    /// The `k` items of `items` closest to `query`, best first, for `--suggest-on-empty`
+    ///
+    /// The query goes through the extended syntax as usual, but its fuzzy terms allow one typo
+    /// per 4 characters, whatever `typos`, `exact` and `regex` say.
+    pub(crate) fn near_misses(
+        options: &SkimOptions,
+        query: &str,
+        items: &[Arc<dyn SkimItem>],
+        k: usize,
+    ) -> Vec<MatchedItem> {
+        let rank_builder = Arc::new(RankBuilder::from_options(options));
+        let mut factory: Box<dyn MatchEngineFactory> = Box::new(AndOrEngineFactory::new(
+            ExactOrFuzzyEngineFactory::builder()
+                .fuzzy_algorithm(options.algorithm)
+                .typos(Typos::Fixed(query.chars().count().div_ceil(4)))
+                .rank_builder(rank_builder.clone())
+                .build(),
+        ));
+        if options.normalize {
+            factory = Box::new(NormalizedEngineFactory::new(factory));
+        }
+        let engine = factory.create_engine_with_case(query, options.case);
+
+        let mut matched: Vec<MatchedItem> = items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| !RemovedItem::is(item.as_ref()))
+            .filter_map(|(index, item)| {
+                let result = engine.match_item(item.as_ref())?;
+                let mut rank = result.rank;
+                rank.index = i32::try_from(index).unwrap_or(i32::MAX);
+                Some(MatchedItem::new(
+                    Arc::clone(item),
+                    rank,
+                    Some(result.matched_range),
+                    &rank_builder,
+                ))
+            })
+            .collect();
+        matched.sort();
+        matched.truncate(k);
+        matched
+    }
+
     /// Creates a Matcher configured from the given `SkimOptions`.
     #[must_use]
     pub fn from_options(options: &SkimOptions) -> Self {
@@ -232,6 +359,7 @@ impl Matcher {
         Matcher::builder(engine_factory)
             .case(options.case)
             .rank_builder(rank_builder)
+            .match_window(options.match_window)
             .build()
     }
 
@@ -255,10 +383,11 @@ impl Matcher {
     ///
     /// Returns a `MatcherControl` that can be used to monitor progress or
     /// stop the matcher.
-    #[allow(clippy::too_many_arguments)]
+    #[allow(clippy::too_many_arguments, clippy::too_many_lines)]
     pub(crate) fn run(
         &self,
         query: &str,
+        query2: &str,
         item_pool: &Arc<ItemPool>,
         thread_pool: &Arc<ThreadPool>,
         processed_items: Arc<SpinLock<Option<ProcessedItems>>>,
@@ -266,7 +395,19 @@ impl Matcher {
         no_sort: bool,
         needs_render: Arc<AtomicBool>,
     ) -> MatcherControl {
-        let matcher_engine = self.engine_factory.create_engine_with_case(query, self.case_matching);
+        let mut matcher_engine = self.engine_factory.create_engine_with_case(query, self.case_matching);
+        if !query2.is_empty() {
+            // the secondary query (`--query2`) narrows the matches of the query
+            let engine2 = self.engine_factory.create_engine_with_case(query2, self.case_matching);
+            matcher_engine = Box::new(AndEngine::builder().engines(vec![matcher_engine, engine2]).build());
+        }
+        if let Some((fields, delimiter)) = &self.scope {
+            matcher_engine = Box::new(FieldScopedEngine::with_fields(
+                matcher_engine,
+                fields.clone(),
+                delimiter.clone(),
+            ));
+        }
         debug!("engine: {matcher_engine}");
         let stopped = Arc::new(AtomicBool::new(false));
         let stopped_clone = stopped.clone();
@@ -276,6 +417,12 @@ impl Matcher {
         let processed_clone = processed.clone();
         let matched = Arc::new(AtomicUsize::new(0));
         let matched_clone = matched.clone();
+        let windowed = Arc::new(AtomicUsize::new(0));
+        let windowed_clone = windowed.clone();
+        let took = Arc::new(AtomicU64::new(0));
+        let took_clone = took.clone();
+        let began = Instant::now();
+        let match_window = self.match_window.filter(|&window| window > 0);
         let rank_builder = self.rank_builder.clone();
 
         // Take items synchronously before spawning to avoid a race condition:
@@ -285,6 +432,7 @@ impl Matcher {
         let start = item_pool.num_taken();
         let items = item_pool.take();
         let total = items.len();
+        let tac = item_pool.tac();
         trace!("matcher start, total: {total}");
 
         // The coordinator runs on a dedicated OS thread so it does not occupy
@@ -293,6 +441,7 @@ impl Matcher {
         let num_workers = thread_pool.num_threads();
         let pool_for_work = Arc::clone(thread_pool);
 
+        let span = debug_span!(target: crate::tui::timings::TARGET, "matcher", items = total);
         std::thread::spawn(move || {
             // Process items in parallel using a shared work queue.  Each worker
             // thread atomically grabs the next available chunk, processes it,
@@ -303,6 +452,7 @@ impl Matcher {
             // The chunk size controls the granularity of work distribution and
             // the frequency of atomic counter updates / interrupt checks.
             const CHUNK_SIZE: usize = 1 << 12;
+            let _span = span.entered();
 
             // Convert items into an Arc slice so all workers can share them.
             let shared_items: Arc<[Arc<dyn SkimItem>]> = items.into();
@@ -312,6 +462,7 @@ impl Matcher {
             let interrupt_for_work = Arc::clone(&interrupt);
             let processed_for_work = Arc::clone(&processed);
             let matched_for_work = Arc::clone(&matched);
+            let windowed_for_work = Arc::clone(&windowed);
             let rank_builder_for_work = Arc::clone(&rank_builder);
 
             thread_pool::parallel_work_queue(
@@ -331,12 +482,22 @@ impl Matcher {
 
                     let mut local_matches = Vec::new();
                     let mut chunk_matched: usize = 0;
+                    let mut chunk_windowed: usize = 0;
 
                     for (i, item) in chunk.iter().enumerate() {
-                        if let Some(match_result) = matcher_engine.match_item(item.as_ref()) {
+                        if RemovedItem::is(item.as_ref()) {
+                            continue;
+                        }
+                        if match_window.is_some_and(|window| exceeds_window(item.as_ref(), window)) {
+                            chunk_windowed += 1;
+                        }
+                        if let Some(mut match_result) = matcher_engine.match_item(item.as_ref()) {
                             chunk_matched += 1;
+                            match_result.apply_external_ranges(item.as_ref());
                             let mut rank = match_result.rank;
-                            let index = chunk_start + i + start;
+                            // The position in the pool, whatever the order of the batch
+                            let offset = chunk_start + i;
+                            let index = start + if tac { total - 1 - offset } else { offset };
                             rank.index = i32::try_from(index).unwrap_or(i32::MAX);
                             local_matches.push(MatchedItem::new(
                                 Arc::clone(item),
@@ -352,6 +513,9 @@ impl Matcher {
                     if chunk_matched > 0 {
                         matched_for_work.fetch_add(chunk_matched, Ordering::Relaxed);
                     }
+                    if chunk_windowed > 0 {
+                        windowed_for_work.fetch_add(chunk_windowed, Ordering::Relaxed);
+                    }
 
                     local_matches
                 },
@@ -384,6 +548,8 @@ impl Matcher {
                     }
 
                     merge_worker_results(worker_results, no_sort, &processed_items, merge_strategy, &needs_render);
+                    let nanos = u64::try_from(began.elapsed().as_nanos()).unwrap_or(u64::MAX);
+                    took.store(nanos.max(1), Ordering::Relaxed);
                 },
             );
             stopped.store(true, Ordering::Relaxed);
@@ -394,6 +560,8 @@ impl Matcher {
             interrupt: interrupt_clone,
             matched: matched_clone,
             processed: processed_clone,
+            windowed: windowed_clone,
+            took: took_clone,
         }
     }
 }
@@ -5,6 +5,7 @@
 
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::sync::Arc;
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use derive_builder::Builder;
@@ -13,15 +14,16 @@ use ratatui_image::picker::Picker;
 use regex::Regex;
 
 use crate::binds::KeyMap;
+use crate::fuzzy_matcher::segment::Segmenter;
 use crate::item::RankCriteria;
 use crate::prelude::SkimItemReader;
 use crate::reader::CommandCollector;
-use crate::tui::event::Action;
-use crate::tui::options::{PreviewLayout, TuiLayout};
+use crate::tui::event::{Action, ActionCallback, ActionRegistry};
+use crate::tui::options::{Anchor, PreviewLayout, TruncateSide, TuiLayout};
 use crate::tui::statusline::{Info, InfoDisplay};
 use crate::tui::{BorderType, PreviewCallback};
 use crate::util::read_file_lines;
-use crate::{CaseMatching, FuzzyAlgorithm, Selector, Typos};
+use crate::{CaseMatching, FuzzyAlgorithm, MatchEngineFactory, Selector, Typos};
 
 #[cfg(feature = "cli")]
 /// Custom value parser for delimiter that handles escape sequences
@@ -30,6 +32,13 @@ fn parse_delimiter_value(s: &str) -> Result<Regex, String> {
     Regex::new(&unescaped).map_err(|e| format!("Invalid regex delimiter: {e}"))
 }
 
+#[cfg(feature = "cli")]
+/// Custom value parser for plain separators, handling the same escape sequences as the delimiter
+#[allow(clippy::unnecessary_wraps)]
+fn parse_separator_value(s: &str) -> Result<String, String> {
+    Ok(crate::util::unescape_delimiter(s))
+}
+
 /// Custom value parser for border
 ///
 /// Any undefined value falls back to [`BorderType::Plain`] (see the `FromStr` impl in `tui`)
@@ -64,6 +73,17 @@ impl clap::builder::TypedValueParser for BorderValueParser {
     }
 }
 
+#[cfg(feature = "cli")]
+/// Custom value parser for a field or a range of fields, as in `--nth`
+fn parse_field_range_value(s: &str) -> Result<String, String> {
+    match crate::field::FieldRange::from_str(s) {
+        Some(_) => Ok(s.to_string()),
+        None => Err(format!(
+            "Invalid field '{s}': expected a field or a range of fields, e.g. '2', '-1' or '2..'"
+        )),
+    }
+}
+
 #[cfg(feature = "cli")]
 /// Custom value parser for typo tolerance
 ///
@@ -82,6 +102,17 @@ fn parse_typos(s: &str) -> Result<Typos, String> {
     }
 }
 
+#[cfg(feature = "cli")]
+/// Custom value parser for `--preview-pane`, checking the multiplexer and the direction
+fn parse_preview_pane(s: &str) -> Result<String, String> {
+    match s.strip_prefix("tmux:") {
+        Some(spec) if matches!(spec.split(',').next(), Some("left" | "right" | "top" | "bottom")) => Ok(s.to_string()),
+        _ => Err(format!(
+            "Invalid preview pane '{s}': expected 'tmux:<left|right|top|bottom>[,SIZE]'"
+        )),
+    }
+}
+
 /// The options for `--scheme`
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 #[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
@@ -96,6 +127,68 @@ pub enum MatchScheme {
     History,
 }
 
+/// The options for `--print-selection-order`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum SelectionOrder {
+    /// In the order the items were selected
+    #[default]
+    Selection,
+    /// In the order of the list, the items no longer shown last
+    Display,
+    /// In the order of their index, the order they were read in
+    Input,
+}
+
+/// The options for `--tie-order`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum TieOrder {
+    /// In the order the items were read, reversed by `--tac`
+    #[default]
+    Input,
+    /// In the opposite order
+    Reverse,
+}
+
+/// The options for `--encoding`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Encoding {
+    /// Skip the lines that are not valid UTF-8
+    #[default]
+    Strict,
+    /// Replace the invalid bytes with `�`
+    Lossy,
+    /// Read the lines that are not valid UTF-8 as Latin-1
+    Latin1,
+}
+
+impl Encoding {
+    /// The text of `line`, `None` when the line is skipped
+    #[must_use]
+    pub fn decode(self, line: &[u8]) -> Option<std::borrow::Cow<'_, str>> {
+        match std::str::from_utf8(line) {
+            Ok(text) => Some(text.into()),
+            Err(_) => match self {
+                Self::Strict => None,
+                Self::Lossy => Some(String::from_utf8_lossy(line)),
+                Self::Latin1 => Some(line.iter().map(|&b| char::from(b)).collect::<String>().into()),
+            },
+        }
+    }
+}
+
+/// The options for `--grep-output`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum GrepOutput {
+    /// `+LINE FILE`
+    Vim,
+    /// `FILE:LINE:COL`
+    Vscode,
+}
+
 /// Image rendering protocols
 #[cfg(feature = "image")]
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
@@ -129,12 +222,39 @@ pub struct SkimOptions {
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Search"))]
     pub tac: bool,
 
+    /// Drop the items read again
+    ///
+    /// Only the first item with a text is kept, the status line counting the others as `dup:N`.
+    /// Handy for merged histories and concatenated logs. The header lines are never dropped.
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Search"))]
+    pub unique: bool,
+
+    /// Drop the items whose FIELD was read before, implies --unique
+    ///
+    /// FIELD is a field or a range of fields as in **nth**, read before **with-nth** is applied.
+    ///
+    /// e.g. sk --delimiter '\t' --unique-by 2
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, value_name = "FIELD", value_parser = parse_field_range_value, help_heading = "Search", verbatim_doc_comment)
+    )]
+    pub unique_by: Option<String>,
+
     /// Minimum query length to start showing results
     ///
     /// Only show results when the query is at least this many characters long
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Search"))]
     pub min_query_length: Option<usize>,
 
+    /// Show the K closest items when nothing matches
+    ///
+    /// When a search ends without a match, the K items closest to the query, as scored by the
+    /// typo-tolerant matcher, are shown dimmed with a `did you mean?` note in the info line, so a
+    /// typo can be fixed without clearing the query. Accepting one prints it as usual, but skim
+    /// exits with status 3. All the items are scanned once per search matching nothing.
+    #[cfg_attr(feature = "cli", arg(long, value_name = "K", help_heading = "Search"))]
+    pub suggest_on_empty: Option<usize>,
+
     /// Do not sort the results
     ///
     /// Often used in combination with --tac
@@ -142,6 +262,22 @@ pub struct SkimOptions {
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Search"))]
     pub no_sort: bool,
 
+    /// Sort the results worst match first
+    ///
+    /// Every criterion of **tiebreak** is reversed; equal ranks keep the order of **tie-order**.
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Search"))]
+    pub reverse_sort: bool,
+
+    /// Order of the results whose ranks are equal
+    ///
+    /// `input` keeps them in the order they were read, reversed by **tac**, and `reverse` in the
+    /// opposite order. Either way the order does not change as more items are read.
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, value_name = "ORDER", default_value = "input", help_heading = "Search")
+    )]
+    pub tie_order: TieOrder,
+
     /// Comma-separated list of sort criteria to apply when the scores are tied.
     ///
     /// * **score**: Score of the fuzzy match algorithm
@@ -163,6 +299,18 @@ pub struct SkimOptions {
     )]
     pub tiebreak: Vec<RankCriteria>,
 
+    /// Rhai script adjusting the score of each match
+    ///
+    /// The script gets the matched text as `text`, the query as `query` and the score of the match,
+    /// higher being better, as `score`, and returns the new score, anything else keeping it, e.g.
+    /// `--rank-hook 'if text.ends_with(".rs") { score + 50 } else { score }'`.
+    #[cfg(feature = "script")]
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, value_name = "SCRIPT", value_parser = crate::script::Script::new, help_heading = "Search")
+    )]
+    pub rank_hook: Option<crate::script::Script>,
+
     /// Fields to be matched
     ///
     /// A field index expression can be a non-zero integer or a range expression (`[BEGIN]..[END]`).
@@ -191,6 +339,25 @@ pub struct SkimOptions {
     )]
     pub nth: Vec<String>,
 
+    /// Field sets the `toggle-nth` action cycles through after **nth**
+    ///
+    /// The sets are separated by `|` and take the same comma-separated field index expressions
+    /// as **nth**, an empty set matching the whole line, e.g. `--nth -1 --nth-cycle '|1,2'` to
+    /// match the file name, the whole path or its two first directories. Without it,
+    /// `toggle-nth` switches between **nth** and the whole line. The active set, when it is not
+    /// **nth**, is shown in the status line.
+    #[cfg_attr(
+        feature = "cli",
+        arg(
+            long,
+            help_heading = "Search",
+            value_name = "NTH",
+            value_delimiter = '|',
+            allow_hyphen_values = true,
+        )
+    )]
+    pub nth_cycle: Vec<String>,
+
     /// Fields to be transformed
     ///
     /// See **nth** for the details
@@ -200,6 +367,18 @@ pub struct SkimOptions {
     )]
     pub with_nth: Vec<String>,
 
+    /// Rhai script mapping each line to the text displayed and matched
+    ///
+    /// The script gets the line as `text` and returns the new text, or `()` to keep the line,
+    /// which is still the output, e.g. `--map-item 'text.sub_string(text.index_of("/") + 1)'`.
+    /// The fields of **nth** and **hide-nth** are those of the new text.
+    #[cfg(feature = "script")]
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, value_name = "SCRIPT", value_parser = crate::script::Script::new, help_heading = "Search")
+    )]
+    pub map_item: Option<crate::script::Script>,
+
     /// Fields to hide from display while keeping them searchable
     ///
     /// Takes the same comma-separated field index expressions as **nth**. The listed
@@ -221,6 +400,44 @@ pub struct SkimOptions {
     )]
     pub hide_nth: Vec<String>,
 
+    /// Read precomputed match ranges from this field
+    ///
+    /// The field holds comma-separated `START-END` byte ranges (e.g. `4-7,12-15`) relative to the item text,
+    /// i.e. after **with-nth** is applied, so the field is typically removed with **with-nth**. The ranges are
+    /// highlighted instead of the matches found by the query, and are used for the `begin` and `end` tiebreaks.
+    /// This lets tools that already know the match positions, like `rg`, show them in skim.
+    ///
+    /// e.g. sk --delimiter '\t' --with-nth 2.. --match-ranges-field 1
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, value_name = "N", help_heading = "Search", verbatim_doc_comment)
+    )]
+    pub match_ranges_field: Option<usize>,
+
+    /// Truncate input lines longer than N bytes
+    ///
+    /// A single huge line (e.g. minified JavaScript) can stall matching and rendering. Longer lines are cut when read,
+    /// on a character boundary, and end with an ellipsis; the truncated text is what gets matched and printed.
+    /// See **skip-long-items** to drop them instead. The status line counts the lines truncated or skipped as
+    /// `long:N`.
+    #[cfg_attr(feature = "cli", arg(long, value_name = "N", help_heading = "Search"))]
+    pub max_item_length: Option<usize>,
+
+    /// Skip the lines longer than **max-item-length** instead of truncating them
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Search", requires = "max_item_length"))]
+    pub skip_long_items: bool,
+
+    /// Split each input line at the first SEP into the item and an annotation
+    ///
+    /// The annotation is never matched: it is shown dimmed at the right edge of the item's first line, e.g. a size, a
+    /// date or a score, and dropped from the output unless **print-annotation** is set. Escapes are supported, e.g.
+    /// `--annotation-sep '\x1f'`.
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, value_name = "SEP", help_heading = "Search", value_parser = parse_separator_value)
+    )]
+    pub annotation_sep: Option<String>,
+
     /// Delimiter between fields
     ///
     /// In regex format, defaults to AWK-style. Escape sequences like \x00, \t, \n are supported.
@@ -230,14 +447,52 @@ pub struct SkimOptions {
     )]
     pub delimiter: Regex,
 
+    /// Read `file:line:col:text` records, as printed by `rg --column --line-number --no-heading`
+    ///
+    /// Unless given, sets **delimiter** to `:`, **nth** to `4..` so that the query only matches
+    /// the text, **preview** to `builtin:file {1}` and the offset of **preview-window** to
+    /// `+{2}-/2`, centering the preview on the line, e.g. `sk --ansi --grep-mode -i -c 'rg --color=always
+    /// --column --line-number --no-heading {q}'`. See **grep-output** to open the result in an
+    /// editor.
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Search"))]
+    pub grep_mode: bool,
+
     /// Run in exact mode
     #[cfg_attr(feature = "cli", arg(short, long, help_heading = "Search"))]
     pub exact: bool,
 
+    /// Make exact terms match whole words only
+    ///
+    /// Exact terms, `'abc` or plain terms with **exact**, then only match where the characters
+    /// around them are not letters or digits, as `'abc'` terms always do: `'foo` matches "a foo.rs"
+    /// but not "foobar". Prefix and suffix terms are not affected.
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Search"))]
+    pub exact_boundary: bool,
+
     /// Start in regex mode instead of fuzzy-match
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Search"))]
     pub regex: bool,
 
+    /// Match the query as a single term
+    ///
+    /// Disables the extended search syntax: spaces, `|`, `!`, `'`, `^`, `$` and `FIELD:` scopes
+    /// are matched literally.
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Search"))]
+    pub no_extended: bool,
+
+    /// Names of the fields, for `NAME:term` scopes in the query
+    ///
+    /// A term prefixed with `FIELD:` only matches within that field, `FIELD` being a field
+    /// expression (see **nth**) or one of these names, the first naming field 1.
+    /// Fields are split at **delimiter**. Scopes are not available with **split-match**.
+    ///
+    /// e.g. `sk -d '[/.]' --field-names dir,name,ext` then `name:foo !ext:tmp`
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, value_name = "NAMES", value_delimiter = ',', help_heading = "Search")
+    )]
+    pub field_names: Vec<String>,
+
     /// Fuzzy matching algorithm
     ///
     /// - arinae (ari) Latest algorithm
@@ -277,10 +532,16 @@ pub struct SkimOptions {
     /// When passed without a value (`--typos`), uses adaptive formula (`pattern_length` / 4).
     /// When passed with a value (e.g. `--typos=2`), uses that exact number as the
     /// maximum allowed typos. `--typos=0` explicitly disables typo tolerance.
-    /// Applies to both fzy and frizbee matchers.
+    /// Also available as `--fuzzy-typos`.
+    ///
+    /// A fuzzy query term ending with `~` (`helo~`) tolerates typos even when they are disabled,
+    /// using the adaptive formula.
+    ///
+    /// Applies to the arinae, fzy and frizbee algorithms (see `--algo`); `skim_v2` and clangd
+    /// never tolerate typos.
     #[cfg_attr(
         feature = "cli",
-        arg(long, default_value = "disabled", default_missing_value = "smart", num_args = 0..=1, value_parser = parse_typos, overrides_with = "no_typos", help_heading = "Search")
+        arg(long, alias = "fuzzy-typos", default_value = "disabled", default_missing_value = "smart", num_args = 0..=1, value_parser = parse_typos, overrides_with = "no_typos", help_heading = "Search")
     )]
     pub typos: Typos,
 
@@ -288,12 +549,43 @@ pub struct SkimOptions {
     #[cfg_attr(feature = "cli", arg(long, overrides_with = "typos", help_heading = "Search"))]
     pub no_typos: bool,
 
-    /// Normalize unicode characters
+    /// Only fuzzy-match a window of BYTES bytes of longer items
     ///
-    /// When set, normalize accents and other unicode diacritics/others
-    #[cfg_attr(feature = "cli", arg(long, help_heading = "Search"))]
+    /// The window starts at the first occurrence of the query's first character, so very long
+    /// lines (minified files, logs) cost a bounded time to match. Matches extending past the
+    /// window are missed. The number of items whose matched text (see `--nth`) is longer than the
+    /// window is shown in the info line as `~N`.
+    #[cfg_attr(feature = "cli", arg(long, value_name = "BYTES", help_heading = "Search"))]
+    pub match_window: Option<usize>,
+
+    /// Ignore the accents of Latin letters when matching (default)
+    ///
+    /// The query and the items are matched with their accented letters folded to the base letter,
+    /// so `cafe` matches `café` and `lodz` matches `Łódź`; the items are displayed as they are.
+    /// Other scripts are not affected.
+    #[cfg_attr(
+        feature = "cli",
+        arg(
+            long,
+            default_value_t = true,
+            overrides_with = "no_normalize",
+            help_heading = "Search"
+        )
+    )]
     pub normalize: bool,
 
+    /// Match accented letters literally
+    #[cfg_attr(feature = "cli", arg(long, overrides_with = "normalize", help_heading = "Search"))]
+    pub no_normalize: bool,
+
+    /// Segment Chinese text into words when scoring matches
+    ///
+    /// Word starts found by jieba get the same bonus as characters following a separator, so
+    /// queries matching whole words rank higher. Only used by the arinae algorithm.
+    #[cfg(feature = "jieba")]
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Search"))]
+    pub jieba: bool,
+
     /// Enable split matching and set delimiter
     ///
     /// Split matching runs the matcher in splits: `foo:bar` will match all items matching `foo`, then
@@ -365,23 +657,50 @@ pub struct SkimOptions {
     )]
     pub bind: Vec<String>,
 
-    /// Enable multiple selection
+    /// Enable multiple selection, of at most N items if given
     ///
-    /// Uses Tab and S-Tab by default for selection
+    /// Uses Tab and S-Tab by default for selection. With N (e.g. `--multi=3`), selecting more
+    /// items is refused with a message in the status line.
     #[cfg_attr(
         feature = "cli",
-        arg(short, long, overrides_with = "no_multi", help_heading = "Interface")
+        arg(
+            short = 'm',
+            long = "multi",
+            value_name = "N",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "0",
+            overrides_with = "no_multi",
+            help_heading = "Interface"
+        )
     )]
+    #[builder(setter(skip))]
+    multi_arg: Option<usize>,
+
+    /// Enable multiple selection, `--multi`
+    #[cfg_attr(feature = "cli", clap(skip))]
     pub multi: bool,
 
+    /// At most this many items can be selected, `--multi=N`
+    #[cfg_attr(feature = "cli", clap(skip))]
+    pub multi_max: Option<usize>,
+
     /// Disable multiple selection
-    #[cfg_attr(feature = "cli", arg(long, overrides_with = "multi", help_heading = "Interface"))]
+    #[cfg_attr(feature = "cli", arg(long, overrides_with = "multi_arg", help_heading = "Interface"))]
     pub no_multi: bool,
 
     /// Disable mouse
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Interface"))]
     pub no_mouse: bool,
 
+    /// Characters ending a word in the query, besides whitespace
+    ///
+    /// Used by the word actions (`backward-kill-word`, `kill-word`, `unix-word-rubout`,
+    /// `backward-word`), e.g. `/.-_` to delete a path one component at a time. When unset, words
+    /// are runs of alphanumeric characters, and `unix-word-rubout` stops at whitespace only.
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Interface", value_name = "CHARS"))]
+    pub word_separators: Option<String>,
+
     /// Command to invoke dynamically in interactive mode
     ///
     /// Will be invoked using `sh -c` on Unix-like systems and `cmd /c` on Windows
@@ -395,6 +714,51 @@ pub struct SkimOptions {
     #[cfg_attr(feature = "cli", arg(short, long, help_heading = "Interface"))]
     pub interactive: bool,
 
+    /// Start without reading any items
+    ///
+    /// Neither stdin nor the default command is read, and the item count is hidden until items are loaded. The
+    /// query line and bound actions like `change:reload(...)` or `enter:become(...)` drive skim instead.
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Interface"))]
+    pub no_input: bool,
+
+    /// Read the items from a source built into skim instead of stdin or the default command
+    ///
+    /// The items are made of tab-separated fields, listed without a shell pipeline so that they
+    /// are also available on Windows. `ps` lists the processes (pid, parent pid and command line,
+    /// the parent pid hidden), `env` the environment variables (name and value), `git-files` the
+    /// files tracked by git and `git-branches` the local and remote branches (name, date of the
+    /// last commit and its subject). The items are separated by NUL chars with **read0**, which
+    /// keeps the git files with a newline in their name; these are left out otherwise. Each comes with a preview, and `reload` without a command
+    /// lists the items again, e.g. `sk --source ps --bind 'ctrl-r:reload'`. The `--delimiter`,
+    /// `--with-nth` and `--preview` options override the defaults.
+    /// Sources: ps, env, git-files, git-branches
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, value_name = "SOURCE", help_heading = "Interface", value_enum)
+    )]
+    pub source: Option<crate::reader::BuiltinSource>,
+
+    /// Read the items from the file at PATH instead of stdin or the default command
+    ///
+    /// A regular file is mapped in memory rather than read through a pipe: the lines are cut in
+    /// parallel and, without **nth**, **with-nth** or any other option changing the items, point
+    /// into the map instead of being copied, so that huge dictionaries or logs are listed almost at
+    /// once. A pipe or a FIFO, e.g. `<(cmd)`, is read as it comes. A `reload` without a command
+    /// reads the file again. The file should not change while skim runs.
+    /// With the `decompress` feature, a gzip or zstd file is decompressed while it is read, as with
+    /// **decompress**.
+    #[cfg_attr(feature = "cli", arg(long, value_name = "PATH", help_heading = "Interface"))]
+    pub input_file: Option<String>,
+
+    /// Decompress a gzip or zstd stream read from stdin
+    ///
+    /// The format is recognized from the start of the input, which goes through unchanged when it
+    /// is not compressed, as with `zcat -f`, so that `sk --decompress < app.log.gz` needs no `zcat`
+    /// in the pipeline. Concatenated gzip members and zstd frames are read one after the other.
+    #[cfg(feature = "decompress")]
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Interface"))]
+    pub decompress: bool,
+
     /// Replace replstr with the selected item in commands
     #[cfg_attr(feature = "cli", arg(short = 'I', default_value = "{}", help_heading = "Interface"))]
     pub replstr: String,
@@ -410,13 +774,57 @@ pub struct SkimOptions {
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Interface", verbatim_doc_comment))]
     pub highlight_line: bool,
 
+    /// Show the selection count (e.g. `3/10 selected`) at the right of the current line
+    ///
+    /// Only in multi-selection mode; the column is styled with the `selected` color.
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Interface"))]
+    pub selection_count: bool,
+
+    /// List at most N of the matched items
+    ///
+    /// The best N matches are kept in the list and a last row tells how many more matched, which
+    /// saves memory and time on huge inputs; the status line still counts all of them. The actions
+    /// on the list (`select-all`, `last`, ...) only see the listed items.
+    #[cfg_attr(feature = "cli", arg(long, value_name = "N", help_heading = "Interface"))]
+    pub max_display: Option<usize>,
+
     /// Disable horizontal scroll
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Interface"))]
     pub no_hscroll: bool,
 
+    /// Number of columns to keep visible after the match when scrolling long items
+    ///
+    /// Long items scroll horizontally to show their match, centered unless that leaves fewer
+    /// than COLS columns after it.
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, default_value = "10", value_name = "COLS", help_heading = "Interface")
+    )]
+    pub hscroll_off: usize,
+
+    /// Number of items to keep visible above and below the cursor when scrolling the list
+    ///
+    /// Capped at half the height of the list, so that the cursor can still move.
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, default_value = "0", value_name = "LINES", help_heading = "Interface")
+    )]
+    pub scroll_off: usize,
+
+    /// Most frames drawn per second for the updates of the reader, the matcher and the preview
+    ///
+    /// The keys are answered right away; lower it to spare CPU and bandwidth on slow terminals
+    /// or over SSH while many items are coming in.
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, default_value = "120", value_name = "FPS", help_heading = "Interface")
+    )]
+    pub max_fps: u32,
+
     /// Keep the right end of the line visible on overflow
     ///
-    /// Effective only when the query string is empty
+    /// Long items are anchored to their right end, useful for deep file paths. When the match
+    /// would be cut off that way, the items scroll to show it instead.
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Interface"))]
     pub keep_right: bool,
 
@@ -440,6 +848,46 @@ pub struct SkimOptions {
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Interface", verbatim_doc_comment))]
     pub no_clear_if_empty: bool,
 
+    /// Delay in milliseconds before re-running the command on reload
+    ///
+    /// When the query changes in interactive mode or a `reload` action fires, wait this long for further
+    /// changes before killing the running command and starting the new one, so that typing fast does not
+    /// spawn one command per keystroke. 0 reloads immediately.
+    #[cfg_attr(feature = "cli", arg(long, default_value = "100", help_heading = "Interface"))]
+    pub reload_delay: u64,
+
+    /// Do not parse the input read while typing, until the query stays unchanged for MS milliseconds
+    ///
+    /// The lines read from the command or stdin are kept as they are, and only split into items,
+    /// their ANSI codes and fields processed, once the typing stops. The matcher then gets the whole
+    /// CPU while the query changes, keeping the keystrokes fast during large bursts of input from
+    /// e.g. `fd` or `rg`. Items given through the library API are not deferred.
+    #[cfg_attr(feature = "cli", arg(long, value_name = "MS", help_heading = "Interface"))]
+    pub defer_ingest: Option<u64>,
+
+    /// Reload when a file under one of the PATHs changes
+    ///
+    /// The command is run again once the changes stop for a moment, as with a `reload` action
+    /// without a command, so that `sk -i -c 'rg --line-number {q}' --watch src` keeps the results
+    /// up to date while the files are edited. Directories are watched recursively. The status line
+    /// shows `watch:N`, N being the number of reloads caused by the changes.
+    #[cfg(feature = "watch")]
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, value_name = "PATH", num_args = 1.., help_heading = "Interface")
+    )]
+    pub watch: Vec<std::path::PathBuf>,
+
+    /// Reload every SECONDS
+    ///
+    /// The command is run again periodically, as with a `reload` action without a command, keeping
+    /// the query, the selected items and the item under the cursor, found again by their text. The
+    /// old items stay shown until the new ones arrive, and a run still going when the next one is
+    /// due is not interrupted. E.g. `sk -m --cmd 'kubectl get pods' --header-lines 1
+    /// --reload-interval 2` keeps a list of pods up to date.
+    #[cfg_attr(feature = "cli", arg(long, value_name = "SECONDS", help_heading = "Interface"))]
+    pub reload_interval: Option<f64>,
+
     /// Do not clear items on start
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Interface"))]
     pub no_clear_start: bool,
@@ -456,11 +904,48 @@ pub struct SkimOptions {
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Interface"))]
     pub show_cmd_error: bool,
 
+    /// Kill the command after this many seconds
+    ///
+    /// With `--show-cmd-error`, a line in the item list tells that the command timed out.
+    #[cfg_attr(feature = "cli", arg(long, value_name = "SECS", help_heading = "Interface"))]
+    pub cmd_timeout: Option<u64>,
+
+    /// Run the command again, up to N times, when it fails or times out without any output
+    ///
+    /// Meant for flaky commands, e.g. queries to a remote service in interactive mode.
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, value_name = "N", default_value = "0", help_heading = "Interface")
+    )]
+    pub cmd_retry: usize,
+
     /// Cycle the results by wrapping around when scrolling
+    ///
+    /// Moving up from the first item goes to the last one and the other way around, the `--header-lines` being
+    /// skipped. Paging stops at the ends of the list.
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Interface"))]
     pub cycle: bool,
 
+    /// Label characters of the `jump` and `jump-accept` actions
+    ///
+    /// The visible items are labeled in order with these characters, the first one
+    /// going to the item nearest to the prompt.
+    #[cfg_attr(
+        feature = "cli",
+        arg(
+            long,
+            default_value = "asdfghjklqwertyuiopzxcvbnm1234567890ASDFGHJKLQWERTYUIOPZXCVBNM",
+            value_name = "CHARS",
+            help_heading = "Interface"
+        )
+    )]
+    pub jump_labels: String,
+
     /// Disable matching entirely
+    ///
+    /// All the items are shown whatever the query, which is still typed and expanded as `{q}`, e.g. to
+    /// `reload` a grep. The `enable-search`, `disable-search` and `toggle-search` actions switch
+    /// matching on and off at runtime.
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Interface"))]
     pub disabled: bool,
 
@@ -505,9 +990,27 @@ pub struct SkimOptions {
     )]
     pub min_height: String,
 
+    /// Where to place skim's window when --height is set
+    ///
+    /// - cursor: start on the current line, scrolling the terminal if needed (default)
+    /// - top: stick to the top of the screen
+    /// - bottom: stick to the bottom of the screen
+    #[cfg_attr(
+        feature = "cli",
+        arg(
+            long,
+            value_enum,
+            default_value = "cursor",
+            help_heading = "Layout",
+            verbatim_doc_comment
+        )
+    )]
+    pub anchor: Anchor,
+
     /// Screen margin
     ///
-    /// For each side, can be either a row count or a percentage of the terminal size
+    /// For each side, can be either a row count or a percentage of skim's window
+    /// (the whole terminal, or the --height area)
     ///
     /// Format can be one of:
     ///     - TRBL
@@ -529,6 +1032,10 @@ pub struct SkimOptions {
     #[cfg_attr(feature = "cli", arg(long, default_value = "c> ", help_heading = "Layout"))]
     pub cmd_prompt: String,
 
+    /// Set the prompt of the secondary query, see **--query2**
+    #[cfg_attr(feature = "cli", arg(long, default_value = "& ", help_heading = "Layout"))]
+    pub prompt2: String,
+
     /// Set selected item icon
     #[cfg_attr(
         feature = "cli",
@@ -574,10 +1081,49 @@ pub struct SkimOptions {
     /// The characters used to display truncated lines
     #[cfg_attr(
         feature = "cli",
-        arg(long, hide = true, allow_hyphen_values = true, default_value = "...")
+        arg(
+            long,
+            allow_hyphen_values = true,
+            default_value = "...",
+            value_name = "STR",
+            help_heading = "Display"
+        )
     )]
     pub ellipsis: String,
 
+    /// Which side of the items wider than the list to cut
+    ///
+    /// `end` scrolls the items to show their match; `start` keeps the end of the items visible
+    /// unless that hides the match, as `--keep-right`; `middle` keeps both ends and cuts the
+    /// middle, unless the match is in the middle, where the items scroll as with `end`.
+    #[cfg_attr(feature = "cli", arg(long, default_value = "end", help_heading = "Display"))]
+    pub truncate_side: TruncateSide,
+
+    /// List the items under the group read from this field
+    ///
+    /// Matched items sharing the field, e.g. a directory or a repository, are shown together
+    /// under a header with the group and its matched count, groups in the order of their best
+    /// match. The field is read before **with-nth** is applied, so it can be left out of the
+    /// display. The `toggle-group` action collapses the group of the current item to its best
+    /// match, and expands it back.
+    ///
+    /// e.g. sk --delimiter ':' --with-nth 2.. --group-by-field 1
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, value_name = "N", help_heading = "Display", verbatim_doc_comment)
+    )]
+    pub group_by_field: Option<usize>,
+
+    /// Lay the items out as a tree of their `/`-separated paths
+    ///
+    /// Each item shows the last component of its path, indented under its directories,
+    /// which are listed too when only their contents match. The `toggle-group` action
+    /// collapses the directory of the current item, or expands it back.
+    ///
+    /// e.g. fd | sk --tree
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Display", verbatim_doc_comment))]
+    pub tree: bool,
+
     /// Set matching result count display position
     ///
     ///   - hidden  do not display info
@@ -633,6 +1179,59 @@ pub struct SkimOptions {
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Display", overrides_with = "border"))]
     pub no_border: bool,
 
+    /// Label shown on the outer edge of skim's borders, with `--border`
+    ///
+    /// The label sits on the top (or bottom, see `--border-label-pos`) border of the whole window, across the
+    /// panes. `change-border-label(...)` replaces it while skim runs.
+    /// e.g. sk --border rounded --border-label ' files '
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, help_heading = "Display", allow_hyphen_values = true, verbatim_doc_comment)
+    )]
+    pub border_label: Option<String>,
+
+    /// Position of the border label
+    ///
+    /// Format: `ALIGN[:top|bottom]`, ALIGN being `left`, `center` (the default) or `right`.
+    /// For compatibility with fzf, ALIGN can also be a number: 0 centers the label, positive
+    /// numbers align it left and negative numbers right.
+    /// e.g. sk --border --border-label ' files ' --border-label-pos right:bottom
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, help_heading = "Display", allow_hyphen_values = true, verbatim_doc_comment)
+    )]
+    pub border_label_pos: Option<String>,
+
+    /// Label shown on the border of the item list, with `--border`
+    ///
+    /// `change-list-label(...)` replaces it while skim runs.
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Display", allow_hyphen_values = true))]
+    pub list_label: Option<String>,
+
+    /// Position of the list label, see `--border-label-pos`
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Display", allow_hyphen_values = true))]
+    pub list_label_pos: Option<String>,
+
+    /// Label shown on the border of the input, with `--border`
+    ///
+    /// `change-input-label(...)` replaces it while skim runs.
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Display", allow_hyphen_values = true))]
+    pub input_label: Option<String>,
+
+    /// Position of the input label, see `--border-label-pos`
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Display", allow_hyphen_values = true))]
+    pub input_label_pos: Option<String>,
+
+    /// Label shown on the border of the header, with `--border`
+    ///
+    /// `change-header-label(...)` replaces it while skim runs.
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Display", allow_hyphen_values = true))]
+    pub header_label: Option<String>,
+
+    /// Position of the header label, see `--border-label-pos`
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Display", allow_hyphen_values = true))]
+    pub header_label_pos: Option<String>,
+
     /// Wrap items in the item list
     #[cfg_attr(feature = "cli", arg(long = "wrap", help_heading = "Display"))]
     pub wrap_items: bool,
@@ -675,6 +1274,7 @@ pub struct SkimOptions {
     /// History file
     ///
     /// Load search history from the specified file and update the file on completion.
+    /// Entries added by other skim instances in the meantime are kept.
     ///
     /// When enabled, CTRL-N and CTRL-P are automatically remapped
     /// to next-history and previous-history.
@@ -688,9 +1288,11 @@ pub struct SkimOptions {
     /// Command history file
     ///
     /// Load command query history from the specified file and update the file on completion.
+    /// Duplicates of the latest command are dropped and the file is replaced atomically.
     ///
     /// When enabled, CTRL-N and CTRL-P are automatically remapped
-    /// to next-history and previous-history.
+    /// to next-history and previous-history. Bind `history-search` (e.g. `--bind ctrl-s:history-search`)
+    /// to search it for a substring instead.
     #[cfg_attr(feature = "cli", arg(long = "cmd-history", help_heading = "History"))]
     pub cmd_history_file: Option<String>,
 
@@ -705,11 +1307,29 @@ pub struct SkimOptions {
     /// is the placeholder that is replaced to the single-quoted string of the current line. To transform the
     /// replacement string, specify field index expressions between the braces (See FIELD INDEX EXPRESSION for the details).
     ///
+    /// `{lines}` and `{columns}` are replaced by the size of the preview window, inside its borders. The output is
+    /// wrapped again when the terminal is resized, and the command only runs again if it uses them.
+    ///
+    /// The command also gets the size of the preview window as `$LINES` and `$COLUMNS`, and its size and
+    /// position as `$SKIM_PREVIEW_LINES`, `$SKIM_PREVIEW_COLUMNS`, `$SKIM_PREVIEW_TOP` and `$SKIM_PREVIEW_LEFT`,
+    /// also exported under the `FZF_PREVIEW_` prefix.
+    ///
+    /// The special command `builtin:file [PATH]` previews the file at PATH (the current line by default) with line
+    /// numbers, without spawning a process. When skim is built with the `syntect` feature, the file is also syntax
+    /// highlighted.
+    ///
+    /// Unless the preview runs in a pty, the command can start its output with a directive line such as
+    /// `#skim: offset=42; wrap=off`, which is not displayed. `offset=N` scrolls to line N, overriding the
+    /// **--preview-window** offset, and `wrap=on|off` sets line wrapping for this preview only.
+    ///
     /// **Examples**:
     ///
     /// ```bash
     /// sk --preview='head -$LINES {}'
+    /// sk --preview='chafa --size {columns}x{lines} {}'
     /// ls -l | sk --preview="echo user={3} when={-4..-2}; cat {-1}" --header-lines=1
+    /// rg --line-number '' | sk --delimiter : --preview 'builtin:file {1}' --preview-window +{2}-/2
+    /// rg --line-number '' | sk --delimiter : --preview 'printf "#skim: offset=%s\n" {2}; cat {1}'
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Preview", verbatim_doc_comment))]
     pub preview: Option<String>,
 
@@ -763,6 +1383,53 @@ pub struct SkimOptions {
     )]
     pub preview_window: PreviewLayout,
 
+    /// Continuation marker for wrapped preview lines
+    ///
+    /// With `--preview-window :wrap`, lines that do not fit are continued on the next rows after this marker, indented
+    /// like the line they continue, so that wrapped code stays readable.
+    /// e.g. sk --preview 'cat {}' --preview-window wrap --wrap-sign '↳ '
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, help_heading = "Preview", allow_hyphen_values = true, verbatim_doc_comment)
+    )]
+    pub wrap_sign: Option<String>,
+
+    /// Prefetch the previews of N items around the cursor
+    ///
+    /// Previews of the N items above and below the current one are computed in the background, at most 2N at a time,
+    /// and cached so that they show up instantly when moving the cursor. Does not apply to `pty` or image previews.
+    /// 0 disables prefetching.
+    #[cfg_attr(feature = "cli", arg(long, default_value = "0", help_heading = "Preview"))]
+    pub preview_prefetch: usize,
+
+    /// Label shown on the top border of the preview window
+    ///
+    /// The label is written as given, surround it with spaces to pad it from the border.
+    /// `change-preview-label(...)` replaces it while skim runs.
+    /// e.g. sk --preview 'git diff {}' --preview-label ' diff '
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, help_heading = "Preview", allow_hyphen_values = true, verbatim_doc_comment)
+    )]
+    pub preview_label: Option<String>,
+
+    /// Position of the preview label, see `--border-label-pos`
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Preview", allow_hyphen_values = true))]
+    pub preview_label_pos: Option<String>,
+
+    /// Show the preview in a tmux pane instead of the preview window
+    ///
+    /// Format: `tmux:<left|right|top|bottom>[,SIZE[%]]`, the size defaulting to 50%.
+    /// The pane is split off skim's own pane on the first preview, the preview command is re-run in it each time the
+    /// focus changes and it is killed when skim exits. The command gets a full terminal, so interactive pagers work.
+    /// `toggle-preview` closes and reopens the pane.
+    /// e.g. sk --preview 'less {}' --preview-pane tmux:right,40%
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, help_heading = "Preview", verbatim_doc_comment, value_parser = parse_preview_pane)
+    )]
+    pub preview_pane: Option<String>,
+
     /// Enable image preview
     ///
     /// This will render the preview argument as an image instead of running it as a command.
@@ -797,10 +1464,44 @@ pub struct SkimOptions {
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting"))]
     pub cmd_query: Option<String>,
 
+    /// Initial secondary query
+    ///
+    /// The secondary query is a second input line, and items have to match it as well as the
+    /// query. `toggle-query2` moves the cursor to it and back, and the line is shown while it has
+    /// the cursor or is not empty. Handy to keep narrowing the items (e.g. by extension) while
+    /// changing the query. In interactive mode, it is a fuzzy filter on the output of the command,
+    /// edited without running the command again.
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting"))]
+    pub query2: Option<String>,
+
     /// Read input delimited by ASCII NUL(\\0) characters
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting"))]
     pub read0: bool,
 
+    /// How to read the lines that are not valid UTF-8
+    ///
+    /// `strict` skips them. `lossy` replaces their invalid bytes with `�` and `latin1` reads them
+    /// as Latin-1, for display and matching only: the selected items are printed as they were read,
+    /// so that `rg --no-heading . | sk --encoding lossy` over files mixing encodings neither drops
+    /// nor mangles lines. A line printed changed, e.g. by **annotation-sep**, **max-item-length** or
+    /// an escape code without **ansi**, is printed decoded, as are the items given to
+    /// **strip-ansi**, **output-format** and the commands.
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, value_name = "ENCODING", default_value = "strict", help_heading = "Scripting")
+    )]
+    pub encoding: Encoding,
+
+    /// Read the input as changes to the items
+    ///
+    /// A line starting with `-` removes the items output as the rest of the line, one starting with
+    /// `+` adds the rest of the line, and the others are added as they are. Long-running commands
+    /// can then keep the list current without a reload: the removed items leave the list while the
+    /// query, the selected items and the cursor stay where they are. E.g. `tail -f changes | sk
+    /// --delta`, `changes` getting lines like `+task 12` and `-task 3`.
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting"))]
+    pub delta: bool,
+
     /// Print output delimited by ASCII NUL(\\0) characters
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting"))]
     pub print0: bool,
@@ -817,20 +1518,99 @@ pub struct SkimOptions {
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting"))]
     pub print_score: bool,
 
+    /// Print the byte offsets of the matched characters after each item, comma-separated
+    ///
+    /// Meant for **--filter**: the offsets (e.g. `12,13,20`) refer to the matched text, so that
+    /// other tools can highlight the matches the way skim does without matching again. With
+    /// **--print-score**, they follow the score.
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting"))]
+    pub print_positions: bool,
+
     /// Print the header as the first line (after print-score)
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting"))]
     pub print_header: bool,
 
+    /// Print the annotation of each item after it, separated by **annotation-sep**
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting", requires = "annotation_sep"))]
+    pub print_annotation: bool,
+
     /// Print the current (highlighted) item as the first line (after print-header)
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting"))]
     pub print_current: bool,
 
+    /// Order of the selected items in the output
+    ///
+    /// `selection` prints them in the order they were selected, `display` in the order of the
+    /// list, the ones filtered out by the query last, and `input` in the order they were read.
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, value_name = "ORDER", default_value = "selection", help_heading = "Scripting")
+    )]
+    pub print_selection_order: SelectionOrder,
+
+    /// Print the key that ended skim as the first line (after print-current)
+    ///
+    /// The key is written the way **--bind** reads it, modifiers first (e.g. `ctrl-alt-f5`,
+    /// `alt-A`), and the line is empty when skim was not ended by a key. Meant for scripts
+    /// that need to tell apart the keys bound to accept or abort.
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting"))]
+    pub print_all_keys: bool,
+
+    /// Exit with a custom code when skim is ended by a key, as `KEY:CODE` (e.g. `ctrl-d:3`)
+    ///
+    /// Comma-separated, or repeated. The code replaces the usual one (0, 1 or 130), whether the
+    /// key accepts or aborts, so that scripts can branch on how skim was exited: **--bind**
+    /// `ctrl-d:abort` **--exit-code** `ctrl-d:3` exits with 3 on ctrl-d and 130 on esc. The output
+    /// is written as usual.
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, help_heading = "Scripting", value_delimiter = ',', value_name = "KEY:CODE")
+    )]
+    pub exit_code: Vec<String>,
+
     /// Set the output format
     /// If set, overrides all `print_` options
     /// Will be expanded the same way as preview or commands
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting"))]
     pub output_format: Option<String>,
 
+    /// Print the selected `file:line[:col]:text` records as the arguments of an editor
+    ///
+    /// `vim` prints `+LINE FILE`, for `vim $(sk ...)`, and `vscode` prints `FILE:LINE:COL`, for
+    /// `code --goto $(sk ...)`. The items without a line number are printed as they are. Works
+    /// with or without **grep-mode**.
+    #[cfg_attr(feature = "cli", arg(long, value_name = "EDITOR", help_heading = "Scripting"))]
+    pub grep_output: Option<GrepOutput>,
+
+    /// Print each selected item as a jump target of an editor, expanding TEMPLATE with its fields
+    ///
+    /// **{file}**, **{line}** and **{col}** stand for the fields **{1}**, **{2}** and **{3}** of the
+    /// item, split by **delimiter**, and the other placeholders of **preview** are expanded for
+    /// the item, unquoted, e.g. `hx $(sk --grep-mode --print-jump '{file}:{line}:{col}')`.
+    #[cfg_attr(
+        feature = "cli",
+        arg(
+            long,
+            value_name = "TEMPLATE",
+            help_heading = "Scripting",
+            conflicts_with = "grep_output"
+        )
+    )]
+    pub print_jump: Option<String>,
+
+    /// Rhai script printing the selected items
+    ///
+    /// The script gets the output of the selected items as the array `items`, the query as `query`
+    /// and the name of the key that accepted as `key`, and returns the lines to print in their
+    /// place, one per element of an array, or `()` to print them as usual, e.g.
+    /// `--accept-hook 'items.map(|item| "file://" + item)'`.
+    #[cfg(feature = "script")]
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, value_name = "SCRIPT", value_parser = crate::script::Script::new, help_heading = "Scripting")
+    )]
+    pub accept_hook: Option<crate::script::Script>,
+
     /// Print the ANSI codes, making the output exactly match the input even when `--ansi` is on
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting", requires = "ansi"))]
     pub no_strip_ansi: bool,
@@ -843,6 +1623,40 @@ pub struct SkimOptions {
     #[cfg_attr(feature = "cli", arg(long, short = '0', help_heading = "Scripting"))]
     pub exit_0: bool,
 
+    /// Accept right away when a read of the command leaves only one item matching
+    ///
+    /// Unlike `--select-1`, applies each time the command is read while skim runs, i.e. after every `reload`, so
+    /// that narrowing commands (e.g. `--bind 'change:reload(...)'`) end the search when a single item is left.
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting"))]
+    pub select_1_on_reload: bool,
+
+    /// Exit right away when a read of the command leaves no item matching
+    ///
+    /// Unlike `--exit-0`, applies each time the command is read while skim runs, i.e. after every `reload`. Exits
+    /// with status 1, as when nothing is selected.
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting"))]
+    pub exit_0_on_reload: bool,
+
+    /// Command to run when skim starts
+    ///
+    /// Runs in the background, with its output discarded, once the interface is up (see the `start` event).
+    /// Placeholders are expanded as in **--preview**.
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting"))]
+    pub on_start: Option<String>,
+
+    /// Command to run when an item is accepted
+    ///
+    /// Placeholders are expanded as in **--preview**, e.g. `--on-accept 'echo {+} >> ~/picked.log'`. The output
+    /// is discarded and skim waits for the command to finish before exiting.
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting", verbatim_doc_comment))]
+    pub on_accept: Option<String>,
+
+    /// Command to run when skim is aborted
+    ///
+    /// Same as **--on-accept**, when skim exits without accepting, e.g. to clean up temporary files.
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting"))]
+    pub on_abort: Option<String>,
+
     /// Synchronous search for multi-staged filtering
     ///
     /// Synchronous search for multi-staged filtering. If specified,
@@ -872,10 +1686,54 @@ pub struct SkimOptions {
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting"))]
     pub pre_select_file: Option<String>,
 
+    /// Pre-select the items printed by this command
+    ///
+    /// The command runs through the shell once, before the items are read, and each line it prints
+    /// pre-selects the items with that text, as with **pre-select-items**. Handy to start from a
+    /// saved selection, e.g. `sk -m --pre-select-cmd 'git diff --name-only'`.
+    #[cfg_attr(feature = "cli", arg(long, value_name = "CMD", help_heading = "Scripting"))]
+    pub pre_select_cmd: Option<String>,
+
     /// Query for filter mode
     #[cfg_attr(feature = "cli", arg(long, short, help_heading = "Scripting"))]
     pub filter: Option<String>,
 
+    /// Sort the matches of **filter** by score
+    ///
+    /// Filter mode prints the matches in input order, as soon as the lines before them are matched,
+    /// so that the first ones come out while the input is still read. With this flag they are
+    /// sorted as in the TUI, and printed once the whole input is matched. **no-sort** wins.
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting"))]
+    pub filter_sort: bool,
+
+    /// Filter with the query, falling back to the TUI unless there is exactly one match
+    ///
+    /// Prints the match and exits without opening the TUI when QUERY matches a single item. Otherwise, the TUI opens
+    /// with QUERY pre-filled. Equivalent to `--query QUERY --select-1`.
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, value_name = "QUERY", help_heading = "Scripting", conflicts_with = "filter")
+    )]
+    pub filter_or_ui: Option<String>,
+
+    /// Shell command and flags used to run the preview, execute and reload commands
+    ///
+    /// The command is appended as the last argument, e.g. `--with-shell 'bash -c'` or
+    /// `--with-shell 'pwsh -NoProfile -Command'`. With the program alone, the flags are guessed from its
+    /// name. Defaults to `$SKIM_SHELL`, then `sh -c` (`cmd /C` on Windows).
+    ///
+    /// An empty string runs commands without a shell: they are split into words like a POSIX shell
+    /// would, without any expansion, pipe or redirection.
+    #[cfg_attr(feature = "cli", arg(long, value_name = "COMMAND", help_heading = "Scripting"))]
+    pub with_shell: Option<String>,
+
+    /// Show the `execute` and `execute-silent` commands in the status line instead of running them
+    ///
+    /// Useful to work out the placeholders of a binding interactively. The expanded commands are also logged,
+    /// see `--log-file`. `execute-echo(...)` does the same for a single binding.
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting"))]
+    pub dry_run: bool,
+
     /// Generate shell completion script
     ///
     /// Generate completion script for the specified shell: bash, zsh, fish, etc.
@@ -899,11 +1757,90 @@ pub struct SkimOptions {
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting", requires = "shell"))]
     pub shell_bindings: bool,
 
+    /// Generate the shell integration for SHELL: completions and key bindings
+    ///
+    /// Prints the completions and the key bindings (ctrl-t to pick files, ctrl-r to search the
+    /// history, alt-c to cd and the `**` completion trigger), after defaults for their variables
+    /// (`SKIM_CTRL_T_OPTS`, `SKIM_CTRL_R_OPTS`, `SKIM_ALT_C_OPTS`...) that apply only when unset.
+    /// Examples: `eval "$(sk --shell-init bash)"` in ~/.bashrc, `sk --shell-init fish | source`
+    /// Supported shells: bash, zsh, fish
+    #[cfg(feature = "cli")]
+    #[cfg_attr(
+        feature = "cli",
+        arg(
+            long,
+            value_name = "SHELL",
+            help_heading = "Scripting",
+            value_enum,
+            conflicts_with = "shell"
+        )
+    )]
+    pub shell_init: Option<crate::shell::Shell>,
+
+    /// Search the history of SHELL and print the chosen command
+    ///
+    /// Reads `$HISTFILE` (or the fish history), newest commands first and without duplicates, with
+    /// multi-line commands kept whole. Meant for a ctrl-r binding, e.g. in bash:
+    /// `bind -x '"\C-r": READLINE_LINE=$(sk --history-widget bash --query "$READLINE_LINE")'`.
+    /// Supported shells: bash, zsh, fish
+    #[cfg(feature = "cli")]
+    #[cfg_attr(
+        feature = "cli",
+        arg(
+            long,
+            value_name = "SHELL",
+            help_heading = "Scripting",
+            value_enum,
+            conflicts_with = "shell"
+        )
+    )]
+    pub history_widget: Option<crate::shell::Shell>,
+
+    /// Search the candidates of a shell completion instead of the input
+    ///
+    /// `path` and `dir` list the files (and directories) under the directory the query starts
+    /// with, the rest of the query being searched: `--query src/ma` searches `ma` under `src`.
+    /// `host` lists the hosts of the ssh config, `known_hosts` and `/etc/hosts`, `proc` the
+    /// processes of `ps -ef`. Used by the `**` completion of the key bindings, e.g.
+    /// `sk --completion-context host --query "$cur"`.
+    /// Contexts: path, dir, host, proc
+    #[cfg(feature = "cli")]
+    #[cfg_attr(
+        feature = "cli",
+        arg(
+            long,
+            value_name = "CONTEXT",
+            help_heading = "Scripting",
+            value_enum,
+            conflicts_with = "history_widget"
+        )
+    )]
+    pub completion_context: Option<crate::shell::CompletionContext>,
+
     /// Generate man page and output it to stdout
     #[cfg(feature = "cli")]
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting"))]
     pub man: bool,
 
+    /// Time every fuzzy algorithm on the lines of FILE and print the results
+    ///
+    /// Helps picking the **--algo** that suits some data. The other matching options (case,
+    /// typos, nth...) apply to every run.
+    #[cfg(feature = "cli")]
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, hide = true, num_args = 2, value_names = ["FILE", "PATTERN"], help_heading = "Scripting")
+    )]
+    pub bench_match: Option<Vec<String>>,
+
+    /// Apply the options of a profile defined in `SKIM_OPTIONS_FILE`
+    ///
+    /// The options listed under `[profile.NAME]` in the file are applied after the other
+    /// default options, so that only the command line overrides them.
+    #[cfg(feature = "cli")]
+    #[cfg_attr(feature = "cli", arg(long, value_name = "NAME", help_heading = "Scripting"))]
+    pub profile: Option<String>,
+
     /// Run an IPC socket with optional name (defaults to `sk`)
     ///
     /// The socket expects Actions in Ron format (similar to Rust code), see `./src/tui/event.rs` for all possible Actions
@@ -926,17 +1863,32 @@ pub struct SkimOptions {
     /// Format: `sk --popup <center|top|bottom|left|right>[,SIZE[%]][,SIZE[%]]`
     /// Note: this will try to detect a Zellij session, then a Tmux session
     /// This means that in nested sessions, `skim` will prioritize Zellij over Tmux
+    /// Tmux older than 3.2 has no popups, skim then runs in a split pane as with `--tmux-pane`
     #[cfg_attr(feature = "cli", arg(long, verbatim_doc_comment, help_heading = "Display", default_missing_value = "center,50%", num_args=0.., alias = "tmux"))]
     pub popup: Option<String>,
 
+    /// Run in a tmux pane split off the current one, instead of a popup
+    ///
+    /// Format: `sk --tmux-pane <bottom|top|left|right>[,SIZE[%]]`, bottom and 50% by default.
+    /// For tmux versions and terminals without popups; the input is forwarded to the pane.
+    #[cfg_attr(feature = "cli", arg(long, verbatim_doc_comment, help_heading = "Display", default_missing_value = "bottom,50%", num_args=0..))]
+    pub tmux_pane: Option<String>,
+
     /// Set the log level
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting"))]
     pub log_level: Option<log::LevelFilter>,
 
     /// Pipe log output to a file
-    #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting"))]
+    ///
+    /// The reads, the matcher runs (debug level) and the renders (trace level) are spans of the `skim::timings`
+    /// target, logged with their duration when they end, e.g. `SKIM_LOG=skim::timings=debug sk --log-file sk.log`.
+    #[cfg_attr(feature = "cli", arg(long, help_heading = "Scripting", verbatim_doc_comment))]
     pub log_file: Option<String>,
 
+    /// Show the durations of the last renders, matcher run and read over the interface
+    #[cfg_attr(feature = "cli", arg(long, hide = true, help_heading = "Scripting"))]
+    pub debug_overlay: bool,
+
     /// Feature flags
     #[cfg_attr(feature = "cli", arg(long, hide = true, help_heading = "Scripting"))]
     pub flags: Vec<FeatureFlag>,
@@ -948,15 +1900,9 @@ pub struct SkimOptions {
     #[cfg_attr(feature = "cli", arg(long, hide = true))]
     #[builder(setter(skip))]
     literal: bool,
-    #[cfg_attr(feature = "cli", arg(long, hide = true, default_value = "10"))]
-    #[builder(setter(skip))]
-    hscroll_off: usize,
     #[cfg_attr(feature = "cli", arg(long, hide = true))]
     #[builder(setter(skip))]
     filepath_word: bool,
-    #[cfg_attr(feature = "cli", arg(long, hide = true, default_value = ""))]
-    #[builder(setter(skip))]
-    jump_labels: String,
     #[cfg_attr(feature = "cli", arg(long, hide = true))]
     #[builder(setter(skip))]
     no_bold: bool,
@@ -977,15 +1923,6 @@ pub struct SkimOptions {
     padding: Option<String>,
     #[cfg_attr(feature = "cli", arg(long, hide = true))]
     #[builder(setter(skip))]
-    border_label: Option<String>,
-    #[cfg_attr(feature = "cli", arg(long, hide = true))]
-    #[builder(setter(skip))]
-    border_label_pos: Option<String>,
-    #[cfg_attr(feature = "cli", arg(long, hide = true))]
-    #[builder(setter(skip))]
-    wrap_sign: Option<String>,
-    #[cfg_attr(feature = "cli", arg(long, hide = true))]
-    #[builder(setter(skip))]
     no_multi_line: bool,
     #[cfg_attr(feature = "cli", arg(long, hide = true))]
     #[builder(setter(skip))]
@@ -1005,9 +1942,6 @@ pub struct SkimOptions {
     #[cfg_attr(feature = "cli", arg(long, hide = true, default_value = "0"))]
     #[builder(setter(skip))]
     freeze_right: usize,
-    #[cfg_attr(feature = "cli", arg(long, hide = true, default_value = "0"))]
-    #[builder(setter(skip))]
-    scroll_off: usize,
     #[cfg_attr(feature = "cli", arg(long, hide = true))]
     #[builder(setter(skip))]
     gutter: Option<String>,
@@ -1022,15 +1956,6 @@ pub struct SkimOptions {
     list_border: Option<String>,
     #[cfg_attr(feature = "cli", arg(long, hide = true))]
     #[builder(setter(skip))]
-    list_label: Option<String>,
-    #[cfg_attr(feature = "cli", arg(long, hide = true))]
-    #[builder(setter(skip))]
-    list_label_pos: Option<String>,
-    #[cfg_attr(feature = "cli", arg(long, hide = true))]
-    #[builder(setter(skip))]
-    no_input: bool,
-    #[cfg_attr(feature = "cli", arg(long, hide = true))]
-    #[builder(setter(skip))]
     info_command: Option<String>,
     #[cfg_attr(feature = "cli", arg(long, hide = true))]
     #[builder(setter(skip))]
@@ -1046,18 +1971,6 @@ pub struct SkimOptions {
     input_border: Option<String>,
     #[cfg_attr(feature = "cli", arg(long, hide = true))]
     #[builder(setter(skip))]
-    input_label: Option<String>,
-    #[cfg_attr(feature = "cli", arg(long, hide = true))]
-    #[builder(setter(skip))]
-    input_label_pos: Option<String>,
-    #[cfg_attr(feature = "cli", arg(long, hide = true))]
-    #[builder(setter(skip))]
-    preview_label: Option<String>,
-    #[cfg_attr(feature = "cli", arg(long, hide = true))]
-    #[builder(setter(skip))]
-    preview_label_pos: Option<String>,
-    #[cfg_attr(feature = "cli", arg(long, hide = true))]
-    #[builder(setter(skip))]
     header_first: bool,
     #[cfg_attr(feature = "cli", arg(long, hide = true))]
     #[builder(setter(skip))]
@@ -1077,11 +1990,12 @@ pub struct SkimOptions {
     #[cfg_attr(feature = "cli", arg(long, hide = true))]
     #[builder(setter(skip))]
     footer_label_pos: Option<String>,
-    #[cfg_attr(feature = "cli", arg(long, hide = true))]
-    #[builder(setter(skip))]
-    with_shell: Option<String>,
 
     /// Deprecated, kept for compatibility purposes. See `accept()` bind instead.
+    ///
+    /// Comma-separated list of keys, each bound to `accept(<key>)`: any key **--bind** can
+    /// parse, modifiers and function keys included (e.g. `ctrl-alt-x,f5`). The key is printed
+    /// before the selection, written the way **--bind** reads it.
     #[cfg_attr(feature = "cli", arg(long, help_heading = "Deprecated", default_value = ""))]
     expect: String,
 
@@ -1106,10 +2020,28 @@ pub struct SkimOptions {
     /// Used to define a function or closure for the preview window, instead of a shell command.
     ///
     /// The function will take a `Vec<Arc<dyn SkimItem>>>` containing the currently selected items
-    /// and return a Vec<String> with the lines to display in UTF-8
+    /// and return a Vec<String> with the lines to display in UTF-8, or, for callbacks built with
+    /// `PreviewCallback::text` or `PreviewCallback::widget`, styled text or a ratatui widget
     #[cfg_attr(feature = "cli", clap(skip))]
     #[debug(skip)]
     pub preview_fn: Option<PreviewCallback>,
+    /// Word segmenter for non-ASCII items
+    ///
+    /// Used by the arinae algorithm to find word starts in text that does not separate words
+    /// with spaces (e.g. Chinese or Japanese), which then get a boundary bonus.
+    #[cfg_attr(feature = "cli", clap(skip))]
+    #[builder(setter(into = false))]
+    #[debug(skip)]
+    pub segmenter: Option<Arc<dyn Segmenter>>,
+    /// Custom match engine factory
+    ///
+    /// Replaces the engines built from `algorithm`, `exact`, `regex` and the other matching
+    /// options, including the parsing of the extended query syntax. The engines' ranks are
+    /// still sorted by `tiebreak`, and their matched ranges highlighted.
+    #[cfg_attr(feature = "cli", clap(skip))]
+    #[builder(setter(into = false))]
+    #[debug(skip)]
+    pub engine_factory: Option<Rc<dyn MatchEngineFactory>>,
 
     /// The internal (parsed) keymap
     #[cfg_attr(feature = "cli", clap(skip))]
@@ -1122,6 +2054,30 @@ pub struct SkimOptions {
     /// its name is queued.
     #[cfg_attr(feature = "cli", clap(skip))]
     pub action_binds: std::collections::HashMap<String, Vec<Action>>,
+
+    /// The parsed **--exit-code** entries, keyed by the key ending skim
+    #[cfg_attr(feature = "cli", clap(skip))]
+    pub exit_codes: std::collections::HashMap<KeyEvent, i32>,
+
+    /// Custom actions, run by `plugin(name)` binds
+    #[cfg_attr(feature = "cli", clap(skip))]
+    pub actions: ActionRegistry,
+
+    /// Callback run when skim starts, after `on_start`
+    ///
+    /// The events it returns are processed like those of a `plugin(...)` action.
+    #[cfg_attr(feature = "cli", clap(skip))]
+    pub on_start_fn: Option<ActionCallback>,
+    /// Callback run when an item is accepted, after `on_accept`
+    ///
+    /// skim is exiting, the events it returns are ignored.
+    #[cfg_attr(feature = "cli", clap(skip))]
+    pub on_accept_fn: Option<ActionCallback>,
+    /// Callback run when skim is aborted, after `on_abort`
+    ///
+    /// skim is exiting, the events it returns are ignored.
+    #[cfg_attr(feature = "cli", clap(skip))]
+    pub on_abort_fn: Option<ActionCallback>,
 }
 
 impl Default for SkimOptions {
@@ -1137,48 +2093,96 @@ impl Default for SkimOptions {
             #[cfg(feature = "listen")]
             remote: None,
             print_header: false,
+            print_annotation: false,
             print_current: false,
+            print_selection_order: SelectionOrder::default(),
+            print_all_keys: false,
+            exit_code: Vec::new(),
             disabled: false,
             disable_pattern: None,
             tac: Default::default(),
+            unique: false,
+            unique_by: None,
             min_query_length: Default::default(),
+            suggest_on_empty: None,
             no_sort: Default::default(),
+            reverse_sort: false,
+            tie_order: TieOrder::default(),
             tiebreak: vec![RankCriteria::Score, RankCriteria::Begin, RankCriteria::End],
+            #[cfg(feature = "script")]
+            rank_hook: None,
             nth: Default::default(),
+            nth_cycle: Default::default(),
             with_nth: Default::default(),
+            #[cfg(feature = "script")]
+            map_item: None,
             hide_nth: Default::default(),
+            match_ranges_field: None,
+            group_by_field: None,
+            tree: false,
+            max_item_length: None,
+            skip_long_items: false,
+            annotation_sep: None,
             delimiter: Regex::new(r"[\t\n ]+").unwrap(),
+            grep_mode: false,
             exact: Default::default(),
             regex: Default::default(),
             algorithm: Default::default(),
             case: Default::default(),
             typos: Typos::Disabled,
             no_typos: false,
-            normalize: false,
+            no_extended: false,
+            exact_boundary: false,
+            field_names: Vec::new(),
+            match_window: None,
+            normalize: true,
+            no_normalize: false,
+            #[cfg(feature = "jieba")]
+            jieba: false,
             last_match: false,
             bind: Default::default(),
             multi: Default::default(),
+            multi_arg: None,
+            multi_max: None,
             no_multi: Default::default(),
             no_mouse: Default::default(),
+            word_separators: None,
             cmd: Default::default(),
             interactive: Default::default(),
+            no_input: false,
+            source: None,
+            input_file: None,
+            #[cfg(feature = "decompress")]
+            decompress: false,
             replstr: String::from("{}"),
             color: Default::default(),
             no_hscroll: Default::default(),
+            hscroll_off: 10,
+            scroll_off: 0,
+            max_fps: 120,
             keep_right: Default::default(),
             skip_to_pattern: Default::default(),
             no_clear_if_empty: Default::default(),
             no_clear_start: Default::default(),
             no_clear: Default::default(),
+            reload_delay: 100,
+            defer_ingest: None,
+            #[cfg(feature = "watch")]
+            watch: Vec::new(),
+            reload_interval: None,
             show_cmd_error: Default::default(),
+            cmd_timeout: None,
+            cmd_retry: 0,
             layout: TuiLayout::default(),
             reverse: Default::default(),
             height: String::from("100%"),
             no_height: Default::default(),
             min_height: String::from("10"),
+            anchor: Anchor::default(),
             margin: Default::default(),
             prompt: String::from("> "),
             cmd_prompt: String::from("c> "),
+            prompt2: String::from("& "),
             selector_icon: String::from(">"),
             multi_select_icon: String::from(">"),
             ansi: Default::default(),
@@ -1194,34 +2198,58 @@ impl Default for SkimOptions {
             cmd_history_size: 1000,
             preview: Default::default(),
             preview_window: PreviewLayout::default(),
+            wrap_sign: None,
+            preview_prefetch: 0,
+            preview_label: None,
+            preview_label_pos: None,
+            preview_pane: None,
             #[cfg(feature = "image")]
             image: None,
             #[cfg(feature = "image")]
             image_picker: None,
             query: Default::default(),
             cmd_query: Default::default(),
+            query2: Default::default(),
             read0: Default::default(),
+            encoding: Encoding::default(),
+            delta: Default::default(),
             print0: Default::default(),
             print_query: Default::default(),
             print_cmd: Default::default(),
             print_score: Default::default(),
+            print_positions: false,
             output_format: Default::default(),
+            grep_output: None,
+            print_jump: None,
+            #[cfg(feature = "script")]
+            accept_hook: None,
             select_1: Default::default(),
             exit_0: Default::default(),
+            select_1_on_reload: false,
+            exit_0_on_reload: false,
+            on_start: None,
+            on_accept: None,
+            on_abort: None,
             sync: Default::default(),
             pre_select_n: Default::default(),
             pre_select_pat: Default::default(),
             pre_select_items: Default::default(),
             pre_select_file: Default::default(),
+            pre_select_cmd: None,
             filter: Default::default(),
+            filter_sort: Default::default(),
+            filter_or_ui: Default::default(),
+            with_shell: Default::default(),
+            dry_run: Default::default(),
             popup: Default::default(),
+            tmux_pane: Default::default(),
             log_file: Default::default(),
+            debug_overlay: false,
             extended: Default::default(),
             literal: Default::default(),
             cycle: Default::default(),
-            hscroll_off: 10,
             filepath_word: Default::default(),
-            jump_labels: String::from("abcdefghijklmnopqrstuvwxyz"),
+            jump_labels: String::from("asdfghjklqwertyuiopzxcvbnm1234567890ASDFGHJKLQWERTYUIOPZXCVBNM"),
             border: Default::default(),
             border_no_collapse: Default::default(),
             no_bold: Default::default(),
@@ -1231,10 +2259,9 @@ impl Default for SkimOptions {
             style: Default::default(),
             no_color: Default::default(),
             padding: Default::default(),
-            border_label: Default::default(),
-            border_label_pos: Default::default(),
             highlight_line: Default::default(),
-            wrap_sign: Default::default(),
+            selection_count: false,
+            max_display: None,
             no_multi_line: Default::default(),
             raw: Default::default(),
             track: Default::default(),
@@ -1242,26 +2269,19 @@ impl Default for SkimOptions {
             gap_line: Default::default(),
             freeze_left: Default::default(),
             freeze_right: Default::default(),
-            scroll_off: Default::default(),
             gutter: Default::default(),
             gutter_raw: Default::default(),
             marker_multi_line: Default::default(),
             ellipsis: Default::default(),
+            truncate_side: TruncateSide::default(),
             scrollbar: Default::default(),
             no_scrollbar: Default::default(),
             list_border: Default::default(),
-            list_label: Default::default(),
-            list_label_pos: Default::default(),
-            no_input: Default::default(),
             info_command: Default::default(),
             separator: Default::default(),
             no_separator: Default::default(),
             ghost: Default::default(),
             input_border: Default::default(),
-            input_label: Default::default(),
-            input_label_pos: Default::default(),
-            preview_label: Default::default(),
-            preview_label_pos: Default::default(),
             header_first: Default::default(),
             header_border: Default::default(),
             header_lines_border: Default::default(),
@@ -1269,24 +2289,48 @@ impl Default for SkimOptions {
             footer_border: Default::default(),
             footer_label: Default::default(),
             footer_label_pos: Default::default(),
-            with_shell: Default::default(),
             expect: Default::default(),
             cmd_collector: Rc::new(RefCell::new(SkimItemReader::default())) as Rc<RefCell<dyn CommandCollector>>,
             query_history: Default::default(),
             cmd_history: Default::default(),
             selector: Default::default(),
             preview_fn: Default::default(),
+            segmenter: Default::default(),
+            engine_factory: None,
             keymap: Default::default(),
             action_binds: Default::default(),
+            exit_codes: Default::default(),
+            actions: Default::default(),
+            on_start_fn: None,
+            on_accept_fn: None,
+            on_abort_fn: None,
             #[cfg(feature = "cli")]
             shell: Default::default(),
             #[cfg(feature = "cli")]
+            history_widget: Default::default(),
+            #[cfg(feature = "cli")]
+            completion_context: Default::default(),
+            #[cfg(feature = "cli")]
             man: false,
             #[cfg(feature = "cli")]
+            bench_match: None,
+            #[cfg(feature = "cli")]
+            profile: None,
+            #[cfg(feature = "cli")]
             shell_bindings: false,
+            #[cfg(feature = "cli")]
+            shell_init: Default::default(),
             flags: Default::default(),
             log_level: Default::default(),
             no_border: false,
+            border_label: None,
+            border_label_pos: None,
+            list_label: None,
+            list_label_pos: None,
+            input_label: None,
+            input_label_pos: None,
+            header_label: None,
+            header_label_pos: None,
         }
     }
 }
@@ -1306,10 +2350,35 @@ impl SkimOptions {
     /// Finalizes the options by applying defaults and initializing components
     #[must_use]
     pub fn build(mut self) -> Self {
+        self.apply_multi_arg();
         if self.no_height {
             self.height = String::from("100%");
         }
 
+        #[cfg(feature = "cli")]
+        if self.history_widget.is_some() {
+            // History entries are NUL-separated so that multi-line commands stay whole
+            self.read0 = true;
+            self.multi = false;
+            self.multiline.get_or_insert(None);
+        }
+
+        #[cfg(feature = "cli")]
+        if let Some(context) = &self.completion_context {
+            use crate::shell::CompletionContext::{Dir, Path, Proc};
+            self.multi = matches!(context, Path | Proc);
+            if matches!(context, Path | Dir) && matches!(self.scheme, None | Some(MatchScheme::Default)) {
+                self.scheme = Some(MatchScheme::Path);
+            }
+        }
+
+        if let Some(source) = self.source {
+            self.apply_source_defaults(source);
+        }
+        if self.grep_mode {
+            self.apply_grep_defaults();
+        }
+
         if let Some(None) = self.multiline {
             if self.read0 {
                 self.multiline = Some(Some(String::from("\n")));
@@ -1331,6 +2400,8 @@ impl SkimOptions {
             .flat_map(|part| crate::binds::parse_action_binds(crate::binds::split_top_level(part, ',').into_iter()))
             .collect();
 
+        self.parse_expected_keys();
+
         if self.reverse {
             self.layout = TuiLayout::Reverse;
         }
@@ -1363,6 +2434,13 @@ impl SkimOptions {
         if self.no_typos {
             self.typos = Typos::Disabled;
         }
+        if self.no_normalize {
+            self.normalize = false;
+        }
+        #[cfg(feature = "jieba")]
+        if self.jieba && self.segmenter.is_none() {
+            self.segmenter = Some(Arc::new(crate::fuzzy_matcher::segment::JiebaSegmenter::new()));
+        }
         if self.no_border {
             self.border = BorderType::ForceOff;
         }
@@ -1372,7 +2450,25 @@ impl SkimOptions {
         {
             self.query = Some(filter_query.clone());
         }
+        // the matches of filter mode come in input order unless sorted on request
+        if self.filter.is_some() && !self.filter_sort {
+            self.no_sort = true;
+        }
+
+        if let Some(ref filter_query) = self.filter_or_ui {
+            if self.query.is_none() {
+                self.query = Some(filter_query.clone());
+            }
+            self.select_1 = true;
+        }
 
+        self.apply_scheme();
+
+        self
+    }
+
+    /// Applies the tiebreaks of the `--scheme`
+    fn apply_scheme(&mut self) {
         match self.scheme {
             None | Some(MatchScheme::Default) => (),
             Some(MatchScheme::Path) => {
@@ -1382,9 +2478,74 @@ impl SkimOptions {
             }
             Some(MatchScheme::History) => self.tiebreak.insert(0, RankCriteria::Index),
         }
+    }
 
-        self
+    /// Applies `--multi[=N]` to `multi` and `multi_max`
+    fn apply_multi_arg(&mut self) {
+        if let Some(max) = self.multi_arg.take() {
+            self.multi = true;
+            self.multi_max = (max > 0).then_some(max);
+        }
     }
+
+    /// Whether the delimiter is the default one, escaped or unescaped by the command line
+    fn has_default_delimiter(&self) -> bool {
+        matches!(self.delimiter.as_str(), r"[\t\n ]+" | "[\t\n ]+")
+    }
+
+    /// Applies the delimiter, displayed fields and preview of a built-in source, unless given
+    fn apply_source_defaults(&mut self, source: crate::reader::BuiltinSource) {
+        if self.has_default_delimiter() {
+            self.delimiter = Regex::new("\t").expect("a tab is a valid regex");
+        }
+        if self.with_nth.iter().all(String::is_empty) {
+            self.with_nth = source.with_nth().iter().map(|&field| field.to_string()).collect();
+        }
+        self.preview.get_or_insert_with(|| source.preview().to_string());
+        if source == crate::reader::BuiltinSource::GitFiles && matches!(self.scheme, None | Some(MatchScheme::Default))
+        {
+            self.scheme = Some(MatchScheme::Path);
+        }
+    }
+
+    /// Applies the delimiter, matched fields and centered preview of `--grep-mode`, unless given
+    fn apply_grep_defaults(&mut self) {
+        if self.has_default_delimiter() {
+            self.delimiter = Regex::new(":").expect("a colon is a valid regex");
+        }
+        if self.nth.iter().all(String::is_empty) {
+            self.nth = vec![String::from("4..")];
+        }
+        self.preview.get_or_insert_with(|| String::from("builtin:file {1}"));
+        self.preview_window
+            .offset
+            .get_or_insert_with(|| String::from("+{2}-/2"));
+    }
+
+    /// Binds the **--expect** keys to `accept`, and parses the **--exit-code** entries
+    fn parse_expected_keys(&mut self) {
+        for key in self.expect.split(',').filter(|key| !key.is_empty()) {
+            match crate::binds::parse_key(key) {
+                Ok(key) => {
+                    let name = crate::binds::key_name(&key);
+                    self.keymap.insert(key, vec![Action::Accept(Some(name))]);
+                }
+                Err(err) => debug!("Failed to parse expected key {key}: {err}"),
+            }
+        }
+
+        for entry in &self.exit_code {
+            let parsed = entry
+                .rsplit_once(':')
+                .and_then(|(key, code)| Some((crate::binds::parse_key(key).ok()?, code.parse().ok()?)));
+            if let Some((key, code)) = parsed {
+                self.exit_codes.insert(key, code);
+            } else {
+                debug!("Failed to parse exit code {entry}");
+            }
+        }
+    }
+
     /// Initializes history from configured history files
     pub fn init_histories(&mut self) {
         if let Some(histfile) = &self.history_file {
@@ -1430,13 +2591,15 @@ impl SkimOptions {
     /// process environment — which makes it unit-testable and platform-agnostic.
     ///
     /// Precedence (lowest to highest): `SKIM_OPTIONS_FILE` contents, then
-    /// `SKIM_DEFAULT_OPTIONS`, then the real CLI args. `default_command`
+    /// `SKIM_DEFAULT_OPTIONS`, then the file's `[profile.NAME]` section selected
+    /// by `--profile NAME`, then the real CLI args. `default_command`
     /// (`SKIM_DEFAULT_COMMAND`) only fills `cmd` when no `--cmd` was given,
     /// falling back to [`crate::SKIM_DEFAULT_COMMAND`].
     ///
     /// # Errors
     ///
-    /// Returns a [`clap::Error`] if argument parsing fails.
+    /// Returns a [`clap::Error`] if argument parsing fails or if the profile is
+    /// not defined.
     #[cfg(feature = "cli")]
     pub(crate) fn merge_args_and_parse(
         prog: String,
@@ -1448,6 +2611,9 @@ impl SkimOptions {
         use clap::Parser;
 
         let mut args = vec![prog];
+        let default_args = default_options.and_then(shlex::split).unwrap_or_default();
+        let cli_args: Vec<String> = cli_args.into_iter().collect();
+        let mut profiles = std::collections::HashMap::new();
 
         if let Some(content) = options_file_content {
             let mut in_comment = false;
@@ -1458,7 +2624,7 @@ impl SkimOptions {
                     (_, _, b'\n') => {
                         in_comment = false;
                         pending_comment = false;
-                        Some(b' ')
+                        Some(b'\n')
                     }
                     (true, _, _) => {
                         pending_comment = false;
@@ -1481,19 +2647,55 @@ impl SkimOptions {
                 })
                 .collect::<Vec<_>>();
             let parsed = String::from_utf8_lossy(&without_comments);
-            args.extend(shlex::split(&parsed).unwrap_or_default());
+            let mut global = String::new();
+            let mut section = &mut global;
+            for line in parsed.lines() {
+                if let Some(name) = line.trim().strip_prefix("[profile.").and_then(|l| l.strip_suffix(']')) {
+                    section = profiles.entry(name.to_string()).or_insert_with(String::new);
+                    continue;
+                }
+                section.push_str(line);
+                section.push(' ');
+            }
+            args.extend(shlex::split(&global).unwrap_or_default());
         }
 
-        args.extend(default_options.and_then(shlex::split).unwrap_or_default());
+        args.extend(default_args.iter().cloned());
+        if let Some(name) = Self::profile_name(default_args.iter().chain(&cli_args)) {
+            let Some(profile) = profiles.get(&name) else {
+                return Err(clap::Error::raw(
+                    clap::error::ErrorKind::InvalidValue,
+                    format!("unknown profile '{name}', define it with [profile.{name}] in SKIM_OPTIONS_FILE\n"),
+                ));
+            };
+            args.extend(shlex::split(profile).unwrap_or_default());
+        }
         args.extend(cli_args);
 
         Self::try_parse_from(args).map(|mut opts| {
-            if opts.cmd.is_none() {
+            opts.apply_multi_arg();
+            // a built-in source or an input file replaces the default command
+            if opts.cmd.is_none() && opts.source.is_none() && opts.input_file.is_none() {
                 opts.cmd = Some(default_command.unwrap_or_else(|| crate::SKIM_DEFAULT_COMMAND.to_string()));
             }
             opts
         })
     }
+
+    /// Value of the last `--profile` in `args`
+    #[cfg(feature = "cli")]
+    fn profile_name<'a>(args: impl IntoIterator<Item = &'a String>) -> Option<String> {
+        let mut args = args.into_iter();
+        let mut name = None;
+        while let Some(arg) = args.next() {
+            if arg == "--profile" {
+                name = args.next().cloned();
+            } else if let Some(value) = arg.strip_prefix("--profile=") {
+                name = Some(value.to_string());
+            }
+        }
+        name
+    }
 }
 
 /// Feature flags
@@ -49,6 +49,69 @@ fn merge_explicit_cmd_flag_overrides_default_command() {
     assert_eq!(opts.cmd.as_deref(), Some("echo flag"));
 }
 
+#[test]
+fn merge_builtin_source_replaces_default_command() {
+    // A built-in source is read instead of SKIM_DEFAULT_COMMAND.
+    let opts = merge(None, Some("--source ps"), Some("echo hello"));
+    assert_eq!(opts.cmd, None);
+}
+
+#[test]
+fn merge_input_file_replaces_default_command() {
+    let opts = merge(None, Some("--input-file words.txt"), Some("echo hello"));
+    assert_eq!(opts.input_file.as_deref(), Some("words.txt"));
+    assert_eq!(opts.cmd, None);
+}
+
+#[test]
+fn merge_multi_takes_an_optional_limit() {
+    let opts = merge(None, Some("-m"), None);
+    assert!(opts.multi);
+    assert_eq!(opts.multi_max, None);
+
+    let opts = merge(None, Some("--multi=3"), None);
+    assert!(opts.multi);
+    assert_eq!(opts.multi_max, Some(3));
+
+    let opts = merge(None, Some("--multi=3 --no-multi"), None);
+    assert!(!opts.multi);
+    assert_eq!(opts.multi_max, None);
+}
+
+#[test]
+fn merge_nth_cycle_splits_the_sets_at_bars() {
+    let opts = merge(None, Some("--nth -1 --nth-cycle |1,2"), None);
+    assert_eq!(opts.nth, vec!["-1"]);
+    assert_eq!(opts.nth_cycle, vec!["", "1,2"]);
+}
+
+#[test]
+fn merge_parses_the_sort_direction_and_tie_order() {
+    let opts = merge(None, None, None);
+    assert!(!opts.reverse_sort);
+    assert_eq!(opts.tie_order, TieOrder::Input);
+
+    let opts = merge(None, Some("--reverse-sort --tie-order reverse"), None);
+    assert!(opts.reverse_sort);
+    assert_eq!(opts.tie_order, TieOrder::Reverse);
+}
+
+#[test]
+fn merge_parses_the_encoding() {
+    assert_eq!(merge(None, None, None).encoding, Encoding::Strict);
+    let opts = merge(Some(b"--encoding latin1"), Some("--encoding lossy"), None);
+    assert_eq!(opts.encoding, Encoding::Lossy);
+}
+
+#[test]
+fn merge_rejects_an_invalid_unique_by_field() {
+    let parse = |default_options| {
+        SkimOptions::merge_args_and_parse("sk".to_string(), None, Some(default_options), std::iter::empty(), None)
+    };
+    assert_eq!(parse("--unique-by 2..").unwrap().unique_by.as_deref(), Some("2.."));
+    assert!(parse("--unique-by two").is_err());
+}
+
 #[test]
 fn merge_applies_skim_default_options() {
     // SKIM_DEFAULT_OPTIONS is shlex-split and merged into the args.
@@ -92,6 +155,39 @@ fn merge_precedence_cli_args_override_default_options() {
     assert_eq!(opts.prompt, "from-cli ");
 }
 
+#[test]
+fn merge_applies_the_selected_profile_under_cli_args() {
+    let content = b"--prompt 'file '\n\
+[profile.grep]\n\
+--prompt 'grep ' # from the profile\n\
+--ansi\n\
+[profile.files]\n\
+--multi\n";
+    let parse = |default_options: Option<&str>, cli: &[&str]| {
+        SkimOptions::merge_args_and_parse(
+            "sk".to_string(),
+            Some(content),
+            default_options,
+            cli.iter().map(ToString::to_string),
+            None,
+        )
+    };
+
+    let opts = parse(None, &[]).unwrap();
+    assert_eq!(opts.prompt, "file ");
+    assert!(!opts.ansi && !opts.multi);
+
+    let opts = parse(Some("--prompt 'env '"), &["--profile", "grep"]).unwrap();
+    assert_eq!(opts.prompt, "grep ");
+    assert!(opts.ansi && !opts.multi);
+
+    let opts = parse(Some("--profile=files"), &["--prompt", "cli "]).unwrap();
+    assert_eq!(opts.prompt, "cli ");
+    assert!(opts.multi && !opts.ansi);
+
+    assert!(parse(None, &["--profile", "nope"]).is_err());
+}
+
 #[test]
 fn build_no_height_forces_full_height() {
     let opts = SkimOptions {
@@ -135,6 +231,42 @@ fn build_reverse_sets_reverse_layout() {
     assert_eq!(opts.layout, TuiLayout::Reverse);
 }
 
+#[test]
+fn build_binds_expected_keys_to_accept_with_their_name() {
+    let opts = SkimOptions {
+        expect: String::from("alt-ctrl-x,f5,notakey"),
+        ..Default::default()
+    }
+    .build();
+    assert_eq!(
+        opts.keymap.get(&KeyEvent::new(
+            KeyCode::Char('x'),
+            KeyModifiers::CONTROL | KeyModifiers::ALT
+        )),
+        Some(&vec![Action::Accept(Some(String::from("ctrl-alt-x")))])
+    );
+    assert_eq!(
+        opts.keymap.get(&KeyEvent::new(KeyCode::F(5), KeyModifiers::NONE)),
+        Some(&vec![Action::Accept(Some(String::from("f5")))])
+    );
+}
+
+#[test]
+fn build_parses_exit_codes_by_key() {
+    let opts = SkimOptions {
+        exit_code: vec![String::from("ctrl-d:3"), String::from("f5:4"), String::from("esc:x")],
+        ..Default::default()
+    }
+    .build();
+    assert_eq!(
+        opts.exit_codes,
+        std::collections::HashMap::from([
+            (KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL), 3),
+            (KeyEvent::new(KeyCode::F(5), KeyModifiers::NONE), 4),
+        ])
+    );
+}
+
 #[test]
 fn build_no_scrollbar_clears_scrollbar() {
     let opts = SkimOptions {
@@ -143,7 +275,7 @@ fn build_no_scrollbar_clears_scrollbar() {
         ..Default::default()
     }
     .build();
-    assert!(opts.scrollbar.is_empty());
+    assert_eq!(opts.scrollbar, "");
 }
 
 #[test]
@@ -210,6 +342,18 @@ fn build_filter_does_not_override_existing_query() {
     assert_eq!(opts.query.as_deref(), Some("explicit"));
 }
 
+#[test]
+fn build_filter_or_ui_sets_query_and_select_1() {
+    let opts = SkimOptions {
+        filter_or_ui: Some(String::from("needle")),
+        ..Default::default()
+    }
+    .build();
+    assert_eq!(opts.query.as_deref(), Some("needle"));
+    assert!(opts.select_1);
+    assert!(opts.filter.is_none());
+}
+
 #[test]
 fn build_scheme_path_adjusts_tiebreak() {
     let opts = SkimOptions {
@@ -232,6 +376,45 @@ fn build_scheme_history_prepends_index() {
     assert_eq!(opts.tiebreak.first(), Some(&RankCriteria::Index));
 }
 
+#[test]
+fn build_builtin_source_sets_its_defaults() {
+    let opts = merge(None, Some("--source ps"), None).build();
+    assert_eq!(opts.delimiter.as_str(), "\t");
+    assert_eq!(opts.with_nth, vec!["1", "3"]);
+    assert_eq!(
+        opts.preview.as_deref(),
+        Some(crate::reader::BuiltinSource::Ps.preview())
+    );
+
+    let opts = merge(None, Some("--source git-files --preview 'cat {}' -d :"), None).build();
+    assert_eq!(opts.delimiter.as_str(), ":");
+    assert_eq!(opts.preview.as_deref(), Some("cat {}"));
+    assert_eq!(opts.scheme, Some(MatchScheme::Path));
+}
+
+#[test]
+fn build_grep_mode_sets_its_defaults() {
+    let opts = merge(None, Some("--grep-mode"), None).build();
+    assert_eq!(opts.delimiter.as_str(), ":");
+    assert_eq!(opts.nth, vec!["4.."]);
+    assert_eq!(opts.preview.as_deref(), Some("builtin:file {1}"));
+    assert_eq!(opts.preview_window.offset.as_deref(), Some("+{2}-/2"));
+
+    let opts = merge(
+        None,
+        Some("--grep-mode -n 1,4.. --preview 'bat {1}' --preview-window up:+{2}"),
+        None,
+    )
+    .build();
+    assert_eq!(opts.nth, vec!["1", "4.."]);
+    assert_eq!(opts.preview.as_deref(), Some("bat {1}"));
+    assert_eq!(opts.preview_window.offset.as_deref(), Some("+{2}"));
+    assert_eq!(
+        merge(None, Some("--grep-output vscode"), None).grep_output,
+        Some(GrepOutput::Vscode)
+    );
+}
+
 #[test]
 fn build_default_keymap_is_populated() {
     let opts = SkimOptions::default().build();
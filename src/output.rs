@@ -1,9 +1,10 @@
 use std::io::{self, Write};
 
+use crossterm::event::KeyCode;
 use derive_builder::Builder;
 
 use crate::item::MatchedItem;
-use crate::options::SkimOptions;
+use crate::options::{GrepOutput, SkimOptions};
 use crate::tui::Event;
 use crate::tui::event::Action;
 
@@ -36,9 +37,25 @@ pub struct SkimOutput {
 
     /// The header
     pub header: String,
+
+    /// Whether the output items are near misses shown by `suggest_on_empty`, nothing matching
+    /// the query
+    pub suggested: bool,
 }
 
 impl SkimOutput {
+    /// The payload of the `accept(payload)` action that ended skim, if any
+    ///
+    /// Bindings like `ctrl-e:accept(edit)` and `ctrl-o:accept(open)` let the caller dispatch on
+    /// the payload, which the CLI prints on its own line before the selection.
+    #[must_use]
+    pub fn accept_payload(&self) -> Option<&str> {
+        match &self.final_event {
+            Event::Action(Action::Accept(payload)) => payload.as_deref(),
+            _ => None,
+        }
+    }
+
     /// Serialize this output to `out` according to the CLI output options.
     ///
     /// This is the formatting half of skim's output and is intentionally
@@ -82,34 +99,65 @@ impl SkimOutput {
 
         if opts.print_current {
             if let Some(ref current) = self.current {
-                write!(out, "{}{}", current.output(), opts.output_ending)?;
+                out.write_all(&current.output_bytes())?;
+                write!(out, "{}", opts.output_ending)?;
             } else {
                 write!(out, "{}", opts.output_ending)?;
             }
         }
 
-        if let Event::Action(Action::Accept(Some(accept_key))) = &self.final_event {
-            write!(out, "{}{}", accept_key, opts.output_ending)?;
-        }
-
-        for item in &self.selected_items {
-            if opts.strip_ansi {
-                write!(
-                    out,
-                    "{}{}",
-                    crate::helper::item::strip_ansi(&item.output()).0,
-                    opts.output_ending
-                )?;
+        if opts.print_all_keys {
+            if self.final_key.code == KeyCode::Null {
+                write!(out, "{}", opts.output_ending)?;
             } else {
-                write!(out, "{}{}", item.output(), opts.output_ending)?;
+                write!(out, "{}{}", crate::binds::key_name(&self.final_key), opts.output_ending)?;
             }
-            if opts.print_score {
-                write!(out, "{}{}", item.rank.score, opts.output_ending)?;
+        }
+
+        if let Some(payload) = self.accept_payload() {
+            write!(out, "{}{}", payload, opts.output_ending)?;
+        }
+
+        #[cfg(feature = "script")]
+        if let Some(lines) = opts
+            .accept_hook
+            .as_ref()
+            .and_then(|hook| self.run_accept_hook(hook, opts))
+        {
+            for line in lines {
+                write!(out, "{line}{}", opts.output_ending)?;
             }
+            return Ok(());
+        }
+
+        for item in &self.selected_items {
+            opts.write_item(out, item, &self.query, &self.cmd)?;
         }
 
         Ok(())
     }
+
+    /// The lines printed by `hook` in place of the selected items, if any (`--accept-hook`)
+    #[cfg(feature = "script")]
+    fn run_accept_hook(&self, hook: &crate::script::Script, opts: &BinOptions) -> Option<Vec<String>> {
+        let items = self
+            .selected_items
+            .iter()
+            .map(|item| {
+                if opts.strip_ansi {
+                    crate::helper::item::strip_ansi(&item.output()).0
+                } else {
+                    item.output().into_owned()
+                }
+            })
+            .collect();
+        let key = if self.final_key.code == KeyCode::Null {
+            String::new()
+        } else {
+            crate::binds::key_name(&self.final_key)
+        };
+        hook.accept(items, &self.query, &key)
+    }
 }
 
 /// Options controlling how a [`SkimOutput`] is serialized to the terminal.
@@ -124,10 +172,16 @@ pub struct BinOptions {
     print_query: bool,
     print_cmd: bool,
     print_score: bool,
+    print_positions: bool,
     print_header: bool,
     print_current: bool,
+    print_all_keys: bool,
     strip_ansi: bool,
     output_format: Option<String>,
+    grep_output: Option<GrepOutput>,
+    print_jump: Option<String>,
+    #[cfg(feature = "script")]
+    accept_hook: Option<crate::script::Script>,
     delimiter: regex::Regex,
     replstr: String,
 }
@@ -140,15 +194,108 @@ impl BinOptions {
             print_query: opts.print_query,
             print_cmd: opts.print_cmd,
             print_score: opts.print_score,
+            print_positions: opts.print_positions,
             print_header: opts.print_header,
             print_current: opts.print_current,
+            print_all_keys: opts.print_all_keys,
             output_ending: String::from(if opts.print0 { "\0" } else { "\n" }),
             strip_ansi: opts.ansi && !opts.no_strip_ansi,
             output_format: opts.output_format.clone(),
+            grep_output: opts.grep_output,
+            print_jump: opts.print_jump.as_deref().map(jump_template),
+            #[cfg(feature = "script")]
+            accept_hook: opts.accept_hook.clone(),
             delimiter: opts.delimiter.clone(),
             replstr: opts.replstr.clone(),
         }
     }
+
+    /// Whether the items are all the output, so that they can be written as they are matched
+    ///
+    /// Nothing is printed before them, and they are written one by one, not through
+    /// `--output-format` nor `--accept-hook`.
+    #[must_use]
+    pub fn streams_items(&self) -> bool {
+        #[cfg(feature = "script")]
+        if self.accept_hook.is_some() {
+            return false;
+        }
+        !(self.print_query
+            || self.print_cmd
+            || self.print_header
+            || self.print_current
+            || self.print_all_keys
+            || self.output_format.is_some())
+    }
+
+    /// Serialize a selected `item` to `out`, `query` and `cmd` being those of the output
+    ///
+    /// # Errors
+    ///
+    /// Returns any [`io::Error`] produced while writing to `out`.
+    pub fn write_item<W: Write>(&self, out: &mut W, item: &MatchedItem, query: &str, cmd: &str) -> io::Result<()> {
+        let editor_args = self
+            .grep_output
+            .and_then(|editor| editor_args(editor, &crate::helper::item::strip_ansi(&item.output()).0));
+        if let Some(ref template) = self.print_jump {
+            let (jump, _) = crate::util::printf_for_shell(
+                template,
+                &self.delimiter,
+                &self.replstr,
+                &std::iter::once(item),
+                Some(item),
+                query,
+                cmd,
+                None,
+            );
+            write!(out, "{jump}{}", self.output_ending)?;
+        } else if let Some(args) = editor_args {
+            write!(out, "{args}{}", self.output_ending)?;
+        } else if self.strip_ansi {
+            write!(
+                out,
+                "{}{}",
+                crate::helper::item::strip_ansi(&item.output()).0,
+                self.output_ending
+            )?;
+        } else {
+            out.write_all(&item.output_bytes())?;
+            write!(out, "{}", self.output_ending)?;
+        }
+        if self.print_score {
+            write!(out, "{}{}", item.rank.score, self.output_ending)?;
+        }
+        if self.print_positions {
+            let offsets: Vec<String> = item.matched_byte_offsets().iter().map(ToString::to_string).collect();
+            write!(out, "{}{}", offsets.join(","), self.output_ending)?;
+        }
+        Ok(())
+    }
+}
+
+/// The `--print-jump` template with its named placeholders replaced by the fields they stand for
+fn jump_template(template: &str) -> String {
+    template
+        .replace("{file}", "{1}")
+        .replace("{line}", "{2}")
+        .replace("{col}", "{3}")
+}
+
+/// The arguments of `editor` opening the `file:line[:col]:text` record `output`, if it is one
+fn editor_args(editor: GrepOutput, output: &str) -> Option<String> {
+    let mut fields = output.splitn(4, ':');
+    let file = fields.next().filter(|file| !file.is_empty())?;
+    let line: usize = fields.next()?.parse().ok()?;
+    // a number is the text of a record without column, unless more text follows it
+    let column = fields
+        .next()
+        .and_then(|column| column.parse::<usize>().ok())
+        .filter(|_| fields.next().is_some());
+    Some(match (editor, column) {
+        (GrepOutput::Vim, _) => format!("+{line} {file}"),
+        (GrepOutput::Vscode, Some(column)) => format!("{file}:{line}:{column}"),
+        (GrepOutput::Vscode, None) => format!("{file}:{line}"),
+    })
 }
 
 #[cfg(test)]
@@ -181,6 +328,7 @@ mod tests {
             selected_items: items,
             current: None,
             header: "hdr".to_string(),
+            suggested: false,
         }
     }
 
@@ -251,6 +399,35 @@ mod tests {
         assert_eq!(render(&out, &opts()), "ctrl-x\na\n");
     }
 
+    #[test]
+    fn print_all_keys_writes_the_final_key_before_the_accept_key() {
+        let mut o = opts();
+        o.print_all_keys = true;
+        let mut out = output_with(
+            vec![matched("a", 0)],
+            Event::Action(Action::Accept(Some("ctrl-alt-f5".to_string()))),
+        );
+        out.final_key = KeyEvent::new(
+            KeyCode::F(5),
+            crossterm::event::KeyModifiers::CONTROL | crossterm::event::KeyModifiers::ALT,
+        );
+        assert_eq!(render(&out, &o), "ctrl-alt-f5\nctrl-alt-f5\na\n");
+        // skim was not ended by a key
+        out.final_key = KeyEvent::new(KeyCode::Null, crossterm::event::KeyModifiers::NONE);
+        out.final_event = Event::Action(Action::Accept(None));
+        assert_eq!(render(&out, &o), "\na\n");
+    }
+
+    #[test]
+    fn accept_payload_is_read_from_the_final_event() {
+        let out = output_with(vec![], Event::Action(Action::Accept(Some("edit".to_string()))));
+        assert_eq!(out.accept_payload(), Some("edit"));
+        let out = output_with(vec![], Event::Action(Action::Accept(None)));
+        assert_eq!(out.accept_payload(), None);
+        let out = output_with(vec![], Event::Action(Action::Abort));
+        assert_eq!(out.accept_payload(), None);
+    }
+
     #[test]
     fn print_score_follows_each_item() {
         let mut o = opts();
@@ -262,6 +439,17 @@ mod tests {
         assert_eq!(render(&out, &o), "a\n50\nb\n18\n");
     }
 
+    #[test]
+    fn print_positions_follow_the_score() {
+        let mut o = opts();
+        o.print_score = true;
+        o.print_positions = true;
+        let mut item = matched("abc", 7);
+        item.matched_range = Some(crate::MatchRange::Chars(vec![0, 2]));
+        let out = output_with(vec![item, matched("d", 1)], Event::Action(Action::Accept(None)));
+        assert_eq!(render(&out, &o), "abc\n7\n0,2\nd\n1\n\n");
+    }
+
     #[test]
     fn strip_ansi_removes_escape_sequences_from_items() {
         let mut o = opts();
@@ -294,6 +482,77 @@ mod tests {
         assert_eq!(render(&out, &o), "\x1b[31mred\x1b[0m\n");
     }
 
+    #[test]
+    fn items_decoded_from_invalid_utf8_are_written_as_read() {
+        let item =
+            crate::helper::item::DefaultSkimItem::new("caf\u{e9}", false, &[], &[], &regex::Regex::new(" ").unwrap())
+                .with_orig_bytes(b"caf\xe9");
+        let item = MatchedItem::new(Arc::new(item), Rank::default(), None, &RankBuilder::default());
+        let mut out = output_with(vec![item.clone()], Event::Action(Action::Accept(None)));
+        out.current = Some(item);
+        let mut o = opts();
+        o.print_current = true;
+        let mut buf = Vec::new();
+        out.write_output(&mut buf, &o).unwrap();
+        assert_eq!(buf, b"caf\xe9\ncaf\xe9\n");
+    }
+
+    #[test]
+    fn grep_output_prints_the_arguments_of_an_editor() {
+        assert_eq!(
+            editor_args(GrepOutput::Vim, "src/a.rs:12:5:fn main() {"),
+            Some("+12 src/a.rs".to_string())
+        );
+        let vscode = |output| editor_args(GrepOutput::Vscode, output);
+        assert_eq!(vscode("src/a.rs:12:5:let x = 1:2;"), Some("src/a.rs:12:5".to_string()));
+        // without a column, a number is the text
+        assert_eq!(vscode("src/a.rs:12:5"), Some("src/a.rs:12".to_string()));
+        assert_eq!(vscode("src/a.rs:12:x: 1"), Some("src/a.rs:12".to_string()));
+        assert_eq!(vscode("src/a.rs"), None);
+        assert_eq!(vscode("note: see a.rs"), None);
+
+        let mut o = opts();
+        o.grep_output = Some(GrepOutput::Vim);
+        o.strip_ansi = true;
+        let out = output_with(
+            vec![matched("\x1b[35ma.rs\x1b[0m:3:1:x", 0), matched("plain", 0)],
+            Event::Action(Action::Accept(None)),
+        );
+        assert_eq!(render(&out, &o), "+3 a.rs\nplain\n");
+    }
+
+    #[test]
+    fn print_jump_expands_the_fields_of_each_item() {
+        let mut o = opts();
+        o.delimiter = regex::Regex::new(":").unwrap();
+        o.print_jump = Some(jump_template("{file} +{line} {col}|{4..}"));
+        o.strip_ansi = true;
+        let out = output_with(
+            vec![matched("\x1b[35ma.rs\x1b[0m:3:1:x: y", 0), matched("plain", 0)],
+            Event::Action(Action::Accept(None)),
+        );
+        assert_eq!(render(&out, &o), "a.rs +3 1|x: y\nplain + |\n");
+    }
+
+    #[cfg(feature = "script")]
+    #[test]
+    fn accept_hook_prints_the_lines_of_the_script() {
+        let mut o = opts();
+        o.accept_hook = Some(crate::script::Script::new(r#"items.map(|item| query + "/" + item)"#).unwrap());
+        o.print_score = true;
+        let mut out = output_with(
+            vec![matched("a", 1), matched("b", 2)],
+            Event::Action(Action::Accept(None)),
+        );
+        assert_eq!(render(&out, &o), "qry/a\nqry/b\n");
+
+        // `()` prints the items as usual
+        o.accept_hook = Some(crate::script::Script::new(r#"if key == "" { } else { key }"#).unwrap());
+        assert_eq!(render(&out, &o), "a\n1\nb\n2\n");
+        out.final_key = KeyEvent::new(KeyCode::Enter, crossterm::event::KeyModifiers::NONE);
+        assert_eq!(render(&out, &o), "enter\n");
+    }
+
     #[test]
     fn output_format_overrides_default_serialization() {
         let mut o = opts();
@@ -0,0 +1,322 @@
+//! Placeholder expansion for command templates.
+//!
+//! This is the expansion skim itself applies to `--preview`, `execute(...)`,
+//! `reload(...)` and friends, exposed so that library users driving their own
+//! commands get the exact same behavior.
+//!
+//! # Placeholders
+//!
+//! | Placeholder   | Expands to                                                        |
+//! | ------------- | ----------------------------------------------------------------- |
+//! | `{}`          | the current item (see [`Context::replstr`] to change the token)   |
+//! | `{raw}`       | the current item, never quoted                                    |
+//! | `{q}`         | the query                                                         |
+//! | `{cq}`        | the command query (interactive mode)                              |
+//! | `{n}`         | the index of the current item                                     |
+//! | `{ln}`, `{off}` | the line number and byte offset of the current item in its file |
+//! | `{N}`, `{N..M}` | fields of the current item, split by [`Context::delimiter`]     |
+//! | `{+}`         | all selected items, or the current one if nothing is selected     |
+//! | `{+n}`        | indices of all selected items                                     |
+//! | `{+N..M}`     | fields of all selected items                                      |
+//! | `{+:SEP}`, `{+n:SEP}`, `{+N..M:SEP}` | the same, joined with `SEP` instead of a space |
+//!
+//! `{ln}` and `{off}` are empty for the items without a
+//! [`source_position`](crate::SkimItem::source_position), i.e. not read with
+//! `--input-file`.
+//!
+//! Field ranges use the `--nth` syntax (1-based, negative indices count from
+//! the end). Unknown placeholders are left untouched, so that braces meant for
+//! the shell or for tools like `awk` survive. [`try_expand`] reports the
+//! templates that are certainly broken: a `{` that is never closed, or a `{+…}`
+//! that is not a valid field range.
+//!
+//! # Escaping
+//!
+//! Items are always expanded with their ANSI escape sequences stripped, and
+//! NUL bytes are replaced by the two characters `\0`.
+//!
+//! When [`Context::quote`] is set, every expanded value is made safe to pass
+//! to `sh -c` by wrapping it in single quotes, with embedded single quotes
+//! written as `'\''`. Multi-item placeholders quote each item separately when
+//! joined with the default space, and quote the whole joined string when a
+//! custom separator is given. `{raw}` is the way to opt out for a single
+//! placeholder. The quoting follows the shell set with [`Context::shell`],
+//! `$SKIM_SHELL` by default: PowerShell doubles embedded single quotes instead, and `cmd.exe`, the
+//! default on Windows, gets double quotes with its metacharacters (`^&|<>()%!"`)
+//! escaped with `^`.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+use thiserror::Error;
+
+use crate::interpreter::Interpreter;
+use crate::item::MatchedItem;
+use crate::options::SkimOptions;
+
+static DEFAULT_DELIMITER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[\t\n ]+").unwrap());
+
+/// Everything a template can refer to.
+///
+/// Build it with [`Context::default`] and the chained setters, or start from
+/// [`Context::from_options`] to pick up the delimiter, replacement string
+/// and shell of a [`SkimOptions`].
+#[derive(Clone, Debug)]
+pub struct Context<'a> {
+    /// The item under the cursor
+    pub current: Option<&'a MatchedItem>,
+    /// The multi-selection, in selection order
+    pub selected: &'a [MatchedItem],
+    /// The query, for `{q}`
+    pub query: &'a str,
+    /// The command query, for `{cq}`
+    pub cmd_query: &'a str,
+    /// Field delimiter for `{N}`-style placeholders (defaults to whitespace)
+    pub delimiter: &'a Regex,
+    /// Token replaced by the current item (defaults to `{}`)
+    pub replstr: &'a str,
+    /// Whether to shell-quote expanded values (defaults to `true`)
+    pub quote: bool,
+    /// Shell the values are quoted for (defaults to `$SKIM_SHELL`)
+    pub(crate) interpreter: Interpreter,
+}
+
+impl Default for Context<'_> {
+    fn default() -> Self {
+        Self {
+            current: None,
+            selected: &[],
+            query: "",
+            cmd_query: "",
+            delimiter: &DEFAULT_DELIMITER,
+            replstr: "{}",
+            quote: true,
+            interpreter: Interpreter::from_env(),
+        }
+    }
+}
+
+impl<'a> Context<'a> {
+    /// Creates a context using the delimiter, replacement string and shell from `options`
+    #[must_use]
+    pub fn from_options(options: &'a SkimOptions) -> Self {
+        Self {
+            delimiter: &options.delimiter,
+            replstr: &options.replstr,
+            interpreter: Interpreter::from_options(options),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the item under the cursor
+    #[must_use]
+    pub fn current(mut self, current: Option<&'a MatchedItem>) -> Self {
+        self.current = current;
+        self
+    }
+
+    /// Sets the multi-selection
+    #[must_use]
+    pub fn selected(mut self, selected: &'a [MatchedItem]) -> Self {
+        self.selected = selected;
+        self
+    }
+
+    /// Sets the query
+    #[must_use]
+    pub fn query(mut self, query: &'a str) -> Self {
+        self.query = query;
+        self
+    }
+
+    /// Sets the command query
+    #[must_use]
+    pub fn cmd_query(mut self, cmd_query: &'a str) -> Self {
+        self.cmd_query = cmd_query;
+        self
+    }
+
+    /// Sets the field delimiter
+    #[must_use]
+    pub fn delimiter(mut self, delimiter: &'a Regex) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Sets the token replaced by the current item
+    #[must_use]
+    pub fn replstr(mut self, replstr: &'a str) -> Self {
+        self.replstr = replstr;
+        self
+    }
+
+    /// Sets whether expanded values are shell-quoted
+    #[must_use]
+    pub fn quote(mut self, quote: bool) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    /// Sets the shell expanded values are quoted for, as in `--with-shell` (e.g. `pwsh`)
+    #[must_use]
+    pub fn shell(mut self, shell: &str) -> Self {
+        self.interpreter = Interpreter::parse(shell);
+        self
+    }
+}
+
+/// Error returned by [`try_expand`] for a template that would produce a broken command
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum PlaceholderError {
+    /// A `{` is never closed; holds the text following it
+    #[error("unterminated placeholder `{{{0}`")]
+    Unterminated(String),
+    /// A `{+...}` placeholder does not hold a valid field range
+    #[error("invalid field range in placeholder `{{{0}}}`")]
+    InvalidFieldRange(String),
+}
+
+/// Expands the placeholders in `template` using `ctx`
+///
+/// See the [module documentation](self) for the supported placeholders and
+/// escaping rules. Placeholders that cannot be expanded are kept as they are,
+/// use [`try_expand`] to reject them.
+#[must_use]
+pub fn expand(template: &str, ctx: &Context) -> String {
+    expand_checked(template, ctx).0
+}
+
+/// Expands the placeholders in `template` using `ctx`, failing on broken placeholders
+///
+/// # Errors
+///
+/// Returns the first placeholder that cannot be expanded, see [`PlaceholderError`].
+pub fn try_expand(template: &str, ctx: &Context) -> Result<String, PlaceholderError> {
+    match expand_checked(template, ctx) {
+        (_, Some(error)) => Err(error),
+        (expanded, None) => Ok(expanded),
+    }
+}
+
+/// Expands `template`, alongside the first placeholder that could not be expanded
+fn expand_checked(template: &str, ctx: &Context) -> (String, Option<PlaceholderError>) {
+    crate::util::printf_for_shell(
+        template,
+        ctx.delimiter,
+        ctx.replstr,
+        &ctx.selected.iter(),
+        ctx.current,
+        ctx.query,
+        ctx.cmd_query,
+        ctx.quote.then_some(&ctx.interpreter),
+    )
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::item::RankBuilder;
+    use crate::options::SkimOptionsBuilder;
+    use crate::{Rank, SkimItem};
+
+    fn item(text: &str, index: i32) -> MatchedItem {
+        let item: Arc<dyn SkimItem> = Arc::new(text.to_string());
+        MatchedItem::new(
+            item,
+            Rank {
+                index,
+                ..Default::default()
+            },
+            None,
+            &RankBuilder::default(),
+        )
+    }
+
+    #[test]
+    fn expands_current_query_and_fields() {
+        let current = item("foo bar baz", 3);
+        let ctx = Context::default().current(Some(&current)).query("q").quote(false);
+        assert_eq!(expand("{} {q} {n} {2} {2..}", &ctx), "foo bar baz q 3 bar bar baz");
+    }
+
+    #[test]
+    fn quotes_values_for_the_shell() {
+        let current = item("it's", 0);
+        let ctx = Context::default().current(Some(&current)).shell("sh");
+        assert_eq!(expand("echo {}", &ctx), r"echo 'it'\''s'");
+        let ctx = ctx.shell("pwsh");
+        assert_eq!(expand("echo {}", &ctx), "echo 'it''s'");
+        assert_eq!(try_expand("echo {}", &ctx), Ok("echo 'it''s'".to_string()));
+        let ctx = ctx.shell("cmd");
+        assert_eq!(try_expand("echo {}", &ctx), Ok(r#"echo ^"it's^""#.to_string()));
+    }
+
+    #[test]
+    fn raw_is_never_quoted() {
+        let current = item("it's", 0);
+        let ctx = Context::default().current(Some(&current)).shell("sh");
+        assert_eq!(expand("{} {raw}", &ctx), r"'it'\''s' it's");
+    }
+
+    #[test]
+    fn try_expand_rejects_broken_placeholders() {
+        let current = item("a b", 0);
+        let ctx = Context::default().current(Some(&current)).quote(false);
+        assert_eq!(try_expand("echo {2} {raw}", &ctx), Ok("echo b a b".to_string()));
+        assert_eq!(
+            try_expand("awk '{print $1}' {2", &ctx),
+            Err(PlaceholderError::Unterminated("2".to_string()))
+        );
+        assert_eq!(
+            try_expand("echo {+x}", &ctx),
+            Err(PlaceholderError::InvalidFieldRange("+x".to_string()))
+        );
+        // `expand` keeps what it cannot expand
+        assert_eq!(expand("awk '{print $1}' {2", &ctx), "awk '{print $1}' {2");
+    }
+
+    #[test]
+    fn multi_selection_falls_back_to_current() {
+        let current = item("c", 2);
+        let selected = [item("a", 0), item("b", 1)];
+        let ctx = Context::default().current(Some(&current)).quote(false);
+        assert_eq!(expand("{+}", &ctx), "c");
+        let ctx = ctx.selected(&selected);
+        assert_eq!(expand("{+} {+n:,}", &ctx), "a b 0,1");
+    }
+
+    #[test]
+    fn from_options_uses_delimiter_and_replstr() {
+        let options = SkimOptionsBuilder::default()
+            .delimiter(Regex::new(":").unwrap())
+            .replstr("XX")
+            .build()
+            .unwrap();
+        let current = item("a:b", 0);
+        let ctx = Context::from_options(&options).current(Some(&current)).quote(false);
+        assert_eq!(expand("XX {2} {}", &ctx), "a:b b {}");
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn from_options_quotes_for_with_shell_over_skim_shell() {
+        let saved = std::env::var_os("SKIM_SHELL");
+        // The platform default, so that the tests reading it concurrently are not affected
+        let default_shell = if cfg!(windows) { "cmd" } else { "sh" };
+        // SAFETY: serialised by #[serial]; the value read by the other tests does not change.
+        unsafe { std::env::set_var("SKIM_SHELL", default_shell) };
+        let options = SkimOptionsBuilder::default().with_shell("pwsh").build().unwrap();
+        let current = item("it's", 0);
+        let ctx = Context::from_options(&options).current(Some(&current));
+        assert_eq!(expand("echo {}", &ctx), "echo 'it''s'");
+        let ctx = Context::default().current(Some(&current));
+        assert_ne!(expand("echo {}", &ctx), "echo 'it''s'");
+        match saved {
+            Some(value) => unsafe { std::env::set_var("SKIM_SHELL", value) },
+            None => unsafe { std::env::remove_var("SKIM_SHELL") },
+        }
+    }
+}
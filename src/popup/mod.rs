@@ -3,7 +3,7 @@
 //! This module provides functionality for running skim within tmux/zellij panes,
 //! allowing skim to be used as a tmux popup or split pane.
 
-mod tmux;
+pub(crate) mod tmux;
 mod zellij;
 
 use std::borrow::Cow;
@@ -23,7 +23,8 @@ use crate::tui::Event;
 use crate::tui::event::Action;
 use crate::{Rank, SkimItem, SkimOptions, SkimOutput};
 
-use tmux::TmuxPopup;
+pub(crate) use tmux::TmuxPreviewPane;
+use tmux::{TmuxPane, TmuxPopup};
 use zellij::ZellijPopup;
 
 #[derive(Debug, PartialEq, Eq)]
@@ -68,6 +69,8 @@ impl SkimItem for SkimPopupOutput {
 
 /// Returns true if a compatible multiplexer is running and we are not already in a popup
 /// (`$_SKIM_POPUP`)
+///
+/// Tmux versions without popups run skim in a split pane instead.
 #[must_use]
 pub fn check_env() -> bool {
     std::env::var("_SKIM_POPUP").is_err() && (tmux::is_available() || zellij::is_available())
@@ -76,7 +79,7 @@ pub fn check_env() -> bool {
 /// Run skim in a tmux popup
 ///
 /// This will extract the tmux options, then build a new sk command
-/// without them and send it to tmux in a popup.
+/// without them and send it to tmux in a popup, or a split pane with `--tmux-pane`.
 ///
 /// # Panics
 ///
@@ -161,15 +164,16 @@ pub fn run_with(opts: &SkimOptions) -> Option<SkimOutput> {
             prev_is_output_format_flag = false;
             continue;
         }
-        if arg == "--tmux" || arg == "--popup" {
+        if arg == "--tmux" || arg == "--popup" || arg == "--tmux-pane" {
             debug!("Found popup arg, skipping this and the next");
             prev_is_popup = true;
             continue;
         } else if arg.starts_with("--tmux") || arg.starts_with("--popup") {
             debug!("Found equal popup arg, skipping");
             continue;
-        } else if arg == "--print-cmd" {
-            debug!("Found print cmd arg, skipping");
+        } else if arg == "--print-cmd" || arg == "--print-positions" {
+            // The child's output is parsed below, positions would not be
+            debug!("Found {arg} arg, skipping");
             continue;
         } else if arg == "--output-format" {
             debug!("Found output format arg, skipping this and the next");
@@ -196,10 +200,14 @@ pub fn run_with(opts: &SkimOptions) -> Option<SkimOutput> {
     debug!("build cmd {stripped_shell_cmd}");
 
     // Run downstream sk in tmux
-    let mut popup: Box<dyn SkimPopup> = if zellij::is_available() {
+    let mut popup: Box<dyn SkimPopup> = if opts.tmux_pane.is_some() && tmux::is_available() {
+        TmuxPane::from_options(opts)
+    } else if zellij::is_available() {
         ZellijPopup::from_options(opts)
-    } else if tmux::is_available() {
+    } else if tmux::supports_popup() {
         TmuxPopup::from_options(opts)
+    } else if tmux::is_available() {
+        TmuxPane::from_options(opts)
     } else {
         panic!("You shouldn't have been able to get here");
     };
@@ -277,7 +285,9 @@ pub fn run_with(opts: &SkimOptions) -> Option<SkimOutput> {
         output_lines.push(item);
     }
 
-    let is_abort = !status.success();
+    // the child exits with 3 after accepting a `--suggest-on-empty` near miss
+    let suggested = status.code() == Some(3);
+    let is_abort = !status.success() && !suggested;
     let final_event = if is_abort {
         Event::Action(Action::Abort)
     } else {
@@ -296,6 +306,7 @@ pub fn run_with(opts: &SkimOptions) -> Option<SkimOutput> {
         selected_items: output_lines,
         current,
         header,
+        suggested,
     };
     Some(skim_output)
 }
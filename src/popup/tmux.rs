@@ -1,12 +1,80 @@
 use crate::SkimOptions;
 
 use super::{PopupWindowDir, SkimPopup};
+use std::io;
+use std::path::PathBuf;
 use std::process::{Command, ExitStatus, Stdio};
 
+/// Keeps the preview pane open once the preview command is done
+const IDLE: &str = "while :; do sleep 3600; done";
+
+/// First tmux version with `display-popup`
+const POPUP_VERSION: (u32, u32) = (3, 2);
+
 pub fn is_available() -> bool {
     cfg!(unix) && std::env::var("TMUX").is_ok() && which::which("tmux").is_ok()
 }
 
+/// Whether we run inside a tmux able to show popups, older versions get a split pane instead
+pub fn supports_popup() -> bool {
+    is_available() && version().is_none_or(|v| v >= POPUP_VERSION)
+}
+
+/// Version of the tmux on `PATH`, `None` when it cannot be told (e.g. `tmux master`)
+fn version() -> Option<(u32, u32)> {
+    let output = Command::new(tmux_bin()).arg("-V").stdin(Stdio::null()).output().ok()?;
+    parse_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parses the output of `tmux -V`, like `tmux 3.3a` or `tmux next-3.4`
+fn parse_version(output: &str) -> Option<(u32, u32)> {
+    let version = output.trim().strip_prefix("tmux ")?;
+    let version = version.strip_prefix("next-").unwrap_or(version);
+    let (major, minor) = version.split_once('.')?;
+    let minor: String = minor.chars().take_while(char::is_ascii_digit).collect();
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
+fn tmux_bin() -> PathBuf {
+    which::which("tmux").unwrap_or_else(|_| "tmux".into())
+}
+
+/// Arguments of the `send-keys` command typing `keys` in the pane (or window) `target`
+///
+/// The keys are sent literally, without a final Enter.
+pub(crate) fn send_keys_args(target: &str, keys: &str) -> [String; 6] {
+    [
+        "send-keys".to_string(),
+        "-t".to_string(),
+        target.to_string(),
+        "-l".to_string(),
+        "--".to_string(),
+        keys.to_string(),
+    ]
+}
+
+/// Run `tmux` with the given arguments, for the `send-to-tmux` action
+pub(crate) fn run<I, S>(args: I) -> io::Result<()>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<std::ffi::OsStr>,
+{
+    if !is_available() {
+        return Err(io::Error::other("not running inside tmux"));
+    }
+    let status = Command::new(tmux_bin())
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("tmux failed with {status}")))
+    }
+}
+
 pub(super) struct TmuxPopup {
     cmd: Command,
 }
@@ -17,8 +85,13 @@ impl TmuxPopup {
         // `is_available` already guarantees tmux is on PATH before we reach here
         // in production; fall back to the bare name so arg-building (and tests)
         // work even when the binary cannot be resolved.
-        let mut cmd = Command::new(which::which("tmux").unwrap_or_else(|_| "tmux".into()));
-        cmd.arg("display-popup").arg("-E").args([
+        let mut cmd = Command::new(tmux_bin());
+        cmd.arg("display-popup").arg("-E");
+        // over the pane skim was started from, even if another one has the focus by now
+        if let Ok(target) = std::env::var("TMUX_PANE") {
+            cmd.args(["-t", &target]);
+        }
+        cmd.args([
             "-d",
             &std::env::current_dir()
                 .ok()
@@ -91,6 +164,221 @@ impl SkimPopup for TmuxPopup {
     }
 }
 
+/// Runs skim in a pane split off the current one, for `--tmux-pane` and tmux without popups
+///
+/// `split-window` returns as soon as the pane is created, so the command signals a `wait-for`
+/// channel once done, after writing its exit code to a file.
+pub(super) struct TmuxPane {
+    cmd: Command,
+    channel: String,
+    status_dir: tempfile::TempDir,
+}
+
+impl TmuxPane {
+    fn build(options: &SkimOptions) -> io::Result<Self> {
+        let spec = options
+            .tmux_pane
+            .as_deref()
+            .or(options.popup.as_deref())
+            .expect("this arg should be present to get here");
+        let mut cmd = Command::new(tmux_bin());
+        cmd.args(Self::split_args(spec));
+        Ok(Self {
+            cmd,
+            channel: format!("sk-pane-{}", std::process::id()),
+            status_dir: tempfile::tempdir()?,
+        })
+    }
+
+    /// Arguments of the `split-window` command, before the environment and the command
+    fn split_args(spec: &str) -> Vec<String> {
+        let (raw_dir, size) = spec.split_once(',').unwrap_or((spec, "50%"));
+        // the size of the other side, given for popups, does not apply
+        let size = size.split_once(',').map_or(size, |(size, _)| size);
+        let (split, before) = match PopupWindowDir::from(raw_dir) {
+            PopupWindowDir::Left => ("-h", true),
+            PopupWindowDir::Right => ("-h", false),
+            PopupWindowDir::Top => ("-v", true),
+            PopupWindowDir::Bottom | PopupWindowDir::Center => ("-v", false),
+        };
+        let mut args = vec!["split-window".to_string(), split.to_string()];
+        if before {
+            args.push("-b".to_string());
+        }
+        if let Ok(target) = std::env::var("TMUX_PANE") {
+            args.extend(["-t".to_string(), target]);
+        }
+        args.extend([
+            "-l".to_string(),
+            size.to_string(),
+            "-c".to_string(),
+            std::env::current_dir()
+                .ok()
+                .map_or(".".to_string(), |d| d.to_string_lossy().to_string()),
+        ]);
+        args
+    }
+
+    fn status_file(&self) -> PathBuf {
+        self.status_dir.path().join("status")
+    }
+
+    /// The command run in the pane: `command`, then the signal of its exit code
+    fn pane_command(&self, command: &str) -> String {
+        format!(
+            "{command}\necho $? > '{}'\ntmux wait-for -S {}",
+            self.status_file().display(),
+            self.channel
+        )
+    }
+}
+
+impl SkimPopup for TmuxPane {
+    fn from_options(options: &SkimOptions) -> Box<dyn SkimPopup> {
+        let pane = Self::build(options).unwrap_or_else(|e| panic!("Failed to create the pane's temp dir: {e}"));
+        Box::new(pane) as Box<dyn SkimPopup>
+    }
+
+    fn add_env(&mut self, key: &str, value: &str) {
+        self.cmd.args(["-e", &format!("{key}={value}")]);
+    }
+
+    fn run_and_wait(&mut self, command: &str) -> std::io::Result<ExitStatus> {
+        use std::os::unix::process::ExitStatusExt as _;
+        debug!("tmux pane command: {command:?}");
+        self.cmd.args(["sh", "-c", &self.pane_command(command)]);
+
+        debug!("tmux full command: {:?}", self.cmd);
+        let status = self
+            .cmd
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .stdin(Stdio::null())
+            .status()?;
+        if !status.success() {
+            return Ok(status);
+        }
+        Command::new(tmux_bin())
+            .args(["wait-for", &self.channel])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+        let code: i32 = std::fs::read_to_string(self.status_file())?.trim().parse().unwrap_or(1);
+        Ok(ExitStatus::from_raw(code << 8))
+    }
+}
+
+/// The tmux pane showing the previews with `--preview-pane`
+///
+/// The pane is split off skim's own pane when created, every preview replaces the process running in
+/// it and it is killed on drop.
+pub(crate) struct TmuxPreviewPane {
+    tmux: PathBuf,
+    pane_id: String,
+}
+
+impl TmuxPreviewPane {
+    /// Split the pane described by a `tmux:DIR[,SIZE]` spec off `$TMUX_PANE`
+    pub(crate) fn open(spec: &str) -> io::Result<Self> {
+        if !is_available() {
+            return Err(io::Error::other("--preview-pane needs to run inside tmux"));
+        }
+        let tmux = tmux_bin();
+        let output = Command::new(&tmux)
+            .args(Self::split_args(spec)?)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "tmux split-window failed with {}",
+                output.status
+            )));
+        }
+        let pane_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        debug!("opened preview pane {pane_id}");
+        Ok(Self { tmux, pane_id })
+    }
+
+    /// Arguments of the `split-window` command creating the pane, which prints its id
+    fn split_args(spec: &str) -> io::Result<Vec<String>> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidInput, format!("invalid preview pane '{spec}'"));
+        let spec = spec.strip_prefix("tmux:").ok_or_else(invalid)?;
+        let (raw_dir, size) = spec.split_once(',').unwrap_or((spec, "50%"));
+        let (split, before) = match PopupWindowDir::from(raw_dir) {
+            PopupWindowDir::Left => ("-h", true),
+            PopupWindowDir::Right => ("-h", false),
+            PopupWindowDir::Top => ("-v", true),
+            PopupWindowDir::Bottom => ("-v", false),
+            PopupWindowDir::Center => return Err(invalid()),
+        };
+
+        let mut args = vec!["split-window", "-d", "-P", "-F", "#{pane_id}", split];
+        if before {
+            args.push("-b");
+        }
+        let mut args: Vec<String> = args.into_iter().map(String::from).collect();
+        if let Ok(target) = std::env::var("TMUX_PANE") {
+            args.extend(["-t".to_string(), target]);
+        }
+        args.extend([
+            "-l".to_string(),
+            size.to_string(),
+            "-c".to_string(),
+            std::env::current_dir()
+                .ok()
+                .map_or(".".to_string(), |d| d.to_string_lossy().to_string()),
+            "sh".to_string(),
+            "-c".to_string(),
+            IDLE.to_string(),
+        ]);
+        Ok(args)
+    }
+
+    /// Arguments of the `respawn-pane` command running `command` in the pane
+    fn respawn_args(&self, command: &str) -> [String; 7] {
+        [
+            "respawn-pane".to_string(),
+            "-k".to_string(),
+            "-t".to_string(),
+            self.pane_id.clone(),
+            "sh".to_string(),
+            "-c".to_string(),
+            // clear what the previous command printed, and keep the pane once this one is done
+            format!("printf '\\033[H\\033[2J'\n{command}\n{IDLE}"),
+        ]
+    }
+
+    /// Run the expanded preview `command` in the pane, killing the previous one
+    pub(crate) fn show(&self, command: &str) -> io::Result<()> {
+        debug!("preview pane {}: {command:?}", self.pane_id);
+        let status = Command::new(&self.tmux)
+            .args(self.respawn_args(command))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!("tmux respawn-pane failed with {status}")))
+        }
+    }
+}
+
+impl Drop for TmuxPreviewPane {
+    fn drop(&mut self) {
+        debug!("killing preview pane {}", self.pane_id);
+        let _ = Command::new(&self.tmux)
+            .args(["kill-pane", "-t", &self.pane_id])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+}
+
 #[cfg(test)]
 #[path = "tmux_tests.rs"]
 mod tests;
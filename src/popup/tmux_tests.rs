@@ -123,3 +123,89 @@ fn add_env_appends_e_flag() {
     let a = args(&popup);
     assert!(a.windows(2).any(|w| w[0] == "-e" && w[1] == "FOO=bar"));
 }
+
+#[test]
+fn preview_pane_split_direction_and_size() {
+    let a = TmuxPreviewPane::split_args("tmux:right,40%").unwrap();
+    assert!(a.contains(&"-h".to_string()));
+    assert!(!a.contains(&"-b".to_string()));
+    assert_eq!(get_flag(&a, "-l"), Some("40%"));
+    assert_eq!(get_flag(&a, "-F"), Some("#{pane_id}"));
+
+    let a = TmuxPreviewPane::split_args("tmux:top").unwrap();
+    assert!(a.contains(&"-v".to_string()));
+    assert!(a.contains(&"-b".to_string()));
+    assert_eq!(get_flag(&a, "-l"), Some("50%"));
+}
+
+#[test]
+fn preview_pane_rejects_invalid_specs() {
+    assert!(TmuxPreviewPane::split_args("right,40%").is_err());
+    assert!(TmuxPreviewPane::split_args("tmux:center").is_err());
+    assert!(TmuxPreviewPane::split_args("zellij:right").is_err());
+}
+
+#[test]
+fn preview_pane_respawns_the_command() {
+    // not dropped, which would run `tmux kill-pane`
+    let pane = std::mem::ManuallyDrop::new(TmuxPreviewPane {
+        tmux: "tmux".into(),
+        pane_id: "%42".to_string(),
+    });
+    let a = pane.respawn_args("less 'foo bar'");
+    assert_eq!(&a[..6], ["respawn-pane", "-k", "-t", "%42", "sh", "-c"]);
+    assert!(a[6].contains("\nless 'foo bar'\n"));
+    assert!(a[6].ends_with(IDLE));
+}
+
+#[test]
+fn parse_tmux_versions() {
+    assert_eq!(parse_version("tmux 3.3a\n"), Some((3, 3)));
+    assert_eq!(parse_version("tmux 3.2"), Some((3, 2)));
+    assert_eq!(parse_version("tmux next-3.5"), Some((3, 5)));
+    assert_eq!(parse_version("tmux 2.9a"), Some((2, 9)));
+    assert_eq!(parse_version("tmux master"), None);
+    assert!(parse_version("tmux 3.1c").unwrap() < POPUP_VERSION);
+}
+
+#[test]
+fn send_keys_targets_the_pane_literally() {
+    let a = send_keys_args("%3", "'foo bar' baz");
+    assert_eq!(get_flag(&a, "-t"), Some("%3"));
+    assert!(a.contains(&"-l".to_string()));
+    assert_eq!(a.last().map(String::as_str), Some("'foo bar' baz"));
+}
+
+#[test]
+fn pane_split_direction_and_size() {
+    let a = TmuxPane::split_args("left,30%");
+    assert!(a.contains(&"-h".to_string()));
+    assert!(a.contains(&"-b".to_string()));
+    assert_eq!(get_flag(&a, "-l"), Some("30%"));
+
+    // popup specs are accepted: center splits below, the second size is ignored
+    let a = TmuxPane::split_args("center,40%,60%");
+    assert!(a.contains(&"-v".to_string()));
+    assert!(!a.contains(&"-b".to_string()));
+    assert_eq!(get_flag(&a, "-l"), Some("40%"));
+}
+
+#[test]
+fn pane_signals_its_exit_code() {
+    let options = SkimOptionsBuilder::default()
+        .tmux_pane("top,20%")
+        .build()
+        .expect("valid options");
+    let pane = TmuxPane::build(&options).unwrap();
+    let cmd = pane.pane_command("sk > out");
+    let mut lines = cmd.lines();
+    assert_eq!(lines.next(), Some("sk > out"));
+    assert_eq!(
+        lines.next(),
+        Some(format!("echo $? > '{}'", pane.status_file().display()).as_str())
+    );
+    assert_eq!(
+        lines.next(),
+        Some(format!("tmux wait-for -S {}", pane.channel).as_str())
+    );
+}
@@ -11,8 +11,8 @@ pub use crate::helper::selector::DefaultSkimSelector;
 pub use crate::options::{SkimOptions, SkimOptionsBuilder};
 pub use crate::output::SkimOutput;
 pub use crate::reader::CommandCollector;
-pub use crate::tui::event::Action;
-pub use crate::tui::{Event, PreviewCallback};
+pub use crate::tui::event::{Action, ActionCallback, ActionRegistry, AppHandle};
+pub use crate::tui::{Event, PreviewCallback, PreviewOutput, PreviewWidget};
 pub use crate::*;
 pub use kanal::{Receiver, Sender, bounded, unbounded};
 pub use std::borrow::Cow;
@@ -0,0 +1,156 @@
+//! Process groups for the commands skim spawns
+//!
+//! Source and preview commands run through the shell, which may start
+//! grandchildren of its own (e.g. `sh -c "find | xargs grep foo"`). Killing the
+//! shell alone leaves those running after a reload or after skim exits, so the
+//! commands are spawned in their own process group on Unix, or assigned to a job
+//! object on Windows, and the whole group is killed at once.
+
+use std::io;
+use std::process::{Child, Command};
+
+/// Handle to the process group of a spawned command
+#[derive(Clone)]
+pub(crate) struct ProcessGroup {
+    #[cfg(unix)]
+    pgid: i32,
+    #[cfg(windows)]
+    job: Option<std::sync::Arc<windows::Job>>,
+}
+
+/// Spawn `command` as the leader of a new process group
+pub(crate) fn spawn(command: &mut Command) -> io::Result<(Child, ProcessGroup)> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt as _;
+        let child = command.process_group(0).spawn()?;
+        let pgid = i32::try_from(child.id()).unwrap_or(i32::MAX);
+        Ok((child, ProcessGroup { pgid }))
+    }
+    #[cfg(windows)]
+    {
+        let child = command.spawn()?;
+        let job = windows::Job::new()
+            .and_then(|job| job.assign(&child).map(|()| job))
+            .inspect_err(|e| debug!("failed to assign child to a job object: {e}"))
+            .ok()
+            .map(std::sync::Arc::new);
+        Ok((child, ProcessGroup { job }))
+    }
+}
+
+impl ProcessGroup {
+    /// Kill every process in the group
+    ///
+    /// Errors are ignored: the group may well be gone already.
+    pub(crate) fn kill(&self) {
+        #[cfg(unix)]
+        {
+            use nix::sys::signal::{Signal, killpg};
+            use nix::unistd::Pid;
+            trace!("killing process group {}", self.pgid);
+            let _ = killpg(Pid::from_raw(self.pgid), Signal::SIGKILL);
+        }
+        #[cfg(windows)]
+        if let Some(job) = &self.job {
+            job.terminate();
+        }
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use std::io;
+    use std::os::windows::io::AsRawHandle as _;
+    use std::process::Child;
+
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+        JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JobObjectExtendedLimitInformation, SetInformationJobObject,
+        TerminateJobObject,
+    };
+
+    /// Job object killing its processes when terminated or closed
+    pub(super) struct Job(HANDLE);
+
+    // SAFETY: job object handles can be used from any thread.
+    unsafe impl Send for Job {}
+    unsafe impl Sync for Job {}
+
+    impl Job {
+        pub(super) fn new() -> io::Result<Self> {
+            // SAFETY: null attributes and name create an anonymous job object.
+            let handle = unsafe { CreateJobObjectW(std::ptr::null(), std::ptr::null()) };
+            if handle.is_null() {
+                return Err(io::Error::last_os_error());
+            }
+            let job = Self(handle);
+            // SAFETY: the structure is plain data, all-zero is a valid value.
+            let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = unsafe { std::mem::zeroed() };
+            info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            // SAFETY: `info` outlives the call and its size is passed along.
+            let ok = unsafe {
+                SetInformationJobObject(
+                    job.0,
+                    JobObjectExtendedLimitInformation,
+                    (&raw const info).cast(),
+                    u32::try_from(size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>()).unwrap_or(u32::MAX),
+                )
+            };
+            if ok == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(job)
+        }
+
+        pub(super) fn assign(&self, child: &Child) -> io::Result<()> {
+            // SAFETY: both handles are valid for the duration of the call.
+            if unsafe { AssignProcessToJobObject(self.0, child.as_raw_handle()) } == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        }
+
+        pub(super) fn terminate(&self) {
+            // SAFETY: the handle stays valid until drop.
+            unsafe { TerminateJobObject(self.0, 1) };
+        }
+    }
+
+    impl Drop for Job {
+        fn drop(&mut self) {
+            // SAFETY: the handle was created by `CreateJobObjectW` and is closed once.
+            unsafe { CloseHandle(self.0) };
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn kill_reaches_grandchildren() {
+        use std::io::Read as _;
+        use std::process::Stdio;
+        use std::time::{Duration, Instant};
+
+        // The backgrounded `sleep` holds the stdout pipe open: EOF only comes once
+        // it is dead too.
+        let mut command = crate::interpreter::Interpreter::default().command("sleep 30 & echo started; wait");
+        command.stdout(Stdio::piped());
+        let (mut child, group) = spawn(&mut command).unwrap();
+        let mut stdout = child.stdout.take().unwrap();
+        let mut started = [0; 8];
+        stdout.read_exact(&mut started).unwrap();
+
+        let start = Instant::now();
+        group.kill();
+        child.wait().unwrap();
+        stdout.read_to_end(&mut Vec::new()).unwrap();
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+}
@@ -1,16 +1,23 @@
 //! Reader is used for reading items from datasource (e.g. stdin or command output)
 //!
 //! After reading in a line, reader will save an item into the pool(items)
-use crate::item::ItemPool;
+use crate::item::{ItemPool, RemovedItem};
 use crate::options::SkimOptions;
 use crate::prelude::{Sender, SkimItemReader};
 use crate::spinlock::SpinLock;
 use crate::thread_pool::ThreadPool;
-use crate::{SkimItem, SkimItemReceiver};
+use crate::{DisplayContext, ItemPreview, PreviewContext, SkimItem, SkimItemReceiver};
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use std::borrow::Cow;
 use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::process::{Command, Stdio};
 use std::rc::Rc;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
 /// Trait for collecting items from command output
 pub trait CommandCollector {
@@ -32,11 +39,144 @@ pub trait CommandCollector {
     /// than spawning additional OS threads.  The default implementation is a
     /// no-op; collectors that support pool-based I/O should override it.
     fn set_thread_pool(&mut self, _pool: Arc<ThreadPool>) {}
+
+    /// Provides the log the stderr of the commands is written to, shown by the
+    /// `toggle-stderr` pane.  The default implementation is a no-op.
+    fn set_stderr_log(&mut self, _log: StderrLog) {}
+
+    /// Provides the variables describing the state of skim, exported to the
+    /// next commands (`SKIM_QUERY`...).  The default implementation is a no-op.
+    fn set_env(&mut self, _env: &[(&str, String)]) {}
+
+    /// Provides the signal telling when the query is being typed, during which
+    /// the input read should not be parsed (`--defer-ingest`).  The default
+    /// implementation is a no-op.
+    fn set_typing_signal(&mut self, _typing: TypingSignal) {}
+
+    /// Number of lines truncated or skipped so far because of `--max-item-length`, shown in
+    /// the status line.  The default implementation counts none.
+    fn num_long_items(&self) -> usize {
+        0
+    }
+
+    /// Turns the lines of the file at `path` into items (`--input-file`)
+    ///
+    /// The default implementation reads the file through [`of_reader`](Self::of_reader).
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be opened.
+    fn of_file(&self, path: &std::path::Path) -> std::io::Result<SkimItemReceiver> {
+        Ok(self.of_reader(Box::new(std::fs::File::open(path)?)))
+    }
+
+    /// Turns the lines of `source`, e.g. a [`BuiltinSource`], into items
+    ///
+    /// The default implementation keeps the lines as they are, without the field
+    /// transforms of the items read from commands.
+    fn of_reader(&self, source: Box<dyn Read + Send>) -> SkimItemReceiver {
+        let (tx_item, rx_item) = kanal::unbounded();
+        std::thread::spawn(move || {
+            for line in BufReader::new(source).lines().map_while(Result::ok) {
+                if tx_item.send(vec![Arc::new(line) as Arc<dyn SkimItem>]).is_err() {
+                    break;
+                }
+            }
+        });
+        rx_item
+    }
+}
+
+/// Most lines kept by a [`StderrLog`]
+const STDERR_LOG_LINES: usize = 1000;
+
+/// Lines written to stderr by the source and preview commands, shown by `toggle-stderr`
+///
+/// Each line is prefixed with the kind of command that wrote it. Only the last
+/// thousand lines are kept.
+#[derive(Clone, Default)]
+pub struct StderrLog {
+    lines: Arc<SpinLock<VecDeque<String>>>,
+}
+
+impl StderrLog {
+    /// Adds the lines of `output`, written by a `kind` command
+    pub fn push(&self, kind: &str, output: &str) {
+        let mut lines = self.lines.lock();
+        for line in output.lines() {
+            if lines.len() == STDERR_LOG_LINES {
+                lines.pop_front();
+            }
+            lines.push_back(format!("[{kind}] {line}"));
+        }
+    }
+
+    /// The last `count` lines, oldest first
+    #[must_use]
+    pub fn last(&self, count: usize) -> Vec<String> {
+        let lines = self.lines.lock();
+        lines.iter().skip(lines.len().saturating_sub(count)).cloned().collect()
+    }
+
+    /// Number of lines kept
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lines.lock().len()
+    }
+
+    /// Whether nothing was written to stderr
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Tells the reader when the query was last edited, see `--defer-ingest`
+///
+/// While the query changed less than `delay` ago, the reader keeps the input it reads
+/// without parsing it, leaving the CPU to the matcher so that the keystrokes stay fast.
+#[derive(Clone)]
+pub struct TypingSignal {
+    delay: Duration,
+    epoch: Instant,
+    /// Nanoseconds from `epoch` to the last edit, 0 before the first one
+    last_edit: Arc<AtomicU64>,
+}
+
+impl Default for TypingSignal {
+    fn default() -> Self {
+        Self::new(Duration::ZERO)
+    }
+}
+
+impl TypingSignal {
+    /// Creates a signal deferring the input for `delay` after each edit, never if zero
+    #[must_use]
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            epoch: Instant::now(),
+            last_edit: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Records an edit of the query
+    pub fn touch(&self) {
+        let nanos = u64::try_from(self.epoch.elapsed().as_nanos()).unwrap_or(u64::MAX);
+        self.last_edit.store(nanos.max(1), Ordering::Relaxed);
+    }
+
+    /// Whether the query changed less than the delay ago
+    #[must_use]
+    pub fn is_typing(&self) -> bool {
+        let last_edit = self.last_edit.load(Ordering::Relaxed);
+        last_edit != 0 && !self.delay.is_zero() && self.epoch.elapsed() < Duration::from_nanos(last_edit) + self.delay
+    }
 }
 
 /// Handle for controlling a running reader
 pub struct ReaderControl {
-    tx_interrupt: Sender<i32>,
+    tx_interrupt: Vec<Sender<i32>>,
     tx_interrupt_cmd: Option<Sender<i32>>,
     components_to_stop: Arc<AtomicUsize>,
     items: Arc<SpinLock<Vec<Arc<dyn SkimItem>>>>,
@@ -51,7 +191,9 @@ impl ReaderControl {
         );
 
         let _ = self.tx_interrupt_cmd.clone().map(|tx| tx.send(1));
-        let _ = self.tx_interrupt.send(1);
+        for tx in &self.tx_interrupt {
+            let _ = tx.send(1);
+        }
         while self.components_to_stop.load(Ordering::SeqCst) != 0 {}
     }
 
@@ -78,10 +220,19 @@ impl Drop for ReaderControl {
     }
 }
 
+/// Item counts of the labeled sources, in the order they were added
+pub type SourceCounts = Arc<SpinLock<Vec<(Arc<str>, usize)>>>;
+
 /// Reader for streaming items from commands or other sources
 pub struct Reader {
     cmd_collector: Rc<RefCell<dyn CommandCollector>>,
     rx_item: Option<SkimItemReceiver>,
+    sources: Vec<(Arc<str>, SkimItemReceiver)>,
+    source_counts: SourceCounts,
+    builtin: Option<BuiltinSource>,
+    line_ending: u8,
+    input_file: Option<String>,
+    stderr_log: Option<StderrLog>,
 }
 
 impl Reader {
@@ -91,16 +242,42 @@ impl Reader {
         Self {
             cmd_collector: options.cmd_collector.clone(),
             rx_item: None,
+            sources: Vec::new(),
+            source_counts: SourceCounts::default(),
+            builtin: options.source,
+            line_ending: if options.read0 { b'\0' } else { b'\n' },
+            input_file: options.input_file.clone(),
+            stderr_log: None,
         }
     }
 
-    /// Sets the item source (if None, will use command collector)
+    /// Sets the item source (if None, will use the built-in source or the command collector)
     #[must_use]
     pub fn source(mut self, rx_item: Option<SkimItemReceiver>) -> Self {
         self.rx_item = rx_item;
         self
     }
 
+    /// Adds a source read alongside the others, its items tagged with `label`
+    ///
+    /// The items are wrapped in a [`SourcedItem`], which shows the label before them and
+    /// returns it from [`SkimItem::source`], so that `source:label` terms of the query match
+    /// them. The number of items read from each source is shown in the status line. Without a
+    /// [`source`](Self::source), no command is run when there are labeled sources.
+    #[must_use]
+    pub fn add_source(mut self, label: impl Into<String>, rx_item: SkimItemReceiver) -> Self {
+        let label: Arc<str> = Arc::from(label.into());
+        self.source_counts.lock().push((label.clone(), 0));
+        self.sources.push((label, rx_item));
+        self
+    }
+
+    /// Item counts of the labeled sources, updated as they are read
+    #[must_use]
+    pub fn source_counts(&self) -> SourceCounts {
+        self.source_counts.clone()
+    }
+
     /// Forwards a shared thread pool to the underlying [`CommandCollector`] so
     /// that I/O work shares the matcher's thread budget instead of spawning
     /// separate OS threads.
@@ -108,51 +285,105 @@ impl Reader {
         self.cmd_collector.borrow_mut().set_thread_pool(pool);
     }
 
-    /// Starts the reader and returns a control handle
-    pub fn run(&mut self, app_tx: Sender<Vec<Arc<dyn SkimItem>>>, cmd: &str) -> ReaderControl {
-        let components_to_stop: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
-        let items = Arc::new(SpinLock::new(Vec::new()));
+    /// Forwards the log of the commands' stderr to the underlying [`CommandCollector`]
+    pub fn set_stderr_log(&mut self, log: StderrLog) {
+        self.stderr_log = Some(log.clone());
+        self.cmd_collector.borrow_mut().set_stderr_log(log);
+    }
 
-        let (rx_item, tx_interrupt_cmd) = self.rx_item.take().map_or_else(
-            || {
-                let components_to_stop_clone = components_to_stop.clone();
-                let (rx_item, tx_interrupt_cmd) = self.cmd_collector.borrow_mut().invoke(cmd, components_to_stop_clone);
-                (rx_item, Some(tx_interrupt_cmd))
-            },
-            |rx| (rx, None),
-        );
+    /// Forwards the signal of the query edits to the underlying [`CommandCollector`]
+    pub fn set_typing_signal(&mut self, typing: TypingSignal) {
+        self.cmd_collector.borrow_mut().set_typing_signal(typing);
+    }
 
-        let components_to_stop_clone = components_to_stop.clone();
-        let tx_interrupt = collect_items(components_to_stop_clone, rx_item, move |items| _ = app_tx.send(items));
+    /// Forwards the state of skim exported to the commands to the underlying [`CommandCollector`]
+    pub fn set_env(&mut self, env: &[(&str, String)]) {
+        self.cmd_collector.borrow_mut().set_env(env);
+    }
 
-        ReaderControl {
-            tx_interrupt,
-            tx_interrupt_cmd,
-            components_to_stop,
-            items,
-        }
+    /// Starts the reader and returns a control handle
+    pub fn run(&mut self, app_tx: Sender<Vec<Arc<dyn SkimItem>>>, cmd: &str) -> ReaderControl {
+        self.start(cmd, move |items| _ = app_tx.send(items))
     }
 
     /// Starts collecting items and sending them to the pool directly
     /// Returns a control handle
     pub fn collect(&mut self, item_pool: Arc<ItemPool>, cmd: &str) -> ReaderControl {
+        let control = self.start(cmd, move |items| {
+            item_pool.append(items);
+        });
+        debug!("collect: started ({:?} components)", control.components_to_stop);
+        control
+    }
+
+    fn start<F>(&mut self, cmd: &str, callback: F) -> ReaderControl
+    where
+        F: Fn(Vec<Arc<dyn SkimItem>>) + Clone + Send + 'static,
+    {
         let components_to_stop: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
         let items = Arc::new(SpinLock::new(Vec::new()));
 
-        let (rx_item, tx_interrupt_cmd) = self.rx_item.take().map_or_else(
-            || {
-                let components_to_stop_clone = components_to_stop.clone();
-                let (rx_item, tx_interrupt_cmd) = self.cmd_collector.borrow_mut().invoke(cmd, components_to_stop_clone);
-                (rx_item, Some(tx_interrupt_cmd))
-            },
-            |rx| (rx, None),
-        );
+        let (rx_item, tx_interrupt_cmd) = match self.rx_item.take() {
+            Some(rx) => (Some(rx), None),
+            None if !self.sources.is_empty() => (None, None),
+            // the built-in source or the input file is read again by a reload without a command
+            None => {
+                if let Some(path) = self.input_file.as_deref().filter(|_| cmd.is_empty()) {
+                    match self.cmd_collector.borrow().of_file(std::path::Path::new(path)) {
+                        Ok(rx_item) => (Some(rx_item), None),
+                        Err(e) => {
+                            warn!("could not read the input file {path}: {e}");
+                            if let Some(log) = &self.stderr_log {
+                                log.push("input-file", &format!("{path}: {e}"));
+                            }
+                            (None, None)
+                        }
+                    }
+                } else if let Some(builtin) = self.builtin.filter(|_| cmd.is_empty()) {
+                    match builtin.spawn(self.line_ending, self.stderr_log.clone()) {
+                        Ok(source) => (Some(self.cmd_collector.borrow().of_reader(source)), None),
+                        Err(e) => {
+                            warn!("could not read the builtin source {builtin:?}: {e}");
+                            (None, None)
+                        }
+                    }
+                } else {
+                    let components_to_stop_clone = components_to_stop.clone();
+                    let (rx_item, tx_interrupt_cmd) =
+                        self.cmd_collector.borrow_mut().invoke(cmd, components_to_stop_clone);
+                    (Some(rx_item), Some(tx_interrupt_cmd))
+                }
+            }
+        };
 
-        let components_to_stop_clone = components_to_stop.clone();
-        let tx_interrupt = collect_items(components_to_stop_clone, rx_item, move |items| {
-            item_pool.append(items);
-        });
-        debug!("collect: started ({components_to_stop:?} components)");
+        let mut tx_interrupt = Vec::with_capacity(self.sources.len() + 1);
+        if let Some(rx_item) = rx_item {
+            tx_interrupt.push(collect_items(components_to_stop.clone(), rx_item, callback.clone()));
+        }
+        for (position, (label, rx_item)) in self.sources.drain(..).enumerate() {
+            let callback = callback.clone();
+            let counts = self.source_counts.clone();
+            tx_interrupt.push(collect_items(components_to_stop.clone(), rx_item, move |items| {
+                if let Some((_, count)) = counts.lock().get_mut(position) {
+                    *count += items.iter().filter(|item| !RemovedItem::is(item.as_ref())).count();
+                }
+                callback(
+                    items
+                        .into_iter()
+                        .map(|item| {
+                            // removals apply to the items of every source
+                            if RemovedItem::is(item.as_ref()) {
+                                return item;
+                            }
+                            Arc::new(SourcedItem {
+                                label: label.clone(),
+                                item,
+                            }) as Arc<dyn SkimItem>
+                        })
+                        .collect(),
+                );
+            }));
+        }
 
         ReaderControl {
             tx_interrupt,
@@ -168,8 +399,320 @@ impl Default for Reader {
         Self {
             cmd_collector: Rc::new(RefCell::new(SkimItemReader::default())) as Rc<RefCell<dyn CommandCollector>>,
             rx_item: Default::default(),
+            sources: Vec::new(),
+            source_counts: SourceCounts::default(),
+            builtin: None,
+            line_ending: b'\n',
+            input_file: None,
+            stderr_log: None,
+        }
+    }
+}
+
+/// An item read from a source added with [`Reader::add_source`], tagged with its label
+///
+/// Downcast [`item`](Self::item) to get back to the item sent by the source.
+pub struct SourcedItem {
+    label: Arc<str>,
+    item: Arc<dyn SkimItem>,
+}
+
+impl SourcedItem {
+    /// Tags `item` with the `label` of its source
+    #[must_use]
+    pub fn new(label: &str, item: Arc<dyn SkimItem>) -> Self {
+        Self {
+            label: Arc::from(label),
+            item,
         }
     }
+
+    /// Label of the source the item was read from
+    #[must_use]
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// The item sent by the source
+    #[must_use]
+    pub fn item(&self) -> &Arc<dyn SkimItem> {
+        &self.item
+    }
+}
+
+impl SkimItem for SourcedItem {
+    fn text(&self) -> Cow<'_, str> {
+        self.item.text()
+    }
+
+    fn display(&self, context: DisplayContext) -> Line<'_> {
+        let badge = Span::styled(
+            format!("[{}] ", self.label),
+            context.base_style.add_modifier(Modifier::DIM),
+        );
+        let mut line = self.item.display(context);
+        line.spans.insert(0, badge);
+        line
+    }
+
+    fn preview(&self, context: PreviewContext) -> ItemPreview {
+        self.item.preview(context)
+    }
+
+    fn output(&self) -> Cow<'_, str> {
+        self.item.output()
+    }
+
+    fn get_matching_ranges(&self) -> Option<&[(usize, usize)]> {
+        self.item.get_matching_ranges()
+    }
+
+    fn hidden_ranges(&self) -> Option<&[(usize, usize)]> {
+        self.item.hidden_ranges()
+    }
+
+    fn external_ranges(&self) -> Option<&[(usize, usize)]> {
+        self.item.external_ranges()
+    }
+
+    fn annotation(&self) -> Option<&str> {
+        self.item.annotation()
+    }
+
+    fn disabled(&self) -> bool {
+        self.item.disabled()
+    }
+
+    fn group(&self) -> Option<Cow<'_, str>> {
+        self.item.group()
+    }
+
+    fn source(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed(&self.label))
+    }
+}
+
+/// A source of items built into skim, read without a shell pipeline (`--source`)
+///
+/// Each item is made of tab-separated fields, see [`with_nth`](Self::with_nth) and
+/// [`preview`](Self::preview) for the defaults shown with them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum BuiltinSource {
+    /// The running processes: pid, parent pid and command line
+    Ps,
+    /// The environment variables: name and value
+    Env,
+    /// The files tracked by git
+    GitFiles,
+    /// The local and remote git branches: name, date of the last commit and its subject
+    GitBranches,
+}
+
+impl BuiltinSource {
+    /// The fields displayed by default, as for `--with-nth`
+    #[must_use]
+    pub fn with_nth(self) -> &'static [&'static str] {
+        match self {
+            Self::Ps => &["1", "3"],
+            Self::Env | Self::GitFiles | Self::GitBranches => &[],
+        }
+    }
+
+    /// The default preview command
+    #[must_use]
+    pub fn preview(self) -> &'static str {
+        match self {
+            #[cfg(windows)]
+            Self::Ps => "tasklist /v /fo list /fi \"PID eq {1}\"",
+            #[cfg(not(windows))]
+            Self::Ps => "ps -o pid,ppid,user,%cpu,%mem,etime,args -p {1}",
+            Self::Env => "echo {2..}",
+            Self::GitFiles => "git show :./{}",
+            Self::GitBranches => "git log --oneline --graph --color=always -n 100 {1}",
+        }
+    }
+
+    /// Starts listing the items in the background, returning them as they are written, each
+    /// ended by `line_ending`
+    ///
+    /// A failure, e.g. listing the git files outside of a repository, is written to `stderr_log`
+    /// and ends the items.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pipe the items are written to cannot be created.
+    pub fn spawn(self, line_ending: u8, stderr_log: Option<StderrLog>) -> std::io::Result<Box<dyn Read + Send>> {
+        let (reader, writer) = std::io::pipe()?;
+        std::thread::spawn(move || {
+            let written = self
+                .lines()
+                .and_then(|lines| write_items(BufWriter::new(writer), &lines, line_ending));
+            if let Err(e) = written {
+                debug!("builtin source {self:?}: {e}");
+                if let Some(log) = stderr_log {
+                    log.push("source", &e.to_string());
+                }
+            }
+        });
+        Ok(Box::new(reader))
+    }
+
+    fn lines(self) -> std::io::Result<Vec<String>> {
+        match self {
+            Self::Ps => processes(),
+            Self::Env => {
+                let mut vars: Vec<_> = std::env::vars_os()
+                    .map(|(name, value)| format!("{}\t{}", name.to_string_lossy(), one_line(&value.to_string_lossy())))
+                    .collect();
+                vars.sort();
+                Ok(vars)
+            }
+            Self::GitFiles => Ok(git(&["ls-files", "-z"])?
+                .split('\0')
+                .filter(|file| !file.is_empty())
+                .map(String::from)
+                .collect()),
+            Self::GitBranches => Ok(parse_branches(&git(&[
+                "for-each-ref",
+                "--sort=-committerdate",
+                "--format=%(refname)%09%(refname:short)%09%(committerdate:relative)%09%(subject)",
+                "refs/heads",
+                "refs/remotes",
+            ])?)),
+        }
+    }
+}
+
+/// Writes `items` to `out`, each ended by `line_ending`
+///
+/// An item holding `line_ending`, e.g. a git file with a newline in its name without `--read0`,
+/// would be split into several items and is left out.
+fn write_items(mut out: impl Write, items: &[String], line_ending: u8) -> std::io::Result<()> {
+    for item in items {
+        if item.as_bytes().contains(&line_ending) {
+            debug!("builtin source: skipping {item:?}, it holds the line ending");
+            continue;
+        }
+        out.write_all(item.as_bytes())?;
+        out.write_all(&[line_ending])?;
+    }
+    out.flush()
+}
+
+/// `text` with its tabs and line breaks replaced by spaces, to keep the fields of an item apart
+fn one_line(text: &str) -> String {
+    text.replace(['\t', '\n', '\r'], " ")
+}
+
+/// Output of `git args`, or its error message when it fails
+fn git(args: &[&str]) -> std::io::Result<String> {
+    let output = Command::new("git").args(args).stdin(Stdio::null()).output()?;
+    if !output.status.success() {
+        let message = String::from_utf8_lossy(&output.stderr);
+        return Err(std::io::Error::other(message.trim().to_string()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The branches of `git for-each-ref`, without the full ref names nor the `HEAD` of the remotes
+fn parse_branches(refs: &str) -> Vec<String> {
+    refs.lines()
+        .filter_map(|line| line.split_once('\t'))
+        .filter(|(full_name, _)| !full_name.ends_with("/HEAD"))
+        .map(|(_, branch)| branch.to_string())
+        .collect()
+}
+
+/// The running processes, read from `/proc`
+#[cfg(target_os = "linux")]
+fn processes() -> std::io::Result<Vec<String>> {
+    let mut processes: Vec<(u32, String)> = std::fs::read_dir("/proc")?
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let pid: u32 = entry.file_name().to_str()?.parse().ok()?;
+            // the process may have exited since
+            let stat = std::fs::read_to_string(entry.path().join("stat")).ok()?;
+            let cmdline = std::fs::read(entry.path().join("cmdline")).unwrap_or_default();
+            let (name, parent) = parse_proc_stat(&stat)?;
+            let args: Vec<_> = cmdline
+                .split(|&b| b == 0)
+                .filter(|arg| !arg.is_empty())
+                .map(String::from_utf8_lossy)
+                .collect();
+            // kernel threads have no command line
+            let command = if args.is_empty() {
+                format!("[{name}]")
+            } else {
+                args.join(" ")
+            };
+            Some((pid, format!("{pid}\t{parent}\t{}", one_line(&command))))
+        })
+        .collect();
+    processes.sort_unstable_by_key(|(pid, _)| *pid);
+    Ok(processes.into_iter().map(|(_, line)| line).collect())
+}
+
+/// The command name and parent pid of a `/proc/<pid>/stat`
+///
+/// The name is between the first `(` and the last `)`, as it may contain both.
+#[cfg(any(target_os = "linux", test))]
+fn parse_proc_stat(stat: &str) -> Option<(&str, &str)> {
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    let name = stat.get(open + 1..close)?;
+    // after the name come the state and the parent pid
+    let ppid = stat.get(close + 1..)?.split_whitespace().nth(1)?;
+    Some((name, ppid))
+}
+
+/// The running processes, listed by `ps`
+#[cfg(all(unix, not(target_os = "linux")))]
+fn processes() -> std::io::Result<Vec<String>> {
+    let output = Command::new("ps")
+        .args(["-axo", "pid=,ppid=,args="])
+        .stdin(Stdio::null())
+        .output()?;
+    Ok(parse_ps(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// The lines of `ps -o pid=,ppid=,args=`, with their columns separated by tabs
+#[cfg(any(all(unix, not(target_os = "linux")), test))]
+fn parse_ps(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (pid, rest) = line.trim_start().split_once(char::is_whitespace)?;
+            let (parent, args) = rest.trim_start().split_once(char::is_whitespace).unwrap_or((rest, ""));
+            Some(format!("{pid}\t{parent}\t{}", one_line(args.trim())))
+        })
+        .collect()
+}
+
+/// The running processes, listed by `tasklist`
+///
+/// `tasklist` does not show the parent pids, left as `-`.
+#[cfg(windows)]
+fn processes() -> std::io::Result<Vec<String>> {
+    let output = Command::new("tasklist")
+        .args(["/fo", "csv", "/nh"])
+        .stdin(Stdio::null())
+        .output()?;
+    Ok(parse_tasklist(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// The lines of `tasklist /fo csv /nh`: `"name","pid",...`
+#[cfg(any(windows, test))]
+fn parse_tasklist(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut columns = line.trim().trim_matches('"').split("\",\"");
+            let name = columns.next()?;
+            let pid = columns.next()?;
+            Some(format!("{pid}\t-\t{name}"))
+        })
+        .collect()
 }
 
 fn collect_items<F>(components_to_stop: Arc<AtomicUsize>, rx_item: SkimItemReceiver, callback: F) -> Sender<i32>
@@ -181,6 +724,8 @@ where
     let started = Arc::new(AtomicBool::new(false));
     let started_clone = started.clone();
     std::thread::spawn(move || {
+        let span = debug_span!(target: crate::tui::timings::TARGET, "reader", items = tracing::field::Empty).entered();
+        let mut read = 0;
         debug!("collect_item start");
         components_to_stop.fetch_add(1, Ordering::SeqCst);
         started_clone.store(true, Ordering::SeqCst); // notify parent that it is started
@@ -193,6 +738,7 @@ where
             match rx_item.recv_timeout(std::time::Duration::from_millis(1)) {
                 Ok(items) => {
                     trace!("collect_item: got {} items", items.len());
+                    read += items.len();
                     callback(items);
                 }
                 Err(kanal::ReceiveErrorTimeout::Timeout) => {
@@ -206,6 +752,7 @@ where
         }
 
         components_to_stop.fetch_sub(1, Ordering::SeqCst);
+        span.record("items", read);
         debug!("collect_item stop");
     });
 
@@ -260,6 +807,35 @@ mod tests {
         drop(control);
     }
 
+    #[test]
+    fn add_source_tags_and_counts_the_items() {
+        let (tx, rx) = kanal::unbounded::<Vec<Arc<dyn SkimItem>>>();
+        let mut reader = Reader::default()
+            .add_source("git", source("a\nb\n"))
+            .add_source("files", source("c\n"));
+        // with labeled sources and no `source`, the command is not run
+        let control = reader.run(tx, "echo nope");
+        wait_until(|| control.is_done());
+
+        let mut labels = Vec::new();
+        while let Ok(Some(batch)) = rx.try_recv() {
+            for item in batch {
+                let sourced = item.as_any().downcast_ref::<SourcedItem>().unwrap();
+                labels.push(format!("{}:{}", sourced.label(), item.text()));
+                assert_eq!(item.source().as_deref(), Some(sourced.label()));
+            }
+        }
+        labels.sort();
+        assert_eq!(labels, ["files:c", "git:a", "git:b"]);
+        let counts: Vec<_> = reader
+            .source_counts()
+            .lock()
+            .iter()
+            .map(|(label, count)| (label.to_string(), *count))
+            .collect();
+        assert_eq!(counts, [("git".to_string(), 2), ("files".to_string(), 1)]);
+    }
+
     #[test]
     fn is_done_true_after_completion() {
         let pool = Arc::new(ItemPool::new());
@@ -326,4 +902,87 @@ mod tests {
         wait_until(|| control.is_done());
         assert!(control.take().is_empty());
     }
+
+    #[test]
+    fn builtin_source_is_read_without_a_command() {
+        let pool = Arc::new(ItemPool::new());
+        let mut reader = Reader {
+            builtin: Some(BuiltinSource::Env),
+            ..Default::default()
+        };
+        let control = reader.collect(pool.clone(), "");
+        wait_until(|| control.is_done());
+        // one item per variable, the variables of the other tests coming and going
+        assert_ne!(pool.len(), 0);
+        drop(control);
+    }
+
+    #[test]
+    fn input_file_is_read_without_a_command() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"a\nb\n").unwrap();
+        let pool = Arc::new(ItemPool::new());
+        let mut reader = Reader {
+            input_file: Some(file.path().to_string_lossy().into_owned()),
+            ..Default::default()
+        };
+        let control = reader.collect(pool.clone(), "");
+        wait_until(|| control.is_done());
+        let texts: Vec<_> = pool.items().iter().map(|item| item.text().into_owned()).collect();
+        assert_eq!(texts, vec!["a", "b"]);
+
+        // a missing file lists nothing and is logged
+        let log = StderrLog::default();
+        let mut reader = Reader {
+            input_file: Some("/nonexistent/input".to_string()),
+            stderr_log: Some(log.clone()),
+            ..Default::default()
+        };
+        let control = reader.collect(Arc::new(ItemPool::new()), "");
+        wait_until(|| control.is_done());
+        assert!(log.last(1)[0].starts_with("[input-file] /nonexistent/input: "));
+    }
+
+    #[test]
+    fn builtin_items_holding_the_line_ending_are_left_out() {
+        let items = vec!["a".to_string(), "new\nline".to_string(), "b".to_string()];
+        let mut out = Vec::new();
+        write_items(&mut out, &items, b'\n').unwrap();
+        assert_eq!(out, b"a\nb\n");
+
+        // with --read0 the items are NUL-separated and kept whole
+        let mut out = Vec::new();
+        write_items(&mut out, &items, b'\0').unwrap();
+        assert_eq!(out, b"a\0new\nline\0b\0");
+    }
+
+    #[test]
+    fn parse_process_lists() {
+        assert_eq!(
+            parse_proc_stat("42 (tmux: server) S 1 42 42 0 -1"),
+            Some(("tmux: server", "1"))
+        );
+        assert_eq!(parse_proc_stat("7 (a) b) R 3 7"), Some(("a) b", "3")));
+        assert_eq!(
+            parse_ps("    1     0 /sbin/init splash\n  512     1 sshd\n"),
+            vec!["1\t0\t/sbin/init splash", "512\t1\tsshd"]
+        );
+        assert_eq!(
+            parse_tasklist(
+                "\"System Idle Process\",\"0\",\"Services\",\"0\",\"8 K\"\r\n\"cmd.exe\",\"4321\",\"Console\",\"1\",\"4,096 K\"\r\n"
+            ),
+            vec!["0\t-\tSystem Idle Process", "4321\t-\tcmd.exe"]
+        );
+    }
+
+    #[test]
+    fn parse_branches_skips_remote_heads() {
+        let refs = "refs/heads/master\tmaster\t2 days ago\tFix\n\
+                    refs/remotes/origin/HEAD\torigin\t2 days ago\tFix\n\
+                    refs/remotes/origin/dev\torigin/dev\t3 weeks ago\tWip\n";
+        assert_eq!(
+            parse_branches(refs),
+            vec!["master\t2 days ago\tFix", "origin/dev\t3 weeks ago\tWip"]
+        );
+    }
 }
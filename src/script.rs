@@ -0,0 +1,180 @@
+//! Rhai scripts customizing the items without a Rust wrapper (`--map-item`, `--rank-hook`, `--accept-hook`)
+//!
+//! A script is compiled once and evaluated for every item, or once on accept, with the variables
+//! of its hook in scope. Its `print` and `debug` go to the log instead of the terminal, and an
+//! error at run time leaves the item as it is. A run going past the limits below, e.g. an endless
+//! loop, is stopped with an error.
+
+use std::sync::Arc;
+
+use rhai::{AST, Dynamic, Engine, EvalAltResult, Scope};
+
+/// Operations of one run of a script
+const MAX_OPERATIONS: u64 = 1_000_000;
+/// Depth of the function calls of a script
+const MAX_CALL_LEVELS: usize = 32;
+/// Bytes of a string built by a script
+const MAX_STRING_SIZE: usize = 16 << 20;
+/// Elements of an array or a map built by a script
+const MAX_COLLECTION_SIZE: usize = 1 << 20;
+
+/// A compiled Rhai script
+#[derive(Clone, derive_more::Debug)]
+#[debug("Script({source:?})")]
+pub struct Script {
+    source: Arc<str>,
+    engine: Arc<Engine>,
+    ast: Arc<AST>,
+}
+
+impl Script {
+    /// Compiles `source`
+    ///
+    /// # Errors
+    ///
+    /// Fails with the position of the syntax error in `source`.
+    pub fn new(source: &str) -> Result<Self, String> {
+        let mut engine = Engine::new();
+        engine
+            .set_max_operations(MAX_OPERATIONS)
+            .set_max_call_levels(MAX_CALL_LEVELS)
+            .set_max_string_size(MAX_STRING_SIZE)
+            .set_max_array_size(MAX_COLLECTION_SIZE)
+            .set_max_map_size(MAX_COLLECTION_SIZE);
+        engine.on_print(|text| info!("script: {text}"));
+        engine.on_debug(|text, _, position| debug!("script {position}: {text}"));
+        let ast = engine.compile(source).map_err(|err| err.to_string())?;
+        Ok(Self {
+            source: Arc::from(source),
+            engine: Arc::new(engine),
+            ast: Arc::new(ast),
+        })
+    }
+
+    /// The source of the script
+    #[must_use]
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Evaluates the script with `scope`
+    fn try_eval(&self, mut scope: Scope) -> Result<Dynamic, Box<EvalAltResult>> {
+        self.engine.eval_ast_with_scope::<Dynamic>(&mut scope, &self.ast)
+    }
+
+    /// Evaluates the script with `scope`, `None` for `()` or an error, which is logged
+    fn eval(&self, scope: Scope) -> Option<Dynamic> {
+        match self.try_eval(scope) {
+            Ok(result) if result.is_unit() => None,
+            Ok(result) => Some(result),
+            Err(err) => {
+                warn!("script {:?} failed: {err}", self.source);
+                None
+            }
+        }
+    }
+
+    /// The text displayed and matched for the line `text` (`--map-item`), `None` to keep it
+    pub(crate) fn map_item(&self, text: &str) -> Option<String> {
+        let mut vars = Scope::new();
+        vars.push("text", text.to_string());
+        self.eval(vars).map(|text| text.to_string())
+    }
+
+    /// The score of `text` matching `query` with `score` (`--rank-hook`), `None` to keep it
+    pub(crate) fn rank(&self, text: &str, query: &str, score: i32) -> Option<i32> {
+        let mut vars = Scope::new();
+        vars.push("text", text.to_string());
+        vars.push("query", query.to_string());
+        vars.push("score", i64::from(score));
+        let score = self.eval(vars)?.as_int().ok()?;
+        Some(i32::try_from(score).unwrap_or(if score < 0 { i32::MIN } else { i32::MAX }))
+    }
+
+    /// The lines printed in place of the selected `items` (`--accept-hook`), `None` to print them
+    ///
+    /// An array gives one line per element, anything else a single line.
+    pub(crate) fn accept(&self, items: Vec<String>, query: &str, key: &str) -> Option<Vec<String>> {
+        let mut vars = Scope::new();
+        vars.push("items", items.into_iter().map(Dynamic::from).collect::<rhai::Array>());
+        vars.push("query", query.to_string());
+        vars.push("key", key.to_string());
+        let result = self.eval(vars)?;
+        if result.is_array() {
+            result
+                .into_array()
+                .ok()
+                .map(|lines| lines.iter().map(ToString::to_string).collect())
+        } else {
+            Some(vec![result.to_string()])
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn syntax_errors_fail_to_compile() {
+        assert!(Script::new("text +").is_err());
+    }
+
+    #[test]
+    fn map_item_keeps_the_text_on_unit_or_error() {
+        let script = Script::new(r##"if text.starts_with("#") { } else { text.to_upper() }"##).unwrap();
+        assert_eq!(script.map_item("abc"), Some("ABC".to_string()));
+        assert_eq!(script.map_item("# abc"), None);
+        assert_eq!(Script::new("text.no_such_fn()").unwrap().map_item("abc"), None);
+    }
+
+    #[test]
+    fn runaway_scripts_are_stopped() {
+        let text = || {
+            let mut vars = Scope::new();
+            vars.push("text", "abc".to_string());
+            vars
+        };
+        let endless = Script::new("loop { }").unwrap();
+        assert!(matches!(
+            *endless.try_eval(text()).unwrap_err(),
+            EvalAltResult::ErrorTooManyOperations(_)
+        ));
+        // the error is logged and the item kept
+        assert_eq!(endless.map_item("abc"), None);
+
+        let recursive = Script::new("fn f(x) { f(x) } f(text)").unwrap();
+        assert!(matches!(
+            *recursive.try_eval(text()).unwrap_err(),
+            EvalAltResult::ErrorStackOverflow(_)
+        ));
+        let growing = Script::new("let s = text; loop { s += s; }").unwrap();
+        assert!(matches!(
+            *growing.try_eval(text()).unwrap_err(),
+            EvalAltResult::ErrorDataTooLarge(..)
+        ));
+    }
+
+    #[test]
+    fn rank_adjusts_the_score() {
+        let script = Script::new(r#"if text.ends_with(".rs") { score + 100 } else { score }"#).unwrap();
+        assert_eq!(script.rank("a.rs", "a", 10), Some(110));
+        assert_eq!(script.rank("a.md", "a", 10), Some(10));
+        assert_eq!(Script::new(r#""high""#).unwrap().rank("a", "a", 10), None);
+        assert_eq!(Script::new("1 << 40").unwrap().rank("a", "a", 10), Some(i32::MAX));
+    }
+
+    #[test]
+    fn accept_prints_arrays_line_by_line() {
+        let items = || vec!["a".to_string(), "b".to_string()];
+        let script = Script::new(r#"items.map(|item| key + ":" + item)"#).unwrap();
+        assert_eq!(
+            script.accept(items(), "q", "enter"),
+            Some(vec!["enter:a".to_string(), "enter:b".to_string()])
+        );
+        let script = Script::new(r#"items.len() + " for " + query"#).unwrap();
+        assert_eq!(script.accept(items(), "q", ""), Some(vec!["2 for q".to_string()]));
+        assert_eq!(Script::new("()").unwrap().accept(items(), "q", ""), None);
+    }
+}
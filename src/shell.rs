@@ -1,9 +1,13 @@
 //! Provides helpers to easily generate shell completions
-use std::io::Write;
+use std::collections::{BTreeSet, HashSet};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 
-use clap::CommandFactory;
+use clap::{CommandFactory, ValueEnum as _};
 
 use crate::SkimOptions;
+use crate::options::MatchScheme;
 
 /// Available shells for completion generation
 #[derive(Clone, clap::ValueEnum, PartialEq, Debug)]
@@ -60,6 +64,355 @@ pub fn generate_key_bindings(sh: &Shell, output: &mut impl Write) -> std::io::Re
     Ok(())
 }
 
+/// Variables read by the key bindings, given a default by `--shell-init` when unset
+///
+/// The defaults use the sk options, so that they follow them.
+fn init_defaults() -> Vec<(&'static str, String)> {
+    let scheme = |scheme: MatchScheme| {
+        let value = scheme.to_possible_value().expect("no scheme is skipped");
+        format!("--scheme={}", value.get_name())
+    };
+    vec![
+        ("SKIM_CTRL_T_OPTS", scheme(MatchScheme::Path)),
+        ("SKIM_CTRL_R_OPTS", scheme(MatchScheme::History)),
+        ("SKIM_ALT_C_OPTS", scheme(MatchScheme::Path)),
+        ("SKIM_COMPLETION_TRIGGER", "**".to_string()),
+    ]
+}
+
+/// Generate the whole shell integration for `--shell-init`: defaults, completions and key bindings
+///
+/// The key bindings provide ctrl-t (files), ctrl-r (history), alt-c (cd) and the `**` completion.
+/// # Errors
+/// This errors if `sh` is not bash, zsh or fish, or if it fails to write the bytes to the output
+pub fn generate_init(sh: &Shell, output: &mut impl Write) -> std::io::Result<()> {
+    use Shell::{Bash, Fish, Zsh};
+    use shell_quote::{Fish as FishQuote, Quote as _, Sh};
+    let name = match sh {
+        Bash => "bash",
+        Zsh => "zsh",
+        Fish => "fish",
+        _ => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("no shell integration for {sh:?}, only for bash, zsh and fish"),
+            ));
+        }
+    };
+    writeln!(
+        output,
+        "# skim shell integration, generated by `sk --shell-init {name}`"
+    )?;
+    for (var, value) in init_defaults() {
+        if *sh == Fish {
+            let value: Vec<u8> = FishQuote::quote(value.as_str());
+            let value = String::from_utf8_lossy(&value);
+            writeln!(output, "set -q {var}; or set -g {var} {value}")?;
+        } else {
+            let value: Vec<u8> = Sh::quote(value.as_str());
+            let value = String::from_utf8_lossy(&value);
+            writeln!(output, "[ -n \"${{{var}+x}}\" ] || {var}={value}")?;
+        }
+    }
+    generate_completions(sh, output);
+    generate_key_bindings(sh, output)
+}
+
+/// Candidates of `--completion-context`, for the `**` completion of the key bindings
+#[derive(Clone, clap::ValueEnum, PartialEq, Debug)]
+pub enum CompletionContext {
+    /// Files and directories under the directory of the word being completed
+    Path,
+    /// Directories under the directory of the word being completed
+    Dir,
+    /// Hosts from the ssh config, `known_hosts` and `/etc/hosts`
+    Host,
+    /// Running processes, as listed by `ps -ef`
+    Proc,
+}
+
+/// Directories never entered when listing paths
+const VCS_DIRS: [&str; 3] = [".git", ".hg", ".svn"];
+
+/// Splits the word being completed into the directory to list and the query
+///
+/// The directory is the longest existing one the word starts with, `.` if there is none, like
+/// the `**` trigger of the key bindings: `src/ma` gives `src` and `ma`.
+#[must_use]
+pub fn completion_base(word: &str) -> (String, String) {
+    let mut dir = if word.contains('/') {
+        word.to_string()
+    } else {
+        String::new()
+    };
+    loop {
+        if dir.is_empty() || Path::new(&dir).is_dir() {
+            let leftover = word.strip_prefix(dir.as_str()).unwrap_or(word);
+            let leftover = leftover.strip_prefix('/').unwrap_or(leftover).to_string();
+            if dir.is_empty() {
+                dir = String::from(".");
+            } else if dir != "/" {
+                dir.truncate(dir.trim_end_matches('/').len());
+            }
+            return (dir, leftover);
+        }
+        dir = match Path::new(&dir).parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => format!("{}/", parent.display()),
+            _ => String::new(),
+        };
+    }
+}
+
+/// Source of the candidates of `ctx`, paths being listed under `root`
+///
+/// Paths are listed by a thread as they are found, the other candidates are read up front.
+/// # Errors
+/// This errors if the pipe of the paths cannot be created, or `ps` cannot be run
+pub fn completion_candidates(ctx: &CompletionContext, root: &str) -> std::io::Result<Box<dyn Read + Send>> {
+    let lines = match ctx {
+        CompletionContext::Path | CompletionContext::Dir => {
+            let (reader, mut writer) = std::io::pipe()?;
+            let root = root.to_string();
+            let dirs_only = *ctx == CompletionContext::Dir;
+            std::thread::spawn(move || {
+                let _ = list_paths(&root, dirs_only, &mut writer);
+            });
+            return Ok(Box::new(reader));
+        }
+        CompletionContext::Host => hosts().into_iter().collect::<Vec<_>>(),
+        CompletionContext::Proc => {
+            let output = Command::new("ps").arg("-ef").stdin(Stdio::null()).output()?;
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .skip(1)
+                .map(String::from)
+                .collect()
+        }
+    };
+    Ok(Box::new(std::io::Cursor::new(lines.join("\n"))))
+}
+
+/// Writes the paths under `root`, without entering VCS directories or following links
+fn list_paths(root: &str, dirs_only: bool, output: &mut impl Write) -> std::io::Result<()> {
+    let display = |path: &Path| {
+        let path = path.to_string_lossy();
+        match path.strip_prefix("./") {
+            Some(rest) if root == "." => rest.to_string(),
+            _ => path.into_owned(),
+        }
+    };
+    let mut pending = vec![PathBuf::from(root)];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut entries: Vec<_> = entries.filter_map(Result::ok).collect();
+        entries.sort_by_key(std::fs::DirEntry::file_name);
+        // listed after the entries of this directory, in order
+        for entry in entries.iter().rev() {
+            let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+            if is_dir && !VCS_DIRS.iter().any(|vcs| entry.file_name() == *vcs) {
+                pending.push(entry.path());
+            }
+        }
+        for entry in entries {
+            let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+            if VCS_DIRS.iter().any(|vcs| entry.file_name() == *vcs) || (dirs_only && !is_dir) {
+                continue;
+            }
+            writeln!(output, "{}", display(&entry.path()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Hosts of the ssh config, `known_hosts` and `/etc/hosts`, sorted and without duplicates
+fn hosts() -> BTreeSet<String> {
+    let read = |path: &Path| std::fs::read_to_string(path).unwrap_or_default();
+    let mut hosts = BTreeSet::new();
+    let ssh_dir = std::env::home_dir().map(|home| home.join(".ssh"));
+    let mut configs: Vec<PathBuf> = vec![PathBuf::from("/etc/ssh/ssh_config")];
+    if let Some(ssh_dir) = &ssh_dir {
+        configs.push(ssh_dir.join("config"));
+        if let Ok(entries) = std::fs::read_dir(ssh_dir.join("config.d")) {
+            configs.extend(entries.filter_map(Result::ok).map(|e| e.path()));
+        }
+        hosts.extend(parse_known_hosts(&read(&ssh_dir.join("known_hosts"))));
+    }
+    for config in configs {
+        hosts.extend(parse_ssh_config(&read(&config)));
+    }
+    hosts.extend(parse_etc_hosts(&read(Path::new("/etc/hosts"))));
+    hosts
+}
+
+/// Names of the `Host` and `HostName` entries of an ssh config, without patterns
+fn parse_ssh_config(data: &str) -> Vec<String> {
+    data.lines()
+        .filter_map(|line| {
+            let mut words = line.split_whitespace();
+            let key = words.next()?.to_ascii_lowercase();
+            (key == "host" || key == "hostname").then_some(words)
+        })
+        .flatten()
+        .filter(|host| !host.contains(['*', '?']))
+        .map(String::from)
+        .collect()
+}
+
+/// Hosts of a `known_hosts` file, hashed entries excepted
+fn parse_known_hosts(data: &str) -> Vec<String> {
+    data.lines()
+        .flat_map(|line| {
+            let len = line
+                .find(|c: char| !(c.is_ascii_lowercase() || c.is_ascii_digit() || ".,:-[".contains(c)))
+                .unwrap_or(line.len());
+            line[..len].split(',')
+        })
+        .map(|host| host.trim_start_matches('['))
+        .filter(|host| !host.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Names of `/etc/hosts`, the `0.0.0.0` blocking entries excepted
+fn parse_etc_hosts(data: &str) -> Vec<String> {
+    data.lines()
+        .filter(|line| !line.trim_start().starts_with('#') && !line.contains("0.0.0.0"))
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .map(String::from)
+        .collect()
+}
+
+/// Location of the history file of `sh`: `$HISTFILE`, then the shell's default
+///
+/// Only bash, zsh and fish are supported.
+#[must_use]
+pub fn history_file(sh: &Shell) -> Option<PathBuf> {
+    use Shell::{Bash, Fish, Zsh};
+    let home = std::env::home_dir();
+    match sh {
+        Bash | Zsh => std::env::var_os("HISTFILE")
+            .filter(|f| !f.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| home.map(|h| h.join(if *sh == Bash { ".bash_history" } else { ".zsh_history" }))),
+        Fish => std::env::var_os("XDG_DATA_HOME")
+            .filter(|d| !d.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| home.map(|h| h.join(".local/share")))
+            .map(|d| d.join("fish/fish_history")),
+        _ => None,
+    }
+}
+
+/// Commands of a history file written by `sh`, newest first and without duplicates
+///
+/// Multi-line commands are kept whole: bash entries run until the next timestamp when
+/// `HISTTIMEFORMAT` was set, zsh entries continue after a trailing backslash, and fish
+/// escapes its newlines.
+#[must_use]
+pub fn parse_history(sh: &Shell, data: &[u8]) -> Vec<String> {
+    let entries = match sh {
+        Shell::Bash => parse_bash_history(&String::from_utf8_lossy(data)),
+        Shell::Zsh => parse_zsh_history(&String::from_utf8_lossy(&unmetafy(data))),
+        Shell::Fish => parse_fish_history(&String::from_utf8_lossy(data)),
+        _ => Vec::new(),
+    };
+    let mut seen = HashSet::new();
+    entries
+        .into_iter()
+        .rev()
+        .filter(|cmd| !cmd.trim().is_empty() && seen.insert(cmd.clone()))
+        .collect()
+}
+
+fn parse_bash_history(data: &str) -> Vec<String> {
+    let is_timestamp =
+        |line: &str| line.len() > 1 && line.starts_with('#') && line[1..].bytes().all(|b| b.is_ascii_digit());
+    if !data.lines().any(is_timestamp) {
+        return data.lines().map(String::from).collect();
+    }
+    let mut entries: Vec<String> = Vec::new();
+    let mut in_entry = false;
+    for line in data.lines() {
+        if is_timestamp(line) {
+            in_entry = false;
+        } else if let Some(entry) = entries.last_mut().filter(|_| in_entry) {
+            entry.push('\n');
+            entry.push_str(line);
+        } else {
+            entries.push(line.to_string());
+            in_entry = true;
+        }
+    }
+    entries
+}
+
+/// Undo the "metafication" zsh applies to the bytes of its history file
+fn unmetafy(data: &[u8]) -> Vec<u8> {
+    const META: u8 = 0x83;
+    let mut out = Vec::with_capacity(data.len());
+    let mut bytes = data.iter();
+    while let Some(&b) = bytes.next() {
+        if b == META {
+            if let Some(&next) = bytes.next() {
+                out.push(next ^ 0x20);
+            }
+        } else {
+            out.push(b);
+        }
+    }
+    out
+}
+
+fn parse_zsh_history(data: &str) -> Vec<String> {
+    let mut entries = Vec::new();
+    let mut current: Option<String> = None;
+    for line in data.lines() {
+        let line = match current.take() {
+            Some(mut entry) => {
+                entry.push_str(line);
+                entry
+            }
+            // `: <start>:<duration>;<command>` with `setopt extended_history`
+            None => match line.strip_prefix(": ").and_then(|l| l.split_once(';')) {
+                Some((stamp, cmd)) if stamp.bytes().all(|b| b.is_ascii_digit() || b == b':') => cmd.to_string(),
+                _ => line.to_string(),
+            },
+        };
+        match line.strip_suffix('\\') {
+            Some(continued) => current = Some(format!("{continued}\n")),
+            None => entries.push(line),
+        }
+    }
+    entries.extend(current);
+    entries
+}
+
+fn parse_fish_history(data: &str) -> Vec<String> {
+    data.lines()
+        .filter_map(|line| line.strip_prefix("- cmd: "))
+        .map(|cmd| {
+            let mut out = String::with_capacity(cmd.len());
+            let mut chars = cmd.chars();
+            while let Some(c) = chars.next() {
+                match (c, chars.clone().next()) {
+                    ('\\', Some('n')) => {
+                        out.push('\n');
+                        chars.next();
+                    }
+                    ('\\', Some('\\')) => {
+                        out.push('\\');
+                        chars.next();
+                    }
+                    _ => out.push(c),
+                }
+            }
+            out
+        })
+        .collect()
+}
+
 #[cfg(test)]
 #[path = "shell_tests.rs"]
 mod tests;
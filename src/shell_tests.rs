@@ -107,3 +107,137 @@ fn key_bindings_unsupported_shells_are_empty() {
         );
     }
 }
+
+#[test]
+fn bash_history_dedups_newest_first() {
+    let history = parse_history(&Shell::Bash, b"ls\ncd /tmp\nls\nmake\n");
+    assert_eq!(history, ["make", "ls", "cd /tmp"]);
+}
+
+#[test]
+fn bash_history_with_timestamps_keeps_multi_line_entries() {
+    let history = parse_history(
+        &Shell::Bash,
+        b"#1700000000\nfor f in *; do\n  echo $f\ndone\n#1700000001\nls\n",
+    );
+    assert_eq!(history, ["ls", "for f in *; do\n  echo $f\ndone"]);
+}
+
+#[test]
+fn zsh_history_reads_extended_and_multi_line_entries() {
+    let data = b": 1700000000:0;echo one\\\ntwo\n: 1700000001:3;ls -l\nplain\n: 1700000002:0;ls -l\n";
+    let history = parse_history(&Shell::Zsh, data);
+    assert_eq!(history, ["ls -l", "plain", "echo one\ntwo"]);
+}
+
+#[test]
+fn zsh_history_is_unmetafied() {
+    // zsh stores the bytes 0x83 to 0xa2 as 0x83 followed by the byte xor 0x20: `→` is e2 86 92
+    let history = parse_history(&Shell::Zsh, b"echo \xe2\x83\xa6\x83\xb2\n");
+    assert_eq!(history, ["echo →"]);
+}
+
+#[test]
+fn fish_history_unescapes_commands() {
+    let data = b"- cmd: echo a\\nb\n  when: 1700000000\n- cmd: printf '\\\\t'\n  when: 1700000001\n";
+    let history = parse_history(&Shell::Fish, data);
+    assert_eq!(history, ["printf '\\t'", "echo a\nb"]);
+}
+
+fn init_for(sh: &Shell) -> std::io::Result<String> {
+    let mut buf = Vec::new();
+    generate_init(sh, &mut buf)?;
+    Ok(String::from_utf8(buf).expect("init output is valid UTF-8"))
+}
+
+#[test]
+fn init_bash_sets_unset_defaults_then_bindings() {
+    let out = init_for(&Shell::Bash).unwrap();
+    assert!(out.contains("[ -n \"${SKIM_CTRL_R_OPTS+x}\" ] || SKIM_CTRL_R_OPTS=--scheme'=history'\n"));
+    assert!(out.contains("SKIM_COMPLETION_TRIGGER='**'"));
+    let defaults = out.find("SKIM_CTRL_T_OPTS=").unwrap();
+    let bindings = out.find("__skim_select__").unwrap();
+    assert!(defaults < bindings);
+}
+
+#[test]
+fn init_fish_uses_set_q() {
+    let out = init_for(&Shell::Fish).unwrap();
+    assert!(out.contains("set -q SKIM_ALT_C_OPTS; or set -g SKIM_ALT_C_OPTS --scheme'=path'\n"));
+    assert!(out.contains("skim-history-widget"));
+}
+
+#[test]
+fn init_is_only_for_bash_zsh_and_fish() {
+    assert!(init_for(&Shell::Zsh).is_ok());
+    assert_eq!(
+        init_for(&Shell::PowerShell).unwrap_err().kind(),
+        std::io::ErrorKind::InvalidInput
+    );
+}
+
+#[test]
+fn completion_base_splits_the_existing_directory() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path().display().to_string();
+    std::fs::create_dir(tmp.path().join("src")).unwrap();
+
+    assert_eq!(completion_base("ma"), (".".to_string(), "ma".to_string()));
+    assert_eq!(completion_base(""), (".".to_string(), String::new()));
+    assert_eq!(
+        completion_base(&format!("{root}/src/ma")),
+        (format!("{root}/src"), "ma".to_string())
+    );
+    assert_eq!(
+        completion_base(&format!("{root}/src/")),
+        (format!("{root}/src"), String::new())
+    );
+    assert_eq!(
+        completion_base(&format!("{root}/nope/ma")),
+        (root.clone(), "nope/ma".to_string())
+    );
+}
+
+#[test]
+fn list_paths_skips_vcs_directories() {
+    let tmp = tempfile::tempdir().unwrap();
+    for dir in ["a", "a/b", ".git"] {
+        std::fs::create_dir(tmp.path().join(dir)).unwrap();
+    }
+    for file in ["a/b/f", "g", ".git/HEAD"] {
+        std::fs::write(tmp.path().join(file), "").unwrap();
+    }
+    let root = tmp.path().display().to_string();
+    let listed = |dirs_only| {
+        let mut buf = Vec::new();
+        list_paths(&root, dirs_only, &mut buf).unwrap();
+        String::from_utf8(buf)
+            .unwrap()
+            .lines()
+            .map(|l| l.strip_prefix(&format!("{root}/")).unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(listed(false), ["a", "g", "a/b", "a/b/f"]);
+    assert_eq!(listed(true), ["a", "a/b"]);
+}
+
+#[test]
+fn hosts_of_the_ssh_config() {
+    let config = "Host work *.internal\n  HostName work.example.com\n  User me\nhost ?x\n";
+    assert_eq!(parse_ssh_config(config), ["work", "work.example.com"]);
+}
+
+#[test]
+fn hosts_of_known_hosts() {
+    let data = "github.com,140.82.121.4 ssh-ed25519 AAAA\n[git.example.com]:2222 ssh-rsa AAAA\n|1|hash= ssh-rsa AAAA\n";
+    assert_eq!(
+        parse_known_hosts(data),
+        ["github.com", "140.82.121.4", "git.example.com"]
+    );
+}
+
+#[test]
+fn hosts_of_etc_hosts() {
+    let data = "# comment\n127.0.0.1 localhost\n0.0.0.0 ads.example.com\n::1 ip6-localhost ip6-loopback\n";
+    assert_eq!(parse_etc_hosts(data), ["localhost", "ip6-localhost"]);
+}
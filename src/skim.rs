@@ -12,9 +12,10 @@ use tokio::select;
 use tokio::task::block_in_place;
 
 use crate::binds::SkimEvent;
+use crate::item::MatchedItem;
 use crate::reader::{Reader, ReaderControl};
 use crate::tui::event::Action;
-use crate::tui::{App, Event, Size, TICK_RATE, Tui};
+use crate::tui::{App, Event, Size, Tui};
 use crate::{SkimItem, SkimItemReceiver, SkimOptions, SkimOutput};
 
 /// Stream type yielded by the IPC listener. With the `listen` feature disabled the
@@ -40,10 +41,17 @@ where
     matcher_interval: Option<tokio::time::Interval>,
     #[cfg(feature = "listen")]
     listener: Option<interprocess::local_socket::tokio::Listener>,
+    /// Watcher of the `--watch` paths, kept alive while skim runs
+    #[cfg(feature = "watch")]
+    watcher: Option<notify::RecommendedWatcher>,
     final_event: Event,
     final_key: KeyEvent,
     /// Whether the `start` event has already been fired (fired exactly once).
     start_fired: bool,
+    /// Reload waiting for `--reload-delay` to elapse, with its deadline.
+    pending_reload: Option<(String, tokio::time::Instant)>,
+    /// Ticks of `--reload-interval`
+    reload_interval: Option<tokio::time::Interval>,
 }
 
 impl Skim {
@@ -128,16 +136,43 @@ impl Skim {
         Self::run_with(options, Some(rx))
     }
 
+    /// Runs filter mode, passing the matched items to `on_matches` instead of keeping them
+    ///
+    /// Unless sorted by `filter_sort`, the items come in input order, each batch as soon as the
+    /// items read before it are matched, so that a long input can be printed while it is read.
+    /// The returned output, that of [`run_with`](Self::run_with), holds no selected items.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if skim initialization fails.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `options.filter` is not set.
+    pub fn run_filter(
+        options: SkimOptions,
+        source: Option<SkimItemReceiver>,
+        mut on_matches: impl FnMut(Vec<MatchedItem>),
+    ) -> Result<SkimOutput> {
+        assert!(options.filter.is_some(), "run_filter needs a filter query");
+        let mut skim = Self::init(options, source)?;
+        skim.start();
+        skim.filter(&mut on_matches);
+        skim.final_event = Event::Action(Action::Accept(None));
+        Ok(skim.output())
+    }
+
     /// Initialize the TUI with the default crossterm backend, but do not enter it yet
     ///
     /// # Errors
     ///
     /// Returns an error if the TUI backend cannot be initialized.
     pub fn init_tui(&mut self) -> Result<()> {
-        let mut tui = Tui::new_with_height(self.height)?;
+        let mut tui = Tui::new_with_height_and_anchor(self.height, self.app.options.anchor)?;
         if self.app.options.no_mouse {
             tui.disable_mouse();
         }
+        self.app.event_tx = Some(tui.event_tx.clone());
         self.tui = Some(tui);
         Ok(())
     }
@@ -158,22 +193,32 @@ where
         // application state
         // Initialize theme from options
         let theme = Arc::new(crate::theme::ColorTheme::init_from_options(&options));
+        // With `--no-input`, read from a closed channel instead of stdin or a command
+        let source = if options.no_input {
+            let (_, rx) = crate::prelude::unbounded();
+            Some(rx)
+        } else {
+            source
+        };
         let mut reader = Reader::from_options(&options).source(source);
         let cmd = options.cmd.clone().unwrap_or_default();
 
-        let app = App::from_options(options, theme.clone(), cmd.clone());
+        let mut app = App::from_options(options, theme.clone(), cmd.clone());
 
         // Give the reader its own dedicated pool (⌈N/3⌉ threads) so it never
         // competes with the matcher's pool (⌊2N/3⌋ threads) for the same
         // worker threads.
         reader.set_thread_pool(Arc::clone(&app.reader_pool));
+        app.source_counts = reader.source_counts();
+        reader.set_stderr_log(app.stderr_pane.log.clone());
+        reader.set_typing_signal(app.typing.clone());
 
         //------------------------------------------------------------------------------
         // reader
         // In interactive mode, expand all placeholders ({}, {q}, etc) with initial query (empty or from --query)
         let initial_cmd = if app.options.interactive && app.options.cmd.is_some() {
             let expanded = app.expand_cmd(&cmd, true);
-            log::debug!("Interactive mode: initial_cmd = {expanded:?} (from template {cmd:?})");
+            debug!("Interactive mode: initial_cmd = {expanded:?} (from template {cmd:?})");
             expanded
         } else {
             cmd.clone()
@@ -189,16 +234,29 @@ where
             matcher_interval: None,
             #[cfg(feature = "listen")]
             listener: None,
+            #[cfg(feature = "watch")]
+            watcher: None,
             final_event: Event::Quit,
             final_key: KeyEvent::new(KeyCode::Enter, KeyModifiers::empty()),
             start_fired: false,
+            pending_reload: None,
+            reload_interval: None,
         })
     }
 
+    /// Adds a source read alongside the others, its items tagged with `label`
+    ///
+    /// Must be called before [`start`](Self::start), see [`Reader::add_source`].
+    pub fn add_source(&mut self, label: impl Into<String>, rx_item: SkimItemReceiver) {
+        self.reader = std::mem::take(&mut self.reader).add_source(label, rx_item);
+    }
+
     /// Start the reader and matcher, but do not enter the TUI yet
     pub fn start(&mut self) {
         debug!("Starting reader with initial_cmd: {:?}", self.initial_cmd);
+        self.reader.set_env(&self.app.state_env());
         self.reader_control = Some(self.reader.collect(self.app.item_pool.clone(), &self.initial_cmd));
+        self.app.timings.read_started();
         self.app.restart_matcher(true);
         // If the TUI is already available (e.g. test harnesses that build the
         // TUI before starting), fire the `start` event now. In the normal
@@ -230,19 +288,24 @@ where
     /// be reused by test harnesses without reimplementing it.
     pub fn handle_reload(&mut self, new_cmd: &str) {
         debug!("reloading with cmd {new_cmd}");
+        // the command sees the state from before the reload
+        self.reader.set_env(&self.app.state_env());
         // Kill the current reader
         if let Some(rc) = self.reader_control.as_mut() {
             rc.kill();
         }
         // Clear items
         self.app.item_pool.clear();
-        // Clear displayed items unless no_clear_if_empty is set
-        if !self.app.options.no_clear_if_empty {
+        // Clear displayed items unless no_clear_if_empty is set, or they are shown until the new
+        // ones arrive
+        if !self.app.options.no_clear_if_empty && !self.app.item_list.keeps_selection() {
             self.app.item_list.clear();
         }
+        self.app.damage.mark_all();
         self.app.restart_matcher(true);
         // Start a new reader with the new command
         self.reader_control = Some(self.reader.collect(self.app.item_pool.clone(), new_cmd));
+        self.app.timings.read_started();
         self.reader_done = false;
         // A new read is in flight: arm the `load` event to fire again once the
         // new item set has been read and rendered.
@@ -250,6 +313,52 @@ where
         self.app.load_event_fired = false;
     }
 
+    /// Schedule a reload after `--reload-delay`, replacing any reload still waiting.
+    ///
+    /// Consecutive reloads (e.g. one per keystroke in interactive mode) are
+    /// debounced: only the last one runs once no other reload was requested for
+    /// the whole delay. With a delay of 0 the reload happens immediately.
+    pub fn schedule_reload(&mut self, new_cmd: &str) {
+        let delay = Duration::from_millis(self.app.options.reload_delay);
+        if delay.is_zero() {
+            self.pending_reload = None;
+            self.handle_reload(new_cmd);
+        } else {
+            trace!("debouncing reload with cmd {new_cmd}");
+            self.pending_reload = Some((new_cmd.to_string(), tokio::time::Instant::now() + delay));
+        }
+    }
+
+    /// Reload for `--reload-interval`, keeping the selection and the cursor
+    ///
+    /// Skipped while the previous command is still running or another reload is waiting.
+    fn reload_periodically(&mut self) {
+        if !self.reader_done() || self.pending_reload.is_some() {
+            trace!("periodic reload skipped, the last one is not done");
+            return;
+        }
+        self.app.item_list.keep_selection();
+        if let Some(tui) = self.tui.as_ref() {
+            let _ = tui.event_tx.try_send(Event::Action(Action::Reload(None)));
+        }
+    }
+
+    /// Run the scheduled reload if its delay has elapsed.
+    ///
+    /// Returns `true` if a reload was started.
+    pub fn flush_pending_reload(&mut self) -> bool {
+        match self.pending_reload.take() {
+            Some((cmd, deadline)) if deadline <= tokio::time::Instant::now() => {
+                self.handle_reload(&cmd);
+                true
+            }
+            pending => {
+                self.pending_reload = pending;
+                false
+            }
+        }
+    }
+
     /// Check if the reader has finished and restart the matcher if needed.
     ///
     /// This encapsulates the reader-status check from the main event loop
@@ -264,6 +373,7 @@ where
             && !self.reader_done
         {
             self.reader_done = true;
+            self.app.timings.read_done(self.app.item_pool.len());
             // Signal that reading is complete. The `load` event is fired later
             // from `App::poll_completion_events` (the heartbeat handler) once the
             // reader is done, the matcher has stopped, and every item has been
@@ -301,6 +411,7 @@ where
     /// Use this instead of [`init_tui()`](Skim::init_tui) when you need a
     /// non-default backend (e.g. `TestBackend` for snapshot tests).
     pub fn init_tui_with(&mut self, tui: Tui<Backend>) {
+        self.app.event_tx = Some(tui.event_tx.clone());
         self.tui = Some(tui);
     }
 
@@ -416,6 +527,8 @@ where
         }
 
         self.init_listener()?;
+        #[cfg(feature = "watch")]
+        self.init_watcher()?;
         self.tui
             .as_mut()
             .expect("TUI needs to be initialized using Skim::init_tui before starting")
@@ -432,38 +545,21 @@ where
     ///
     /// Panics if `start` has not been called before this method.
     pub fn should_enter(&mut self) -> bool {
+        // Filter mode: wait for all items to be read and matched, then return without entering TUI
+        if self.app.options.filter.is_some() {
+            let mut items = Vec::new();
+            self.filter(&mut |matched| items.extend(matched));
+            debug!("filter mode: matched {} items", items.len());
+            self.app.item_list.items = items;
+            return false;
+        }
+
         let reader_control = self
             .reader_control
             .as_ref()
             .expect("reader_control needs to be initialized using Skim::start");
         let app = &mut self.app;
 
-        // Filter mode: wait for all items to be read and matched, then return without entering TUI
-        if app.options.filter.is_some() {
-            trace!("filter mode: waiting for all items to be processed");
-            loop {
-                let matcher_stopped = app.matcher_control.stopped();
-                let reader_done = reader_control.is_done();
-                if matcher_stopped && reader_done && app.item_pool.num_not_taken() == 0 {
-                    break;
-                }
-                std::thread::sleep(Duration::from_millis(1));
-                app.restart_matcher(false);
-            }
-            app.item_list.items = app
-                .item_list
-                .processed_items
-                .lock()
-                .take()
-                .unwrap_or_default()
-                .items
-                .into_iter()
-                .filter(|i| !i.item.disabled())
-                .collect();
-            debug!("filter mode: matched {} items", app.item_list.items.len());
-            return false;
-        }
-
         // Deal with read-0 / select-1
         let min_items_before_enter = if app.options.exit_0 {
             1
@@ -513,6 +609,48 @@ where
         true
     }
 
+    /// Waits for all the items to be read and matched, passing the matches to `on_matches`
+    ///
+    /// The matches of each search are passed as soon as it ends when they are in input order,
+    /// all at once otherwise: sorted by score, reversed by `--tac`, or changed by a `--delta`
+    /// removal.
+    fn filter(&mut self, on_matches: &mut dyn FnMut(Vec<MatchedItem>)) {
+        let reader_control = self
+            .reader_control
+            .as_ref()
+            .expect("reader_control needs to be initialized using Skim::start");
+        let app = &mut self.app;
+        let stream = app.options.no_sort && !app.options.tac && !app.options.delta;
+        let enabled = |items: Vec<MatchedItem>| -> Vec<MatchedItem> {
+            items.into_iter().filter(|i| !i.item.disabled()).collect()
+        };
+        trace!("filter mode: waiting for all items to be processed, streaming: {stream}");
+        loop {
+            // a stopped search has published its matches
+            let finished =
+                app.matcher_control.stopped() && reader_control.is_done() && app.item_pool.num_not_taken() == 0;
+            if stream && let Some(processed) = app.item_list.processed_items.lock().take() {
+                let items = enabled(processed.items);
+                if !items.is_empty() {
+                    on_matches(items);
+                }
+            }
+            if finished {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+            app.restart_matcher(false);
+        }
+        if !stream {
+            let mut items = enabled(app.item_list.processed_items.lock().take().unwrap_or_default().items);
+            if app.options.no_sort && app.options.tac {
+                // each batch was matched last first, the whole input has to be
+                items.sort_by_key(|item| std::cmp::Reverse(item.rank.index));
+            }
+            on_matches(items);
+        }
+    }
+
     /// Initialize the IPC socket listener
     /// This needs to be called from an async context despite being sync
     #[cfg_attr(not(feature = "listen"), allow(clippy::unnecessary_wraps, clippy::unused_self))]
@@ -530,6 +668,28 @@ where
         Ok(())
     }
 
+    /// Start watching the `--watch` paths, sending [`Event::Watched`] when they change
+    #[cfg(feature = "watch")]
+    fn init_watcher(&mut self) -> Result<()> {
+        use notify::Watcher;
+
+        if self.app.options.watch.is_empty() {
+            return Ok(());
+        }
+        let (tx_change, rx_change) = std::sync::mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx_change)?;
+        for path in &self.app.options.watch {
+            watcher
+                .watch(path, notify::RecursiveMode::Recursive)
+                .map_err(|e| eyre::eyre!("could not watch {}: {e}", path.display()))?;
+        }
+        let event_tx = self.event_sender();
+        std::thread::spawn(move || forward_changes(&rx_change, &event_tx));
+        self.watcher = Some(watcher);
+        self.app.watch_reloads = Some(0);
+        Ok(())
+    }
+
     /// Capture `self` and extract the output
     /// This will perform cleanup
     ///
@@ -556,6 +716,7 @@ where
         let query = self.app.input.to_string();
         let current = self.app.item_list.selected();
         let header = self.app.header.header.clone();
+        let suggested = !is_abort && self.app.item_list.suggestions;
         let final_event = self.final_event.clone();
         let final_key = self.final_key;
 
@@ -570,6 +731,7 @@ where
             selected_items,
             current,
             header,
+            suggested,
         }
     }
 
@@ -580,12 +742,10 @@ where
 
     /// If `needs_render` has been set (e.g. by the matcher thread), immediately
     /// send a `Render` event to the TUI so the screen updates without waiting
-    /// for the next heartbeat tick.  Respects the 30 FPS frame-rate cap.
+    /// for the next heartbeat tick.  Respects the `--max-fps` frame-rate cap.
     fn try_flush_render(&mut self) {
         use std::sync::atomic::Ordering;
-        if self.app.needs_render.load(Ordering::Relaxed)
-            && self.app.last_render_timer.elapsed().as_millis() > 1000 / u128::from(TICK_RATE)
-        {
+        if self.app.needs_render.load(Ordering::Relaxed) && self.app.frame_due() {
             self.app.needs_render.store(false, Ordering::Relaxed);
             self.app.last_render_timer = std::time::Instant::now();
             if let Some(tui) = self.tui.as_ref() {
@@ -625,6 +785,8 @@ where
         self.fire_start_event();
         let matcher_interval = &mut self.matcher_interval;
         let items_available = self.app.item_pool.items_available.clone();
+        let reload_deadline = self.pending_reload.as_ref().map(|(_, deadline)| *deadline);
+        let reload_interval = &mut self.reload_interval;
         select! {
             event = self.tui.as_mut().expect("TUI should be initialized before the event loop can start").next() => {
                 let evt = event.ok_or_eyre("Could not acquire next event")?;
@@ -638,7 +800,7 @@ where
 
                 // Handle reload event separately
                 if let Event::Reload(new_cmd) = &evt {
-                    self.handle_reload(&new_cmd.clone());
+                    self.schedule_reload(&new_cmd.clone());
                 } else {
                     self.app.handle_event(self.tui.as_mut().expect("TUI should be initialized before handling events"), &evt)?;
                 }
@@ -682,6 +844,24 @@ where
                   self.matcher_interval = None;
               }
             }
+            // Start the debounced reload once no other reload was requested for
+            // `--reload-delay`.
+            () = async {
+                match reload_deadline {
+                    Some(deadline) => tokio::time::sleep_until(deadline).await,
+                    None => std::future::pending::<()>().await,
+                }
+            } => {
+                self.flush_pending_reload();
+            }
+            () = async {
+                match reload_interval {
+                    Some(interval) => { interval.tick().await; },
+                    None => std::future::pending::<()>().await,
+                }
+            } => {
+                self.reload_periodically();
+            }
             // Wake immediately when new items arrive in the pool so the matcher
             // can pick them up without waiting for the next periodic interval.
             () = items_available.notified() => {
@@ -737,6 +917,17 @@ where
     /// Returns an error if any tick in the event loop fails.
     pub async fn run(&mut self) -> Result<()> {
         self.matcher_interval = Some(tokio::time::interval(Duration::from_millis(10)));
+        self.reload_interval = self
+            .app
+            .options
+            .reload_interval
+            .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+            .filter(|period| !period.is_zero())
+            .map(|period| {
+                let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+                interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                interval
+            });
         trace!("Starting event loop");
         loop {
             if self.tick().await? {
@@ -745,12 +936,54 @@ where
         }
     }
 
+    /// Stream of the matched items, for frontends that do not use the TUI
+    ///
+    /// Starts the reader and the matcher if [`start()`](Self::start) was not called yet, then yields
+    /// every matched item, in the order the TUI would show them, each time the matcher publishes new
+    /// results. The stream ends once all the items are read and matched. To search again, change the
+    /// query through [`app_mut()`](Self::app_mut), call [`App::restart_matcher`] and take a new stream.
+    ///
+    /// ```ignore
+    /// let mut skim = Skim::init(options, Some(rx))?;
+    /// let mut matches = std::pin::pin!(skim.matched_items());
+    /// while let Some(items) = matches.next().await {
+    ///     println!("{} matches", items.len());
+    /// }
+    /// ```
+    pub fn matched_items(&mut self) -> impl futures::Stream<Item = Vec<MatchedItem>> + '_ {
+        if self.reader_control.is_none() {
+            self.start();
+        }
+        // (skim, whether the last snapshot was sent, whether any snapshot was sent)
+        futures::stream::unfold((self, false, false), |(skim, done, sent)| async move {
+            if done {
+                return None;
+            }
+            loop {
+                skim.check_reader();
+                skim.app.restart_matcher(false);
+                let finished = skim.reader_done() && skim.matcher_stopped() && skim.app.item_pool.num_not_taken() == 0;
+                // Always send a final snapshot, even if nothing matched
+                if skim.app.item_list.merge_processed() || (finished && !sent) {
+                    let items = skim.app.item_list.items.clone();
+                    return Some((items, (skim, finished, true)));
+                }
+                if finished {
+                    return None;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+    }
+
     /// Spawn the event loop and run a user-provided future concurrently.
     ///
     /// This consumes `self`, spawns the event loop as a local task, and runs
     /// `user_task` alongside it. When the user accepts or aborts in the TUI,
-    /// the event loop completes and the [`SkimOutput`] is returned — regardless
-    /// of whether `user_task` has finished.
+    /// the event loop completes and the [`SkimOutput`] is returned. If
+    /// `user_task` has not finished by then, it is cancelled: it would only be
+    /// pushing items nobody reads anymore. Use [`run_alongside`](Self::run_alongside)
+    /// to keep it running instead.
     ///
     /// Use this when you need to send items or do other work concurrently
     /// while the TUI is running.
@@ -777,11 +1010,74 @@ where
                     self.run().await?;
                     Ok(self.output())
                 });
-                tokio::task::spawn_local(user_task);
-                handle.await?
+                let user_handle = tokio::task::spawn_local(user_task);
+                let output = handle.await;
+                user_handle.abort();
+                output?
             })
             .await
     }
+
+    /// Run the event loop while `user_task` runs on the tokio runtime, and hand the task back
+    ///
+    /// Unlike [`run_until`](Self::run_until), the task is not cancelled when the user accepts or
+    /// aborts: the returned [`JoinHandle`](tokio::task::JoinHandle) can be awaited for its result, or
+    /// aborted. The task is only aborted if the event loop fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the event loop fails.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let (output, producer) = skim.run_alongside(async move {
+    ///     let mut sent = 0;
+    ///     while tx.send(next_batch().await).is_ok() {
+    ///         sent += 1;
+    ///     }
+    ///     sent
+    /// }).await?;
+    /// let sent = producer.await?;
+    /// ```
+    pub async fn run_alongside<F>(mut self, user_task: F) -> Result<(SkimOutput, tokio::task::JoinHandle<F::Output>)>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let user_handle = tokio::spawn(user_task);
+        if let Err(e) = self.run().await {
+            user_handle.abort();
+            return Err(e);
+        }
+        Ok((self.output(), user_handle))
+    }
+}
+
+/// Time without changes to the watched paths before reloading, so that saving many files reloads once
+#[cfg(feature = "watch")]
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Sends an [`Event::Watched`] after each burst of changes to the watched paths
+///
+/// Reads do not count as changes, since the reloaded command may read the watched files itself.
+#[cfg(feature = "watch")]
+fn forward_changes(
+    rx_change: &std::sync::mpsc::Receiver<notify::Result<notify::Event>>,
+    event_tx: &tokio::sync::mpsc::Sender<Event>,
+) {
+    let is_change =
+        |change: &notify::Result<notify::Event>| change.as_ref().is_ok_and(|change| !change.kind.is_access());
+    while let Ok(change) = rx_change.recv() {
+        if !is_change(&change) {
+            continue;
+        }
+        // wait for the changes to stop
+        while rx_change.recv_timeout(WATCH_DEBOUNCE).is_ok() {}
+        if event_tx.blocking_send(Event::Watched).is_err() {
+            break;
+        }
+    }
 }
 
 #[cfg(test)]
@@ -4,6 +4,15 @@ use std::fmt::{Debug, Display};
 
 use crate::{AsAny, DisplayContext, ItemPreview, PreviewContext};
 
+/// Where an item was read in the file it comes from, see [`SkimItem::source_position`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourcePosition {
+    /// The 1-based number of the line, for `{ln}`
+    pub line: usize,
+    /// The byte offset of the start of the line, for `{off}`
+    pub offset: usize,
+}
+
 /// A `SkimItem` defines what's been processed(fetched, matched, previewed and returned) by skim
 ///
 /// # Downcast Example
@@ -68,6 +77,17 @@ pub trait SkimItem: AsAny + Send + Sync + 'static {
         self.text()
     }
 
+    /// Get the bytes printed by `sk` on accept, default to `output()`
+    ///
+    /// Differs from `output()` for the items read from lines that are not valid UTF-8, printed as
+    /// they were read (`--encoding lossy|latin1`).
+    fn output_bytes(&self) -> Cow<'_, [u8]> {
+        match self.output() {
+            Cow::Borrowed(output) => Cow::Borrowed(output.as_bytes()),
+            Cow::Owned(output) => Cow::Owned(output.into_bytes()),
+        }
+    }
+
     /// Limit the matching ranges of the `get_text` of the item.
     /// providing (`start_byte`, `end_byte`) of the range
     fn get_matching_ranges(&self) -> Option<&[(usize, usize)]> {
@@ -85,6 +105,45 @@ pub trait SkimItem: AsAny + Send + Sync + 'static {
         None
     }
 
+    /// Byte ranges of `text()` matched by the source of the item, e.g. `rg`.
+    ///
+    /// When present, these ranges are highlighted instead of the ones found by the
+    /// query, and the span they cover is used for the `begin` and `end` tiebreaks.
+    /// The query still decides whether the item matches. Ranges are expressed as
+    /// (`start_byte`, `end_byte`); ranges out of bounds or not on char boundaries
+    /// are ignored.
+    fn external_ranges(&self) -> Option<&[(usize, usize)]> {
+        None
+    }
+
+    /// Text shown dimmed at the right edge of the item, never matched (via `--annotation-sep`)
+    fn annotation(&self) -> Option<&str> {
+        None
+    }
+
+    /// Name of the group the item is listed under, e.g. its directory or repository
+    ///
+    /// Matched items of the same group are shown together under a header naming the group,
+    /// which the `toggle-group` action collapses and expands. Items without a group get no
+    /// header. `--group-by-field` sets it for the items read by skim.
+    fn group(&self) -> Option<Cow<'_, str>> {
+        None
+    }
+
+    /// Label of the source the item was read from, see [`Reader::add_source`](crate::reader::Reader::add_source)
+    ///
+    /// Matched by the `source:` terms of the query.
+    fn source(&self) -> Option<Cow<'_, str>> {
+        None
+    }
+
+    /// Position of the item in the file it was read from, expanded by `{ln}` and `{off}`
+    ///
+    /// Set for the items read with `--input-file`, where the lines keep their place in the file.
+    fn source_position(&self) -> Option<SourcePosition> {
+        None
+    }
+
     /// Returns true if the item should be disabled
     /// Disabled items cannot be selected
     fn disabled(&self) -> bool {
@@ -125,6 +184,7 @@ mod tests {
         // `output` defaults to `text`.
         assert_eq!(item.output(), "hello");
         assert!(item.get_matching_ranges().is_none());
+        assert!(item.external_ranges().is_none());
         assert!(!item.disabled());
     }
 
@@ -122,6 +122,53 @@ fn nested_accept_actions_are_reported_as_accepts() {
     }
 }
 
+/// Sets its flag when dropped, to tell whether a task was cancelled
+struct DropFlag(Arc<std::sync::atomic::AtomicBool>);
+
+impl Drop for DropFlag {
+    fn drop(&mut self) {
+        self.0.store(true, std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn run_until_cancels_the_user_task_on_accept() {
+    let mut options = SkimOptions::default();
+    options.bind = vec!["start:accept".to_string()];
+    let skim = started_skim_with(options.build(), &["a"]);
+    let dropped = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let flag = DropFlag(dropped.clone());
+
+    let output = tokio::runtime::Runtime::new()
+        .unwrap()
+        .block_on(skim.run_until(async move {
+            let _flag = flag;
+            std::future::pending::<()>().await;
+        }))
+        .unwrap();
+
+    assert!(!output.is_abort);
+    assert!(dropped.load(std::sync::atomic::Ordering::SeqCst));
+}
+
+#[test]
+fn run_alongside_hands_back_the_user_task() {
+    let mut options = SkimOptions::default();
+    options.bind = vec!["start:accept".to_string()];
+    let skim = started_skim_with(options.build(), &["a"]);
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let (output, task) = rt
+        .block_on(skim.run_alongside(async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            42
+        }))
+        .unwrap();
+
+    assert!(!output.is_abort);
+    assert_eq!(rt.block_on(task).unwrap(), 42);
+}
+
 #[test]
 fn output_uses_input_as_cmd_in_interactive_mode() {
     let mut options = SkimOptions::default();
@@ -227,3 +274,123 @@ fn try_flush_render_noop_when_not_needed() {
     skim.try_flush_render();
     assert!(!skim.app.needs_render.load(Ordering::Relaxed));
 }
+
+#[test]
+fn schedule_reload_debounces_until_delay_elapses() {
+    let mut options = SkimOptions::default().build();
+    options.reload_delay = 50;
+    let mut skim = started_skim_with(options, &["a"]);
+    wait_until(|| skim.check_reader());
+
+    skim.schedule_reload("echo one");
+    skim.schedule_reload("echo two");
+    // Only the last reload is kept, and nothing runs before the delay.
+    assert_eq!(skim.pending_reload.as_ref().unwrap().0, "echo two");
+    assert!(!skim.flush_pending_reload());
+    assert!(skim.reader_done());
+
+    std::thread::sleep(Duration::from_millis(60));
+    assert!(skim.flush_pending_reload());
+    assert!(skim.pending_reload.is_none());
+    assert!(!skim.reader_done());
+}
+
+#[test]
+fn periodic_reload_keeps_the_items_until_the_new_ones_arrive() {
+    let mut skim = started_skim(&["a", "b"]);
+    wait_until(|| skim.check_reader());
+    wait_until(|| {
+        skim.app.item_list.merge_processed();
+        skim.app.item_list.items.len() == 2
+    });
+
+    // a reload still waiting skips this tick
+    skim.pending_reload = Some((
+        "echo later".to_string(),
+        tokio::time::Instant::now() + Duration::from_secs(60),
+    ));
+    skim.reload_periodically();
+    assert!(!skim.app.item_list.keeps_selection());
+    skim.pending_reload = None;
+
+    skim.reload_periodically();
+    assert!(skim.app.item_list.keeps_selection());
+    let mut reloads = 0;
+    while let Ok(ev) = skim.tui.as_mut().unwrap().event_rx.try_recv() {
+        if matches!(ev, Event::Action(Action::Reload(None))) {
+            reloads += 1;
+        }
+    }
+    assert_eq!(reloads, 1);
+
+    skim.handle_reload("echo c");
+    assert_eq!(skim.app.item_list.items.len(), 2);
+}
+
+#[test]
+fn schedule_reload_without_delay_is_immediate() {
+    let mut options = SkimOptions::default().build();
+    options.reload_delay = 0;
+    let mut skim = started_skim_with(options, &["a"]);
+    wait_until(|| skim.check_reader());
+
+    skim.schedule_reload("echo one");
+    assert!(skim.pending_reload.is_none());
+    assert!(!skim.reader_done());
+}
+
+#[test]
+fn no_input_ignores_the_source() {
+    let mut options = SkimOptions::default().build();
+    options.no_input = true;
+    let mut skim = started_skim_with(options, &["a", "b"]);
+    wait_until(|| skim.check_reader());
+    assert!(skim.reader_done());
+    assert_eq!(skim.app().item_pool.len(), 0);
+}
+
+#[test]
+fn matched_items_streams_the_matches_without_a_tui() {
+    use futures::StreamExt as _;
+    let (tx, rx) = crate::prelude::unbounded();
+    let batch: Vec<Arc<dyn SkimItem>> = ["apple", "banana", "cherry"]
+        .iter()
+        .map(|s| Arc::new(s.to_string()) as Arc<dyn SkimItem>)
+        .collect();
+    tx.send(batch).unwrap();
+    drop(tx);
+
+    let options = crate::prelude::SkimOptionsBuilder::default()
+        .query("an")
+        .build()
+        .unwrap();
+    let mut skim = Skim::<TestBackend>::init(options, Some(rx)).unwrap();
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let snapshots: Vec<Vec<MatchedItem>> = rt.block_on(skim.matched_items().collect());
+    let last = snapshots.last().expect("at least one snapshot");
+    assert_eq!(last.len(), 1);
+    assert_eq!(last[0].item.text(), "banana");
+}
+
+#[cfg(feature = "watch")]
+#[test]
+fn watched_changes_are_debounced() {
+    use notify::event::{AccessKind, EventKind, ModifyKind};
+
+    let (tx_change, rx_change) = std::sync::mpsc::channel();
+    let (event_tx, mut event_rx) = tokio::sync::mpsc::channel(8);
+    // reads are not changes
+    tx_change
+        .send(Ok(notify::Event::new(EventKind::Access(AccessKind::Any))))
+        .unwrap();
+    for _ in 0..3 {
+        tx_change
+            .send(Ok(notify::Event::new(EventKind::Modify(ModifyKind::Any))))
+            .unwrap();
+    }
+    drop(tx_change);
+    forward_changes(&rx_change, &event_tx);
+
+    assert!(matches!(event_rx.try_recv(), Ok(Event::Watched)));
+    assert!(event_rx.try_recv().is_err());
+}
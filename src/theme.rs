@@ -34,14 +34,18 @@ pub struct ColorTheme {
     pub prompt: Style,
     /// Cursor/Selector/pointer (prefix of current item)
     pub cursor: Style,
-    /// Multi-selector/marker (prefix of selected items)
+    /// Multi-selector/marker (prefix of selected items), and the selection count
     pub selected: Style,
+    /// Selector and marker columns of the item list (the gutter), under their icons
+    pub gutter: Style,
     /// Header lines
     pub header: Style,
     /// Border
     pub border: Style,
     /// Scrollbar thumb on the item list
     pub scrollbar: Style,
+    /// Border labels (`--border-label`, `--preview-label`, ...)
+    pub label: Style,
 }
 
 impl Default for ColorTheme {
@@ -81,9 +85,11 @@ impl ColorTheme {
             prompt: def,
             cursor: def,
             selected: def,
+            gutter: def,
             header: def,
             border: def,
             scrollbar: def,
+            label: def,
         }
     }
 
@@ -192,9 +198,11 @@ impl ColorTheme {
             prompt: base.prompt.fg(lavender),
             cursor: base.cursor.fg(red),
             selected: base.selected.fg(red),
+            gutter: base.gutter,
             header: base.header.fg(subtext1),
             border: base.header.fg(lavender),
             scrollbar: base.scrollbar.fg(overlay0),
+            label: base.label.fg(text),
         }
     }
     #[allow(unused_variables)]
@@ -220,9 +228,11 @@ impl ColorTheme {
             prompt: base.prompt.fg(lavender),
             cursor: base.cursor.fg(red),
             selected: base.selected.fg(red),
+            gutter: base.gutter,
             header: base.header.fg(subtext1),
             border: base.header.fg(lavender),
             scrollbar: base.scrollbar.fg(overlay0),
+            label: base.label.fg(text),
         }
     }
     #[allow(unused_variables)]
@@ -248,9 +258,11 @@ impl ColorTheme {
             prompt: base.prompt.fg(lavender),
             cursor: base.cursor.fg(red),
             selected: base.selected.fg(red),
+            gutter: base.gutter,
             header: base.header.fg(subtext1),
             border: base.header.fg(lavender),
             scrollbar: base.scrollbar.fg(overlay0),
+            label: base.label.fg(text),
         }
     }
     #[allow(unused_variables)]
@@ -276,9 +288,11 @@ impl ColorTheme {
             prompt: base.prompt.fg(lavender),
             cursor: base.cursor.fg(red),
             selected: base.selected.fg(red),
+            gutter: base.gutter,
             header: base.header.fg(subtext1),
             border: base.header.fg(lavender),
             scrollbar: base.scrollbar.fg(overlay0),
+            label: base.label.fg(text),
         }
     }
 
@@ -331,9 +345,11 @@ impl ColorTheme {
             "prompt" => &mut self.prompt,
             "cursor" | "pointer" => &mut self.cursor,
             "selected" | "marker" => &mut self.selected,
+            "gutter" => &mut self.gutter,
             "header" => &mut self.header,
             "border" => &mut self.border,
             "scrollbar" => &mut self.scrollbar,
+            "label" => &mut self.label,
             _ => return,
         };
 
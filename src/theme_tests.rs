@@ -175,6 +175,14 @@ fn test_component_name_aliases() {
     assert_eq!(theme.selected.fg, Some(Color::Indexed(168)));
 }
 
+#[test]
+fn test_gutter_color() {
+    let theme = ColorTheme::from_options("gutter-bg:236,marker:200");
+    assert_eq!(theme.gutter.bg, Some(Color::Indexed(236)));
+    assert_eq!(theme.selected.fg, Some(Color::Indexed(200)));
+    assert_eq!(ColorTheme::dark256().gutter, Style::default());
+}
+
 #[test]
 fn test_background_color() {
     // Test setting background color explicitly
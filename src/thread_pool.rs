@@ -444,7 +444,7 @@ pub fn parallel_work_queue<S, T, R, P, M, I, W, G>(
 
         merge(results);
     } else {
-        log::error!("More than one ref to the slots remaining after workers exit. This SHOULD NOT happen.");
+        error!("More than one ref to the slots remaining after workers exit. This SHOULD NOT happen.");
     }
 }
 
@@ -1,38 +1,46 @@
+use std::path::PathBuf;
 use std::process::Stdio;
 use std::rc::Rc;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 
+use crate::field::FieldRange;
+use crate::interpreter::Interpreter;
 use crate::item::{ItemPool, MatchedItem};
 use crate::matcher::{Matcher, MatcherControl};
+use crate::placeholder::PlaceholderError;
 use crate::prelude::ExactOrFuzzyEngineFactory;
+use crate::reader::{SourceCounts, TypingSignal};
+use crate::tui::SkimRender;
 use crate::tui::input::StatusInfo;
 use crate::tui::layout::{AppLayout, LayoutTemplate};
-use crate::tui::options::TuiLayout;
+use crate::tui::options::{LabelPosition, TuiLayout};
 use crate::tui::statusline::InfoDisplay;
 use crate::tui::widget::SkimWidget;
-use crate::tui::{SkimRender, TICK_RATE};
-use crate::{ItemPreview, PreviewContext, Rank, SkimItem, SkimOptions, util};
+use crate::{ItemPreview, PreviewContext, Rank, SkimItem, SkimOptions, placeholder, util};
 
 #[cfg(test)]
 #[path = "app_tests.rs"]
 mod tests;
 
-use super::event::Action;
+use super::damage::{self, Damage};
+use super::event::{Action, AppHandle};
 use super::header::Header;
 use super::item_list::ItemList;
-use super::{Event, Tui, input, preview};
+use super::stderr_pane::StderrPane;
+use super::timings::Timings;
+use super::{Event, Tui, file_preview, input, preview};
 use crate::binds::SkimEvent;
 use crate::thread_pool::{self, ThreadPool};
-use crossterm::event::{KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use eyre::{Result, bail};
 use input::Input;
-use preview::Preview;
+use preview::{Preview, PreviewJob};
 use ratatui::buffer::Buffer;
 use ratatui::crossterm::event::KeyCode::Char;
 use ratatui::layout::Rect;
 use ratatui::prelude::Backend;
-use ratatui::widgets::Widget;
+use ratatui::widgets::{Block, Widget};
 use std::sync::LazyLock;
 
 static NUM_THREADS: LazyLock<usize> = LazyLock::new(|| {
@@ -44,6 +52,33 @@ static NUM_THREADS: LazyLock<usize> = LazyLock::new(|| {
 const MATCHER_DEBOUNCE_MS: u128 = 200;
 const HIDE_GRACE_MS: u128 = 500;
 const DOUBLE_CLICK_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+/// Matched items whose words `tab-complete` looks at, best first
+const COMPLETION_ITEMS: usize = 1000;
+/// Candidates listed by `tab-complete` when it cannot complete further
+const COMPLETIONS_SHOWN: usize = 8;
+
+/// State of an ongoing `history-search`
+#[derive(Default)]
+struct HistorySearchState {
+    /// Substring searched for in the history
+    pattern: String,
+    /// Index of the history entry currently shown in the input
+    index: Option<usize>,
+    /// Whether the last search found nothing
+    failed: bool,
+    /// Input restored when the search is cancelled
+    saved_input: String,
+    /// Prompt restored when the search ends
+    saved_prompt: String,
+}
+
+/// The finder run by `pick(...)`: this executable when it is `sk`, otherwise `sk` from `$PATH`
+fn nested_finder() -> std::process::Command {
+    let exe = std::env::current_exe()
+        .ok()
+        .filter(|exe| exe.file_stem().is_some_and(|stem| stem == "sk"));
+    std::process::Command::new(exe.unwrap_or_else(|| PathBuf::from("sk")))
+}
 
 /// Application state for skim's TUI
 #[allow(clippy::struct_excessive_bools)]
@@ -54,6 +89,14 @@ pub struct App {
     pub matcher_pool: Arc<ThreadPool>,
     /// Thread pool used by the reader pipeline (⌈N/3⌉ threads).
     pub reader_pool: Arc<ThreadPool>,
+    /// Item counts of the labeled sources of the reader, shown in the status line
+    pub source_counts: SourceCounts,
+    /// Sender of the events of the TUI, handed to the plugin actions once there is one
+    pub(crate) event_tx: Option<tokio::sync::mpsc::Sender<Event>>,
+    /// Edits of the query, deferring the parsing of the input read meanwhile (`--defer-ingest`)
+    pub typing: TypingSignal,
+    /// Number of reloads caused by the changes to the `--watch` paths, when watching
+    pub watch_reloads: Option<usize>,
     /// Whether the application should quit
     pub should_quit: bool,
     /// The terminating action, including one dispatched inside a follow-up or conditional chain.
@@ -65,8 +108,8 @@ pub struct App {
     pub matcher_control: MatcherControl,
     /// The matcher for filtering items
     pub matcher: Matcher,
-    /// Register for yank/paste operations
-    pub yank_register: String,
+    /// Index of the field set matched in `nth_sets()`, see `toggle-nth`
+    nth_index: usize,
     /// Last time the matcher was restarted
     pub last_matcher_restart: std::time::Instant,
     /// Whether a matcher restart is pending
@@ -78,8 +121,16 @@ pub struct App {
 
     /// Input field widget
     pub input: Input,
+    /// Secondary query, which items have to match as well as the query, see `--query2`
+    pub(crate) query2: Input,
+    /// Whether the secondary query has the cursor, see `toggle-query2`
+    query2_focused: bool,
+    /// Whether `input` holds the secondary query, swapped in by `dispatch_focused` to edit it
+    editing_query2: bool,
     /// Preview pane widget
     pub preview: Preview,
+    /// Pane showing the stderr of the commands, see `toggle-stderr`
+    pub(crate) stderr_pane: StderrPane,
     /// Header widget
     pub header: Header,
     /// Item list widget
@@ -146,6 +197,28 @@ pub struct App {
     /// The last item that had focus, tracked so the `focus` event fires only
     /// when the focused item actually changes on cursor movement.
     last_focused: Option<Arc<dyn SkimItem>>,
+    /// Command set by the `preview(...)` action, used instead of the preview
+    /// command until the focused item changes or the preview is refreshed.
+    preview_override: Option<String>,
+    /// The tmux pane of `--preview-pane`, opened on the first preview
+    #[cfg(unix)]
+    preview_pane: Option<crate::popup::TmuxPreviewPane>,
+    /// Reverse-i-search over the query (or command) history, while active
+    history_search: Option<HistorySearchState>,
+    /// Whether the running `jump` accepts the labeled item, see `ItemList::jumping`
+    jump_accept: bool,
+    /// Shell running the `execute` commands, see `--with-shell`
+    shell: Interpreter,
+    /// Placeholder error or dry-run command, shown in the status line until the next key
+    pub(crate) status_message: Option<String>,
+    /// Items longer than `--match-window` counted by the previous matcher runs of this search
+    windowed_before: usize,
+    /// Timings of the reader, the matcher and the renders, see `--debug-overlay`
+    pub(crate) timings: Timings,
+    /// Widgets to render again in the next frame, the others are kept from `last_frame`
+    pub(crate) damage: Damage,
+    /// The last frame drawn
+    last_frame: Buffer,
 }
 
 impl Widget for &mut App {
@@ -164,32 +237,52 @@ impl Widget for &mut App {
             self.last_header_height = current_header_height;
             self.layout_template = LayoutTemplate::from_options(&self.options, current_header_height);
         }
-        self.layout = self.layout_template.apply(area);
+        let last_layout = std::mem::replace(&mut self.layout, self.layout_template.apply(area));
+        if self.query2_focused || !self.query2.is_empty() {
+            self.layout.split_query2(self.options.layout == TuiLayout::Reverse);
+        }
+        if self.stderr_pane.visible {
+            let rows = self.stderr_pane.height(self.layout.list_area.height);
+            self.layout
+                .split_stderr(rows, self.options.layout != TuiLayout::Reverse);
+        }
+        if self.layout != last_layout {
+            self.damage.mark_all();
+        }
+        if self.item_list.processed_items.lock().is_some() {
+            self.damage.list = true;
+        }
 
         if let Some(header_area) = self.layout.header_area {
             res |= self.header.render(header_area, buf);
         }
 
-        if let Some(preview_area) = self.layout.preview_area {
+        if let Some(preview_area) = self.layout.preview_area
+            && (self.damage.preview || !damage::keep(preview_area, &self.last_frame, buf))
+        {
             res |= self.preview.render(preview_area, buf);
         }
 
-        res |= self.item_list.render(self.layout.list_area, buf);
+        if self.damage.list || !damage::keep(self.layout.list_area, &self.last_frame, buf) {
+            res |= self.item_list.render(self.layout.list_area, buf);
+        }
+        if let Some(stderr_area) = self.layout.stderr_area {
+            self.stderr_pane.render(stderr_area, buf);
+        }
 
         // Render the input after the item list so that the status shows correct information.
-        self.input.status_info = if self.options.info.display == InfoDisplay::Hidden {
+        let nothing_read = self.options.no_input && self.item_pool.is_empty();
+        self.input.status_info = if self.options.info.display == InfoDisplay::Hidden || nothing_read {
             None
         } else {
+            // the removed items keep their place in the pool, see `--delta`
+            let total = self.item_pool.len() - self.item_pool.num_removed();
             Some(StatusInfo {
-                total: self.item_pool.len(),
+                total,
                 matched: self.item_list.count(),
-                processed: self.matcher_control.get_num_processed(),
+                processed: self.matcher_control.get_num_processed().min(total),
                 show_spinner: self.show_spinner,
-                matcher_mode: if self.options.regex {
-                    "RE".to_string()
-                } else {
-                    String::new()
-                },
+                matcher_mode: if self.options.regex { "RE" } else { "" }.to_string(),
                 multi_selection: self.options.multi,
                 selected: self.item_list.selection.len(),
                 current_item_idx: self.item_list.current,
@@ -201,16 +294,44 @@ impl Widget for &mut App {
                     .separator()
                     .unwrap_or(super::statusline::DEFAULT_SEPARATOR)
                     .to_string(),
+                windowed: self.windowed_before + self.matcher_control.get_num_windowed(),
+                duplicates: self.item_pool.num_duplicates(),
+                long_items: self.num_long_items(),
+                nth: (self.nth_index != 0).then(|| match &self.nth_sets()[self.nth_index] {
+                    set if set.is_empty() => "..".to_string(),
+                    set => set.clone(),
+                }),
+                sources: self.source_counts.lock().clone(),
+                watch_reloads: self.watch_reloads,
+                message: self
+                    .status_message
+                    .clone()
+                    .or_else(|| self.item_list.suggestions.then(|| "did you mean?".to_string())),
             })
         };
         res |= self.input.render(self.layout.input_area, buf);
+        if let Some(query2_area) = self.layout.query2_area {
+            self.query2.render(query2_area, buf);
+        }
+        if has_border {
+            self.render_border_label(buf);
+        }
+        // kept before the overlay, which is drawn again in every frame
+        self.last_frame.clone_from(buf);
+        self.damage = Damage::NONE;
+        if self.options.debug_overlay {
+            self.timings.render_overlay(area, buf, &self.theme);
+        }
 
         // Cursor position needs to account for input border and title.
-        self.cursor_pos = (
-            self.layout.input_area.x + self.input.cursor_pos() + u16::from(has_border),
-            self.layout.input_area.y
-                + u16::from(!(self.options.layout == TuiLayout::Reverse && self.options.border.is_none())),
-        );
+        self.cursor_pos = match self.layout.query2_area {
+            Some(query2_area) if self.query2_focused => (query2_area.x + self.query2.cursor_pos(), query2_area.y),
+            _ => (
+                self.layout.input_area.x + self.input.cursor_pos() + u16::from(has_border),
+                self.layout.input_area.y
+                    + u16::from(!(self.options.layout == TuiLayout::Reverse && self.options.border.is_none())),
+            ),
+        };
         if res.run_preview {
             self.pending_preview_run = true;
         }
@@ -226,13 +347,25 @@ impl Default for App {
         let layout_template = LayoutTemplate::from_options(&opts, initial_header_height);
         let layout = layout_template.apply(Rect::default());
         let (reader_threads, matcher_threads) = thread_pool::partition_threads(*NUM_THREADS);
+        let shell = Interpreter::from_options(&opts);
+        let preview = Preview::from_options(&opts, theme.clone());
+        let mut stderr_pane = StderrPane::from_options(&opts, theme.clone());
+        stderr_pane.log = preview.stderr_log.clone();
         Self {
             input: Input::from_options(&opts, theme.clone()),
-            preview: Preview::from_options(&opts, theme.clone()),
+            query2: Input::secondary(&opts, theme.clone()),
+            query2_focused: false,
+            editing_query2: false,
+            preview,
+            stderr_pane,
             header,
             item_list: ItemList::from_options(&opts, theme.clone()),
             matcher_pool: Arc::new(ThreadPool::new(matcher_threads)),
             reader_pool: Arc::new(ThreadPool::new(reader_threads)),
+            source_counts: SourceCounts::default(),
+            event_tx: None,
+            typing: TypingSignal::default(),
+            watch_reloads: None,
             item_pool: Arc::default(),
             theme,
             should_quit: false,
@@ -241,7 +374,7 @@ impl Default for App {
             matcher: Matcher::builder(Rc::new(ExactOrFuzzyEngineFactory::builder().build()))
                 .case(crate::CaseMatching::default())
                 .build(),
-            yank_register: String::new(),
+            nth_index: 0,
             matcher_control: MatcherControl::default(),
             matcher_timer: std::time::Instant::now(),
             last_matcher_restart: std::time::Instant::now(),
@@ -277,6 +410,17 @@ impl Default for App {
             load_event_fired: false,
             result_pending: false,
             last_focused: None,
+            preview_override: None,
+            #[cfg(unix)]
+            preview_pane: None,
+            history_search: None,
+            jump_accept: false,
+            shell,
+            status_message: None,
+            windowed_before: 0,
+            timings: Timings::default(),
+            damage: Damage::default(),
+            last_frame: Buffer::default(),
         }
     }
 }
@@ -300,12 +444,24 @@ impl App {
         if options.flags.contains(&crate::options::FeatureFlag::SingleMatcher) {
             matcher_threads = 1;
         }
+        let shell = Interpreter::from_options(&options);
+        let preview = Preview::from_options(&options, theme.clone());
+        let mut stderr_pane = StderrPane::from_options(&options, theme.clone());
+        stderr_pane.log = preview.stderr_log.clone();
         Self {
             input: Input::from_options(&options, theme.clone()),
-            preview: Preview::from_options(&options, theme.clone()),
+            query2: Input::secondary(&options, theme.clone()),
+            query2_focused: false,
+            editing_query2: false,
+            preview,
+            stderr_pane,
             header,
             matcher_pool: Arc::new(ThreadPool::new(matcher_threads)),
             reader_pool: Arc::new(ThreadPool::new(reader_threads)),
+            source_counts: SourceCounts::default(),
+            event_tx: None,
+            typing: TypingSignal::new(std::time::Duration::from_millis(options.defer_ingest.unwrap_or(0))),
+            watch_reloads: None,
             item_pool: Arc::new(ItemPool::from_options(&options)),
             item_list: ItemList::from_options(&options, theme.clone()),
             theme,
@@ -313,7 +469,7 @@ impl App {
             final_action: None,
             cursor_pos: (0, 0),
             matcher: Matcher::from_options(&options),
-            yank_register: String::new(),
+            nth_index: 0,
             matcher_control: MatcherControl::default(),
             reader_timer: std::time::Instant::now(),
             matcher_timer: std::time::Instant::now(),
@@ -351,6 +507,17 @@ impl App {
             load_event_fired: false,
             result_pending: false,
             last_focused: None,
+            preview_override: None,
+            #[cfg(unix)]
+            preview_pane: None,
+            history_search: None,
+            jump_accept: false,
+            shell,
+            status_message: None,
+            windowed_before: 0,
+            timings: Timings::default(),
+            damage: Damage::default(),
+            last_frame: Buffer::default(),
         }
     }
 
@@ -363,6 +530,29 @@ impl App {
     pub fn resize(&mut self, cols: u16, rows: u16) {
         self.layout_template = LayoutTemplate::from_options(&self.options, self.header.height());
         self.layout = self.layout_template.apply(Rect::new(0, 0, cols, rows));
+        if let Some(area) = self.layout.preview_area {
+            self.preview.set_area(area);
+            // the text is wrapped again when rendered, only the offset depends on the size
+            if let Some(offset_expr) = &self.options.preview_window.offset {
+                let offset = self.calculate_preview_offset(offset_expr);
+                self.preview.set_offset(offset);
+            }
+        }
+    }
+
+    /// Whether the preview has to be computed again after a resize
+    ///
+    /// The output of the `--preview` command is kept and wrapped to the new width, unless the
+    /// command takes the size of the preview with `{lines}` or `{columns}`. The previews of
+    /// callbacks and of the pty are computed again, as they are sized when created.
+    fn preview_needs_rerun(&self) -> bool {
+        if self.options.preview_fn.is_some() || self.options.preview_window.pty {
+            return true;
+        }
+        self.preview_override
+            .as_deref()
+            .or(self.options.preview.as_deref())
+            .is_some_and(preview::uses_size)
     }
 
     /// Calculate preview offset from offset expression (e.g., "+123", "+{2}", "+{2}-2")
@@ -379,6 +569,12 @@ impl App {
                 .trim_matches(|x: char| !x.is_numeric())
                 .parse::<u16>()
                 .unwrap_or(0);
+            // `-/N` subtracts a fraction of the preview height, e.g. `-/2` centers the line
+            let right_val = if right.trim_start().starts_with('/') {
+                self.preview.rows.checked_div(right_val).unwrap_or(0)
+            } else {
+                right_val
+            };
             left_val.saturating_sub(right_val)
         } else if let Some((left, right)) = substituted.split_once('+') {
             let left_val = left.trim_matches(|x: char| !x.is_numeric()).parse::<u16>().unwrap_or(0);
@@ -399,6 +595,38 @@ impl App {
         self.needs_render.store(true, Ordering::Relaxed);
     }
 
+    /// The command query: the query in interactive mode, `--cmd-query` otherwise
+    fn cmd_query(&self) -> &str {
+        if self.options.interactive {
+            &self.input.value
+        } else {
+            self.options.cmd_query.as_deref().unwrap_or(&self.input.value)
+        }
+    }
+
+    /// State of the finder exported to the commands it runs: execute, reload and preview
+    pub(crate) fn state_env(&self) -> Vec<(&'static str, String)> {
+        let matched = self.item_list.count();
+        // 1-based like the status line, 0 when nothing matches
+        let pos = if matched == 0 { 0 } else { self.item_list.current + 1 };
+        vec![
+            ("SKIM_QUERY", self.input.value.clone()),
+            ("SKIM_CMD_QUERY", self.cmd_query().to_owned()),
+            ("SKIM_SELECT_COUNT", self.item_list.selection.len().to_string()),
+            ("SKIM_POS", pos.to_string()),
+            (
+                "SKIM_TOTAL_COUNT",
+                (self.item_pool.len() - self.item_pool.num_removed()).to_string(),
+            ),
+            ("SKIM_MATCH_COUNT", matched.to_string()),
+        ]
+    }
+
+    /// Whether the last frame is old enough for another one, see `--max-fps`
+    pub(crate) fn frame_due(&self) -> bool {
+        self.last_render_timer.elapsed() >= std::time::Duration::from_secs(1) / self.options.max_fps.max(1)
+    }
+
     /// Call after items are added or filtered (e.g., `Event::NewItem`, matcher completes)
     fn on_items_updated(&mut self) {
         self.pending_matcher_restart = true;
@@ -413,6 +641,12 @@ impl App {
     /// Emits the `focus` event when the focused item actually changed, so a
     /// `focus:<action>` binding runs on cursor movement.
     fn on_selection_changed(&mut self) -> Vec<Event> {
+        if self.item_list.take_refused() {
+            self.status_message = Some(match self.item_list.multi_max {
+                Some(max) if self.item_list.selection_is_full() => format!("! at most {max} items can be selected"),
+                _ => "! the item cannot be selected".to_string(),
+            });
+        }
         let mut events = vec![Event::RunPreview];
         events.extend(self.take_focus_event());
         events
@@ -429,6 +663,7 @@ impl App {
         };
         if changed {
             self.last_focused = focused;
+            self.preview_override = None;
             Some(Event::Key(SkimEvent::Focus.into()))
         } else {
             None
@@ -452,6 +687,17 @@ impl App {
         {
             self.load_event_fired = true;
             events.push(Event::Key(SkimEvent::Load.into()));
+            if self.item_list.keeps_selection() {
+                self.item_list.forget_kept();
+                self.damage.list = true;
+            }
+            let matched = self.matcher_control.get_num_matched();
+            if (self.options.select_1_on_reload && matched == 1) || (self.options.exit_0_on_reload && matched == 0) {
+                // accept what the matcher found, the list may not have shown it yet
+                self.item_list.merge_processed();
+                self.damage.list = true;
+                events.push(Event::Action(Action::Accept(None)));
+            }
         }
 
         if self.result_pending && self.matcher_control.stopped() {
@@ -459,7 +705,10 @@ impl App {
             events.push(Event::Key(SkimEvent::Result.into()));
             if self.reader_done {
                 match self.matcher_control.get_num_matched() {
-                    0 => events.push(Event::Key(SkimEvent::Zero.into())),
+                    0 => {
+                        self.suggest_near_misses();
+                        events.push(Event::Key(SkimEvent::Zero.into()));
+                    }
                     1 => events.push(Event::Key(SkimEvent::One.into())),
                     _ => {}
                 }
@@ -469,14 +718,49 @@ impl App {
         events
     }
 
+    /// With `--suggest-on-empty`, shows the items closest to a query that matched nothing
+    fn suggest_near_misses(&mut self) {
+        let Some(k) = self.options.suggest_on_empty else {
+            return;
+        };
+        if k == 0 || self.options.interactive || self.options.disabled || self.input.value.trim().is_empty() {
+            return;
+        }
+        // apply the empty result first, so that the next render does not replace the near misses
+        let processed = self.item_list.processed_items.lock().take();
+        if let Some(processed) = processed {
+            self.item_list.merge(processed);
+        }
+        if !self.item_list.items.is_empty() {
+            // earlier batches of items matched
+            return;
+        }
+        let near_misses = Matcher::near_misses(&self.options, &self.input.value, &self.item_pool.items(), k);
+        debug!(
+            "no match for {:?}, suggesting {} items",
+            self.input.value,
+            near_misses.len()
+        );
+        self.item_list.show_suggestions(near_misses);
+        self.needs_render.store(true, Ordering::Relaxed);
+    }
+
     /// Call when query changes (e.g., `AddChar`, `BackwardDeleteChar`, etc.)
     fn on_query_changed(&mut self) -> Vec<Event> {
-        // In interactive mode with --cmd, execute the command with {} substitution
-        if self.options.interactive && self.options.cmd.is_some() {
+        self.typing.touch();
+        if self.editing_query2 {
+            // the secondary query only filters, with the queries back in place, see
+            // `dispatch_focused`
+            std::mem::swap(&mut self.input, &mut self.query2);
+            self.restart_matcher_debounced();
+            std::mem::swap(&mut self.input, &mut self.query2);
+        } else if self.options.interactive && self.options.cmd.is_some() {
+            // In interactive mode with --cmd, execute the command with {} substitution
             let expanded_cmd = self.expand_cmd(&self.cmd, true);
             return vec![Event::Reload(expanded_cmd)];
+        } else {
+            self.restart_matcher_debounced();
         }
-        self.restart_matcher_debounced();
         vec![
             Event::Key(crate::binds::SkimEvent::Change.into()), // fire the `change` event binding
             Event::RunPreview,
@@ -520,8 +804,15 @@ impl App {
         }
 
         self.pending_preview_run = false;
+        self.damage.preview = true;
         self.last_preview_spawn = now;
 
+        #[cfg(unix)]
+        if self.options.preview_pane.is_some() {
+            self.run_pane_preview();
+            return Ok(());
+        }
+
         if let Some(preview_opt) = &self.options.preview
             && let Some(item) = self.item_list.selected()
         {
@@ -530,11 +821,7 @@ impl App {
             let selected = self.item_list.selected();
             let ctx = PreviewContext {
                 query: &self.input.value,
-                cmd_query: if self.options.interactive {
-                    &self.input.value
-                } else {
-                    self.options.cmd_query.as_deref().unwrap_or(&self.input.value)
-                },
+                cmd_query: self.cmd_query(),
                 width: self.preview.cols as usize,
                 height: self.preview.rows as usize,
                 current_index: selected
@@ -550,7 +837,11 @@ impl App {
                     .collect::<Vec<_>>(),
                 selections: &selection_str,
             };
-            let preview = item.preview(ctx);
+            let template = self.preview_override.clone().unwrap_or_else(|| preview_opt.clone());
+            let preview = match &self.preview_override {
+                Some(cmd) => ItemPreview::Command(cmd.clone()),
+                None => item.preview(ctx),
+            };
             let preview_ready = !matches!(
                 preview,
                 ItemPreview::Global | ItemPreview::Command(_) | ItemPreview::CommandWithPos(_, _)
@@ -560,13 +851,13 @@ impl App {
             #[cfg(not(feature = "image"))]
             let quote_cmd = true;
             match preview {
-                ItemPreview::Command(cmd) => self.preview.spawn(tui, &self.expand_cmd(&cmd, quote_cmd))?,
+                ItemPreview::Command(cmd) => self.spawn_preview(tui, &cmd, quote_cmd)?,
                 ItemPreview::Text(t) | ItemPreview::AnsiText(t) => {
                     self.preview.content(&t.bytes().collect::<Vec<_>>())?;
                 }
                 ItemPreview::CommandWithPos(cmd, preview_position) => {
                     // Execute command and apply position after content is ready
-                    self.preview.spawn(tui, &self.expand_cmd(&cmd, quote_cmd))?;
+                    self.spawn_preview(tui, &cmd, quote_cmd)?;
                     // Apply position offsets
                     let v_scroll = match preview_position.v_scroll {
                         crate::tui::Size::Fixed(n) => n,
@@ -604,11 +895,12 @@ impl App {
                 ItemPreview::TextWithPos(t, preview_position) | ItemPreview::AnsiWithPos(t, preview_position) => self
                     .preview
                     .content_with_position(&t.bytes().collect::<Vec<_>>(), preview_position)?,
-                ItemPreview::Global => self.preview.spawn(tui, &self.expand_cmd(preview_opt, quote_cmd))?,
+                ItemPreview::Global => self.spawn_preview(tui, &template, quote_cmd)?,
             }
             if preview_ready {
                 let _ = tui.event_tx.try_send(Event::PreviewReady);
             }
+            self.prefetch_previews(&template, quote_cmd);
         } else if let Some(cb) = &self.options.preview_fn {
             let selection: Vec<Arc<dyn SkimItem>>;
             if self.options.multi {
@@ -618,11 +910,143 @@ impl App {
             } else {
                 selection = Vec::new();
             }
-            self.preview.content(&cb(selection).join("\n").into_bytes())?;
+            self.preview.output(cb.preview(selection))?;
         }
         Ok(())
     }
 
+    /// Runs `tmux` with the expanded `args`, for `execute-tmux`
+    #[cfg(unix)]
+    fn execute_tmux(&mut self, args: &str) {
+        let Some(expanded_args) = self.expand_cmd_or_report(args, true) else {
+            return;
+        };
+        let expanded_cmd = format!("tmux {expanded_args}");
+        if self.options.dry_run {
+            self.echo_cmd(&expanded_cmd);
+            return;
+        }
+        if !crate::popup::tmux::is_available() {
+            self.status_message = Some("! not running inside tmux".to_string());
+            return;
+        }
+        debug!("execute-tmux: {expanded_cmd}");
+        let mut command = self.shell.command(&expanded_cmd);
+        command
+            .envs(self.state_env())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        let _ = command.spawn();
+    }
+
+    /// Types the selection in the tmux pane or window `target`, for `send-to-tmux`
+    #[cfg(unix)]
+    fn send_to_tmux(&mut self, target: &str) {
+        let keys = self.expand_cmd("{+}", true);
+        let args = crate::popup::tmux::send_keys_args(target, &keys);
+        if self.options.dry_run {
+            self.echo_cmd(&format!("tmux {}", args.join(" ")));
+            return;
+        }
+        debug!("send-to-tmux: {args:?}");
+        if let Err(e) = crate::popup::tmux::run(args) {
+            warn!("send-to-tmux({target}): {e}");
+            self.status_message = Some(format!("! {e}"));
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn report_no_tmux(&mut self, arg: &str) {
+        warn!("not running `{arg}` in tmux: not available on this platform");
+        self.status_message = Some("! not running inside tmux".to_string());
+    }
+
+    /// Run the preview command in the `--preview-pane`, opening the pane if needed
+    ///
+    /// The pane is closed while the preview is hidden.
+    #[cfg(unix)]
+    fn run_pane_preview(&mut self) {
+        if self.options.preview_window.hidden {
+            self.preview_pane = None;
+            return;
+        }
+        let Some(template) = self.preview_override.clone().or_else(|| self.options.preview.clone()) else {
+            return;
+        };
+        if self.item_list.selected().is_none() {
+            return;
+        }
+        let Some(cmd) = self.expand_cmd_or_report(&template, true) else {
+            return;
+        };
+        if self.preview_pane.is_none() {
+            let spec = self.options.preview_pane.as_deref().unwrap_or_default();
+            match crate::popup::TmuxPreviewPane::open(spec) {
+                Ok(pane) => self.preview_pane = Some(pane),
+                Err(e) => {
+                    warn!("could not open the preview pane: {e}");
+                    self.status_message = Some(format!("! {e}"));
+                    return;
+                }
+            }
+        }
+        if let Some(pane) = &self.preview_pane
+            && let Err(e) = pane.show(&cmd)
+        {
+            warn!("could not run the preview in its pane: {e}");
+        }
+    }
+
+    /// Prefetch the previews of the `--preview-prefetch` items closest to the cursor
+    fn prefetch_previews(&mut self, template: &str, quote_cmd: bool) {
+        let n = self.options.preview_prefetch;
+        if n == 0 {
+            return;
+        }
+        let current = self.item_list.current;
+        let neighbors = (1..=n).flat_map(|k| [current.checked_add(k), current.checked_sub(k)]);
+        let selected: Vec<MatchedItem> = self.item_list.selection.iter().cloned().collect();
+        let ctx = placeholder::Context::from_options(&self.options)
+            .selected(&selected)
+            .query(&self.input.value)
+            .cmd_query(&self.input.value);
+        let template = preview::expand_size(template, self.preview.inner_size());
+        self.preview.env = self.state_env();
+        let file_arg = file_preview::builtin_file_arg(&template);
+        let jobs = neighbors
+            .flatten()
+            .filter_map(|i| self.item_list.items.get(i))
+            .filter(|item| !item.item.disabled())
+            .map(|item| {
+                let ctx = ctx.clone().current(Some(item));
+                match file_arg {
+                    Some(arg) => PreviewJob::File(PathBuf::from(placeholder::expand(arg, &ctx.quote(false)))),
+                    None => PreviewJob::Command(placeholder::expand(&template, &ctx.quote(quote_cmd))),
+                }
+            })
+            .collect();
+        self.preview.prefetch(jobs);
+    }
+
+    /// Expand and run a preview command, using the builtin file previewer for `builtin:file`
+    fn spawn_preview<B: Backend>(&mut self, tui: &mut Tui<B>, cmd: &str, quote_cmd: bool) -> Result<()>
+    where
+        B::Error: Send + Sync + 'static,
+    {
+        let cmd = preview::expand_size(cmd, self.preview.inner_size());
+        self.preview.env = self.state_env();
+        if let Some(arg) = file_preview::builtin_file_arg(&cmd) {
+            let path = self.expand_cmd(arg, false);
+            self.preview.spawn_file(tui, PathBuf::from(path));
+            Ok(())
+        } else {
+            match self.expand_cmd_or_report(&cmd, quote_cmd) {
+                Some(expanded) => self.preview.spawn(tui, &expanded),
+                None => Ok(()),
+            }
+        }
+    }
+
     /// Handles a TUI event and updates application state
     ///
     /// # Errors
@@ -634,14 +1058,24 @@ impl App {
         B::Error: Send + Sync + 'static,
     {
         trace!("handling event {event:?}");
+        if !matches!(
+            event,
+            Event::Render | Event::Heartbeat | Event::Tick | Event::RunPreview | Event::PreviewReady
+        ) {
+            self.damage.mark_all();
+        }
         match event {
             Event::Render => {
                 // Always render to avoid freezing, but the render function itself can optimize
                 tui.get_frame();
+                let render_start = std::time::Instant::now();
+                let render = trace_span!(target: super::timings::TARGET, "render").entered();
                 tui.draw(|f| {
                     f.render_widget(&mut *self, f.area());
                     f.set_cursor_position(self.cursor_pos);
                 })?;
+                drop(render);
+                self.timings.frame(render_start.elapsed());
                 // Matcher output is merged into the item list during rendering,
                 // so this is where result-driven focus changes become observable.
                 if let Some(event) = self.take_focus_event() {
@@ -652,15 +1086,17 @@ impl App {
                 // Heartbeat is used for periodic UI updates
                 self.update_spinner();
                 if self.preview.is_loading() {
+                    self.damage.preview = true;
                     self.needs_render.store(true, Ordering::Relaxed);
                 }
 
+                if let Some(took) = self.matcher_control.take_duration() {
+                    self.timings.matched(took, self.matcher_control.get_num_processed());
+                }
                 if self.pending_matcher_restart {
                     self.restart_matcher(true);
                 }
-                if self.needs_render.load(Ordering::Relaxed)
-                    && self.last_render_timer.elapsed().as_millis() > 1000 / u128::from(TICK_RATE)
-                {
+                if self.needs_render.load(Ordering::Relaxed) && self.frame_due() {
                     debug!("Triggering render");
                     self.needs_render.store(false, Ordering::Relaxed);
                     self.last_render_timer = std::time::Instant::now();
@@ -693,7 +1129,21 @@ impl App {
                 }
             }
             Event::RunExecute(cmd) => {
-                tui.run_execute(cmd)?;
+                let mut command = self.shell.command(cmd);
+                command.envs(self.state_env());
+                tui.run_execute(command)?;
+                self.handle_event(tui, &Event::Redraw)?;
+            }
+            Event::RunPick(cmd) => {
+                let mut source = self.shell.command(cmd);
+                source.envs(self.state_env());
+                let picked = tui.run_pick(source, nested_finder())?;
+                if let Some(picked) = picked.filter(|p| !p.trim().is_empty()) {
+                    self.input.insert_str(&picked.lines().collect::<Vec<_>>().join(" "));
+                    for evt in self.on_query_changed() {
+                        tui.event_tx.try_send(evt)?;
+                    }
+                }
                 self.handle_event(tui, &Event::Redraw)?;
             }
             Event::Clear => {
@@ -712,8 +1162,18 @@ impl App {
                 tui.force_full_redraw();
                 self.handle_event(tui, &Event::Render)?;
             }
+            Event::Suspend => {
+                tui.suspend()?;
+                self.handle_event(tui, &Event::Redraw)?;
+            }
+            Event::Continued => {
+                // the shell may have reset the terminal modes while skim was stopped
+                tui.resume(true)?;
+                self.handle_event(tui, &Event::Redraw)?;
+            }
             Event::Quit | Event::Close => {
                 tui.exit()?;
+                self.run_exit_hooks(false);
                 self.should_quit = true;
             }
             Event::PreviewReady => {
@@ -723,6 +1183,8 @@ impl App {
                     let offset = self.calculate_preview_offset(offset_expr);
                     self.preview.set_offset(offset);
                 }
+                self.preview.apply_directives();
+                self.damage.preview = true;
                 self.needs_render();
             }
             Event::Error(msg) => {
@@ -732,7 +1194,13 @@ impl App {
             Event::Action(act) => {
                 let events = self.handle_action(act)?;
                 for evt in events {
-                    tui.event_tx.try_send(evt)?;
+                    // The rest of the key's chain is already queued: run the nested
+                    // finder now so that it sees the picked query
+                    if matches!(evt, Event::RunPick(_)) {
+                        self.handle_event(tui, &evt)?;
+                    } else {
+                        tui.event_tx.try_send(evt)?;
+                    }
                 }
                 tui.event_tx.try_send(Event::Render)?;
             }
@@ -766,7 +1234,9 @@ impl App {
                     });
                 }
                 self.resize(*cols, *rows);
-                if let Err(e) = self.run_preview(tui) {
+                if self.preview_needs_rerun()
+                    && let Err(e) = self.run_preview(tui)
+                {
                     warn!("error while rerunnig preview after resize: {e}");
                 }
             }
@@ -787,6 +1257,12 @@ impl App {
                 self.item_pool.append(items.to_owned());
                 self.restart_matcher(false);
             }
+            Event::Watched => {
+                if let Some(reloads) = &mut self.watch_reloads {
+                    *reloads += 1;
+                }
+                self.handle_event(tui, &Event::Action(Action::Reload(None)))?;
+            }
             Event::Reload(_) => {
                 unreachable!("Reload is handled by the TUI event loop in lib.rs")
             }
@@ -803,10 +1279,31 @@ impl App {
     fn handle_key(&mut self, key: &KeyEvent) -> Vec<Event> {
         let normalized_key = KeyEvent::new(key.code, key.modifiers);
         debug!("key event: {key:?}, normalized: {normalized_key:?}");
+        self.status_message = None;
+        self.damage.mark_all();
+
+        if self.history_search.is_some()
+            && let Some(events) = self.handle_history_search_key(&normalized_key)
+        {
+            return events;
+        }
+
+        if self.item_list.jumping {
+            return self.handle_jump_key(&normalized_key);
+        }
 
+        let mut events = if normalized_key == SkimEvent::Start.key_event() {
+            self.run_start_hooks()
+        } else {
+            Vec::new()
+        };
         if let Some(act) = &self.options.keymap.get(&normalized_key) {
             debug!("{act:?}");
-            return act.iter().map(|a| Event::Action(a.clone())).collect();
+            events.extend(act.iter().map(|a| Event::Action(a.clone())));
+            return events;
+        }
+        if !events.is_empty() {
+            return events;
         }
         match key.modifiers {
             KeyModifiers::CONTROL => {
@@ -829,30 +1326,267 @@ impl App {
         vec![]
     }
 
+    /// Ends a `jump`, moving to (or accepting) the item labeled with the key; other keys cancel it
+    fn handle_jump_key(&mut self, key: &KeyEvent) -> Vec<Event> {
+        self.item_list.jumping = false;
+        let label = match (key.code, key.modifiers) {
+            (Char(c), KeyModifiers::NONE) => c,
+            (Char(c), KeyModifiers::SHIFT) => c.to_uppercase().next().unwrap_or(c),
+            _ => return Vec::new(),
+        };
+        let Some(index) = self.item_list.jump_target(label) else {
+            return Vec::new();
+        };
+        self.item_list.current = index;
+        let mut events = self.on_selection_changed();
+        if self.jump_accept {
+            events.push(Event::Action(Action::Accept(None)));
+        }
+        events
+    }
+
+    /// Runs `--on-start` in the background and the `on_start_fn` callback, returning the events of the latter
+    fn run_start_hooks(&mut self) -> Vec<Event> {
+        if let Some(cmd) = self.options.on_start.clone()
+            && let Some(mut command) = self.hook_command(&cmd)
+        {
+            let _ = command.spawn();
+        }
+        let Some(callback) = self.options.on_start_fn.clone() else {
+            return Vec::new();
+        };
+        callback.call(self).unwrap_or_else(|e| {
+            warn!("on_start_fn failed: {e}");
+            Vec::new()
+        })
+    }
+
+    /// Runs `--on-accept` or `--on-abort`, waiting for it, then the matching callback
+    fn run_exit_hooks(&mut self, accepted: bool) {
+        let (cmd, callback) = if accepted {
+            (self.options.on_accept.clone(), self.options.on_accept_fn.clone())
+        } else {
+            (self.options.on_abort.clone(), self.options.on_abort_fn.clone())
+        };
+        if let Some(cmd) = cmd
+            && let Some(mut command) = self.hook_command(&cmd)
+            && let Err(e) = command.status()
+        {
+            warn!("failed to run `{cmd}`: {e}");
+        }
+        if let Some(callback) = callback
+            && let Err(e) = callback.call(self)
+        {
+            warn!("exit hook failed: {e}");
+        }
+    }
+
+    /// The command of a `--on-*` hook, its output discarded; `None` if it can't be expanded or in `--dry-run`
+    fn hook_command(&self, cmd: &str) -> Option<std::process::Command> {
+        let expanded_cmd = self
+            .try_expand_cmd(cmd, true)
+            .inspect_err(|e| warn!("not running `{cmd}`: {e}"))
+            .ok()?;
+        if self.options.dry_run {
+            debug!("dry run, not running hook `{expanded_cmd}`");
+            return None;
+        }
+        debug!("hook: {expanded_cmd}");
+        let mut command = self.shell.command(&expanded_cmd);
+        command
+            .envs(self.state_env())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        Some(command)
+    }
+
+    /// History searched by `history-search`: command history in interactive mode, query history otherwise
+    fn active_history(&self) -> &[String] {
+        if self.options.interactive {
+            &self.cmd_history
+        } else {
+            &self.query_history
+        }
+    }
+
+    /// Completes the query token before the cursor to the longest common prefix of the words of
+    /// the matched items starting with it, or lists these words when it cannot go further
+    fn tab_complete(&mut self) -> Vec<Event> {
+        let range = self.input.token_range();
+        let token = self.input.value[range.clone()].to_owned();
+        if token.is_empty() {
+            return Vec::new();
+        }
+        let mut candidates = std::collections::BTreeSet::new();
+        for item in self.item_list.items.iter().take(COMPLETION_ITEMS) {
+            let text = item.item.text();
+            candidates.extend(input::completions(&text, &token).map(str::to_owned));
+        }
+        let prefix = input::common_prefix(candidates.iter().map(String::as_str)).unwrap_or_default();
+        if prefix.len() > token.len() {
+            self.input.value.replace_range(range.clone(), prefix);
+            self.input.cursor_pos = u16::try_from(range.start + prefix.len()).unwrap_or(u16::MAX);
+            return self.on_query_changed();
+        }
+        if candidates.len() > 1 {
+            let mut listed: Vec<&str> = candidates.iter().take(COMPLETIONS_SHOWN).map(String::as_str).collect();
+            if candidates.len() > COMPLETIONS_SHOWN {
+                listed.push("…");
+            }
+            self.status_message = Some(listed.join(" "));
+            self.needs_render();
+        }
+        Vec::new()
+    }
+
+    /// Starts a history search, or moves to the next older match if one is running
+    fn history_search(&mut self) -> Vec<Event> {
+        if let Some(search) = &self.history_search {
+            let before = search.index.unwrap_or(self.active_history().len());
+            return self.search_history(before);
+        }
+        self.history_search = Some(HistorySearchState {
+            saved_input: self.input.value.clone(),
+            saved_prompt: self.input.prompt.clone(),
+            ..Default::default()
+        });
+        self.update_history_search_prompt();
+        Vec::new()
+    }
+
+    /// Shows the most recent history entry before `before` that contains the search pattern
+    fn search_history(&mut self, before: usize) -> Vec<Event> {
+        let Some(search) = &self.history_search else {
+            return Vec::new();
+        };
+        let history = self.active_history();
+        let found = history[..before.min(history.len())]
+            .iter()
+            .rposition(|entry| entry.contains(&search.pattern));
+        let entry = found.map(|idx| history[idx].clone());
+        let search = self.history_search.as_mut().unwrap();
+        search.failed = found.is_none();
+        let events = if let (Some(idx), Some(entry)) = (found, entry) {
+            search.index = Some(idx);
+            self.input.value = entry;
+            self.input.move_to_end();
+            self.on_query_changed()
+        } else {
+            Vec::new()
+        };
+        self.update_history_search_prompt();
+        events
+    }
+
+    fn update_history_search_prompt(&mut self) {
+        if let Some(search) = &self.history_search {
+            let failed = if search.failed { "failed " } else { "" };
+            self.input.prompt = format!("({failed}history '{}') ", search.pattern);
+        }
+    }
+
+    /// Ends the history search, keeping the found entry in the input unless `cancel` is set
+    fn end_history_search(&mut self, cancel: bool) -> Vec<Event> {
+        let Some(search) = self.history_search.take() else {
+            return Vec::new();
+        };
+        self.input.prompt = search.saved_prompt;
+        if cancel && self.input.value != search.saved_input {
+            self.input.value = search.saved_input;
+            self.input.move_to_end();
+            return self.on_query_changed();
+        }
+        Vec::new()
+    }
+
+    /// Edits the pattern of a running history search
+    ///
+    /// Returns `None` for keys that end the search and should then be handled normally.
+    fn handle_history_search_key(&mut self, key: &KeyEvent) -> Option<Vec<Event>> {
+        if self
+            .options
+            .keymap
+            .get(key)
+            .is_some_and(|acts| acts.iter().any(|a| matches!(a, Action::HistorySearch)))
+        {
+            return None;
+        }
+        let search = self.history_search.as_mut()?;
+        // Keep showing the current match while it still contains the pattern
+        let from = search.index.map_or(usize::MAX, |idx| idx + 1);
+        match (key.code, key.modifiers) {
+            (Char(c), KeyModifiers::NONE) => search.pattern.push(c),
+            (Char(c), KeyModifiers::SHIFT) => search.pattern.extend(c.to_uppercase()),
+            (KeyCode::Backspace, _) => {
+                search.pattern.pop();
+            }
+            (KeyCode::Esc, _) | (Char('c' | 'g'), KeyModifiers::CONTROL) => return Some(self.end_history_search(true)),
+            (KeyCode::Enter, _) => return Some(self.end_history_search(false)),
+            _ => {
+                self.end_history_search(false);
+                return None;
+            }
+        }
+        Some(self.search_history(from))
+    }
+
     /// Runs an action, then directly dispatches any follow-up actions bound to it.
     ///
     /// Follow-ups use non-recursive (`noremap`) semantics: an action in the
     /// follow-up chain does not trigger its own follow-up binding. If the chain
     /// contains [`Action::Suppress`], the triggering action is skipped.
     fn handle_action(&mut self, act: &Action) -> Result<Vec<Event>> {
+        self.damage.mark_all();
         let follow = self.options.action_binds.get(act.name()).cloned();
         let suppress_default = follow
             .as_ref()
             .is_some_and(|chain| chain.iter().any(|a| matches!(a, Action::Suppress)));
 
+        let on_query2 = self.query2_focused && act.edits_query();
+        let edited = self.edited_input(on_query2);
+        let (value, cursor_pos) = (edited.value.clone(), edited.cursor_pos);
         let mut events = if suppress_default {
             Vec::new()
         } else {
-            self.dispatch_action(act)?
+            self.dispatch_focused(act)?
         };
         if let Some(chain) = follow {
             for action in chain.iter().filter(|a| !matches!(a, Action::Suppress)) {
-                events.extend(self.dispatch_action(action)?);
+                events.extend(self.dispatch_focused(action)?);
             }
         }
+        let edited = self.edited_input(on_query2);
+        if !matches!(act, Action::Undo) {
+            edited.record_edit(value, cursor_pos, matches!(act, Action::AddChar(_)));
+        }
+        if !matches!(act, Action::Yank | Action::YankPop) {
+            edited.kill_ring.end_yank();
+        }
         Ok(events)
     }
 
+    /// The query edited by the actions, the secondary one when `on_query2`
+    fn edited_input(&mut self, on_query2: bool) -> &mut Input {
+        if on_query2 { &mut self.query2 } else { &mut self.input }
+    }
+
+    /// Dispatches `act`, editing the secondary query in place of the query while it has the cursor
+    ///
+    /// The two are only swapped while the edit runs, the other actions of a chain seeing them in
+    /// place, and [`App::on_query_changed`] swaps them back to restart the search.
+    fn dispatch_focused(&mut self, act: &Action) -> Result<Vec<Event>> {
+        self.editing_query2 = self.query2_focused && act.edits_query();
+        if !self.editing_query2 {
+            return self.dispatch_action(act);
+        }
+        std::mem::swap(&mut self.input, &mut self.query2);
+        let events = self.dispatch_action(act);
+        std::mem::swap(&mut self.input, &mut self.query2);
+        self.editing_query2 = false;
+        events
+    }
+
     fn dispatch_conditional(&mut self, condition: bool, then: &str, otherwise: Option<&str>) -> Result<Vec<Event>> {
         let Some(chain) = condition.then_some(then).or(otherwise) else {
             return Ok(Vec::new());
@@ -882,6 +1616,7 @@ impl App {
         use ratatui::widgets::ListDirection::{BottomToTop, TopToBottom};
         match act {
             Abort | Accept(_) => {
+                self.run_exit_hooks(matches!(act, Accept(_)));
                 self.should_quit = true;
                 self.final_action = Some(act.clone());
             }
@@ -926,7 +1661,7 @@ impl App {
             BackwardKillWord => {
                 let deleted = self.input.delete_backward_word();
                 if !deleted.is_empty() {
-                    self.yank(deleted);
+                    self.input.kill_ring.push(deleted);
                     return Ok(self.on_query_changed());
                 }
             }
@@ -949,6 +1684,23 @@ impl App {
                 self.matcher_control.kill();
                 self.preview.kill();
             }
+            ChangeBorderLabel(label) => {
+                self.options.border_label = Some(self.expand_cmd(label, false));
+            }
+            ChangeHeaderLabel(label) => {
+                // kept in the options too, `set-header` rebuilds the header from them
+                self.options.header_label = Some(self.expand_cmd(label, false));
+                self.header.label.clone_from(&self.options.header_label);
+            }
+            ChangeInputLabel(label) => {
+                self.input.label = Some(self.expand_cmd(label, false));
+            }
+            ChangeListLabel(label) => {
+                self.item_list.label = Some(self.expand_cmd(label, false));
+            }
+            ChangePreviewLabel(label) => {
+                self.preview.label = Some(self.expand_cmd(label, false));
+            }
             ClearScreen => {
                 return Ok(vec![Event::Clear]);
             }
@@ -978,6 +1730,8 @@ impl App {
                 }
                 return Ok(self.on_selection_changed());
             }
+            DisableSearch => self.set_search(false),
+            EnableSearch => self.set_search(true),
             EndOfLine => {
                 self.input.move_to_end();
             }
@@ -986,17 +1740,43 @@ impl App {
                 // skim's input reader and toggle terminal modes), which this
                 // method does not have. Expand the command here and hand it to
                 // the event loop, which runs it via `Event::RunExecute`.
-                let expanded_cmd = self.expand_cmd(cmd, true);
+                let Some(expanded_cmd) = self.expand_cmd_or_report(cmd, true) else {
+                    return Ok(Vec::new());
+                };
+                if self.options.dry_run {
+                    self.echo_cmd(&expanded_cmd);
+                    return Ok(Vec::new());
+                }
                 debug!("execute: {expanded_cmd}");
                 return Ok(vec![Event::RunExecute(expanded_cmd)]);
             }
+            ExecuteEcho(cmd) => {
+                if let Some(expanded_cmd) = self.expand_cmd_or_report(cmd, true) {
+                    self.echo_cmd(&expanded_cmd);
+                }
+            }
             ExecuteSilent(cmd) => {
-                let expanded_cmd = self.expand_cmd(cmd, true);
+                let Some(expanded_cmd) = self.expand_cmd_or_report(cmd, true) else {
+                    return Ok(Vec::new());
+                };
+                if self.options.dry_run {
+                    self.echo_cmd(&expanded_cmd);
+                    return Ok(Vec::new());
+                }
                 debug!("execute-silent: {expanded_cmd}");
-                let mut command = crate::shell_cmd(&expanded_cmd);
-                command.stdout(Stdio::null()).stderr(Stdio::null());
+                let mut command = self.shell.command(&expanded_cmd);
+                command
+                    .envs(self.state_env())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null());
                 let _ = command.spawn();
             }
+            ExecuteTmux(args) => {
+                #[cfg(unix)]
+                self.execute_tmux(args);
+                #[cfg(not(unix))]
+                self.report_no_tmux(args);
+            }
             First | Top => {
                 // Jump to first item (considering reserved items)
                 self.item_list.jump_to_first();
@@ -1021,18 +1801,22 @@ impl App {
             // suppression effect is applied in `handle_action` when it appears in
             // an action's follow-up chain.
             Ignore | Suppress => (),
+            Jump | JumpAccept => {
+                self.item_list.jumping = !self.item_list.items.is_empty();
+                self.jump_accept = matches!(act, JumpAccept);
+            }
             KillLine => {
                 let cursor = self.input.cursor_pos as usize;
                 let deleted = self.input.split_off(cursor);
-                self.yank(deleted);
+                self.input.kill_ring.push(deleted);
                 return Ok(self.on_query_changed());
             }
             KillWord => {
                 let deleted = self.input.delete_forward_word();
-                self.yank(deleted);
+                self.input.kill_ring.push(deleted);
                 return Ok(self.on_query_changed());
             }
-            Last => {
+            Last | Bottom => {
                 // Jump to last item
                 self.item_list.jump_to_last();
                 return Ok(self.on_selection_changed());
@@ -1078,36 +1862,61 @@ impl App {
             HalfPageDown(n) => {
                 let offset = i32::from(self.item_list.height) / 2;
                 if self.options.layout == TuiLayout::Default {
-                    self.item_list.scroll_by_rows(-offset * n);
+                    self.item_list.page_by_rows(-offset * n);
                 } else {
-                    self.item_list.scroll_by_rows(offset * n);
+                    self.item_list.page_by_rows(offset * n);
                 }
                 return Ok(self.on_selection_changed());
             }
             HalfPageUp(n) => {
                 let offset = i32::from(self.item_list.height) / 2;
                 if self.options.layout == TuiLayout::Default {
-                    self.item_list.scroll_by_rows(offset * n);
+                    self.item_list.page_by_rows(offset * n);
                 } else {
-                    self.item_list.scroll_by_rows(-offset * n);
+                    self.item_list.page_by_rows(-offset * n);
                 }
                 return Ok(self.on_selection_changed());
             }
+            Pick(cmd) => {
+                let Some(expanded_cmd) = self.expand_cmd_or_report(cmd, true) else {
+                    return Ok(Vec::new());
+                };
+                debug!("pick: {expanded_cmd}");
+                return Ok(vec![Event::RunPick(expanded_cmd)]);
+            }
+            Plugin(name) => {
+                let Some(action) = self.options.actions.get(name) else {
+                    warn!("no plugin action registered as `{name}`");
+                    self.status_message = Some(format!("! unknown plugin action `{name}`"));
+                    return Ok(Vec::new());
+                };
+                let handle = AppHandle::new(
+                    self.input.value.clone(),
+                    self.item_list.selected().map(|matched| matched.item),
+                    self.item_list
+                        .selection
+                        .iter()
+                        .map(|matched| matched.item.clone())
+                        .collect(),
+                    self.event_tx.clone(),
+                );
+                return action(&handle).map_err(|e| eyre::eyre!("{}", e));
+            }
             PageDown(n) => {
                 let offset = i32::from(self.item_list.height);
                 if self.options.layout == TuiLayout::Default {
-                    self.item_list.scroll_by_rows(-offset * n);
+                    self.item_list.page_by_rows(-offset * n);
                 } else {
-                    self.item_list.scroll_by_rows(offset * n);
+                    self.item_list.page_by_rows(offset * n);
                 }
                 return Ok(self.on_selection_changed());
             }
             PageUp(n) => {
                 let offset = i32::from(self.item_list.height);
                 if self.options.layout == TuiLayout::Default {
-                    self.item_list.scroll_by_rows(offset * n);
+                    self.item_list.page_by_rows(offset * n);
                 } else {
-                    self.item_list.scroll_by_rows(-offset * n);
+                    self.item_list.page_by_rows(-offset * n);
                 }
                 return Ok(self.on_selection_changed());
             }
@@ -1135,6 +1944,14 @@ impl App {
                 self.preview.page_down();
                 self.needs_render();
             }
+            Preview(cmd) => {
+                // Settle any pending focus change first, so it does not
+                // immediately revert the override we are about to set.
+                let mut events: Vec<Event> = self.take_focus_event().into_iter().collect();
+                self.preview_override = Some(cmd.to_owned());
+                events.push(Event::RunPreview);
+                return Ok(events);
+            }
             PreviousHistory => {
                 // Use cmd_history in interactive mode, query_history otherwise
                 let (history, history_index, saved_input) = if self.options.interactive {
@@ -1174,10 +1991,14 @@ impl App {
 
                 return Ok(self.on_query_changed());
             }
+            HistorySearch => return Ok(self.history_search()),
             Redraw => return Ok(vec![Event::Clear]),
             Reload(Some(s)) => {
+                let Some(expanded_cmd) = self.expand_cmd_or_report(s, true) else {
+                    return Ok(Vec::new());
+                };
                 self.item_list.clear_selection();
-                return Ok(vec![Event::Reload(self.expand_cmd(s, true))]);
+                return Ok(vec![Event::Reload(expanded_cmd)]);
             }
             Reload(None) => {
                 self.item_list.clear_selection();
@@ -1191,6 +2012,8 @@ impl App {
                 }
             }
             RefreshPreview => {
+                self.preview_override = None;
+                self.preview.clear_cache();
                 return Ok(vec![Event::RunPreview]);
             }
             RestartMatcher => {
@@ -1210,7 +2033,7 @@ impl App {
                     // fuzzy -> exact
                     self.options.exact = true;
                 }
-                self.matcher = Matcher::from_options(&self.options);
+                self.matcher = self.build_matcher();
                 self.restart_matcher(true);
             }
             ScrollLeft(n) => {
@@ -1242,11 +2065,33 @@ impl App {
                 self.options.preview = Some(cmd.to_owned());
                 return Ok(vec![Event::RunPreview]);
             }
+            Put(text) => {
+                if !text.is_empty() {
+                    self.input.insert_str(text);
+                    return Ok(self.on_query_changed());
+                }
+            }
+            ReplaceQuery => {
+                if let Some(item) = self.item_list.selected() {
+                    self.input.value = item.item.text().into_owned();
+                    self.input.move_to_end();
+                    return Ok(self.on_query_changed());
+                }
+            }
             SetQuery(value) => {
                 self.input.value = self.expand_cmd(value, false);
                 self.input.move_to_end();
                 return Ok(self.on_query_changed());
             }
+            SendToTmux(target) => {
+                #[cfg(unix)]
+                self.send_to_tmux(target);
+                #[cfg(not(unix))]
+                self.report_no_tmux(target);
+            }
+            // Stopping needs the `Tui` to hand the terminal back first
+            Suspend => return Ok(vec![Event::Suspend]),
+            TabComplete => return Ok(self.tab_complete()),
             Toggle => {
                 self.item_list.toggle();
                 return Ok(self.on_selection_changed());
@@ -1255,6 +2100,10 @@ impl App {
                 self.item_list.toggle_all();
                 return Ok(self.on_selection_changed());
             }
+            ToggleGroup => {
+                self.item_list.toggle_group();
+                return Ok(self.on_selection_changed());
+            }
             ToggleIn => {
                 self.item_list.toggle();
                 match self.item_list.direction {
@@ -1268,6 +2117,14 @@ impl App {
                 self.input.switch_mode();
                 self.restart_matcher(true);
             }
+            ToggleNth => {
+                let sets = self.nth_sets().len();
+                if sets > 1 {
+                    self.nth_index = (self.nth_index + 1) % sets;
+                    self.matcher = self.build_matcher();
+                    self.restart_matcher(true);
+                }
+            }
             ToggleOut => {
                 self.item_list.toggle();
                 match self.item_list.direction {
@@ -1276,19 +2133,32 @@ impl App {
                 }
                 return Ok(self.on_selection_changed());
             }
+            ToggleQuery2 => {
+                self.query2_focused = !self.query2_focused;
+                self.needs_render();
+            }
+            ToggleStderr => {
+                self.stderr_pane.visible = !self.stderr_pane.visible;
+                self.needs_render();
+            }
             TogglePreview => {
                 self.options.preview_window.hidden = !self.options.preview_window.hidden;
                 self.layout_template = LayoutTemplate::from_options(&self.options, self.header.height());
                 self.needs_render();
+                if self.options.preview_pane.is_some() {
+                    // open or close the pane
+                    return Ok(vec![Event::RunPreview]);
+                }
             }
             TogglePreviewWrap => {
-                self.preview.wrap = !self.preview.wrap;
+                self.preview.toggle_wrap();
                 self.needs_render();
             }
             ToggleSort => {
                 self.options.no_sort = !self.options.no_sort;
                 self.restart_matcher(true);
             }
+            ToggleSearch => self.set_search(self.options.disabled),
             Unbind(spec) => {
                 // Remove the bindings for one or more keys or action triggers.
                 for trigger in crate::binds::split_top_level(spec, ',') {
@@ -1296,22 +2166,37 @@ impl App {
                         Ok(parsed) => {
                             self.options.keymap.remove(&parsed);
                         }
-                        Err(err) => match crate::binds::action_trigger_name(trigger) {
-                            Some(name) => {
+                        Err(err) => {
+                            if let Some(name) = crate::binds::action_trigger_name(trigger) {
                                 self.options.action_binds.remove(name);
+                            } else {
+                                debug!("Failed to unbind {trigger}: {err}");
                             }
-                            None => debug!("Failed to unbind {trigger}: {err}"),
-                        },
+                        }
                     }
                 }
             }
+            TransposeChars => {
+                if self.input.transpose_chars() {
+                    return Ok(self.on_query_changed());
+                }
+            }
+            Undo => {
+                if self.input.undo() {
+                    return Ok(self.on_query_changed());
+                }
+            }
             UnixLineDiscard => {
-                if !self.input.delete_to_beginning().is_empty() {
+                let deleted = self.input.delete_to_beginning();
+                if !deleted.is_empty() {
+                    self.input.kill_ring.push(deleted);
                     return Ok(self.on_query_changed());
                 }
             }
             UnixWordRubout => {
-                if !self.input.delete_backward_to_whitespace().is_empty() {
+                let deleted = self.input.delete_backward_to_whitespace();
+                if !deleted.is_empty() {
+                    self.input.kill_ring.push(deleted);
                     return Ok(self.on_query_changed());
                 }
             }
@@ -1323,9 +2208,15 @@ impl App {
                 return Ok(self.on_selection_changed());
             }
             Yank => {
-                // Insert from yank register at cursor position
-                self.input.insert_str(&self.yank_register);
-                return Ok(self.on_query_changed());
+                // Insert the last killed text at cursor position
+                if self.input.yank() {
+                    return Ok(self.on_query_changed());
+                }
+            }
+            YankPop => {
+                if self.input.yank_pop() {
+                    return Ok(self.on_query_changed());
+                }
             }
             Custom(cb) => {
                 return cb.call(self).map_err(|e| eyre::eyre!("{}", e));
@@ -1338,9 +2229,9 @@ impl App {
     pub fn results(&mut self) -> Vec<MatchedItem> {
         if self.options.filter.is_some() {
             // In filter mode, drain items to avoid cloning
-            self.item_list.items.drain(..).collect()
+            std::mem::take(&mut self.item_list.items)
         } else if self.options.multi && !self.item_list.selection.is_empty() {
-            self.item_list.selection.clone().into_iter().collect()
+            self.item_list.selection_in(self.options.print_selection_order)
         } else if let Some(sel) = self.item_list.selected() {
             vec![sel]
         } else {
@@ -1355,6 +2246,21 @@ impl App {
     /// to process or if the previous matcher has completed.
     pub fn restart_matcher(&mut self, force: bool) {
         use crate::tui::item_list::MergeStrategy;
+        let removed = self.item_pool.take_removed();
+        let mut force = force;
+        if !removed.is_empty() {
+            self.item_list.remove_items(&removed);
+            self.damage.list = true;
+            // the running search may have matched them
+            force |= !self.matcher_control.stopped();
+        }
+        let compacted = self.item_pool.take_compacted();
+        if !compacted.is_empty() {
+            self.item_list.reindex(&compacted, &self.matcher.rank_builder);
+            // the indices of the items after them changed
+            force = true;
+        }
+
         // Check if query meets minimum length requirement
         if let Some(min_length) = self.options.min_query_length
             && !self.options.disabled
@@ -1389,12 +2295,17 @@ impl App {
             } else {
                 &self.input
             };
+            let query2 = if self.options.disabled { "" } else { &self.query2 };
             let item_pool = self.item_pool.clone();
             let thread_pool = &self.matcher_pool;
             let no_sort = self.options.no_sort;
 
             if force {
                 self.item_pool.reset();
+                self.windowed_before = 0;
+            } else {
+                // Only the new items are matched, the previous runs counted the others
+                self.windowed_before += self.matcher_control.get_num_windowed();
             }
 
             let merge_strategy = if force {
@@ -1407,6 +2318,7 @@ impl App {
 
             self.matcher_control = self.matcher.run(
                 query,
+                query2,
                 &item_pool,
                 thread_pool,
                 self.item_list.processed_items.clone(),
@@ -1420,28 +2332,110 @@ impl App {
         }
     }
 
-    fn yank(&mut self, contents: String) {
-        self.yank_register = contents;
-    }
-
     /// Expand placeholders in a command string with current app state.
     /// Replaces {}, {q}, {cq}, {n}, {+}, {+n}, and field patterns.
     ///
     /// Note: in command mode, the replstr is replaced by the current query
+    /// Placeholders that cannot be expanded are kept as they are, see
+    /// [`try_expand_cmd`](Self::try_expand_cmd).
     #[must_use]
     pub fn expand_cmd(&self, cmd: &str, quote_args: bool) -> String {
-        util::printf(
+        self.expand_cmd_lenient(cmd, quote_args).0
+    }
+
+    /// Same as [`expand_cmd`](Self::expand_cmd), failing on broken placeholders
+    ///
+    /// # Errors
+    ///
+    /// Returns the first placeholder that cannot be expanded.
+    pub fn try_expand_cmd(&self, cmd: &str, quote_args: bool) -> std::result::Result<String, PlaceholderError> {
+        match self.expand_cmd_lenient(cmd, quote_args) {
+            (_, Some(error)) => Err(error),
+            (expanded, None) => Ok(expanded),
+        }
+    }
+
+    fn expand_cmd_lenient(&self, cmd: &str, quote_args: bool) -> (String, Option<PlaceholderError>) {
+        util::printf_for_shell(
             cmd,
             &self.options.delimiter,
             &self.options.replstr,
             &self.item_list.selection.iter(),
-            &self.item_list.selected(),
+            self.item_list.selected().as_ref(),
             &self.input.value,
             &self.input.value,
-            quote_args,
+            quote_args.then_some(&self.shell),
         )
     }
 
+    /// Expand a command about to be run, showing the error in the status line
+    /// instead of running a broken command
+    fn expand_cmd_or_report(&mut self, cmd: &str, quote_args: bool) -> Option<String> {
+        self.try_expand_cmd(cmd, quote_args)
+            .inspect_err(|e| {
+                warn!("not running `{cmd}`: {e}");
+                self.status_message = Some(format!("! {e}"));
+            })
+            .ok()
+    }
+
+    /// Show a command in the status line instead of running it, for `execute-echo` and `--dry-run`
+    fn echo_cmd(&mut self, cmd: &str) {
+        info!("dry run: {cmd}");
+        self.status_message = Some(format!("$ {cmd}"));
+    }
+
+    /// Number of the lines read so far that were truncated or skipped by `--max-item-length`
+    fn num_long_items(&self) -> usize {
+        self.options
+            .cmd_collector
+            .try_borrow()
+            .map_or(0, |collector| collector.num_long_items())
+    }
+
+    /// The field sets `toggle-nth` cycles through, `--nth` first, then `--nth-cycle` or the whole
+    /// line; an empty set is the whole line
+    fn nth_sets(&self) -> Vec<String> {
+        let nth = self
+            .options
+            .nth
+            .iter()
+            .filter(|f| !f.is_empty())
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(",");
+        let mut sets = vec![nth];
+        if !self.options.nth_cycle.is_empty() {
+            sets.extend(self.options.nth_cycle.iter().cloned());
+        } else if !sets[0].is_empty() {
+            sets.push(String::new());
+        }
+        sets
+    }
+
+    /// The matcher of the options, scoped to the field set picked by `toggle-nth`
+    fn build_matcher(&self) -> Matcher {
+        let matcher = Matcher::from_options(&self.options);
+        if self.nth_index == 0 {
+            // the items were read with `--nth`
+            return matcher;
+        }
+        let fields = self.nth_sets()[self.nth_index]
+            .split(',')
+            .filter_map(|f| if f.is_empty() { None } else { FieldRange::from_str(f) })
+            .collect();
+        matcher.scope(fields, self.options.delimiter.clone())
+    }
+
+    /// Matches the query again, or stops matching and shows all the items, as `--disabled`
+    fn set_search(&mut self, enabled: bool) {
+        if self.options.disabled != enabled {
+            return;
+        }
+        self.options.disabled = !enabled;
+        self.restart_matcher(true);
+    }
+
     /// Restart matcher with debouncing to avoid excessive restarts during rapid typing
     fn restart_matcher_debounced(&mut self) {
         const DEBOUNCE_MS: u64 = 10;
@@ -1460,6 +2454,31 @@ impl App {
         }
     }
 
+    /// Draws `--border-label` over the top (or bottom) border of all the panes
+    fn render_border_label(&self, buf: &mut Buffer) {
+        let Some(label) = self.options.border_label.as_deref() else {
+            return;
+        };
+        let layout = &self.layout;
+        let outer = [layout.header_area, layout.preview_area]
+            .into_iter()
+            .flatten()
+            .fold(layout.list_area.union(layout.input_area), Rect::union);
+        let position = LabelPosition::from(self.options.border_label_pos.as_deref().unwrap_or_default());
+        // between the corners of the border
+        let row = Rect {
+            x: outer.x + 1,
+            y: if position.bottom {
+                outer.bottom().saturating_sub(1)
+            } else {
+                outer.y
+            },
+            width: outer.width.saturating_sub(2),
+            height: outer.height.min(1),
+        };
+        position.title(Block::new(), label, self.theme.label).render(row, buf);
+    }
+
     /// Returns the border-adjusted inner rect of the list area.
     fn list_inner_area(&self) -> ratatui::layout::Rect {
         let list_area = self.layout.list_area;
@@ -74,6 +74,24 @@ fn load_waits_until_all_items_are_consumed() {
     assert!(app.poll_completion_events().is_empty());
 }
 
+#[test]
+fn exit_0_on_reload_accepts_an_empty_read() {
+    let accepts = |app: &mut App| {
+        app.poll_completion_events()
+            .iter()
+            .any(|event| matches!(event, Event::Action(Action::Accept(None))))
+    };
+    let mut app = App::default();
+    app.reader_done = true;
+    assert!(!accepts(&mut app));
+
+    // every read completes a `load`, reloads included
+    app.options.exit_0_on_reload = true;
+    app.load_event_fired = false;
+    assert!(accepts(&mut app));
+    assert!(!accepts(&mut app));
+}
+
 #[test]
 fn cardinality_events_wait_for_reader_completion() {
     let mut app = App::default();
@@ -99,6 +117,32 @@ fn cardinality_events_wait_for_reader_completion() {
     );
 }
 
+#[test]
+fn zero_result_shows_near_misses() {
+    let mut app = App::default();
+    app.options.suggest_on_empty = Some(1);
+    app.item_pool
+        .append(vec![Arc::new("main.rs".to_string()), Arc::new("lib.rs".to_string())]);
+    app.input.value = "mian".to_string();
+    app.reader_done = true;
+    app.result_pending = true;
+    app.poll_completion_events();
+
+    assert!(app.item_list.suggestions);
+    assert_eq!(app.item_list.count(), 0);
+    let texts: Vec<_> = app
+        .item_list
+        .items
+        .iter()
+        .map(|item| item.text().into_owned())
+        .collect();
+    assert_eq!(texts, vec!["main.rs"]);
+
+    // the next search result replaces them
+    app.item_list.append(&mut vec![matched("lib.rs", 1)]);
+    assert!(!app.item_list.suggestions);
+}
+
 #[test]
 fn add_char_updates_query_and_emits_events() {
     let mut app = App::default();
@@ -170,33 +214,79 @@ fn delete_char_eof_quits_on_empty() {
 }
 
 #[test]
-fn kill_line_and_word_fill_yank_register() {
+fn kill_line_and_word_fill_the_kill_ring() {
     // KillLine from the start of the line removes everything to the right and
-    // stores it in the yank register.
+    // stores it in the kill ring.
     let mut app = App::default();
     app.input.value = "foo bar".to_string();
     act(&mut app, Action::BeginningOfLine);
     act(&mut app, Action::KillLine);
     assert_eq!(app.input.value, "");
-    assert_eq!(app.yank_register, "foo bar");
+    assert_eq!(app.input.kill_ring.top(), Some("foo bar"));
 
-    // BackwardKillWord removes the word before the cursor into the register.
+    // BackwardKillWord removes the word before the cursor into the ring.
     let mut app = App::default();
     app.input.value = "foo bar".to_string();
     app.input.move_to_end();
     act(&mut app, Action::BackwardKillWord);
     assert_eq!(app.input.value, "foo ");
-    assert_eq!(app.yank_register, "bar");
+    assert_eq!(app.input.kill_ring.top(), Some("bar"));
 }
 
 #[test]
-fn yank_inserts_register_contents() {
+fn yank_inserts_the_last_killed_text() {
     let mut app = App::default();
-    app.yank_register = "pasted".to_string();
+    app.input.kill_ring.push("pasted".to_string());
     act(&mut app, Action::Yank);
     assert_eq!(app.input.value, "pasted");
 }
 
+#[test]
+fn yank_pop_cycles_through_the_kill_ring_right_after_a_yank() {
+    let mut app = App::default();
+    app.input.value = "one two three".to_string();
+    app.input.move_to_end();
+    act(&mut app, Action::UnixWordRubout);
+    act(&mut app, Action::UnixWordRubout);
+    assert_eq!(app.input.value, "one ");
+    act(&mut app, Action::Yank);
+    assert_eq!(app.input.value, "one two ");
+    act(&mut app, Action::YankPop);
+    assert_eq!(app.input.value, "one three");
+    act(&mut app, Action::YankPop);
+    assert_eq!(app.input.value, "one two ");
+
+    // any other action ends the yank
+    act(&mut app, Action::BackwardChar);
+    act(&mut app, Action::ForwardChar);
+    act(&mut app, Action::YankPop);
+    assert_eq!(app.input.value, "one two ");
+}
+
+#[test]
+fn undo_restores_the_query_typed_characters_together() {
+    let mut app = App::default();
+    for c in "foo".chars() {
+        act(&mut app, Action::AddChar(c));
+    }
+    act(&mut app, Action::AddChar(' '));
+    act(&mut app, Action::BackwardChar);
+    for c in "bar".chars() {
+        act(&mut app, Action::AddChar(c));
+    }
+    act(&mut app, Action::UnixLineDiscard);
+    assert_eq!(app.input.value, " ");
+
+    act(&mut app, Action::Undo);
+    assert_eq!((app.input.value.as_str(), app.input.cursor_pos), ("foobar ", 6));
+    act(&mut app, Action::Undo);
+    assert_eq!((app.input.value.as_str(), app.input.cursor_pos), ("foo ", 3));
+    act(&mut app, Action::Undo);
+    assert_eq!(app.input.value, "");
+    act(&mut app, Action::Undo);
+    assert_eq!(app.input.value, "");
+}
+
 #[test]
 fn unix_line_discard_and_word_rubout() {
     let mut app = App::default();
@@ -207,7 +297,48 @@ fn unix_line_discard_and_word_rubout() {
     assert!(!app.input.value.ends_with("world"));
 
     act(&mut app, Action::UnixLineDiscard);
-    assert!(app.input.value.is_empty());
+    assert_eq!(app.input.value, "");
+}
+
+#[test]
+fn tab_complete_extends_the_token_to_the_common_prefix() {
+    let mut app = app_with_items(&[
+        "src/tui/app.rs: fn handle_action",
+        "src/tui/app.rs: fn handle_key",
+        "src/tui/input.rs: fn insert",
+    ]);
+    app.input.insert_str("'hand");
+    act(&mut app, Action::TabComplete);
+    assert_eq!(app.input.value, "'handle_");
+    assert_eq!(app.input.cursor_pos, 8);
+
+    // nothing more in common: the candidates are listed
+    act(&mut app, Action::TabComplete);
+    assert_eq!(app.input.value, "'handle_");
+    assert_eq!(app.status_message.as_deref(), Some("handle_action handle_key"));
+
+    // words start at word boundaries
+    app.input.value = "x tu".to_string();
+    app.input.move_to_end();
+    act(&mut app, Action::TabComplete);
+    assert_eq!(app.input.value, "x tui/");
+}
+
+#[test]
+fn put_and_replace_query_edit_the_query() {
+    let mut app = app_with_items(&["src/main.rs", "src/lib.rs"]);
+    act(&mut app, Action::ReplaceQuery);
+    assert_eq!(app.input.value, "src/main.rs");
+    act(&mut app, Action::BeginningOfLine);
+    act(&mut app, Action::Put("vim {} ".to_string()));
+    // the text is inserted as is, placeholders included
+    assert_eq!(app.input.value, "vim {} src/main.rs");
+    assert_eq!(app.input.cursor_pos, 7);
+
+    let mut app = App::default();
+    app.input.insert_str("query");
+    act(&mut app, Action::ReplaceQuery);
+    assert_eq!(app.input.value, "query");
 }
 
 #[test]
@@ -365,6 +496,30 @@ fn set_query_and_header_and_preview_cmd() {
     assert!(events.iter().any(|e| matches!(e, Event::RunPreview)));
 }
 
+#[test]
+fn preview_action_overrides_until_focus_changes() {
+    let mut app = app_with_items(&["a", "b", "c"]);
+    let events = act(&mut app, Action::Preview("echo alt {}".to_string()));
+    assert_eq!(app.preview_override.as_deref(), Some("echo alt {}"));
+    assert!(events.iter().any(|e| matches!(e, Event::RunPreview)));
+
+    // Re-running the preview on the same item keeps the override.
+    assert!(app.take_focus_event().is_none());
+    assert_eq!(app.preview_override.as_deref(), Some("echo alt {}"));
+
+    act(&mut app, Action::Up(1));
+    assert!(app.preview_override.is_none());
+}
+
+#[test]
+fn refresh_preview_clears_preview_override() {
+    let mut app = app_with_items(&["a", "b"]);
+    act(&mut app, Action::Preview("echo alt".to_string()));
+    act(&mut app, Action::RefreshPreview);
+    assert!(app.preview_override.is_none());
+    assert!(app.options.preview.is_none());
+}
+
 #[test]
 fn clear_screen_and_redraw_emit_clear() {
     let mut app = App::default();
@@ -394,6 +549,56 @@ fn refresh_cmd_reloads_in_interactive_mode() {
     assert!(act(&mut app, Action::RefreshCmd).is_empty());
 }
 
+#[test]
+fn secondary_query_filters_the_command_output_in_interactive_mode() {
+    let mut app = App::default();
+    app.options.interactive = true;
+    app.options.cmd = Some("rg {}".to_string());
+    app.cmd = "rg {}".to_string();
+    let reloads = |events: &[Event]| events.iter().any(|e| matches!(e, Event::Reload(_)));
+
+    assert!(reloads(&act(&mut app, Action::AddChar('a'))));
+
+    // the fuzzy filter is edited without running the command again
+    act(&mut app, Action::ToggleQuery2);
+    assert!(!reloads(&act(&mut app, Action::AddChar('b'))));
+    assert_eq!(app.input.value, "a");
+    assert_eq!(app.query2.value, "b");
+
+    act(&mut app, Action::ToggleQuery2);
+    assert!(reloads(&act(&mut app, Action::AddChar('c'))));
+    assert_eq!(app.input.value, "ac");
+    assert_eq!(app.query2.value, "b");
+}
+
+#[test]
+fn secondary_query_edits_run_the_matcher_with_the_queries_in_place() {
+    let mut app = App::default();
+    app.options.interactive = true;
+    app.options.cmd = Some("rg {}".to_string());
+    app.cmd = "rg {}".to_string();
+    app.handle_items(vec![Arc::new("apple".to_string()), Arc::new("banana".to_string())]);
+    act(&mut app, Action::AddChar('p'));
+    act(&mut app, Action::ToggleQuery2);
+
+    app.last_matcher_restart = past_instant(std::time::Duration::from_secs(1));
+    act(&mut app, Action::AddChar('n'));
+    let start = std::time::Instant::now();
+    while !app.matcher_control.stopped() && start.elapsed() < std::time::Duration::from_secs(5) {
+        std::thread::sleep(std::time::Duration::from_millis(2));
+    }
+    app.item_list.merge_processed();
+    // filtered by the secondary query, not by the command query `p`
+    let texts: Vec<_> = app
+        .item_list
+        .items
+        .iter()
+        .map(|item| item.text().into_owned())
+        .collect();
+    assert_eq!(texts, vec!["banana"]);
+    assert_eq!(app.input.value, "p");
+}
+
 #[test]
 fn reload_actions_emit_reload() {
     let mut app = app_with_items(&["a"]);
@@ -624,6 +829,50 @@ fn execute_action_expands_and_defers_to_event_loop() {
     );
 }
 
+#[test]
+fn broken_placeholder_is_reported_instead_of_executed() {
+    let mut app = App::default();
+    let events = act(&mut app, Action::Execute("echo {q".to_string()));
+    assert!(events.is_empty(), "expected nothing to run, got {events:?}");
+    assert_eq!(app.status_message.as_deref(), Some("! unterminated placeholder `{q`"));
+    assert!(act(&mut app, Action::Reload(Some("ls {+x}".to_string()))).is_empty());
+    assert!(app.status_message.as_deref().unwrap().contains("{+x}"));
+
+    app.handle_key(&KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE));
+    assert!(app.status_message.is_none());
+}
+
+#[cfg(unix)]
+#[test]
+fn dry_run_shows_the_command_instead_of_running_it() {
+    let mut app = App::default();
+    app.input.value = "hello".to_string();
+    assert!(act(&mut app, Action::ExecuteEcho("echo {q}".to_string())).is_empty());
+    assert_eq!(app.status_message.as_deref(), Some("$ echo 'hello'"));
+
+    app.status_message = None;
+    app.options.dry_run = true;
+    assert!(act(&mut app, Action::Execute("less {q}".to_string())).is_empty());
+    assert_eq!(app.status_message.as_deref(), Some("$ less 'hello'"));
+    assert!(act(&mut app, Action::ExecuteSilent("rm {q}".to_string())).is_empty());
+    assert_eq!(app.status_message.as_deref(), Some("$ rm 'hello'"));
+}
+
+#[cfg(unix)]
+#[test]
+fn tmux_actions_show_the_tmux_command_on_dry_run() {
+    let mut app = app_with_items(&["a b", "c"]);
+    app.input.value = "hello".to_string();
+    app.options.dry_run = true;
+    assert!(act(&mut app, Action::ExecuteTmux("display-message {q}".to_string())).is_empty());
+    assert_eq!(app.status_message.as_deref(), Some("$ tmux display-message 'hello'"));
+    assert!(act(&mut app, Action::SendToTmux(":2".to_string())).is_empty());
+    assert_eq!(
+        app.status_message.as_deref(),
+        Some("$ tmux send-keys -t :2 -l -- 'a b'")
+    );
+}
+
 #[test]
 fn run_execute_event_runs_command_and_restarts_reader() {
     // Driving `Event::RunExecute` through `handle_event` should run the command
@@ -653,6 +902,17 @@ fn run_execute_event_runs_command_and_restarts_reader() {
     assert!(tui.task.is_some(), "reader task should be running after execute");
 }
 
+#[test]
+fn pick_hands_the_expanded_command_to_the_event_loop() {
+    let mut app = App::default();
+    app.input.value = "src".to_string();
+    let events = act(&mut app, Action::Pick("ls {q}".to_string()));
+    assert!(
+        matches!(events.as_slice(), [Event::RunPick(cmd)] if cmd.contains("src")),
+        "expected RunPick with expanded query, got {events:?}"
+    );
+}
+
 #[test]
 fn if_non_matched_branches() {
     let mut app = App::default();
@@ -692,6 +952,54 @@ fn history_navigation_query_mode() {
     assert_eq!(app.input.value, "");
 }
 
+#[test]
+fn history_search_finds_substring_matches() {
+    let mut app = App::default();
+    app.options.interactive = true;
+    app.cmd_history = vec!["git log".into(), "ls -la".into(), "git status".into()];
+    app.input.prompt = "c> ".into();
+    app.input.value = "typed".into();
+    let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+
+    act(&mut app, Action::HistorySearch);
+    assert_eq!(app.input.prompt, "(history '') ");
+    app.handle_key(&key('g'));
+    app.handle_key(&key('i'));
+    assert_eq!(app.input.value, "git status");
+    assert_eq!(app.input.prompt, "(history 'gi') ");
+
+    // Searching again moves to the next older match.
+    act(&mut app, Action::HistorySearch);
+    assert_eq!(app.input.value, "git log");
+    act(&mut app, Action::HistorySearch);
+    assert_eq!(app.input.value, "git log");
+    assert_eq!(app.input.prompt, "(failed history 'gi') ");
+
+    // Enter keeps the match and restores the prompt.
+    assert!(
+        app.handle_key(&KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+            .is_empty()
+    );
+    assert_eq!(app.input.value, "git log");
+    assert_eq!(app.input.prompt, "c> ");
+}
+
+#[test]
+fn history_search_escape_restores_input() {
+    let mut app = App::default();
+    app.query_history = vec!["alpha".into(), "beta".into()];
+    app.input.value = "typed".into();
+
+    act(&mut app, Action::HistorySearch);
+    app.handle_key(&KeyEvent::new(KeyCode::Char('l'), KeyModifiers::NONE));
+    assert_eq!(app.input.value, "alpha");
+    app.handle_key(&KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE));
+    assert_eq!(app.input.value, "alpha");
+    app.handle_key(&KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+    assert_eq!(app.input.value, "typed");
+    assert!(app.history_search.is_none());
+}
+
 #[test]
 fn history_navigation_empty_is_noop() {
     let mut app = App::default();
@@ -781,6 +1089,33 @@ fn custom_action_runs_async_callback() {
     assert!(events.iter().any(|e| matches!(e, Event::Action(Action::Abort))));
 }
 
+#[test]
+fn plugin_action_runs_the_registered_callback() {
+    use crate::tui::event::AppHandle;
+    let mut app = App::default();
+    app.options.actions.register("shout", |app: &AppHandle| {
+        assert!(app.current().is_none() && app.selection().is_empty());
+        // no event loop to send to
+        assert!(!app.send(Event::Render));
+        Ok(vec![Event::Action(Action::SetQuery(app.query().to_uppercase()))])
+    });
+    app.input.value = "hello".to_string();
+    let events = act(&mut app, Action::Plugin("shout".to_string()));
+    assert!(matches!(events.as_slice(), [Event::Action(Action::SetQuery(q))] if q == "HELLO"));
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel(1);
+    app.event_tx = Some(tx);
+    app.options.actions.register("later", |app: &AppHandle| {
+        assert!(app.send(Event::Action(Action::Abort)));
+        Ok(Vec::new())
+    });
+    assert!(act(&mut app, Action::Plugin("later".to_string())).is_empty());
+    assert!(matches!(rx.try_recv(), Ok(Event::Action(Action::Abort))));
+
+    assert!(act(&mut app, Action::Plugin("missing".to_string())).is_empty());
+    assert_eq!(app.status_message.as_deref(), Some("! unknown plugin action `missing`"));
+}
+
 #[test]
 fn bind_action_adds_action_chain() {
     let mut app = App::default();
@@ -945,6 +1280,15 @@ fn expand_cmd_substitutes_query() {
     assert!(expanded.contains("myquery"));
 }
 
+#[test]
+fn expand_cmd_quotes_for_the_configured_shell() {
+    let mut options = SkimOptions::default();
+    options.with_shell = Some("pwsh".into());
+    let mut app = App::from_options(options, Arc::new(crate::theme::ColorTheme::default()), String::new());
+    app.input.value = "it's".to_string();
+    assert_eq!(app.expand_cmd("echo {q}", true), "echo 'it''s'");
+}
+
 #[test]
 fn calculate_preview_offset_variants() {
     let app = App::default();
@@ -955,6 +1299,16 @@ fn calculate_preview_offset_variants() {
     assert_eq!(app.calculate_preview_offset("+2-5"), 0);
 }
 
+#[test]
+fn calculate_preview_offset_fraction_of_height() {
+    let mut app = App::default();
+    app.preview.rows = 20;
+    assert_eq!(app.calculate_preview_offset("+30-/2"), 20);
+    assert_eq!(app.calculate_preview_offset("+30-/4"), 25);
+    // A zero denominator is ignored rather than panicking.
+    assert_eq!(app.calculate_preview_offset("+30-/0"), 30);
+}
+
 #[test]
 fn results_single_selection() {
     let mut app = app_with_items(&["a", "b", "c"]);
@@ -977,7 +1331,21 @@ fn results_filter_mode_drains_items() {
     app.options.filter = Some("x".to_string());
     let results = app.results();
     assert_eq!(results.len(), 2);
-    assert!(app.item_list.items.is_empty());
+    assert_eq!(app.item_list.items, []);
+}
+
+#[test]
+fn state_env_describes_the_query_and_the_list() {
+    let mut app = app_with_items(&["a", "b", "c"]);
+    app.input.value = "q".to_string();
+    act(&mut app, Action::Up(1));
+    let env = app.state_env();
+    let var = |key: &str| env.iter().find(|(k, _)| *k == key).map(|(_, v)| v.as_str());
+    assert_eq!(var("SKIM_QUERY"), Some("q"));
+    assert_eq!(var("SKIM_CMD_QUERY"), Some("q"));
+    assert_eq!(var("SKIM_POS"), Some("2"));
+    assert_eq!(var("SKIM_MATCH_COUNT"), Some("3"));
+    assert_eq!(var("SKIM_SELECT_COUNT"), Some("0"));
 }
 
 #[test]
@@ -987,6 +1355,20 @@ fn resize_updates_layout() {
     assert_eq!(app.layout.list_area.width, 120);
 }
 
+#[test]
+fn resize_reruns_only_the_previews_sized_by_placeholders() {
+    let mut app = App::default();
+    app.options.preview = Some("cat {}".to_string());
+    app.options.preview_window.offset = Some("+{n}-/2".to_string());
+    app.layout_template = LayoutTemplate::from_options(&app.options, app.header.height());
+    app.resize(120, 40);
+    assert_eq!((app.preview.rows, app.preview.cols), (40, 60));
+    assert!(!app.preview_needs_rerun());
+
+    app.options.preview = Some("head -n {lines} {}".to_string());
+    assert!(app.preview_needs_rerun());
+}
+
 #[test]
 fn resize_fixed_updates_layout() {
     let mut app = App::default();
@@ -1000,7 +1382,7 @@ fn restart_matcher_short_query_clears_items() {
     app.options.min_query_length = Some(3);
     app.input.value = "ab".to_string();
     app.restart_matcher(true);
-    assert!(app.item_list.items.is_empty());
+    assert_eq!(app.item_list.items, []);
 }
 
 #[test]
@@ -1021,6 +1403,72 @@ fn restart_matcher_no_sort_uses_append_strategy() {
     assert!(!app.should_quit);
 }
 
+#[test]
+fn windowed_items_add_up_across_incremental_matcher_runs() {
+    let mut app = App::default();
+    let options = crate::prelude::SkimOptionsBuilder::default()
+        .match_window(4usize)
+        .build()
+        .unwrap();
+    app.matcher = crate::matcher::Matcher::from_options(&options);
+    let wait = |app: &App| {
+        let start = std::time::Instant::now();
+        while !app.matcher_control.stopped() && start.elapsed() < std::time::Duration::from_secs(5) {
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+    };
+    let windowed = |app: &App| app.windowed_before + app.matcher_control.get_num_windowed();
+
+    app.handle_items(vec![Arc::new("ab".to_string()), Arc::new("abcdefgh".to_string())]);
+    app.restart_matcher(false);
+    wait(&app);
+    app.handle_items(vec![Arc::new("abcdefghij".to_string())]);
+    app.restart_matcher(false);
+    wait(&app);
+    assert_eq!(windowed(&app), 2);
+
+    // A new search matches everything again
+    app.restart_matcher(true);
+    wait(&app);
+    assert_eq!(windowed(&app), 2);
+}
+
+#[test]
+fn removed_items_leave_the_list_and_are_not_matched_again() {
+    let mut app = App::default();
+    let wait = |app: &App| {
+        let start = std::time::Instant::now();
+        while !app.matcher_control.stopped() && start.elapsed() < std::time::Duration::from_secs(5) {
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+    };
+    let texts = |app: &mut App| {
+        app.item_list.merge_processed();
+        let mut texts: Vec<_> = app
+            .item_list
+            .items
+            .iter()
+            .map(|item| item.text().into_owned())
+            .collect();
+        texts.sort();
+        texts
+    };
+
+    app.handle_items(vec![Arc::new("a".to_string()), Arc::new("b".to_string())]);
+    app.restart_matcher(false);
+    wait(&app);
+    assert_eq!(texts(&mut app), vec!["a", "b"]);
+
+    app.handle_items(vec![Arc::new(crate::item::RemovedItem::new("a"))]);
+    app.restart_matcher(false);
+    wait(&app);
+    assert_eq!(texts(&mut app), vec!["b"]);
+
+    app.restart_matcher(true);
+    wait(&app);
+    assert_eq!(texts(&mut app), vec!["b"]);
+}
+
 // ---------------------------------------------------------------------------
 // Rendering — the `Widget for &mut App` path, driven with a ratatui Buffer.
 // ---------------------------------------------------------------------------
@@ -1047,6 +1495,21 @@ fn render_default_app_populates_buffer() {
     assert!(app.cursor_pos.0 <= 80);
 }
 
+#[test]
+fn render_keeps_the_undamaged_list_from_the_last_frame() {
+    let mut app = app_with_items(&["alpha", "beta"]);
+    let _ = render(&mut app, 40, 10);
+    assert_eq!(app.damage, Damage::NONE);
+
+    // nothing marked the list damaged: its rows are copied from the last frame
+    app.item_list.clear();
+    assert!(buffer_to_string(&render(&mut app, 40, 10)).contains("alpha"));
+
+    // actions damage every widget
+    act(&mut app, Action::Down(1));
+    assert!(!buffer_to_string(&render(&mut app, 40, 10)).contains("alpha"));
+}
+
 #[test]
 fn render_with_preview_and_border() {
     let mut app = app_with_items(&["one", "two"]);
@@ -1058,6 +1521,21 @@ fn render_with_preview_and_border() {
     assert!(app.pending_preview_run || app.layout.preview_area.is_some());
 }
 
+#[test]
+fn toggle_stderr_shows_the_log_above_the_list() {
+    let mut app = app_with_items(&["one", "two"]);
+    app.stderr_pane.log.push("source", "rg: no such file");
+    act(&mut app, Action::ToggleStderr);
+    let rendered = buffer_to_string(&render(&mut app, 40, 12));
+    assert!(rendered.contains("[source] rg: no such file"));
+    assert_eq!(app.layout.stderr_area.map(|area| area.y), Some(0));
+
+    act(&mut app, Action::ToggleStderr);
+    let rendered = buffer_to_string(&render(&mut app, 40, 12));
+    assert!(!rendered.contains("rg: no such file"));
+    assert!(app.layout.stderr_area.is_none());
+}
+
 #[test]
 fn render_hidden_info_sets_no_status() {
     let mut app = app_with_items(&["x"]);
@@ -1075,6 +1553,37 @@ fn render_regex_mode_reports_re_indicator() {
     assert_eq!(info.matcher_mode, "RE");
 }
 
+#[test]
+fn jump_labels_the_visible_items_and_moves_to_the_typed_one() {
+    let mut app = app_with_items(&["alpha", "beta", "gamma"]);
+    act(&mut app, Action::Jump);
+    let rendered = buffer_to_string(&render(&mut app, 40, 10));
+    assert!(rendered.contains("a alpha"));
+    assert!(rendered.contains("s beta"));
+    assert!(rendered.contains("d gamma"));
+
+    app.handle_key(&KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+    assert!(!app.item_list.jumping);
+    assert_eq!(app.item_list.current, 2);
+    assert!(!buffer_to_string(&render(&mut app, 40, 10)).contains("a alpha"));
+}
+
+#[test]
+fn jump_accept_accepts_the_typed_item_and_other_keys_cancel() {
+    let mut app = app_with_items(&["alpha", "beta", "gamma"]);
+    let _ = render(&mut app, 40, 10);
+    act(&mut app, Action::JumpAccept);
+    let events = app.handle_key(&KeyEvent::new(KeyCode::Char('s'), KeyModifiers::NONE));
+    assert_eq!(app.item_list.current, 1);
+    assert!(events.iter().any(|e| matches!(e, Event::Action(Action::Accept(None)))));
+
+    act(&mut app, Action::JumpAccept);
+    let events = app.handle_key(&KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+    assert!(events.is_empty());
+    assert!(!app.item_list.jumping);
+    assert_eq!(app.item_list.current, 1);
+}
+
 fn buffer_to_string(buf: &Buffer) -> String {
     let mut out = String::new();
     for y in 0..buf.area.height {
@@ -1155,6 +1664,93 @@ fn run_preview_callback_with_no_items() {
     app.run_preview(&mut tui).unwrap();
 }
 
+#[test]
+fn run_preview_builtin_file_reads_the_current_item() {
+    use std::io::Write;
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(b"first\nsecond\n").unwrap();
+    let path = file.path().to_str().unwrap().to_string();
+    let mut app = app_with_items(&[&path]);
+    app.options.preview = Some("builtin:file".into());
+    app.last_preview_spawn = past_instant(Duration::from_secs(1));
+
+    let mut tui = test_tui();
+    app.run_preview(&mut tui).unwrap();
+    app.preview.thread_handle.take().unwrap().join().unwrap();
+    let content = app.preview.content.read().unwrap();
+    let crate::tui::preview::PreviewContent::Text(text) = &*content else {
+        panic!("expected text content");
+    };
+    assert_eq!(text.lines[1].to_string(), "2 second");
+}
+
+#[test]
+fn run_preview_prefetches_adjacent_items() {
+    let mut options = SkimOptions::default();
+    options.preview = Some("echo {}".into());
+    options.preview_prefetch = 1;
+    let mut app = App::from_options(options, Arc::new(crate::theme::ColorTheme::default()), String::new());
+    let mut items: Vec<MatchedItem> = ["a", "b", "c"]
+        .iter()
+        .enumerate()
+        .map(|(i, t)| matched(t, i32::try_from(i).unwrap()))
+        .collect();
+    app.item_list.append(&mut items);
+    app.item_list.current = 1;
+    app.last_preview_spawn = past_instant(Duration::from_secs(1));
+
+    let mut tui = test_tui();
+    app.run_preview(&mut tui).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let cache = app.preview.cache.clone().unwrap();
+    let key = |item: &str| {
+        if cfg!(windows) {
+            format!("echo {item}")
+        } else {
+            format!("echo '{item}'")
+        }
+    };
+    while cache.lock().unwrap().get(&key("a")).is_none() || cache.lock().unwrap().get(&key("c")).is_none() {
+        assert!(Instant::now() < deadline, "prefetch did not complete");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    // Moving to a prefetched item shows it without running the command again.
+    app.item_list.current = 2;
+    app.last_preview_spawn = past_instant(Duration::from_secs(1));
+    app.run_preview(&mut tui).unwrap();
+    assert!(
+        app.preview
+            .thread_handle
+            .as_ref()
+            .is_none_or(std::thread::JoinHandle::is_finished)
+    );
+    let content = app.preview.content.read().unwrap();
+    let crate::tui::preview::PreviewContent::Text(text) = &*content else {
+        panic!("expected text content");
+    };
+    assert_eq!(text.to_string().trim(), "c");
+}
+
+#[test]
+fn run_preview_text_callback_keeps_styles() {
+    use crate::tui::PreviewCallback;
+    use ratatui::style::Stylize;
+    let mut app = app_with_items(&["a", "b"]);
+    app.options.preview = None;
+    app.options.preview_fn = Some(PreviewCallback::text(|items: Vec<Arc<dyn SkimItem>>| {
+        items
+            .iter()
+            .map(|i| ratatui::text::Line::from(i.text().into_owned().bold()))
+            .collect()
+    }));
+    app.last_preview_spawn = past_instant(Duration::from_secs(1));
+
+    let mut tui = test_tui();
+    app.run_preview(&mut tui).unwrap();
+    assert_eq!(app.preview.total_lines, 1);
+}
+
 #[test]
 fn scroll_moves_current_based_on_position() {
     let mut app = app_with_items(&["a", "b", "c", "d", "e", "f", "g", "h"]);
@@ -1243,6 +1839,16 @@ fn handle_event_append_items_grows_pool() {
     assert_eq!(app.item_pool.len(), 1);
 }
 
+#[test]
+fn handle_event_watched_counts_and_reloads() {
+    let mut app = App::default();
+    app.watch_reloads = Some(0);
+    let mut tui = test_tui();
+    app.handle_event(&mut tui, &Event::Watched).unwrap();
+    assert_eq!(app.watch_reloads, Some(1));
+    assert!(drain_events(&mut tui).iter().any(|e| matches!(e, Event::Reload(_))));
+}
+
 #[test]
 fn handle_event_action_emits_render() {
     let mut app = App::default();
@@ -1334,7 +1940,7 @@ fn handle_event_resize_reflows_and_reruns_preview() {
     let mut app = app_with_items(&["a", "b"]);
     let _ = render(&mut app, 40, 10);
     let mut tui = test_tui();
-    // Resize updates the cached layout and triggers a preview re-run.
+    // Resize updates the cached layout; the preview only runs again when sized by placeholders.
     app.handle_event(&mut tui, &Event::Resize(60, 20)).unwrap();
     assert_eq!(app.layout.list_area.width, 60);
 }
@@ -1903,3 +2509,145 @@ fn run_preview_command_with_position_neg_and_percent() {
     assert_eq!(app.preview.scroll_x, 74);
     app.preview.kill();
 }
+
+#[test]
+fn exit_hooks_run_the_command_of_the_way_skim_ends() {
+    let dir = tempfile::tempdir().unwrap();
+    let log = dir.path().join("log");
+    let mut app = app_with_items(&["alpha", "beta"]);
+    app.options.on_accept = Some(format!("echo accepted {{}} > {}", log.display()));
+    app.options.on_abort = Some(format!("echo aborted > {}", log.display()));
+
+    act(&mut app, Action::Abort);
+    assert_eq!(std::fs::read_to_string(&log).unwrap(), "aborted\n");
+    act(&mut app, Action::Accept(None));
+    assert_eq!(std::fs::read_to_string(&log).unwrap(), "accepted alpha\n");
+}
+
+#[test]
+fn search_actions_stop_and_resume_matching_the_query() {
+    let mut app = App::default();
+    let wait = |app: &App| {
+        let start = std::time::Instant::now();
+        while !app.matcher_control.stopped() && start.elapsed() < std::time::Duration::from_secs(5) {
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+    };
+    let count = |app: &mut App| {
+        wait(app);
+        app.item_list.merge_processed();
+        app.item_list.items.len()
+    };
+
+    app.handle_items(vec![Arc::new("apple".to_string()), Arc::new("berry".to_string())]);
+    app.input.value = "app".to_string();
+    app.restart_matcher(true);
+    assert_eq!(count(&mut app), 1);
+
+    act(&mut app, Action::DisableSearch);
+    assert!(app.options.disabled);
+    assert_eq!(count(&mut app), 2);
+    // typing does not search while disabled
+    act(&mut app, Action::AddChar('x'));
+    assert_eq!(count(&mut app), 2);
+
+    act(&mut app, Action::ToggleSearch);
+    assert!(!app.options.disabled);
+    assert_eq!(count(&mut app), 0);
+
+    act(&mut app, Action::ToggleSearch);
+    act(&mut app, Action::EnableSearch);
+    assert!(!app.options.disabled);
+}
+
+#[test]
+fn toggle_nth_cycles_the_matched_fields() {
+    let mut app = App::default();
+    app.options.delimiter = regex::Regex::new("/").unwrap();
+    app.options.nth_cycle = vec!["-1".to_string(), String::new()];
+    let wait = |app: &App| {
+        let start = std::time::Instant::now();
+        while !app.matcher_control.stopped() && start.elapsed() < std::time::Duration::from_secs(5) {
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+    };
+    let count = |app: &mut App| {
+        wait(app);
+        app.item_list.merge_processed();
+        app.item_list.items.len()
+    };
+    let nth = |app: &mut App| {
+        let _ = render(app, 40, 10);
+        app.input.status_info.as_ref().and_then(|info| info.nth.clone())
+    };
+
+    app.handle_items(vec![
+        Arc::new("src/app.rs".to_string()),
+        Arc::new("lib/main.rs".to_string()),
+    ]);
+    app.input.value = "src".to_string();
+    app.restart_matcher(true);
+    assert_eq!(count(&mut app), 1);
+    assert_eq!(nth(&mut app), None);
+
+    // the file name only
+    act(&mut app, Action::ToggleNth);
+    assert_eq!(count(&mut app), 0);
+    assert_eq!(nth(&mut app).as_deref(), Some("-1"));
+
+    act(&mut app, Action::ToggleNth);
+    assert_eq!(count(&mut app), 1);
+    assert_eq!(nth(&mut app).as_deref(), Some(".."));
+
+    // back to --nth
+    act(&mut app, Action::ToggleNth);
+    assert_eq!(nth(&mut app), None);
+}
+
+#[test]
+fn tac_keeps_equal_ranks_in_order_across_batches() {
+    let mut app = App::default();
+    let options = crate::prelude::SkimOptionsBuilder::default().tac(true).build().unwrap();
+    app.item_pool = Arc::new(crate::item::ItemPool::from_options(&options));
+    app.matcher = crate::matcher::Matcher::from_options(&options);
+    let wait = |app: &App| {
+        let start = std::time::Instant::now();
+        while !app.matcher_control.stopped() && start.elapsed() < std::time::Duration::from_secs(5) {
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+    };
+    let texts = |app: &mut App| {
+        app.item_list.merge_processed();
+        app.item_list
+            .items
+            .iter()
+            .map(|item| item.text().into_owned())
+            .collect::<Vec<_>>()
+    };
+
+    app.handle_items(vec![Arc::new("a".to_string()), Arc::new("b".to_string())]);
+    app.restart_matcher(false);
+    wait(&app);
+    app.handle_items(vec![Arc::new("c".to_string()), Arc::new("d".to_string())]);
+    app.restart_matcher(false);
+    wait(&app);
+    assert_eq!(texts(&mut app), vec!["d", "c", "b", "a"]);
+
+    // A new search gives the same order as the batches
+    app.restart_matcher(true);
+    wait(&app);
+    assert_eq!(texts(&mut app), vec!["d", "c", "b", "a"]);
+}
+
+#[test]
+fn typing_defers_the_input_with_defer_ingest() {
+    let mut app = App::default();
+    act(&mut app, Action::AddChar('a'));
+    assert!(!app.typing.is_typing());
+
+    app.typing = TypingSignal::new(std::time::Duration::from_secs(60));
+    assert!(!app.typing.is_typing());
+    act(&mut app, Action::AddChar('b'));
+    assert_eq!(app.input.value, "ab");
+    assert!(app.typing.is_typing());
+}
@@ -18,6 +18,7 @@ use tokio::sync::mpsc::{Receiver, Sender, channel};
 use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
 
+use super::options::Anchor;
 use super::util::cursor_pos_from_tty;
 use super::{Event, Size, TICK_RATE};
 
@@ -43,6 +44,13 @@ where
     /// Whether running in fullscreen mode
     pub is_fullscreen: bool,
     enable_mouse: bool,
+    /// Where the inline viewport is placed
+    anchor: Anchor,
+    /// Row to leave the cursor on when exiting the inline viewport
+    exit_cursor_row: u16,
+    /// Signal actions replaced by [`enter_terminal`](Self::enter_terminal), restored on exit
+    #[cfg(unix)]
+    saved_handlers: Option<super::job_control::SavedHandlers>,
 }
 
 impl Tui {
@@ -52,8 +60,17 @@ impl Tui {
     ///
     /// Returns an error if the TUI backend cannot be initialized.
     pub fn new_with_height(height: Size) -> Result<Self> {
+        Self::new_with_height_and_anchor(height, Anchor::default())
+    }
+    /// Creates a TUI with the default backend (buffered stderr), the specified height and, when
+    /// not fullscreen, the given anchor
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the TUI backend cannot be initialized.
+    pub fn new_with_height_and_anchor(height: Size, anchor: Anchor) -> Result<Self> {
         let backend = CrosstermBackend::new(std::io::BufWriter::new(stderr()));
-        Self::new_with_height_and_backend(backend, height)
+        Self::new_with_anchor_and_backend(backend, height, anchor)
     }
     /// Disable mouse handling.
     /// Needs to be called before enter.
@@ -77,6 +94,19 @@ where
     ///
     /// Panics if the terminal size cannot be read from the backend.
     pub fn new_with_height_and_backend(backend: B, height: Size) -> Result<Self> {
+        Self::new_with_anchor_and_backend(backend, height, Anchor::default())
+    }
+
+    /// Creates a new TUI with the specified backend, height and anchor
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the terminal size cannot be determined or setup fails.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the terminal size cannot be read from the backend.
+    pub fn new_with_anchor_and_backend(backend: B, height: Size, anchor: Anchor) -> Result<Self> {
         let event_channel = channel(1024 * 1024);
 
         let term_height = backend.size().expect("Failed to get terminal height").height;
@@ -87,16 +117,16 @@ where
             Size::Neg(lines) => Some(term_height.saturating_sub(lines)),
         };
 
-        let viewport = if let Some(mut height) = lines {
+        let mut exit_cursor_row = 0;
+        let viewport = if let Some(height) = lines {
             // Until https://github.com/crossterm-rs/crossterm/issues/919 is fixed, we need to do it ourselves
             let cursor_pos = cursor_pos_from_tty()?;
-            let mut y = cursor_pos.1 - 1;
-            height = height.min(term_height);
-            if term_height - cursor_pos.1 < height {
-                let to_scroll = height - (term_height - cursor_pos.1) - 1;
+            let height = height.min(term_height);
+            let (y, to_scroll) = anchor.viewport_row(term_height, cursor_pos.1 - 1, height);
+            if to_scroll > 0 {
                 crossterm::execute!(stderr(), crossterm::terminal::ScrollUp(to_scroll))?;
-                y = y.saturating_sub(to_scroll);
             }
+            exit_cursor_row = (cursor_pos.1 - 1).saturating_sub(to_scroll);
             Viewport::Fixed(Rect::new(
                 0,
                 y,
@@ -117,6 +147,10 @@ where
             cancellation_token: CancellationToken::default(),
             is_fullscreen: lines.is_none(),
             enable_mouse: true,
+            anchor,
+            exit_cursor_row,
+            #[cfg(unix)]
+            saved_handlers: None,
         })
     }
 
@@ -145,6 +179,11 @@ where
         // performs terminal cleanup instead of killing the process abruptly.
         #[cfg(windows)]
         super::windows::install_ctrl_c_handler()?;
+        // On Unix, catch SIGTSTP so that the terminal is restored before skim is stopped.
+        #[cfg(unix)]
+        if self.saved_handlers.is_none() {
+            self.saved_handlers = Some(super::job_control::install_handlers()?);
+        }
 
         self.execute_enter()?;
         Ok(())
@@ -161,16 +200,33 @@ where
         // Remove our console ctrl handler now that raw mode is off.
         #[cfg(windows)]
         super::windows::uninstall_ctrl_c_handler();
+        // Only give back the handlers that `enter_terminal` replaced, e.g. not for a test backend.
+        #[cfg(unix)]
+        if let Some(saved) = self.saved_handlers.take() {
+            saved.restore();
+        }
         // When using the inline layout, we want to remove all previous output
-        //  -> reset cursor at the top of the drawing area
+        //  -> clear the drawing area and put the cursor back where it was
+        //     (the top of the drawing area unless anchored elsewhere)
         if !self.is_fullscreen {
             let area = self.get_frame().area();
-            let orig = ratatui::layout::Position { x: area.x, y: area.y };
-            crossterm::execute!(
-                stderr(),
-                cursor::MoveTo(orig.x, orig.y),
-                Clear(ClearType::FromCursorDown)
-            )?;
+            if self.anchor == Anchor::Top {
+                // Anything below the viewport is the user's, keep it.
+                for row in area.top()..area.bottom() {
+                    crossterm::execute!(stderr(), cursor::MoveTo(0, row), Clear(ClearType::CurrentLine))?;
+                }
+            } else {
+                crossterm::execute!(
+                    stderr(),
+                    cursor::MoveTo(area.x, area.y),
+                    Clear(ClearType::FromCursorDown)
+                )?;
+            }
+            let orig = ratatui::layout::Position {
+                x: area.x,
+                y: self.exit_cursor_row,
+            };
+            crossterm::execute!(stderr(), cursor::MoveTo(orig.x, orig.y))?;
             self.set_cursor_position(orig)?;
         }
         Ok(())
@@ -274,6 +330,15 @@ where
                       }
                     },
                     _ = tick_delay => {
+                        #[cfg(unix)]
+                        {
+                          if super::job_control::take_suspend_request() {
+                            _ = event_tx_clone.try_send(Event::Suspend);
+                          }
+                          if super::job_control::take_continued() {
+                            _ = event_tx_clone.try_send(Event::Continued);
+                          }
+                        }
                         _ = event_tx_clone.try_send(Event::Heartbeat);
                     },
                 }
@@ -354,7 +419,50 @@ where
     /// screen and raw mode, runs the command to completion, then restores skim's
     /// terminal state and restarts the reader. The child is given its own handle
     /// to the controlling terminal as stdin (see [`execute_child_stdin`]).
-    pub(crate) fn run_execute(&mut self, cmd: &str) -> Result<()> {
+    pub(crate) fn run_execute(&mut self, mut command: std::process::Command) -> Result<()> {
+        command.stdin(execute_child_stdin());
+        self.in_foreground(move || {
+            let _ = command.spawn().and_then(|mut c| c.wait());
+        })
+    }
+
+    /// Run a nested finder over the output of `source`, returning what it printed
+    ///
+    /// The terminal is handed over as in [`Tui::run_execute`]. `finder` reads the
+    /// output of `source` on its stdin; `None` is returned if it could not run or
+    /// exited with an error, e.g. when aborted.
+    pub(crate) fn run_pick(
+        &mut self,
+        mut source: std::process::Command,
+        mut finder: std::process::Command,
+    ) -> Result<Option<String>> {
+        source.stdin(Stdio::null()).stdout(Stdio::piped());
+        self.in_foreground(move || {
+            let (mut source, group) = crate::process_group::spawn(&mut source).ok()?;
+            let output = source
+                .stdout
+                .take()
+                .and_then(|stdout| finder.stdin(stdout).stdout(Stdio::piped()).output().ok());
+            // The source may still be running if the finder exited early
+            group.kill();
+            let _ = source.wait();
+            let output = output.filter(|o| o.status.success())?;
+            Some(String::from_utf8_lossy(&output.stdout).into_owned())
+        })
+    }
+
+    /// Stop skim as a shell job, handing the terminal back to the shell until it is continued
+    ///
+    /// Like [`Tui::run_execute`], the reader and the terminal modes are suspended meanwhile.
+    /// Does nothing where there is no job control.
+    pub(crate) fn suspend(&mut self) -> Result<()> {
+        #[cfg(unix)]
+        self.in_foreground(super::job_control::stop_job)?;
+        Ok(())
+    }
+
+    /// Suspend the reader and the terminal modes around `run`, see [`Tui::run_execute`]
+    fn in_foreground<T>(&mut self, run: impl FnOnce() -> T) -> Result<T> {
         use std::io::IsTerminal as _;
 
         let has_tty = std::io::stderr().is_terminal();
@@ -368,9 +476,7 @@ where
             in_raw_mode = self.pause()?;
         }
 
-        let mut command = crate::shell_cmd(cmd);
-        command.stdin(execute_child_stdin());
-        let _ = command.spawn().and_then(|mut c| c.wait());
+        let res = run();
 
         let mut restore_result = Ok(());
         if has_tty {
@@ -379,7 +485,7 @@ where
 
         // Resume skim's input reader now that the terminal is ours again.
         self.start();
-        restore_result
+        restore_result.map(|()| res)
     }
 }
 
@@ -507,4 +613,27 @@ mod tests {
         assert_eq!(area.width, 80);
         assert_eq!(area.height, 24);
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_pick_returns_the_finder_output() {
+        use std::process::Command;
+        let rt = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let _guard = rt.enter();
+        let mut tui = fullscreen_tui();
+        let sh = |cmd: &str| {
+            let mut c = Command::new("sh");
+            c.args(["-c", cmd]);
+            c
+        };
+
+        let picked = rt.block_on(async { tui.run_pick(sh("printf 'a\\nb\\n'"), sh("tail -n 1")) });
+        assert_eq!(picked.unwrap().as_deref(), Some("b\n"));
+        // An aborted finder picks nothing
+        let picked = rt.block_on(async { tui.run_pick(sh("echo a"), sh("cat; exit 130")) });
+        assert_eq!(picked.unwrap(), None);
+    }
 }
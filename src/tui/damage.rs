@@ -0,0 +1,75 @@
+//! Widgets changed since the last frame
+//!
+//! Redrawing the item list and the preview is what costs in a frame, so they are only rendered
+//! again once something marked them damaged; otherwise their cells are copied over from the last
+//! frame and ratatui's diff leaves them untouched on the terminal. The cheap widgets (input,
+//! header, stderr pane) are rendered in every frame.
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+
+/// Which of the costly widgets have to be rendered again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Damage {
+    /// The item list: moves, selections, matcher results
+    pub(crate) list: bool,
+    /// The preview: new output, scrolling
+    pub(crate) preview: bool,
+}
+
+impl Default for Damage {
+    /// Everything is damaged before the first frame
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl Damage {
+    /// Every widget has to be rendered again
+    pub(crate) const ALL: Self = Self {
+        list: true,
+        preview: true,
+    };
+    /// Nothing changed since the last frame
+    pub(crate) const NONE: Self = Self {
+        list: false,
+        preview: false,
+    };
+
+    /// Marks every widget damaged
+    pub(crate) fn mark_all(&mut self) {
+        *self = Self::ALL;
+    }
+}
+
+/// Copies the cells of `area` from the `last` frame, when it covers it
+///
+/// Returns false when `last` does not cover `area`, the widget has to be rendered then.
+pub(crate) fn keep(area: Rect, last: &Buffer, buf: &mut Buffer) -> bool {
+    if last.area.intersection(area) != area || buf.area.intersection(area) != area {
+        return false;
+    }
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            buf[(x, y)] = last[(x, y)].clone();
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keep_copies_the_area_from_the_last_frame() {
+        let last = Buffer::with_lines(["abc", "def"]);
+        let mut buf = Buffer::empty(last.area);
+        assert!(keep(Rect::new(1, 0, 2, 2), &last, &mut buf));
+        assert_eq!(buf, Buffer::with_lines([" bc", " ef"]));
+
+        // a smaller last frame, e.g. before a resize, does not cover the area
+        let mut buf = Buffer::empty(Rect::new(0, 0, 4, 2));
+        assert!(!keep(Rect::new(0, 0, 4, 1), &last, &mut buf));
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::{Arc, Mutex};
@@ -5,6 +6,8 @@ use std::sync::{Arc, Mutex};
 use crossterm::event::{KeyEvent, MouseEvent};
 use derive_more::{Debug, Eq, PartialEq};
 
+use crate::SkimItem;
+
 type BoxError = Box<dyn std::error::Error + Sync + Send>;
 type BoxFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<Event>, BoxError>> + Send + 'a>>;
 
@@ -106,6 +109,114 @@ impl ActionCallback {
     }
 }
 
+/// The state of skim handed to the actions of an [`ActionRegistry`]
+///
+/// A snapshot taken when the action runs rather than the whole `App`: the query, the current item
+/// and the selection, with a sender for the events to process later, e.g. from a task the action
+/// spawned.
+#[derive(Clone, Debug)]
+pub struct AppHandle {
+    query: String,
+    #[debug(skip)]
+    current: Option<Arc<dyn SkimItem>>,
+    #[debug(skip)]
+    selection: Vec<Arc<dyn SkimItem>>,
+    #[debug(skip)]
+    events: Option<tokio::sync::mpsc::Sender<Event>>,
+}
+
+impl AppHandle {
+    pub(crate) fn new(
+        query: String,
+        current: Option<Arc<dyn SkimItem>>,
+        selection: Vec<Arc<dyn SkimItem>>,
+        events: Option<tokio::sync::mpsc::Sender<Event>>,
+    ) -> Self {
+        Self {
+            query,
+            current,
+            selection,
+            events,
+        }
+    }
+
+    /// The query
+    #[must_use]
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// The item under the cursor
+    #[must_use]
+    pub fn current(&self) -> Option<&Arc<dyn SkimItem>> {
+        self.current.as_ref()
+    }
+
+    /// The selected items, in the order they were selected
+    #[must_use]
+    pub fn selection(&self) -> &[Arc<dyn SkimItem>] {
+        &self.selection
+    }
+
+    /// Sends `event` to skim, processed after the events returned by the action
+    ///
+    /// Returns `false` when skim has no event loop running, or its queue is full.
+    #[must_use]
+    pub fn send(&self, event: Event) -> bool {
+        self.events
+            .as_ref()
+            .is_some_and(|events| events.try_send(event).is_ok())
+    }
+}
+
+/// An action of an [`ActionRegistry`]
+type PluginFn = dyn Fn(&AppHandle) -> Result<Vec<Event>, BoxError> + Send + Sync;
+
+/// Named custom actions, run by the `plugin(name)` action
+///
+/// Lets embedders expose their own actions to `--bind` strings, e.g.
+/// `ctrl-e:plugin(open-editor)`, without building the keymap by hand. The actions get an
+/// [`AppHandle`], from which they can read the query, the current item and the selection, and
+/// return the events to process next.
+///
+/// ```rust,ignore
+/// let mut actions = ActionRegistry::default();
+/// actions.register("open-editor", |app: &AppHandle| {
+///     Ok(vec![Event::Action(Action::Execute(format!("$EDITOR {}", app.query())))])
+/// });
+/// let options = SkimOptionsBuilder::default().actions(actions).bind(vec!["ctrl-e:plugin(open-editor)".into()]).build()?;
+/// ```
+#[derive(Clone, Default)]
+pub struct ActionRegistry(HashMap<String, Arc<PluginFn>>);
+
+impl std::fmt::Debug for ActionRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.names()).finish()
+    }
+}
+
+impl ActionRegistry {
+    /// Register `action` under `name`, replacing any action already registered with that name
+    pub fn register<F>(&mut self, name: impl Into<String>, action: F) -> &mut Self
+    where
+        F: Fn(&AppHandle) -> Result<Vec<Event>, BoxError> + Send + Sync + 'static,
+    {
+        self.0.insert(name.into(), Arc::new(action));
+        self
+    }
+
+    /// The action registered under `name`
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&PluginFn> {
+        self.0.get(name).map(|action| &**action)
+    }
+
+    /// Names of the registered actions, in no particular order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+}
+
 /// Events that can occur during skim's execution
 #[derive(Clone, Debug)]
 pub enum Event {
@@ -148,10 +259,23 @@ pub enum Event {
     /// `handle_action`, because running a foreground process requires
     /// suspending skim's own input reader and toggling terminal modes.
     RunExecute(String),
+    /// Run a nested finder over the output of a command, see [`Action::Pick`]
+    ///
+    /// Like [`Event::RunExecute`], it needs the terminal and is handled by the
+    /// event loop.
+    RunPick(String),
     /// Redraw the screen
     Redraw,
+    /// Stop skim as a job, see [`Action::Suspend`]
+    ///
+    /// Handled by the event loop, which hands the terminal back before stopping.
+    Suspend,
+    /// skim was continued after being stopped by someone else: enter raw mode again and repaint
+    Continued,
     /// Reload with a new command
     Reload(String),
+    /// A file under the paths of `--watch` changed
+    Watched,
     /// Terminal was resized to (columns, rows)
     Resize(u16, u16),
 }
@@ -162,7 +286,7 @@ pub enum Event {
 pub enum Action {
     /// Abort and exit with error
     Abort,
-    /// Accept selection and exit with optional key
+    /// Accept selection and exit, with an optional payload printed before the selection
     Accept(Option<String>),
     /// Add a character to the query
     AddChar(char),
@@ -182,8 +306,20 @@ pub enum Action {
     BeginningOfLine,
     /// Bind one or more keys to action chains (`key:action[+action][,key:action…]`)
     Bind(String),
+    /// Jump to last item in list (alias for Last)
+    Bottom,
     /// Cancel current operation
     Cancel,
+    /// Set `--border-label` to the expanded value (or remove it on an empty value)
+    ChangeBorderLabel(String),
+    /// Set the label of the header to the expanded value (or remove it on an empty value)
+    ChangeHeaderLabel(String),
+    /// Set the label of the input to the expanded value (or remove it on an empty value)
+    ChangeInputLabel(String),
+    /// Set the label of the item list to the expanded value (or remove it on an empty value)
+    ChangeListLabel(String),
+    /// Set the label of the preview window to the expanded value (or remove it on an empty value)
+    ChangePreviewLabel(String),
     /// Clear the screen
     ClearScreen,
     /// Delete character under cursor
@@ -194,18 +330,28 @@ pub enum Action {
     DeselectAll,
     /// Move selection down by N items
     Down(u16),
+    /// Stop matching, showing all the items whatever the query (as `--disabled`)
+    DisableSearch,
+    /// Match the query again after `disable-search` or `--disabled`
+    EnableSearch,
     /// Move cursor to end of line
     EndOfLine,
     /// Execute a command
     Execute(String),
     /// Execute a command silently
     ExecuteSilent(String),
+    /// Show the expanded command in the status line without running it
+    ExecuteEcho(String),
+    /// Run `tmux` with the expanded arguments, e.g. `execute-tmux(select-window -t :2)`
+    ExecuteTmux(String),
     /// Jump to first item in list
     First,
     /// Move cursor forward one character
     ForwardChar,
     /// Move cursor forward one word
     ForwardWord,
+    /// Search the query history (command history in interactive mode) for a substring, like reverse-i-search
+    HistorySearch,
     /// Execute action if query is empty
     IfQueryEmpty(String, Option<String>),
     /// Execute action if query is not empty
@@ -214,6 +360,10 @@ pub enum Action {
     IfNonMatched(String, Option<String>),
     /// Ignore the action
     Ignore,
+    /// Label the visible items, then move the cursor to the item whose label is typed
+    Jump,
+    /// Label the visible items, then accept the item whose label is typed
+    JumpAccept,
     /// Delete from cursor to end of line
     KillLine,
     /// Delete word after cursor
@@ -226,6 +376,10 @@ pub enum Action {
     HalfPageDown(i32),
     /// Scroll up by half a page
     HalfPageUp(i32),
+    /// Pick from the output of a command in a nested finder, inserting the choice in the query
+    Pick(String),
+    /// Run the custom action registered under this name, see [`ActionRegistry`]
+    Plugin(String),
     /// Scroll down by a page
     PageDown(i32),
     /// Scroll up by a page
@@ -242,8 +396,13 @@ pub enum Action {
     PreviewPageUp(i32),
     /// Scroll preview down by a page
     PreviewPageDown(i32),
+    /// Preview the current item with the given command instead of the preview command,
+    /// until the cursor moves or the preview is refreshed
+    Preview(String),
     /// Move to previous history entry
     PreviousHistory,
+    /// Insert the text at the cursor, as is
+    Put(String),
     /// Redraw the screen
     Redraw,
     /// Refresh the command
@@ -254,6 +413,8 @@ pub enum Action {
     RestartMatcher,
     /// Reload with optional new command
     Reload(Option<String>),
+    /// Set the query to the text of the current item
+    ReplaceQuery,
     /// Rotate through matching modes
     RotateMode,
     /// Scroll item list left
@@ -266,6 +427,9 @@ pub enum Action {
     SelectRow(usize),
     /// Select current item
     Select,
+    /// Stop skim as a shell job, like ctrl-z in other programs, restoring the terminal until
+    /// it is continued (e.g. with `fg`)
+    Suspend,
     /// Suppress the default behaviour of the action this is bound to.
     ///
     /// Only meaningful as a follow-up bound to an action (e.g. `act-up:suppress`):
@@ -278,14 +442,22 @@ pub enum Action {
     SetPreviewCmd(String),
     /// Set the query to the expanded value
     SetQuery(String),
+    /// Type the selected items (or the current one), shell-quoted, in a tmux pane or window
+    SendToTmux(String),
+    /// Complete the query token before the cursor from the words of the matched items
+    TabComplete,
     /// Toggle selection of current item
     Toggle,
     /// Toggle selection of all items
     ToggleAll,
+    /// Collapse the group of the current item to its best match, or expand it back
+    ToggleGroup,
     /// Toggle and move in
     ToggleIn,
     /// Toggle interactive mode
     ToggleInteractive,
+    /// Match the next field set of `--nth-cycle`
+    ToggleNth,
     /// Toggle and move out
     ToggleOut,
     /// Toggle preview visibility
@@ -294,10 +466,20 @@ pub enum Action {
     TogglePreviewWrap,
     /// Toggle sorting
     ToggleSort,
+    /// Enable or disable search
+    ToggleSearch,
+    /// Move the cursor to the secondary query, or back to the query
+    ToggleQuery2,
+    /// Show or hide the stderr of the source and preview commands
+    ToggleStderr,
     /// Jump to first item in list (alias for First)
     Top,
+    /// Swap the characters before and under the cursor
+    TransposeChars,
     /// Unbind one or more keys (`key[,key…]`)
     Unbind(String),
+    /// Restore the query from before the last edit
+    Undo,
     /// Discard line (unix-style)
     UnixLineDiscard,
     /// Delete word backward (unix-style)
@@ -306,6 +488,8 @@ pub enum Action {
     Up(u16),
     /// Yank (paste)
     Yank,
+    /// Replace the text just yanked with the previous entry of the kill ring
+    YankPop,
     /// Custom action from lib
     #[debug("custom")]
     #[eq(skip)]
@@ -368,28 +552,43 @@ define_action_catalog! {
     BackwardWord => "backward-word" => Some(BackwardWord),
     BeginningOfLine => "beginning-of-line" => Some(BeginningOfLine),
     Bind(_) => "bind" => Some(Bind(arg.unwrap_or_default())),
+    Bottom => "bottom" => Some(Bottom),
     Cancel => "cancel" => Some(Cancel),
+    ChangeBorderLabel(_) => "change-border-label" => Some(ChangeBorderLabel(arg.unwrap_or_default())),
+    ChangeHeaderLabel(_) => "change-header-label" => Some(ChangeHeaderLabel(arg.unwrap_or_default())),
+    ChangeInputLabel(_) => "change-input-label" => Some(ChangeInputLabel(arg.unwrap_or_default())),
+    ChangeListLabel(_) => "change-list-label" => Some(ChangeListLabel(arg.unwrap_or_default())),
+    ChangePreviewLabel(_) => "change-preview-label" => Some(ChangePreviewLabel(arg.unwrap_or_default())),
     ClearScreen => "clear-screen" => Some(ClearScreen),
     DeleteChar => "delete-char" => Some(DeleteChar),
     DeleteCharEof => "delete-char/eof" => Some(DeleteCharEof),
     DeselectAll => "deselect-all" => Some(DeselectAll),
     Down(_) => "down" => Some(Down(arg.and_then(|s| s.parse().ok()).unwrap_or(1))),
+    DisableSearch => "disable-search" => Some(DisableSearch),
+    EnableSearch => "enable-search" => Some(EnableSearch),
     EndOfLine => "end-of-line" => Some(EndOfLine),
     Execute(_) => "execute" => Some(Execute(arg.unwrap_or_default())),
     ExecuteSilent(_) => "execute-silent" => Some(ExecuteSilent(arg.unwrap_or_default())),
+    ExecuteEcho(_) => "execute-echo" => Some(ExecuteEcho(arg.unwrap_or_default())),
+    ExecuteTmux(_) => "execute-tmux" => Some(ExecuteTmux(arg.unwrap_or_default())),
     First => "first" => Some(First),
     ForwardChar => "forward-char" => Some(ForwardChar),
     ForwardWord => "forward-word" => Some(ForwardWord),
+    HistorySearch => "history-search" => Some(HistorySearch),
     IfQueryEmpty(..) => "if-query-empty" => parse_conditional(arg, IfQueryEmpty),
     IfQueryNotEmpty(..) => "if-query-not-empty" => parse_conditional(arg, IfQueryNotEmpty),
     IfNonMatched(..) => "if-non-matched" => parse_conditional(arg, IfNonMatched),
     Ignore => "ignore" => Some(Ignore),
+    Jump => "jump" => Some(Jump),
+    JumpAccept => "jump-accept" => Some(JumpAccept),
     KillLine => "kill-line" => Some(KillLine),
     KillWord => "kill-word" => Some(KillWord),
     Last => "last" => Some(Last),
     NextHistory => "next-history" => Some(NextHistory),
     HalfPageDown(_) => "half-page-down" => Some(HalfPageDown(arg.and_then(|s| s.parse().ok()).unwrap_or(1))),
     HalfPageUp(_) => "half-page-up" => Some(HalfPageUp(arg.and_then(|s| s.parse().ok()).unwrap_or(1))),
+    Pick(_) => "pick" => Some(Pick(arg.unwrap_or_default())),
+    Plugin(_) => "plugin" => Some(Plugin(arg.unwrap_or_default())),
     PageDown(_) => "page-down" => Some(PageDown(arg.and_then(|s| s.parse().ok()).unwrap_or(1))),
     PageUp(_) => "page-up" => Some(PageUp(arg.and_then(|s| s.parse().ok()).unwrap_or(1))),
     PreviewUp(_) => "preview-up" => Some(PreviewUp(arg.and_then(|s| s.parse().ok()).unwrap_or(1))),
@@ -398,12 +597,15 @@ define_action_catalog! {
     PreviewRight(_) => "preview-right" => Some(PreviewRight(arg.and_then(|s| s.parse().ok()).unwrap_or(1))),
     PreviewPageUp(_) => "preview-page-up" => Some(PreviewPageUp(arg.and_then(|s| s.parse().ok()).unwrap_or(1))),
     PreviewPageDown(_) => "preview-page-down" => Some(PreviewPageDown(arg.and_then(|s| s.parse().ok()).unwrap_or(1))),
+    Preview(_) => "preview" => Some(Preview(arg.unwrap_or_default())),
     PreviousHistory => "previous-history" => Some(PreviousHistory),
+    Put(_) => "put" => Some(Put(arg.unwrap_or_default())),
     Redraw => "redraw" => Some(Redraw),
     RefreshCmd => "refresh-cmd" => Some(RefreshCmd),
     RefreshPreview => "refresh-preview" => Some(RefreshPreview),
     RestartMatcher => "restart-matcher" => Some(RestartMatcher),
     Reload(_) => "reload" => Some(Reload(arg)),
+    ReplaceQuery => "replace-query" => Some(ReplaceQuery),
     RotateMode => "rotate-mode" => Some(RotateMode),
     ScrollLeft(_) => "scroll-left" => Some(ScrollLeft(arg.and_then(|s| s.parse().ok()).unwrap_or(1))),
     ScrollRight(_) => "scroll-right" => Some(ScrollRight(arg.and_then(|s| s.parse().ok()).unwrap_or(1))),
@@ -413,24 +615,71 @@ define_action_catalog! {
     SetHeader(_) => "set-header" => Some(SetHeader(arg)),
     SetPreviewCmd(_) => "set-preview-cmd" => Some(SetPreviewCmd(arg.unwrap_or_default())),
     SetQuery(_) => "set-query" => Some(SetQuery(arg.unwrap_or_default())),
+    SendToTmux(_) => "send-to-tmux" => Some(SendToTmux(arg.unwrap_or_default())),
+    Suspend => "suspend" => Some(Suspend),
     Suppress => "suppress" => Some(Suppress),
+    TabComplete => "tab-complete" => Some(TabComplete),
     Toggle => "toggle" => Some(Toggle),
     ToggleAll => "toggle-all" => Some(ToggleAll),
+    ToggleGroup => "toggle-group" => Some(ToggleGroup),
     ToggleIn => "toggle-in" => Some(ToggleIn),
     ToggleInteractive => "toggle-interactive" => Some(ToggleInteractive),
+    ToggleNth => "toggle-nth" => Some(ToggleNth),
     ToggleOut => "toggle-out" => Some(ToggleOut),
     TogglePreview => "toggle-preview" => Some(TogglePreview),
     TogglePreviewWrap => "toggle-preview-wrap" => Some(TogglePreviewWrap),
     ToggleSort => "toggle-sort" => Some(ToggleSort),
+    ToggleSearch => "toggle-search" => Some(ToggleSearch),
+    ToggleQuery2 => "toggle-query2" => Some(ToggleQuery2),
+    ToggleStderr => "toggle-stderr" => Some(ToggleStderr),
     Top => "top" => Some(Top),
+    TransposeChars => "transpose-chars" => Some(TransposeChars),
     Unbind(_) => "unbind" => Some(Unbind(arg.unwrap_or_default())),
+    Undo => "undo" => Some(Undo),
     UnixLineDiscard => "unix-line-discard" => Some(UnixLineDiscard),
     UnixWordRubout => "unix-word-rubout" => Some(UnixWordRubout),
     Up(_) => "up" => Some(Up(arg.and_then(|s| s.parse().ok()).unwrap_or(1))),
     Yank => "yank" => Some(Yank),
+    YankPop => "yank-pop" => Some(YankPop),
     Custom(_) => "custom" => None,
 }
 
+impl Action {
+    /// Whether the action edits the query line, or moves its cursor
+    ///
+    /// These apply to the secondary query while `toggle-query2` gives it the cursor.
+    #[must_use]
+    pub fn edits_query(&self) -> bool {
+        #[allow(clippy::enum_glob_use)]
+        use Action::*;
+        matches!(
+            self,
+            AddChar(_)
+                | BackwardChar
+                | BackwardDeleteChar
+                | BackwardDeleteCharEof
+                | BackwardKillWord
+                | BackwardWord
+                | BeginningOfLine
+                | DeleteChar
+                | DeleteCharEof
+                | EndOfLine
+                | ForwardChar
+                | ForwardWord
+                | KillLine
+                | KillWord
+                | Put(_)
+                | TabComplete
+                | TransposeChars
+                | Undo
+                | UnixLineDiscard
+                | UnixWordRubout
+                | Yank
+                | YankPop
+        )
+    }
+}
+
 /// Parses an action string into an Action enum
 ///
 /// Returns `None` if the action is unrecognized, or an `if-*` action is
@@ -453,7 +702,19 @@ pub fn parse_action(raw_action: &str) -> Option<Action> {
 
     if matches!(
         action,
-        "add-char" | "bind" | "execute" | "execute-silent" | "set-preview-cmd" | "set-query" | "unbind"
+        "add-char"
+            | "bind"
+            | "execute"
+            | "execute-silent"
+            | "execute-echo"
+            | "execute-tmux"
+            | "plugin"
+            | "preview"
+            | "put"
+            | "set-preview-cmd"
+            | "send-to-tmux"
+            | "set-query"
+            | "unbind"
     ) && arg.is_none()
     {
         None
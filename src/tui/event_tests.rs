@@ -9,16 +9,22 @@ const NO_ARG_ACTIONS: &[&str] = &[
     "backward-kill-word",
     "backward-word",
     "beginning-of-line",
+    "bottom",
     "cancel",
     "clear-screen",
     "delete-char",
     "delete-char/eof",
     "deselect-all",
+    "disable-search",
+    "enable-search",
     "end-of-line",
     "first",
     "forward-char",
     "forward-word",
+    "history-search",
     "ignore",
+    "jump",
+    "jump-accept",
     "kill-line",
     "kill-word",
     "last",
@@ -27,23 +33,34 @@ const NO_ARG_ACTIONS: &[&str] = &[
     "redraw",
     "refresh-cmd",
     "refresh-preview",
+    "replace-query",
     "restart-matcher",
     "rotate-mode",
     "select",
     "select-all",
+    "suspend",
     "suppress",
+    "tab-complete",
     "toggle",
     "toggle-all",
+    "toggle-group",
     "toggle-in",
     "toggle-interactive",
+    "toggle-nth",
     "toggle-out",
     "toggle-preview",
     "toggle-preview-wrap",
     "toggle-sort",
+    "toggle-search",
+    "toggle-query2",
+    "toggle-stderr",
     "top",
+    "transpose-chars",
+    "undo",
     "unix-line-discard",
     "unix-word-rubout",
     "yank",
+    "yank-pop",
 ];
 
 #[test]
@@ -97,8 +114,15 @@ fn parse_string_arg_actions() {
     for (spec, name) in [
         ("execute:ls -la", "execute"),
         ("execute-silent:touch x", "execute-silent"),
+        ("execute-echo:echo {}", "execute-echo"),
+        ("execute-tmux(select-window -t :2)", "execute-tmux"),
+        ("send-to-tmux(%3)", "send-to-tmux"),
+        ("plugin(open-editor)", "plugin"),
+        ("pick(ls ~/src)", "pick"),
         ("set-query:hello", "set-query"),
+        ("put(git log )", "put"),
         ("set-preview-cmd:cat {}", "set-preview-cmd"),
+        ("preview(cat {})", "preview"),
         ("add-char:z", "add-char"),
     ] {
         assert_eq!(parse_action(spec).map(|action| action.name()), Some(name));
@@ -121,11 +145,27 @@ fn parse_string_arg_actions() {
         Some(Action::SetPreviewCmd("cat {}".to_string()))
     );
     assert_eq!(parse_action("add-char:z"), Some(Action::AddChar('z')));
+    assert_eq!(
+        parse_action("preview(git diff {})"),
+        Some(Action::Preview("git diff {}".to_string()))
+    );
+    assert_eq!(parse_action("preview"), None);
+    assert_eq!(parse_action("plugin"), None);
+    assert_eq!(parse_action("plugin:"), None);
 }
 
 #[test]
 fn parse_optional_arg_actions() {
-    for name in ["accept", "set-header", "reload"] {
+    for name in [
+        "accept",
+        "change-border-label",
+        "change-header-label",
+        "change-input-label",
+        "change-list-label",
+        "change-preview-label",
+        "set-header",
+        "reload",
+    ] {
         assert_eq!(parse_action(name).map(|action| action.name()), Some(name));
     }
 
@@ -135,6 +175,10 @@ fn parse_optional_arg_actions() {
         Some(Action::Accept(Some("enter".to_string())))
     );
     assert_eq!(parse_action("set-header"), Some(Action::SetHeader(None)));
+    assert_eq!(
+        parse_action("change-preview-label( {} )"),
+        Some(Action::ChangePreviewLabel(" {} ".to_string()))
+    );
     assert_eq!(parse_action("reload"), Some(Action::Reload(None)));
     assert_eq!(
         parse_action("reload:find ."),
@@ -0,0 +1,158 @@
+//! Builtin file previewer (`--preview builtin:file`)
+//!
+//! Renders a file with line numbers directly in skim's process, instead of
+//! spawning `bat` or `cat` on every cursor move. With the `syntect` feature the
+//! content is also syntax highlighted.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span, Text};
+
+/// Preview command selecting the builtin file previewer
+pub(crate) const BUILTIN_FILE: &str = "builtin:file";
+
+/// Bytes inspected to decide whether a file is binary
+const BINARY_SNIFF_BYTES: usize = 8192;
+
+/// Returns the path template of a builtin file preview command
+///
+/// `builtin:file` previews the current item, `builtin:file TEMPLATE` previews
+/// the expansion of `TEMPLATE` (e.g. `builtin:file {1}` for `grep -n` output).
+pub(crate) fn builtin_file_arg(cmd: &str) -> Option<&str> {
+    let rest = cmd.trim_start().strip_prefix(BUILTIN_FILE)?;
+    if rest.is_empty() {
+        Some("{}")
+    } else if rest.starts_with(char::is_whitespace) {
+        Some(rest.trim()).filter(|arg| !arg.is_empty()).or(Some("{}"))
+    } else {
+        None
+    }
+}
+
+/// Read at most `max_bytes` of `path` and render it with line numbers
+pub(crate) fn render_file(path: &Path, max_bytes: usize) -> Text<'static> {
+    let mut bytes = Vec::new();
+    let res = File::open(path).and_then(|f| f.take(max_bytes as u64).read_to_end(&mut bytes));
+    if let Err(e) = res {
+        return Text::raw(format!("Failed to read {}: {e}", path.display()));
+    }
+    if bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0) {
+        return Text::raw(format!("{}: binary file", path.display()));
+    }
+    let content = String::from_utf8_lossy(&bytes);
+    let lines = highlight(path, &content);
+    let width = lines.len().to_string().len();
+    let gutter = Style::default().fg(Color::DarkGray);
+    lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let mut spans = vec![Span::styled(format!("{:>width$} ", i + 1), gutter)];
+            spans.extend(line.spans);
+            Line::from(spans)
+        })
+        .collect()
+}
+
+#[cfg(not(feature = "syntect"))]
+fn highlight(_path: &Path, content: &str) -> Vec<Line<'static>> {
+    content.lines().map(|l| Line::raw(l.to_string())).collect()
+}
+
+#[cfg(feature = "syntect")]
+fn highlight(path: &Path, content: &str) -> Vec<Line<'static>> {
+    use std::sync::LazyLock;
+
+    use syntect::easy::HighlightLines;
+    use syntect::highlighting::{FontStyle, ThemeSet};
+    use syntect::parsing::SyntaxSet;
+    use syntect::util::LinesWithEndings;
+
+    static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+    static THEMES: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+
+    let syntax = SYNTAXES
+        .find_syntax_for_file(path)
+        .ok()
+        .flatten()
+        .or_else(|| SYNTAXES.find_syntax_by_first_line(content))
+        .unwrap_or_else(|| SYNTAXES.find_syntax_plain_text());
+    let mut highlighter = HighlightLines::new(syntax, &THEMES.themes["base16-ocean.dark"]);
+
+    LinesWithEndings::from(content)
+        .map(|line| {
+            let Ok(ranges) = highlighter.highlight_line(line, &SYNTAXES) else {
+                return Line::raw(line.trim_end_matches(['\n', '\r']).to_string());
+            };
+            ranges
+                .into_iter()
+                .map(|(style, text)| {
+                    let mut s =
+                        Style::default().fg(Color::Rgb(style.foreground.r, style.foreground.g, style.foreground.b));
+                    if style.font_style.contains(FontStyle::BOLD) {
+                        s = s.bold();
+                    }
+                    if style.font_style.contains(FontStyle::ITALIC) {
+                        s = s.italic();
+                    }
+                    if style.font_style.contains(FontStyle::UNDERLINE) {
+                        s = s.underlined();
+                    }
+                    Span::styled(text.trim_end_matches(['\n', '\r']).to_string(), s)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    #[test]
+    fn builtin_file_arg_defaults_to_current_item() {
+        assert_eq!(builtin_file_arg("builtin:file"), Some("{}"));
+        assert_eq!(builtin_file_arg("builtin:file  "), Some("{}"));
+        assert_eq!(builtin_file_arg("builtin:file {1}"), Some("{1}"));
+        assert_eq!(builtin_file_arg("builtin:files"), None);
+        assert_eq!(builtin_file_arg("cat {}"), None);
+    }
+
+    #[test]
+    fn render_file_numbers_lines() {
+        let mut file = tempfile::Builder::new().suffix(".txt").tempfile().unwrap();
+        file.write_all("one\ntwo\n".repeat(5).as_bytes()).unwrap();
+        let text = render_file(file.path(), 1024);
+        assert_eq!(text.lines.len(), 10);
+        assert_eq!(text.lines[0].to_string(), " 1 one");
+        assert_eq!(text.lines[9].to_string(), "10 two");
+    }
+
+    #[test]
+    fn render_file_detects_binary_and_missing_files() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"\x7fELF\0\0").unwrap();
+        assert!(render_file(file.path(), 1024).to_string().ends_with("binary file"));
+        assert!(
+            render_file(Path::new("/nonexistent/skim"), 1024)
+                .to_string()
+                .starts_with("Failed to read")
+        );
+    }
+
+    #[cfg(feature = "syntect")]
+    #[test]
+    fn render_file_highlights_by_extension() {
+        let mut file = tempfile::Builder::new().suffix(".rs").tempfile().unwrap();
+        file.write_all(b"fn main() {}\n").unwrap();
+        let text = render_file(file.path(), 1024);
+        assert_eq!(text.lines[0].to_string(), "1 fn main() {}");
+        assert!(text.lines[0].spans.len() > 2);
+    }
+}
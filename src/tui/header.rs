@@ -3,7 +3,7 @@
 //! This module provides the header widget that displays static text above the item list.
 use crate::theme::ColorTheme;
 use crate::tui::BorderType;
-use crate::tui::options::TuiLayout;
+use crate::tui::options::{LabelPosition, TuiLayout};
 use crate::tui::util::{char_display_width, clip_line_to_chars, style_line, style_text};
 use crate::tui::widget::{SkimRender, SkimWidget};
 use crate::{DisplayContext, SkimItem, SkimOptions};
@@ -38,6 +38,9 @@ pub struct Header {
     theme: Arc<ColorTheme>,
     /// Border type
     pub border: BorderType,
+    /// Label on the border (`--header-label`, `change-header-label`)
+    pub(crate) label: Option<String>,
+    label_pos: LabelPosition,
     /// Whether to reverse the order of `header_lines` (for default/bottom-to-top layout)
     reverse_lines: bool,
     /// Reverse layout
@@ -143,6 +146,8 @@ impl SkimWidget for Header {
                 .expect("Failed to fit selector lens into an u16"),
             theme,
             border: options.border,
+            label: options.header_label.clone(),
+            label_pos: LabelPosition::from(options.header_label_pos.as_deref().unwrap_or_default()),
             reverse_lines,
             reverse: options.layout == TuiLayout::Reverse,
         }
@@ -150,10 +155,14 @@ impl SkimWidget for Header {
 
     fn render(&mut self, area: Rect, buf: &mut Buffer) -> SkimRender {
         let block = if let Some(border_type) = self.border.into_ratatui() {
-            Block::default()
+            let block = Block::default()
                 .borders(Borders::ALL)
                 .border_type(border_type)
-                .border_style(self.theme.border)
+                .border_style(self.theme.border);
+            match &self.label {
+                Some(label) => self.label_pos.title(block, label, self.theme.label),
+                None => block,
+            }
         } else {
             Block::default()
         }
@@ -1,5 +1,6 @@
+use std::collections::VecDeque;
 use std::fmt::Write as _;
-use std::ops::{Deref, DerefMut};
+use std::ops::{Deref, DerefMut, Range};
 use std::sync::Arc;
 use std::time::Instant;
 
@@ -7,12 +8,13 @@ use ansi_to_tui::IntoText;
 use ratatui::prelude::*;
 use ratatui::widgets::Widget;
 use unicode_display_width::width as display_width;
+use unicode_segmentation::UnicodeSegmentation;
 
 use crate::SkimOptions;
 use crate::helper::item::strip_ansi;
 use crate::theme::ColorTheme;
 use crate::tui::BorderType;
-use crate::tui::options::TuiLayout;
+use crate::tui::options::{LabelPosition, TuiLayout};
 use crate::tui::statusline::{Info, InfoDisplay, spinner_char};
 use crate::tui::util::style_line;
 use crate::tui::widget::{SkimRender, SkimWidget};
@@ -42,6 +44,20 @@ pub struct StatusInfo {
     pub start: Option<Instant>,
     /// Inline prefix/separator (when the spinner is hidden)
     pub inline_separator: String,
+    /// Number of items longer than `--match-window`, only partly matched
+    pub windowed: usize,
+    /// Number of items dropped because they were read before, see `--unique`
+    pub duplicates: usize,
+    /// Number of lines truncated or skipped because of `--max-item-length`
+    pub long_items: usize,
+    /// Field set matched in place of `--nth`, see `toggle-nth`
+    pub nth: Option<String>,
+    /// Number of items read from each labeled source, see `Reader::add_source`
+    pub sources: Vec<(Arc<str>, usize)>,
+    /// Number of reloads caused by the changes to the `--watch` paths, when watching
+    pub watch_reloads: Option<usize>,
+    /// Message to show after the counts, e.g. a command that could not be expanded
+    pub message: Option<String>,
 }
 
 impl StatusInfo {
@@ -79,6 +95,34 @@ impl StatusInfo {
             let _ = write!(parts, " [{}]", self.selected);
         }
 
+        if self.windowed > 0 {
+            let _ = write!(parts, " ~{}", self.windowed);
+        }
+
+        if self.duplicates > 0 {
+            let _ = write!(parts, " dup:{}", self.duplicates);
+        }
+
+        if self.long_items > 0 {
+            let _ = write!(parts, " long:{}", self.long_items);
+        }
+
+        if let Some(nth) = &self.nth {
+            let _ = write!(parts, " nth:{nth}");
+        }
+
+        for (label, count) in &self.sources {
+            let _ = write!(parts, " {label}:{count}");
+        }
+
+        if let Some(reloads) = self.watch_reloads {
+            let _ = write!(parts, " watch:{reloads}");
+        }
+
+        if let Some(message) = &self.message {
+            let _ = write!(parts, " {message}");
+        }
+
         parts
     }
 
@@ -122,6 +166,34 @@ impl StatusInfo {
             let _ = write!(parts, " [{}]", self.selected);
         }
 
+        if self.windowed > 0 {
+            let _ = write!(parts, " ~{}", self.windowed);
+        }
+
+        if self.duplicates > 0 {
+            let _ = write!(parts, " dup:{}", self.duplicates);
+        }
+
+        if self.long_items > 0 {
+            let _ = write!(parts, " long:{}", self.long_items);
+        }
+
+        if let Some(nth) = &self.nth {
+            let _ = write!(parts, " nth:{nth}");
+        }
+
+        for (label, count) in &self.sources {
+            let _ = write!(parts, " {label}:{count}");
+        }
+
+        if let Some(reloads) = self.watch_reloads {
+            let _ = write!(parts, " watch:{reloads}");
+        }
+
+        if let Some(message) = &self.message {
+            let _ = write!(parts, " {message}");
+        }
+
         parts
     }
 
@@ -131,6 +203,43 @@ impl StatusInfo {
     }
 }
 
+/// Most entries kept by the [`KillRing`]
+const KILL_RING_SIZE: usize = 16;
+/// Most query states kept for `undo`
+const UNDO_LIMIT: usize = 100;
+
+/// Text removed by the kill actions, most recent last, inserted back by `yank` and `yank-pop`
+#[derive(Default)]
+pub struct KillRing {
+    entries: VecDeque<String>,
+    /// Entry inserted by the last `yank` or `yank-pop`, and the bytes of the query it took
+    last_yank: Option<(usize, Range<usize>)>,
+}
+
+impl KillRing {
+    /// Adds killed text to the ring, dropping the oldest entry when it is full
+    pub fn push(&mut self, text: String) {
+        if text.is_empty() {
+            return;
+        }
+        if self.entries.len() == KILL_RING_SIZE {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(text);
+    }
+
+    /// The most recently killed text
+    #[must_use]
+    pub fn top(&self) -> Option<&str> {
+        self.entries.back().map(String::as_str)
+    }
+
+    /// Forgets the last yank, so that `yank-pop` does nothing until the next `yank`
+    pub fn end_yank(&mut self) {
+        self.last_yank = None;
+    }
+}
+
 pub struct Input {
     pub prompt: String,
     /// see `alternate_value`
@@ -143,12 +252,24 @@ pub struct Input {
     pub theme: Arc<ColorTheme>,
     /// Border type
     pub border: BorderType,
+    /// Label on the border (`--input-label`, `change-input-label`)
+    pub(crate) label: Option<String>,
+    label_pos: LabelPosition,
     /// Status information to display as the input's title
     pub status_info: Option<StatusInfo>,
     /// How to display the info/status (default, inline, or hidden)
     pub info: Info,
     /// Whether layout is reversed (status goes below input instead of above)
     pub reverse: bool,
+    /// Characters ending a word for the word actions, besides whitespace; when unset, words are
+    /// alphanumeric runs
+    pub word_separators: Option<String>,
+    /// Killed text, see [`KillRing`]
+    pub kill_ring: KillRing,
+    /// Values and cursor positions before the last edits, most recent last
+    undo_stack: Vec<(String, u16)>,
+    /// Whether the last recorded edit was typing, which the next typed characters join
+    typing: bool,
 }
 
 impl Default for Input {
@@ -160,48 +281,55 @@ impl Default for Input {
 impl Input {
     pub fn insert(&mut self, c: char) {
         self.value.insert(self.cursor_pos.into(), c);
-        // unwrap: len_utf8 < 4
-        self.move_cursor(c.len_utf8().try_into().unwrap());
+        self.move_cursor_to(
+            self.cursor_pos
+                .saturating_add(u16::try_from(c.len_utf8()).unwrap_or(u16::MAX)),
+        );
     }
     pub fn insert_str(&mut self, s: &str) {
         self.value.insert_str(self.cursor_pos as usize, s);
-        self.move_cursor(
-            s.chars()
-                .count()
-                .try_into()
-                .expect("Failed to fit inserted str len into an i32"),
+        self.move_cursor_to(
+            self.cursor_pos
+                .saturating_add(u16::try_from(s.len()).expect("Failed to fit inserted str len into an u16")),
         );
     }
-    fn nchars(&self) -> usize {
-        self.value.chars().count()
-    }
-    pub fn delete(&mut self, offset: i32) -> Option<char> {
-        if self.value.is_empty() {
-            return None;
+    /// Byte position `offset` grapheme clusters away from the cursor, clamped to the value
+    fn grapheme_boundary(&self, offset: i32) -> usize {
+        let cursor = self.cursor_pos as usize;
+        let count = offset.unsigned_abs() as usize;
+        if offset < 0 {
+            self.value[..cursor]
+                .grapheme_indices(true)
+                .nth_back(count - 1)
+                .map_or(0, |(pos, _)| pos)
+        } else {
+            self.value[cursor..]
+                .grapheme_indices(true)
+                .nth(count)
+                .map_or(self.value.len(), |(pos, _)| cursor + pos)
         }
-        let new_pos = i32::from(self.cursor_pos) + offset;
-        if new_pos < 0 || usize::try_from(new_pos).map_or(true, |p| p >= self.value.len()) {
+    }
+    /// Delete the grapheme cluster `offset` clusters away from the cursor: `-1` is the one before
+    /// the cursor, `0` the one under it
+    pub fn delete(&mut self, offset: i32) -> Option<String> {
+        let start = self.grapheme_boundary(offset);
+        let end = self.grapheme_boundary(offset + 1);
+        if start >= end {
             return None;
         }
-        let pos = self.value.floor_char_boundary(new_pos.unsigned_abs() as usize);
-        let ch = self.value.remove(pos);
+        let deleted: String = self.value.drain(start..end).collect();
         // Only move cursor if deleting backwards
         if offset < 0 {
-            self.move_cursor(-1);
+            self.cursor_pos -= u16::try_from(deleted.len()).unwrap_or(u16::MAX);
         }
-        Some(ch)
+        Some(deleted)
     }
+    /// Move the cursor by `offset` grapheme clusters
     pub fn move_cursor(&mut self, offset: i32) {
         if offset == 0 {
             return;
         }
-        if offset < 0 {
-            let new_pos = (i32::from(self.cursor_pos) + offset).max(0).unsigned_abs() as usize;
-            self.move_cursor_to(u16::try_from(self.value.floor_char_boundary(new_pos)).unwrap_or(u16::MAX));
-        } else {
-            let new_pos = (i32::from(self.cursor_pos) + offset).unsigned_abs() as usize;
-            self.move_cursor_to(u16::try_from(self.value.ceil_char_boundary(new_pos)).unwrap_or(u16::MAX));
-        }
+        self.move_cursor_to(u16::try_from(self.grapheme_boundary(offset)).unwrap_or(u16::MAX));
     }
     pub fn move_cursor_to(&mut self, pos: u16) {
         if self.value.is_char_boundary(pos as usize) {
@@ -219,112 +347,41 @@ impl Input {
         );
     }
 
-    /// Check if a character is a word character (alphanumeric only)
-    fn is_word_char(ch: char) -> bool {
-        ch.is_alphanumeric()
+    /// Check if a character is part of a word: not whitespace nor one of the `word_separators`,
+    /// or alphanumeric when these are unset
+    fn is_word_char(&self, ch: char) -> bool {
+        match &self.word_separators {
+            Some(separators) => !ch.is_whitespace() && !separators.contains(ch),
+            None => crate::field::is_word_char(ch),
+        }
     }
 
     /// Find the position of the end of the next word (alphanumeric boundaries for deletion)
     fn find_next_word_end(&self, start_pos: usize) -> usize {
-        let mut pos = start_pos;
-
+        let rest = &self.value[start_pos..];
         // Skip any non-word characters
-        while pos < self.nchars() {
-            let ch = self.value.chars().nth(pos).unwrap();
-            if Self::is_word_char(ch) {
-                break;
-            }
-            pos += 1;
-        }
-
+        let word = rest.trim_start_matches(|ch| !self.is_word_char(ch));
         // Skip to the end of the word
-        while pos < self.nchars() {
-            let ch = self.value.chars().nth(pos).unwrap();
-            if !Self::is_word_char(ch) {
-                break;
-            }
-            pos += 1;
-        }
-
-        pos
+        self.value.len() - word.trim_start_matches(|ch| self.is_word_char(ch)).len()
     }
 
     /// Find the end of compound word (whitespace boundaries for cursor movement)
     fn find_compound_word_end(&self, start_pos: usize) -> usize {
-        let mut pos = start_pos;
-
-        // Skip any whitespace
-        while pos < self.nchars() {
-            let ch = self.value.chars().nth(pos).unwrap();
-            if !ch.is_whitespace() {
-                break;
-            }
-            pos += 1;
-        }
-
-        // Skip to the end of the non-whitespace sequence (includes punctuation)
-        while pos < self.nchars() {
-            let ch = self.value.chars().nth(pos).unwrap();
-            if ch.is_whitespace() {
-                break;
-            }
-            pos += 1;
-        }
-
-        pos
+        let rest = &self.value[start_pos..];
+        // Skip any whitespace, then the non-whitespace sequence (includes punctuation)
+        let word = rest.trim_start_matches(char::is_whitespace);
+        self.value.len() - word.trim_start_matches(|ch: char| !ch.is_whitespace()).len()
     }
 
     /// Find the position of the start of the previous word (alphanumeric word boundaries)
+    ///
+    /// This is also where a backward word deletion stops.
     fn find_prev_word_start(&self, start_pos: usize) -> usize {
-        if start_pos == 0 {
-            return 0;
-        }
-
-        let mut pos = start_pos;
-
-        // Move back at least one position
-        pos = pos.saturating_sub(1);
-
-        // Skip any non-word characters
-        while pos > 0 && !Self::is_word_char(self.value.chars().nth(pos).unwrap()) {
-            pos -= 1;
-        }
-
-        // Skip to the beginning of the word
-        while pos > 0 && Self::is_word_char(self.value.chars().nth(pos - 1).unwrap()) {
-            pos -= 1;
-        }
-
-        pos
-    }
-
-    /// Find the position to delete backward to (stops at non-word characters)
-    fn find_delete_backward_pos(&self, start_pos: usize) -> usize {
-        if start_pos == 0 {
-            return 0;
-        }
-
-        let mut pos = start_pos;
-
+        let head = &self.value[..start_pos];
         // Skip any non-word characters (whitespace, punctuation, etc.)
-        while pos > 0 {
-            let ch = self.value.chars().nth(pos - 1).unwrap();
-            if Self::is_word_char(ch) {
-                break;
-            }
-            pos -= 1;
-        }
-
+        let word = head.trim_end_matches(|ch| !self.is_word_char(ch));
         // Skip back through word characters
-        while pos > 0 {
-            let ch = self.value.chars().nth(pos - 1).unwrap();
-            if !Self::is_word_char(ch) {
-                break;
-            }
-            pos -= 1;
-        }
-
-        pos
+        word.trim_end_matches(|ch| self.is_word_char(ch)).len()
     }
 
     pub fn delete_backward_word(&mut self) -> String {
@@ -332,7 +389,7 @@ impl Input {
             return String::new();
         }
         // Delete back by alphanumeric word boundaries (for Alt+Backspace)
-        let start_pos = self.find_delete_backward_pos(self.cursor_pos as usize);
+        let start_pos = self.find_prev_word_start(self.cursor_pos as usize);
         let deleted = self.value[start_pos..self.cursor_pos as usize].to_string();
         self.value = format!(
             "{}{}",
@@ -347,18 +404,21 @@ impl Input {
         if self.cursor_pos == 0 {
             return String::new();
         }
-        // Unix word rubout: delete back to whitespace (for Ctrl+W)
-        let mut pos = self.cursor_pos as usize;
-
-        // Skip any trailing whitespace
-        while pos > 0 && self.value.chars().nth(pos - 1).unwrap_or_default().is_whitespace() {
-            pos -= 1;
-        }
+        // Unix word rubout: delete back to whitespace, or a word separator (for Ctrl+W)
+        let head = &self.value[..self.cursor_pos as usize];
+        let is_boundary = |ch: char| {
+            ch.is_whitespace()
+                || self
+                    .word_separators
+                    .as_ref()
+                    .is_some_and(|separators| separators.contains(ch))
+        };
 
-        // Delete back to next whitespace or start
-        while pos > 0 && !self.value.chars().nth(pos - 1).unwrap_or_default().is_whitespace() {
-            pos -= 1;
-        }
+        // Skip any trailing boundaries, then delete back to the next boundary or start
+        let pos = head
+            .trim_end_matches(is_boundary)
+            .trim_end_matches(|ch| !is_boundary(ch))
+            .len();
 
         let deleted = self.value[pos..self.cursor_pos as usize].to_string();
         self.value = format!("{}{}", &self.value[..pos], &self.value[self.cursor_pos as usize..]);
@@ -399,6 +459,143 @@ impl Input {
         std::mem::swap(&mut self.prompt, &mut self.alternate_prompt);
         std::mem::swap(&mut self.value, &mut self.alternate_value);
         std::mem::swap(&mut self.cursor_pos, &mut self.alternate_cursor_pos);
+        // the edits were made to the other value
+        self.undo_stack.clear();
+        self.kill_ring.end_yank();
+    }
+
+    /// Swap the grapheme cluster before the cursor with the one under it, moving past both; at
+    /// the end of the value, swap the last two
+    pub fn transpose_chars(&mut self) -> bool {
+        if self.cursor_pos == 0 {
+            return false;
+        }
+        if self.cursor_pos as usize == self.value.len() {
+            self.move_cursor(-1);
+        }
+        let start = self.grapheme_boundary(-1);
+        let middle = self.cursor_pos as usize;
+        let end = self.grapheme_boundary(1);
+        if start == middle || middle == end {
+            self.move_to_end();
+            return false;
+        }
+        let swapped = format!("{}{}", &self.value[middle..end], &self.value[start..middle]);
+        self.value.replace_range(start..end, &swapped);
+        self.cursor_pos = u16::try_from(end).unwrap_or(u16::MAX);
+        true
+    }
+
+    /// Insert the most recently killed text at the cursor
+    pub fn yank(&mut self) -> bool {
+        let Some(text) = self.kill_ring.top().map(str::to_owned) else {
+            return false;
+        };
+        let start = self.cursor_pos as usize;
+        self.insert_str(&text);
+        self.kill_ring.last_yank = Some((self.kill_ring.entries.len() - 1, start..self.cursor_pos as usize));
+        true
+    }
+
+    /// Replace the text inserted by the last `yank` or `yank-pop` with the previous entry of the
+    /// kill ring
+    pub fn yank_pop(&mut self) -> bool {
+        let Some((index, range)) = self.kill_ring.last_yank.clone() else {
+            return false;
+        };
+        let entries = &self.kill_ring.entries;
+        if range.end != self.cursor_pos as usize
+            || self.value.get(range.clone()) != entries.get(index).map(String::as_str)
+        {
+            self.kill_ring.end_yank();
+            return false;
+        }
+        let index = index.checked_sub(1).unwrap_or(entries.len() - 1);
+        let text = entries[index].clone();
+        let end = range.start + text.len();
+        self.value.replace_range(range.clone(), &text);
+        self.cursor_pos = u16::try_from(end).unwrap_or(u16::MAX);
+        self.kill_ring.last_yank = Some((index, range.start..end));
+        true
+    }
+
+    /// Byte range of the query token before the cursor, without its leading `'`, `^` and `!`
+    #[must_use]
+    pub fn token_range(&self) -> Range<usize> {
+        let cursor = self.cursor_pos as usize;
+        let head = &self.value[..cursor];
+        let token = head.rsplit(char::is_whitespace).next().unwrap_or(head);
+        let token = token.trim_start_matches(['\'', '^', '!']);
+        cursor - token.len()..cursor
+    }
+
+    /// Record the value and cursor position from before an action, if the action changed the
+    /// value; consecutive `typing` edits are undone together
+    pub fn record_edit(&mut self, value: String, cursor_pos: u16, typing: bool) {
+        if value == self.value {
+            self.typing = false;
+            return;
+        }
+        if !(typing && self.typing) {
+            if self.undo_stack.len() == UNDO_LIMIT {
+                self.undo_stack.remove(0);
+            }
+            self.undo_stack.push((value, cursor_pos));
+        }
+        self.typing = typing;
+    }
+
+    /// Restore the value from before the last recorded edit
+    pub fn undo(&mut self) -> bool {
+        let Some((value, cursor_pos)) = self.undo_stack.pop() else {
+            return false;
+        };
+        self.value = value;
+        self.cursor_pos = cursor_pos;
+        self.typing = false;
+        true
+    }
+}
+
+/// Words of `text` starting with `prefix`, from a word boundary to the next whitespace
+pub(crate) fn completions<'a>(text: &'a str, prefix: &'a str) -> impl Iterator<Item = &'a str> {
+    text.char_indices()
+        .filter(move |&(pos, _)| {
+            text[..pos]
+                .chars()
+                .next_back()
+                .is_none_or(|ch| !crate::field::is_word_char(ch))
+        })
+        .filter(move |&(pos, _)| text[pos..].starts_with(prefix))
+        .map(move |(pos, _)| text[pos..].split(char::is_whitespace).next().unwrap_or_default())
+}
+
+/// Longest prefix shared by all the `words`
+pub(crate) fn common_prefix<'a>(mut words: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let first = words.next()?;
+    Some(words.fold(first, |prefix, word| {
+        let len = prefix
+            .char_indices()
+            .zip(word.chars())
+            .find(|&((_, a), b)| a != b)
+            .map_or(prefix.len().min(word.len()), |((pos, _), _)| pos);
+        &prefix[..len]
+    }))
+}
+
+impl Input {
+    /// The secondary query line of **--query2**, without border nor status
+    pub(crate) fn secondary(options: &SkimOptions, theme: Arc<ColorTheme>) -> Self {
+        let mut res = Self::from_options(options, theme);
+        res.prompt.clone_from(&options.prompt2);
+        res.value = options.query2.clone().unwrap_or_default();
+        res.cursor_pos = u16::try_from(res.value.len()).unwrap_or(u16::MAX);
+        res.border = BorderType::None;
+        res.info = Info {
+            display: InfoDisplay::Hidden,
+            separator: None,
+        };
+        res
     }
 }
 
@@ -407,6 +604,8 @@ impl SkimWidget for Input {
         let mut res = Self {
             theme,
             border: options.border,
+            label: options.input_label.clone(),
+            label_pos: LabelPosition::from(options.input_label_pos.as_deref().unwrap_or_default()),
             info: options.info.clone(),
             reverse: options.layout == TuiLayout::Reverse,
             prompt: String::new(),
@@ -416,6 +615,10 @@ impl SkimWidget for Input {
             cursor_pos: 0,
             alternate_cursor_pos: 0,
             status_info: None,
+            word_separators: options.word_separators.clone(),
+            kill_ring: KillRing::default(),
+            undo_stack: Vec::new(),
+            typing: false,
         };
         if options.interactive {
             res.prompt.clone_from(&options.cmd_prompt);
@@ -453,6 +656,9 @@ impl SkimWidget for Input {
                 .borders(Borders::ALL)
                 .border_type(border_type)
                 .border_style(self.theme.border);
+            if let Some(label) = &self.label {
+                block = self.label_pos.title(block, label, self.theme.label);
+            }
         }
 
         // Handle different info display modes
@@ -13,6 +13,13 @@ fn status() -> StatusInfo {
         hscroll_offset: 3,
         start: None,
         inline_separator: " < ".to_string(),
+        windowed: 0,
+        duplicates: 0,
+        long_items: 0,
+        nth: None,
+        sources: Vec::new(),
+        watch_reloads: None,
+        message: None,
     }
 }
 
@@ -41,6 +48,57 @@ fn left_title_with_spinner_mode_progress_and_selection() {
     assert!(title.contains("[4]"));
 }
 
+#[test]
+fn status_shows_the_message_last() {
+    let mut s = status();
+    s.message = Some("! boom".to_string());
+    assert!(s.left_title().ends_with("42/100 ! boom"));
+    assert_eq!(s.inline_status(), "42/100 ! boom");
+}
+
+#[test]
+fn status_counts_the_windowed_items() {
+    let mut s = status();
+    s.windowed = 3;
+    s.message = Some("! boom".to_string());
+    assert_eq!(s.inline_status(), "42/100 ~3 ! boom");
+}
+
+#[test]
+fn status_counts_the_duplicates() {
+    let mut s = status();
+    s.windowed = 3;
+    s.duplicates = 7;
+    assert!(s.left_title().ends_with("42/100 ~3 dup:7"));
+    assert_eq!(s.inline_status(), "42/100 ~3 dup:7");
+}
+
+#[test]
+fn status_counts_the_long_items() {
+    let mut s = status();
+    s.duplicates = 7;
+    s.long_items = 2;
+    assert!(s.left_title().ends_with("42/100 dup:7 long:2"));
+    assert_eq!(s.inline_status(), "42/100 dup:7 long:2");
+}
+
+#[test]
+fn status_counts_the_items_of_each_source() {
+    let mut s = status();
+    s.sources = vec![(Arc::from("git"), 12), (Arc::from("files"), 88)];
+    assert!(s.left_title().ends_with("42/100 git:12 files:88"));
+    assert_eq!(s.inline_status(), "42/100 git:12 files:88");
+}
+
+#[test]
+fn status_counts_the_watch_reloads() {
+    let mut s = status();
+    s.watch_reloads = Some(2);
+    s.message = Some("! boom".to_string());
+    assert!(s.left_title().ends_with("42/100 watch:2 ! boom"));
+    assert_eq!(s.inline_status(), "42/100 watch:2 ! boom");
+}
+
 #[test]
 fn inline_separator_switches_with_spinner() {
     let mut s = status();
@@ -51,7 +109,7 @@ fn inline_separator_switches_with_spinner() {
     s.start = Some(Instant::now());
     let out = s.inline_separator_or_spinner();
     assert_ne!(out, " < ");
-    assert!(!out.is_empty());
+    assert_ne!(out, "");
 }
 
 #[test]
@@ -217,6 +275,113 @@ fn delete_at_bounds_returns_none() {
     assert!(input.delete(0).is_none());
 }
 
+#[test]
+fn cursor_moves_and_deletes_by_grapheme_cluster() {
+    let mut input = Input {
+        prompt: String::new(),
+        ..Input::default()
+    };
+    // `e` + U+0301, a ZWJ family emoji and a CJK char
+    input.insert_str("e\u{301}\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}日");
+    input.move_cursor(-1);
+    assert_eq!(&input.value[input.cursor_pos as usize..], "日");
+    input.move_cursor(-1);
+    assert_eq!(input.cursor_pos, 3);
+    assert_eq!(input.cursor_pos(), 1);
+    assert_eq!(
+        input.delete(0).as_deref(),
+        Some("\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}")
+    );
+    assert_eq!(input.delete(-1).as_deref(), Some("e\u{301}"));
+    assert_eq!((input.value.as_str(), input.cursor_pos), ("日", 0));
+    input.move_cursor(1);
+    assert_eq!(input.cursor_pos(), 2);
+}
+
+#[test]
+fn word_motions_on_multibyte_text() {
+    let mut input = Input::default();
+    input.insert_str("café über");
+    assert_eq!(input.delete_backward_word(), "über");
+    input.move_cursor_backward_word();
+    assert_eq!(input.cursor_pos, 0);
+    input.move_cursor_forward_word();
+    assert_eq!(input.cursor_pos as usize, "café".len());
+}
+
+#[test]
+fn word_separators_end_words() {
+    let mut input = Input {
+        word_separators: Some(String::from("/.")),
+        ..Input::default()
+    };
+    input.insert_str("src/tui/app_tests.rs");
+    assert_eq!(input.delete_backward_word(), "rs");
+    assert_eq!(input.delete_backward_to_whitespace(), "app_tests.");
+    assert_eq!(input.value, "src/tui/");
+    input.move_cursor_to(0);
+    assert_eq!(input.delete_forward_word(), "src");
+
+    // without separators, words are alphanumeric and rubout stops at whitespace only
+    let mut input = Input::default();
+    input.insert_str("a src/tui_app");
+    assert_eq!(input.delete_backward_word(), "app");
+    assert_eq!(input.delete_backward_to_whitespace(), "src/tui_");
+}
+
+#[test]
+fn transpose_chars_swaps_around_the_cursor() {
+    let mut input = Input::default();
+    input.insert_str("abc");
+    input.move_cursor_to(1);
+    assert!(input.transpose_chars());
+    assert_eq!((input.value.as_str(), input.cursor_pos), ("bac", 2));
+    // at the end, the last two are swapped
+    input.move_to_end();
+    assert!(input.transpose_chars());
+    assert_eq!((input.value.as_str(), input.cursor_pos), ("bca", 3));
+    input.move_cursor_to(0);
+    assert!(!input.transpose_chars());
+
+    let mut input = Input::default();
+    input.insert_str("e\u{301}日");
+    assert!(input.transpose_chars());
+    assert_eq!(input.value, "日e\u{301}");
+}
+
+#[test]
+fn token_range_skips_the_query_syntax() {
+    let mut input = Input::default();
+    input.insert_str("foo !^bar baz");
+    input.move_cursor_to(9);
+    assert_eq!(&input.value[input.token_range()], "bar");
+    input.move_cursor_to(4);
+    assert_eq!(input.token_range(), 4..4);
+}
+
+#[test]
+fn completions_and_common_prefix() {
+    let words: Vec<&str> = completions("a/app.rs apply xapp", "app").collect();
+    assert_eq!(words, ["app.rs", "apply"]);
+    assert_eq!(common_prefix(words.into_iter()), Some("app"));
+    assert_eq!(common_prefix(["héllo", "hélas"].into_iter()), Some("hél"));
+    assert_eq!(common_prefix(["abc", "ab"].into_iter()), Some("ab"));
+    assert_eq!(common_prefix(std::iter::empty()), None);
+}
+
+#[test]
+fn kill_ring_keeps_the_last_entries() {
+    let mut ring = KillRing::default();
+    ring.push(String::new());
+    assert_eq!(ring.top(), None);
+    for i in 0..=KILL_RING_SIZE {
+        ring.push(i.to_string());
+    }
+    assert_eq!(ring.entries.len(), KILL_RING_SIZE);
+    assert_eq!(ring.entries.front().map(String::as_str), Some("1"));
+    assert_eq!(ring.top(), Some("16"));
+}
+
 #[test]
 fn input_render_writes_prompt_and_value() {
     use ratatui::buffer::Buffer;
@@ -1,22 +1,27 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use std::sync::Arc;
 
 use indexmap::IndexSet;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
 use ratatui::widgets::{
     Block, Borders, Clear, List, ListDirection, ListItem, Scrollbar, ScrollbarOrientation, ScrollbarState,
     StatefulWidget, Widget,
 };
 use regex::Regex;
 
-use crate::item::MatchedItem;
-use crate::options::feature_flag;
+use crate::item::{MatchedItem, RankBuilder};
+use crate::options::{SelectionOrder, feature_flag};
 use crate::spinlock::SpinLock;
 use crate::theme::ColorTheme;
 use crate::tui::BorderType;
 use crate::tui::item_renderer::ItemRenderer;
-use crate::tui::options::TuiLayout;
+use crate::tui::options::{LabelPosition, TruncateSide, TuiLayout};
+use crate::tui::tree::{self, TreeItem};
 use crate::tui::widget::{SkimRender, SkimWidget};
-use crate::{Selector, SkimOptions};
+use crate::{Selector, SkimItem, SkimOptions};
 
 /// How to apply processed items to the display list
 #[derive(Default, Clone, Copy)]
@@ -62,15 +67,27 @@ pub struct ItemList {
     pub(crate) multi_select: bool,
     reserved: usize,
     pub(crate) no_hscroll: bool,
+    pub(crate) hscroll_off: usize,
+    /// Items kept visible around the cursor, see `--scroll-off`
+    scroll_off: usize,
     pub(crate) ellipsis: String,
     pub(crate) keep_right: bool,
+    pub(crate) truncate_side: TruncateSide,
     pub(crate) skip_to_pattern: Option<Regex>,
     pub(crate) tabstop: usize,
     selector: Option<Rc<dyn Selector>>,
     pre_select_target: usize, // How many items we want to pre-select
+    /// Items the selector already pre-selected, left alone once deselected
+    pre_selected: HashSet<MatchedItem>,
+    /// At most this many items can be selected, see `--multi=N`
+    pub(crate) multi_max: Option<usize>,
+    /// Whether a selection was refused, see [`take_refused`](Self::take_refused)
+    refused: bool,
     no_clear_if_empty: bool,
-    interactive: bool,              // Whether we're in interactive mode
-    showing_stale_items: bool,      // True when displaying old items due to no_clear_if_empty
+    interactive: bool,         // Whether we're in interactive mode
+    showing_stale_items: bool, // True when displaying old items due to no_clear_if_empty
+    /// True when the items are the near misses of `--suggest-on-empty`, nothing matching the query
+    pub(crate) suggestions: bool,
     pub(crate) manual_hscroll: i32, // Manual horizontal scroll offset for ScrollLeft/ScrollRight
     pub(crate) selector_icon: String,
     pub(crate) multi_select_icon: String,
@@ -80,13 +97,48 @@ pub struct ItemList {
     pub(crate) multiline: Option<String>,
     /// Border type
     pub border: BorderType,
+    /// Label on the border (`--list-label`, `change-list-label`)
+    pub(crate) label: Option<String>,
+    label_pos: LabelPosition,
     /// When true, prepend each item's match score to its display text
     pub(crate) show_score: bool,
     pub(crate) show_index: bool,
     /// When true, highlight the entire current line (not just the matched text)
     pub(crate) highlight_line: bool,
+    /// When true (and in multi-selection mode), show `selected/matched` on the current line
+    pub(crate) selection_count: bool,
     /// Scrollbar display configuration
     pub(crate) scrollbar_thumb: String,
+    /// Label characters of `jump`, see `--jump-labels`
+    pub(crate) jump_labels: Vec<char>,
+    /// Whether the visible items show their `jump` labels
+    pub(crate) jumping: bool,
+    /// How many items, from `offset`, the last render showed
+    visible_items: usize,
+    /// Whether the items are laid out as a tree of their paths, see `--tree`
+    pub(crate) tree: bool,
+    /// The matched items in rank order while `items` lists them by group or as a tree
+    layout_source: Option<Vec<MatchedItem>>,
+    /// Matched count of each group, see [`SkimItem::group`](crate::SkimItem::group)
+    group_counts: HashMap<String, usize>,
+    /// Groups collapsed to their best match by `toggle-group`
+    collapsed_groups: HashSet<String>,
+    /// Tree nodes whose descendants are hidden by `toggle-group`
+    collapsed_paths: HashSet<String>,
+    /// The selection and cursor to find again in the items of a reload, see `--reload-interval`
+    kept: Option<KeptSelection>,
+    /// At most this many matched items are listed, see `--max-display`
+    max_display: Option<usize>,
+    /// Number of the matched items past `max_display`, dropped from the list
+    pub(crate) hidden: usize,
+}
+
+/// Texts of the selected items and of the current one before a reload
+struct KeptSelection {
+    selected: HashSet<String>,
+    current: Option<String>,
+    /// Whether the items of the reload arrived, replacing the old selection
+    replaced: bool,
 }
 
 impl Default for ItemList {
@@ -102,10 +154,16 @@ impl ItemList {
 
     /// Returns the count of items for status display.
     ///
-    /// This may differ from `items.len()` when `no_clear_if_empty` is active and showing stale items
+    /// This may differ from `items.len()` when `no_clear_if_empty` is active and showing stale items,
+    /// when showing the near misses of `suggest_on_empty`, when the items are grouped or laid
+    /// out as a tree, or when `--max-display` drops some
     #[must_use]
     pub fn count(&self) -> usize {
-        if self.showing_stale_items { 0 } else { self.items.len() }
+        if self.showing_stale_items || self.suggestions {
+            0
+        } else {
+            self.layout_source.as_ref().unwrap_or(&self.items).len() + self.hidden
+        }
     }
 
     /// Returns the currently selected item, if any
@@ -113,7 +171,7 @@ impl ItemList {
     pub fn selected(&self) -> Option<MatchedItem> {
         let item = self.items.get(self.cursor());
         if item.is_some_and(|i| !i.item.disabled()) {
-            item.cloned()
+            item.cloned().map(TreeItem::unwrap)
         } else {
             None
         }
@@ -123,6 +181,24 @@ impl ItemList {
     pub fn append(&mut self, items: &mut Vec<MatchedItem>) {
         self.items.append(items);
         self.showing_stale_items = false;
+        self.suggestions = false;
+    }
+
+    /// Shows the near misses of a search that matched nothing, in place of its empty result
+    pub(crate) fn show_suggestions(&mut self, items: Vec<MatchedItem>) {
+        self.items = items;
+        self.layout_source = None;
+        self.current = 0;
+        self.offset = 0;
+        self.sub_offset = 0;
+        self.showing_stale_items = false;
+        self.suggestions = !self.items.is_empty();
+    }
+
+    /// Index of the visible item labeled `label` by `jump`
+    pub(crate) fn jump_target(&self, label: char) -> Option<usize> {
+        let position = self.jump_labels.iter().position(|&c| c == label)?;
+        (position < self.visible_items).then_some(self.offset + position)
     }
 
     /// Toggles the selection state of the item at the given index
@@ -130,9 +206,9 @@ impl ItemList {
         if self.items.is_empty() {
             return;
         }
-        let item = &self.items[index];
+        let item = self.items[index].clone();
         trace!("Toggled item {} at index {}", item.text(), index);
-        toggle_item(&mut self.selection, item);
+        self.toggle_item(&item);
         trace!(
             "Selection is now {:#?}",
             self.selection.iter().map(|item| item.item.text()).collect::<Vec<_>>()
@@ -144,8 +220,9 @@ impl ItemList {
     }
     /// Toggles the selection state of all items
     pub fn toggle_all(&mut self) {
-        for item in &self.items {
-            toggle_item(&mut self.selection, item);
+        for index in 0..self.items.len() {
+            let item = self.items[index].clone();
+            self.toggle_item(&item);
         }
     }
 
@@ -157,18 +234,75 @@ impl ItemList {
 
     /// Add row to selection
     pub fn select_row(&mut self, index: usize) {
-        let item = self.items[index].clone();
-        if !item.disabled() {
-            self.selection.insert(item);
-        }
+        let item = TreeItem::unwrap(self.items[index].clone());
+        self.try_select(item);
     }
     /// Selects all items
     pub fn select_all(&mut self) {
         for item in self.items.clone() {
-            if !item.disabled() {
-                self.selection.insert(item.clone());
+            self.try_select(TreeItem::unwrap(item));
+        }
+    }
+
+    fn toggle_item(&mut self, item: &MatchedItem) {
+        if self.selection.contains(item) {
+            self.selection.shift_remove(item);
+        } else {
+            self.try_select(TreeItem::unwrap(item.clone()));
+        }
+    }
+
+    /// Selects `item` unless it is disabled or [`can_select`](Self::can_select) refuses it
+    fn try_select(&mut self, item: MatchedItem) {
+        if item.disabled() || self.selection.contains(&item) {
+            return;
+        }
+        if self.can_select(&item) {
+            self.selection.insert(item);
+        } else {
+            self.refused = true;
+        }
+    }
+
+    /// Whether `item` can join the selection, under `--multi=N` and [`Selector::can_select`]
+    fn can_select(&self, item: &MatchedItem) -> bool {
+        if self.selection_is_full() {
+            return false;
+        }
+        self.selector.as_ref().is_none_or(|selector| {
+            let selected: Vec<&dyn SkimItem> = self.selection.iter().map(|item| item.item.as_ref()).collect();
+            selector.can_select(item.item.as_ref(), &selected)
+        })
+    }
+
+    /// Whether the `--multi=N` items are selected
+    pub(crate) fn selection_is_full(&self) -> bool {
+        self.multi_max.is_some_and(|max| self.selection.len() >= max)
+    }
+
+    /// Whether a selection was refused since the last call
+    pub(crate) fn take_refused(&mut self) -> bool {
+        std::mem::take(&mut self.refused)
+    }
+
+    /// The selected items, in `order`
+    pub(crate) fn selection_in(&self, order: SelectionOrder) -> Vec<MatchedItem> {
+        let mut selected: Vec<MatchedItem> = self.selection.iter().cloned().collect();
+        match order {
+            SelectionOrder::Selection => {}
+            SelectionOrder::Display => {
+                let positions: HashMap<MatchedItem, usize> = self
+                    .items
+                    .iter()
+                    .enumerate()
+                    .map(|(position, item)| (TreeItem::unwrap(item.clone()), position))
+                    .collect();
+                // stable: the items no longer shown stay in selection order
+                selected.sort_by_key(|item| positions.get(item).copied().unwrap_or(usize::MAX));
             }
+            SelectionOrder::Input => selected.sort_by_key(|item| item.rank.index),
         }
+        selected
     }
     /// Clears all selections
     pub fn clear_selection(&mut self) {
@@ -177,11 +311,313 @@ impl ItemList {
     /// Clears all items from the list
     pub fn clear(&mut self) {
         self.items.clear();
+        self.layout_source = None;
         self.selection.clear();
+        self.pre_selected.clear();
         self.current = 0;
         self.offset = 0;
         self.sub_offset = 0;
         self.showing_stale_items = false;
+        self.suggestions = false;
+        self.hidden = 0;
+    }
+    /// Merges a batch of matcher results into the items
+    pub(crate) fn merge(&mut self, processed: ProcessedItems) {
+        // grouped items are merged in rank order, then grouped (or laid out as a tree) again
+        let mut existing = self
+            .layout_source
+            .take()
+            .unwrap_or_else(|| std::mem::take(&mut self.items));
+        let mut items = match processed.merge {
+            MergeStrategy::Replace => {
+                self.sub_offset = 0;
+                self.hidden = 0;
+                processed.items
+            }
+            MergeStrategy::SortedMerge => {
+                self.sub_offset = 0;
+                // in place: only the items ranking below the new ones move
+                MatchedItem::merge_into_sorted(&mut existing, processed.items);
+                existing
+            }
+            MergeStrategy::Append => {
+                existing.extend(processed.items);
+                existing
+            }
+        };
+        // the items past the limit rank below the listed ones, and stay below them in the next
+        // merges: only their number is kept
+        if let Some(max) = self.max_display
+            && items.len() > max
+        {
+            self.hidden += items.len() - max;
+            items.truncate(max);
+        }
+        self.lay_out(items);
+        self.restore_kept();
+    }
+
+    /// Sets the matched items, in rank order, listing them by group or as a tree if needed
+    fn lay_out(&mut self, items: Vec<MatchedItem>) {
+        if self.tree {
+            self.items = tree::layout(&items, &self.collapsed_paths);
+            self.layout_source = Some(items);
+        } else if items.iter().any(|item| item.item.group().is_some()) {
+            self.items = self.group(&items);
+            self.layout_source = Some(items);
+        } else {
+            self.items = items;
+            self.group_counts.clear();
+        }
+    }
+
+    /// Drops the items removed from the pool, see `--delta`
+    ///
+    /// The cursor and the scroll position stay on the items they were on, or on the next ones if
+    /// those are removed.
+    pub(crate) fn remove_items(&mut self, removed: &[Arc<dyn SkimItem>]) {
+        let removed: HashSet<*const ()> = removed.iter().map(|item| Arc::as_ptr(item).cast::<()>()).collect();
+        let is_removed = |item: &MatchedItem| removed.contains(&Arc::as_ptr(&item.item).cast::<()>());
+        if let Some(processed) = self.processed_items.lock().as_mut() {
+            processed.items.retain(|item| !is_removed(item));
+        }
+        self.selection.retain(|item| !is_removed(item));
+        let removed_before = |position: usize| self.items.iter().take(position).filter(|item| is_removed(item)).count();
+        let current = self.current - removed_before(self.current);
+        let offset = self.offset - removed_before(self.offset);
+        let mut items = self
+            .layout_source
+            .take()
+            .unwrap_or_else(|| std::mem::take(&mut self.items));
+        items.retain(|item| !is_removed(item));
+        self.lay_out(items);
+        let last = self.items.len().saturating_sub(1).max(self.reserved);
+        self.current = current.min(last);
+        self.offset = offset.min(self.current);
+    }
+
+    /// Moves the selected items back over the positions dropped from the pool
+    ///
+    /// See [`ItemPool::take_compacted`](crate::item::ItemPool::take_compacted); the matched items
+    /// are replaced by a new search.
+    pub(crate) fn reindex(&mut self, compacted: &[usize], rank_builder: &RankBuilder) {
+        self.selection = self
+            .selection
+            .iter()
+            .map(|item| {
+                let index = usize::try_from(item.rank.index).unwrap_or_default();
+                let index = index - compacted.partition_point(|&p| p < index);
+                let rank = crate::Rank {
+                    index: i32::try_from(index).unwrap_or(i32::MAX),
+                    ..item.rank
+                };
+                MatchedItem::new(item.item.clone(), rank, item.matched_range.clone(), rank_builder)
+            })
+            .collect();
+    }
+
+    /// The texts to pre-select from `--pre-select-items`, `--pre-select-file` and `--pre-select-cmd`
+    fn preset_items(options: &SkimOptions) -> Vec<String> {
+        use crate::interpreter::Interpreter;
+        use crate::util::{read_cmd_lines, read_file_lines};
+
+        let mut preset_items: Vec<String> = options
+            .pre_select_items
+            .split('\n')
+            .filter(|s| !s.is_empty())
+            .map(std::string::ToString::to_string)
+            .collect();
+
+        if let Some(ref pre_select_file) = options.pre_select_file
+            && let Ok(file_items) = read_file_lines(pre_select_file)
+        {
+            preset_items.extend(file_items);
+        }
+
+        if let Some(ref pre_select_cmd) = options.pre_select_cmd {
+            match read_cmd_lines(pre_select_cmd, &Interpreter::from_options(options)) {
+                Ok(cmd_items) => preset_items.extend(cmd_items),
+                Err(e) => warn!("could not pre-select the items of `{pre_select_cmd}`: {e}"),
+            }
+        }
+        preset_items
+    }
+
+    /// Keeps the selection and the cursor through a reload, finding their items again by text
+    ///
+    /// The old items stay shown until the new ones are matched. Call [`forget_kept`](Self::forget_kept)
+    /// once the reload is read.
+    pub(crate) fn keep_selection(&mut self) {
+        self.kept = Some(KeptSelection {
+            selected: self.selection.iter().map(|item| item.text().into_owned()).collect(),
+            current: self.selected().map(|item| item.text().into_owned()),
+            replaced: false,
+        });
+    }
+
+    /// Whether the selection is kept through the running reload
+    pub(crate) fn keeps_selection(&self) -> bool {
+        self.kept.is_some()
+    }
+
+    /// Stops looking for the kept selection in the new items
+    pub(crate) fn forget_kept(&mut self) {
+        // the last results may not be shown yet
+        self.merge_processed();
+        if self.kept.take().is_some_and(|kept| !kept.replaced) {
+            // the reload found nothing, the old items were still shown
+            self.items.clear();
+            self.selection.clear();
+            self.showing_stale_items = false;
+        }
+    }
+
+    /// Selects the new items with the texts of the kept selection, and moves the cursor back
+    fn restore_kept(&mut self) {
+        let Some(kept) = &mut self.kept else {
+            return;
+        };
+        if !kept.replaced {
+            kept.replaced = true;
+            self.selection.clear();
+        }
+        for item in &self.items {
+            if kept.selected.contains(item.text().as_ref()) && !self.selection.contains(item) {
+                self.selection.insert(item.clone());
+            }
+        }
+        if let Some(current) = &kept.current
+            && let Some(position) = self.items.iter().position(|item| item.text() == current.as_str())
+        {
+            self.current = position;
+            kept.current = None;
+        }
+    }
+
+    /// `items` listed by group, groups in the order of their best match, collapsed groups
+    /// keeping only it
+    fn group(&mut self, items: &[MatchedItem]) -> Vec<MatchedItem> {
+        let mut positions: HashMap<Option<Cow<'_, str>>, usize> = HashMap::new();
+        let mut groups: Vec<(Option<Cow<'_, str>>, Vec<MatchedItem>)> = Vec::new();
+        for item in items {
+            let group = item.item.group();
+            if let Some(&position) = positions.get(&group) {
+                groups[position].1.push(item.clone());
+            } else {
+                positions.insert(group.clone(), groups.len());
+                groups.push((group, vec![item.clone()]));
+            }
+        }
+        self.group_counts = groups
+            .iter()
+            .filter_map(|(group, members)| Some((group.as_deref()?.to_owned(), members.len())))
+            .collect();
+        let mut grouped = Vec::with_capacity(items.len());
+        for (group, mut members) in groups {
+            if group.is_some_and(|group| self.collapsed_groups.contains(group.as_ref())) {
+                members.truncate(1);
+            }
+            grouped.append(&mut members);
+        }
+        grouped
+    }
+
+    /// Collapses the group of the current item to its best match, or expands it back
+    ///
+    /// In a tree, collapses the current directory, or the parent of the current file, or
+    /// expands it back.
+    pub fn toggle_group(&mut self) {
+        if self.tree {
+            self.toggle_node();
+            return;
+        }
+        let Some(group) = self
+            .items
+            .get(self.current)
+            .and_then(|item| item.item.group().map(Cow::into_owned))
+        else {
+            return;
+        };
+        if !self.collapsed_groups.remove(&group) {
+            self.collapsed_groups.insert(group.clone());
+        }
+        if let Some(source) = self.layout_source.take() {
+            self.items = self.group(&source);
+            self.layout_source = Some(source);
+        }
+        // the best match of the group is the one left when it is collapsed
+        if let Some(first) = self
+            .items
+            .iter()
+            .position(|item| item.item.group().as_deref() == Some(group.as_str()))
+        {
+            self.current = first;
+        }
+    }
+
+    fn toggle_node(&mut self) {
+        let Some(node) = self
+            .items
+            .get(self.current)
+            .and_then(|item| item.item.as_any().downcast_ref::<TreeItem>())
+        else {
+            return;
+        };
+        let path = node.path();
+        let path = if node.has_children() {
+            path.into_owned()
+        } else if let Some(parent) = tree::parent_path(&path) {
+            parent.to_owned()
+        } else {
+            return;
+        };
+        if !self.collapsed_paths.remove(&path) {
+            self.collapsed_paths.insert(path.clone());
+        }
+        if let Some(source) = self.layout_source.take() {
+            self.items = tree::layout(&source, &self.collapsed_paths);
+            self.layout_source = Some(source);
+        }
+        if let Some(row) = self.items.iter().position(|item| {
+            item.item
+                .as_any()
+                .downcast_ref::<TreeItem>()
+                .is_some_and(|node| node.path() == path)
+        }) {
+            self.current = row;
+        }
+    }
+
+    /// The group of the item at `index` when it is the first of its group
+    fn group_start(&self, index: usize) -> Option<Cow<'_, str>> {
+        self.layout_source.as_ref()?;
+        let group = self.items.get(index)?.item.group()?;
+        if index > 0 && self.items[index - 1].item.group().as_ref() == Some(&group) {
+            return None;
+        }
+        Some(group)
+    }
+
+    /// The header shown before the item at `index` when it is the first of its group
+    fn group_header(&self, index: usize) -> Option<Line<'static>> {
+        let group = self.group_start(index)?;
+        let marker = if self.collapsed_groups.contains(group.as_ref()) {
+            '▸'
+        } else {
+            '▾'
+        };
+        let count = self.group_counts.get(group.as_ref()).copied().unwrap_or_default();
+        Some(Line::styled(format!("{marker} {group} ({count})"), self.theme.header))
+    }
+    /// Merges the latest matcher results, if any, outside of rendering
+    ///
+    /// Returns whether the items changed.
+    pub(crate) fn merge_processed(&mut self) -> bool {
+        let Some(processed) = self.processed_items.lock().take() else {
+            return false;
+        };
+        self.merge(processed);
+        true
     }
     /// Scrolls the list by `rows` terminal rows, counting each item's sub-lines.
     /// Positive = toward higher indices (up the screen in default layout).
@@ -213,6 +649,24 @@ impl ItemList {
         }
     }
 
+    /// Moves the cursor by `rows` terminal rows like [`scroll_by_rows`](Self::scroll_by_rows), scrolling
+    /// the list along so that the cursor keeps its place on screen. Used by the `page-*` actions.
+    pub fn page_by_rows(&mut self, rows: i32) {
+        let before = self.current;
+        self.scroll_by_rows(rows);
+        let last_offset = self
+            .items
+            .len()
+            .saturating_sub(usize::from(self.height))
+            .max(self.reserved);
+        self.offset = if self.current >= before {
+            (self.offset + (self.current - before)).min(last_offset)
+        } else {
+            self.offset.saturating_sub(before - self.current).max(self.reserved)
+        };
+        self.sub_offset = 0;
+    }
+
     /// Scrolls the list by the given offset
     pub fn scroll_by(&mut self, offset: i32) {
         if self.reserved >= self.items.len() {
@@ -222,12 +676,20 @@ impl ItemList {
         let total = i32::try_from(self.items.len()).unwrap_or(i32::MAX);
         let mut new = i32::try_from(self.current).unwrap_or(0) + offset;
         if self.cycle {
-            let n = total - reserved;
-            new = reserved + (new + n - reserved) % n;
+            new = reserved + (new - reserved).rem_euclid(total - reserved);
         } else {
             new = new.min(total - 1).max(reserved);
         }
-        self.current = new.max(0).unsigned_abs() as usize;
+        let new = new.max(0).unsigned_abs() as usize;
+        if offset > 0 && new < self.current {
+            // wrapped around to the top
+            self.offset = self.reserved;
+            self.sub_offset = 0;
+        } else if offset < 0 && new > self.current {
+            // wrapped around to the bottom
+            self.show_last_page(new);
+        }
+        self.current = new;
         debug!("Scrolled to {}", self.current);
         debug!("Selection: {:?}", self.selection);
     }
@@ -288,22 +750,31 @@ impl ItemList {
     pub fn jump_to_last(&mut self) {
         if !self.items.is_empty() {
             self.current = self.items.len().saturating_sub(1);
-            self.sub_offset = 0;
+            self.show_last_page(self.current);
         }
     }
 
+    /// Scrolls the list to the page ending with item `index`, instead of letting the next render
+    /// scroll it there one row at a time
+    fn show_last_page(&mut self, index: usize) {
+        self.offset = (index + 1).saturating_sub(usize::from(self.height)).max(self.reserved);
+        self.sub_offset = 0;
+    }
+
     /// Number of terminal rows item at `index` occupies.
     ///
     /// When `--multiline` is active this is the number of sub-lines produced by
     /// splitting on the separator; otherwise every item is exactly 1 row.
     fn item_row_count(&self, index: usize) -> usize {
-        if let Some(sep) = self.multiline.as_deref()
+        let rows = if let Some(sep) = self.multiline.as_deref()
             && let Some(item) = self.items.get(index)
         {
             item.item.text().split(sep).count().max(1)
         } else {
             1
-        }
+        };
+        // the group header counts as the first line of the first item of a group
+        rows + usize::from(self.group_start(index).is_some())
     }
 
     /// How many terminal rows are consumed by items `[from, from + count)`.
@@ -347,7 +818,6 @@ impl ItemList {
 impl SkimWidget for ItemList {
     fn from_options(options: &SkimOptions, theme: Arc<ColorTheme>) -> Self {
         use crate::helper::selector::DefaultSkimSelector;
-        use crate::util::read_file_lines;
 
         let skip_to_pattern = options
             .skip_to_pattern
@@ -359,29 +829,17 @@ impl SkimWidget for ItemList {
             || !options.pre_select_pat.is_empty()
             || !options.pre_select_items.is_empty()
             || options.pre_select_file.is_some()
+            || options.pre_select_cmd.is_some()
             || options.selector.is_some()
         {
             if let Some(s) = options.selector.clone() {
                 // For custom selectors, use a very large target (pre-select all matching)
                 (Some(s), usize::MAX)
             } else {
-                let mut preset_items: Vec<String> = options
-                    .pre_select_items
-                    .split('\n')
-                    .filter(|s| !s.is_empty())
-                    .map(std::string::ToString::to_string)
-                    .collect();
-
-                if let Some(ref pre_select_file) = options.pre_select_file
-                    && let Ok(file_items) = read_file_lines(pre_select_file)
-                {
-                    preset_items.extend(file_items);
-                }
-
                 let selector = DefaultSkimSelector::default()
                     .first_n(options.pre_select_n)
                     .regex(&options.pre_select_pat)
-                    .preset(preset_items.clone());
+                    .preset(Self::preset_items(options));
 
                 // Only use a target for --pre-select-n
                 // For pattern/items, the selector always returns the same matches regardless of timing
@@ -415,15 +873,22 @@ impl SkimWidget for ItemList {
             theme,
             multi_select,
             no_hscroll: options.no_hscroll,
+            hscroll_off: options.hscroll_off,
+            scroll_off: options.scroll_off,
             ellipsis: options.ellipsis.clone(),
             keep_right: options.keep_right,
+            truncate_side: options.truncate_side,
             skip_to_pattern,
             tabstop: options.tabstop.max(1),
             selector,
             pre_select_target,
+            pre_selected: HashSet::new(),
+            multi_max: options.multi_max,
+            refused: false,
             no_clear_if_empty,
             interactive,
             showing_stale_items: false,
+            suggestions: false,
             manual_hscroll: 0,
             items: Default::default(),
             selection: Default::default(),
@@ -439,10 +904,24 @@ impl SkimWidget for ItemList {
                 .clone()
                 .map(|opt_m| opt_m.unwrap_or(String::from("\\n"))),
             border: options.border,
+            label: options.list_label.clone(),
+            label_pos: LabelPosition::from(options.list_label_pos.as_deref().unwrap_or_default()),
             show_score: feature_flag!(options, ShowScore),
             show_index: feature_flag!(options, ShowIndex),
             highlight_line: options.highlight_line,
+            selection_count: options.selection_count && multi_select,
             scrollbar_thumb: options.scrollbar.clone(),
+            jump_labels: options.jump_labels.chars().collect(),
+            tree: options.tree,
+            jumping: false,
+            visible_items: 0,
+            layout_source: None,
+            group_counts: HashMap::new(),
+            collapsed_groups: HashSet::new(),
+            collapsed_paths: HashSet::new(),
+            kept: None,
+            max_display: options.max_display,
+            hidden: 0,
         }
     }
 
@@ -463,6 +942,26 @@ impl SkimWidget for ItemList {
         } else {
             area
         };
+        // the last row of the list tells how many matches `--max-display` left out
+        let (inner_area, footer_area) = if this.hidden > 0 && inner_area.height > 1 {
+            let (list_y, footer_y) = match this.direction {
+                ListDirection::TopToBottom => (inner_area.y, inner_area.y + inner_area.height - 1),
+                ListDirection::BottomToTop => (inner_area.y + 1, inner_area.y),
+            };
+            let list_area = ratatui::layout::Rect {
+                y: list_y,
+                height: inner_area.height - 1,
+                ..inner_area
+            };
+            let footer_area = ratatui::layout::Rect {
+                y: footer_y,
+                height: 1,
+                ..inner_area
+            };
+            (list_area, Some(footer_area))
+        } else {
+            (inner_area, None)
+        };
 
         this.height = inner_area.height;
         let available_rows = inner_area.height as usize;
@@ -475,46 +974,41 @@ impl SkimWidget for ItemList {
             this.current = this.current.min(this.items.len() - 1).max(this.reserved);
         }
 
-        if this.current < this.offset {
-            // Cursor moved above the top item: snap to it with no sub-line offset.
-            this.offset = this.current;
+        // `--scroll-off` items around the cursor stay visible, as long as the cursor can move
+        let margin = this.scroll_off.min(available_rows.saturating_sub(1) / 2);
+        let last_kept = (this.current + margin).min(this.items.len().saturating_sub(1));
+        if this.current < this.offset + margin {
+            // Cursor moved above the top items: snap to them with no sub-line offset.
+            this.offset = this.current.saturating_sub(margin).max(this.reserved).min(this.current);
             this.sub_offset = 0;
-        } else if this.rows_visible(this.offset, this.sub_offset, this.current) > available_rows {
-            // Current item is below the visible window: advance one row at a time.
-            (this.offset, this.sub_offset) = this.advance_to_fit(this.current, available_rows);
+        } else if this.rows_visible(this.offset, this.sub_offset, last_kept) > available_rows {
+            // Items kept below the cursor are out of the visible window: advance one row at a time.
+            (this.offset, this.sub_offset) = this.advance_to_fit(last_kept, available_rows);
         }
         let initial_current = this.selected();
 
         // Check for pre-processed items from background thread (non-blocking)
-        let items_updated = if let Some(processed) = this.processed_items.lock().take() {
+        let processed = this.processed_items.lock().take();
+        let items_updated = if let Some(processed) = processed {
             debug!("Render: Got {} processed items", processed.items.len());
 
             // Check if items are empty or blank for no_clear_if_empty handling
             let items_are_empty_or_blank =
                 processed.items.is_empty() || processed.items.iter().all(|item| item.item.text().trim().is_empty());
 
-            if this.interactive && this.no_clear_if_empty && items_are_empty_or_blank && !this.items.is_empty() {
+            // a reload keeping the selection shows the old items until it finds new ones
+            let keep_old =
+                (this.interactive && this.no_clear_if_empty) || this.kept.as_ref().is_some_and(|kept| !kept.replaced);
+            if keep_old && items_are_empty_or_blank && !this.items.is_empty() {
                 debug!(
                     "no_clear_if_empty: keeping {} old items for display (new items are empty/blank)",
                     this.items.len()
                 );
                 this.showing_stale_items = true;
             } else {
-                match processed.merge {
-                    MergeStrategy::Replace => {
-                        this.items = processed.items;
-                        this.sub_offset = 0;
-                    }
-                    MergeStrategy::SortedMerge => {
-                        let existing = std::mem::take(&mut this.items);
-                        this.items = MatchedItem::sorted_merge(existing, processed.items);
-                        this.sub_offset = 0;
-                    }
-                    MergeStrategy::Append => {
-                        this.items.extend(processed.items);
-                    }
-                }
+                this.merge(processed);
                 this.showing_stale_items = false;
+                this.suggestions = false;
 
                 // Apply pre-selection only when new items arrive and only if we haven't reached target
                 // This runs once per item batch, not on every render
@@ -533,7 +1027,7 @@ impl SkimWidget for ItemList {
                             break;
                         }
                         let should_select = selector.should_select(index, item.item.as_ref());
-                        if should_select {
+                        if should_select && this.can_select(item) && this.pre_selected.insert(item.clone()) {
                             debug!("Pre-selecting item[{}]: '{}'", index, item.item.text());
                             this.selection.insert(item.clone());
                         }
@@ -555,6 +1049,8 @@ impl SkimWidget for ItemList {
 
         let mut flat_rows: Vec<ListItem<'static>> = Vec::with_capacity(available_rows + 1);
         let mut rows_used = 0usize;
+        // the row of the first shown line of each item, counted from the start of the list
+        let mut first_rows: Vec<usize> = Vec::with_capacity(available_rows);
 
         for (idx, item) in this.items.iter().enumerate().skip(this.offset) {
             if rows_used >= available_rows {
@@ -562,8 +1058,16 @@ impl SkimWidget for ItemList {
             }
             let is_current = idx == this.current;
             let is_selected = this.selection.contains(item);
-            let skip_subs = if idx == this.offset { sub_offset } else { 0 };
-            rows_used += renderer.render_item(
+            let mut skip_subs = if idx == this.offset { sub_offset } else { 0 };
+            if let Some(header) = this.group_header(idx) {
+                if skip_subs == 0 {
+                    flat_rows.push(ListItem::new(header));
+                    rows_used += 1;
+                } else {
+                    skip_subs -= 1;
+                }
+            }
+            let added = renderer.render_item(
                 item,
                 is_current,
                 is_selected,
@@ -572,7 +1076,17 @@ impl SkimWidget for ItemList {
                 rows_used,
                 &mut flat_rows,
             );
+            if added == 0 {
+                break;
+            }
+            // bottom-to-top lists put the sub-lines of an item in reverse
+            first_rows.push(match this.direction {
+                ListDirection::TopToBottom => rows_used,
+                ListDirection::BottomToTop => rows_used + added.saturating_sub(1),
+            });
+            rows_used += added;
         }
+        this.visible_items = first_rows.len();
 
         let list = List::new(flat_rows).direction(this.direction).style(this.theme.normal);
 
@@ -580,10 +1094,13 @@ impl SkimWidget for ItemList {
 
         // Render border if enabled
         if let Some(border_type) = this.border.into_ratatui() {
-            let block = Block::default()
+            let mut block = Block::default()
                 .borders(Borders::ALL)
                 .border_type(border_type)
                 .border_style(this.theme.border);
+            if let Some(label) = &this.label {
+                block = this.label_pos.title(block, label, this.theme.label);
+            }
             Widget::render(block, area, buf);
         }
 
@@ -591,6 +1108,34 @@ impl SkimWidget for ItemList {
         // Widget — this bypasses ratatui's get_items_bounds which can index out of
         // bounds on our pre-sliced flat_rows when the selected row is near the edge.
         Widget::render(list, inner_area, buf);
+        if this.suggestions {
+            // near misses are dimmed, they do not match the query
+            buf.set_style(inner_area, Style::default().add_modifier(Modifier::DIM));
+        }
+        if let Some(footer_area) = footer_area {
+            let footer = format!("{:icon_width$}and {} more", "", this.hidden);
+            buf.set_stringn(
+                footer_area.x,
+                footer_area.y,
+                footer,
+                usize::from(footer_area.width),
+                this.theme.info,
+            );
+        }
+
+        // `jump` labels cover the selector column of the first line of the items
+        if this.jumping {
+            let label_width = this.selector_icon.chars().count().max(1);
+            let label_style = this.theme.cursor.add_modifier(Modifier::BOLD);
+            for (label, row) in this.jump_labels.iter().zip(&first_rows) {
+                let row = u16::try_from(*row).unwrap_or(u16::MAX);
+                let y = match this.direction {
+                    ListDirection::TopToBottom => inner_area.y + row,
+                    ListDirection::BottomToTop => (inner_area.y + inner_area.height).saturating_sub(row + 1),
+                };
+                buf.set_string(inner_area.x, y, format!("{label:<label_width$}"), label_style);
+            }
+        }
 
         // Render the scrollbar on top of the rightmost column of inner_area, but only
         // when there are more items than fit on screen (nothing to scroll → no bar).
@@ -630,14 +1175,6 @@ impl SkimWidget for ItemList {
     }
 }
 
-fn toggle_item(sel: &mut IndexSet<MatchedItem>, item: &MatchedItem) {
-    if sel.contains(item) {
-        sel.shift_remove(item);
-    } else if !item.disabled() {
-        sel.insert(item.clone());
-    }
-}
-
 #[cfg(test)]
 #[path = "item_list_tests.rs"]
 mod tests;
@@ -93,6 +93,23 @@ fn scroll_by_cycles_when_enabled() {
     assert_eq!(il.current, 2);
 }
 
+#[test]
+fn scroll_by_cycles_past_the_header_lines_and_moves_the_page() {
+    let mut il = list(30);
+    il.cycle = true;
+    il.reserved = 2;
+    il.current = 2;
+    il.scroll_by(-1);
+    assert_eq!((il.current, il.offset), (29, 20));
+    il.scroll_by(1);
+    assert_eq!((il.current, il.offset), (2, 2));
+    // steps longer than the list wrap as many times as needed
+    il.scroll_by(-57);
+    assert_eq!(il.current, 29);
+    il.scroll_by(30);
+    assert_eq!(il.current, 3);
+}
+
 #[test]
 fn scroll_by_rows_moves_cursor() {
     let mut il = list(10);
@@ -170,7 +187,7 @@ fn clear_resets_state() {
     il.current = 2;
     il.select_all();
     il.clear();
-    assert!(il.items.is_empty());
+    assert_eq!(il.items, []);
     assert!(il.selection.is_empty());
     assert_eq!(il.current, 0);
 }
@@ -202,7 +219,7 @@ fn select_row_adds_specific_item() {
 #[test]
 fn processed_items_default_replaces() {
     let pi = ProcessedItems::default();
-    assert!(pi.items.is_empty());
+    assert_eq!(pi.items, []);
     assert!(matches!(pi.merge, MergeStrategy::Replace));
 }
 
@@ -273,12 +290,57 @@ fn render_applies_sorted_merge_strategy() {
     assert_eq!(il.items.len(), 2);
 }
 
+#[test]
+fn kept_selection_is_found_again_in_the_reloaded_items() {
+    let mut il = list(4);
+    il.multi_select = true;
+    il.select_row(1);
+    il.select_row(3);
+    il.current = 3;
+    il.keep_selection();
+
+    // the reload finds nothing yet: the old items stay
+    set_processed(&il, Vec::new(), MergeStrategy::Replace);
+    render_list(&mut il, 20, 5);
+    assert_eq!(il.items.len(), 4);
+
+    // the same items at other indices, item1 gone
+    let reloaded = ["item3", "item0", "item2"];
+    let reloaded = reloaded
+        .iter()
+        .zip(10..)
+        .map(|(text, index)| matched(text, index))
+        .collect();
+    set_processed(&il, reloaded, MergeStrategy::Replace);
+    render_list(&mut il, 20, 5);
+    let selected: Vec<_> = il.selection.iter().map(|item| item.text().into_owned()).collect();
+    assert_eq!(selected, vec!["item3"]);
+    assert_eq!(il.selected().unwrap().text(), "item3");
+    assert_eq!(il.selection.first().unwrap().rank.index, 10);
+
+    il.forget_kept();
+    assert!(!il.keeps_selection());
+    assert_eq!(il.items.len(), 3);
+}
+
+#[test]
+fn forgetting_an_empty_reload_clears_the_old_items() {
+    let mut il = list(2);
+    il.keep_selection();
+    set_processed(&il, Vec::new(), MergeStrategy::Replace);
+    render_list(&mut il, 20, 5);
+    assert_eq!(il.items.len(), 2);
+
+    il.forget_kept();
+    assert_eq!(il.items, []);
+}
+
 #[test]
 fn render_empty_list_does_not_panic() {
     let mut il = ItemList::default();
     il.height = 5;
     render_list(&mut il, 20, 5);
-    assert!(il.items.is_empty());
+    assert_eq!(il.items, []);
 }
 
 #[test]
@@ -289,3 +351,343 @@ fn render_scrolled_list_shows_lower_items() {
     render_list(&mut il, 20, 6);
     assert!(il.current < il.items.len());
 }
+
+struct Grouped(&'static str, &'static str);
+
+impl crate::SkimItem for Grouped {
+    fn text(&self) -> std::borrow::Cow<'_, str> {
+        std::borrow::Cow::Borrowed(self.0)
+    }
+    fn group(&self) -> Option<std::borrow::Cow<'_, str>> {
+        Some(std::borrow::Cow::Borrowed(self.1))
+    }
+}
+
+fn grouped(text: &'static str, group: &'static str, index: i32) -> MatchedItem {
+    MatchedItem::new(
+        std::sync::Arc::new(Grouped(text, group)) as std::sync::Arc<dyn crate::SkimItem>,
+        Rank {
+            index,
+            ..Default::default()
+        },
+        None,
+        &RankBuilder::default(),
+    )
+}
+
+fn texts(il: &ItemList) -> Vec<String> {
+    il.items.iter().map(|item| item.text().into_owned()).collect()
+}
+
+#[test]
+fn merge_lists_grouped_items_by_group_in_order_of_their_best_match() {
+    let mut il = ItemList::default();
+    il.merge(ProcessedItems {
+        items: vec![grouped("a1", "a", 0), grouped("b1", "b", 1), grouped("a2", "a", 2)],
+        merge: MergeStrategy::Replace,
+    });
+    assert_eq!(texts(&il), ["a1", "a2", "b1"]);
+
+    // later results are merged in rank order before being grouped again
+    il.merge(ProcessedItems {
+        items: vec![grouped("b2", "b", 3)],
+        merge: MergeStrategy::Append,
+    });
+    assert_eq!(texts(&il), ["a1", "a2", "b1", "b2"]);
+    assert_eq!(il.group_counts.get("b"), Some(&2));
+}
+
+#[test]
+fn toggle_group_collapses_the_group_to_its_best_match() {
+    let mut il = ItemList::default();
+    il.merge(ProcessedItems {
+        items: vec![grouped("a1", "a", 0), grouped("b1", "b", 1), grouped("a2", "a", 2)],
+        merge: MergeStrategy::Replace,
+    });
+    il.current = 1;
+    il.toggle_group();
+    assert_eq!(texts(&il), ["a1", "b1"]);
+    assert_eq!(il.current, 0);
+
+    // collapsed groups stay collapsed in new results
+    il.merge(ProcessedItems {
+        items: vec![grouped("a3", "a", 0), grouped("a4", "a", 1)],
+        merge: MergeStrategy::Replace,
+    });
+    assert_eq!(texts(&il), ["a3"]);
+    assert_eq!(il.group_counts.get("a"), Some(&2));
+
+    il.toggle_group();
+    assert_eq!(texts(&il), ["a3", "a4"]);
+}
+
+#[test]
+fn render_shows_a_header_before_each_group() {
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+
+    let mut il = ItemList::default();
+    il.direction = ListDirection::TopToBottom;
+    il.merge(ProcessedItems {
+        items: vec![grouped("a1", "a", 0), grouped("b1", "b", 1), grouped("a2", "a", 2)],
+        merge: MergeStrategy::Replace,
+    });
+    let area = Rect::new(0, 0, 12, 6);
+    let mut buf = Buffer::empty(area);
+    il.render(area, &mut buf);
+    let rows: Vec<String> = (0..area.height)
+        .map(|y| {
+            (0..area.width)
+                .map(|x| buf[(x, y)].symbol())
+                .collect::<String>()
+                .trim_end()
+                .to_owned()
+        })
+        .collect();
+    assert_eq!(rows, ["▾ a (2)", "> a1", "  a2", "▾ b (1)", "  b1", ""]);
+}
+
+#[test]
+fn toggle_group_collapses_the_tree_node_of_the_current_item() {
+    let mut il = ItemList {
+        tree: true,
+        ..ItemList::default()
+    };
+    il.merge(ProcessedItems {
+        items: vec![matched("src/a.rs", 0), matched("src/b.rs", 1), matched("README.md", 2)],
+        merge: MergeStrategy::Replace,
+    });
+    assert_eq!(texts(&il), ["src", "src/a.rs", "src/b.rs", "README.md"]);
+
+    // on a file, its directory is collapsed
+    il.current = 2;
+    il.toggle_group();
+    assert_eq!(texts(&il), ["src", "README.md"]);
+    assert_eq!(il.current, 0);
+
+    // the selection holds the matched items, not the rows of the tree
+    il.current = 1;
+    il.toggle();
+    let selected = il.selection.first().unwrap();
+    assert!(selected.item.as_any().downcast_ref::<String>().is_some());
+
+    il.current = 0;
+    il.toggle_group();
+    assert_eq!(texts(&il), ["src", "src/a.rs", "src/b.rs", "README.md"]);
+}
+
+#[test]
+fn page_by_rows_keeps_the_cursor_row() {
+    let mut il = list(30);
+    render_list(&mut il, 20, 10);
+    il.scroll_by_rows(3);
+    il.page_by_rows(10);
+    assert_eq!((il.current, il.offset), (13, 10));
+    il.page_by_rows(-5);
+    assert_eq!((il.current, il.offset), (8, 5));
+    // the last page stays full, the cursor goes on
+    il.page_by_rows(30);
+    assert_eq!((il.current, il.offset), (29, 20));
+    il.page_by_rows(-30);
+    assert_eq!((il.current, il.offset), (0, 0));
+}
+
+#[test]
+fn render_keeps_scroll_off_items_around_the_cursor() {
+    let mut il = list(30);
+    il.scroll_off = 2;
+    render_list(&mut il, 20, 10);
+    il.current = 8;
+    render_list(&mut il, 20, 10);
+    assert_eq!(il.offset, 1);
+    il.current = 3;
+    render_list(&mut il, 20, 10);
+    assert_eq!(il.offset, 1);
+    // near the ends of the list, the cursor reaches the edges
+    il.current = 0;
+    render_list(&mut il, 20, 10);
+    assert_eq!(il.offset, 0);
+    il.current = 29;
+    render_list(&mut il, 20, 10);
+    assert_eq!(il.offset, 20);
+}
+
+#[test]
+fn removed_items_leave_the_cursor_and_the_selection_on_the_others() {
+    let mut il = list(6);
+    il.multi_select = true;
+    il.select_row(1);
+    il.select_row(4);
+    il.current = 4;
+    il.offset = 2;
+    let removed: Vec<_> = [0, 1]
+        .iter()
+        .map(|&position| std::sync::Arc::clone(&il.items[position].item))
+        .collect();
+    // the pending results of a search lose them too
+    set_processed(
+        &il,
+        vec![il.items[0].clone(), il.items[5].clone()],
+        MergeStrategy::Append,
+    );
+
+    il.remove_items(&removed);
+    assert_eq!(il.items.len(), 4);
+    assert_eq!(il.selected().unwrap().text(), "item4");
+    assert_eq!(il.offset, 0);
+    let selected: Vec<_> = il.selection.iter().map(|item| item.text().into_owned()).collect();
+    assert_eq!(selected, vec!["item4"]);
+    let pending = il.processed_items.lock().take().unwrap();
+    assert_eq!(pending.items.len(), 1);
+}
+
+#[test]
+fn reindex_moves_the_selection_back_over_the_dropped_positions() {
+    let mut il = list(6);
+    il.select_row(1);
+    il.select_row(4);
+    il.reindex(&[0, 2, 3], &RankBuilder::default());
+    let selected: Vec<_> = il
+        .selection
+        .iter()
+        .map(|item| (item.text().into_owned(), item.rank.index))
+        .collect();
+    assert_eq!(selected, vec![("item1".to_string(), 0), ("item4".to_string(), 1)]);
+    // a new search finds them again
+    assert!(il.selection.contains(&matched("item4", 1)));
+}
+
+#[cfg(unix)]
+#[test]
+fn pre_select_cmd_selects_the_printed_items_once() {
+    let mut options = SkimOptions::default();
+    options.multi = true;
+    options.pre_select_cmd = Some("printf 'b\\nc\\n'".to_string());
+    let mut il = ItemList::from_options(&options, Arc::new(crate::theme::ColorTheme::default()));
+    il.multi_select = true;
+    let items = vec![matched("a", 0), matched("b", 1), matched("c", 2)];
+    set_processed(&il, items.clone(), MergeStrategy::Replace);
+    render_list(&mut il, 20, 5);
+    let selected: Vec<_> = il.selection.iter().map(|item| item.text().into_owned()).collect();
+    assert_eq!(selected, vec!["b", "c"]);
+
+    // a deselected item stays deselected when more items arrive
+    il.selection.shift_remove(&items[1]);
+    set_processed(&il, vec![matched("c", 3)], MergeStrategy::Append);
+    render_list(&mut il, 20, 5);
+    let selected: Vec<_> = il.selection.iter().map(|item| item.text().into_owned()).collect();
+    assert_eq!(selected, vec!["c", "c"]);
+}
+
+#[test]
+fn selection_in_prints_the_selection_in_the_given_order() {
+    let mut il = ItemList::default();
+    let mut items = vec![matched("c", 2), matched("a", 0), matched("b", 1), matched("d", 3)];
+    il.append(&mut items);
+    il.select_row(2);
+    il.select_row(3);
+    il.select_row(0);
+    // "d" is no longer shown
+    il.items.truncate(3);
+    let texts = |order| {
+        il.selection_in(order)
+            .iter()
+            .map(|item| item.text().into_owned())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(texts(SelectionOrder::Selection), vec!["b", "d", "c"]);
+    assert_eq!(texts(SelectionOrder::Display), vec!["c", "b", "d"]);
+    assert_eq!(texts(SelectionOrder::Input), vec!["b", "c", "d"]);
+}
+
+#[test]
+fn multi_max_refuses_the_selections_past_it() {
+    let mut il = list(4);
+    il.multi_max = Some(2);
+    il.toggle_at(0);
+    il.toggle_at(1);
+    assert!(!il.take_refused());
+    il.toggle_at(2);
+    assert_eq!(il.selection.len(), 2);
+    assert!(il.take_refused());
+    assert!(!il.take_refused());
+
+    // deselecting makes room again
+    il.toggle_at(0);
+    il.select_all();
+    let selected: Vec<_> = il.selection.iter().map(|item| item.text().into_owned()).collect();
+    assert_eq!(selected, vec!["item1", "item0"]);
+    assert!(il.take_refused());
+}
+
+#[test]
+fn selector_can_refuse_a_selection() {
+    struct SameInitial;
+    impl Selector for SameInitial {
+        fn should_select(&self, _index: usize, _item: &dyn crate::SkimItem) -> bool {
+            false
+        }
+        fn can_select(&self, item: &dyn crate::SkimItem, selected: &[&dyn crate::SkimItem]) -> bool {
+            selected
+                .iter()
+                .all(|other| other.text().chars().next() == item.text().chars().next())
+        }
+    }
+    let mut il = ItemList::default();
+    il.selector = Some(Rc::new(SameInitial));
+    il.append(&mut vec![
+        matched("apple", 0),
+        matched("banana", 1),
+        matched("avocado", 2),
+    ]);
+    il.toggle_all();
+    let selected: Vec<_> = il.selection.iter().map(|item| item.text().into_owned()).collect();
+    assert_eq!(selected, vec!["apple", "avocado"]);
+    assert!(il.take_refused());
+}
+
+#[test]
+fn max_display_lists_the_best_matches_and_counts_the_others() {
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+
+    let mut options = SkimOptions::default();
+    options.max_display = Some(2);
+    let mut il = ItemList::from_options(&options, Arc::new(ColorTheme::default()));
+    il.direction = ListDirection::TopToBottom;
+    il.merge(ProcessedItems {
+        items: vec![matched("a", 0), matched("c", 2), matched("e", 4)],
+        merge: MergeStrategy::Replace,
+    });
+    assert_eq!(il.items.len(), 2);
+    assert_eq!(il.count(), 3);
+
+    // a better match takes the place of a listed one, which is only counted
+    il.merge(ProcessedItems {
+        items: vec![matched("b", 1), matched("f", 5)],
+        merge: MergeStrategy::SortedMerge,
+    });
+    let texts: Vec<_> = il.items.iter().map(|item| item.text().into_owned()).collect();
+    assert_eq!(texts, ["a", "b"]);
+    assert_eq!(il.count(), 5);
+
+    let area = Rect::new(0, 0, 16, 4);
+    let mut buf = Buffer::empty(area);
+    il.render(area, &mut buf);
+    let rows: Vec<String> = (0..area.height)
+        .map(|y| {
+            (0..area.width)
+                .map(|x| buf[(x, y)].symbol())
+                .collect::<String>()
+                .trim_end()
+                .to_owned()
+        })
+        .collect();
+    assert_eq!(rows, ["> a", "  b", "", "  and 3 more"]);
+
+    il.merge(ProcessedItems {
+        items: vec![matched("z", 9)],
+        merge: MergeStrategy::Replace,
+    });
+    assert_eq!(il.count(), 1);
+}
@@ -1,12 +1,18 @@
+use std::borrow::Cow;
+
 use ratatui::style::Color;
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{ListDirection, ListItem};
 use unicode_display_width::width as display_width;
+use unicode_segmentation::UnicodeSegmentation;
 
 use crate::item::MatchedItem;
 use crate::theme::ColorTheme;
 use crate::tui::item_list::ItemList;
-use crate::tui::util::{char_display_width, clip_line_to_chars, wrap_text};
+use crate::tui::options::TruncateSide;
+use crate::tui::util::{
+    bidi_visual_order, clip_line_to_chars, grapheme_display_width, reorder_line, visual_range, wrap_text,
+};
 use crate::{DisplayContext, MatchRange};
 
 #[allow(clippy::struct_excessive_bools)]
@@ -24,6 +30,7 @@ struct SubLineState {
 
 /// Rendering parameters that are constant across all items in one render pass.
 #[allow(clippy::struct_excessive_bools)]
+#[derive(Clone)]
 pub(crate) struct ItemRenderer<'a> {
     pub theme: &'a ColorTheme,
     pub selector_icon: &'a str,
@@ -37,7 +44,10 @@ pub(crate) struct ItemRenderer<'a> {
     pub multi_select: bool,
     pub tabstop: usize,
     pub no_hscroll: bool,
+    /// Columns to keep visible after the match when scrolling, see `--hscroll-off`
+    pub hscroll_off: usize,
     pub keep_right: bool,
+    pub truncate_side: TruncateSide,
     pub manual_hscroll: i32,
     pub skip_to_pattern: Option<&'a regex::Regex>,
     /// When true, reverse the order of sub-lines within each multiline item
@@ -46,6 +56,8 @@ pub(crate) struct ItemRenderer<'a> {
     pub reverse_sub_lines: bool,
     /// When true, fill the rest of the current line with the `current` background color
     pub highlight_line: bool,
+    /// Selected and matched item counts, shown on the current line with `--selection-count`
+    pub selection_count: Option<(usize, usize)>,
 }
 
 impl<'a> ItemRenderer<'a> {
@@ -65,11 +77,14 @@ impl<'a> ItemRenderer<'a> {
             multi_select: list.multi_select,
             tabstop: list.tabstop,
             no_hscroll: list.no_hscroll,
+            hscroll_off: list.hscroll_off,
             keep_right: list.keep_right,
+            truncate_side: list.truncate_side,
             manual_hscroll: list.manual_hscroll,
             skip_to_pattern: list.skip_to_pattern.as_ref(),
             reverse_sub_lines: list.direction == ListDirection::BottomToTop,
             highlight_line: list.highlight_line,
+            selection_count: list.selection_count.then(|| (list.selection.len(), list.items.len())),
         }
     }
 
@@ -191,12 +206,40 @@ impl<'a> ItemRenderer<'a> {
         match_end_char: usize,
     ) -> ListItem<'static> {
         let mut all_spans = self.prefix_spans(item, state);
-        let content_line = self.content_line(item, sub_text, state, match_start_char, match_end_char);
+        // The annotation and selection count take the right edge of the first row, the content is
+        // laid out in what remains of the container.
+        let right_spans = self.right_spans(item, state);
+        let right_width: usize = right_spans.iter().map(Span::width).sum();
+        let narrowed;
+        let renderer = if right_spans.is_empty() {
+            self
+        } else {
+            narrowed = Self {
+                container_width: self.container_width - right_width,
+                ..self.clone()
+            };
+            &narrowed
+        };
+        let content_line = renderer.content_line(item, sub_text, state, match_start_char, match_end_char);
 
-        if state.needs_ellipsis {
-            all_spans.extend(self.trim_with_ellipsis(content_line, state.is_current));
+        let content_spans = if state.needs_ellipsis {
+            renderer.trim_with_ellipsis(content_line, state.is_current)
         } else {
-            all_spans.extend(content_line.spans);
+            content_line.spans
+        };
+        if right_spans.is_empty() {
+            all_spans.extend(content_spans);
+        } else {
+            let used: usize = content_spans
+                .iter()
+                .map(|s| renderer.text_display_width(&s.content))
+                .sum();
+            all_spans.extend(content_spans);
+            all_spans.push(Span::styled(
+                " ".repeat(renderer.container_width.saturating_sub(used)),
+                self.base_style(state.is_current),
+            ));
+            all_spans.extend(right_spans);
         }
 
         if item.item.disabled() {
@@ -208,21 +251,47 @@ impl<'a> ItemRenderer<'a> {
         self.list_item_from_spans(all_spans, state.is_current)
     }
 
+    /// Spans right-aligned on the item's first row, unless wrapping: its annotation, shown
+    /// dimmed unless wider than half the container, then the selection count on the current row
+    fn right_spans(&self, item: &MatchedItem, state: &SubLineState) -> Vec<Span<'static>> {
+        let mut spans = Vec::new();
+        if self.wrap || !(state.is_first && state.is_first_sub_line) {
+            return spans;
+        }
+        let count = self
+            .selection_count
+            .filter(|_| state.is_current)
+            .map(|(selected, matched)| {
+                let style = self.base_style(state.is_current).patch(self.theme.selected);
+                Span::styled(format!(" {selected}/{matched} selected"), style)
+            })
+            .filter(|span| span.width() < self.container_width);
+        let available = self.container_width - count.as_ref().map_or(0, Span::width);
+        if let Some(annotation) = item.item.annotation().filter(|a| !a.is_empty()) {
+            let span = Span::styled(format!(" {annotation}"), self.base_style(state.is_current).dim());
+            if span.width() * 2 <= available {
+                spans.push(span);
+            }
+        }
+        spans.extend(count);
+        spans
+    }
+
     fn prefix_spans(&self, item: &MatchedItem, state: &SubLineState) -> Vec<Span<'static>> {
         let mut prefix: Vec<Span<'static>> = Vec::with_capacity(4);
         // When highlight_line is active for the current item, the line-level style fills the
         // entire row with the current background. Reset the bg on prefix spans so the
-        // selector/marker columns are not highlighted.
-        let prefix_cursor_style = if self.highlight_line && state.is_current {
-            self.theme.cursor.bg(self.theme.cursor.bg.unwrap_or(Color::Reset))
-        } else {
-            self.theme.cursor
-        };
-        let prefix_selected_style = if self.highlight_line && state.is_current {
-            self.theme.selected.bg(self.theme.selected.bg.unwrap_or(Color::Reset))
-        } else {
-            self.theme.selected
+        // selector/marker columns are not highlighted. Both columns sit on the gutter style.
+        let prefix_style = |style: ratatui::style::Style| {
+            let style = self.theme.gutter.patch(style);
+            if self.highlight_line && state.is_current {
+                style.bg(style.bg.unwrap_or(Color::Reset))
+            } else {
+                style
+            }
         };
+        let prefix_cursor_style = prefix_style(self.theme.cursor);
+        let prefix_selected_style = prefix_style(self.theme.selected);
 
         prefix.push(Span::styled(
             if state.is_first && state.is_current {
@@ -310,12 +379,25 @@ impl<'a> ItemRenderer<'a> {
         } else {
             dl
         };
+        // Right-to-left text is shown in visual order, the match range following its chars
+        let mut text = Cow::Borrowed(first_sub_line);
+        let (mut match_start_char, mut match_end_char) = (match_start_char, match_end_char);
+        if let Some(order) = bidi_visual_order(&line) {
+            (match_start_char, match_end_char) = visual_range(&order, match_start_char, match_end_char);
+            line = reorder_line(&line, &order);
+            text = Cow::Owned(line.spans.iter().map(|span| span.content.as_ref()).collect());
+        }
         if !self.wrap {
-            let (shift, full_width, _, _) = if self.multiline.is_some() {
-                self.calc_hscroll(first_sub_line, match_start_char, match_end_char)
+            let full_width = if self.multiline.is_some() {
+                self.text_display_width(&text)
             } else {
-                self.calc_line_hscroll(&line, first_sub_line, match_start_char, match_end_char)
+                self.line_display_width(&line)
             };
+            if let Some(cut) = self.cut_middle(&line, &text, full_width, (match_start_char, match_end_char)) {
+                return cut;
+            }
+            let (shift, full_width, _, _) =
+                self.calc_hscroll_for_width(&text, match_start_char, match_end_char, full_width);
             line = self.apply_hscroll(line, shift, full_width);
         }
         Self::into_static_line(line)
@@ -323,9 +405,14 @@ impl<'a> ItemRenderer<'a> {
 
     fn continuation_sub_line_content(&self, sub_text: &str, is_current: bool) -> Line<'static> {
         let sub_str = sub_text.to_string();
-        let raw: Line<'static> = Line::from(vec![Span::styled(sub_str, self.base_style(is_current))]);
+        let mut raw: Line<'static> = Line::from(vec![Span::styled(sub_str, self.base_style(is_current))]);
+        if let Some(order) = bidi_visual_order(&raw) {
+            raw = reorder_line(&raw, &order);
+        }
         if self.wrap {
             raw
+        } else if let Some(cut) = self.cut_middle(&raw, sub_text, self.text_display_width(sub_text), (0, 0)) {
+            cut
         } else {
             let (shift, full_width, _, _) = self.calc_hscroll(sub_text, 0, 0);
             let scrolled = self.apply_hscroll(raw, shift, full_width);
@@ -349,13 +436,12 @@ impl<'a> ItemRenderer<'a> {
         let mut used = 0usize;
 
         'trim: for span in content_line.spans {
-            let span_chars: Vec<char> = span.content.chars().collect();
-            for (i, ch) in span_chars.iter().enumerate() {
-                let w = char_display_width(*ch);
+            for (i, grapheme) in span.content.grapheme_indices(true) {
+                let w = grapheme_display_width(grapheme);
                 if used + w > available {
-                    let partial: String = span_chars[..i].iter().collect();
+                    let partial = &span.content[..i];
                     if !partial.is_empty() {
-                        trimmed.push(Span::styled(partial, span.style));
+                        trimmed.push(Span::styled(partial.to_owned(), span.style));
                     }
                     break 'trim;
                 }
@@ -417,16 +503,6 @@ impl<'a> ItemRenderer<'a> {
         self.calc_hscroll_for_width(text, match_start_char, match_end_char, full_width)
     }
 
-    fn calc_line_hscroll(
-        &self,
-        line: &Line<'_>,
-        text: &str,
-        match_start_char: usize,
-        match_end_char: usize,
-    ) -> (usize, usize, bool, bool) {
-        self.calc_hscroll_for_width(text, match_start_char, match_end_char, self.line_display_width(line))
-    }
-
     fn calc_hscroll_for_width(
         &self,
         text: &str,
@@ -441,46 +517,39 @@ impl<'a> ItemRenderer<'a> {
             return (0, full_width, false, false);
         };
 
+        // the left ellipsis covers the first columns scrolled to, so showing the right end takes
+        // scrolling past them
+        let max_shift = if full_width > available_width {
+            full_width - available_width + ell_w
+        } else {
+            0
+        };
+        let keep_right = self.keep_right || self.truncate_side == TruncateSide::Start;
         let base_shift = if self.no_hscroll {
             0
         } else if match_start_char == 0 && match_end_char == 0 {
             let skip_width = self.calc_skip_width(text);
             if skip_width > 0 {
                 skip_width
-            } else if self.keep_right {
-                full_width.saturating_sub(available_width)
+            } else if keep_right {
+                max_shift
             } else {
                 0
             }
         } else {
-            let mut match_start_width = 0;
-            let mut match_end_width = 0;
-            let mut current_width = 0;
-            let mut found_start = false;
-            let mut found_end = false;
-
-            for (idx, ch) in text.chars().enumerate() {
-                if idx == match_start_char {
-                    match_start_width = current_width;
-                    found_start = true;
-                }
-                if idx == match_end_char {
-                    match_end_width = current_width;
-                    found_end = true;
-                    break;
-                }
-                current_width = self.add_char_width(current_width, ch);
-            }
-            if found_start && !found_end {
-                match_end_width = current_width;
-            }
-
+            let (match_start_width, match_end_width) = self.match_columns(text, match_start_char, match_end_char);
             let match_width = match_end_width.saturating_sub(match_start_width);
-            if match_width >= available_width {
+            if keep_right && match_start_width >= max_shift {
+                // the match is already visible with the right end anchored
+                max_shift
+            } else if match_width >= available_width {
                 match_start_width
             } else {
-                let desired = match_start_width.saturating_sub((available_width - match_width) / 2);
-                desired.min(full_width.saturating_sub(available_width))
+                // centered, unless that leaves fewer than `hscroll_off` columns after the match
+                let spare = available_width - match_width;
+                let left_margin = (spare / 2).min(spare.saturating_sub(self.hscroll_off));
+                let desired = match_start_width.saturating_sub(left_margin);
+                desired.min(max_shift)
             }
         };
 
@@ -488,7 +557,7 @@ impl<'a> ItemRenderer<'a> {
             .max(0)
             .unsigned_abs() as usize;
         let shift = if full_width > available_width {
-            proposed.min(full_width.saturating_sub(available_width))
+            proposed.min(max_shift)
         } else {
             proposed
         };
@@ -496,23 +565,108 @@ impl<'a> ItemRenderer<'a> {
         (shift, full_width, shift > 0, shift + available_width < full_width)
     }
 
+    /// The columns spanned by the chars `match_start_char..match_end_char` of `text`
+    fn match_columns(&self, text: &str, match_start_char: usize, match_end_char: usize) -> (usize, usize) {
+        let mut match_start_width = 0;
+        let mut match_end_width = 0;
+        let mut current_width = 0;
+        let mut found_start = false;
+        let mut found_end = false;
+        let mut char_index = 0;
+
+        // the match bounds are char indices, widened to the grapheme clusters holding them
+        for grapheme in text.graphemes(true) {
+            let char_end = char_index + grapheme.chars().count();
+            if !found_start && char_end > match_start_char {
+                match_start_width = current_width;
+                found_start = true;
+            }
+            if char_index >= match_end_char {
+                match_end_width = current_width;
+                found_end = true;
+                break;
+            }
+            current_width = self.add_grapheme_width(current_width, grapheme);
+            char_index = char_end;
+        }
+        if found_start && !found_end {
+            match_end_width = current_width;
+        }
+        (match_start_width, match_end_width)
+    }
+
+    /// `line` with its middle replaced by the ellipsis to fit, for `--truncate-side middle`
+    ///
+    /// `None` when the line fits, when scrolled by hand, or when the matched chars of `text` would
+    /// fall in the cut, the line then scrolling as usual.
+    fn cut_middle(
+        &self,
+        line: &Line<'_>,
+        text: &str,
+        full_width: usize,
+        (match_start_char, match_end_char): (usize, usize),
+    ) -> Option<Line<'static>> {
+        if self.truncate_side != TruncateSide::Middle
+            || self.no_hscroll
+            || self.manual_hscroll != 0
+            || full_width <= self.container_width
+        {
+            return None;
+        }
+        let ell_w = usize::try_from(display_width(self.ellipsis)).unwrap();
+        let content_width = self.container_width.checked_sub(ell_w)?;
+        let head = content_width.div_ceil(2);
+        let tail_start = full_width - (content_width - head);
+        if (match_start_char, match_end_char) != (0, 0) {
+            let (start, end) = self.match_columns(text, match_start_char, match_end_char);
+            if end > head && start < tail_start {
+                return None;
+            }
+        }
+
+        let mut result = Line::default();
+        let mut width = 0;
+        let mut ellipsis_pushed = false;
+        for span in &line.spans {
+            let mut kept = String::new();
+            for grapheme in span.content.graphemes(true) {
+                let next_width = self.add_grapheme_width(width, grapheme);
+                if next_width <= head || width >= tail_start {
+                    kept.push_str(&self.expand_tabs(grapheme, width));
+                } else if !ellipsis_pushed {
+                    if !kept.is_empty() {
+                        result.push_span(Span::styled(std::mem::take(&mut kept), span.style));
+                    }
+                    result.push_span(Span::raw(self.ellipsis.to_owned()));
+                    ellipsis_pushed = true;
+                }
+                width = next_width;
+            }
+            if !kept.is_empty() {
+                result.push_span(Span::styled(kept, span.style));
+            }
+        }
+        Some(result)
+    }
+
     fn text_display_width(&self, text: &str) -> usize {
-        text.chars().fold(0usize, |width, ch| self.add_char_width(width, ch))
+        text.graphemes(true)
+            .fold(0usize, |width, grapheme| self.add_grapheme_width(width, grapheme))
     }
 
     fn line_display_width(&self, line: &Line<'_>) -> usize {
         line.spans.iter().fold(0usize, |width, span| {
-            span.content
-                .chars()
-                .fold(width, |span_width, ch| self.add_char_width(span_width, ch))
+            span.content.graphemes(true).fold(width, |span_width, grapheme| {
+                self.add_grapheme_width(span_width, grapheme)
+            })
         })
     }
 
-    fn add_char_width(&self, width: usize, ch: char) -> usize {
-        if ch == '\t' {
+    fn add_grapheme_width(&self, width: usize, grapheme: &str) -> usize {
+        if grapheme == "\t" {
             width + self.tabstop - (width % self.tabstop)
         } else {
-            width + char_display_width(ch)
+            width + grapheme_display_width(grapheme)
         }
     }
 
@@ -531,26 +685,26 @@ impl<'a> ItemRenderer<'a> {
             result.push_span(Span::raw(self.ellipsis));
         }
 
-        let mut current_char_index = 0;
+        let mut current_grapheme_index = 0;
         let mut current_width = 0;
-        let shift_char_start = self.char_index_at_width(&line, shift);
-        let shift_char_end = self.char_index_at_width(&line, shift + content_width);
+        let shift_start = self.grapheme_index_at_width(&line, shift);
+        let shift_end = self.grapheme_index_at_width(&line, shift + content_width);
 
         for span in line.spans {
             let span_text = span.content.as_ref();
-            let span_chars: Vec<char> = span_text.chars().collect();
-            let span_start = current_char_index;
-            let span_end = current_char_index + span_chars.len();
-
-            if span_end > shift_char_start && span_start < shift_char_end {
-                let vis_start = shift_char_start.saturating_sub(span_start);
-                let vis_end = if span_end > shift_char_end {
-                    shift_char_end - span_start
+            let span_graphemes: Vec<&str> = span_text.graphemes(true).collect();
+            let span_start = current_grapheme_index;
+            let span_end = current_grapheme_index + span_graphemes.len();
+
+            if span_end > shift_start && span_start < shift_end {
+                let vis_start = shift_start.saturating_sub(span_start);
+                let vis_end = if span_end > shift_end {
+                    shift_end - span_start
                 } else {
-                    span_chars.len()
+                    span_graphemes.len()
                 };
-                if vis_start < vis_end && vis_start < span_chars.len() {
-                    let visible: String = span_chars[vis_start..vis_end.min(span_chars.len())].iter().collect();
+                if vis_start < vis_end && vis_start < span_graphemes.len() {
+                    let visible: String = span_graphemes[vis_start..vis_end.min(span_graphemes.len())].concat();
                     let processed = if visible.contains('\t') {
                         self.expand_tabs(&visible, current_width)
                     } else {
@@ -561,7 +715,7 @@ impl<'a> ItemRenderer<'a> {
                     }
                 }
             }
-            current_char_index += span_chars.len();
+            current_grapheme_index += span_graphemes.len();
             current_width += usize::try_from(display_width(span_text)).unwrap();
         }
 
@@ -571,34 +725,33 @@ impl<'a> ItemRenderer<'a> {
         result
     }
 
-    fn char_index_at_width(&self, line: &Line<'_>, target_width: usize) -> usize {
+    /// Index of the first grapheme cluster of `line` starting at or after column `target_width`
+    fn grapheme_index_at_width(&self, line: &Line<'_>, target_width: usize) -> usize {
         let mut current_width = 0;
-        let mut char_index = 0;
+        let mut grapheme_index = 0;
         for span in &line.spans {
-            for ch in span.content.chars() {
+            for grapheme in span.content.graphemes(true) {
                 if current_width >= target_width {
-                    return char_index;
+                    return grapheme_index;
                 }
-                current_width = self.add_char_width(current_width, ch);
-                char_index += 1;
+                current_width = self.add_grapheme_width(current_width, grapheme);
+                grapheme_index += 1;
             }
         }
-        char_index
+        grapheme_index
     }
 
     fn expand_tabs(&self, text: &str, start_width: usize) -> String {
         let mut result = String::new();
         let mut current_width = start_width;
-        for ch in text.chars() {
-            if ch == '\t' {
-                let next_width = self.add_char_width(current_width, ch);
-                let tab_width = next_width - current_width;
-                result.push_str(&" ".repeat(tab_width));
-                current_width = next_width;
+        for grapheme in text.graphemes(true) {
+            let next_width = self.add_grapheme_width(current_width, grapheme);
+            if grapheme == "\t" {
+                result.push_str(&" ".repeat(next_width - current_width));
             } else {
-                result.push(ch);
-                current_width = self.add_char_width(current_width, ch);
+                result.push_str(grapheme);
             }
+            current_width = next_width;
         }
         result
     }
@@ -24,11 +24,14 @@ fn renderer(theme: &ColorTheme) -> ItemRenderer<'_> {
         multi_select: false,
         tabstop: 4,
         no_hscroll: false,
+        hscroll_off: 0,
         keep_right: false,
+        truncate_side: TruncateSide::End,
         manual_hscroll: 0,
         skip_to_pattern: None,
         reverse_sub_lines: false,
         highlight_line: false,
+        selection_count: None,
     }
 }
 
@@ -181,6 +184,49 @@ fn prefix_spans_with_highlight_line_resets_prefix_background() {
     assert_eq!(spans[0].style.bg, Some(ratatui::style::Color::Reset));
 }
 
+#[test]
+fn prefix_spans_sit_on_the_gutter_style() {
+    let theme = ColorTheme {
+        gutter: Style::default().bg(ratatui::style::Color::Indexed(236)),
+        ..ColorTheme::default()
+    };
+    let mut renderer = renderer(&theme);
+    renderer.multi_select = true;
+    let item = matched_item("alpha", None);
+    let state = SubLineState {
+        is_current: false,
+        is_selected: true,
+        is_first: true,
+        is_first_sub_line: true,
+        needs_ellipsis: false,
+    };
+
+    let spans = renderer.prefix_spans(&item, &state);
+    assert_eq!(spans_text(&spans), " *");
+    assert!(
+        spans
+            .iter()
+            .all(|s| s.style.bg == Some(ratatui::style::Color::Indexed(236)))
+    );
+    // The marker keeps its own color over the gutter
+    assert_eq!(spans[1].style.fg, theme.selected.fg);
+}
+
+#[test]
+fn render_item_shows_the_selection_count_on_the_current_row() {
+    let theme = ColorTheme::default();
+    let mut renderer = renderer(&theme);
+    renderer.container_width = 20;
+    renderer.selection_count = Some((3, 10));
+    let item = matched_item("alpha", None);
+    let mut out = Vec::new();
+
+    renderer.render_item(&item, true, false, 0, 1, 0, &mut out);
+    renderer.render_item(&item, false, false, 0, 1, 0, &mut out);
+    assert_eq!(rendered_row_text(out.remove(0), 22), "> alpha  3/10 selected");
+    assert_eq!(rendered_row_text(out.remove(0), 22).trim_end(), "  alpha");
+}
+
 #[test]
 fn trim_with_ellipsis_reserves_space_for_marker() {
     let theme = ColorTheme::default();
@@ -195,6 +241,33 @@ fn trim_with_ellipsis_reserves_space_for_marker() {
     assert_eq!(spans_text(&trimmed), "abcd..");
 }
 
+#[test]
+fn trim_with_ellipsis_keeps_grapheme_clusters_whole() {
+    let theme = ColorTheme::default();
+    let renderer = renderer(&theme);
+    // each `e` + U+0301 takes one column, whatever its char count
+    let line = Line::from("e\u{301}e\u{301}e\u{301}e\u{301}e\u{301}");
+
+    let trimmed = renderer.trim_with_ellipsis(line, false);
+
+    assert_eq!(spans_text(&trimmed), "e\u{301}e\u{301}e\u{301}e\u{301}..");
+}
+
+#[test]
+fn hscroll_counts_columns_per_grapheme_cluster() {
+    let theme = ColorTheme::default();
+    let renderer = renderer(&theme);
+    // the ZWJ family emoji is 5 chars wide in chars, 2 in columns
+    let text = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}abcdef";
+    assert_eq!(renderer.text_display_width(text), 8);
+    // a match on `ef` (chars 9..11) scrolls by columns, never splitting the emoji
+    let (shift, full_width, _, _) = renderer.calc_hscroll(text, 9, 11);
+    assert_eq!((shift, full_width), (4, 8));
+    // a shift landing inside the emoji drops it whole instead of cutting it
+    let line = renderer.apply_hscroll(Line::from(text), 1, full_width);
+    assert_eq!(line_text(&line), "..a..");
+}
+
 #[test]
 fn continuation_sub_line_content_applies_hscroll() {
     let theme = ColorTheme::default();
@@ -243,8 +316,83 @@ fn calc_hscroll_keep_right_shifts_to_end() {
     renderer.keep_right = true;
     // No match (0,0) + keep_right → shift so the right edge is visible.
     let (shift, _full, _l, _r) = renderer.calc_hscroll_for_width("hello world", 0, 0, 11);
-    // full_width(11) - available_width(6) = 5.
-    assert_eq!(shift, 5);
+    // full_width(11) - available_width(6) + the left ellipsis(2) = 7.
+    assert_eq!(shift, 7);
+}
+
+#[test]
+fn calc_hscroll_keep_right_shows_a_match_cut_off_on_the_left() {
+    let theme = ColorTheme::default();
+    let mut renderer = renderer(&theme);
+    renderer.keep_right = true;
+    // a match within the right end keeps the line anchored there
+    let (shift, _full, _l, _r) = renderer.calc_hscroll_for_width("hello world", 8, 9, 11);
+    assert_eq!(shift, 7);
+    // one before it scrolls to show the match
+    let (shift, _full, _l, _r) = renderer.calc_hscroll_for_width("hello world", 1, 2, 11);
+    assert_eq!(shift, 0);
+}
+
+#[test]
+fn calc_hscroll_truncate_start_shifts_to_end() {
+    let theme = ColorTheme::default();
+    let mut renderer = renderer(&theme);
+    renderer.truncate_side = TruncateSide::Start;
+    let (shift, _full, _l, _r) = renderer.calc_hscroll_for_width("hello world", 0, 0, 11);
+    assert_eq!(shift, 7);
+}
+
+#[test]
+fn truncate_middle_keeps_both_ends_of_the_line() {
+    let theme = ColorTheme::default();
+    let mut renderer = renderer(&theme);
+    renderer.truncate_side = TruncateSide::Middle;
+    renderer.container_width = 7;
+
+    let line = renderer.continuation_sub_line_content("abcdefghijkl", false);
+    assert_eq!(line_text(&line), "abc..kl");
+
+    // a line that fits is left alone
+    let line = renderer.continuation_sub_line_content("abcdefg", false);
+    assert_eq!(line_text(&line), "abcdefg");
+}
+
+#[test]
+fn truncate_middle_scrolls_when_the_match_is_in_the_cut() {
+    let theme = ColorTheme::default();
+    let mut renderer = renderer(&theme);
+    renderer.truncate_side = TruncateSide::Middle;
+    renderer.container_width = 7;
+
+    let item = matched_item("abcdefghijkl", Some(MatchRange::ByteRange(10, 11)));
+    let line = renderer.first_sub_line_content(&item, "abcdefghijkl", false, 10, 11);
+    assert_eq!(line_text(&line), "abc..kl");
+
+    let item = matched_item("abcdefghijkl", Some(MatchRange::ByteRange(6, 7)));
+    let line = renderer.first_sub_line_content(&item, "abcdefghijkl", false, 6, 7);
+    renderer.truncate_side = TruncateSide::End;
+    let scrolled = renderer.first_sub_line_content(&item, "abcdefghijkl", false, 6, 7);
+    assert_eq!(line_text(&line), line_text(&scrolled));
+    assert!(line_text(&line).starts_with(".."));
+}
+
+#[test]
+fn calc_hscroll_keeps_hscroll_off_columns_after_the_match() {
+    let theme = ColorTheme::default();
+    let mut renderer = renderer(&theme);
+    renderer.container_width = 10;
+    let text = "abcdefghijklmnopqrstuvwxyz0123";
+    // the 2-column match on chars 15..17 is centered: 4 columns on each side
+    let (shift, _full, _l, _r) = renderer.calc_hscroll_for_width(text, 15, 17, 30);
+    assert_eq!(shift, 11);
+    // 6 columns are kept after it
+    renderer.hscroll_off = 6;
+    let (shift, _full, _l, _r) = renderer.calc_hscroll_for_width(text, 15, 17, 30);
+    assert_eq!(shift, 13);
+    // a margin wider than the room left puts the match at the left edge
+    renderer.hscroll_off = 100;
+    let (shift, _full, _l, _r) = renderer.calc_hscroll_for_width(text, 15, 17, 30);
+    assert_eq!(shift, 15);
 }
 
 #[test]
@@ -273,5 +421,23 @@ fn render_item_with_multiline_skip_starts_at_skipped_sub_line() {
     assert_eq!(added, 2);
     assert_eq!(rendered_row_text(out.remove(0), 8), "  second");
     assert_eq!(rendered_row_text(out.remove(0), 8), "  third ");
-    assert!(out.is_empty());
+    assert_eq!(out, []);
+}
+
+#[test]
+fn render_item_shows_the_annotation_at_the_right_edge() {
+    let theme = ColorTheme::default();
+    let mut renderer = renderer(&theme);
+    renderer.container_width = 12;
+
+    let item = crate::helper::item::DefaultSkimItem::from("file.rs".to_string()).with_annotation("4K", None);
+    let item = MatchedItem::new(Arc::new(item), Rank::default(), None, &RankBuilder::default());
+    let mut out = Vec::new();
+    renderer.render_item(&item, false, false, 0, 1, 0, &mut out);
+    assert_eq!(rendered_row_text(out.remove(0), 14), "  file.rs   4K");
+
+    // Annotations wider than half the container are dropped rather than squeezing the item
+    renderer.container_width = 4;
+    renderer.render_item(&item, false, false, 0, 1, 0, &mut out);
+    assert_eq!(rendered_row_text(out.remove(0), 6), "  fi..");
 }
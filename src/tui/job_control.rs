@@ -0,0 +1,130 @@
+//! Unix job control: suspending skim with ctrl-z and continuing it with `fg`.
+//!
+//! In raw mode the terminal does not turn ctrl-z into `SIGTSTP`, so suspending is the
+//! `suspend` action; a `SIGTSTP` sent by someone else (e.g. `kill -TSTP`) is caught and
+//! turned into the same action. Either way the event loop restores the terminal before the
+//! process group is stopped, and enters raw mode again once it is continued. A `SIGCONT`
+//! after a `SIGSTOP`, which cannot be caught, is reported too so that the screen is repainted.
+//!
+//! The handlers only set flags; the event reader polls them on every tick.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use nix::sys::signal::{SaFlags, SigAction, SigHandler, SigSet, Signal, kill, sigaction};
+use nix::unistd::Pid;
+
+static SUSPEND_REQUESTED: AtomicBool = AtomicBool::new(false);
+static CONTINUED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_tstp(_: nix::libc::c_int) {
+    SUSPEND_REQUESTED.store(true, Ordering::Relaxed);
+}
+
+extern "C" fn on_cont(_: nix::libc::c_int) {
+    CONTINUED.store(true, Ordering::Relaxed);
+}
+
+fn set_handler(signal: Signal, handler: SigHandler) -> nix::Result<SigAction> {
+    let action = SigAction::new(handler, SaFlags::SA_RESTART, SigSet::empty());
+    // SAFETY: the handlers only store to atomics, which is async-signal-safe
+    unsafe { sigaction(signal, &action) }
+}
+
+/// The actions of `SIGTSTP` and `SIGCONT` before [`install_handlers`], given back by
+/// [`restore`](Self::restore)
+#[derive(Debug)]
+#[must_use]
+pub(crate) struct SavedHandlers {
+    tstp: SigAction,
+    cont: SigAction,
+}
+
+impl SavedHandlers {
+    /// Gives `SIGTSTP` and `SIGCONT` the actions they had before skim caught them
+    pub(crate) fn restore(self) {
+        // SAFETY: the actions were in place before, restoring them is as safe as they were
+        unsafe {
+            let _ = sigaction(Signal::SIGTSTP, &self.tstp);
+            let _ = sigaction(Signal::SIGCONT, &self.cont);
+        }
+    }
+}
+
+/// Catches `SIGTSTP` and `SIGCONT` while skim owns the terminal
+pub(crate) fn install_handlers() -> nix::Result<SavedHandlers> {
+    SUSPEND_REQUESTED.store(false, Ordering::Relaxed);
+    CONTINUED.store(false, Ordering::Relaxed);
+    let tstp = set_handler(Signal::SIGTSTP, SigHandler::Handler(on_tstp))?;
+    match set_handler(Signal::SIGCONT, SigHandler::Handler(on_cont)) {
+        Ok(cont) => Ok(SavedHandlers { tstp, cont }),
+        Err(e) => {
+            // SAFETY: see `SavedHandlers::restore`
+            let _ = unsafe { sigaction(Signal::SIGTSTP, &tstp) };
+            Err(e)
+        }
+    }
+}
+
+/// Whether a `SIGTSTP` was caught since the last call
+pub(crate) fn take_suspend_request() -> bool {
+    SUSPEND_REQUESTED.swap(false, Ordering::Relaxed)
+}
+
+/// Whether a `SIGCONT` was caught since the last call
+pub(crate) fn take_continued() -> bool {
+    CONTINUED.swap(false, Ordering::Relaxed)
+}
+
+/// Stops the process group of skim, returning once it is continued
+///
+/// The whole group is stopped, as the terminal would do on ctrl-z, so that the shell sees the
+/// job stopped even when skim is part of a pipeline.
+pub(crate) fn stop_job() {
+    if set_handler(Signal::SIGTSTP, SigHandler::SigDfl).is_err() {
+        return;
+    }
+    if let Err(e) = kill(Pid::from_raw(0), Signal::SIGTSTP) {
+        warn!("Failed to stop the job: {e}");
+    }
+    // the continuation is handled by the caller
+    CONTINUED.store(false, Ordering::Relaxed);
+    let _ = set_handler(Signal::SIGTSTP, SigHandler::Handler(on_tstp));
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[serial_test::serial]
+    fn caught_signals_are_reported_once() {
+        // the handlers are called directly, a real SIGTSTP would stop the test binary
+        on_tstp(Signal::SIGTSTP as nix::libc::c_int);
+        on_cont(Signal::SIGCONT as nix::libc::c_int);
+        assert!(take_suspend_request());
+        assert!(!take_suspend_request());
+        assert!(take_continued());
+        assert!(!take_continued());
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn restoring_gives_back_the_previous_handlers() {
+        let handler = |signal| {
+            let current = set_handler(signal, SigHandler::SigIgn).unwrap();
+            set_handler(signal, current.handler()).unwrap();
+            current.handler()
+        };
+        set_handler(Signal::SIGTSTP, SigHandler::SigDfl).unwrap();
+        set_handler(Signal::SIGCONT, SigHandler::SigIgn).unwrap();
+
+        let saved = install_handlers().unwrap();
+        assert!(matches!(handler(Signal::SIGTSTP), SigHandler::Handler(_)));
+        assert!(matches!(handler(Signal::SIGCONT), SigHandler::Handler(_)));
+        saved.restore();
+        assert!(matches!(handler(Signal::SIGTSTP), SigHandler::SigDfl));
+        assert!(matches!(handler(Signal::SIGCONT), SigHandler::SigIgn));
+        set_handler(Signal::SIGCONT, SigHandler::SigDfl).unwrap();
+    }
+}
@@ -17,7 +17,7 @@
 use ratatui::layout::{Constraint, Direction as RatatuiDirection, Layout, Rect};
 
 use crate::SkimOptions;
-use crate::tui::options::TuiLayout;
+use crate::tui::options::{Margin, TuiLayout};
 use crate::tui::statusline::InfoDisplay;
 use crate::tui::{Direction, Size};
 
@@ -58,6 +58,8 @@ pub struct LayoutTemplate {
     preview_layout: Option<Layout>,
     /// Whether adjacent bordered widgets share their touching row or column.
     collapse_borders: bool,
+    /// Screen margins, applied to the area before anything else is laid out.
+    margin: Margin,
     /// Pre-built [`Layout`] for splitting the work area into three slots.
     ///
     /// When `work_layout_reversed` is `false` the slots map to
@@ -101,7 +103,9 @@ impl LayoutTemplate {
         // Preview placement and layout.
         let preview_visible = (options.preview.is_some() || options.preview_fn.is_some())
             && !options.preview_window.hidden
-            && !matches!(options.preview_window.size, Size::Fixed(0));
+            && !matches!(options.preview_window.size, Size::Fixed(0))
+            // `--preview-pane` previews in a tmux pane instead
+            && !(cfg!(unix) && options.preview_pane.is_some());
 
         let (preview_placement, preview_layout) = if preview_visible {
             let (preview_c, rest_c) = size_to_constraint(options.preview_window.size);
@@ -162,6 +166,7 @@ impl LayoutTemplate {
             work_layout_reversed,
             preview_layout,
             collapse_borders,
+            margin: Margin::from(options.margin.as_str()),
             work_layout,
         }
     }
@@ -170,6 +175,8 @@ impl LayoutTemplate {
     /// absolute [`AppLayout`] for one render frame.
     #[must_use]
     pub fn apply(&self, area: Rect) -> AppLayout {
+        let area = self.margin.apply(area);
+
         // ── Step 1: carve out the preview from the full area ─────────────────
         let (work_area, preview_area): (Rect, Option<Rect>) = match &self.preview_layout {
             Some(layout) => {
@@ -224,6 +231,8 @@ impl LayoutTemplate {
             input_area,
             header_area,
             preview_area,
+            stderr_area: None,
+            query2_area: None,
         }
     }
 }
@@ -247,6 +256,11 @@ pub struct AppLayout {
     pub header_area: Option<Rect>,
     /// Area for the preview pane (`None` when preview is hidden or disabled).
     pub preview_area: Option<Rect>,
+    /// Area for the stderr pane, taken from the list (`None` unless `toggle-stderr` shows it).
+    pub stderr_area: Option<Rect>,
+    /// Row of the secondary query, taken from the list next to the input (`None` unless shown,
+    /// see `--query2`).
+    pub query2_area: Option<Rect>,
 }
 
 impl AppLayout {
@@ -260,6 +274,40 @@ impl AppLayout {
     pub fn compute(area: Rect, options: &SkimOptions, header_height: u16) -> Self {
         LayoutTemplate::from_options(options, header_height).apply(area)
     }
+
+    /// Gives `rows` of the list area to the stderr pane, at the top of the list or at its
+    /// bottom, away from the input
+    pub fn split_stderr(&mut self, rows: u16, at_top: bool) {
+        let list = self.list_area;
+        let rows = rows.min(list.height);
+        let (stderr, rest) = if at_top {
+            let [stderr, rest] = Layout::vertical([Constraint::Length(rows), Constraint::Fill(1)]).areas(list);
+            (stderr, rest)
+        } else {
+            let [rest, stderr] = Layout::vertical([Constraint::Fill(1), Constraint::Length(rows)]).areas(list);
+            (stderr, rest)
+        };
+        self.list_area = rest;
+        self.stderr_area = Some(stderr);
+    }
+
+    /// Gives a row of the list area to the secondary query, at the top of the list or at its
+    /// bottom, next to the input
+    pub fn split_query2(&mut self, at_top: bool) {
+        let list = self.list_area;
+        if list.height == 0 {
+            return;
+        }
+        let (query2, rest) = if at_top {
+            let [query2, rest] = Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(list);
+            (query2, rest)
+        } else {
+            let [rest, query2] = Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(list);
+            (query2, rest)
+        };
+        self.list_area = rest;
+        self.query2_area = Some(query2);
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -562,3 +562,40 @@ fn very_small_area() {
     // input and header fit, list may have zero height but must exist.
     assert_eq!(layout.list_area.width, 20);
 }
+
+// ── Stderr pane ───────────────────────────────────────────────────────
+
+#[test]
+fn stderr_pane_takes_rows_from_the_list() {
+    let options = opts().build().unwrap();
+    let full_list = compute(&options).list_area;
+
+    let mut layout = compute(&options);
+    layout.split_stderr(5, true);
+    let stderr = layout.stderr_area.unwrap();
+    assert_eq!(stderr, Rect { height: 5, ..full_list });
+    assert_vertically_adjacent(stderr, layout.list_area, "stderr -> list");
+    assert_eq!(layout.list_area.height, full_list.height - 5);
+
+    let mut layout = compute(&options);
+    layout.split_stderr(5, false);
+    assert_vertically_adjacent(layout.list_area, layout.stderr_area.unwrap(), "list -> stderr");
+}
+
+#[test]
+fn query2_takes_a_row_of_the_list_next_to_the_input() {
+    let options = opts().build().unwrap();
+    let mut layout = compute(&options);
+    layout.split_query2(false);
+    let query2 = layout.query2_area.unwrap();
+    assert_eq!(query2.height, 1);
+    assert_vertically_adjacent(layout.list_area, query2, "list -> query2");
+    assert_vertically_adjacent(query2, layout.input_area, "query2 -> input");
+
+    let options = opts().layout(TuiLayout::Reverse).build().unwrap();
+    let mut layout = compute(&options);
+    layout.split_query2(true);
+    let query2 = layout.query2_area.unwrap();
+    assert_vertically_adjacent(layout.input_area, query2, "input -> query2");
+    assert_vertically_adjacent(query2, layout.list_area, "query2 -> list");
+}
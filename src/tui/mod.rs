@@ -8,7 +8,7 @@ use std::num::ParseIntError;
 
 pub use app::App;
 pub use event::Event;
-pub use preview::PreviewCallback;
+pub use preview::{PreviewCallback, PreviewOutput, PreviewWidget};
 use thiserror::Error;
 pub use widget::{SkimRender, SkimWidget};
 mod app;
@@ -17,8 +17,10 @@ pub(crate) mod util;
 #[cfg(windows)]
 mod windows;
 pub use backend::Tui;
+mod damage;
 /// Event handling and action definitions
 pub mod event;
+mod file_preview;
 /// Header display components
 pub mod header;
 mod input;
@@ -26,13 +28,20 @@ mod input;
 pub mod item_list;
 /// Single item rendering
 pub(crate) mod item_renderer;
+#[cfg(unix)]
+mod job_control;
 /// Pre-computed widget layout areas
 pub mod layout;
 /// TUI-specific options and configuration
 pub mod options;
 mod preview;
+mod preview_cache;
+mod preview_directives;
 /// Status line display
 pub mod statusline;
+mod stderr_pane;
+pub(crate) mod timings;
+mod tree;
 /// Widget rendering utilities
 pub mod widget;
 
@@ -1,3 +1,8 @@
+use ratatui::layout::{Alignment, Rect};
+use ratatui::style::Style;
+use ratatui::text::Line;
+use ratatui::widgets::Block;
+
 use crate::tui::{Direction, Size};
 
 /// Layout configuration for the TUI
@@ -13,6 +18,191 @@ pub enum TuiLayout {
     ReverseList,
 }
 
+/// Which side of the items wider than the list is cut (`--truncate-side`)
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum TruncateSide {
+    /// Cut the start, keeping the end of the items visible
+    Start,
+    /// Keep both ends of the items, cutting their middle
+    Middle,
+    /// Cut the end
+    #[default]
+    End,
+}
+
+/// Where a non-fullscreen (`--height`) UI is placed on the screen
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Anchor {
+    /// Start on the cursor line, scrolling the terminal up if there is not enough room below it
+    #[default]
+    Cursor,
+    /// Stick to the top of the screen, drawing over the existing content
+    Top,
+    /// Stick to the bottom of the screen, scrolling the terminal up to keep the existing content
+    Bottom,
+}
+
+impl Anchor {
+    /// Computes the first row of a `height`-row viewport on a `term_height`-row terminal,
+    /// given the 0-based row of the cursor.
+    ///
+    /// Returns `(row, to_scroll)`, where `to_scroll` is the number of lines the terminal must
+    /// be scrolled up before drawing so that the content above the cursor is kept.
+    #[must_use]
+    pub fn viewport_row(self, term_height: u16, cursor_row: u16, height: u16) -> (u16, u16) {
+        let height = height.min(term_height);
+        let last_start = term_height - height;
+        match self {
+            Anchor::Cursor => {
+                let to_scroll = cursor_row.saturating_sub(last_start);
+                (cursor_row - to_scroll, to_scroll)
+            }
+            Anchor::Top => (0, 0),
+            Anchor::Bottom => (last_start, cursor_row.saturating_sub(last_start)),
+        }
+    }
+}
+
+/// Screen margins (`--margin`), one [`Size`] per side
+///
+/// Percentages are relative to the area skim draws in: the whole terminal in fullscreen mode,
+/// the `--height` viewport otherwise. Negative sizes are not meaningful here and count as `0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Margin {
+    /// Top margin
+    pub top: Size,
+    /// Right margin
+    pub right: Size,
+    /// Bottom margin
+    pub bottom: Size,
+    /// Left margin
+    pub left: Size,
+}
+
+impl Default for Margin {
+    fn default() -> Self {
+        Self {
+            top: Size::Fixed(0),
+            right: Size::Fixed(0),
+            bottom: Size::Fixed(0),
+            left: Size::Fixed(0),
+        }
+    }
+}
+
+impl Margin {
+    /// Shrinks `area` by the margins, always leaving at least one row and column of a non-empty area
+    #[must_use]
+    pub fn apply(&self, area: Rect) -> Rect {
+        if area.is_empty() {
+            return area;
+        }
+        let resolve = |size: Size, total: u16| match size {
+            Size::Fixed(n) => n,
+            Size::Percent(p) => u16::try_from(u32::from(total) * u32::from(p) / 100).unwrap_or(u16::MAX),
+            Size::Neg(_) => 0,
+        };
+        let top = resolve(self.top, area.height);
+        let bottom = resolve(self.bottom, area.height);
+        let left = resolve(self.left, area.width);
+        let right = resolve(self.right, area.width);
+
+        let height = area.height.saturating_sub(top.saturating_add(bottom)).max(1);
+        let width = area.width.saturating_sub(left.saturating_add(right)).max(1);
+        Rect {
+            x: area.x + left.min(area.width - width),
+            y: area.y + top.min(area.height - height),
+            width,
+            height,
+        }
+    }
+}
+
+impl From<&str> for Margin {
+    /// Parses `TRBL`, `TB,RL`, `T,RL,B` or `T,R,B,L`; unparsable sides count as `0`
+    fn from(value: &str) -> Self {
+        let sides: Vec<Size> = value
+            .split(',')
+            .map(|s| Size::try_from(s.trim()).unwrap_or(Size::Fixed(0)))
+            .collect();
+        let (top, right, bottom, left) = match sides.as_slice() {
+            [all] => (*all, *all, *all, *all),
+            [tb, rl] => (*tb, *rl, *tb, *rl),
+            [t, rl, b] => (*t, *rl, *b, *rl),
+            [t, r, b, l, ..] => (*t, *r, *b, *l),
+            [] => unreachable!("split always yields at least one part"),
+        };
+        Self {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+}
+
+/// Position of a border label (`--border-label-pos`, `--list-label-pos`, ...)
+///
+/// Parsed from `ALIGN[:top|bottom]`, `ALIGN` being `left`, `center` or `right`, or a number as
+/// in fzf: `0` centers the label, positive numbers align it left and negative numbers right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LabelPosition {
+    /// Alignment of the label on the border
+    pub alignment: Alignment,
+    /// Whether the label goes on the bottom border instead of the top one
+    pub bottom: bool,
+}
+
+impl Default for LabelPosition {
+    fn default() -> Self {
+        Self {
+            alignment: Alignment::Center,
+            bottom: false,
+        }
+    }
+}
+
+impl LabelPosition {
+    /// Adds `label` to the titles of `block`, unless it is empty
+    #[must_use]
+    pub fn title<'a>(self, block: Block<'a>, label: &'a str, style: Style) -> Block<'a> {
+        if label.is_empty() {
+            return block;
+        }
+        let line = Line::styled(label, style).alignment(self.alignment);
+        if self.bottom {
+            block.title_bottom(line)
+        } else {
+            block.title_top(line)
+        }
+    }
+}
+
+impl From<&str> for LabelPosition {
+    /// Unknown alignments and sides keep their default
+    fn from(value: &str) -> Self {
+        let mut res = Self::default();
+        for part in value.split(':') {
+            match part {
+                "top" => res.bottom = false,
+                "bottom" => res.bottom = true,
+                "left" => res.alignment = Alignment::Left,
+                "center" => res.alignment = Alignment::Center,
+                "right" => res.alignment = Alignment::Right,
+                n => match n.parse::<i32>().map(i32::signum) {
+                    Ok(1) => res.alignment = Alignment::Left,
+                    Ok(-1) => res.alignment = Alignment::Right,
+                    Ok(_) => res.alignment = Alignment::Center,
+                    Err(_) => {}
+                },
+            }
+        }
+        res
+    }
+}
+
 /// Configuration for the preview pane layout
 #[derive(Debug, Clone)]
 pub struct PreviewLayout {
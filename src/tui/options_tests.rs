@@ -104,3 +104,80 @@ fn test_preview_layout_skips_empty_parts() {
     assert_eq!(layout.direction, Direction::Left);
     assert_eq!(layout.size, Size::Percent(50));
 }
+
+#[test]
+fn test_margin_parse_forms() {
+    let all = Margin::from("2");
+    assert_eq!(all.top, Size::Fixed(2));
+    assert_eq!(all.left, Size::Fixed(2));
+
+    let tb_rl = Margin::from("1,10%");
+    assert_eq!((tb_rl.top, tb_rl.bottom), (Size::Fixed(1), Size::Fixed(1)));
+    assert_eq!((tb_rl.left, tb_rl.right), (Size::Percent(10), Size::Percent(10)));
+
+    let t_rl_b = Margin::from("1,2,3");
+    assert_eq!(
+        (t_rl_b.top, t_rl_b.right, t_rl_b.bottom, t_rl_b.left),
+        (Size::Fixed(1), Size::Fixed(2), Size::Fixed(3), Size::Fixed(2))
+    );
+
+    let trbl = Margin::from("1,2,3,4");
+    assert_eq!(
+        (trbl.top, trbl.right, trbl.bottom, trbl.left),
+        (Size::Fixed(1), Size::Fixed(2), Size::Fixed(3), Size::Fixed(4))
+    );
+
+    assert_eq!(Margin::from("oops"), Margin::default());
+    assert_eq!(Margin::from(""), Margin::default());
+}
+
+#[test]
+fn test_margin_apply_is_relative_to_area() {
+    // Percentages are taken from the area itself, so an inline viewport gets
+    // proportionally smaller margins than the full terminal.
+    let margin = Margin::from("10%,1");
+    assert_eq!(margin.apply(Rect::new(0, 5, 80, 20)), Rect::new(1, 7, 78, 16));
+    assert_eq!(margin.apply(Rect::new(0, 0, 80, 10)), Rect::new(1, 1, 78, 8));
+}
+
+#[test]
+fn test_margin_apply_never_collapses_area() {
+    let margin = Margin::from("50");
+    let area = margin.apply(Rect::new(3, 4, 10, 6));
+    assert_eq!((area.width, area.height), (1, 1));
+    assert!(area.x >= 3 && area.right() <= 13);
+    assert!(area.y >= 4 && area.bottom() <= 10);
+}
+
+#[test]
+fn test_anchor_viewport_row() {
+    // Enough room below the cursor: cursor and bottom anchors differ.
+    assert_eq!(Anchor::Cursor.viewport_row(24, 5, 10), (5, 0));
+    assert_eq!(Anchor::Bottom.viewport_row(24, 5, 10), (14, 0));
+    assert_eq!(Anchor::Top.viewport_row(24, 5, 10), (0, 0));
+
+    // Not enough room: both cursor and bottom scroll to keep the content.
+    assert_eq!(Anchor::Cursor.viewport_row(24, 20, 10), (14, 6));
+    assert_eq!(Anchor::Bottom.viewport_row(24, 20, 10), (14, 6));
+    assert_eq!(Anchor::Top.viewport_row(24, 20, 10), (0, 0));
+
+    // Taller than the terminal: clamped.
+    assert_eq!(Anchor::Bottom.viewport_row(24, 3, 40), (0, 3));
+}
+
+#[test]
+fn test_label_position_parse() {
+    assert_eq!(LabelPosition::from(""), LabelPosition::default());
+    assert_eq!(
+        LabelPosition::from("right:bottom"),
+        LabelPosition {
+            alignment: Alignment::Right,
+            bottom: true,
+        }
+    );
+    // fzf-style offsets only keep their side
+    assert_eq!(LabelPosition::from("3").alignment, Alignment::Left);
+    assert_eq!(LabelPosition::from("-2:top").alignment, Alignment::Right);
+    assert_eq!(LabelPosition::from("0:bottom").alignment, Alignment::Center);
+    assert_eq!(LabelPosition::from("oops"), LabelPosition::default());
+}
@@ -1,10 +1,10 @@
 use ansi_to_tui::IntoText;
 use eyre::{Result, eyre};
 use portable_pty::{PtyPair, PtySize, native_pty_system};
-use ratatui::layout::Alignment;
+use ratatui::layout::{Alignment, Position, Rect};
 use ratatui::prelude::Backend;
 use ratatui::style::Stylize;
-use ratatui::text::{Line, Text};
+use ratatui::text::{Line, Span, Text};
 use ratatui::widgets::{Block, Borders, Clear, Paragraph, Widget};
 #[cfg(feature = "image")]
 use ratatui_image::picker::Picker;
@@ -13,24 +13,81 @@ use ratatui_image::protocol::Protocol as ImageProtocol;
 use tui_term::vt100;
 use tui_term::widget::PseudoTerminal;
 
+use std::borrow::Cow;
 use std::env;
 use std::io::Read;
-use std::sync::{Arc, RwLock, mpsc};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex, RwLock, mpsc};
 use std::thread::JoinHandle;
 use std::time::Instant;
 
+use super::options::LabelPosition;
+use super::preview_cache::PreviewCache;
+use super::preview_directives::PreviewDirectives;
 use super::statusline::spinner_char;
-use super::util::{find_csi_end, find_osc_end, handle_csi_query, handle_osc_query};
+use super::util::{
+    bidi_visual_order, find_csi_end, find_osc_end, handle_csi_query, handle_osc_query, reorder_line,
+    wrap_text_with_sign,
+};
 use super::widget::{SkimRender, SkimWidget};
 use super::{BorderType, Direction, Event, Tui};
 
+use crate::interpreter::Interpreter;
+use crate::process_group::{self, ProcessGroup};
+use crate::reader::StderrLog;
 use crate::theme::ColorTheme;
 use crate::{SkimItem, SkimOptions};
 
 // PreviewCallback for ratatui - returns Vec<String> instead of AnsiString
 pub type PreviewCallbackFn = dyn Fn(Vec<Arc<dyn SkimItem>>) -> Vec<String> + Send + Sync + 'static;
+/// Preview callback returning any [`PreviewOutput`], see [`PreviewCallback::output`]
+pub type PreviewOutputFn = dyn Fn(Vec<Arc<dyn SkimItem>>) -> PreviewOutput + Send + Sync + 'static;
 const PREVIEW_MAX_BYTES: usize = 1024 * 1024;
 const VT_SCROLLBACK: usize = 100_000;
+/// Number of previews kept around for `--preview-prefetch`, beyond the prefetched ones
+const PREVIEW_CACHE_EXTRA: usize = 32;
+
+/// A widget that can be drawn in the preview pane
+///
+/// Implemented for every cloneable ratatui [`Widget`] (`Paragraph`, `Table`,
+/// `List`...). The widget is drawn inside the preview border on every frame.
+pub trait PreviewWidget: Send + Sync {
+    /// Draw the widget into `area`
+    fn render(&self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer);
+}
+
+impl<W> PreviewWidget for W
+where
+    W: Widget + Clone + Send + Sync,
+{
+    fn render(&self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+        self.clone().render(area, buf);
+    }
+}
+
+/// Content returned by a [`PreviewCallback`]
+pub enum PreviewOutput {
+    /// Lines of text, which may contain ANSI escape codes
+    Lines(Vec<String>),
+    /// Already styled text, displayed as-is
+    Text(Text<'static>),
+    /// Any widget, drawn in the preview area
+    ///
+    /// Scrolling the preview has no effect on widgets.
+    Widget(Box<dyn PreviewWidget>),
+}
+
+impl PreviewOutput {
+    /// The output as lines of text, the text of styled lines and none for a widget
+    fn into_lines(self) -> Vec<String> {
+        match self {
+            PreviewOutput::Lines(lines) => lines,
+            PreviewOutput::Text(text) => text.lines.iter().map(ToString::to_string).collect(),
+            PreviewOutput::Widget(_) => Vec::new(),
+        }
+    }
+}
 
 /// Preview content options
 pub(crate) enum PreviewContent {
@@ -38,6 +95,8 @@ pub(crate) enum PreviewContent {
     Text(Text<'static>),
     /// Terminal screen (for PTY previews with cursor positioning)
     Terminal(Arc<RwLock<vt100::Parser>>),
+    /// Widget returned by a preview callback
+    Widget(Box<dyn PreviewWidget>),
     /// Image
     #[cfg(feature = "image")]
     Image {
@@ -53,10 +112,98 @@ impl Default for PreviewContent {
     }
 }
 
+/// A preview to compute ahead of time with [`Preview::prefetch`]
+pub(crate) enum PreviewJob {
+    /// Expanded shell command
+    Command(String),
+    /// File for the builtin file previewer
+    File(PathBuf),
+}
+
+impl PreviewJob {
+    /// Cache key, matching the `cmd` the preview is spawned with
+    fn key(&self) -> String {
+        match self {
+            PreviewJob::Command(cmd) => cmd.clone(),
+            PreviewJob::File(path) => file_cache_key(path),
+        }
+    }
+}
+
+fn file_cache_key(path: &std::path::Path) -> String {
+    format!("{} {}", super::file_preview::BUILTIN_FILE, path.display())
+}
+
+/// Placeholders of the preview commands expanding to the size of the preview, in lines and columns
+const SIZE_PLACEHOLDERS: [&str; 2] = ["{lines}", "{columns}"];
+
+/// Whether the preview command `cmd` depends on the size of the preview, see [`expand_size`]
+pub(crate) fn uses_size(cmd: &str) -> bool {
+    SIZE_PLACEHOLDERS.iter().any(|placeholder| cmd.contains(placeholder))
+}
+
+/// Expands `{lines}` and `{columns}` in the preview command `cmd`, before its other placeholders
+pub(crate) fn expand_size(cmd: &str, (lines, columns): (u16, u16)) -> Cow<'_, str> {
+    if uses_size(cmd) {
+        Cow::Owned(
+            cmd.replace(SIZE_PLACEHOLDERS[0], &lines.to_string())
+                .replace(SIZE_PLACEHOLDERS[1], &columns.to_string()),
+        )
+    } else {
+        Cow::Borrowed(cmd)
+    }
+}
+
 /// Callback function for generating preview content
+///
+/// Closures returning `Vec<String>` convert with [`From`]; use
+/// [`PreviewCallback::text`] or [`PreviewCallback::widget`] to return styled
+/// text or arbitrary widgets instead. Calling the callback through [`Deref`](std::ops::Deref)
+/// gives lines, [`PreviewCallback::preview`] gives the output as returned.
 #[derive(Clone)]
 pub struct PreviewCallback {
     inner: Arc<PreviewCallbackFn>,
+    output: Arc<PreviewOutputFn>,
+}
+
+impl PreviewCallback {
+    /// Create a callback returning styled text
+    ///
+    /// Unlike lines, the text is not parsed for ANSI escape codes.
+    pub fn text<F>(func: F) -> Self
+    where
+        F: Fn(Vec<Arc<dyn SkimItem>>) -> Text<'static> + Send + Sync + 'static,
+    {
+        Self::output(move |items| PreviewOutput::Text(func(items)))
+    }
+
+    /// Create a callback returning a widget to draw in the preview pane
+    pub fn widget<F, W>(func: F) -> Self
+    where
+        F: Fn(Vec<Arc<dyn SkimItem>>) -> W + Send + Sync + 'static,
+        W: PreviewWidget + 'static,
+    {
+        Self::output(move |items| PreviewOutput::Widget(Box::new(func(items))))
+    }
+
+    /// Create a callback that picks the kind of output for each call
+    pub fn output<F>(func: F) -> Self
+    where
+        F: Fn(Vec<Arc<dyn SkimItem>>) -> PreviewOutput + Send + Sync + 'static,
+    {
+        let output: Arc<PreviewOutputFn> = Arc::new(func);
+        let lines = Arc::clone(&output);
+        Self {
+            inner: Arc::new(move |items| lines(items).into_lines()),
+            output,
+        }
+    }
+
+    /// The preview of `items`, in the kind of output the callback returns
+    #[must_use]
+    pub fn preview(&self, items: Vec<Arc<dyn SkimItem>>) -> PreviewOutput {
+        (self.output)(items)
+    }
 }
 
 impl<F> From<F> for PreviewCallback
@@ -64,7 +211,12 @@ where
     F: Fn(Vec<Arc<dyn SkimItem>>) -> Vec<String> + Send + Sync + 'static,
 {
     fn from(func: F) -> Self {
-        Self { inner: Arc::new(func) }
+        let inner: Arc<PreviewCallbackFn> = Arc::new(func);
+        let lines = Arc::clone(&inner);
+        Self {
+            inner,
+            output: Arc::new(move |items| PreviewOutput::Lines(lines(items))),
+        }
     }
 }
 
@@ -76,21 +228,39 @@ impl std::ops::Deref for PreviewCallback {
     }
 }
 
+#[allow(clippy::struct_excessive_bools)]
 pub struct Preview {
     pub(crate) content: Arc<RwLock<PreviewContent>>,
     pub cmd: String,
     pub rows: u16,
     pub cols: u16,
+    /// Top left corner of the pane in skim's window, borders included
+    origin: Position,
     pub scroll_y: u16,
     pub scroll_x: u16,
     pub thread_handle: Option<JoinHandle<()>>,
     /// Channel to signal thread interruption
     interrupt_tx: Option<mpsc::Sender<()>>,
+    /// Process group of the running preview command
+    process_group: Option<ProcessGroup>,
     pub theme: Arc<ColorTheme>,
     /// Border type
     pub border: BorderType,
     pub direction: Direction,
     pub wrap: bool,
+    /// Wrapping requested by the `#skim:` header of the current preview, overriding `wrap`
+    wrap_override: Option<bool>,
+    /// Whether the current preview is a command's output, which may start with a `#skim:` header
+    directives: bool,
+    /// Continuation marker for wrapped lines (`--wrap-sign`)
+    wrap_sign: Option<String>,
+    /// Label on the border (`--preview-label`, `change-preview-label`)
+    pub(crate) label: Option<String>,
+    label_pos: LabelPosition,
+    /// Shell running the preview commands, see `--with-shell`
+    shell: Interpreter,
+    /// State of skim exported to the preview commands, see `App::state_env`
+    pub(crate) env: Vec<(&'static str, String)>,
     pty: Option<PtyPair>,
     pty_child: Option<Box<dyn portable_pty::Child + Send + Sync>>,
     #[cfg(feature = "image")]
@@ -100,6 +270,12 @@ pub struct Preview {
     pub total_lines: u16,
     loading: bool,
     spinner_start: Instant,
+    /// Number of adjacent previews to prefetch (`--preview-prefetch`)
+    prefetch: usize,
+    /// Rendered previews, only kept when prefetching
+    pub(crate) cache: Option<Arc<Mutex<PreviewCache>>>,
+    /// Log the stderr of the preview commands is written to, shown by `toggle-stderr`
+    pub(crate) stderr_log: StderrLog,
 }
 
 impl Default for Preview {
@@ -223,17 +399,76 @@ impl Preview {
 
     pub fn content(&mut self, content: &[u8]) -> Result<()> {
         let text = content.to_owned().into_text()?;
+        self.set_content(PreviewContent::Text(text))
+    }
+
+    /// Display the output of a preview callback
+    pub fn output(&mut self, output: PreviewOutput) -> Result<()> {
+        match output {
+            PreviewOutput::Lines(lines) => self.content(lines.join("\n").as_bytes()),
+            PreviewOutput::Text(text) => self.set_content(PreviewContent::Text(text)),
+            PreviewOutput::Widget(widget) => self.set_content(PreviewContent::Widget(widget)),
+        }
+    }
+
+    fn set_content(&mut self, new: PreviewContent) -> Result<()> {
         let Ok(mut content) = self.content.write() else {
             return Err(eyre::eyre!("Failed to acquire content for writing"));
         };
-        self.total_lines = text.lines.len().try_into().unwrap();
-        *content = PreviewContent::Text(text);
+        self.total_lines = match &new {
+            PreviewContent::Text(text) => text.lines.len().try_into().unwrap_or(u16::MAX),
+            _ => 0,
+        };
+        *content = new;
         self.scroll_y = 0;
         self.scroll_x = 0;
+        self.wrap_override = None;
+        self.directives = false;
         self.loading = false;
         Ok(())
     }
 
+    /// Apply and remove the `#skim:` header of a command's output, see [`PreviewDirectives`]
+    ///
+    /// Called once the output is complete, after the `--preview-window` offset so that the
+    /// header's offset wins.
+    pub(crate) fn apply_directives(&mut self) {
+        if !std::mem::take(&mut self.directives) {
+            return;
+        }
+        let directives = {
+            let Ok(mut content) = self.content.write() else {
+                return;
+            };
+            let PreviewContent::Text(text) = &mut *content else {
+                return;
+            };
+            let Some(directives) = PreviewDirectives::take(text) else {
+                return;
+            };
+            self.total_lines = text.lines.len().try_into().unwrap_or(u16::MAX);
+            directives
+        };
+        trace!("applying preview directives {directives:?}");
+        if directives.wrap.is_some() {
+            self.wrap_override = directives.wrap;
+        }
+        if let Some(offset) = directives.offset {
+            self.set_offset(offset);
+        }
+    }
+
+    /// Whether long lines are wrapped in the current preview
+    fn wrapped(&self) -> bool {
+        self.wrap_override.unwrap_or(self.wrap)
+    }
+
+    /// Flip line wrapping, dropping the current preview's `#skim:` wrap directive
+    pub(crate) fn toggle_wrap(&mut self) {
+        self.wrap = !self.wrapped();
+        self.wrap_override = None;
+    }
+
     pub(crate) fn is_loading(&self) -> bool {
         self.loading
     }
@@ -283,6 +518,83 @@ impl Preview {
         self.scroll_x = self.scroll_x.saturating_add(cols);
     }
 
+    /// Area of the text inside the borders, in skim's window
+    fn inner_area(&self) -> Rect {
+        self.block().inner(Rect::from((
+            self.origin,
+            ratatui::layout::Size::new(self.cols, self.rows),
+        )))
+    }
+
+    /// Lines and columns of the text inside the borders
+    pub(crate) fn inner_size(&self) -> (u16, u16) {
+        let inner = self.inner_area();
+        (inner.height, inner.width)
+    }
+
+    /// Size and position of the text, for the environment of the preview commands
+    ///
+    /// `LINES` and `COLUMNS` are set like a terminal of the size of the preview would, `ROWS` is
+    /// kept for the existing scripts; `SKIM_PREVIEW_*` and their `FZF_PREVIEW_*` counterparts
+    /// also give the position of the preview, from the top left corner of skim's window.
+    fn size_env(&self) -> Vec<(&'static str, String)> {
+        let inner = self.inner_area();
+        let (lines, columns, top, left) = (
+            inner.height.to_string(),
+            inner.width.to_string(),
+            inner.y.to_string(),
+            inner.x.to_string(),
+        );
+        vec![
+            ("LINES", lines.clone()),
+            ("ROWS", lines.clone()),
+            ("COLUMNS", columns.clone()),
+            ("SKIM_PREVIEW_LINES", lines.clone()),
+            ("SKIM_PREVIEW_COLUMNS", columns.clone()),
+            ("SKIM_PREVIEW_TOP", top.clone()),
+            ("SKIM_PREVIEW_LEFT", left.clone()),
+            ("FZF_PREVIEW_LINES", lines),
+            ("FZF_PREVIEW_COLUMNS", columns),
+            ("FZF_PREVIEW_TOP", top),
+            ("FZF_PREVIEW_LEFT", left),
+        ]
+    }
+
+    /// Sets the area of the whole pane, borders included, resizing the pty to match
+    pub(crate) fn set_area(&mut self, area: Rect) {
+        self.origin = area.as_position();
+        if self.rows == area.height && self.cols == area.width {
+            return;
+        }
+        self.rows = area.height;
+        self.cols = area.width;
+        // Commands see the preview size through $LINES/$COLUMNS
+        self.clear_cache();
+        self.pty.as_ref().map(|p| {
+            p.master.resize(PtySize {
+                rows: self.rows,
+                cols: self.cols,
+                ..Default::default()
+            })
+        });
+    }
+
+    /// The borders of the pane, without its label
+    fn block(&self) -> Block<'static> {
+        let block = Block::new().style(self.theme.normal).border_style(self.theme.border);
+        if let Some(border_type) = self.border.into_ratatui() {
+            block.borders(Borders::ALL).border_type(border_type)
+        } else {
+            // No border on preview itself - separator will be drawn between areas
+            match self.direction {
+                Direction::Up => block.borders(Borders::BOTTOM),
+                Direction::Down => block.borders(Borders::TOP),
+                Direction::Left => block.borders(Borders::RIGHT),
+                Direction::Right => block.borders(Borders::LEFT),
+            }
+        }
+    }
+
     pub fn set_offset(&mut self, offset: u16) {
         self.scroll_y = offset.saturating_sub(1); // -1 because line numbers are 1-indexed
     }
@@ -301,6 +613,9 @@ impl Preview {
         if let Some(tx) = self.interrupt_tx.take() {
             let _ = tx.send(());
         }
+        if let Some(group) = self.process_group.take() {
+            group.kill();
+        }
 
         if let Some(mut child) = self.pty_child.take() {
             trace!("killing pty child process");
@@ -322,6 +637,131 @@ impl Preview {
         }
     }
 
+    /// Shell command running `cmd` with the preview environment
+    fn shell_cmd(&self, cmd: &str) -> std::process::Command {
+        let mut shell_cmd = self.shell.command(cmd);
+        shell_cmd
+            .envs(self.size_env())
+            .envs(self.env.iter().cloned())
+            .env("PAGER", "");
+        if let Ok(cwd) = env::current_dir() {
+            shell_cmd.current_dir(cwd);
+        }
+        shell_cmd
+    }
+
+    /// Display the cached preview for `self.cmd`, if any
+    fn show_cached<B: Backend>(&mut self, tui: &Tui<B>) -> bool
+    where
+        B::Error: Send + Sync + 'static,
+    {
+        let Some(text) = self.cache.as_ref().and_then(|c| c.lock().unwrap().get(&self.cmd)) else {
+            return false;
+        };
+        trace!("using cached preview for {}", self.cmd);
+        if let Ok(mut content) = self.content.write() {
+            *content = PreviewContent::Text(text);
+        }
+        let _ = tui.event_tx.try_send(Event::PreviewReady);
+        true
+    }
+
+    /// Compute previews in the background so that they display instantly later
+    ///
+    /// Jobs are started in order, skipping cached ones, until twice
+    /// `--preview-prefetch` of them are running (one window on each side of the
+    /// cursor). Does nothing for PTY and image previews.
+    pub(crate) fn prefetch(&mut self, jobs: Vec<PreviewJob>) {
+        let Some(cache) = &self.cache else {
+            return;
+        };
+        #[cfg(feature = "image")]
+        if self.image {
+            return;
+        }
+        if self.pty.is_some() {
+            return;
+        }
+        for job in jobs {
+            let key = job.key();
+            if !cache.lock().unwrap().start(&key, 2 * self.prefetch) {
+                continue;
+            }
+            trace!("prefetching preview {key}");
+            let cache = cache.clone();
+            let shell_cmd = match &job {
+                PreviewJob::Command(cmd) => Some(self.shell_cmd(cmd)),
+                PreviewJob::File(_) => None,
+            };
+            std::thread::spawn(move || {
+                let text = match (job, shell_cmd) {
+                    (PreviewJob::File(path), _) => Some(super::file_preview::render_file(&path, PREVIEW_MAX_BYTES)),
+                    (PreviewJob::Command(_), Some(mut shell_cmd)) => shell_cmd
+                        .output()
+                        .ok()
+                        .filter(|out| out.status.success())
+                        .map(|mut out| {
+                            out.stdout.truncate(PREVIEW_MAX_BYTES);
+                            out.stdout.into_text().unwrap_or_default()
+                        }),
+                    (PreviewJob::Command(_), None) => None,
+                };
+                let mut cache = cache.lock().unwrap();
+                match text {
+                    Some(text) => cache.insert(key, text),
+                    None => cache.abandon(&key),
+                }
+            });
+        }
+    }
+
+    /// Forget cached previews
+    pub(crate) fn clear_cache(&mut self) {
+        if let Some(cache) = &self.cache {
+            cache.lock().unwrap().clear();
+        }
+    }
+
+    /// Preview `path` with the builtin file previewer, in a background thread
+    pub(crate) fn spawn_file<B: Backend>(&mut self, tui: &mut Tui<B>, path: PathBuf)
+    where
+        B::Error: Send + Sync + 'static,
+    {
+        self.kill();
+        self.cmd = file_cache_key(&path);
+        self.loading = true;
+        self.scroll_y = 0;
+        self.scroll_x = 0;
+        self.wrap_override = None;
+        self.directives = false;
+
+        if self.show_cached(tui) {
+            return;
+        }
+
+        let event_tx_clone = tui.event_tx.clone();
+        let content = self.content.clone();
+        let (interrupt_tx, interrupt_rx) = mpsc::channel();
+        self.interrupt_tx = Some(interrupt_tx);
+        let cache = self.cache.clone();
+        let key = self.cmd.clone();
+
+        self.thread_handle = Some(std::thread::spawn(move || {
+            let text = super::file_preview::render_file(&path, PREVIEW_MAX_BYTES);
+            if let Some(cache) = &cache {
+                cache.lock().unwrap().insert(key, text.clone());
+            }
+            if interrupt_rx.try_recv().is_ok() {
+                trace!("interrupt signal received, exiting");
+                return;
+            }
+            if let Ok(mut c) = content.write() {
+                *c = PreviewContent::Text(text);
+                let _ = event_tx_clone.blocking_send(Event::PreviewReady);
+            }
+        }));
+    }
+
     #[allow(clippy::too_many_lines)]
     pub fn spawn<B: Backend>(&mut self, tui: &mut Tui<B>, cmd: &str) -> Result<()>
     where
@@ -334,6 +774,8 @@ impl Preview {
         // Reset scroll position and manual_scroll flag for new preview
         self.scroll_y = 0;
         self.scroll_x = 0;
+        self.wrap_override = None;
+        self.directives = false;
 
         let event_tx_clone = tui.event_tx.clone();
         let content = self.content.clone();
@@ -391,16 +833,14 @@ impl Preview {
             );
             self.init_pty();
             trace!("initialized pty");
-            // no PTY on windows, we can keep sh
-            let mut shell_cmd = portable_pty::CommandBuilder::new("/bin/sh");
-            shell_cmd.env("ROWS", self.rows.to_string());
-            shell_cmd.env("COLUMNS", self.cols.to_string());
+            let mut shell_cmd = self.shell.pty_command(cmd);
+            for (key, value) in self.size_env().into_iter().chain(self.env.iter().cloned()) {
+                shell_cmd.env(key, value);
+            }
             shell_cmd.env("PAGER", "");
-            shell_cmd.arg("-c");
             if let Ok(cwd) = env::current_dir() {
                 shell_cmd.cwd(cwd);
             }
-            shell_cmd.arg(cmd);
             self.pty_child = Some(pty.slave.spawn_command(shell_cmd).map_err(|e| {
                 warn!("{:#?}", e.backtrace());
                 eyre!(Box::<dyn std::error::Error + Send + Sync + 'static>::from(e))
@@ -485,32 +925,36 @@ impl Preview {
                 let _ = event_tx_clone.blocking_send(Event::PreviewReady);
             }));
         } else {
-            trace!("spawning preview cmd {cmd}");
-            let mut shell_cmd = crate::shell_cmd(cmd);
-            shell_cmd
-                .env("ROWS", self.rows.to_string())
-                .env("COLUMNS", self.cols.to_string())
-                .env("PAGER", "");
-            if let Ok(cwd) = env::current_dir() {
-                shell_cmd.current_dir(cwd);
+            self.directives = true;
+            if self.show_cached(tui) {
+                return Ok(());
             }
+            trace!("spawning preview cmd {cmd}");
+            let mut shell_cmd = self.shell_cmd(cmd);
+            shell_cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+            // Spawn here rather than in the thread so that `kill` can reach the
+            // command's process group right away.
+            let child = process_group::spawn(&mut shell_cmd).map(|(child, group)| {
+                self.process_group = Some(group);
+                child
+            });
 
             let (interrupt_tx, interrupt_rx) = mpsc::channel();
             self.interrupt_tx = Some(interrupt_tx);
+            let cache = self.cache.clone();
+            let key = self.cmd.clone();
+            let stderr_log = self.stderr_log.clone();
 
             self.thread_handle = Some(std::thread::spawn(move || {
-                if interrupt_rx.try_recv().is_ok() {
-                    return;
-                }
-
-                let try_out = shell_cmd.output();
+                let try_out = child.and_then(std::process::Child::wait_with_output);
                 if try_out.is_err() {
-                    log::info!("Shell cmd in error: {try_out:?}");
+                    info!("Shell cmd in error: {try_out:?}");
                     let _ = event_tx_clone.blocking_send(Event::PreviewReady);
                     return;
                 }
 
                 let mut out = try_out.unwrap();
+                stderr_log.push("preview", &String::from_utf8_lossy(&out.stderr));
 
                 if interrupt_rx.try_recv().is_ok() {
                     return;
@@ -519,7 +963,11 @@ impl Preview {
                 if let Ok(mut c) = content.write() {
                     if out.status.success() {
                         out.stdout.resize(PREVIEW_MAX_BYTES.min(out.stdout.len()), 0);
-                        *c = PreviewContent::Text(out.stdout.into_text().unwrap_or_default());
+                        let text = out.stdout.into_text().unwrap_or_default();
+                        if let Some(cache) = &cache {
+                            cache.lock().unwrap().insert(key, text.clone());
+                        }
+                        *c = PreviewContent::Text(text);
                     } else {
                         *c = PreviewContent::Text(out.stderr.clone().into_text().unwrap_or_default());
                     }
@@ -542,13 +990,27 @@ impl Preview {
         // Calculate total lines in content
         let total_lines: u16 = text.lines.len().try_into().unwrap();
 
-        // Create paragraph with optional block
-        let mut paragraph = Paragraph::new(text.clone()).scroll((self.scroll_y, self.scroll_x));
+        // Show right-to-left lines in visual order
+        let mut text = text.clone();
+        for line in &mut text.lines {
+            if let Some(order) = bidi_visual_order(line) {
+                *line = reorder_line(line, &order);
+            }
+        }
 
-        // Enable wrapping if wrap is true
-        if self.wrap {
-            paragraph = paragraph.wrap(ratatui::widgets::Wrap { trim: false });
+        // Create paragraph with optional block
+        let wrap = self.wrapped();
+        let mut paragraph = match self.wrap_sign.as_deref().filter(|_| wrap) {
+            // Reflow ourselves to mark continuation lines
+            Some(sign) => {
+                let width = usize::from(outer.inner(area).width);
+                let sign = Span::styled(sign, self.theme.info);
+                Paragraph::new(wrap_text_with_sign(&text, width, &sign))
+            }
+            None if wrap => Paragraph::new(text).wrap(ratatui::widgets::Wrap { trim: false }),
+            None => Paragraph::new(text),
         }
+        .scroll((self.scroll_y, self.scroll_x));
 
         // Add scroll position indicator at top-right if scrolled
         if self.scroll_y > 0 && total_lines > 0 {
@@ -666,14 +1128,23 @@ impl SkimWidget for Preview {
             border: options.border,
             direction: options.preview_window.direction,
             wrap: options.preview_window.wrap,
+            wrap_override: None,
+            directives: false,
+            wrap_sign: options.wrap_sign.clone(),
+            label: options.preview_label.clone(),
+            label_pos: LabelPosition::from(options.preview_label_pos.as_deref().unwrap_or_default()),
+            shell: Interpreter::from_options(options),
+            env: Vec::new(),
             content: Arc::new(RwLock::new(PreviewContent::default())),
             cmd: Default::default(),
             rows: 0,
             cols: 0,
+            origin: Position::default(),
             scroll_y: 0,
             scroll_x: 0,
             thread_handle: None,
             interrupt_tx: None,
+            process_group: None,
             pty: None,
             pty_child: None,
             #[cfg(feature = "image")]
@@ -683,6 +1154,13 @@ impl SkimWidget for Preview {
             total_lines: 0,
             loading: false,
             spinner_start: Instant::now(),
+            prefetch: options.preview_prefetch,
+            cache: (options.preview_prefetch > 0).then(|| {
+                Arc::new(Mutex::new(PreviewCache::new(
+                    2 * options.preview_prefetch + PREVIEW_CACHE_EXTRA,
+                )))
+            }),
+            stderr_log: StderrLog::default(),
         };
         #[cfg(target_os = "linux")]
         if options.preview_window.pty {
@@ -692,34 +1170,15 @@ impl SkimWidget for Preview {
     }
 
     fn render(&mut self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) -> SkimRender {
-        if self.rows != area.height || self.cols != area.width {
-            self.rows = area.height;
-            self.cols = area.width;
-            self.pty.as_ref().map(|p| {
-                p.master.resize(PtySize {
-                    rows: self.rows,
-                    cols: self.cols,
-                    ..Default::default()
-                })
-            });
-        }
+        self.set_area(area);
         let Ok(mut content) = self.content.try_write() else {
             return SkimRender::default();
         };
 
-        let mut block = Block::new().style(self.theme.normal).border_style(self.theme.border);
+        let mut block = self.block();
 
-        // Add borders based on direction and border setting
-        if let Some(border_type) = self.border.into_ratatui() {
-            block = block.borders(Borders::ALL).border_type(border_type);
-        } else {
-            // No border on preview itself - separator will be drawn between areas
-            match self.direction {
-                Direction::Up => block = block.borders(Borders::BOTTOM),
-                Direction::Down => block = block.borders(Borders::TOP),
-                Direction::Left => block = block.borders(Borders::RIGHT),
-                Direction::Right => block = block.borders(Borders::LEFT),
-            }
+        if let Some(label) = &self.label {
+            block = self.label_pos.title(block, label, self.theme.label);
         }
 
         Clear.render(area, buf);
@@ -728,6 +1187,11 @@ impl SkimWidget for Preview {
         match &mut *content {
             PreviewContent::Text(text) => self.total_lines = self.render_text(block, area, buf, text),
             PreviewContent::Terminal(parser) => self.total_lines = self.render_pty(block, area, buf, parser.as_ref()),
+            PreviewContent::Widget(widget) => {
+                let inner = block.inner(area);
+                block.render(area, buf);
+                widget.render(inner, buf);
+            }
             #[cfg(feature = "image")]
             PreviewContent::Image { source, protocol, size } => {
                 self.render_image(block, area, buf, source, protocol, size);
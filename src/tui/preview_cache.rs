@@ -0,0 +1,110 @@
+//! Cache of rendered previews for `--preview-prefetch`
+//!
+//! Entries are keyed by the expanded preview command, so a cached preview is
+//! only reused when the exact same command would have been run. The cache also
+//! tracks prefetch jobs that are still running, to bound their number and avoid
+//! starting the same one twice.
+
+use std::collections::HashSet;
+
+use indexmap::IndexMap;
+use ratatui::text::Text;
+
+/// Least-recently-used cache of preview contents
+pub(crate) struct PreviewCache {
+    entries: IndexMap<String, Text<'static>>,
+    in_flight: HashSet<String>,
+    capacity: usize,
+}
+
+impl PreviewCache {
+    /// Create a cache holding at most `capacity` previews
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            entries: IndexMap::new(),
+            in_flight: HashSet::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Get the preview for `key`, marking it as recently used
+    pub(crate) fn get(&mut self, key: &str) -> Option<Text<'static>> {
+        let text = self.entries.shift_remove(key)?;
+        self.entries.insert(key.to_string(), text.clone());
+        Some(text)
+    }
+
+    /// Store the preview for `key`, evicting the least recently used one if full
+    pub(crate) fn insert(&mut self, key: String, text: Text<'static>) {
+        self.in_flight.remove(&key);
+        self.entries.shift_remove(&key);
+        if self.entries.len() >= self.capacity {
+            self.entries.shift_remove_index(0);
+        }
+        self.entries.insert(key, text);
+    }
+
+    /// Try to register a prefetch job for `key`
+    ///
+    /// Returns `false` if the preview is already cached or being computed, or if
+    /// `max_jobs` jobs are already running.
+    pub(crate) fn start(&mut self, key: &str, max_jobs: usize) -> bool {
+        if self.entries.contains_key(key) || self.in_flight.contains(key) || self.in_flight.len() >= max_jobs {
+            return false;
+        }
+        self.in_flight.insert(key.to_string());
+        true
+    }
+
+    /// Unregister a prefetch job that produced nothing worth caching
+    pub(crate) fn abandon(&mut self, key: &str) {
+        self.in_flight.remove(key);
+    }
+
+    /// Drop every cached preview, e.g. when the preview size changed
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = PreviewCache::new(2);
+        cache.insert("a".into(), Text::raw("A"));
+        cache.insert("b".into(), Text::raw("B"));
+        assert!(cache.get("a").is_some());
+        cache.insert("c".into(), Text::raw("C"));
+        assert!(cache.get("b").is_none());
+        assert_eq!(cache.get("a").unwrap().to_string(), "A");
+        assert_eq!(cache.get("c").unwrap().to_string(), "C");
+    }
+
+    #[test]
+    fn start_bounds_running_jobs() {
+        let mut cache = PreviewCache::new(8);
+        assert!(cache.start("a", 2));
+        assert!(!cache.start("a", 2));
+        assert!(cache.start("b", 2));
+        assert!(!cache.start("c", 2));
+        cache.abandon("a");
+        assert!(cache.start("c", 2));
+        cache.insert("b".into(), Text::raw("B"));
+        assert!(!cache.start("b", 2));
+        assert!(cache.start("d", 2));
+    }
+
+    #[test]
+    fn clear_keeps_running_jobs() {
+        let mut cache = PreviewCache::new(8);
+        cache.insert("a".into(), Text::raw("A"));
+        assert!(cache.start("b", 1));
+        cache.clear();
+        assert!(cache.get("a").is_none());
+        assert!(!cache.start("c", 1));
+    }
+}
@@ -0,0 +1,96 @@
+//! Directives that preview commands put on their first line
+//!
+//! A preview command can start its output with a header such as
+//! `#skim: offset=42; wrap=off` to set the scroll offset and line wrapping of
+//! the preview it produces. The header line is removed before the preview is
+//! displayed. Unknown keys and invalid values are ignored.
+
+use ratatui::text::Text;
+
+/// Prefix marking the directive header
+const PREFIX: &str = "#skim:";
+
+/// Settings requested by a preview command for its own output
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PreviewDirectives {
+    /// Line to scroll to, 1-based as in `--preview-window +N`
+    pub(crate) offset: Option<u16>,
+    /// Whether to wrap long lines
+    pub(crate) wrap: Option<bool>,
+}
+
+impl PreviewDirectives {
+    /// Parse a header line, `None` if it does not start with `#skim:`
+    pub(crate) fn parse(line: &str) -> Option<Self> {
+        let mut directives = Self::default();
+        for directive in line.trim_end().strip_prefix(PREFIX)?.split(';') {
+            let Some((key, value)) = directive.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "offset" => directives.offset = value.trim_start_matches('+').parse().ok(),
+                "wrap" => {
+                    directives.wrap = match value {
+                        "on" | "true" | "yes" => Some(true),
+                        "off" | "false" | "no" => Some(false),
+                        _ => None,
+                    };
+                }
+                key => debug!("ignoring unknown preview directive {key}"),
+            }
+        }
+        Some(directives)
+    }
+
+    /// Remove the directive header from `text`, returning the directives it held
+    pub(crate) fn take(text: &mut Text<'_>) -> Option<Self> {
+        let first = text.lines.first()?;
+        let line: String = first.spans.iter().map(|s| s.content.as_ref()).collect();
+        let directives = Self::parse(&line)?;
+        text.lines.remove(0);
+        Some(directives)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_offset_and_wrap() {
+        assert_eq!(
+            PreviewDirectives::parse("#skim: offset=42; wrap=off"),
+            Some(PreviewDirectives {
+                offset: Some(42),
+                wrap: Some(false),
+            })
+        );
+        assert_eq!(
+            PreviewDirectives::parse("#skim:wrap=on;offset=+3;color=red"),
+            Some(PreviewDirectives {
+                offset: Some(3),
+                wrap: Some(true),
+            })
+        );
+    }
+
+    #[test]
+    fn ignores_other_first_lines() {
+        assert_eq!(PreviewDirectives::parse("# skim: offset=1"), None);
+        assert_eq!(PreviewDirectives::parse("fn main() {}"), None);
+    }
+
+    #[test]
+    fn take_removes_the_header_line() {
+        let mut text = Text::from("#skim: offset=2\nfirst\nsecond");
+        let directives = PreviewDirectives::take(&mut text);
+        assert_eq!(directives.and_then(|d| d.offset), Some(2));
+        assert_eq!(text.lines.len(), 2);
+
+        let mut text = Text::from("first\n#skim: offset=2");
+        assert_eq!(PreviewDirectives::take(&mut text), None);
+        assert_eq!(text.lines.len(), 2);
+    }
+}
@@ -5,7 +5,13 @@ use ratatui::layout::Size;
 #[cfg(feature = "image")]
 use ratatui_image::picker::Picker;
 
-use super::Preview;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::text::Text;
+use ratatui::widgets::Paragraph;
+
+use super::{Preview, PreviewContent, PreviewOutput};
+use crate::tui::SkimWidget;
 
 #[cfg(feature = "image")]
 fn image(width: u32, height: u32) -> DynamicImage {
@@ -120,6 +126,27 @@ fn mark_ready_clears_loading() {
     assert!(!p.is_loading());
 }
 
+#[test]
+fn apply_directives_strips_the_header_of_command_output() {
+    let mut p = Preview::default();
+    p.wrap = true;
+    p.content(b"#skim: offset=3; wrap=off\na\nb\nc\nd\n").unwrap();
+    // Only the output of preview commands is parsed
+    p.apply_directives();
+    assert_eq!(p.total_lines, 5);
+
+    p.directives = true;
+    p.apply_directives();
+    assert_eq!(p.total_lines, 4);
+    assert_eq!(p.scroll_y, 2);
+    assert!(!p.wrapped());
+
+    p.toggle_wrap();
+    assert!(p.wrap && p.wrapped());
+    p.content(b"e\n").unwrap();
+    assert!(p.wrapped());
+}
+
 #[test]
 fn content_with_position_applies_offsets() {
     use crate::PreviewPosition;
@@ -178,3 +205,89 @@ fn set_image_picker_sets_and_clears() {
     p.set_image_picker(None);
     assert!(p.image_picker.is_none());
 }
+
+#[test]
+fn output_lines_are_parsed_as_ansi() {
+    let mut p = Preview::default();
+    p.output(PreviewOutput::Lines(vec!["\x1b[31mred\x1b[0m".into(), "plain".into()]))
+        .unwrap();
+    assert_eq!(p.total_lines, 2);
+    let content = p.content.read().unwrap();
+    let PreviewContent::Text(text) = &*content else {
+        panic!("expected text content");
+    };
+    assert_eq!(text.lines[0].to_string(), "red");
+}
+
+#[test]
+fn output_text_is_kept_verbatim() {
+    let mut p = Preview::default();
+    p.scroll_y = 3;
+    p.output(PreviewOutput::Text(Text::from("\x1b[31m\nb\nc"))).unwrap();
+    assert_eq!(p.total_lines, 3);
+    assert_eq!(p.scroll_y, 0);
+    let content = p.content.read().unwrap();
+    let PreviewContent::Text(text) = &*content else {
+        panic!("expected text content");
+    };
+    assert_eq!(text.lines[0].to_string(), "\x1b[31m");
+}
+
+#[test]
+fn output_widget_is_rendered_inside_the_border() {
+    let mut p = Preview::default();
+    p.output(PreviewOutput::Widget(Box::new(Paragraph::new("widget"))))
+        .unwrap();
+    assert_eq!(p.total_lines, 0);
+
+    let area = Rect::new(0, 0, 10, 3);
+    let mut buf = Buffer::empty(area);
+    p.render(area, &mut buf);
+    let rows: Vec<String> = (0..area.height)
+        .map(|y| (0..area.width).map(|x| buf[(x, y)].symbol()).collect())
+        .collect();
+    assert_eq!(rows[0], "│widget   ");
+}
+
+#[test]
+fn size_placeholders_expand_to_the_inner_size() {
+    let mut p = Preview::default();
+    p.set_area(Rect::new(0, 0, 40, 12));
+    // the default preview on the right only has a border on its left
+    assert_eq!(p.inner_size(), (12, 39));
+    assert!(super::uses_size("bat --line-range :{lines} {}"));
+    assert!(!super::uses_size("cat {}"));
+    assert_eq!(
+        super::expand_size("chafa -s {columns}x{lines} {}", p.inner_size()),
+        "chafa -s 39x12 {}"
+    );
+}
+
+#[test]
+fn commands_get_the_size_and_position_of_the_text() {
+    let mut p = Preview::default();
+    p.set_area(Rect::new(50, 2, 40, 12));
+    let env = p.size_env();
+    let var = |key: &str| env.iter().find(|(k, _)| *k == key).map(|(_, v)| v.as_str());
+    assert_eq!(var("LINES"), Some("12"));
+    assert_eq!(var("COLUMNS"), Some("39"));
+    assert_eq!(var("SKIM_PREVIEW_TOP"), Some("2"));
+    assert_eq!(var("FZF_PREVIEW_LEFT"), Some("51"));
+}
+
+#[test]
+fn callbacks_called_directly_return_lines() {
+    use super::PreviewCallback;
+
+    let lines = PreviewCallback::from(|_: Vec<std::sync::Arc<dyn crate::SkimItem>>| vec!["a".to_string()]);
+    assert_eq!(lines(Vec::new()), vec!["a"]);
+    assert!(matches!(lines.preview(Vec::new()), PreviewOutput::Lines(l) if l == ["a"]));
+
+    let text = PreviewCallback::text(|_| Text::from("b\nc"));
+    assert_eq!(text(Vec::new()), vec!["b", "c"]);
+    assert!(matches!(text.preview(Vec::new()), PreviewOutput::Text(_)));
+
+    let widget = PreviewCallback::widget(|_| Paragraph::new("d"));
+    assert_eq!(widget(Vec::new()), Vec::<String>::new());
+    assert!(matches!(widget.preview(Vec::new()), PreviewOutput::Widget(_)));
+}
@@ -0,0 +1,88 @@
+//! Pane showing the stderr of the source and preview commands, toggled by `toggle-stderr`
+use std::sync::Arc;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Widget};
+
+use crate::SkimOptions;
+use crate::reader::StderrLog;
+use crate::theme::ColorTheme;
+use crate::tui::widget::{SkimRender, SkimWidget};
+
+/// Most lines of the log shown at once
+const PANE_LINES: u16 = 8;
+
+/// Widget showing the last lines of the [`StderrLog`], under a border
+pub struct StderrPane {
+    /// Lines written to stderr by the commands
+    pub(crate) log: StderrLog,
+    /// Whether the pane takes part of the item list, see `toggle-stderr`
+    pub(crate) visible: bool,
+    theme: Arc<ColorTheme>,
+}
+
+impl StderrPane {
+    /// Rows taken by the pane when visible, borders included, at most half of `available`
+    #[must_use]
+    pub(crate) fn height(&self, available: u16) -> u16 {
+        let lines = u16::try_from(self.log.len()).unwrap_or(u16::MAX).clamp(1, PANE_LINES);
+        (lines + 2).min(available / 2)
+    }
+}
+
+impl SkimWidget for StderrPane {
+    fn from_options(_options: &SkimOptions, theme: Arc<ColorTheme>) -> Self {
+        Self {
+            log: StderrLog::default(),
+            visible: false,
+            theme,
+        }
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) -> SkimRender {
+        let block = Block::bordered()
+            .border_style(self.theme.border)
+            .title(Line::styled(" stderr ", self.theme.info));
+        let rows = usize::from(block.inner(area).height);
+        let lines: Vec<Line> = self.log.last(rows).into_iter().map(Line::from).collect();
+        Paragraph::new(lines)
+            .style(self.theme.normal)
+            .block(block)
+            .render(area, buf);
+        SkimRender::default()
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_the_last_lines_of_the_log() {
+        let mut pane = StderrPane::_default();
+        for line in ["one", "two", "three"] {
+            pane.log.push("source", line);
+        }
+        assert_eq!(pane.height(20), 5);
+        assert_eq!(pane.height(6), 3);
+
+        let area = Rect::new(0, 0, 24, 4);
+        let mut buf = Buffer::empty(area);
+        pane.render(area, &mut buf);
+        let rows: Vec<String> = (0..area.height)
+            .map(|y| (0..area.width).map(|x| buf[(x, y)].symbol()).collect())
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                "┌ stderr ──────────────┐",
+                "│[source] two          │",
+                "│[source] three        │",
+                "└──────────────────────┘",
+            ]
+        );
+    }
+}
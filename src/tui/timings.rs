@@ -0,0 +1,134 @@
+//! Timings of the reader, the matcher and the renders, for performance debugging
+//!
+//! The reader, each matcher run and each render run in a span of the [`TARGET`] target, logged
+//! with its duration when it closes, e.g. with `SKIM_LOG=skim::timings=debug sk --log-file sk.log`.
+//! The last timings are kept here for the hidden `--debug-overlay`.
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Clear, Paragraph, Widget};
+
+use crate::theme::ColorTheme;
+
+/// Target of the spans of the reader (debug), the matcher (debug) and the renders (trace)
+pub(crate) const TARGET: &str = "skim::timings";
+
+/// Frames kept for the render statistics
+const FRAMES: usize = 60;
+
+/// Last timings of the reader, the matcher and the renders
+#[derive(Debug)]
+pub(crate) struct Timings {
+    /// Durations of the last renders, the most recent last
+    frames: VecDeque<Duration>,
+    /// When the reader started reading the command, until it is done
+    read_start: Option<Instant>,
+    /// Duration of the last complete read, and the items read
+    last_read: Option<(Duration, usize)>,
+    /// Duration of the last complete matcher run, and the items it went through
+    last_match: Option<(Duration, usize)>,
+}
+
+impl Default for Timings {
+    fn default() -> Self {
+        Self {
+            frames: VecDeque::with_capacity(FRAMES),
+            read_start: None,
+            last_read: None,
+            last_match: None,
+        }
+    }
+}
+
+impl Timings {
+    /// Records a render
+    pub(crate) fn frame(&mut self, took: Duration) {
+        if self.frames.len() == FRAMES {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(took);
+    }
+
+    /// Records the start of a read of the command
+    pub(crate) fn read_started(&mut self) {
+        self.read_start = Some(Instant::now());
+    }
+
+    /// Records the end of the read started last
+    pub(crate) fn read_done(&mut self, items: usize) {
+        if let Some(start) = self.read_start.take() {
+            self.last_read = Some((start.elapsed(), items));
+        }
+    }
+
+    /// Records a complete matcher run
+    pub(crate) fn matched(&mut self, took: Duration, items: usize) {
+        self.last_match = Some((took, items));
+    }
+
+    /// The lines of the overlay
+    fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::with_capacity(3);
+        if let Some(max) = self.frames.iter().max() {
+            let total: Duration = self.frames.iter().sum();
+            let avg = total / u32::try_from(self.frames.len()).unwrap_or(u32::MAX);
+            lines.push(format!("render {avg:.1?} avg, {max:.1?} max"));
+        }
+        if let Some((took, items)) = self.last_match {
+            lines.push(format!("match  {took:.1?} / {items} items"));
+        }
+        match (self.read_start, self.last_read) {
+            (Some(start), _) => lines.push(format!("read   {:.1?}…", start.elapsed())),
+            (None, Some((took, items))) => lines.push(format!("read   {took:.1?} / {items} items")),
+            (None, None) => {}
+        }
+        lines
+    }
+
+    /// Draws the timings in a box at the top right corner of `area`
+    pub(crate) fn render_overlay(&self, area: Rect, buf: &mut Buffer, theme: &ColorTheme) {
+        let lines = self.lines();
+        let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0) + 2;
+        let width = u16::try_from(width).unwrap_or(u16::MAX).min(area.width);
+        let height = u16::try_from(lines.len() + 2).unwrap_or(u16::MAX).min(area.height);
+        let overlay = Rect {
+            x: area.right() - width,
+            y: area.y,
+            width,
+            height,
+        };
+        Clear.render(overlay, buf);
+        Paragraph::new(lines.into_iter().map(Line::from).collect::<Vec<_>>())
+            .style(theme.info)
+            .block(Block::bordered().border_style(theme.border))
+            .render(overlay, buf);
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_last_frames_and_runs() {
+        let mut timings = Timings::default();
+        assert_eq!(timings.lines(), Vec::<String>::new());
+        for ms in 1..=FRAMES as u64 + 10 {
+            timings.frame(Duration::from_millis(ms));
+        }
+        timings.matched(Duration::from_millis(5), 1000);
+        timings.read_started();
+        timings.read_done(1000);
+        // a read without start is not recorded
+        timings.read_done(1);
+
+        let lines = timings.lines();
+        assert_eq!(lines[0], "render 40.5ms avg, 70.0ms max");
+        assert_eq!(lines[1], "match  5.0ms / 1000 items");
+        assert!(lines[2].ends_with("/ 1000 items"), "{}", lines[2]);
+    }
+}
@@ -0,0 +1,265 @@
+//! Tree layout of path-like items for `--tree`
+//!
+//! The matched items are laid out as a tree of `/`-separated paths, each row wrapping its
+//! item in a [`TreeItem`] that indents it and shows only the last component of its path. The
+//! ancestors of the matched items are shown too, as rows of their own, so every match sits
+//! under its directories. Siblings are kept in the order of their best match.
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use ratatui::text::{Line, Span};
+
+use crate::helper::item::{project_visible_text, retain_visible_spans};
+use crate::item::{MatchedItem, RankBuilder};
+use crate::{DisplayContext, ItemPreview, PreviewContext, Rank, SkimItem};
+
+/// A row of the tree, wrapping the matched item (or the ancestor) it shows
+pub(crate) struct TreeItem {
+    /// The matched item, or a bare path for the ancestors that did not match
+    source: MatchedItem,
+    /// The directory part of the path, hidden from display
+    hidden: [(usize, usize); 1],
+    /// Indentation and expansion marker
+    prefix: String,
+    has_children: bool,
+}
+
+impl TreeItem {
+    /// The path of the node, without its trailing `/`
+    pub(crate) fn path(&self) -> Cow<'_, str> {
+        match self.source.item.text() {
+            Cow::Borrowed(text) => Cow::Borrowed(node_path(text)),
+            Cow::Owned(text) => Cow::Owned(node_path(&text).to_owned()),
+        }
+    }
+
+    pub(crate) fn has_children(&self) -> bool {
+        self.has_children
+    }
+
+    /// The matched item shown by `item`, for the rows of a tree
+    #[must_use]
+    pub(crate) fn unwrap(item: MatchedItem) -> MatchedItem {
+        match item.item.as_any().downcast_ref::<TreeItem>() {
+            Some(tree_item) => tree_item.source.clone(),
+            None => item,
+        }
+    }
+}
+
+impl SkimItem for TreeItem {
+    fn text(&self) -> Cow<'_, str> {
+        self.source.item.text()
+    }
+
+    fn display(&self, context: DisplayContext) -> Line<'_> {
+        let base_style = context.base_style;
+        let line = self.source.item.display(context);
+        let mut spans = vec![Span::styled(self.prefix.clone(), base_style)];
+        if self.source.item.hidden_ranges().is_none() && self.hidden[0].1 > 0 {
+            let (_, map) = project_visible_text(&self.source.item.text(), &self.hidden);
+            spans.extend(retain_visible_spans(line.spans, &map));
+        } else {
+            spans.extend(line.spans);
+        }
+        Line::from(spans)
+    }
+
+    fn preview(&self, context: PreviewContext) -> ItemPreview {
+        self.source.item.preview(context)
+    }
+
+    fn output(&self) -> Cow<'_, str> {
+        self.source.item.output()
+    }
+
+    fn get_matching_ranges(&self) -> Option<&[(usize, usize)]> {
+        self.source.item.get_matching_ranges()
+    }
+
+    fn hidden_ranges(&self) -> Option<&[(usize, usize)]> {
+        // items hiding fields of their own are shown whole
+        self.source
+            .item
+            .hidden_ranges()
+            .or((self.hidden[0].1 > 0).then_some(&self.hidden[..]))
+    }
+
+    fn external_ranges(&self) -> Option<&[(usize, usize)]> {
+        self.source.item.external_ranges()
+    }
+
+    fn annotation(&self) -> Option<&str> {
+        self.source.item.annotation()
+    }
+
+    fn disabled(&self) -> bool {
+        self.source.item.disabled()
+    }
+}
+
+/// `path` without its trailing `/`, unless it is the root
+fn node_path(path: &str) -> &str {
+    match path.trim_end_matches('/') {
+        "" => path,
+        trimmed => trimmed,
+    }
+}
+
+/// The parent directory of `path`, if it has one
+pub(crate) fn parent_path(path: &str) -> Option<&str> {
+    match path.rfind('/')? {
+        0 => None,
+        slash => Some(&path[..slash]),
+    }
+}
+
+struct Node {
+    path: String,
+    item: Option<MatchedItem>,
+    children: Vec<usize>,
+}
+
+/// Lays out `items`, in rank order, as a tree, leaving out the descendants of `collapsed` paths
+pub(crate) fn layout(items: &[MatchedItem], collapsed: &HashSet<String>) -> Vec<MatchedItem> {
+    let mut nodes: Vec<Node> = Vec::new();
+    let mut by_path: HashMap<String, usize> = HashMap::new();
+    let mut roots: Vec<usize> = Vec::new();
+
+    for item in items {
+        let text = item.item.text();
+        let path = node_path(&text);
+        let id = match by_path.get(path) {
+            // the same path listed twice shows up twice, as siblings
+            Some(&id) if nodes[id].item.is_some() => add_node(&mut nodes, &mut roots, &mut by_path, path, false),
+            Some(&id) => id,
+            None => add_node(&mut nodes, &mut roots, &mut by_path, path, true),
+        };
+        nodes[id].item = Some(item.clone());
+    }
+
+    let mut rows = Vec::with_capacity(nodes.len());
+    let mut stack: Vec<(usize, usize)> = roots.iter().rev().map(|&id| (id, 0)).collect();
+    while let Some((id, depth)) = stack.pop() {
+        let node = &nodes[id];
+        let is_collapsed = collapsed.contains(&node.path);
+        rows.push(row(node, depth, is_collapsed));
+        if !is_collapsed {
+            stack.extend(node.children.iter().rev().map(|&child| (child, depth + 1)));
+        }
+    }
+    rows
+}
+
+/// Adds the node of `path`, and of its ancestors missing from `by_path`
+fn add_node(
+    nodes: &mut Vec<Node>,
+    roots: &mut Vec<usize>,
+    by_path: &mut HashMap<String, usize>,
+    path: &str,
+    index: bool,
+) -> usize {
+    let parent = parent_path(path).map(|parent| match by_path.get(parent) {
+        Some(&id) => id,
+        None => add_node(nodes, roots, by_path, parent, true),
+    });
+    let id = nodes.len();
+    nodes.push(Node {
+        path: path.to_owned(),
+        item: None,
+        children: Vec::new(),
+    });
+    match parent {
+        Some(parent) => nodes[parent].children.push(id),
+        None => roots.push(id),
+    }
+    if index {
+        by_path.insert(path.to_owned(), id);
+    }
+    id
+}
+
+fn row(node: &Node, depth: usize, is_collapsed: bool) -> MatchedItem {
+    let source = node.item.clone().unwrap_or_else(|| {
+        MatchedItem::new(
+            Arc::new(node.path.clone()),
+            Rank {
+                index: -1,
+                ..Rank::default()
+            },
+            None,
+            &RankBuilder::default(),
+        )
+    });
+    let has_children = !node.children.is_empty();
+    let marker = match (has_children, is_collapsed) {
+        (_, true) => "▸ ",
+        (true, false) => "▾ ",
+        (false, false) => "  ",
+    };
+    let name_start = parent_path(&node.path).map_or(0, |parent| parent.len() + 1);
+    let (rank, matched_range) = (source.rank, source.matched_range.clone());
+    let tree_item = TreeItem {
+        source,
+        hidden: [(0, name_start)],
+        prefix: format!("{}{marker}", "  ".repeat(depth)),
+        has_children: has_children || is_collapsed,
+    };
+    MatchedItem::new(Arc::new(tree_item), rank, matched_range, &RankBuilder::default())
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use super::*;
+
+    fn matched(text: &str, index: i32) -> MatchedItem {
+        MatchedItem::new(
+            Arc::new(text.to_owned()),
+            Rank {
+                index,
+                ..Rank::default()
+            },
+            None,
+            &RankBuilder::default(),
+        )
+    }
+
+    fn rows(items: &[MatchedItem]) -> Vec<String> {
+        items
+            .iter()
+            .map(|item| {
+                let line = item.item.display(DisplayContext::default());
+                line.spans.iter().map(|span| span.content.as_ref()).collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn layout_nests_paths_under_their_ancestors() {
+        let items = [
+            matched("src/tui/app.rs", 0),
+            matched("README.md", 1),
+            matched("src/lib.rs", 2),
+        ];
+        let tree = layout(&items, &HashSet::new());
+        assert_eq!(
+            rows(&tree),
+            ["▾ src", "  ▾ tui", "      app.rs", "    lib.rs", "  README.md"]
+        );
+        // ancestors that did not match are rows of their own
+        assert_eq!(tree[0].rank.index, -1);
+        assert_eq!(TreeItem::unwrap(tree[2].clone()).rank.index, 0);
+    }
+
+    #[test]
+    fn layout_hides_the_descendants_of_collapsed_paths() {
+        let items = [matched("src/", 0), matched("src/lib.rs", 1), matched("doc/a.md", 2)];
+        let collapsed = HashSet::from(["src".to_owned()]);
+        let tree = layout(&items, &collapsed);
+        assert_eq!(rows(&tree), ["▸ src/", "▾ doc", "    a.md"]);
+        assert_eq!(tree[0].item.text(), "src/");
+    }
+}
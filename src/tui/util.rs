@@ -9,7 +9,10 @@ use std::{
     os::fd::{AsFd, AsRawFd},
     os::unix::fs::OpenOptionsExt as _,
 };
-use unicode_display_width::is_double_width;
+use unicode_display_width::{is_double_width, width as display_width};
+use unicode_segmentation::UnicodeSegmentation;
+#[cfg(feature = "bidi")]
+use wezterm_bidi::{BidiClass, BidiContext, ParagraphDirectionHint, bidi_class_for_char};
 
 /// Clips a [`Line`] to at most `max_chars` characters, preserving per-span styles.
 ///
@@ -50,6 +53,91 @@ pub fn char_display_width(c: char) -> usize {
     1
 }
 
+/// Display width of a grapheme cluster: a base char with its combining marks takes one column,
+/// and an emoji sequence two, however many chars they are made of
+#[inline]
+pub(crate) fn grapheme_display_width(grapheme: &str) -> usize {
+    usize::try_from(display_width(grapheme)).unwrap_or(usize::MAX)
+}
+
+/// Whether `c` is a strong right-to-left char or a right-to-left control, the chars the bidi
+/// algorithm reorders text around
+#[cfg(feature = "bidi")]
+fn is_rtl(c: char) -> bool {
+    !c.is_ascii()
+        && matches!(
+            bidi_class_for_char(c),
+            BidiClass::RightToLeft
+                | BidiClass::ArabicLetter
+                | BidiClass::RightToLeftEmbedding
+                | BidiClass::RightToLeftOverride
+                | BidiClass::RightToLeftIsolate
+        )
+}
+
+/// The visual order of the chars of `line` under the Unicode bidi algorithm: the logical index
+/// of the char displayed at each position
+///
+/// The paragraph direction is that of the first strong char. `None` when the line has no
+/// right-to-left text, left as it is then.
+///
+/// wezterm-bidi rather than unicode-bidi, which does not apply rule L3: the combining marks of a
+/// right-to-left run would be put before their base char and drawn on the wrong letter.
+#[cfg(feature = "bidi")]
+pub(crate) fn bidi_visual_order(line: &Line<'_>) -> Option<Vec<usize>> {
+    if !line.spans.iter().any(|span| span.content.chars().any(is_rtl)) {
+        return None;
+    }
+    let chars: Vec<char> = line.spans.iter().flat_map(|span| span.content.chars()).collect();
+    let mut context = BidiContext::new();
+    // keep the combining marks after their base char in right-to-left runs
+    context.set_reorder_non_spacing_marks(true);
+    context.resolve_paragraph(&chars, ParagraphDirectionHint::AutoLeftToRight);
+    Some(context.reorder_line(0..chars.len()).1)
+}
+
+/// Without the `bidi` feature, lines are shown in logical order
+#[cfg(not(feature = "bidi"))]
+pub(crate) fn bidi_visual_order(_line: &Line<'_>) -> Option<Vec<usize>> {
+    None
+}
+
+/// Reorder the chars of `line` to `order` (see [`bidi_visual_order`]), each char keeping the
+/// style of its span so that match highlights stay on the chars they were put on
+pub(crate) fn reorder_line(line: &Line<'_>, order: &[usize]) -> Line<'static> {
+    let chars: Vec<(char, Style)> = line
+        .spans
+        .iter()
+        .flat_map(|span| span.content.chars().map(move |c| (c, span.style)))
+        .collect();
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    for &(c, style) in order.iter().filter_map(|&index| chars.get(index)) {
+        match spans.last_mut() {
+            Some(span) if span.style == style => span.content.to_mut().push(c),
+            _ => spans.push(Span::styled(c.to_string(), style)),
+        }
+    }
+    Line {
+        spans,
+        style: line.style,
+        alignment: line.alignment,
+    }
+}
+
+/// The visual char positions spanned by the logical chars `start..end`, given the `order` of
+/// [`bidi_visual_order`]
+pub(crate) fn visual_range(order: &[usize], start: usize, end: usize) -> (usize, usize) {
+    order
+        .iter()
+        .enumerate()
+        .filter(|&(_, logical)| (start..end).contains(logical))
+        .fold(None, |range, (visual, _)| match range {
+            None => Some((visual, visual + 1)),
+            Some((first, _)) => Some((first, visual + 1)),
+        })
+        .unwrap_or((start, end))
+}
+
 pub fn wrap_text(input: Text, width: usize) -> Text {
     if input.width() <= width {
         return input;
@@ -63,8 +151,9 @@ pub fn wrap_text(input: Text, width: usize) -> Text {
         for span in &input_line.spans {
             let mut curr = Span::default().style(span.style);
             let mut curr_content = String::new();
-            for c in span.content.chars() {
-                if w + char_display_width(c) > width {
+            for g in span.content.graphemes(true) {
+                let g_width = grapheme_display_width(g);
+                if w + g_width > width {
                     // Push current span and line before wrapping
                     if !curr_content.is_empty() {
                         curr.content = curr_content.into();
@@ -77,8 +166,8 @@ pub fn wrap_text(input: Text, width: usize) -> Text {
                     curr_content = String::new();
                     w = 0;
                 }
-                curr_content.push(c);
-                w += char_display_width(c);
+                curr_content.push_str(g);
+                w += g_width;
             }
             // Push remaining content in current span
             if !curr_content.is_empty() {
@@ -95,6 +184,63 @@ pub fn wrap_text(input: Text, width: usize) -> Text {
     output
 }
 
+/// Wraps `input` to `width` columns, marking continuation lines.
+///
+/// Every continuation line starts with a hanging indent matching the leading
+/// spaces of the line it continues, followed by `sign`. The indent is dropped,
+/// then the sign, when they would leave no room for the content. Spans are split
+/// on display width, so styles (e.g. from ANSI escapes) are preserved.
+pub(crate) fn wrap_text_with_sign(input: &Text<'_>, width: usize, sign: &Span<'_>) -> Text<'static> {
+    let width = width.max(1);
+    let sign_width: usize = sign.content.chars().map(char_display_width).sum();
+    let mut output = Text::default();
+
+    for input_line in &input.lines {
+        let text = input_line.to_string();
+        let mut indent = text.chars().take_while(|&c| c == ' ').count();
+        if indent + sign_width >= width {
+            indent = 0;
+        }
+        let mut prefix = Vec::new();
+        if indent > 0 {
+            prefix.push(Span::raw(" ".repeat(indent)));
+        }
+        if sign_width > 0 && sign_width < width {
+            prefix.push(Span::styled(sign.content.to_string(), sign.style));
+        }
+        let prefix_width = if prefix.is_empty() { 0 } else { indent + sign_width };
+
+        let mut current_line = Line::default().style(input_line.style);
+        let mut w = 0;
+        // Whether the current row holds any content yet: a char wider than the
+        // row is still placed on it rather than wrapping forever.
+        let mut row_used = false;
+        for span in &input_line.spans {
+            let mut curr_content = String::new();
+            for c in span.content.chars() {
+                let cw = char_display_width(c);
+                if w + cw > width && row_used {
+                    if !curr_content.is_empty() {
+                        current_line.push_span(Span::styled(std::mem::take(&mut curr_content), span.style));
+                    }
+                    let next = Line::from(prefix.clone()).style(input_line.style);
+                    output.push_line(std::mem::replace(&mut current_line, next));
+                    w = prefix_width;
+                }
+                curr_content.push(c);
+                w += cw;
+                row_used = true;
+            }
+            if !curr_content.is_empty() {
+                current_line.push_span(Span::styled(curr_content, span.style));
+            }
+        }
+        output.push_line(current_line);
+    }
+
+    output
+}
+
 /// Merges styles from right to left
 /// left has higher priority
 /// contrary to ratatui's `Style::patch`, this will override `Reset` with the new style if set
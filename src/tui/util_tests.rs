@@ -366,7 +366,7 @@ fn test_handle_osc_query_ignores_non_query() {
     let buf = SharedBuf::new();
     let mut writer: Box<dyn std::io::Write + Send> = Box::new(buf.clone());
     handle_osc_query(b"\x1b]0;just a title\x07", &mut writer);
-    assert!(buf.contents().is_empty());
+    assert_eq!(buf.contents(), []);
 }
 
 #[test]
@@ -397,7 +397,38 @@ fn test_handle_csi_query_non_query_returns_false() {
     let buf = SharedBuf::new();
     let mut writer: Box<dyn std::io::Write + Send> = Box::new(buf.clone());
     assert!(!handle_csi_query(b"\x1b[1;2H", &mut writer));
-    assert!(buf.contents().is_empty());
+    assert_eq!(buf.contents(), []);
+}
+
+#[test]
+fn test_wrap_text_with_sign_indents_continuations() {
+    let red = Style::default().fg(Color::Red);
+    let input = Text::from(vec![
+        Line::from(vec![Span::raw("  let "), Span::styled("abcdef", red)]),
+        Line::from("short"),
+    ]);
+    let result = wrap_text_with_sign(&input, 8, &Span::raw("> "));
+    let lines: Vec<String> = result.lines.iter().map(ToString::to_string).collect();
+    assert_eq!(lines, vec!["  let ab", "  > cdef", "short"]);
+    // The style of a span split across rows is kept on both sides.
+    assert_eq!(result.lines[0].spans[1].style, red);
+    assert_eq!(result.lines[1].spans[2].content, "cdef");
+    assert_eq!(result.lines[1].spans[2].style, red);
+}
+
+#[test]
+fn test_wrap_text_with_sign_drops_prefix_when_too_wide() {
+    // An indent that leaves no room for content is dropped, keeping the sign.
+    let input = Text::from("    abcdef");
+    let result = wrap_text_with_sign(&input, 4, &Span::raw(">"));
+    let lines: Vec<String> = result.lines.iter().map(ToString::to_string).collect();
+    assert_eq!(lines, vec!["    ", ">abc", ">def"]);
+
+    // Double-width chars wider than the remaining room still make progress.
+    let input = Text::from("日本");
+    let result = wrap_text_with_sign(&input, 1, &Span::raw(">"));
+    let lines: Vec<String> = result.lines.iter().map(ToString::to_string).collect();
+    assert_eq!(lines, vec!["日", "本"]);
 }
 
 #[test]
@@ -412,3 +443,49 @@ fn test_style_span_and_line() {
     style_line(&mut line, red);
     assert!(line.spans.iter().all(|s| s.style.fg == Some(Color::Red)));
 }
+
+#[test]
+fn test_bidi_leaves_left_to_right_lines_alone() {
+    assert!(bidi_visual_order(&Line::from("abc 日本 é")).is_none());
+}
+
+#[test]
+#[cfg(feature = "bidi")]
+fn test_bidi_reorders_right_to_left_runs() {
+    // "abc " then the Hebrew word שלום, stored first letter first
+    let line = Line::from("abc \u{5E9}\u{5DC}\u{5D5}\u{5DD}");
+    let order = bidi_visual_order(&line).unwrap();
+    assert_eq!(order, vec![0, 1, 2, 3, 7, 6, 5, 4]);
+    assert_eq!(
+        reorder_line(&line, &order).to_string(),
+        "abc \u{5DD}\u{5D5}\u{5DC}\u{5E9}"
+    );
+
+    // a line starting with Hebrew is a right-to-left paragraph
+    let order = bidi_visual_order(&Line::from("\u{5D0}\u{5D1} ab")).unwrap();
+    assert_eq!(order, vec![3, 4, 2, 1, 0]);
+}
+
+#[test]
+#[cfg(feature = "bidi")]
+fn test_bidi_keeps_combining_marks_after_their_base_char() {
+    // alef with a qamats point, then bet
+    let order = bidi_visual_order(&Line::from("\u{5D0}\u{5B8}\u{5D1}")).unwrap();
+    assert_eq!(order, vec![2, 0, 1]);
+}
+
+#[test]
+#[cfg(feature = "bidi")]
+fn test_bidi_styles_follow_their_chars() {
+    let red = Style::default().fg(Color::Red);
+    // the first Hebrew letter is highlighted, it is displayed last
+    let line = Line::from(vec![Span::styled("\u{5D0}", red), Span::raw("\u{5D1}\u{5D2}")]);
+    let order = bidi_visual_order(&line).unwrap();
+    let reordered = reorder_line(&line, &order);
+    assert_eq!(reordered.spans.len(), 2);
+    assert_eq!(reordered.spans[0].content, "\u{5D2}\u{5D1}");
+    assert_eq!(reordered.spans[1].content, "\u{5D0}");
+    assert_eq!(reordered.spans[1].style, red);
+    assert_eq!(visual_range(&order, 0, 1), (2, 3));
+    assert_eq!(visual_range(&order, 0, 0), (0, 0));
+}
@@ -1,6 +1,8 @@
 use crate::field::{FieldRange, get_string_by_field};
 use crate::helper::item::strip_ansi;
+use crate::interpreter::Interpreter;
 use crate::item::MatchedItem;
+use crate::placeholder::PlaceholderError;
 use regex::Regex;
 use std::fmt::Write as _;
 use std::fs::File;
@@ -78,6 +80,19 @@ pub fn read_file_lines(filename: &str) -> std::result::Result<Vec<String>, std::
     BufReader::new(file).lines().collect()
 }
 
+/// Run `cmd` through `shell` and return the lines it printed
+pub fn read_cmd_lines(cmd: &str, shell: &Interpreter) -> std::result::Result<Vec<String>, std::io::Error> {
+    let output = shell
+        .command(cmd)
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        return Err(std::io::Error::other(format!("`{cmd}` failed: {}", output.status)));
+    }
+    output.stdout.as_slice().lines().collect()
+}
+
 /// Replace the fields in `pattern` with the items, expanding {...} patterns
 ///
 /// Replaces:
@@ -86,8 +101,12 @@ pub fn read_file_lines(filename: &str) -> std::result::Result<Vec<String>, std::
 /// - `{+}` -> all selected items (multi-select)
 /// - `{q}` -> current query
 /// - `{cq}` -> current command query
+/// - `{ln}`, `{off}` -> line number and byte offset of the current item in its file
+///
+/// See [`placeholder::expand`](crate::placeholder::expand) for a typed alternative.
+///
+/// Arguments are quoted for the shell set by `$SKIM_SHELL` when `quote_args` is set.
 #[allow(clippy::too_many_arguments)]
-#[allow(clippy::too_many_lines)]
 pub fn printf<'a>(
     pattern: &str,
     delimiter: &Regex,
@@ -96,26 +115,54 @@ pub fn printf<'a>(
     current: &Option<MatchedItem>,
     query: &str,
     command_query: &str,
-    mut quote_args: bool,
+    quote_args: bool,
 ) -> String {
-    // Windows uses different shell quoting conventions (double quotes, caret escaping)
-    // that are incompatible with the Unix-style single-quote escaping implemented here.
-    if cfg!(windows) {
-        quote_args = false;
-    }
+    let shell = quote_args.then(Interpreter::from_env);
+    printf_for_shell(
+        pattern,
+        delimiter,
+        replstr,
+        selected,
+        current.as_ref(),
+        query,
+        command_query,
+        shell.as_ref(),
+    )
+    .0
+}
+
+/// Same as [`printf`], quoting the arguments for `shell` if set
+///
+/// Placeholders that cannot be expanded are kept as they are; the first one is
+/// returned alongside the expansion.
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::too_many_lines)]
+pub(crate) fn printf_for_shell<'a>(
+    pattern: &str,
+    delimiter: &Regex,
+    replstr: &str,
+    selected: &(impl Iterator<Item = &'a MatchedItem> + std::clone::Clone),
+    current: Option<&MatchedItem>,
+    query: &str,
+    command_query: &str,
+    shell: Option<&Interpreter>,
+) -> (String, Option<PlaceholderError>) {
+    let quote_args = shell.is_some();
     let escape_arg = |s: &str, quote: bool| {
-        let mut res = s.replace('\0', "\\0").clone();
-        if quote && quote_args {
-            res = format!("'{}'", res.replace('\'', "'\\''"));
+        let res = s.replace('\0', "\\0");
+        match shell {
+            Some(shell) if quote => shell.quote(&res),
+            _ => res,
         }
-        res
     };
 
-    let item_text = current.as_ref().map(|s| strip_ansi(&s.output()).0).unwrap_or_default();
+    let item_text = current.map(|s| strip_ansi(&s.output()).0).unwrap_or_default();
     let escaped_item = escape_arg(&item_text, true);
     let escaped_query = escape_arg(query, true);
     let escaped_cmd_query = escape_arg(command_query, true);
 
+    let mut error = None;
+
     // Split on replstr first
     let replstr_parts = pattern.split(replstr);
     let mut replaced_parts = Vec::new();
@@ -135,15 +182,21 @@ pub fn printf<'a>(
                             "" => replaced.push_str("{}"),
                             "q" => replaced.push_str(&escaped_query),
                             "cq" => replaced.push_str(&escaped_cmd_query),
-                            "n" if current.as_ref().is_some() => {
-                                replaced.push_str(
-                                    current
-                                        .as_ref()
-                                        .map(|i| i.rank.index)
-                                        .unwrap_or_default()
-                                        .to_string()
-                                        .as_str(),
-                                );
+                            "raw" => replaced.push_str(&escape_arg(&item_text, false)),
+                            "n" if current.is_some() => {
+                                replaced
+                                    .push_str(current.map(|i| i.rank.index).unwrap_or_default().to_string().as_str());
+                            }
+                            // empty for an item without a position in a file
+                            "ln" | "off" if current.is_some() => {
+                                if let Some(position) = current.and_then(|i| i.source_position()) {
+                                    let value = if content == "ln" {
+                                        position.line
+                                    } else {
+                                        position.offset
+                                    };
+                                    let _ = write!(replaced, "{value}");
+                                }
                             }
                             s if s == "+n" || s.starts_with("+n:") || s == "+" || s.starts_with("+:") => {
                                 let is_n = s.starts_with("+n");
@@ -169,13 +222,12 @@ pub fn printf<'a>(
                                     .unwrap_or_default();
                                 if expanded.is_empty() {
                                     expanded = current
-                                        .as_ref()
                                         .map(|i| escape_arg(&accessor(i), quote_args))
                                         .unwrap_or_default();
                                 }
 
                                 if quote_args && !quote_individually {
-                                    let _ = write!(replaced, "'{expanded}'");
+                                    replaced.push_str(&escape_arg(&expanded, true));
                                 } else {
                                     replaced.push_str(&expanded);
                                 }
@@ -206,20 +258,21 @@ pub fn printf<'a>(
                                             .unwrap_or_default();
 
                                         if quote_args && !quote_individually {
-                                            let _ = write!(replaced, "'{expanded}'");
+                                            replaced.push_str(&escape_arg(&expanded, true));
                                         } else {
                                             replaced.push_str(&expanded);
                                         }
                                     } else {
-                                        log::warn!("Failed to build multi-item field range from {content}");
+                                        warn!("Failed to build multi-item field range from {content}");
                                         let _ = write!(replaced, "{{{s}}}");
+                                        error.get_or_insert_with(|| PlaceholderError::InvalidFieldRange(s.to_string()));
                                     }
                                 } else if let Some(range) = FieldRange::from_str(stripped) {
                                     let replacement =
                                         get_string_by_field(delimiter, &item_text, &range).unwrap_or_default();
                                     replaced.push_str(&escape_arg(replacement, true));
                                 } else {
-                                    log::warn!("Failed to build field range from {content}");
+                                    warn!("Failed to build field range from {content}");
                                     let _ = write!(replaced, "{{{s}}}");
                                 }
                             }
@@ -230,22 +283,24 @@ pub fn printf<'a>(
                     } else {
                         content.push(c);
                     }
-                } else if c == '{' {
-                    inside = true;
                 } else {
                     replaced.push(c);
                 }
             }
-            // }
+            if inside {
+                let _ = write!(replaced, "{{{content}");
+                error.get_or_insert(PlaceholderError::Unterminated(content));
+            }
         }
         replaced_parts.push(replaced);
     }
 
     // Join back the replstr parts into the res
-    replaced_parts
+    let expanded = replaced_parts
         .into_iter()
-        .reduce(|a: String, b| a + &escaped_item + &b)
-        .unwrap_or_default()
+        .reduce(|a: String, b| a + escaped_item.as_str() + b.as_str())
+        .unwrap_or_default();
+    (expanded, error)
 }
 
 #[cfg(test)]
@@ -63,9 +63,9 @@ fn test_printf() {
             true
         ),
         if cfg!(unix) {
-            "[1] 'item 2' [2] 'item 2' [3] '2' [4] 'item 1' 'item 2' 'item 3' 'item 4' [5] 'query' [6] 'cmd query' [7] 'item 1, item 2, item 3, item 4' [8] '0','0','0','0'"
+            r"[1] 'item 2' [2] 'item 2' [3] '2' [4] 'item 1' 'item 2' 'item 3' 'item 4' [5] 'query' [6] 'cmd query' [7] 'item 1, item 2, item 3, item 4' [8] '0'\'','\''0'\'','\''0'\'','\''0'"
         } else {
-            "[1] item 2 [2] item 2 [3] 2 [4] item 1 item 2 item 3 item 4 [5] query [6] cmd query [7] item 1, item 2, item 3, item 4 [8] 0','0','0','0"
+            r#"[1] ^"item 2^" [2] ^"item 2^" [3] ^"2^" [4] ^"item 1^" ^"item 2^" ^"item 3^" ^"item 4^" [5] ^"query^" [6] ^"cmd query^" [7] ^"item 1, item 2, item 3, item 4^" [8] ^"0','0','0','0^""#
         }
     );
 }
@@ -82,7 +82,7 @@ fn test_printf_plus() {
             "cq",
             true
         ),
-        if cfg!(unix) { "'1' '2'" } else { "1 2" }
+        if cfg!(unix) { "'1' '2'" } else { r#"^"1^" ^"2^""# }
     );
     assert_eq!(
         printf(
@@ -95,7 +95,7 @@ fn test_printf_plus() {
             "cq",
             true
         ),
-        if cfg!(unix) { "'1'" } else { "1" }
+        if cfg!(unix) { "'1'" } else { r#"^"1^""# }
     );
 }
 #[test]
@@ -167,6 +167,35 @@ fn test_printf_index_no_current() {
     );
 }
 
+/// `{ln}` and `{off}` expand to the position of the current item in its file, or to nothing.
+#[test]
+fn test_printf_source_position() {
+    struct Line;
+    impl crate::SkimItem for Line {
+        fn text(&self) -> std::borrow::Cow<'_, str> {
+            "b".into()
+        }
+        fn source_position(&self) -> Option<crate::SourcePosition> {
+            Some(crate::SourcePosition { line: 2, offset: 2 })
+        }
+    }
+    let expand = |current: MatchedItem| {
+        printf(
+            "bat -H {ln} -r {ln}: {off}",
+            &Regex::new(" ").unwrap(),
+            "{}",
+            &[].iter(),
+            &Some(current),
+            "q",
+            "cq",
+            false,
+        )
+    };
+    let line = MatchedItem::new(Arc::new(Line), Rank::default(), None, &RankBuilder::default());
+    assert_eq!(expand(line), "bat -H 2 -r 2: 2");
+    assert_eq!(expand(make_item("a")), "bat -H  -r : ");
+}
+
 /// `{+n}` joins all selected indices; `{+n:,}` uses an explicit delimiter.
 #[test]
 fn test_printf_plus_index() {
@@ -223,6 +252,29 @@ fn test_printf_plus_field_range() {
     );
 }
 
+/// `{+:DELIM}` and `{+FIELD:DELIM}` quote the joined items as a single word for the shell.
+#[test]
+fn test_printf_plus_delimiter_quotes_for_the_shell() {
+    let items = [make_item("it's a"), make_item("b")];
+    let expand = |pattern, shell: &str| {
+        printf_for_shell(
+            pattern,
+            &Regex::new(" ").unwrap(),
+            "{}",
+            &items.iter(),
+            Some(&items[0]),
+            "q",
+            "cq",
+            Some(&Interpreter::new(shell)),
+        )
+        .0
+    };
+    assert_eq!(expand("{+:,}", "sh"), r"'it'\''s a,b'");
+    assert_eq!(expand("{+:,}", "pwsh"), "'it''s a,b'");
+    assert_eq!(expand("{+1:,}", "pwsh"), "'it''s,b'");
+    assert_eq!(expand("{+:&}", "cmd"), r#"^"it's a^&b^""#);
+}
+
 /// An unparsable `{+FIELD}` range is left verbatim and logged.
 #[test]
 fn test_printf_plus_invalid_field() {
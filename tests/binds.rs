@@ -184,6 +184,20 @@ insta_test!(bind_set_preview_cmd, ["a", "b", "c"], &["--preview", "echo initial
     @snap;
 });
 
+insta_test!(bind_preview_override, ["a", "b", "c"], &["--preview", "echo initial {}", "--bind", "ctrl-a:preview(echo other {})"], {
+    @snap;
+    @ctrl 'a';
+    @snap;
+    @key Up;
+    @snap;
+});
+
+insta_test!(bind_change_preview_label, ["a", "b", "c"], &["--preview", "echo {}", "--preview-label", " diff ", "--bind", "ctrl-a:change-preview-label( item {} )"], {
+    @snap;
+    @ctrl 'a';
+    @snap;
+});
+
 insta_test!(bind_set_header_from_empty, ["a", "b", "c"], &["--bind", "ctrl-a:set-header(foo)"], {
     @snap;
     @ctrl 'a';
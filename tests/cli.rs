@@ -93,6 +93,24 @@ fn filter_mode_empty_query_matches_all() {
     assert!(stdout.contains("three"));
 }
 
+#[test]
+fn max_item_length_truncates_input() {
+    let (code, stdout, _) = run_sk("abcdef\\nab", "-f '' --max-item-length 3");
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout, "abc…\nab\n");
+}
+
+#[test]
+fn annotation_sep_keeps_annotations_out_of_matching_and_output() {
+    let (code, stdout, _) = run_sk("apple\x1fred\\nberry\x1fapple pie", "-f apple --annotation-sep '\\x1f'");
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout, "apple\n");
+
+    let (code, stdout, _) = run_sk("apple\x1fred", "-f apple --annotation-sep '\\x1f' --print-annotation");
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout, "apple\x1fred\n");
+}
+
 #[test]
 fn filter_mode_with_print_query() {
     // --print-query prepends the query line to the output.
@@ -102,6 +120,52 @@ fn filter_mode_with_print_query() {
     assert_eq!(lines.next(), Some("a"));
 }
 
+#[test]
+fn filter_mode_with_print_positions() {
+    // Byte offsets: the two-byte `é` shifts `b` to offset 4.
+    let (code, stdout, _) = run_sk("xaéb\\nzzz", "-f ab --print-positions");
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout, "xaéb\n1,4\n");
+}
+
+#[test]
+fn filter_mode_with_field_scopes() {
+    let (code, stdout, _) = run_sk_argv(
+        "src/foo.rs\\nsrc/foo.tmp\\nfoo/bar.rs",
+        &["-d", "[/.]", "--field-names", "dir,name,ext", "-f", "name:foo !ext:tmp"],
+        &[],
+    );
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout, "src/foo.rs\n");
+}
+
+#[test]
+fn filter_mode_with_no_extended() {
+    let (code, stdout, _) = run_sk("a b\\n!ab", "-f '!a' --no-extended");
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout, "!ab\n");
+}
+
+#[test]
+fn filter_mode_with_typo_tolerant_term() {
+    let (_, stdout, _) = run_sk("hello world\\nabc", "-f helxlo");
+    assert_eq!(stdout, "");
+    let (code, stdout, _) = run_sk("hello world\\nabc", "-f helxlo~");
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout, "hello world\n");
+    let (_, stdout, _) = run_sk("hello world\\nabc", "-f helxlo --fuzzy-typos");
+    assert_eq!(stdout, "hello world\n");
+}
+
+#[test]
+fn filter_mode_ignores_accents_by_default() {
+    let (code, stdout, _) = run_sk("café\\nŁódź\\ntea", "-f lodz");
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout, "Łódź\n");
+    let (_, stdout, _) = run_sk("café\\ncafe", "-f cafe --no-normalize");
+    assert_eq!(stdout, "cafe\n");
+}
+
 #[test]
 fn filter_mode_with_print0() {
     // --print0 separates output records with NUL instead of newline.
@@ -128,6 +192,48 @@ fn filter_mode_no_sort_preserves_input_order() {
     );
 }
 
+#[test]
+fn filter_mode_prints_in_input_order_unless_filter_sort() {
+    let (code, stdout, _) = run_sk("xab\\nab\\naxxb\\nzz", "-f ab");
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout, "xab\nab\naxxb\n");
+    let (_, stdout, _) = run_sk("xab\\nab\\naxxb\\nzz", "-f ab --filter-sort");
+    assert_eq!(stdout.lines().next(), Some("ab"));
+    // --no-sort wins
+    let (_, stdout, _) = run_sk("xab\\nab\\naxxb\\nzz", "-f ab --filter-sort --no-sort");
+    assert_eq!(stdout, "xab\nab\naxxb\n");
+    let (_, stdout, _) = run_sk("xab\\nab\\naxxb\\nzz", "-f ab --tac");
+    assert_eq!(stdout, "axxb\nab\nxab\n");
+}
+
+#[test]
+fn filter_mode_prints_the_matches_while_the_input_is_read() {
+    use std::io::{BufRead, BufReader};
+    let mut child = Command::new(sk_bin())
+        .args(["-f", "a"])
+        .env_remove("SKIM_DEFAULT_OPTIONS")
+        .env_remove("SKIM_DEFAULT_COMMAND")
+        .env_remove("SKIM_OPTIONS_FILE")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to spawn sk");
+    let mut stdin = child.stdin.take().expect("stdin piped");
+    stdin.write_all(b"a1\nb\n").expect("write stdin");
+    stdin.flush().expect("flush stdin");
+    let mut stdout = BufReader::new(child.stdout.take().expect("stdout piped"));
+    let mut line = String::new();
+    // the input is still open
+    stdout.read_line(&mut line).expect("read stdout");
+    assert_eq!(line, "a1\n");
+    stdin.write_all(b"a2\n").expect("write stdin");
+    drop(stdin);
+    line.clear();
+    stdout.read_line(&mut line).expect("read stdout");
+    assert_eq!(line, "a2\n");
+    assert_eq!(child.wait().expect("wait sk").code(), Some(0));
+}
+
 #[test]
 fn select_1_with_output_format() {
     // --output-format renders the selected item through the printf branch.
@@ -136,6 +242,26 @@ fn select_1_with_output_format() {
     assert!(stdout.contains('3'));
 }
 
+#[test]
+fn filter_or_ui_prints_single_match() {
+    let (code, stdout, _) = run_sk("apple\\nbanana\\ncherry", "--filter-or-ui nan");
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout.trim(), "banana");
+}
+
+#[test]
+fn filter_or_ui_conflicts_with_filter() {
+    let (code, _, stderr) = run_sk("apple", "--filter-or-ui a --filter a");
+    assert_eq!(code, Some(2));
+    assert!(stderr.contains("cannot be used with"));
+}
+
+#[test]
+fn no_input_does_not_read_stdin() {
+    let (_, stdout, _) = run_sk("apple\\nbanana", "--no-input -f ''");
+    assert_eq!(stdout, "");
+}
+
 #[test]
 fn filter_mode_output_format_current_item_is_empty() {
     // `{}` expands to the *current* (highlighted) item, just like in previews.
@@ -180,6 +306,22 @@ fn select_1_writes_history_file() {
         .unwrap();
     assert!(contents.contains('3'));
     let _ = std::fs::remove_file(&hist);
+    let _ = std::fs::remove_file(format!("{hist_path}.lock"));
+}
+
+#[test]
+fn history_widget_prints_the_chosen_command() {
+    // Newest first, without duplicates, and the multi-line entry stays whole.
+    let hist = std::env::temp_dir().join(format!("sk_zsh_hist_{}", std::process::id()));
+    std::fs::write(&hist, ": 1:0;echo a\\\nb\n: 2:0;ls\n: 3:0;echo a\\\nb\n").unwrap();
+    let (code, stdout, _) = run_sk_argv(
+        "",
+        &["--history-widget", "zsh", "-f", ""],
+        &[("HISTFILE", hist.to_str().unwrap())],
+    );
+    let _ = std::fs::remove_file(&hist);
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout, "echo a\nb\nls\n");
 }
 
 #[test]
@@ -192,7 +334,7 @@ fn select_1_print_current() {
 
 #[test]
 fn log_file_initializes_logger() {
-    // --log-file routes env_logger to a file (covers init_logger's Pipe target
+    // --log-file routes the tracing subscriber to a file (covers init_logger's file writer
     // and builder). SKIM_LOG=trace makes the run actually emit records.
     let log = std::env::temp_dir().join(format!("sk_log_{}", std::process::id()));
     let log_path = log.to_str().unwrap();
@@ -202,11 +344,28 @@ fn log_file_initializes_logger() {
         &[("SKIM_LOG", "trace")],
     );
     assert_eq!(code, Some(0));
-    // The log file was created by the file target.
-    assert!(log.exists());
+    // The log file was created by the file target, with the timings of the closed spans.
+    let content = std::fs::read_to_string(&log).unwrap();
+    assert!(content.contains("reader{items=3}"), "{content}");
+    assert!(content.contains("close time.busy="), "{content}");
     let _ = std::fs::remove_file(&log);
 }
 
+#[test]
+fn bench_match_times_every_algorithm() {
+    let corpus = std::env::temp_dir().join(format!("sk_bench_{}", std::process::id()));
+    std::fs::write(&corpus, "src/main.rs\nREADME.md\nsrc/lib.rs\n").unwrap();
+    let (code, stdout, _) = run_sk_argv("", &["--bench-match", corpus.to_str().unwrap(), "srs"], &[]);
+    let _ = std::fs::remove_file(&corpus);
+    assert_eq!(code, Some(0));
+    let mut lines = stdout.lines();
+    assert_eq!(lines.next(), Some("3 lines, pattern \"srs\", best of 5 runs"));
+    for (line, algo) in lines.zip(["arinae", "clangd", "fzy"]) {
+        assert!(line.starts_with(algo), "{line}");
+        assert!(line.contains(" 2 matches "), "{line}");
+    }
+}
+
 #[test]
 fn shell_completion_bash() {
     // --shell bash generates a completion script and exits 0 without reading stdin.
@@ -257,6 +416,13 @@ fn help_flag_exits_zero() {
     assert!(!stdout.is_empty());
 }
 
+#[test]
+fn preview_pane_rejects_unknown_multiplexers() {
+    let (code, _stdout, stderr) = run_sk("", "--preview-pane zellij:right");
+    assert_ne!(code, Some(0));
+    assert!(stderr.contains("tmux:<left|right|top|bottom>"));
+}
+
 #[test]
 fn invalid_flag_exits_with_error() {
     // An unknown flag makes clap print usage and exit non-zero (main()'s
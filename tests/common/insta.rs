@@ -259,7 +259,7 @@ impl TestHarness {
             self.skim.app().cursor_pos.0,
             self.skim.app().cursor_pos.1
         );
-        insta::assert_snapshot!(buf + &cursor_pos);
+        insta::assert_snapshot!(buf + cursor_pos.as_str());
         Ok(())
     }
 
@@ -582,7 +582,7 @@ macro_rules! snap {
             snapshot_suffix => format!("{:03}", $count),
             omit_expression => true,
         }, {
-            insta::assert_snapshot!(__buf + &__cursor_pos);
+            insta::assert_snapshot!(__buf + __cursor_pos.as_str());
         });
     }};
     // With description only (simple/single-snapshot variants) — no suffix, so
@@ -596,7 +596,7 @@ macro_rules! snap {
             $harness.skim.app().cursor_pos.0 + 1
         );
         insta::with_settings!({ description => $desc, omit_expression => true }, {
-            insta::assert_snapshot!(__buf + &__cursor_pos);
+            insta::assert_snapshot!(__buf + __cursor_pos.as_str());
         });
     }};
     // Backward-compat form — no description, no suffix.
@@ -12,8 +12,9 @@ use std::io::{Read, Result, Write};
 use std::os::unix::fs::PermissionsExt;
 use std::path::Path;
 
-use common::zellij::Keys::*;
-use common::zellij::{ZellijController, wait};
+use common::SK;
+use skim_test::Keys::*;
+use skim_test::{ZellijController, wait};
 
 /// Read the whole file at `path` into a `String`.
 fn read_file(path: &Path) -> Result<String> {
@@ -39,7 +40,7 @@ fn read_file(path: &Path) -> Result<String> {
 /// exercised for both the fullscreen and inline layouts, since the post-execute
 /// repaint path differs from a normal render.
 fn run_interactive_execute(name: &str, extra_opts: &[&str]) -> Result<()> {
-    let mut tmux = ZellijController::new_named(name)?;
+    let mut tmux = ZellijController::new_named(name)?.with_binary(SK);
 
     let dir = tmux.tempdir.path().to_path_buf();
     let script = dir.join("interactive.sh");
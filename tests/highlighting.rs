@@ -20,3 +20,24 @@ insta_test!(highlight_split_match, ["apple", "banana", "grape"], &["--color=matc
     @snap;
     @snap_color;
 });
+
+// `--match-ranges-field` highlights the ranges supplied by the source instead of
+// the query's own matches: the query `o` still filters, but `bar` is highlighted.
+insta_test!(
+    highlight_external_ranges,
+    ["4-7\tfoo bar", "0-3\tboo far", "1-2\txyz"],
+    &[
+        "--delimiter",
+        "\t",
+        "--with-nth",
+        "2..",
+        "--match-ranges-field",
+        "1",
+        "--color=matched:9,current_match:1"
+    ],
+    {
+        @type "o";
+        @snap;
+        @snap_color;
+    }
+);
@@ -1,12 +1,10 @@
 #![allow(missing_docs, clippy::pedantic)]
 // Pure Zellij-harness e2e tests: they drive `sk` entirely through the terminal
 // and depend on nothing OS-specific beyond the harness itself, which is
-// cross-platform (see tests/common/zellij.rs). So these run on Linux, macOS and
+// cross-platform (see the `skim-test` crate). So these run on Linux, macOS and
 // Windows.
-#[allow(dead_code)]
-#[macro_use]
-mod common;
-use common::zellij::Keys::*;
+use skim_test::Keys::*;
+use skim_test::sk_test;
 
 sk_test!(sk_version_long, "", &["--version"], {
   @output[0] starts_with("sk ");
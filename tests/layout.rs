@@ -60,3 +60,7 @@ insta_test!(layout_reverse_list_border, ["header line 1", "header line 2", "a",
 insta_test!(layout_reverse_list_border_no_collapse, ["header line 1", "header line 2", "a", "b", "c", "ab", "ac"], &args(&["--layout", "reverse-list", "--border", "--border-no-collapse"]), {
     @snap;
 });
+
+insta_test!(layout_margin, ["header line 1", "header line 2", "a", "b", "c", "ab", "ac"], &args(&["--margin", "1,10%", "--border"]), {
+    @snap;
+});
@@ -6,18 +6,17 @@
 // unix domain socket here. (`#![cfg(unix)]` already covers both Linux and macOS.)
 #![cfg(all(unix, feature = "listen"))]
 #[allow(dead_code)]
-#[macro_use]
 mod common;
 
-use common::zellij::Keys::*;
 use rand::RngExt as _;
 use rand::distr::Alphabetic;
+use skim_test::Keys::*;
 use std::io::{Result, Write as _};
 use std::process::{Child, Command, Stdio};
 
-use common::zellij::ZellijController;
+use skim_test::{SKIM_ENV_REMOVES, ZellijController, sk_test};
 
-use crate::common::{SK, SKIM_ENV_REMOVES};
+use crate::common::SK;
 
 fn connect(name: &str) -> Result<Child> {
     let mut cmd = Command::new(SK);
@@ -34,7 +33,7 @@ fn send(child: &mut Child, msg: &str) -> Result<()> {
 }
 
 fn setup(name: &str, extra_args: &[&str]) -> Result<(ZellijController, Child)> {
-    let mut tmux = ZellijController::new_named(name)?;
+    let mut tmux = ZellijController::new_named(name)?.with_binary(SK);
     let socket_name = format!(
         "sk-test-{name}{}",
         rand::rng()
@@ -19,7 +19,7 @@ insta_test!(insta_normalize_unaccented_item_accented_query, ["café", "cafe", "t
 });
 
 // Test without normalize: accented item does NOT match unaccented query
-insta_test!(insta_no_normalize_accented_item, ["café", "cafe", "tea"], &[], {
+insta_test!(insta_no_normalize_accented_item, ["café", "cafe", "tea"], &["--no-normalize"], {
     @snap;
     @type "cafe";
     @snap;
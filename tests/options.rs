@@ -255,6 +255,45 @@ insta_test!(opt_hscroll_end, [&format!("{}b", ["a"; 1000].join(""))], &["-q", "b
     @snap;
 });
 
+insta_test!(opt_keep_right, [&format!("/{}/src/main.rs", ["dir"; 100].join("/"))], &["--keep-right", "-q", "src"], {
+    @snap;
+});
+
+insta_test!(
+    opt_group_by_field,
+    ["src:lib.rs", "doc:guide.md", "src:main.rs"],
+    &["--delimiter", ":", "--with-nth", "2..", "--group-by-field", "1", "--bind", "ctrl-g:toggle-group"],
+    {
+        @snap;
+        @ctrl 'g';
+        @snap;
+    }
+);
+
+insta_test!(
+    opt_tree,
+    ["src/tui/app.rs", "README.md", "src/lib.rs"],
+    &["--tree", "--bind", "ctrl-g:toggle-group"],
+    {
+        @snap;
+        @ctrl 'g';
+        @snap;
+    }
+);
+
+insta_test!(
+    opt_query2,
+    ["src/app.rs", "src/app.md", "doc/guide.md", "src/lib.rs"],
+    &["-q", "src", "--query2", "rs", "--bind", "ctrl-g:toggle-query2"],
+    {
+        @snap;
+        @ctrl 'g';
+        @key Backspace;
+        @char 'm';
+        @snap;
+    }
+);
+
 insta_test!(opt_no_hscroll, [&format!("{}b", ["a"; 1000].join(""))], &["-q", "b", "--no-hscroll"], {
     @snap;
 });
@@ -584,6 +623,12 @@ insta_test!(opt_border_quadrant_outside, ["a", "b", "c", "ac"], &["-q", "a", "--
     @snap;
 });
 
+insta_test!(opt_border_labels, ["a", "b", "c", "ac"], &["-q", "a", "--border", "--border-label", " all ", "--border-label-pos", "right:bottom", "--list-label", " list ", "--input-label", " query ", "--input-label-pos", "left", "--bind", "ctrl-a:change-list-label( {} )"], {
+    @snap;
+    @ctrl 'a';
+    @snap;
+});
+
 #[cfg(unix)]
 #[test]
 fn opt_select_1() -> std::io::Result<()> {
@@ -878,3 +923,8 @@ fn opt_multiple_flags_parse() {
         let _ = common::insta::parse_options(&refs);
     }
 }
+
+// `--no-input` reads nothing, so the item count stays hidden.
+insta_test!(opt_no_input, ["line1", "line2"], &["--no-input"], {
+    @snap;
+});
@@ -8,8 +8,9 @@
 #[allow(dead_code)]
 mod common;
 
-use common::zellij::Keys::*;
-use common::zellij::ZellijController;
+use common::SK;
+use skim_test::Keys::*;
+use skim_test::ZellijController;
 use std::fs::{File, Permissions};
 use std::io::{Read, Result, Write};
 use std::os::unix::fs::PermissionsExt;
@@ -59,7 +60,7 @@ fn tmux_via_skim_default_options() -> Result<()> {
     let tmux = ZellijController::new()?;
     let outfile = setup_tmux_mock(&tmux)?;
     // Run sk with SKIM_DEFAULT_OPTIONS=--tmux set inline so the popup path is exercised.
-    let cmd = format!("SKIM_DEFAULT_OPTIONS='--tmux' {}", crate::common::SK);
+    let cmd = format!("SKIM_DEFAULT_OPTIONS='--tmux' {}", SK);
     tmux.send_keys(&[Str(&cmd), Enter])?;
     tmux.until(|_| Path::new(&outfile).exists())?;
     let cmd = get_tmux_cmd(&outfile)?;
@@ -73,7 +74,7 @@ fn tmux_via_skim_default_options() -> Result<()> {
 
 #[test]
 fn tmux_vanilla() -> Result<()> {
-    let mut tmux = ZellijController::new()?;
+    let mut tmux = ZellijController::new()?.with_binary(SK);
     let outfile = setup_tmux_mock(&tmux)?;
     tmux.start_sk(None, &["--tmux"])?;
     tmux.until(|_| Path::new(&outfile).exists())?;
@@ -91,7 +92,7 @@ fn tmux_vanilla() -> Result<()> {
 
 #[test]
 fn tmux_output_format() -> Result<()> {
-    let mut tmux = ZellijController::new()?;
+    let mut tmux = ZellijController::new()?.with_binary(SK);
     let outfile = setup_tmux_mock(&tmux)?;
     tmux.start_sk(
         None,
@@ -119,7 +120,7 @@ fn tmux_output_format() -> Result<()> {
 
 #[test]
 fn tmux_stdin() -> Result<()> {
-    let mut tmux = ZellijController::new()?;
+    let mut tmux = ZellijController::new()?.with_binary(SK);
     let outfile = setup_tmux_mock(&tmux)?;
     tmux.start_sk(Some("ls"), &["--tmux"])?;
     tmux.until(|_| Path::new(&outfile).exists())?;
@@ -132,7 +133,7 @@ fn tmux_stdin() -> Result<()> {
 
 #[test]
 fn tmux_quote() -> Result<()> {
-    let mut tmux = ZellijController::new()?;
+    let mut tmux = ZellijController::new()?.with_binary(SK);
     let outfile = setup_tmux_mock(&tmux)?;
     tmux.send_keys(&[Str("export SHELL=/bin/sh"), Enter])?;
     tmux.send_keys(&[Str("export SKIM_ESCAPED_VAR=';;'"), Enter])?;
@@ -51,6 +51,10 @@ insta_test!(preview_wrap, ["x"], &["--preview", "echo a      bbbbbbbb", "--previ
     @snap;
 });
 
+insta_test!(preview_wrap_sign, ["x"], &["--preview", "echo '  indented bbbbbbbbbbbb'", "--preview-window", "left:12:wrap", "--wrap-sign", "> "], {
+    @snap;
+});
+
 // Test that preview updates when navigating between items
 insta_test!(preview_navigation, ["a", "b", "c"], &["--preview", "echo {}"], {
     @snap;